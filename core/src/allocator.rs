@@ -6,6 +6,9 @@ use std::{convert::TryInto, ptr::null_mut};
 mod rust;
 pub use rust::RustAllocator;
 
+mod tracking;
+pub use tracking::{AllocatorUsage, TrackingAllocator};
+
 /// Raw memory pointer
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "allocator")))]
 pub type RawMemPtr = *mut u8;
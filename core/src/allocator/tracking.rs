@@ -0,0 +1,174 @@
+use std::{
+    ptr::null_mut,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use super::{Allocator, RawMemPtr};
+
+/// Memory usage statistics collected by a [`TrackingAllocator`].
+///
+/// The statistics can be shared and read from any thread while the runtime is in use.
+#[derive(Debug, Default)]
+pub struct AllocatorUsage {
+    allocated: AtomicUsize,
+    peak: AtomicUsize,
+    allocations: AtomicUsize,
+}
+
+impl AllocatorUsage {
+    /// Returns the number of bytes currently allocated.
+    pub fn allocated(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest number of bytes allocated at the same time.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of live allocations.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    fn add(&self, size: usize) {
+        let allocated = self.allocated.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    fn sub(&self, size: usize) {
+        self.allocated.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+/// An allocator wrapper which tracks the memory usage of a runtime.
+///
+/// Because the runtime owns the allocator, the usage is read through a shared
+/// [`AllocatorUsage`] handle.
+/// ```
+/// # use rquickjs::{Runtime, Context, allocator::{RustAllocator, TrackingAllocator}};
+/// let allocator = TrackingAllocator::new(RustAllocator);
+/// let usage = allocator.usage();
+/// let rt = Runtime::new_with_alloc(allocator).unwrap();
+/// assert!(usage.allocated() > 0);
+/// ```
+///
+/// Unlike [`Runtime::set_memory_limit`](crate::Runtime::set_memory_limit), which has no effect
+/// with a custom allocator, [`TrackingAllocator::with_limit`] can be used to limit the amount
+/// of memory a runtime can use.
+pub struct TrackingAllocator<A> {
+    inner: A,
+    limit: Option<usize>,
+    usage: Arc<AllocatorUsage>,
+}
+
+impl<A: Allocator> TrackingAllocator<A> {
+    /// Wrap an allocator.
+    pub fn new(inner: A) -> Self {
+        TrackingAllocator {
+            inner,
+            limit: None,
+            usage: Arc::new(AllocatorUsage::default()),
+        }
+    }
+
+    /// Wrap an allocator and fail allocations which would exceed the limit in bytes.
+    pub fn with_limit(inner: A, limit: usize) -> Self {
+        TrackingAllocator {
+            limit: Some(limit),
+            ..Self::new(inner)
+        }
+    }
+
+    /// Returns the handle to the usage statistics.
+    pub fn usage(&self) -> Arc<AllocatorUsage> {
+        self.usage.clone()
+    }
+
+    fn exceeds_limit(&self, additional: usize) -> bool {
+        self.limit
+            .map(|limit| self.usage.allocated().saturating_add(additional) > limit)
+            .unwrap_or(false)
+    }
+}
+
+impl<A: Allocator> Allocator for TrackingAllocator<A> {
+    fn alloc(&mut self, size: usize) -> RawMemPtr {
+        if self.exceeds_limit(size) {
+            return null_mut();
+        }
+        let ptr = self.inner.alloc(size);
+        if !ptr.is_null() {
+            self.usage.add(A::usable_size(ptr));
+            self.usage.allocations.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    fn dealloc(&mut self, ptr: RawMemPtr) {
+        self.usage.sub(A::usable_size(ptr));
+        self.usage.allocations.fetch_sub(1, Ordering::Relaxed);
+        self.inner.dealloc(ptr)
+    }
+
+    fn realloc(&mut self, ptr: RawMemPtr, new_size: usize) -> RawMemPtr {
+        let old_size = A::usable_size(ptr);
+        if new_size > old_size && self.exceeds_limit(new_size - old_size) {
+            return null_mut();
+        }
+        let new_ptr = self.inner.realloc(ptr, new_size);
+        if !new_ptr.is_null() {
+            self.usage.sub(old_size);
+            self.usage.add(A::usable_size(new_ptr));
+        }
+        new_ptr
+    }
+
+    fn usable_size(ptr: RawMemPtr) -> usize {
+        A::usable_size(ptr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        allocator::{RustAllocator, TrackingAllocator},
+        Context, Error, Runtime,
+    };
+
+    #[test]
+    fn tracks_usage() {
+        let allocator = TrackingAllocator::new(RustAllocator);
+        let usage = allocator.usage();
+        let rt = Runtime::new_with_alloc(allocator).unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let before = usage.allocated();
+        ctx.with(|ctx| {
+            let _: () = ctx
+                .eval("globalThis.data = new Array(10000).fill(0).map((_, i) => ({ i }))")
+                .unwrap();
+        });
+        assert!(usage.allocated() > before);
+        assert!(usage.peak() >= usage.allocated());
+        drop(ctx);
+        drop(rt);
+        assert_eq!(usage.allocated(), 0);
+        assert_eq!(usage.allocations(), 0);
+    }
+
+    #[test]
+    fn limit() {
+        let allocator = TrackingAllocator::with_limit(RustAllocator, 4 * 1024 * 1024);
+        let usage = allocator.usage();
+        let rt = Runtime::new_with_alloc(allocator).unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let res = ctx.eval::<(), _>("globalThis.data = new Array(10000000).fill(0).join()");
+            assert!(matches!(res, Err(Error::Exception)));
+        });
+        assert!(usage.peak() <= 4 * 1024 * 1024);
+    }
+}
@@ -19,10 +19,19 @@ mod cell;
 pub use cell::{
     Borrow, BorrowMut, JsCell, Mutability, OwnedBorrow, OwnedBorrowMut, Readable, Writable,
 };
+mod exotic;
 mod ffi;
 mod trace;
+pub use exotic::{ExoticMethods, JsExotic};
+mod operators;
+mod registry;
+mod weak;
+pub use operators::{Operator, Operators};
+pub(crate) use registry::RegisteredClass;
+pub use registry::{ClassInfo, ClassRegistry, DynamicClass};
 use rquickjs_sys::JS_VALUE_GET_TAG;
 pub use trace::{Trace, Tracer};
+pub use weak::{FinalizationRegistry, FinalizationToken, WeakRef};
 #[doc(hidden)]
 pub mod impl_;
 
@@ -36,6 +45,15 @@ pub trait JsClass<'js>: Trace<'js> {
     /// This should either be [`Readable`] or [`Writable`].
     type Mutable: Mutability;
 
+    /// Whether scripts can add properties to instances of the class.
+    ///
+    /// Instances of a class which is not extensible are created with
+    /// [`Object.preventExtensions`], libraries which tag objects with metadata then fail to do so
+    /// and throw a `TypeError` in strict mode.
+    ///
+    /// [`Object.preventExtensions`]: https://developer.mozilla.org/en-US/docs/Web/JavaScript/Reference/Global_Objects/Object/preventExtensions
+    const EXTENSIBLE: bool = true;
+
     /// A unique id for the class.
     fn class_id() -> &'static ClassId;
 
@@ -52,6 +70,21 @@ pub trait JsClass<'js>: Trace<'js> {
     fn function() -> Option<StaticJsFn> {
         None
     }
+
+    /// The exotic methods of the class.
+    ///
+    /// Returning a table from this method, created from an implementation of [`JsExotic`],
+    /// allows the class to resolve its properties dynamically.
+    fn exotic() -> Option<ExoticMethods> {
+        None
+    }
+
+    /// Called when an instance of the class is freed by the garbage collector, right before the
+    /// Rust value is dropped.
+    ///
+    /// The object can no longer be reached from JavaScript at this point so this function can't
+    /// be used to access any JavaScript values.
+    fn finalize(&mut self) {}
 }
 
 /// A object which is instance of a Rust class.
@@ -109,10 +142,9 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         };
         let ptr: *mut JsCell<'js, C> = Box::into_raw(Box::new(JsCell::new(value)));
         unsafe { qjs::JS_SetOpaque(val, ptr.cast()) };
-        Ok(Self(
-            unsafe { Object::from_js_value(ctx, val) },
-            PhantomData,
-        ))
+        let res = Self(unsafe { Object::from_js_value(ctx, val) }, PhantomData);
+        res.prevent_extensions();
+        Ok(res)
     }
 
     /// Create a class from a Rust object with a given prototype.
@@ -129,10 +161,20 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         };
         let ptr: *mut JsCell<'js, C> = Box::into_raw(Box::new(JsCell::new(value)));
         unsafe { qjs::JS_SetOpaque(val, ptr.cast()) };
-        Ok(Self(
+        let res = Self(
             unsafe { Object::from_js_value(proto.ctx.clone(), val) },
             PhantomData,
-        ))
+        );
+        res.prevent_extensions();
+        Ok(res)
+    }
+
+    /// Make a new instance non extensible if the class is.
+    fn prevent_extensions(&self) {
+        if !C::EXTENSIBLE {
+            // Can't fail for objects which are not proxies.
+            unsafe { qjs::JS_PreventExtensions(self.0.ctx.as_ptr(), self.0.as_js_value()) };
+        }
     }
 
     /// Returns the prototype for the class.
@@ -201,11 +243,18 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
                 finalizer,
                 gc_mark: Some(ffi::trace::<C>),
                 call,
-                exotic: ptr::null_mut(),
+                exotic: C::exotic()
+                    .map(ExoticMethods::as_ptr)
+                    .unwrap_or(ptr::null_mut()),
             };
             if 0 != unsafe { qjs::JS_NewClass(rt, class_id, &class_def) } {
                 return Err(Error::Unknown);
             }
+            unsafe { &mut (*ctx.get_opaque()).classes }.push(RegisteredClass {
+                id: class_id,
+                name: C::NAME.into(),
+                dynamic: false,
+            });
         }
 
         let proto_val = unsafe { qjs::JS_GetClassProto(ctx.as_ptr(), class_id) };
@@ -275,6 +324,29 @@ impl<'js, C: JsClass<'js>> Class<'js, C> {
         self.get_cell().try_borrow_mut().map_err(Error::ClassBorrow)
     }
 
+    /// Borrow the Rust class type for as long as the returned guard lives.
+    ///
+    /// Unlike [`Class::try_borrow`] the guard owns the class, so it can be held across an
+    /// `.await` or returned from a function.
+    ///
+    /// This returns an error when the class is already borrowed mutably.
+    #[inline]
+    pub fn try_borrow_owned(self) -> Result<OwnedBorrow<'js, C>> {
+        OwnedBorrow::try_from_class(self).map_err(Error::ClassBorrow)
+    }
+
+    /// Borrow the Rust class type mutably for as long as the returned guard lives.
+    ///
+    /// Unlike [`Class::try_borrow_mut`] the guard owns the class, so it can be held across an
+    /// `.await` or returned from a function.
+    ///
+    /// This returns an error when the class is already borrowed mutably, immutably or the class
+    /// can't be borrowed mutably.
+    #[inline]
+    pub fn try_borrow_mut_owned(self) -> Result<OwnedBorrowMut<'js, C>> {
+        OwnedBorrowMut::try_from_class(self).map_err(Error::ClassBorrow)
+    }
+
     /// returns a pointer to the class object.
     #[inline]
     pub(crate) fn get_class_ptr(&self) -> NonNull<JsCell<'js, C>> {
@@ -471,6 +543,12 @@ mod test {
             cls.borrow_mut().inner.push(cls_clone);
             ctx.globals().set("t", cls).unwrap();
         });
+        // The cycle is only collected when the runtime is freed, which still needs the
+        // bookkeeping data of the runtime for finalizing the class.
+        drop_test.store(false, Ordering::SeqCst);
+        std::mem::drop(ctx);
+        std::mem::drop(rt);
+        assert!(drop_test.load(Ordering::SeqCst));
     }
 
     #[test]
@@ -595,4 +673,71 @@ mod test {
             Class::<X>::register(&ctx).unwrap();
         })
     }
+
+    /// Test circular references through shared persistent values.
+    #[test]
+    fn trace_persistent() {
+        use std::sync::atomic::AtomicUsize;
+
+        use crate::Persistent;
+
+        pub struct Holder {
+            obj: Persistent<Object<'static>>,
+            dropped: Arc<AtomicUsize>,
+        }
+
+        impl Drop for Holder {
+            fn drop(&mut self) {
+                self.dropped.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        impl<'js> Trace<'js> for Holder {
+            fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+                self.obj.trace(tracer)
+            }
+        }
+
+        impl<'js> JsClass<'js> for Holder {
+            const NAME: &'static str = "Holder";
+
+            type Mutable = Writable;
+
+            fn class_id() -> &'static crate::class::ClassId {
+                static ID: ClassId = ClassId::new();
+                &ID
+            }
+
+            fn prototype(ctx: &crate::Ctx<'js>) -> crate::Result<Option<crate::Object<'js>>> {
+                Ok(Some(Object::new(ctx.clone())?))
+            }
+
+            fn constructor(
+                _ctx: &crate::Ctx<'js>,
+            ) -> crate::Result<Option<crate::value::Constructor<'js>>> {
+                Ok(None)
+            }
+        }
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let obj = ctx.with(|ctx| Persistent::save(&ctx, Object::new(ctx.clone()).unwrap()));
+        // Cloned without holding the lock, so with the parallel feature both share the value.
+        let handles = [obj.clone(), obj];
+        ctx.with(|ctx| {
+            let obj = handles[0].clone().restore(&ctx).unwrap();
+            for (idx, handle) in handles.into_iter().enumerate() {
+                let holder = Holder {
+                    obj: handle,
+                    dropped: dropped.clone(),
+                };
+                let holder = Class::instance(ctx.clone(), holder).unwrap();
+                obj.set(idx as u32, holder).unwrap();
+            }
+        });
+        rt.run_gc();
+        assert_eq!(dropped.load(Ordering::SeqCst), 2);
+    }
 }
@@ -5,9 +5,13 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use crate::{result::BorrowError, Ctx, Error, FromJs, IntoJs, Value};
+use crate::{
+    result::BorrowError,
+    safe_ref::{Mut, Ref},
+    Ctx, Error, FromJs, IntoJs, Value,
+};
 
-use super::{Class, JsClass};
+use super::{weak::WeakState, Class, JsClass};
 
 /// A trait to allow classes to choose there borrowing implementation.
 ///
@@ -66,6 +70,10 @@ pub unsafe trait Mutability {
     /// # Safety
     /// [`borrow_mut`] must first be called on the cell and return without error before calling deref.
     unsafe fn deref_mut<'a, T>(cell: &'a Self::Cell<T>) -> &'a mut T;
+
+    #[doc(hidden)]
+    /// Returns a mutable reference to the value of a uniquely owned cell.
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> &mut T;
 }
 
 /// A marker type used for marking the mutability of a class.
@@ -98,6 +106,10 @@ unsafe impl Mutability for Readable {
     unsafe fn deref_mut<'a, T>(_cell: &'a Self::Cell<T>) -> &'a mut T {
         unreachable!()
     }
+
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> &mut T {
+        cell
+    }
 }
 
 /// A marker type used for marking the mutability of a class.
@@ -198,6 +210,10 @@ unsafe impl Mutability for Writable {
     unsafe fn deref_mut<'a, T>(cell: &'a Self::Cell<T>) -> &'a mut T {
         &mut *cell.value.get()
     }
+
+    fn get_mut<T>(cell: &mut Self::Cell<T>) -> &mut T {
+        cell.value.get_mut()
+    }
 }
 
 /// A cell type for Rust classes passed to JavaScript.
@@ -205,6 +221,7 @@ unsafe impl Mutability for Writable {
 /// Implements [`RefCell`](std::cell::RefCell)-like borrow checking.
 pub struct JsCell<'js, T: JsClass<'js>> {
     pub(crate) cell: <T::Mutable as Mutability>::Cell<T>,
+    pub(crate) weak: Mut<Option<Ref<WeakState>>>,
 }
 
 impl<'js, T: JsClass<'js>> JsCell<'js, T> {
@@ -212,6 +229,7 @@ impl<'js, T: JsClass<'js>> JsCell<'js, T> {
     pub fn new(t: T) -> Self {
         JsCell {
             cell: <T::Mutable as Mutability>::new_cell(t),
+            weak: Mut::new(None),
         }
     }
 
@@ -314,6 +332,11 @@ impl<'js, T: JsClass<'js> + 'js> OwnedBorrow<'js, T> {
         Ok(OwnedBorrow(ManuallyDrop::new(class)))
     }
 
+    /// Returns the class which is borrowed.
+    pub fn as_class(&self) -> &Class<'js, T> {
+        &self.0
+    }
+
     /// Turn the owned borrow back into the class releasing the borrow.
     pub fn into_inner(mut self) -> Class<'js, T> {
         unsafe { <T::Mutable as Mutability>::unborrow(&self.0.get_cell().cell) };
@@ -372,6 +395,11 @@ impl<'js, T: JsClass<'js> + 'js> OwnedBorrowMut<'js, T> {
         Ok(OwnedBorrowMut(ManuallyDrop::new(class)))
     }
 
+    /// Returns the class which is borrowed.
+    pub fn as_class(&self) -> &Class<'js, T> {
+        &self.0
+    }
+
     /// Turn the owned borrow back into the class releasing the borrow.
     pub fn into_inner(mut self) -> Class<'js, T> {
         unsafe { <T::Mutable as Mutability>::unborrow_mut(&self.0.get_cell().cell) };
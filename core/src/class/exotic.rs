@@ -0,0 +1,229 @@
+//! Classes with dynamically resolved properties.
+
+use std::{
+    marker::PhantomData,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{qjs, Atom, Ctx, Result, Value};
+
+use super::{JsCell, JsClass};
+
+/// A trait for classes which resolve some of their properties dynamically.
+///
+/// This allows for creating lazy or virtual objects, for example rows of a database or a
+/// configuration tree, without defining every property up front.
+///
+/// Properties which are defined on the object itself take precedence over dynamic properties and
+/// dynamic properties in turn take precedence over properties from the prototype.
+///
+/// For QuickJS to use these methods the class must return [`ExoticMethods`] from
+/// [`JsClass::exotic`].
+pub trait JsExotic<'js>: JsClass<'js> {
+    /// Returns the value of a dynamic own property, or `None` if the object doesn't have the
+    /// property.
+    fn get_own_property(&self, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<Option<Value<'js>>>;
+
+    /// Returns the keys of all the dynamic own properties of the object.
+    fn own_keys(&self, _ctx: &Ctx<'js>) -> Result<Vec<Atom<'js>>> {
+        Ok(Vec::new())
+    }
+}
+
+/// The table of exotic methods for a class implementing [`JsExotic`].
+#[derive(Clone, Copy, Debug)]
+pub struct ExoticMethods(pub(crate) &'static qjs::JSClassExoticMethods);
+
+struct ExoticTable<C>(PhantomData<C>);
+
+impl<'js, C: JsExotic<'js>> ExoticTable<C> {
+    const METHODS: qjs::JSClassExoticMethods = qjs::JSClassExoticMethods {
+        get_own_property: Some(get_own_property::<C>),
+        get_own_property_names: Some(get_own_property_names::<C>),
+        delete_property: None,
+        define_own_property: None,
+        has_property: None,
+        get_property: None,
+        set_property: None,
+    };
+}
+
+impl ExoticMethods {
+    /// Create the exotic methods table for a class.
+    pub fn new<'js, C: JsExotic<'js>>() -> Self {
+        ExoticMethods(&ExoticTable::<C>::METHODS)
+    }
+
+    pub(crate) fn as_ptr(self) -> *mut qjs::JSClassExoticMethods {
+        self.0 as *const _ as *mut _
+    }
+}
+
+/// Calls a closure with the borrowed class of an exotic object, converting errors and panics
+/// into JavaScript exceptions.
+unsafe fn with_class<'js, C, F>(ctx: *mut qjs::JSContext, obj: qjs::JSValue, f: F) -> qjs::c_int
+where
+    C: JsExotic<'js>,
+    F: FnOnce(&Ctx<'js>, &C) -> Result<qjs::c_int>,
+{
+    let ctx = Ctx::from_ptr(ctx);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let ptr = qjs::JS_GetOpaque(obj, C::class_id().get()).cast::<JsCell<C>>();
+        debug_assert!(!ptr.is_null());
+        let borrow = (*ptr).try_borrow().map_err(crate::Error::ClassBorrow)?;
+        f(&ctx, &borrow)
+    }));
+    match res {
+        Ok(Ok(x)) => x,
+        Ok(Err(e)) => {
+            e.throw(&ctx);
+            -1
+        }
+        Err(e) => {
+            (*ctx.get_opaque()).panic = Some(e);
+            qjs::JS_Throw(ctx.as_ptr(), qjs::JS_MKVAL(qjs::JS_TAG_EXCEPTION, 0));
+            -1
+        }
+    }
+}
+
+unsafe extern "C" fn get_own_property<'js, C: JsExotic<'js>>(
+    ctx: *mut qjs::JSContext,
+    desc: *mut qjs::JSPropertyDescriptor,
+    obj: qjs::JSValue,
+    prop: qjs::JSAtom,
+) -> qjs::c_int {
+    with_class::<C, _>(ctx, obj, |ctx, this| {
+        let atom = Atom::from_atom_val_dup(ctx.clone(), prop);
+        let Some(value) = this.get_own_property(ctx, atom)? else {
+            return Ok(0);
+        };
+        if !desc.is_null() {
+            desc.write(qjs::JSPropertyDescriptor {
+                flags: qjs::JS_PROP_C_W_E as _,
+                value: value.into_js_value(),
+                getter: qjs::JS_UNDEFINED,
+                setter: qjs::JS_UNDEFINED,
+            });
+        }
+        Ok(1)
+    })
+}
+
+unsafe extern "C" fn get_own_property_names<'js, C: JsExotic<'js>>(
+    ctx: *mut qjs::JSContext,
+    ptab: *mut *mut qjs::JSPropertyEnum,
+    plen: *mut u32,
+    obj: qjs::JSValue,
+) -> qjs::c_int {
+    with_class::<C, _>(ctx, obj, |ctx, this| {
+        let keys = this.own_keys(ctx)?;
+        let len = u32::try_from(keys.len()).map_err(|_| crate::Error::Allocation)?;
+        let tab = if keys.is_empty() {
+            ptr::null_mut()
+        } else {
+            let tab = qjs::js_malloc(
+                ctx.as_ptr(),
+                (mem::size_of::<qjs::JSPropertyEnum>() * keys.len()) as _,
+            )
+            .cast::<qjs::JSPropertyEnum>();
+            if tab.is_null() {
+                return Err(crate::Error::Allocation);
+            }
+            for (idx, key) in keys.into_iter().enumerate() {
+                // The ownership of the atom is transferred to the table.
+                tab.add(idx).write(qjs::JSPropertyEnum {
+                    is_enumerable: 1,
+                    atom: key.into_atom_val(),
+                });
+            }
+            tab
+        };
+        ptab.write(tab);
+        plen.write(len);
+        Ok(0)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        class::{ClassId, ExoticMethods, JsClass, JsExotic, Readable, Trace, Tracer},
+        test_with,
+        value::Constructor,
+        Atom, Class, Ctx, IntoJs, Object, Result, Value,
+    };
+
+    pub struct Config(BTreeMap<&'static str, u32>);
+
+    impl<'js> Trace<'js> for Config {
+        fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+    }
+
+    impl<'js> JsClass<'js> for Config {
+        const NAME: &'static str = "Config";
+
+        type Mutable = Readable;
+
+        fn class_id() -> &'static ClassId {
+            static ID: ClassId = ClassId::new();
+            &ID
+        }
+
+        fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+            let proto = Object::new(ctx.clone())?;
+            proto.set("fromProto", true)?;
+            Ok(Some(proto))
+        }
+
+        fn constructor(_ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+            Ok(None)
+        }
+
+        fn exotic() -> Option<ExoticMethods> {
+            Some(ExoticMethods::new::<Self>())
+        }
+    }
+
+    impl<'js> JsExotic<'js> for Config {
+        fn get_own_property(&self, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<Option<Value<'js>>> {
+            self.0
+                .get(atom.to_string()?.as_str())
+                .map(|x| x.into_js(ctx))
+                .transpose()
+        }
+
+        fn own_keys(&self, ctx: &Ctx<'js>) -> Result<Vec<Atom<'js>>> {
+            self.0
+                .keys()
+                .map(|x| Atom::from_str(ctx.clone(), x))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn dynamic_properties() {
+        test_with(|ctx| {
+            let config = Config([("a", 1), ("b", 2)].into_iter().collect());
+            let cls = Class::instance(ctx.clone(), config).unwrap();
+            ctx.globals().set("config", cls).unwrap();
+
+            let res: u32 = ctx.eval("config.a + config.b").unwrap();
+            assert_eq!(res, 3);
+            let res: bool = ctx.eval("config.c === undefined").unwrap();
+            assert!(res);
+            let res: bool = ctx.eval("'a' in config && !('c' in config)").unwrap();
+            assert!(res);
+            let res: bool = ctx.eval("config.fromProto").unwrap();
+            assert!(res);
+            let res: String = ctx.eval("Object.keys(config).join()").unwrap();
+            assert_eq!(res, "a,b");
+            let res: u32 = ctx.eval("config.a = 5; config.a").unwrap();
+            assert_eq!(res, 5);
+        })
+    }
+}
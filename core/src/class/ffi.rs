@@ -2,17 +2,24 @@ use std::mem;
 
 use crate::{class::JsCell, qjs};
 
-use super::{JsClass, Mutability, Tracer};
+use super::{weak::FinalizeGuard, JsClass, Mutability, Tracer};
 
 /// FFI finalizer, destroying the object once it is delete by the Gc.
 pub(crate) unsafe extern "C" fn finalizer<'js, C: JsClass<'js>>(
-    _rt: *mut qjs::JSRuntime,
+    rt: *mut qjs::JSRuntime,
     val: qjs::JSValue,
 ) {
     let ptr = qjs::JS_GetOpaque(val, C::class_id().get()).cast::<JsCell<C>>();
     debug_assert!(!ptr.is_null());
-    let inst = Box::from_raw(ptr);
+    let _guard = FinalizeGuard::new(rt);
+    let mut inst = Box::from_raw(ptr);
+    <C::Mutable as Mutability>::get_mut(&mut inst.cell).finalize();
+    let callbacks = inst.weak.lock().take().map(|weak| weak.kill());
     mem::drop(inst);
+    callbacks
+        .into_iter()
+        .flatten()
+        .for_each(|callback| callback());
 }
 
 /// FFI tracing function.
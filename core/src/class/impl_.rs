@@ -18,6 +18,11 @@ pub trait ConstructorCreator<'js, T>: Sized {
     }
 }
 
+/// Trait used for borrow specialization for finalizing classes without access to the class.
+pub trait FinalizeImplementor<T>: Sized {
+    fn finalize(&self, _this: &mut T) {}
+}
+
 /// A helper type for borrow specialization
 #[derive(Default)]
 pub struct MethodImpl<T>(PhantomData<T>);
@@ -38,6 +43,16 @@ impl<T> ConstructorCreate<T> {
     }
 }
 
+/// A helper type for borrow specialization
+#[derive(Default)]
+pub struct FinalizeImpl<T>(PhantomData<T>);
+
+impl<T> FinalizeImpl<T> {
+    pub fn new() -> Self {
+        FinalizeImpl(PhantomData)
+    }
+}
+
 /// Specialization isn't stabilized yet so in the macro we can't normally have a default
 /// implementation for class prototypes if it doesn't have an associated impl item.
 ///
@@ -62,6 +77,8 @@ impl<T> MethodImplementor<T> for &MethodImpl<T> {}
 
 impl<'js, T> ConstructorCreator<'js, T> for &ConstructorCreate<T> {}
 
+impl<T> FinalizeImplementor<T> for &FinalizeImpl<T> {}
+
 /// A helper struct to implement [`FromJs`](crate::FromJs) for types which implement [`Clone`].
 pub struct CloneWrapper<'a, T>(pub &'a T);
 /// A helper trait to implement [`FromJs`](crate::FromJs) for types which implement [`Clone`].
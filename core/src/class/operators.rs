@@ -0,0 +1,299 @@
+use crate::{
+    atom::PredefinedAtom,
+    function::{Rest, This},
+    Ctx, Error, Function, IntoJs, Object, Result, Value,
+};
+
+/// An operator which can be overloaded with [`Operators`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * b`
+    Mul,
+    /// `a / b`
+    Div,
+    /// `a % b`
+    Mod,
+    /// `a ** b`
+    Pow,
+    /// `a | b`
+    BitOr,
+    /// `a & b`
+    BitAnd,
+    /// `a ^ b`
+    BitXor,
+    /// `a << b`
+    Shl,
+    /// `a >> b`
+    Sar,
+    /// `a >>> b`
+    Shr,
+    /// `a == b`, also used for `!=`
+    Eq,
+    /// `a < b`, also used for `>`, `<=` and `>=`
+    Lt,
+    /// `+a`
+    Pos,
+    /// `-a`
+    Neg,
+    /// `++a` and `a++`
+    Inc,
+    /// `--a` and `a--`
+    Dec,
+    /// `~a`
+    BitNot,
+}
+
+impl Operator {
+    /// The name of the operator as used by the QuickJS `Operators` API.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Mod => "%",
+            Operator::Pow => "**",
+            Operator::BitOr => "|",
+            Operator::BitAnd => "&",
+            Operator::BitXor => "^",
+            Operator::Shl => "<<",
+            Operator::Sar => ">>",
+            Operator::Shr => ">>>",
+            Operator::Eq => "==",
+            Operator::Lt => "<",
+            Operator::Pos => "pos",
+            Operator::Neg => "neg",
+            Operator::Inc => "++",
+            Operator::Dec => "--",
+            Operator::BitNot => "~",
+        }
+    }
+
+    /// Returns true if the operator takes a single operand.
+    pub const fn is_unary(self) -> bool {
+        matches!(
+            self,
+            Operator::Pos | Operator::Neg | Operator::Inc | Operator::Dec | Operator::BitNot
+        )
+    }
+}
+
+/// A builder for an operator set, the QuickJS extension which allows overloading operators.
+///
+/// The operator functions are called with the operands as arguments. Binary operators are used
+/// when both operands are objects with the operator set, other types can be mixed in with
+/// [`Operators::left`] and [`Operators::right`]. Overloading requires the
+/// [`Operators`](crate::context::intrinsic::Operators) intrinsic, which is not included in
+/// [`Context::full`](crate::Context::full).
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, Object, prelude::Func, context::intrinsic, class::{Operator, Operators}};
+/// # let rt = Runtime::new().unwrap();
+/// let ctx = Context::custom::<intrinsic::All>(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let meters: Function = ctx.eval("(function Meters(v) { this.v = v; })").unwrap();
+/// let number: Function = ctx.globals().get("Number").unwrap();
+/// Operators::new(ctx.clone())
+///     .unwrap()
+///     .op(Operator::Add, Func::from(|a: Object, b: Object| {
+///         a.get::<_, f64>("v").unwrap() + b.get::<_, f64>("v").unwrap()
+///     }))
+///     .unwrap()
+///     .right(number, Operator::Mul, Func::from(|a: Object, b: f64| {
+///         a.get::<_, f64>("v").unwrap() * b
+///     }))
+///     .unwrap()
+///     .apply(&meters.get::<_, Object>("prototype").unwrap())
+///     .unwrap();
+/// ctx.globals().set("Meters", meters).unwrap();
+/// let res: f64 = ctx.eval("new Meters(2) + new Meters(3)").unwrap();
+/// assert_eq!(res, 5.0);
+/// let res: f64 = ctx.eval("new Meters(3) * 2").unwrap();
+/// assert_eq!(res, 6.0);
+/// # });
+/// ```
+pub struct Operators<'js> {
+    ctx: Ctx<'js>,
+    ops: Object<'js>,
+    others: Vec<(&'static str, Value<'js>, Object<'js>)>,
+}
+
+impl<'js> Operators<'js> {
+    /// Create a new empty operator set.
+    pub fn new(ctx: Ctx<'js>) -> Result<Self> {
+        let ops = Object::new(ctx.clone())?;
+        Ok(Operators {
+            ctx,
+            ops,
+            others: Vec::new(),
+        })
+    }
+
+    /// Overload an operator for when all operands have this operator set.
+    pub fn op<F: IntoJs<'js>>(self, op: Operator, f: F) -> Result<Self> {
+        self.ops.set(op.as_str(), f)?;
+        Ok(self)
+    }
+
+    /// Overload a binary operator for when the left operand is an instance of another type.
+    ///
+    /// The type is given by its constructor, like `Number` or a class with its own operator set.
+    pub fn left<C, F>(self, constructor: C, op: Operator, f: F) -> Result<Self>
+    where
+        C: IntoJs<'js>,
+        F: IntoJs<'js>,
+    {
+        self.other("left", constructor, op, f)
+    }
+
+    /// Overload a binary operator for when the right operand is an instance of another type.
+    ///
+    /// The type is given by its constructor, like `Number` or a class with its own operator set.
+    pub fn right<C, F>(self, constructor: C, op: Operator, f: F) -> Result<Self>
+    where
+        C: IntoJs<'js>,
+        F: IntoJs<'js>,
+    {
+        self.other("right", constructor, op, f)
+    }
+
+    fn other<C, F>(mut self, side: &'static str, constructor: C, op: Operator, f: F) -> Result<Self>
+    where
+        C: IntoJs<'js>,
+        F: IntoJs<'js>,
+    {
+        if op.is_unary() {
+            return Err(Error::new_from_js_message(
+                "operator",
+                "Operators",
+                format!(
+                    "unary operator `{}` can't have a {} operand",
+                    op.as_str(),
+                    side
+                ),
+            ));
+        }
+        let constructor = constructor.into_js(&self.ctx)?;
+        let existing = self
+            .others
+            .iter()
+            .position(|(s, c, _)| *s == side && *c == constructor);
+        let ops = if let Some(idx) = existing {
+            self.others[idx].2.clone()
+        } else {
+            let ops = Object::new(self.ctx.clone())?;
+            ops.set(side, constructor.clone())?;
+            self.others.push((side, constructor, ops.clone()));
+            ops
+        };
+        ops.set(op.as_str(), f)?;
+        Ok(self)
+    }
+
+    /// Create the operator set object with `Operators.create`.
+    pub fn build(self) -> Result<Object<'js>> {
+        let operators: Object = self.ctx.globals().get("Operators")?;
+        let create: Function = operators.get("create")?;
+        let mut args = vec![self.ops.into_value()];
+        args.extend(self.others.into_iter().map(|(_, _, ops)| ops.into_value()));
+        create.call((This(operators), Rest(args)))
+    }
+
+    /// Create the operator set and install it on a prototype, enabling the overloaded operators
+    /// for all objects which inherit from it.
+    pub fn apply(self, prototype: &Object<'js>) -> Result<()> {
+        let set = self.build()?;
+        prototype.set(PredefinedAtom::SymbolOperatorSet, set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        class::{Operator, Operators},
+        context::intrinsic,
+        prelude::Func,
+        Context, Function, Object, Result, Runtime,
+    };
+
+    fn add<'js>(a: Object<'js>, b: Object<'js>) -> Result<Object<'js>> {
+        let constructor: Function = a.get("constructor")?;
+        let x = a.get::<_, f64>("x")? + b.get::<_, f64>("x")?;
+        let y = a.get::<_, f64>("y")? + b.get::<_, f64>("y")?;
+        constructor.construct((x, y))
+    }
+
+    #[test]
+    fn overload() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::custom::<intrinsic::All>(&rt).unwrap();
+        ctx.with(|ctx| {
+            let vec: Function = ctx
+                .eval("(function Vec(x, y) { this.x = x; this.y = y; })")
+                .unwrap();
+            let number: Function = ctx.globals().get("Number").unwrap();
+            Operators::new(ctx.clone())
+                .unwrap()
+                .op(Operator::Add, Func::from(add))
+                .unwrap()
+                .op(
+                    Operator::Eq,
+                    Func::from(|a: Object<'_>, b: Object<'_>| {
+                        Ok::<_, crate::Error>(
+                            a.get::<_, f64>("x")? == b.get::<_, f64>("x")?
+                                && a.get::<_, f64>("y")? == b.get::<_, f64>("y")?,
+                        )
+                    }),
+                )
+                .unwrap()
+                .op(
+                    Operator::Neg,
+                    Func::from(|a: Object<'_>| a.get::<_, f64>("x")),
+                )
+                .unwrap()
+                .left(
+                    number.clone(),
+                    Operator::Mul,
+                    Func::from(|a: f64, b: Object<'_>| {
+                        Ok::<_, crate::Error>(a * b.get::<_, f64>("x")?)
+                    }),
+                )
+                .unwrap()
+                .right(
+                    number.clone(),
+                    Operator::Mul,
+                    Func::from(|a: Object<'_>, b: f64| {
+                        Ok::<_, crate::Error>(a.get::<_, f64>("y")? * b)
+                    }),
+                )
+                .unwrap()
+                .apply(&vec.get::<_, Object>("prototype").unwrap())
+                .unwrap();
+            ctx.globals().set("Vec", vec).unwrap();
+
+            let res: bool = ctx
+                .eval(
+                    r#"
+                    const a = new Vec(1, 2);
+                    const b = new Vec(3, 4);
+                    const c = a + b;
+                    c.x == 4 && c.y == 6
+                        && c == new Vec(4, 6) && c != a
+                        && -b == 3
+                        && 2 * b == 6 && b * 2 == 8
+                "#,
+                )
+                .unwrap();
+            assert!(res);
+
+            assert!(Operators::new(ctx.clone())
+                .unwrap()
+                .left(number, Operator::Neg, Func::from(|| 0))
+                .is_err());
+        });
+    }
+}
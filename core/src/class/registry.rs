@@ -0,0 +1,437 @@
+//! Introspection of registered classes and classes defined at runtime.
+
+use std::ffi::CString;
+
+use crate::{
+    atom::PredefinedAtom,
+    function::{Rest, This},
+    qjs,
+    value::Constructor,
+    Ctx, Error, Function, IntoAtom, Object, Result, Value,
+};
+
+/// A class registered in the runtime
+#[derive(Debug, Clone)]
+pub(crate) struct RegisteredClass {
+    pub id: qjs::JSClassID,
+    pub name: String,
+    pub dynamic: bool,
+}
+
+/// The classes registered in the runtime of a context.
+///
+/// Returned by [`Ctx::class_registry`], this is a snapshot which doesn't include classes
+/// registered afterwards.
+pub struct ClassRegistry<'js> {
+    ctx: Ctx<'js>,
+    classes: Vec<RegisteredClass>,
+}
+
+impl<'js> ClassRegistry<'js> {
+    pub(crate) fn new(ctx: Ctx<'js>) -> Self {
+        let classes = unsafe { (*ctx.get_opaque()).classes.clone() };
+        ClassRegistry { ctx, classes }
+    }
+
+    /// Returns the number of registered classes.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Returns if no classes are registered.
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Returns the information of the class registered with the given name.
+    pub fn get(&self, name: &str) -> Option<ClassInfo<'js>> {
+        self.iter().find(|class| class.name() == name)
+    }
+
+    /// Returns if a class with the given name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.classes.iter().any(|class| class.name == name)
+    }
+
+    /// Iterate over the registered classes in the order in which they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = ClassInfo<'js>> + '_ {
+        self.classes.iter().map(|class| ClassInfo {
+            ctx: self.ctx.clone(),
+            class: class.clone(),
+        })
+    }
+}
+
+/// The information about a registered class.
+pub struct ClassInfo<'js> {
+    ctx: Ctx<'js>,
+    class: RegisteredClass,
+}
+
+impl<'js> ClassInfo<'js> {
+    /// The name of the class.
+    pub fn name(&self) -> &str {
+        &self.class.name
+    }
+
+    /// The QuickJS class id of the class.
+    pub fn id(&self) -> qjs::JSClassID {
+        self.class.id
+    }
+
+    /// Returns if the class was defined at runtime with [`DynamicClass`].
+    pub fn is_dynamic(&self) -> bool {
+        self.class.dynamic
+    }
+
+    /// Returns the prototype of the class in this context, if the class has one.
+    pub fn prototype(&self) -> Option<Object<'js>> {
+        let proto = unsafe {
+            let proto = qjs::JS_GetClassProto(self.ctx.as_ptr(), self.class.id);
+            Value::from_js_value(self.ctx.clone(), proto)
+        };
+        proto.into_object()
+    }
+
+    /// Returns if the class has a prototype in this context.
+    pub fn has_prototype(&self) -> bool {
+        self.prototype().is_some()
+    }
+}
+
+type DynamicFn<'js> =
+    Box<dyn Fn(Ctx<'js>, Object<'js>, Rest<Value<'js>>) -> Result<Value<'js>> + 'js>;
+type DynamicGetter<'js> = Box<dyn Fn(Ctx<'js>, Object<'js>) -> Result<Value<'js>> + 'js>;
+type DynamicSetter<'js> = Box<dyn Fn(Ctx<'js>, Object<'js>, Value<'js>) -> Result<()> + 'js>;
+type DynamicInit<'js> = Box<dyn Fn(Ctx<'js>, Object<'js>, Rest<Value<'js>>) -> Result<()> + 'js>;
+
+/// A class whose shape is assembled at runtime instead of with the class macros.
+///
+/// The class gets its own class id, so it shows up in the [`ClassRegistry`] and its instances can
+/// be told apart from other objects. The state of instances is kept in JavaScript properties on
+/// the instance or in the captures of the closures.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Value, class::DynamicClass};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     DynamicClass::new("Counter")
+///         .constructor(|_ctx, this, args| this.set("count", args.0.first().cloned()))
+///         .method("increment", |ctx, this, _args| {
+///             let count = this.get::<_, i32>("count")? + 1;
+///             this.set("count", count)?;
+///             Ok(Value::new_int(ctx, count))
+///         })
+///         .getter("double", |ctx, this| {
+///             Ok(Value::new_int(ctx, this.get::<_, i32>("count")? * 2))
+///         })
+///         .define(&ctx.globals())
+///         .unwrap();
+///
+///     let res: i32 = ctx
+///         .eval("const c = new Counter(1); c.increment(); c.double")
+///         .unwrap();
+///     assert_eq!(res, 4);
+///     assert!(ctx.class_registry().get("Counter").unwrap().has_prototype());
+/// });
+/// ```
+pub struct DynamicClass<'js> {
+    name: String,
+    constructor: Option<DynamicInit<'js>>,
+    methods: Vec<(String, DynamicFn<'js>)>,
+    accessors: Vec<(
+        String,
+        Option<DynamicGetter<'js>>,
+        Option<DynamicSetter<'js>>,
+    )>,
+    statics: Vec<(String, Value<'js>)>,
+}
+
+impl<'js> DynamicClass<'js> {
+    /// Start the definition of a class with the given name.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        DynamicClass {
+            name: name.into(),
+            constructor: None,
+            methods: Vec::new(),
+            accessors: Vec::new(),
+            statics: Vec::new(),
+        }
+    }
+
+    /// Set the function which initializes new instances with the constructor arguments.
+    #[must_use]
+    pub fn constructor<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Ctx<'js>, Object<'js>, Rest<Value<'js>>) -> Result<()> + 'js,
+    {
+        self.constructor = Some(Box::new(f));
+        self
+    }
+
+    /// Add a method to the prototype.
+    #[must_use]
+    pub fn method<N, F>(mut self, name: N, f: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(Ctx<'js>, Object<'js>, Rest<Value<'js>>) -> Result<Value<'js>> + 'js,
+    {
+        self.methods.push((name.into(), Box::new(f)));
+        self
+    }
+
+    /// Add a getter to the prototype.
+    #[must_use]
+    pub fn getter<N, F>(self, name: N, f: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(Ctx<'js>, Object<'js>) -> Result<Value<'js>> + 'js,
+    {
+        self.accessor(name, Some(Box::new(f)), None)
+    }
+
+    /// Add a getter and a setter to the prototype.
+    #[must_use]
+    pub fn property<N, G, S>(self, name: N, get: G, set: S) -> Self
+    where
+        N: Into<String>,
+        G: Fn(Ctx<'js>, Object<'js>) -> Result<Value<'js>> + 'js,
+        S: Fn(Ctx<'js>, Object<'js>, Value<'js>) -> Result<()> + 'js,
+    {
+        self.accessor(name, Some(Box::new(get)), Some(Box::new(set)))
+    }
+
+    fn accessor<N: Into<String>>(
+        mut self,
+        name: N,
+        get: Option<DynamicGetter<'js>>,
+        set: Option<DynamicSetter<'js>>,
+    ) -> Self {
+        self.accessors.push((name.into(), get, set));
+        self
+    }
+
+    /// Add a static value to the constructor.
+    #[must_use]
+    pub fn static_value<N: Into<String>>(mut self, name: N, value: Value<'js>) -> Self {
+        self.statics.push((name.into(), value));
+        self
+    }
+
+    /// Register the class and create its constructor.
+    ///
+    /// Defining a class with the same name again in the same runtime reuses its class id and
+    /// replaces the prototype in the context.
+    pub fn build(self, ctx: &Ctx<'js>) -> Result<Constructor<'js>> {
+        let id = register_dynamic(ctx, &self.name)?;
+
+        let proto = Object::new(ctx.clone())?;
+        for (name, f) in self.methods {
+            let func = Function::new(
+                ctx.clone(),
+                move |ctx: Ctx<'js>, this: This<Value<'js>>, args: Rest<Value<'js>>| {
+                    f(ctx, check_instance(id, this.0)?, args)
+                },
+            )?
+            .with_name(&name)?;
+            proto.set(name, func)?;
+        }
+        for (name, get, set) in self.accessors {
+            let get = get
+                .map(|get| {
+                    Function::new(ctx.clone(), move |ctx: Ctx<'js>, this: This<Value<'js>>| {
+                        get(ctx, check_instance(id, this.0)?)
+                    })
+                })
+                .transpose()?;
+            let set = set
+                .map(|set| {
+                    Function::new(
+                        ctx.clone(),
+                        move |ctx: Ctx<'js>, this: This<Value<'js>>, value: Value<'js>| {
+                            set(ctx, check_instance(id, this.0)?, value)
+                        },
+                    )
+                })
+                .transpose()?;
+            let atom = name.into_atom(ctx)?;
+            let res = unsafe {
+                qjs::JS_DefinePropertyGetSet(
+                    ctx.as_ptr(),
+                    proto.as_js_value(),
+                    atom.atom,
+                    get.map(|x| x.into_value().into_js_value())
+                        .unwrap_or(qjs::JS_UNDEFINED),
+                    set.map(|x| x.into_value().into_js_value())
+                        .unwrap_or(qjs::JS_UNDEFINED),
+                    qjs::JS_PROP_CONFIGURABLE as _,
+                )
+            };
+            if res < 0 {
+                return Err(ctx.raise_exception());
+            }
+        }
+        unsafe {
+            qjs::JS_SetClassProto(ctx.as_ptr(), id, proto.clone().into_value().into_js_value())
+        };
+
+        // Capturing the prototype would create a cycle the garbage collector can't see.
+        let init = self.constructor;
+        let func = Function::new(
+            ctx.clone(),
+            move |ctx: Ctx<'js>, new_target: This<Value<'js>>, args: Rest<Value<'js>>| {
+                let proto = match new_target.0.as_function() {
+                    Some(func) => func.get(PredefinedAtom::Prototype)?,
+                    None => unsafe {
+                        let proto = qjs::JS_GetClassProto(ctx.as_ptr(), id);
+                        Value::from_js_value(ctx.clone(), proto)
+                    },
+                };
+                let this = unsafe {
+                    let val = qjs::JS_NewObjectProtoClass(ctx.as_ptr(), proto.as_js_value(), id);
+                    let val = ctx.handle_exception(val)?;
+                    // Marks the object as an instance, see `check_instance`.
+                    qjs::JS_SetOpaque(val, INSTANCE_MARKER.as_ptr() as _);
+                    Object::from_js_value(ctx.clone(), val)
+                };
+                if let Some(init) = &init {
+                    init(ctx, this.clone(), args)?;
+                }
+                Ok::<_, Error>(this)
+            },
+        )?
+        .with_name(&self.name)?
+        .with_constructor(true);
+        unsafe { qjs::JS_SetConstructor(ctx.as_ptr(), func.as_js_value(), proto.as_js_value()) };
+        for (name, value) in self.statics {
+            func.set(name, value)?;
+        }
+        Ok(Constructor(func))
+    }
+
+    /// Register the class and define its constructor on the given object.
+    pub fn define(self, object: &Object<'js>) -> Result<Constructor<'js>> {
+        let name = self.name.clone();
+        let constructor = self.build(object.ctx())?;
+        object.set(name, constructor.clone())?;
+        Ok(constructor)
+    }
+}
+
+static INSTANCE_MARKER: [u8; 1] = [0];
+
+fn register_dynamic(ctx: &Ctx<'_>, name: &str) -> Result<qjs::JSClassID> {
+    let classes = unsafe { &mut (*ctx.get_opaque()).classes };
+    if let Some(class) = classes
+        .iter()
+        .find(|class| class.dynamic && class.name == name)
+    {
+        return Ok(class.id);
+    }
+
+    let class_name = CString::new(name)?;
+    let mut id = 0;
+    unsafe { qjs::JS_NewClassID(&mut id) };
+    let class_def = qjs::JSClassDef {
+        class_name: class_name.as_ptr(),
+        finalizer: None,
+        gc_mark: None,
+        call: None,
+        exotic: std::ptr::null_mut(),
+    };
+    let rt = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
+    if 0 != unsafe { qjs::JS_NewClass(rt, id, &class_def) } {
+        return Err(Error::Unknown);
+    }
+    classes.push(RegisteredClass {
+        id,
+        name: name.into(),
+        dynamic: true,
+    });
+    Ok(id)
+}
+
+fn check_instance<'js>(id: qjs::JSClassID, this: Value<'js>) -> Result<Object<'js>> {
+    let is_instance = !unsafe { qjs::JS_GetOpaque(this.as_js_value(), id) }.is_null();
+    match this.into_object() {
+        Some(object) if is_instance => Ok(object),
+        _ => Err(Error::new_from_js("value", "class instance")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DynamicClass;
+    use crate::{test_with, value::Constructor, Function, Object, Value};
+
+    #[test]
+    fn registry() {
+        test_with(|ctx| {
+            assert!(!ctx.class_registry().contains("Point"));
+            DynamicClass::new("Point")
+                .constructor(|_ctx, this, args| {
+                    this.set("x", args.0.first().cloned())?;
+                    this.set("y", args.0.get(1).cloned())
+                })
+                .property(
+                    "x2",
+                    |ctx, this| Ok(Value::new_int(ctx, this.get::<_, i32>("x")? * 2)),
+                    |_ctx, this, value| this.set("x", value.as_int().unwrap_or(0) / 2),
+                )
+                .static_value("ORIGIN", Value::new_int(ctx.clone(), 0))
+                .define(&ctx.globals())
+                .unwrap();
+
+            let registry = ctx.class_registry();
+            let point = registry.get("Point").unwrap();
+            assert!(point.is_dynamic());
+            assert!(point.has_prototype());
+            assert!(registry.iter().any(|class| !class.is_dynamic()));
+
+            let res: i32 = ctx
+                .eval("const p = new Point(1, 2); p.x2 = 10; p.x + p.y + Point.ORIGIN")
+                .unwrap();
+            assert_eq!(res, 7);
+            assert!(ctx
+                .eval::<bool, _>("p instanceof Point && p.constructor === Point")
+                .unwrap());
+            assert!(ctx
+                .eval::<(), _>(
+                    "Object.getOwnPropertyDescriptor(Point.prototype, 'x2').get.call({})"
+                )
+                .is_err());
+        })
+    }
+
+    #[test]
+    fn redefine() {
+        test_with(|ctx| {
+            let first: Constructor = DynamicClass::new("Shape")
+                .method("sides", |ctx, _this, _args| Ok(Value::new_int(ctx, 3)))
+                .build(&ctx)
+                .unwrap();
+            let second: Constructor = DynamicClass::new("Shape")
+                .method("sides", |ctx, _this, _args| Ok(Value::new_int(ctx, 4)))
+                .build(&ctx)
+                .unwrap();
+            let registry = ctx.class_registry();
+            assert_eq!(
+                registry
+                    .iter()
+                    .filter(|class| class.name() == "Shape")
+                    .count(),
+                1
+            );
+            let sides = |c: &Constructor| {
+                let this: Object = c.construct(()).unwrap();
+                let sides: Function = this.get("sides").unwrap();
+                sides
+                    .call::<_, i32>((crate::function::This(this),))
+                    .unwrap()
+            };
+            assert_eq!(sides(&first), 3);
+            assert_eq!(sides(&second), 4);
+        })
+    }
+}
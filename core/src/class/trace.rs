@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 
-use crate::{markers::Invariant, qjs, Class, Ctx, Value};
+use crate::{markers::Invariant, qjs, Class, Ctx, Persistent, Value};
 
 use super::JsClass;
 
@@ -68,6 +68,31 @@ impl<'js> Trace<'js> for Ctx<'js> {
     }
 }
 
+impl<'js, T> Trace<'js> for Persistent<T>
+where
+    T: Trace<'static>,
+{
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        // A persistent value from a different runtime can't be part of a cycle with this one.
+        if self.rt != tracer.rt {
+            return;
+        }
+        // Clones made without holding the lock share a single reference, so every handle needs
+        // its own reference for marking them.
+        #[cfg(feature = "parallel")]
+        self.reserve_references();
+        // The persistent value is from the runtime being traced so it is fine to trace it with
+        // the lifetime of the tracer.
+        let tracer = Tracer {
+            rt: tracer.rt,
+            mark_func: tracer.mark_func,
+            _inv: Invariant::new(),
+            _marker: PhantomData,
+        };
+        self.value().trace(tracer)
+    }
+}
+
 impl<'js, T> Trace<'js> for Class<'js, T>
 where
     T: JsClass<'js>,
@@ -215,6 +240,7 @@ trace_impls! {
 
 trace_impls! {
     list:
+    Option,
     Vec,
     std::collections::VecDeque,
     std::collections::LinkedList,
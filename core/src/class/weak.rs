@@ -0,0 +1,350 @@
+//! Weak references to Rust class instances.
+
+use std::{
+    collections::HashMap,
+    ffi::c_void,
+    fmt,
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use crate::{
+    markers::{ParallelSend, ParallelSync},
+    persistent::HoldingLock,
+    qjs,
+    runtime::raw::Opaque,
+    safe_ref::{Mut, Ref, Weak},
+    Ctx, Object, Outlive,
+};
+
+use super::{Class, JsClass, Trace, Tracer};
+
+#[cfg(not(feature = "parallel"))]
+type FinalizeCallback = Box<dyn FnOnce() + 'static>;
+#[cfg(feature = "parallel")]
+type FinalizeCallback = Box<dyn FnOnce() + Send + 'static>;
+
+#[cfg(not(feature = "parallel"))]
+type CleanupFn<H> = Box<dyn Fn(H) + 'static>;
+#[cfg(feature = "parallel")]
+type CleanupFn<H> = Box<dyn Fn(H) + Send + Sync + 'static>;
+
+/// Book keeping shared between a class instance and the weak references pointing to it.
+pub(crate) struct WeakState {
+    alive: AtomicBool,
+    callbacks: Mut<Vec<FinalizeCallback>>,
+}
+
+impl WeakState {
+    fn new() -> Self {
+        WeakState {
+            alive: AtomicBool::new(true),
+            callbacks: Mut::new(Vec::new()),
+        }
+    }
+
+    /// Marks the instance as dead and takes the callbacks which should be run once the instance
+    /// is dropped.
+    pub(crate) fn kill(&self) -> Vec<FinalizeCallback> {
+        self.alive.store(false, Ordering::SeqCst);
+        std::mem::take(&mut *self.callbacks.lock())
+    }
+}
+
+/// Returns the weak state of a class instance, creating it if there is none yet.
+fn weak_state<'js, C: JsClass<'js>>(class: &Class<'js, C>) -> Ref<WeakState> {
+    class
+        .get_cell()
+        .weak
+        .lock()
+        .get_or_insert_with(|| Ref::new(WeakState::new()))
+        .clone()
+}
+
+/// A weak reference to a Rust class instance.
+///
+/// Unlike [`Class`] a weak reference doesn't keep the instance alive. Once the garbage collector
+/// has freed the instance [`WeakRef::upgrade`] will return `None`.
+///
+/// The QuickJS version used by this library doesn't implement the JavaScript `WeakRef` so weak
+/// references are only available for objects created from Rust classes.
+pub struct WeakRef<'js, C: JsClass<'js>> {
+    ctx: Ctx<'js>,
+    ptr: *mut c_void,
+    state: Ref<WeakState>,
+    _marker: PhantomData<C>,
+}
+
+impl<'js, C: JsClass<'js>> Clone for WeakRef<'js, C> {
+    fn clone(&self) -> Self {
+        WeakRef {
+            ctx: self.ctx.clone(),
+            ptr: self.ptr,
+            state: self.state.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'js, C: JsClass<'js>> fmt::Debug for WeakRef<'js, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakRef")
+            .field("class", &C::NAME)
+            .field("alive", &self.is_alive())
+            .finish()
+    }
+}
+
+unsafe impl<'js, C> Outlive<'js> for WeakRef<'js, C>
+where
+    C: JsClass<'js> + Outlive<'js>,
+    for<'to> C::Target<'to>: JsClass<'to>,
+{
+    type Target<'to> = WeakRef<'to, C::Target<'to>>;
+}
+
+impl<'js, C: JsClass<'js>> Trace<'js> for WeakRef<'js, C> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        // The instance isn't kept alive by a weak reference but the context is.
+        tracer.mark_ctx(&self.ctx)
+    }
+}
+
+impl<'js, C: JsClass<'js>> WeakRef<'js, C> {
+    /// Create a new weak reference to a class instance.
+    pub fn new(class: &Class<'js, C>) -> Self {
+        WeakRef {
+            ctx: class.ctx().clone(),
+            ptr: unsafe { qjs::JS_VALUE_GET_PTR(class.0.as_js_value()) },
+            state: weak_state(class),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns if the referenced instance has not yet been freed.
+    pub fn is_alive(&self) -> bool {
+        self.state.alive.load(Ordering::SeqCst)
+    }
+
+    /// Returns the instance if it has not yet been freed by the garbage collector.
+    ///
+    /// Always returns `None` if called while a class is being finalized, as the referenced
+    /// instance might be in the middle of being freed.
+    pub fn upgrade(&self) -> Option<Class<'js, C>> {
+        if !self.is_alive() || unsafe { (*self.ctx.get_opaque()).finalizing > 0 } {
+            return None;
+        }
+        let value = unsafe {
+            let value = qjs::JS_MKPTR(qjs::JS_TAG_OBJECT, self.ptr);
+            Object::from_js_value(self.ctx.clone(), qjs::JS_DupValue(value))
+        };
+        Some(Class(value, PhantomData))
+    }
+}
+
+impl<'js, C: JsClass<'js>> Class<'js, C> {
+    /// Create a weak reference to this instance.
+    pub fn downgrade(&self) -> WeakRef<'js, C> {
+        WeakRef::new(self)
+    }
+}
+
+/// A token returned when registering an instance with a [`FinalizationRegistry`].
+///
+/// The token can be used to unregister the instance again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FinalizationToken(usize);
+
+struct RegistryInner<H> {
+    cleanup: CleanupFn<H>,
+    held: Mut<HashMap<usize, H>>,
+    next: AtomicUsize,
+}
+
+/// A registry of Rust callbacks which are called after a class instance is freed.
+///
+/// Similar to the JavaScript `FinalizationRegistry` every registered instance is associated
+/// with a held value which is passed to the cleanup callback once the instance is collected.
+///
+/// The cleanup callback is called from within the garbage collector and can therefore not use
+/// any JavaScript values, it should only be used for releasing Rust side resources like cache
+/// entries.
+pub struct FinalizationRegistry<H> {
+    inner: Ref<RegistryInner<H>>,
+}
+
+impl<H> Clone for FinalizationRegistry<H> {
+    fn clone(&self) -> Self {
+        FinalizationRegistry {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<H: ParallelSend + 'static> FinalizationRegistry<H> {
+    /// Create a new registry with the given cleanup callback.
+    pub fn new<F>(cleanup: F) -> Self
+    where
+        F: Fn(H) + ParallelSend + ParallelSync + 'static,
+    {
+        FinalizationRegistry {
+            inner: Ref::new(RegistryInner {
+                cleanup: Box::new(cleanup),
+                held: Mut::new(HashMap::new()),
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Register a class instance, the held value is passed to the cleanup callback once the
+    /// instance is freed.
+    pub fn register<'js, C: JsClass<'js>>(
+        &self,
+        target: &Class<'js, C>,
+        held: H,
+    ) -> FinalizationToken {
+        let id = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        self.inner.held.lock().insert(id, held);
+
+        let registry: Weak<RegistryInner<H>> = Ref::downgrade(&self.inner);
+        let callback = move || {
+            if let Some(registry) = registry.upgrade() {
+                // Release the lock before calling the callback so it can use the registry.
+                let held = registry.held.lock().remove(&id);
+                if let Some(held) = held {
+                    (registry.cleanup)(held);
+                }
+            }
+        };
+        weak_state(target).callbacks.lock().push(Box::new(callback));
+        FinalizationToken(id)
+    }
+
+    /// Unregister a previously registered instance, returning the held value if the cleanup
+    /// callback was not yet called.
+    pub fn unregister(&self, token: FinalizationToken) -> Option<H> {
+        self.inner.held.lock().remove(&token.0)
+    }
+
+    /// Returns the amount of registered instances which have not yet been cleaned up.
+    pub fn len(&self) -> usize {
+        self.inner.held.lock().len()
+    }
+
+    /// Returns if there are no registered instances which have not yet been cleaned up.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Marks the runtime as finalizing a class for the duration of the guard.
+pub(crate) struct FinalizeGuard(*mut Opaque<'static>, HoldingLock);
+
+impl FinalizeGuard {
+    pub(crate) unsafe fn new(rt: *mut qjs::JSRuntime) -> Self {
+        let opaque = qjs::JS_GetRuntimeOpaque(rt).cast::<Opaque>();
+        (*opaque).finalizing += 1;
+        // Finalizers are run while holding the runtime lock.
+        FinalizeGuard(opaque, HoldingLock::new(rt))
+    }
+}
+
+impl Drop for FinalizeGuard {
+    fn drop(&mut self) {
+        unsafe { (*self.0).finalizing -= 1 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        class::{ClassId, FinalizationRegistry, JsClass, Readable, Trace, Tracer, WeakRef},
+        value::Constructor,
+        Class, Context, Object, Outlive, Persistent, Runtime,
+    };
+
+    pub struct Item(u32);
+
+    unsafe impl<'js> Outlive<'js> for Item {
+        type Target<'to> = Item;
+    }
+
+    impl<'js> Trace<'js> for Item {
+        fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+    }
+
+    impl<'js> JsClass<'js> for Item {
+        const NAME: &'static str = "Item";
+
+        type Mutable = Readable;
+
+        fn class_id() -> &'static ClassId {
+            static ID: ClassId = ClassId::new();
+            &ID
+        }
+
+        fn prototype(ctx: &crate::Ctx<'js>) -> crate::Result<Option<Object<'js>>> {
+            Object::new(ctx.clone()).map(Some)
+        }
+
+        fn constructor(_ctx: &crate::Ctx<'js>) -> crate::Result<Option<Constructor<'js>>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn weak_ref() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        let weak = ctx.with(|ctx| {
+            let cls = Class::instance(ctx.clone(), Item(42)).unwrap();
+            ctx.globals().set("item", cls.clone()).unwrap();
+            let weak = cls.downgrade();
+            assert_eq!(weak.upgrade().unwrap().borrow().0, 42);
+            Persistent::save(&ctx, weak)
+        });
+
+        rt.run_gc();
+        ctx.with(|ctx| {
+            let weak: WeakRef<Item> = weak.clone().restore(&ctx).unwrap();
+            assert!(weak.is_alive());
+            assert_eq!(weak.upgrade().unwrap().borrow().0, 42);
+            ctx.globals().remove("item").unwrap();
+        });
+
+        rt.run_gc();
+        ctx.with(|ctx| {
+            let weak: WeakRef<Item> = weak.restore(&ctx).unwrap();
+            assert!(!weak.is_alive());
+            assert!(weak.upgrade().is_none());
+        });
+    }
+
+    #[test]
+    fn finalization_registry() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        let cleaned = Arc::new(Mutex::new(Vec::new()));
+        let cleaned_clone = cleaned.clone();
+        let registry = FinalizationRegistry::new(move |held: &'static str| {
+            cleaned_clone.lock().unwrap().push(held)
+        });
+
+        ctx.with(|ctx| {
+            let a = Class::instance(ctx.clone(), Item(1)).unwrap();
+            let b = Class::instance(ctx.clone(), Item(2)).unwrap();
+            registry.register(&a, "a");
+            let token = registry.register(&b, "b");
+            assert_eq!(registry.unregister(token), Some("b"));
+            assert_eq!(registry.len(), 1);
+        });
+
+        rt.run_gc();
+        assert_eq!(*cleaned.lock().unwrap(), ["a"]);
+        assert!(registry.is_empty());
+    }
+}
@@ -0,0 +1,24 @@
+//! The clock used by the profiler and the `performance` global, which comes from the embedder on
+//! `wasm32-unknown-unknown`.
+
+#[cfg(not(all(feature = "wasm", target_family = "wasm", target_os = "unknown")))]
+pub(crate) use std::time::Instant;
+
+#[cfg(all(feature = "wasm", target_family = "wasm", target_os = "unknown"))]
+pub(crate) use crate::wasm::Instant;
+
+/// The current time in milliseconds since the unix epoch.
+pub(crate) fn unix_millis() -> f64 {
+    #[cfg(not(all(feature = "wasm", target_family = "wasm", target_os = "unknown")))]
+    {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|x| x.as_secs_f64() * 1000.0)
+            .unwrap_or_default()
+    }
+    #[cfg(all(feature = "wasm", target_family = "wasm", target_os = "unknown"))]
+    {
+        crate::wasm::now()
+    }
+}
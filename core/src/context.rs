@@ -23,6 +23,21 @@ pub trait MultiWith<'js> {
 mod base;
 pub use base::Context;
 
+mod pool;
+pub use pool::{ContextPool, PooledContext, ResetPolicy};
+
+mod realm;
+pub use realm::RealmTemplate;
+
+pub(crate) mod snapshot;
+pub use snapshot::{Snapshot, SnapshotBuilder};
+
+mod repl;
+pub use repl::{complete, CompleteOrPartial};
+
+mod stats;
+pub use stats::ContextStats;
+
 #[cfg(feature = "futures")]
 mod r#async;
 #[cfg(feature = "futures")]
@@ -1,6 +1,11 @@
 use std::{future::Future, mem, pin::Pin, ptr::NonNull};
 
-use crate::{markers::ParallelSend, qjs, runtime::AsyncRuntime, Ctx, Error, Result};
+use crate::{
+    markers::ParallelSend,
+    qjs,
+    runtime::{raw::Opaque, AsyncRuntime},
+    Ctx, Error, Result,
+};
 
 use self::future::WithFuture;
 
@@ -102,6 +107,18 @@ impl Clone for Inner {
 #[cfg(feature = "parallel")]
 unsafe impl Send for Inner {}
 
+impl Inner {
+    /// Removes the futures spawned from this context, as they can't outlive it.
+    ///
+    /// # Safety
+    /// The runtime lock must be held.
+    unsafe fn cancel_spawned(&self) -> Option<Vec<impl Future<Output = ()>>> {
+        let rt = qjs::JS_GetRuntime(self.ctx.as_ptr());
+        let opaque = &*(qjs::JS_GetRuntimeOpaque(rt) as *const Opaque);
+        opaque.spawner.as_ref().map(|x| x.cancel(self.ctx))
+    }
+}
+
 impl Drop for Inner {
     fn drop(&mut self) {
         //TODO
@@ -118,6 +135,9 @@ impl Drop for Inner {
                         // following assertion to trigger
                         assert!(std::thread::panicking());
                     }
+                    // The lock is held further up the stack, for example by a future driven by
+                    // the runtime which dropped the context.
+                    mem::drop(unsafe { self.cancel_spawned() });
                     unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
                     return;
                 }
@@ -132,6 +152,7 @@ impl Drop for Inner {
             }
         };
         guard.runtime.update_stack_top();
+        mem::drop(unsafe { self.cancel_spawned() });
         unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         mem::drop(guard);
@@ -7,7 +7,9 @@ use std::{
 
 use async_lock::futures::Lock;
 
-use crate::{markers::ParallelSend, runtime::InnerRuntime, AsyncContext, Ctx};
+use crate::{
+    markers::ParallelSend, persistent::HoldingLock, runtime::InnerRuntime, AsyncContext, Ctx,
+};
 
 pub struct WithFuture<'a, F, R> {
     context: &'a AsyncContext,
@@ -84,6 +86,7 @@ where
         };
 
         lock.runtime.update_stack_top();
+        let _holding = HoldingLock::new(lock.runtime.rt.as_ptr());
 
         // At this point we have locked the runtime so we start running the actual future
         let res = loop {
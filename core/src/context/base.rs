@@ -1,6 +1,8 @@
 use std::{mem, ptr::NonNull};
 
-use crate::{class::Class, function::RustFunction, qjs, Ctx, Error, Result, Runtime};
+use crate::{
+    class::Class, function::RustFunction, persistent::HoldingLock, qjs, Ctx, Error, Result, Runtime,
+};
 
 use super::{intrinsic, r#ref::ContextRef, ContextBuilder, Intrinsic};
 
@@ -117,6 +119,7 @@ impl Context {
     {
         let guard = self.0.rt.inner.lock();
         guard.update_stack_top();
+        let _holding = HoldingLock::new(self.get_runtime_ptr());
         let ctx = unsafe { Ctx::new(self) };
         f(ctx)
     }
@@ -127,13 +130,13 @@ impl Context {
     }
 }
 
-impl Drop for Context {
+impl Drop for Inner {
     fn drop(&mut self) {
         //TODO
-        let guard = match self.0.rt.inner.try_lock() {
+        let guard = match self.rt.inner.try_lock() {
             Some(x) => x,
             None => {
-                let p = unsafe { &mut *(self.0.ctx.as_ptr() as *mut qjs::JSRefCountHeader) };
+                let p = unsafe { &mut *(self.ctx.as_ptr() as *mut qjs::JSRefCountHeader) };
                 if p.ref_count <= 1 {
                     // Lock was poisoned, this should only happen on a panic.
                     // We should still free the context.
@@ -141,12 +144,12 @@ impl Drop for Context {
                     // following assertion to trigger
                     assert!(std::thread::panicking());
                 }
-                unsafe { qjs::JS_FreeContext(self.0.ctx.as_ptr()) }
+                unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
                 return;
             }
         };
         guard.update_stack_top();
-        unsafe { qjs::JS_FreeContext(self.0.ctx.as_ptr()) }
+        unsafe { qjs::JS_FreeContext(self.ctx.as_ptr()) }
         // Explicitly drop the guard to ensure it is valid during the entire use of runtime
         mem::drop(guard);
     }
@@ -167,6 +170,21 @@ mod test {
     use super::*;
     use crate::*;
 
+    #[test]
+    fn drop_clone() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        // Dropping a clone must not free the context which is still used by the other handle.
+        std::mem::drop(ctx.clone());
+        ctx.with(|ctx| {
+            ctx.globals().set("a", 1).unwrap();
+            assert_eq!(ctx.eval::<i32, _>("a + 1").unwrap(), 2);
+        });
+        let clone = ctx.clone();
+        std::mem::drop(ctx);
+        clone.with(|ctx| assert_eq!(ctx.eval::<i32, _>("a").unwrap(), 1));
+    }
+
     #[test]
     fn base() {
         test_with(|ctx| {
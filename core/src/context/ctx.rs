@@ -1,7 +1,10 @@
 use std::{
+    any::TypeId,
     convert::TryInto,
     ffi::{CStr, CString},
-    fs, mem,
+    fs,
+    io::Read,
+    mem,
     path::Path,
     ptr::NonNull,
 };
@@ -12,18 +15,44 @@ use std::future::Future;
 #[cfg(feature = "futures")]
 use crate::AsyncContext;
 use crate::{
-    markers::Invariant, qjs, runtime::raw::Opaque, Context, Error, FromJs, Function, IntoJs,
-    Module, Object, Result, String, Value,
+    context::snapshot::{read_object, write_object},
+    markers::Invariant,
+    qjs,
+    runtime::{cache_header, cache_key, metered, profile, raw::Opaque, ProfileKind},
+    Context, Error, FromJs, Function, IntoJs, Module, Object, Result, StdString, String, Value,
 };
 
 /// Eval options.
+///
+/// More options might be added in the future, so create the options from the [`Default`]
+/// implementation, with the builder methods or with `..Default::default()` in a struct literal:
+/// ```
+/// # use rquickjs::context::EvalOptions;
+/// let options = EvalOptions::default()
+///     .strict(false)
+///     .filename("config.js")
+///     .line_offset(2);
+/// # assert!(!options.strict);
+/// let options = EvalOptions {
+///     strict: false,
+///     ..Default::default()
+/// };
+/// # assert!(options.global);
+/// ```
 pub struct EvalOptions {
-    /// Global code.
+    /// Global code, if false the source is evaluated as a module.
     pub global: bool,
     /// Force 'strict' mode.
     pub strict: bool,
     /// Don't include the stack frames before this eval in the Error() backtraces.
     pub backtrace_barrier: bool,
+    /// Don't include the stack frames of the functions defined by this eval in the Error()
+    /// backtraces.
+    pub hide_frames: bool,
+    /// The file name used in error backtraces, defaults to `eval_script`.
+    pub filename: Option<StdString>,
+    /// The number of lines preceding the source, used to offset the line numbers in backtraces.
+    pub line_offset: u32,
 }
 
 impl EvalOptions {
@@ -42,8 +71,57 @@ impl EvalOptions {
             flag |= qjs::JS_EVAL_FLAG_BACKTRACE_BARRIER;
         }
 
+        if self.hide_frames {
+            flag |= qjs::JS_EVAL_FLAG_HIDE_FRAMES;
+        }
+
         flag as i32
     }
+
+    fn file_name(&self) -> Result<Option<CString>> {
+        Ok(self.filename.as_deref().map(CString::new).transpose()?)
+    }
+
+    /// Set whether the source is evaluated as global code instead of as a module.
+    pub fn global(mut self, global: bool) -> Self {
+        self.global = global;
+        self
+    }
+
+    /// Set whether 'strict' mode is forced.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set whether the stack frames before this eval are excluded from Error() backtraces.
+    pub fn backtrace_barrier(mut self, backtrace_barrier: bool) -> Self {
+        self.backtrace_barrier = backtrace_barrier;
+        self
+    }
+
+    /// Set whether the stack frames of the functions defined by this eval are excluded from
+    /// Error() backtraces.
+    ///
+    /// This hides the code of an embedder, like a bootstrap script wrapping the scripts of its
+    /// users, from the backtraces shown to the users. The location of an error is taken from
+    /// the first frame which is not hidden.
+    pub fn hide_frames(mut self, hide_frames: bool) -> Self {
+        self.hide_frames = hide_frames;
+        self
+    }
+
+    /// Set the file name used in error backtraces.
+    pub fn filename<S: Into<StdString>>(mut self, filename: S) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Set the number of lines preceding the source.
+    pub fn line_offset(mut self, line_offset: u32) -> Self {
+        self.line_offset = line_offset;
+        self
+    }
 }
 
 impl Default for EvalOptions {
@@ -52,6 +130,9 @@ impl Default for EvalOptions {
             global: true,
             strict: true,
             backtrace_barrier: false,
+            hide_frames: false,
+            filename: None,
+            line_offset: 0,
         }
     }
 }
@@ -112,23 +193,98 @@ impl<'js> Ctx<'js> {
         }
     }
 
+    /// Evaluate a source, `line_offset` is the number of lines preceding it in its file.
     pub(crate) unsafe fn eval_raw<S: Into<Vec<u8>>>(
         &self,
         source: S,
         file_name: &CStr,
+        line_offset: u32,
         flag: i32,
     ) -> Result<qjs::JSValue> {
         let src = source.into();
         let len = src.len();
         let src = CString::new(src)?;
-        let val = qjs::JS_Eval(
+        profile(
+            self.get_opaque(),
+            ProfileKind::Eval,
+            || file_name.to_string_lossy().into_owned(),
+            || {
+                if let Some(res) = self.eval_cached(&src, file_name, line_offset, flag) {
+                    return res;
+                }
+                let val = qjs::JS_EvalLine(
+                    self.ctx.as_ptr(),
+                    src.as_ptr(),
+                    len as _,
+                    file_name.as_ptr(),
+                    line_offset.saturating_add(1).min(i32::MAX as u32) as _,
+                    flag,
+                );
+                self.handle_exception(val)
+            },
+            |res| res.is_err(),
+        )
+    }
+
+    /// Evaluate a global script through the compile cache of the runtime.
+    ///
+    /// Returns `None` if there is no cache or the script is not run, which is never cached.
+    unsafe fn eval_cached(
+        &self,
+        src: &CStr,
+        file_name: &CStr,
+        line_offset: u32,
+        flag: i32,
+    ) -> Option<Result<qjs::JSValue>> {
+        let opaque = self.get_opaque();
+        let cacheable = flag & qjs::JS_EVAL_TYPE_MASK as i32 == qjs::JS_EVAL_TYPE_GLOBAL as i32
+            && flag & qjs::JS_EVAL_FLAG_COMPILE_ONLY as i32 == 0;
+        if !cacheable || (*opaque).compile_cache.is_none() {
+            return None;
+        }
+        let key = cache_key(src.to_bytes(), file_name, line_offset, flag);
+        let header = cache_header(src.to_bytes(), file_name, line_offset, flag);
+        // The cache is not borrowed while running the script as it may evaluate scripts too.
+        let cached = (*opaque).compile_cache.as_mut()?.get(key);
+        // An entry of another script with the same key is a miss.
+        let bytecode = cached
+            .as_deref()
+            .and_then(|entry| entry.strip_prefix(header.as_slice()));
+        if let Some(bytecode) = bytecode {
+            // Safety: the cache only returns entries of this QuickJS build, see `CompileCache`.
+            match read_object(self, bytecode, qjs::JS_READ_OBJ_BYTECODE as _) {
+                // JS_EvalFunction frees the function.
+                Ok(func) => {
+                    return Some(self.handle_exception(qjs::JS_EvalFunction(self.as_ptr(), func)))
+                }
+                // The bytecode is from a different QuickJS version, compile it again.
+                Err(_) => mem::drop(self.catch()),
+            }
+        }
+        let func = qjs::JS_EvalLine(
             self.ctx.as_ptr(),
             src.as_ptr(),
-            len as _,
+            src.to_bytes().len() as _,
             file_name.as_ptr(),
-            flag,
+            line_offset.saturating_add(1).min(i32::MAX as u32) as _,
+            flag | qjs::JS_EVAL_FLAG_COMPILE_ONLY as i32,
         );
-        self.handle_exception(val)
+        let func = match self.handle_exception(func) {
+            Ok(func) => func,
+            Err(error) => return Some(Err(error)),
+        };
+        match write_object(self, func, qjs::JS_WRITE_OBJ_BYTECODE as _) {
+            Ok(bytecode) => {
+                if let Some(cache) = (*opaque).compile_cache.as_mut() {
+                    let mut entry = header;
+                    entry.extend_from_slice(&bytecode);
+                    cache.insert(key, entry);
+                }
+            }
+            // Scripts which can't be serialized still run, they are just not cached.
+            Err(_) => mem::drop(self.catch()),
+        }
+        Some(self.handle_exception(qjs::JS_EvalFunction(self.as_ptr(), func)))
     }
 
     /// Evaluate a script in global context.
@@ -142,14 +298,66 @@ impl<'js> Ctx<'js> {
         source: S,
         options: EvalOptions,
     ) -> Result<V> {
-        let file_name = cstr!("eval_script");
+        let file_name = options.file_name()?;
+        let file_name = file_name.as_deref().unwrap_or(cstr!("eval_script"));
+        let source = source.into();
+        #[cfg(feature = "source-map")]
+        self.register_linked_source_map(&file_name.to_string_lossy(), &source, None);
+        #[cfg(feature = "coverage")]
+        let source = self.instrument_source(file_name, source, options.line_offset);
 
         V::from_js(self, unsafe {
-            let val = self.eval_raw(source, file_name, options.to_flag())?;
+            let val = self.eval_raw(source, file_name, options.line_offset, options.to_flag())?;
             Value::from_js_value(self.clone(), val)
         })
     }
 
+    /// Evaluate a script with a budget of fuel, returns the value and the fuel consumed.
+    ///
+    /// One unit of fuel is consumed every time the interpreter checks for interrupts, which it
+    /// does on every function call and every jump, so the consumption only depends on the
+    /// executed code and is the same on every machine. Running out of fuel aborts the evaluation
+    /// with [`Error::OutOfFuel`].
+    ///
+    /// Only the code running in this context is metered, the fuel consumed by a nested metered
+    /// evaluation is not counted against the outer budget.
+    pub fn eval_with_fuel<V: FromJs<'js>, S: Into<Vec<u8>>>(
+        &self,
+        source: S,
+        fuel: u64,
+    ) -> Result<(V, u64)> {
+        let (res, consumed) = metered(self, fuel, || self.eval(source));
+        res.map(|x| (x, consumed))
+    }
+
+    /// Evaluate a script read from a reader.
+    ///
+    /// This is a convenience wrapper which reads the whole source into a buffer before
+    /// evaluating it. The source can't be fed to QuickJS in chunks: its parser needs the complete
+    /// source in one contiguous buffer, as it goes back to earlier tokens, for example to tell
+    /// arrow functions from parenthesized expressions, and keeps pointers into the source until
+    /// the end of the functions it parses. So the memory used is at least the size of the source,
+    /// reading directly into a byte buffer only avoids the intermediate `String`, its UTF-8
+    /// validation and the copy into a C string.
+    pub fn eval_reader<V: FromJs<'js>, R: Read>(&self, reader: R) -> Result<V> {
+        self.eval_reader_with_options(reader, Default::default())
+    }
+
+    /// Evaluate a script read from a reader with the given options.
+    ///
+    /// See [`Ctx::eval_reader`] for how the source is read.
+    pub fn eval_reader_with_options<V: FromJs<'js>, R: Read>(
+        &self,
+        mut reader: R,
+        options: EvalOptions,
+    ) -> Result<V> {
+        let mut source = Vec::new();
+        reader.read_to_end(&mut source)?;
+        // Reserve space for the nul terminator so it can be added without reallocating.
+        source.reserve_exact(1);
+        self.eval_with_options(source, options)
+    }
+
     /// Evaluate a script directly from a file.
     pub fn eval_file<V: FromJs<'js>, P: AsRef<Path>>(&self, path: P) -> Result<V> {
         self.eval_file_with_options(path, Default::default())
@@ -161,16 +369,32 @@ impl<'js> Ctx<'js> {
         options: EvalOptions,
     ) -> Result<V> {
         let buffer = fs::read(path.as_ref())?;
-        let file_name = CString::new(
-            path.as_ref()
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .into_owned(),
-        )?;
+        let file_name = match options.file_name()? {
+            Some(x) => x,
+            None => CString::new(
+                path.as_ref()
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned(),
+            )?,
+        };
+        #[cfg(feature = "source-map")]
+        self.register_linked_source_map(
+            &file_name.to_string_lossy(),
+            &buffer,
+            path.as_ref().parent(),
+        );
+        #[cfg(feature = "coverage")]
+        let buffer = self.instrument_source(&file_name, buffer, options.line_offset);
 
         V::from_js(self, unsafe {
-            let val = self.eval_raw(buffer, file_name.as_c_str(), options.to_flag())?;
+            let val = self.eval_raw(
+                buffer,
+                file_name.as_c_str(),
+                options.line_offset,
+                options.to_flag(),
+            )?;
             Value::from_js_value(self.clone(), val)
         })
     }
@@ -225,6 +449,9 @@ impl<'js> Ctx<'js> {
     }
 
     /// Parse json into a JavaScript value.
+    ///
+    /// This calls the JSON parser of QuickJS directly, so it also works in contexts without the
+    /// `JSON` global.
     pub fn json_parse<S>(&self, json: S) -> Result<Value<'js>>
     where
         S: Into<Vec<u8>>,
@@ -241,9 +468,12 @@ impl<'js> Ctx<'js> {
     where
         S: Into<Vec<u8>>,
     {
-        let src = json.into();
+        let mut src = json.into();
         let len = src.len();
-        let src = CString::new(src)?;
+        // The parser stops at a nul byte, so there is no need to check for them like
+        // `CString::new` does.
+        src.reserve_exact(1);
+        src.push(0);
         unsafe {
             let flag = if allow_extensions {
                 qjs::JS_PARSE_JSON_EXT as i32
@@ -264,6 +494,9 @@ impl<'js> Ctx<'js> {
     }
 
     /// Stringify a JavaScript value into its JSON representation
+    ///
+    /// Returns `None` if the value has no JSON representation, like `undefined` or a function.
+    /// Like [`Ctx::json_parse`] this does not depend on the `JSON` global.
     pub fn json_stringify<V>(&self, value: V) -> Result<Option<String<'js>>>
     where
         V: IntoJs<'js>,
@@ -363,19 +596,84 @@ impl<'js> Ctx<'js> {
         })
     }
 
+    /// Returns the user data of the given type stored in the runtime.
+    ///
+    /// User data is stored with [`Runtime::set_userdata`](crate::Runtime::set_userdata).
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Function};
+    /// struct Config {
+    ///     greeting: &'static str,
+    /// }
+    ///
+    /// let rt = Runtime::new().unwrap();
+    /// rt.set_userdata(Config { greeting: "hello" });
+    /// let ctx = Context::full(&rt).unwrap();
+    /// ctx.with(|ctx| {
+    ///     let greet = Function::new(ctx.clone(), |ctx: rquickjs::Ctx| {
+    ///         ctx.userdata::<Config>().unwrap().greeting
+    ///     })
+    ///     .unwrap();
+    ///     let res: String = greet.call(()).unwrap();
+    ///     assert_eq!(res, "hello");
+    /// });
+    /// ```
+    pub fn userdata<T: 'static>(&self) -> Option<&T> {
+        // The user data can only be replaced or removed while holding the runtime lock, which
+        // is held for as long as the Ctx exists.
+        unsafe {
+            (*self.get_opaque())
+                .userdata
+                .get(&TypeId::of::<T>())
+                .and_then(|x| x.downcast_ref())
+        }
+    }
+
+    /// Returns the classes registered in the runtime, including the classes defined with
+    /// [`DynamicClass`](crate::class::DynamicClass).
+    pub fn class_registry(&self) -> crate::class::ClassRegistry<'js> {
+        crate::class::ClassRegistry::new(self.clone())
+    }
+
+    /// Returns the amount of native Rust functions currently running in the runtime, nested in
+    /// each other, including the calling one.
+    ///
+    /// See [`Runtime::set_max_native_recursion`](crate::Runtime::set_max_native_recursion).
+    pub fn native_call_depth(&self) -> usize {
+        unsafe { (*self.get_opaque()).native_depth }
+    }
+
     pub(crate) unsafe fn get_opaque(&self) -> *mut Opaque<'js> {
         let rt = qjs::JS_GetRuntime(self.ctx.as_ptr());
         qjs::JS_GetRuntimeOpaque(rt).cast::<Opaque>()
     }
 
-    /// Spawn future using configured async runtime
+    /// Spawn a future onto the task set of the async runtime.
+    ///
+    /// The future is driven along with the runtime, like by [`AsyncRuntime::idle`] or the
+    /// [`AsyncRuntime::drive`] future, and can use the context for background work like calling
+    /// into JavaScript when something happens. It never outlives the engine: it is dropped
+    /// without completing when the last handle to the [`AsyncContext`] it was spawned from, or
+    /// the runtime, is dropped first.
+    ///
+    /// [`AsyncRuntime::idle`]: crate::AsyncRuntime::idle
+    /// [`AsyncRuntime::drive`]: crate::AsyncRuntime::drive
+    /// [`AsyncContext`]: crate::AsyncContext
+    ///
+    /// # Panic
+    /// Panics if the context doesn't belong to an async runtime.
     #[cfg(feature = "futures")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
     pub fn spawn<F>(&self, future: F)
     where
         F: Future<Output = ()> + 'js,
     {
-        unsafe { (*self.get_opaque()).spawner().push(future) }
+        unsafe { (*self.get_opaque()).spawner().push(self.ctx, future) }
+    }
+
+    /// Reseed `Math.random` of this context, after which it returns the same numbers for the
+    /// same seed.
+    pub fn set_random_seed(&self, seed: u64) {
+        unsafe { qjs::JS_SetContextRandomSeed(self.as_ptr(), seed) }
     }
 
     /// Create a new `Ctx` from a pointer to the context and a invariant lifetime.
@@ -545,6 +843,148 @@ mod test {
         })
     }
 
+    #[test]
+    fn eval_with_options_filename_and_line_offset() {
+        use crate::{context::EvalOptions, CatchResultExt, CaughtError, Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let options = || EvalOptions::default().filename("bundle.js").line_offset(10);
+            let err = ctx
+                .eval_with_options::<(), _>("\nthrow new Error('oops')", options())
+                .catch(&ctx)
+                .unwrap_err();
+            let CaughtError::Exception(err) = err else {
+                panic!("expected an exception");
+            };
+            assert!(err.stack().unwrap().contains("bundle.js:12"));
+
+            // The source is not padded so column numbers and the source text stay intact.
+            let res: crate::StdString = ctx
+                .eval_with_options("(function() { return new Error().stack })()", options())
+                .unwrap();
+            assert!(res.contains("bundle.js:11"), "{res}");
+            let err = ctx
+                .eval_with_options::<(), _>("let a =\n;", options())
+                .catch(&ctx)
+                .unwrap_err();
+            let CaughtError::Exception(err) = err else {
+                panic!("expected an exception");
+            };
+            assert!(err.stack().unwrap().contains("bundle.js:12"));
+
+            // Scripts evaluated later start at the first line again.
+            let res: crate::StdString = ctx
+                .eval_with_options(
+                    "new Error().stack",
+                    EvalOptions::default().filename("other.js"),
+                )
+                .unwrap();
+            assert!(res.contains("other.js:1"), "{res}");
+        })
+    }
+
+    #[test]
+    fn eval_with_options_hidden_frames() {
+        use crate::{context::EvalOptions, CatchResultExt, CaughtError, Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            ctx.eval_with_options::<(), _>(
+                "globalThis.run = function harness(f) { return f() }",
+                EvalOptions::default()
+                    .filename("harness.js")
+                    .hide_frames(true),
+            )
+            .unwrap();
+            ctx.eval_with_options::<(), _>(
+                "globalThis.main = function main() { return run(() => { throw new Error('oops') }) }",
+                EvalOptions::default().filename("user.js"),
+            )
+            .unwrap();
+
+            let err = ctx
+                .eval_with_options::<(), _>(
+                    "run(main)",
+                    EvalOptions::default()
+                        .filename("entry.js")
+                        .hide_frames(true)
+                        .backtrace_barrier(true),
+                )
+                .catch(&ctx)
+                .unwrap_err();
+            let CaughtError::Exception(err) = err else {
+                panic!("expected an exception");
+            };
+            let stack = err.stack().unwrap();
+            assert!(!stack.contains("harness"), "{stack}");
+            assert!(!stack.contains("entry.js"), "{stack}");
+            assert_eq!(stack.matches("user.js").count(), 2, "{stack}");
+            assert!(stack.starts_with("    at <anonymous> (user.js"), "{stack}");
+
+            let stack: crate::StdString = ctx
+                .eval_with_options(
+                    "run(() => new Error().stack)",
+                    EvalOptions::default().filename("visible.js"),
+                )
+                .unwrap();
+            assert!(!stack.contains("harness"), "{stack}");
+            assert!(stack.contains("visible.js"), "{stack}");
+        })
+    }
+
+    #[test]
+    fn eval_reader() {
+        use crate::{Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let source = "let a = 0;\n".to_string() + &"a += 1;\n".repeat(1000) + "a";
+            let res: i32 = ctx.eval_reader(source.as_bytes()).unwrap();
+            assert_eq!(res, 1000);
+        })
+    }
+
+    #[test]
+    fn eval_with_fuel() {
+        use crate::{Context, Error, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            for iterations in [10, 100_000] {
+                let source = format!("var a = 0; for (let i = 0; i < {iterations}; i++) a += i; a");
+                let (_, consumed) = ctx
+                    .eval_with_fuel::<f64, _>(source.clone(), u64::MAX)
+                    .unwrap();
+                assert!(consumed > iterations);
+                let (res, same) = ctx
+                    .eval_with_fuel::<f64, _>(source.clone(), consumed)
+                    .unwrap();
+                assert_eq!(same, consumed);
+                assert_eq!(res, (iterations * (iterations - 1) / 2) as f64);
+                let err = ctx
+                    .eval_with_fuel::<f64, _>(source, consumed - 1)
+                    .unwrap_err();
+                assert!(matches!(err, Error::OutOfFuel));
+            }
+
+            let err = ctx
+                .eval_with_fuel::<(), _>("try { while (true) {} } catch (e) {}", 1000)
+                .unwrap_err();
+            assert!(err.is_out_of_fuel());
+            assert!(ctx.catch().is_null());
+
+            let res: i32 = ctx
+                .eval("let b = 0; for (let i = 0; i < 20000; i++) b++; b")
+                .unwrap();
+            assert_eq!(res, 20000);
+        })
+    }
+
     #[test]
     fn json_parse() {
         use crate::{Array, Context, Object, Runtime};
@@ -617,4 +1057,62 @@ mod test {
             assert_eq!(str, r#"{"a":{"b":1,"c":true},"d":[0,"foo"]}"#);
         })
     }
+
+    #[test]
+    fn json_without_global() {
+        use crate::{Context, Runtime, Undefined};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::base(&runtime).unwrap();
+        ctx.with(|ctx| {
+            assert!(!ctx.globals().contains_key("JSON").unwrap());
+            let v = ctx.json_parse(r#"{ "a": [1, 2], "b": "c" }"#).unwrap();
+            let str = ctx
+                .json_stringify_replacer_space(v, vec!["a"], 1)
+                .unwrap()
+                .unwrap()
+                .to_string()
+                .unwrap();
+            assert_eq!(str, "{\n \"a\": [\n  1,\n  2\n ]\n}");
+            assert!(ctx.json_stringify(Undefined).unwrap().is_none());
+            assert!(ctx.json_parse("{ a: 1 }").is_err());
+            ctx.catch();
+            assert!(ctx.json_parse("[1]\0[2]").is_err());
+        })
+    }
+
+    #[cfg(feature = "unsafe-api")]
+    #[test]
+    fn raw_api() {
+        use crate::{qjs, Context, Error, Object, Runtime, Value};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+        ctx.with(|ctx| {
+            let object = unsafe {
+                let value = ctx
+                    .handle_exception(qjs::JS_NewObject(ctx.as_raw().as_ptr()))
+                    .unwrap();
+                Value::from_raw(ctx.clone(), value)
+            };
+            let object = object.into_object().unwrap();
+            object.set("a", 1).unwrap();
+            ctx.globals().set("object", object.clone()).unwrap();
+            assert_eq!(ctx.eval::<i32, _>("object.a").unwrap(), 1);
+
+            let thrower: Object = ctx
+                .eval("({ get a() { throw new Error('raw') } })")
+                .unwrap();
+            let res = unsafe {
+                ctx.handle_exception(qjs::JS_GetPropertyStr(
+                    ctx.as_raw().as_ptr(),
+                    thrower.as_raw(),
+                    crate::cstr!("a").as_ptr(),
+                ))
+            };
+            assert!(matches!(res, Err(Error::Exception)));
+            let error = ctx.catch().into_exception().unwrap();
+            assert_eq!(error.message().as_deref(), Some("raw"));
+        })
+    }
 }
@@ -0,0 +1,292 @@
+use std::{fmt, mem, ops::Deref, sync::Arc};
+
+use crate::{
+    context::Snapshot,
+    markers::{ParallelSend, ParallelSync},
+    safe_ref::{Mut, Ref},
+    Context, Result, Runtime,
+};
+
+/// How a context is reset when it is returned to a [`ContextPool`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ResetPolicy {
+    /// Replace the context with a newly created one, discarding all global state.
+    #[default]
+    NewContext,
+    /// Replace the context with a newly created one and restore a [`Snapshot`] into it.
+    ///
+    /// The snapshot is also restored into the contexts created when filling the pool. This
+    /// avoids re-running the initialization code in the closure creating the contexts.
+    Restore(Arc<Snapshot>),
+    /// Keep the context, global state persists between checkouts.
+    Reuse,
+}
+
+#[cfg(not(feature = "parallel"))]
+type CreateFn = dyn Fn(&Runtime) -> Result<Context>;
+#[cfg(feature = "parallel")]
+type CreateFn = dyn Fn(&Runtime) -> Result<Context> + Send + Sync;
+
+struct Entry {
+    runtime: Runtime,
+    context: Context,
+}
+
+struct Inner {
+    create: Box<CreateFn>,
+    policy: ResetPolicy,
+    size: usize,
+    idle: Mut<Vec<Entry>>,
+}
+
+impl Inner {
+    fn create(&self) -> Result<Entry> {
+        let runtime = Runtime::new()?;
+        let context = self.create_context(&runtime)?;
+        Ok(Entry { runtime, context })
+    }
+
+    fn create_context(&self, runtime: &Runtime) -> Result<Context> {
+        let context = (self.create)(runtime)?;
+        if let ResetPolicy::Restore(snapshot) = &self.policy {
+            context.with(|ctx| snapshot.restore(&ctx))?;
+        }
+        Ok(context)
+    }
+
+    fn release(&self, mut entry: Entry) {
+        if self.idle.lock().len() >= self.size {
+            return;
+        }
+        if self.policy != ResetPolicy::Reuse {
+            mem::drop(entry.context);
+            entry.runtime.run_gc();
+            match self.create_context(&entry.runtime) {
+                Ok(context) => entry.context = context,
+                // The runtime is discarded, a new one is created on the next checkout.
+                Err(_) => return,
+            }
+        }
+        let mut idle = self.idle.lock();
+        if idle.len() < self.size {
+            idle.push(entry);
+        }
+    }
+}
+
+/// A pool of pre-warmed runtimes, each with a single context.
+///
+/// Every runtime in the pool is only used by a single checked out context at a time, which
+/// makes the pool useful for servers which need to handle a lot of requests in isolation.
+///
+/// ```
+/// # use rquickjs::{Context, context::{ContextPool, ResetPolicy}};
+/// let pool = ContextPool::new(2, ResetPolicy::NewContext, Context::full).unwrap();
+///
+/// let ctx = pool.checkout().unwrap();
+/// ctx.with(|ctx| ctx.eval::<(), _>("globalThis.leak = 1").unwrap());
+/// drop(ctx);
+///
+/// let ctx = pool.checkout().unwrap();
+/// ctx.with(|ctx| assert!(!ctx.globals().contains_key("leak").unwrap()));
+/// ```
+///
+/// With the `parallel` feature enabled the pool can be shared between threads.
+#[derive(Clone)]
+pub struct ContextPool(Ref<Inner>);
+
+impl ContextPool {
+    /// Create a new pool with `size` runtimes.
+    ///
+    /// The `create` closure is called to create a context for a runtime, it can be used to
+    /// configure the runtime and to run initialization code in the new context.
+    pub fn new<F>(size: usize, policy: ResetPolicy, create: F) -> Result<Self>
+    where
+        F: Fn(&Runtime) -> Result<Context> + ParallelSend + ParallelSync + 'static,
+    {
+        let inner = Inner {
+            create: Box::new(create),
+            policy,
+            size,
+            idle: Mut::new(Vec::with_capacity(size)),
+        };
+        let entries = (0..size)
+            .map(|_| inner.create())
+            .collect::<Result<Vec<_>>>()?;
+        *inner.idle.lock() = entries;
+        Ok(ContextPool(Ref::new(inner)))
+    }
+
+    /// Check out a context from the pool.
+    ///
+    /// If all runtimes are in use a new runtime is created. The context is returned to the pool
+    /// once the [`PooledContext`] is dropped.
+    pub fn checkout(&self) -> Result<PooledContext> {
+        let entry = self.0.idle.lock().pop();
+        let entry = match entry {
+            Some(x) => x,
+            None => self.0.create()?,
+        };
+        Ok(PooledContext {
+            entry: Some(entry),
+            pool: self.0.clone(),
+        })
+    }
+
+    /// Returns the number of runtimes ready to be checked out.
+    pub fn idle(&self) -> usize {
+        self.0.idle.lock().len()
+    }
+
+    /// Returns the reset policy of the pool.
+    pub fn policy(&self) -> &ResetPolicy {
+        &self.0.policy
+    }
+}
+
+impl fmt::Debug for ContextPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContextPool")
+            .field("policy", &self.0.policy)
+            .field("size", &self.0.size)
+            .field("idle", &self.idle())
+            .finish()
+    }
+}
+
+/// A context checked out from a [`ContextPool`], returned to the pool when dropped.
+///
+/// Clones of the context should not be kept after this object is dropped as the runtime will
+/// be reused for other checkouts.
+pub struct PooledContext {
+    entry: Option<Entry>,
+    pool: Ref<Inner>,
+}
+
+impl PooledContext {
+    /// Returns the runtime of the context.
+    pub fn runtime(&self) -> &Runtime {
+        &self.entry.as_ref().unwrap().runtime
+    }
+
+    /// Returns the context.
+    pub fn context(&self) -> &Context {
+        &self.entry.as_ref().unwrap().context
+    }
+
+    /// Remove the context from the pool, it won't be returned when dropped.
+    pub fn detach(mut self) -> (Runtime, Context) {
+        let entry = self.entry.take().unwrap();
+        (entry.runtime, entry.context)
+    }
+}
+
+impl Deref for PooledContext {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        self.context()
+    }
+}
+
+impl Drop for PooledContext {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            self.pool.release(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::SnapshotBuilder;
+
+    #[test]
+    fn new_context() {
+        let pool = ContextPool::new(1, ResetPolicy::NewContext, |rt| {
+            let ctx = Context::full(rt)?;
+            ctx.with(|ctx| ctx.eval::<(), _>("globalThis.count = 0"))?;
+            Ok(ctx)
+        })
+        .unwrap();
+        assert_eq!(pool.idle(), 1);
+
+        for _ in 0..2 {
+            let ctx = pool.checkout().unwrap();
+            assert_eq!(pool.idle(), 0);
+            let count: i32 = ctx.with(|ctx| ctx.eval("++count")).unwrap();
+            assert_eq!(count, 1);
+        }
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn restore() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let snapshot = ctx.with(|ctx| {
+            let mut builder = SnapshotBuilder::new(ctx);
+            builder
+                .eval("init.js", "function next() { return ++count }")
+                .unwrap();
+            builder.eval("data.js", "globalThis.count = 0").unwrap();
+            builder.capture_global("count").unwrap();
+            builder.finish()
+        });
+
+        let pool =
+            ContextPool::new(1, ResetPolicy::Restore(Arc::new(snapshot)), Context::full).unwrap();
+        for _ in 0..2 {
+            let ctx = pool.checkout().unwrap();
+            let count: i32 = ctx.with(|ctx| ctx.eval("next()")).unwrap();
+            assert_eq!(count, 1);
+        }
+        assert!(matches!(pool.policy(), ResetPolicy::Restore(_)));
+    }
+
+    #[test]
+    fn reuse() {
+        let pool = ContextPool::new(1, ResetPolicy::Reuse, |rt| {
+            let ctx = Context::full(rt)?;
+            ctx.with(|ctx| ctx.eval::<(), _>("globalThis.count = 0"))?;
+            Ok(ctx)
+        })
+        .unwrap();
+
+        for i in 1..3 {
+            let ctx = pool.checkout().unwrap();
+            let count: i32 = ctx.with(|ctx| ctx.eval("++count")).unwrap();
+            assert_eq!(count, i);
+        }
+    }
+
+    #[test]
+    fn grow_and_detach() {
+        let pool = ContextPool::new(1, ResetPolicy::NewContext, Context::full).unwrap();
+        let a = pool.checkout().unwrap();
+        let b = pool.checkout().unwrap();
+        let (_rt, ctx) = b.detach();
+        ctx.with(|ctx| ctx.eval::<(), _>("1")).unwrap();
+        drop(a);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn threads() {
+        let pool = ContextPool::new(2, ResetPolicy::NewContext, Context::full).unwrap();
+        let handles = (0..4)
+            .map(|i| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    let ctx = pool.checkout().unwrap();
+                    let res: i32 = ctx.with(|ctx| ctx.eval(format!("{i} * 2"))).unwrap();
+                    assert_eq!(res, i * 2);
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().for_each(|x| x.join().unwrap());
+        assert_eq!(pool.idle(), 2);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{mem, ptr::NonNull};
+
+use crate::{qjs, Context, Ctx, Error, Function, Result, Runtime, Value};
+
+use super::{base::Inner, r#ref::ContextRef};
+
+/// Deep freezes all objects reachable from the given roots.
+///
+/// Freezing doesn't affect the internal slots of objects, so objects which keep their content
+/// in them, like a `Map`, are rejected.
+const FREEZE: &str = r#"
+(roots) => {
+    const method = (name, key) => globalThis[name] && globalThis[name].prototype[key];
+    const getter = (proto, key) => proto && Object.getOwnPropertyDescriptor(proto, key).get;
+    const brands = [
+        ["Map", method("Map", "has")],
+        ["Set", method("Set", "has")],
+        ["WeakMap", method("WeakMap", "has")],
+        ["WeakSet", method("WeakSet", "has")],
+        ["Date", method("Date", "getTime")],
+        ["FinalizationRegistry", method("FinalizationRegistry", "unregister")],
+        ["ArrayBuffer", getter(globalThis.ArrayBuffer && ArrayBuffer.prototype, "byteLength")],
+        ["SharedArrayBuffer", getter(globalThis.SharedArrayBuffer && SharedArrayBuffer.prototype, "byteLength")],
+        ["DataView", getter(globalThis.DataView && DataView.prototype, "byteLength")],
+        ["typed array", getter(globalThis.Uint8Array && Object.getPrototypeOf(Uint8Array.prototype), "length")],
+    ].filter(([, check]) => check);
+    const brand = (value) => {
+        for (const [name, check] of brands) {
+            try {
+                Reflect.apply(check, value, [{}]);
+                return name;
+            } catch (e) {}
+        }
+    };
+    const seen = new Set();
+    const stack = roots.slice();
+    while (stack.length > 0) {
+        const value = stack.pop();
+        if ((typeof value !== "object" && typeof value !== "function") || value === null || seen.has(value)) {
+            continue;
+        }
+        seen.add(value);
+        const name = brand(value);
+        if (name) {
+            throw new TypeError(`A ${name} can't be shared by a realm template as its content can't be frozen`);
+        }
+        Object.freeze(value);
+        stack.push(Object.getPrototypeOf(value));
+        for (const key of Reflect.ownKeys(value)) {
+            const desc = Reflect.getOwnPropertyDescriptor(value, key);
+            stack.push(desc.value, desc.get, desc.set);
+        }
+    }
+}
+"#;
+
+/// A prepared and frozen realm which lightweight contexts can be created from.
+///
+/// The template runs its setup code once and then deep freezes its global object and all its
+/// intrinsics, like `Object.prototype` or `Array`. The contexts created with
+/// [`RealmTemplate::instantiate`] share these intrinsics instead of creating their own, which
+/// makes creating them a lot cheaper, and each get a fresh global object which inherits the
+/// globals of the template. Globals defined by one context are only visible to that context and
+/// as the shared objects are frozen, no context can change what the other contexts see.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, context::RealmTemplate};
+/// let rt = Runtime::new().unwrap();
+/// let template = RealmTemplate::new(Context::full(&rt).unwrap(), |ctx| {
+///     ctx.eval("globalThis.greet = (name) => `Hello, ${name}`")
+/// })
+/// .unwrap();
+///
+/// let tenant = template.instantiate().unwrap();
+/// tenant.with(|ctx| {
+///     assert_eq!(ctx.eval::<String, _>("greet('rquickjs')").unwrap(), "Hello, rquickjs");
+///     assert!(ctx.eval::<(), _>("Array.prototype.map = null").is_err());
+///     ctx.eval::<(), _>("globalThis.secret = 42").unwrap();
+/// });
+///
+/// let other = template.instantiate().unwrap();
+/// other.with(|ctx| assert!(!ctx.globals().contains_key("secret").unwrap()));
+/// ```
+///
+/// Only properties of the global object are inherited, `let`, `const` and `class`
+/// declarations of the setup code stay private to the template. Functions created by the
+/// shared `Function` constructor run in the global scope of the template, which can't be
+/// changed as it is frozen.
+///
+/// Freezing only affects properties, so setting up a template fails if a global keeps its
+/// content in internal slots, like a `Map`, `Set`, `WeakMap`, `WeakSet`, `Date`, `ArrayBuffer`,
+/// `DataView` or typed array, which could otherwise be changed by one context for all the
+/// others. Instances of Rust classes aren't checked, their methods can change the Rust value
+/// shared by all contexts.
+#[derive(Clone)]
+pub struct RealmTemplate {
+    context: Context,
+}
+
+impl RealmTemplate {
+    /// Create a template from a context, running `setup` in it before freezing it.
+    ///
+    /// The context determines the intrinsics available to the contexts created from the
+    /// template, it should not be used for anything else afterwards.
+    pub fn new<F>(context: Context, setup: F) -> Result<Self>
+    where
+        F: for<'js> FnOnce(&Ctx<'js>) -> Result<()>,
+    {
+        context.with(|ctx| {
+            setup(&ctx)?;
+            let roots = unsafe {
+                let roots = qjs::JS_GetIntrinsics(ctx.as_ptr());
+                Value::from_js_value(ctx.clone(), ctx.handle_exception(roots)?)
+            };
+            let freeze: Function = ctx.eval(FREEZE)?;
+            freeze.call::<_, ()>((roots,))
+        })?;
+        Ok(RealmTemplate { context })
+    }
+
+    /// Create a new context sharing the frozen intrinsics and globals of the template.
+    pub fn instantiate(&self) -> Result<Context> {
+        let runtime = self.context.runtime();
+        let guard = runtime.inner.lock();
+        let ctx =
+            NonNull::new(unsafe { qjs::JS_NewContextFromTemplate(self.context.as_raw().as_ptr()) })
+                .ok_or(Error::Allocation)?;
+        unsafe { Context::init_raw(ctx.as_ptr()) }
+        let res = Inner {
+            ctx,
+            rt: runtime.clone(),
+        };
+        mem::drop(guard);
+
+        Ok(Context(ContextRef::new(res)))
+    }
+
+    /// Returns the runtime of the template.
+    pub fn runtime(&self) -> &Runtime {
+        self.context.runtime()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{intrinsic, RealmTemplate},
+        *,
+    };
+
+    fn template(rt: &Runtime) -> RealmTemplate {
+        RealmTemplate::new(Context::full(rt).unwrap(), |ctx| {
+            ctx.eval(
+                r#"
+                    globalThis.config = { names: ["a", "b"] };
+                    globalThis.count = () => config.names.length;
+                    Array.prototype.last = function() { return this[this.length - 1] };
+                "#,
+            )
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn shared_globals() {
+        let rt = Runtime::new().unwrap();
+        let template = template(&rt);
+        let ctx = template.instantiate().unwrap();
+        // The shared objects are kept alive by the instances.
+        drop(template);
+        ctx.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("count() + [1, 2].last()").unwrap(), 4);
+            assert!(ctx.eval::<bool, _>("[] instanceof Array").unwrap());
+            assert_eq!(
+                ctx.eval::<i32, _>("(0, eval)('var local = 3'); local")
+                    .unwrap(),
+                3
+            );
+            assert!(ctx.globals().contains_key("local").unwrap());
+            assert!(ctx
+                .eval::<bool, _>("Object.isFrozen(Object.prototype)")
+                .unwrap());
+        });
+    }
+
+    #[test]
+    fn isolated_tenants() {
+        let rt = Runtime::new().unwrap();
+        let template = template(&rt);
+        let first = template.instantiate().unwrap();
+        first.with(|ctx| {
+            for source in [
+                "config.names.push('c')",
+                "count = null",
+                "Array.prototype.last = null",
+                "Object.getPrototypeOf(function*() {}).next = null",
+                "Object.prototype.polluted = true",
+            ] {
+                assert!(ctx.eval::<(), _>(source).is_err(), "{}", source);
+                ctx.catch();
+            }
+            ctx.eval::<(), _>("globalThis.leak = 1; var other = 2")
+                .unwrap();
+            assert_eq!(ctx.eval::<i32, _>("leak + other").unwrap(), 3);
+        });
+
+        let second = template.instantiate().unwrap();
+        second.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("count()").unwrap(), 2);
+            let globals = ctx.globals();
+            assert!(!globals.contains_key("leak").unwrap());
+            assert!(!globals.contains_key("other").unwrap());
+        });
+    }
+
+    #[test]
+    fn unfreezable() {
+        let rt = Runtime::new().unwrap();
+        for source in [
+            "globalThis.bytes = new Uint8Array(4)",
+            "globalThis.empty = new Uint8Array(new ArrayBuffer(8), 0, 0)",
+            "globalThis.buffer = new ArrayBuffer(8)",
+            "globalThis.view = new DataView(new ArrayBuffer(8))",
+            "globalThis.cache = { entries: new Map() }",
+            "globalThis.names = new Set()",
+            "globalThis.weak = new WeakMap()",
+            "globalThis.start = new Date()",
+        ] {
+            let res = RealmTemplate::new(Context::full(&rt).unwrap(), |ctx| ctx.eval(source));
+            assert!(res.is_err(), "{}", source);
+        }
+        // The checks of the missing intrinsics are skipped.
+        let base = Context::custom::<(intrinsic::Base, intrinsic::Eval, intrinsic::MapSet)>(&rt);
+        RealmTemplate::new(base.unwrap(), |ctx| {
+            ctx.eval("globalThis.value = { a: [1] }")
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+//! Building blocks for interactive shells.
+
+use std::{collections::BTreeSet, ffi::CStr};
+
+use crate::{object::Filter, qjs, Ctx, Error, Exception, Function, Object, Result, Value};
+
+/// The result of checking whether some input is ready to be evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompleteOrPartial {
+    /// The input is a complete script.
+    Complete,
+    /// The input ends before the script is complete, like in an unclosed block or an unfinished
+    /// expression, and more lines should be read.
+    Partial,
+    /// The input has a syntax error which more input can't fix.
+    Invalid,
+}
+
+impl<'js> Ctx<'js> {
+    /// Check whether the source can be evaluated as is, needs more input or is invalid.
+    ///
+    /// The source is compiled as a global script but not run.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, context::CompleteOrPartial};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// assert_eq!(ctx.check_syntax("let a = 1").unwrap(), CompleteOrPartial::Complete);
+    /// assert_eq!(ctx.check_syntax("if (a) {").unwrap(), CompleteOrPartial::Partial);
+    /// assert_eq!(ctx.check_syntax("let a = ;").unwrap(), CompleteOrPartial::Invalid);
+    /// # });
+    /// ```
+    pub fn check_syntax<S: Into<Vec<u8>>>(&self, source: S) -> Result<CompleteOrPartial> {
+        let mut source = source.into();
+        let Some(line) = self.syntax_error_line(source.clone())? else {
+            return Ok(CompleteOrPartial::Complete);
+        };
+        // Errors at the end of the input move with it, errors in the middle of the input don't.
+        source.extend_from_slice(b"\n\n");
+        let extended = self.syntax_error_line(source)?;
+        if extended.map(|x| x != line).unwrap_or(false) {
+            Ok(CompleteOrPartial::Partial)
+        } else {
+            Ok(CompleteOrPartial::Invalid)
+        }
+    }
+
+    /// Compile the source and return the line of the syntax error, if any.
+    fn syntax_error_line(&self, source: Vec<u8>) -> Result<Option<i32>> {
+        const FILE_NAME: &CStr = cstr!("<input>");
+        let flag = qjs::JS_EVAL_TYPE_GLOBAL | qjs::JS_EVAL_FLAG_COMPILE_ONLY;
+        match unsafe { self.eval_raw(source, FILE_NAME, 0, flag as i32) } {
+            Ok(func) => {
+                unsafe { qjs::JS_FreeValue(self.as_ptr(), func) };
+                Ok(None)
+            }
+            Err(Error::Exception) => {
+                let error = self.catch();
+                let syntax_error = error
+                    .as_object()
+                    .and_then(|x| x.get::<_, Option<std::string::String>>("name").ok())
+                    .flatten()
+                    .map(|x| x == "SyntaxError")
+                    .unwrap_or(false);
+                let exception = error.clone().into_object().and_then(Exception::from_object);
+                match exception.and_then(|x| x.line()) {
+                    Some(line) if syntax_error => Ok(Some(line)),
+                    _ => Err(self.throw(error)),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Returns the completions for the property path at the end of the input.
+///
+/// For input like `let x = obj.pre` the properties of `obj`, including inherited ones, which
+/// start with `pre` are returned as the completed input, like `let x = obj.prefix`. Without a
+/// dot the properties of the global object are completed. Only plain paths of identifiers are
+/// evaluated to find the object, so no functions are called except for getters along the
+/// path. Errors while looking up the properties result in no completions.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, context::complete};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// ctx.eval::<(), _>("globalThis.config = { verbose: true, version: 2 }").unwrap();
+/// assert_eq!(complete(&ctx, "config.ver"), ["config.verbose", "config.version"]);
+/// assert_eq!(complete(&ctx, "Math.PI.toFi"), ["Math.PI.toFixed"]);
+/// # });
+/// ```
+pub fn complete<'js>(ctx: &Ctx<'js>, input: &str) -> Vec<std::string::String> {
+    let start = input
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| *c == '.' || is_identifier_char(*c))
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(input.len());
+    let path = &input[start..];
+    let (base, prefix) = match path.rfind('.') {
+        Some(idx) => (Some(&path[..idx]), &path[idx + 1..]),
+        None => (None, path),
+    };
+    if let Some(base) = base {
+        let valid = base.split('.').all(|x| {
+            x.chars()
+                .next()
+                .map(|c| !c.is_ascii_digit())
+                .unwrap_or(false)
+        });
+        if !valid {
+            return Vec::new();
+        }
+    }
+    match property_names(ctx, base, prefix) {
+        Ok(names) => {
+            let head = &input[..input.len() - prefix.len()];
+            names
+                .into_iter()
+                .map(|x| format!("{}{}", head, x))
+                .collect()
+        }
+        Err(Error::Exception) => {
+            ctx.catch();
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c == '_' || c == '$' || c.is_alphanumeric()
+}
+
+fn property_names<'js>(
+    ctx: &Ctx<'js>,
+    base: Option<&str>,
+    prefix: &str,
+) -> Result<BTreeSet<std::string::String>> {
+    let value = match base {
+        Some(base) => ctx.eval::<Value, _>(base)?,
+        None => ctx.globals().into_value(),
+    };
+    let mut names = BTreeSet::new();
+    if value.is_null() || value.is_undefined() {
+        return Ok(names);
+    }
+    let mut object = match value.as_object() {
+        Some(x) => Some(x.clone()),
+        None => {
+            let wrap: Function = ctx.globals().get("Object")?;
+            Some(wrap.call::<_, Object>((value,))?)
+        }
+    };
+    while let Some(current) = object {
+        for key in current.own_keys::<std::string::String>(Filter::new().string()) {
+            let key = key?;
+            let identifier = key
+                .chars()
+                .next()
+                .map(|c| !c.is_ascii_digit())
+                .unwrap_or(false)
+                && key.chars().all(is_identifier_char);
+            if identifier && key.starts_with(prefix) {
+                names.insert(key);
+            }
+        }
+        object = current.get_prototype();
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        context::{complete, CompleteOrPartial},
+        test_with,
+    };
+
+    #[test]
+    fn check_syntax() {
+        test_with(|ctx| {
+            let check = |source: &str| ctx.check_syntax(source).unwrap();
+            assert_eq!(check("1 + 2"), CompleteOrPartial::Complete);
+            assert_eq!(
+                check("function f() {\n  return 1;\n}"),
+                CompleteOrPartial::Complete
+            );
+            assert_eq!(check(""), CompleteOrPartial::Complete);
+            assert_eq!(
+                check("function f() {\n  return 1;"),
+                CompleteOrPartial::Partial
+            );
+            assert_eq!(check("foo(1,"), CompleteOrPartial::Partial);
+            assert_eq!(check("[1, 2"), CompleteOrPartial::Partial);
+            assert_eq!(check("`template\n"), CompleteOrPartial::Partial);
+            assert_eq!(check("/* comment"), CompleteOrPartial::Partial);
+            assert_eq!(check("let x = 1 +"), CompleteOrPartial::Partial);
+            assert_eq!(check("let x = ;"), CompleteOrPartial::Invalid);
+            assert_eq!(check("1 +\n)"), CompleteOrPartial::Invalid);
+            assert_eq!(check("'unterminated"), CompleteOrPartial::Invalid);
+            // Nothing was run or declared.
+            assert!(ctx.eval::<bool, _>("typeof f === 'undefined'").unwrap());
+        })
+    }
+
+    #[test]
+    fn completion() {
+        test_with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                class Base { baseMethod() {} }
+                globalThis.item = Object.assign(new Base(), { bar: 1, baz: { deep: 2 }, 0: 'x' });
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                complete(&ctx, "item.ba"),
+                ["item.bar", "item.baseMethod", "item.baz"]
+            );
+            assert_eq!(
+                complete(&ctx, "let x = item.baz.d"),
+                ["let x = item.baz.deep"]
+            );
+            assert!(complete(&ctx, "item.").contains(&"item.hasOwnProperty".to_string()));
+            assert_eq!(complete(&ctx, "ite"), ["item"]);
+            assert!(complete(&ctx, "'abc'.length.toFix").is_empty());
+            assert!(complete(&ctx, "missing.fo").is_empty());
+            assert!(complete(&ctx, "1.5").is_empty());
+            assert!(complete(&ctx, "item.baz.deep.nothing.x").is_empty());
+        })
+    }
+}
@@ -0,0 +1,277 @@
+use std::{ffi::CString, io, mem::MaybeUninit, slice};
+
+use crate::{qjs, Ctx, Error, Result, StdString, Value};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    /// Bytecode of a script which is run when restoring.
+    Script(Vec<u8>),
+    /// A serialized global value which is set when restoring.
+    Global(StdString, Vec<u8>),
+}
+
+const MAGIC: &[u8; 8] = b"RQJSSNAP";
+
+/// The initialized state of a context which can quickly be restored into new contexts.
+///
+/// A snapshot is created with a [`SnapshotBuilder`] which records the compiled bytecode of the
+/// bootstrap scripts and the values of selected globals. Restoring the snapshot runs the
+/// bytecode without parsing the scripts again and sets the captured globals, which is
+/// considerably faster than evaluating the original sources.
+///
+/// Only plain data, like numbers, strings, arrays and objects can be captured as a global,
+/// functions have to be defined by a bootstrap script.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, context::SnapshotBuilder};
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+/// let snapshot = ctx.with(|ctx| {
+///     let mut builder = SnapshotBuilder::new(ctx);
+///     builder.eval("init.js", "function greet(name) { return `${prefix} ${name}` }").unwrap();
+///     builder.eval("data.js", "globalThis.prefix = ['Hello', 'world'].join(', ')").unwrap();
+///     builder.capture_global("prefix").unwrap();
+///     builder.finish()
+/// });
+///
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     snapshot.restore(&ctx).unwrap();
+///     let res: String = ctx.eval("greet('rquickjs')").unwrap();
+///     assert_eq!(res, "Hello, world rquickjs");
+/// });
+/// ```
+///
+/// The serialized form returned by [`Snapshot::to_bytes`] contains QuickJS bytecode, it can
+/// only be restored by the same version of QuickJS on a target with the same endianness, and
+/// must be stored where it can't be tampered with, see [`Snapshot::from_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Snapshot {
+    steps: Vec<Step>,
+}
+
+impl Snapshot {
+    /// Restore the snapshot into a context.
+    pub fn restore(&self, ctx: &Ctx) -> Result<()> {
+        let globals = ctx.globals();
+        for step in &self.steps {
+            match step {
+                Step::Script(bytes) => unsafe {
+                    let func = read_object(ctx, bytes, qjs::JS_READ_OBJ_BYTECODE as _)?;
+                    let res = ctx.handle_exception(qjs::JS_EvalFunction(ctx.as_ptr(), func))?;
+                    qjs::JS_FreeValue(ctx.as_ptr(), res);
+                },
+                Step::Global(name, bytes) => {
+                    let value = unsafe {
+                        let value = read_object(ctx, bytes, 0)?;
+                        Value::from_js_value(ctx.clone(), value)
+                    };
+                    globals.set(name.as_str(), value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Serialize the snapshot.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn push_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+            buf.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            buf.extend_from_slice(bytes);
+        }
+
+        let mut buf = MAGIC.to_vec();
+        for step in &self.steps {
+            match step {
+                Step::Script(bytes) => {
+                    buf.push(0);
+                    push_bytes(&mut buf, bytes);
+                }
+                Step::Global(name, bytes) => {
+                    buf.push(1);
+                    push_bytes(&mut buf, name.as_bytes());
+                    push_bytes(&mut buf, bytes);
+                }
+            }
+        }
+        buf
+    }
+
+    /// Deserialize a snapshot created with [`Snapshot::to_bytes`].
+    ///
+    /// Only the framing of the steps is validated here, the bytecode is read when the snapshot
+    /// is restored.
+    ///
+    /// # Safety
+    /// QuickJS doesn't validate bytecode, so the bytes must be a snapshot serialized by the
+    /// same version of QuickJS, which hasn't been modified since. Restoring a snapshot from
+    /// crafted bytes is undefined behavior.
+    pub unsafe fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        fn invalid() -> Error {
+            Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid snapshot data",
+            ))
+        }
+
+        fn take_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+            if bytes.len() < 4 {
+                return Err(invalid());
+            }
+            let (len, rest) = bytes.split_at(4);
+            let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+            if rest.len() < len {
+                return Err(invalid());
+            }
+            let (res, rest) = rest.split_at(len);
+            *bytes = rest;
+            Ok(res)
+        }
+
+        let mut bytes = bytes.strip_prefix(MAGIC).ok_or_else(invalid)?;
+        let mut steps = Vec::new();
+        while let Some((&tag, rest)) = bytes.split_first() {
+            bytes = rest;
+            let step = match tag {
+                0 => Step::Script(take_bytes(&mut bytes)?.to_vec()),
+                1 => {
+                    let name = StdString::from_utf8(take_bytes(&mut bytes)?.to_vec())
+                        .map_err(|e| Error::Utf8(e.utf8_error()))?;
+                    Step::Global(name, take_bytes(&mut bytes)?.to_vec())
+                }
+                _ => return Err(invalid()),
+            };
+            steps.push(step);
+        }
+        Ok(Snapshot { steps })
+    }
+}
+
+/// A builder for recording a [`Snapshot`] while initializing a context.
+///
+/// Every operation is applied to the context directly and recorded, restoring the snapshot will
+/// replay the operations in the same order.
+pub struct SnapshotBuilder<'js> {
+    ctx: Ctx<'js>,
+    snapshot: Snapshot,
+}
+
+impl<'js> SnapshotBuilder<'js> {
+    /// Create a new builder which initializes the given context.
+    pub fn new(ctx: Ctx<'js>) -> Self {
+        SnapshotBuilder {
+            ctx,
+            snapshot: Snapshot::default(),
+        }
+    }
+
+    /// Evaluate a bootstrap script in global scope and record its bytecode.
+    pub fn eval<N, S>(&mut self, name: N, source: S) -> Result<()>
+    where
+        N: Into<Vec<u8>>,
+        S: Into<Vec<u8>>,
+    {
+        let ctx = &self.ctx;
+        let name = CString::new(name)?;
+        let flag =
+            qjs::JS_EVAL_TYPE_GLOBAL | qjs::JS_EVAL_FLAG_STRICT | qjs::JS_EVAL_FLAG_COMPILE_ONLY;
+        unsafe {
+            let func = ctx.eval_raw(source, name.as_c_str(), 0, flag as i32)?;
+            let bytes = write_object(ctx, func, qjs::JS_WRITE_OBJ_BYTECODE as _);
+            // JS_EvalFunction frees the function.
+            let res = ctx.handle_exception(qjs::JS_EvalFunction(ctx.as_ptr(), func))?;
+            qjs::JS_FreeValue(ctx.as_ptr(), res);
+            self.snapshot.steps.push(Step::Script(bytes?));
+        }
+        Ok(())
+    }
+
+    /// Capture the current value of a global.
+    ///
+    /// Returns an exception if the value is not plain data.
+    pub fn capture_global(&mut self, name: &str) -> Result<()> {
+        let ctx = &self.ctx;
+        let value: Value = ctx.globals().get(name)?;
+        let bytes = unsafe { write_object(ctx, value.as_js_value(), 0)? };
+        self.snapshot
+            .steps
+            .push(Step::Global(name.to_string(), bytes));
+        Ok(())
+    }
+
+    /// Returns the recorded snapshot.
+    pub fn finish(self) -> Snapshot {
+        self.snapshot
+    }
+}
+
+/// Serialize a value or bytecode with `JS_WriteObject`.
+pub(crate) unsafe fn write_object(ctx: &Ctx, value: qjs::JSValue, flags: i32) -> Result<Vec<u8>> {
+    let mut len = MaybeUninit::uninit();
+    let buf = qjs::JS_WriteObject(ctx.as_ptr(), len.as_mut_ptr(), value, flags);
+    if buf.is_null() {
+        return Err(ctx.raise_exception());
+    }
+    let bytes = slice::from_raw_parts(buf, len.assume_init() as _).to_vec();
+    qjs::js_free(ctx.as_ptr(), buf as _);
+    Ok(bytes)
+}
+
+/// Deserialize a value or bytecode with `JS_ReadObject`.
+pub(crate) unsafe fn read_object(ctx: &Ctx, bytes: &[u8], flags: i32) -> Result<qjs::JSValue> {
+    let value = qjs::JS_ReadObject(ctx.as_ptr(), bytes.as_ptr(), bytes.len() as _, flags);
+    ctx.handle_exception(value)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{context::SnapshotBuilder, *};
+
+    fn bootstrap() -> context::Snapshot {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let mut builder = SnapshotBuilder::new(ctx.clone());
+            builder
+                .eval(
+                    "init.js",
+                    "function add(a, b) { return a + b + config.offset }",
+                )
+                .unwrap();
+            builder
+                .eval(
+                    "config.js",
+                    "globalThis.config = { offset: 10, names: ['a', 'b'] }",
+                )
+                .unwrap();
+            builder.capture_global("config").unwrap();
+            assert!(builder.capture_global("add").is_err());
+            builder.finish()
+        })
+    }
+
+    #[test]
+    fn restore() {
+        let snapshot = bootstrap();
+        let bytes = snapshot.to_bytes();
+        let snapshot = unsafe { context::Snapshot::from_bytes(&bytes) }.unwrap();
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            snapshot.restore(&ctx).unwrap();
+            let res: i32 = ctx.eval("add(1, 2)").unwrap();
+            assert_eq!(res, 13);
+            let res: StdString = ctx.eval("config.names.join()").unwrap();
+            assert_eq!(res, "a,b");
+        })
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        assert!(unsafe { context::Snapshot::from_bytes(b"foo") }.is_err());
+        let mut bytes = bootstrap().to_bytes();
+        bytes.pop();
+        assert!(unsafe { context::Snapshot::from_bytes(&bytes) }.is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{qjs, Ctx};
+
+/// Execution statistics of a single context, returned by [`Ctx::stats`].
+///
+/// The counters are kept by the engine itself and are always collected, they count from the
+/// creation of the context or the last call to [`Ctx::reset_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContextStats {
+    /// The number of scripts, modules and bytecode functions evaluated from Rust.
+    pub evaluations: u64,
+    /// The number of pending jobs, like promise reactions, executed in the context.
+    pub jobs: u64,
+    /// The number of exceptions thrown in the context, including rethrown ones.
+    pub exceptions: u64,
+    /// The number of interrupt polls, done for every function call and taken jump.
+    pub interrupt_checks: u64,
+    /// The highest memory usage of the runtime seen while running code in this context.
+    ///
+    /// Memory is sampled at interrupt polls and after evaluations and jobs, and is only known
+    /// when using the default allocator.
+    pub peak_memory: usize,
+}
+
+impl From<qjs::JSContextStats> for ContextStats {
+    fn from(stats: qjs::JSContextStats) -> Self {
+        ContextStats {
+            evaluations: stats.evaluations as u64,
+            jobs: stats.jobs as u64,
+            exceptions: stats.exceptions as u64,
+            interrupt_checks: stats.interrupt_checks as u64,
+            peak_memory: stats.peak_memory as usize,
+        }
+    }
+}
+
+impl<'js> Ctx<'js> {
+    /// Returns the execution statistics of this context.
+    pub fn stats(&self) -> ContextStats {
+        let mut stats = std::mem::MaybeUninit::uninit();
+        unsafe {
+            qjs::JS_GetContextStats(self.as_ptr(), stats.as_mut_ptr());
+            stats.assume_init().into()
+        }
+    }
+
+    /// Reset the execution statistics of this context to zero.
+    pub fn reset_stats(&self) {
+        unsafe { qjs::JS_ResetContextStats(self.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{context::ContextStats, *};
+
+    #[test]
+    fn stats() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let other = Context::full(&rt).unwrap();
+
+        ctx.with(|ctx| {
+            ctx.reset_stats();
+            ctx.eval::<(), _>("for (var i = 0; i < 100; i++) {}")
+                .unwrap();
+            let _ = ctx.eval::<(), _>("try { throw 1 } catch (e) {} throw 2");
+            ctx.eval::<(), _>("Promise.resolve().then(() => {})")
+                .unwrap();
+
+            let stats = ctx.stats();
+            assert_eq!(stats.evaluations, 3);
+            assert_eq!(stats.exceptions, 2);
+            assert!(stats.interrupt_checks >= 100, "{:?}", stats);
+            #[cfg(not(feature = "rust-alloc"))]
+            assert!(stats.peak_memory > 0);
+        });
+        rt.execute_pending_job().unwrap();
+
+        ctx.with(|ctx| assert_eq!(ctx.stats().jobs, 1));
+        other.with(|ctx| {
+            let stats = ctx.stats();
+            assert_eq!((stats.evaluations, stats.jobs, stats.exceptions), (0, 0, 0));
+        });
+
+        ctx.with(|ctx| {
+            ctx.reset_stats();
+            let stats = ctx.stats();
+            assert_eq!(
+                stats,
+                ContextStats {
+                    peak_memory: stats.peak_memory,
+                    ..ContextStats::default()
+                }
+            );
+        });
+    }
+}
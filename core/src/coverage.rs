@@ -0,0 +1,478 @@
+//! Code coverage collection for evaluated scripts.
+//!
+//! When coverage is enabled with [`Runtime::enable_coverage`], every script and module evaluated
+//! afterwards is instrumented with counters for its functions and statements before it is
+//! compiled. The collected coverage can then be retrieved with [`Runtime::coverage`] and exported
+//! in the lcov or Istanbul JSON formats.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, context::EvalOptions};
+//! let rt = Runtime::new().unwrap();
+//! rt.enable_coverage();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let options = EvalOptions::default().filename("test.js");
+//!     let _: () = ctx.eval_with_options("function add(a, b) { return a + b }", options).unwrap();
+//! });
+//! let coverage = rt.coverage().unwrap();
+//! assert_eq!(coverage.files[0].functions[0].hits, 0);
+//! assert!(coverage.to_lcov().contains("FNDA:0,add"));
+//! ```
+//!
+//! The counters are calls to a global function inserted into the source, columns reported in
+//! error stacks of instrumented code are shifted by them.
+//!
+//! [`Runtime::enable_coverage`]: crate::Runtime::enable_coverage
+//! [`Runtime::coverage`]: crate::Runtime::coverage
+
+use std::{collections::BTreeMap, ffi::CStr, fmt::Write, os::raw::c_int, slice};
+
+use crate::{qjs, runtime::raw::Opaque, Ctx, StdString, Value};
+
+mod instrument;
+use instrument::{Point, PointKind};
+
+/// The name of the global function called by the counters.
+const COUNTER: &str = "__rquickjs_cov";
+
+/// A position in a source, lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// The coverage of a single function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    /// The name of the function, anonymous functions are named `(anonymous_N)`.
+    pub name: StdString,
+    pub start: Position,
+    pub end: Position,
+    /// The number of times the function was called.
+    pub hits: u64,
+}
+
+/// The coverage of a single statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementCoverage {
+    pub start: Position,
+    pub end: Position,
+    /// The number of times the statement was executed.
+    pub hits: u64,
+}
+
+/// The coverage of a single evaluated source.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FileCoverage {
+    /// The file name the source was evaluated with.
+    pub name: StdString,
+    pub functions: Vec<FunctionCoverage>,
+    pub statements: Vec<StatementCoverage>,
+}
+
+impl FileCoverage {
+    /// Returns the lines containing the start of a statement and their number of executions.
+    pub fn lines(&self) -> Vec<(u32, u64)> {
+        let mut lines = BTreeMap::new();
+        for statement in &self.statements {
+            let hits = lines.entry(statement.start.line).or_insert(0);
+            *hits = statement.hits.max(*hits);
+        }
+        lines.into_iter().collect()
+    }
+}
+
+/// The coverage collected by a runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Coverage {
+    pub files: Vec<FileCoverage>,
+}
+
+impl Coverage {
+    /// Export the coverage in the lcov tracefile format.
+    pub fn to_lcov(&self) -> StdString {
+        let mut res = StdString::new();
+        for file in &self.files {
+            writeln!(res, "TN:\nSF:{}", file.name).unwrap();
+            for function in &file.functions {
+                writeln!(res, "FN:{},{}", function.start.line, function.name).unwrap();
+            }
+            for function in &file.functions {
+                writeln!(res, "FNDA:{},{}", function.hits, function.name).unwrap();
+            }
+            let hit = file.functions.iter().filter(|x| x.hits > 0).count();
+            writeln!(res, "FNF:{}\nFNH:{}", file.functions.len(), hit).unwrap();
+            let lines = file.lines();
+            for (line, hits) in &lines {
+                writeln!(res, "DA:{line},{hits}").unwrap();
+            }
+            let hit = lines.iter().filter(|x| x.1 > 0).count();
+            writeln!(res, "LF:{}\nLH:{}\nend_of_record", lines.len(), hit).unwrap();
+        }
+        res
+    }
+
+    /// Export the coverage in the Istanbul JSON format used by `nyc` and `c8`.
+    pub fn to_istanbul(&self) -> StdString {
+        fn location(res: &mut StdString, start: Position, end: Position) {
+            // Istanbul columns start at 0.
+            write!(
+                res,
+                r#"{{"start":{{"line":{},"column":{}}},"end":{{"line":{},"column":{}}}}}"#,
+                start.line,
+                start.column.saturating_sub(1),
+                end.line,
+                end.column.saturating_sub(1)
+            )
+            .unwrap();
+        }
+
+        fn counts<'a>(res: &mut StdString, hits: impl Iterator<Item = &'a u64>) {
+            res.push('{');
+            for (idx, hits) in hits.enumerate() {
+                if idx != 0 {
+                    res.push(',');
+                }
+                write!(res, r#""{idx}":{hits}"#).unwrap();
+            }
+            res.push('}');
+        }
+
+        let mut res = StdString::from("{");
+        for (idx, file) in self.files.iter().enumerate() {
+            if idx != 0 {
+                res.push(',');
+            }
+            json_string(&mut res, &file.name);
+            res.push_str(r#":{"path":"#);
+            json_string(&mut res, &file.name);
+            res.push_str(r#","statementMap":{"#);
+            for (idx, statement) in file.statements.iter().enumerate() {
+                if idx != 0 {
+                    res.push(',');
+                }
+                write!(res, r#""{idx}":"#).unwrap();
+                location(&mut res, statement.start, statement.end);
+            }
+            res.push_str(r#"},"fnMap":{"#);
+            for (idx, function) in file.functions.iter().enumerate() {
+                if idx != 0 {
+                    res.push(',');
+                }
+                write!(res, r#""{idx}":{{"name":"#).unwrap();
+                json_string(&mut res, &function.name);
+                res.push_str(r#","decl":"#);
+                location(&mut res, function.start, function.start);
+                res.push_str(r#","loc":"#);
+                location(&mut res, function.start, function.end);
+                write!(res, r#","line":{}}}"#, function.start.line).unwrap();
+            }
+            res.push_str(r#"},"branchMap":{},"s":"#);
+            counts(&mut res, file.statements.iter().map(|x| &x.hits));
+            res.push_str(r#","f":"#);
+            counts(&mut res, file.functions.iter().map(|x| &x.hits));
+            res.push_str(r#","b":{}}"#);
+        }
+        res.push('}');
+        res
+    }
+}
+
+fn json_string(res: &mut StdString, value: &str) {
+    res.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+}
+
+/// The counter a point of an instrumented source increments.
+#[derive(Debug, Clone, Copy)]
+enum Counter {
+    Function(usize),
+    Statement(usize),
+}
+
+/// The coverage data of a runtime.
+#[derive(Default)]
+pub(crate) struct Collector {
+    files: Vec<(FileCoverage, Vec<Counter>)>,
+}
+
+impl Collector {
+    pub fn coverage(&self) -> Coverage {
+        Coverage {
+            files: self.files.iter().map(|(file, _)| file.clone()).collect(),
+        }
+    }
+
+    fn hit(&mut self, file: usize, point: usize) {
+        let Some((file, counters)) = self.files.get_mut(file) else {
+            return;
+        };
+        match counters.get(point) {
+            Some(&Counter::Function(idx)) => file.functions[idx].hits += 1,
+            Some(&Counter::Statement(idx)) => file.statements[idx].hits += 1,
+            None => {}
+        }
+    }
+
+    fn add(&mut self, name: StdString, points: Vec<Point>) {
+        let mut file = FileCoverage {
+            name,
+            ..Default::default()
+        };
+        let counters = points
+            .into_iter()
+            .map(|point| match point.kind {
+                PointKind::Function(name) => {
+                    file.functions.push(FunctionCoverage {
+                        name,
+                        start: point.start,
+                        end: point.end,
+                        hits: 0,
+                    });
+                    Counter::Function(file.functions.len() - 1)
+                }
+                PointKind::Statement => {
+                    file.statements.push(StatementCoverage {
+                        start: point.start,
+                        end: point.end,
+                        hits: 0,
+                    });
+                    Counter::Statement(file.statements.len() - 1)
+                }
+            })
+            .collect();
+        self.files.push((file, counters));
+    }
+
+    /// Returns the index of an already instrumented file with the same name and counters.
+    fn find(&self, name: &str, points: &[Point]) -> Option<usize> {
+        self.files.iter().position(|(file, counters)| {
+            file.name == name
+                && counters.len() == points.len()
+                && counters.iter().zip(points).all(|(counter, point)| {
+                    let (start, end) = match *counter {
+                        Counter::Function(idx) => {
+                            (file.functions[idx].start, file.functions[idx].end)
+                        }
+                        Counter::Statement(idx) => {
+                            (file.statements[idx].start, file.statements[idx].end)
+                        }
+                    };
+                    start == point.start && end == point.end
+                })
+        })
+    }
+}
+
+unsafe extern "C" fn hit(
+    ctx: *mut qjs::JSContext,
+    _this: qjs::JSValue,
+    argc: c_int,
+    argv: *mut qjs::JSValue,
+) -> qjs::JSValue {
+    if argc >= 2 {
+        let args = slice::from_raw_parts(argv, 2);
+        let opaque = qjs::JS_GetRuntimeOpaque(qjs::JS_GetRuntime(ctx)).cast::<Opaque>();
+        if let Some(collector) = (*opaque).coverage.as_mut() {
+            if args
+                .iter()
+                .all(|&x| qjs::JS_VALUE_GET_TAG(x) == qjs::JS_TAG_INT)
+            {
+                let file = qjs::JS_VALUE_GET_INT(args[0]) as usize;
+                let point = qjs::JS_VALUE_GET_INT(args[1]) as usize;
+                collector.hit(file, point);
+            }
+        }
+    }
+    qjs::JS_UNDEFINED
+}
+
+impl<'js> Ctx<'js> {
+    /// Instrument a source for coverage if coverage is enabled.
+    ///
+    /// Sources which can't be instrumented are returned unchanged.
+    pub(crate) fn instrument_source(
+        &self,
+        file_name: &CStr,
+        source: Vec<u8>,
+        line_offset: u32,
+    ) -> Vec<u8> {
+        let collector = unsafe { &mut (*self.get_opaque()).coverage };
+        let Some(collector) = collector.as_mut() else {
+            return source;
+        };
+        let globals = self.globals();
+        if !globals.contains_key(COUNTER).unwrap_or(false) {
+            let name = cstr!("__rquickjs_cov");
+            let func = unsafe {
+                let func = qjs::JS_NewCFunction2(
+                    self.as_ptr(),
+                    Some(hit),
+                    name.as_ptr(),
+                    2,
+                    qjs::JSCFunctionEnum_JS_CFUNC_generic,
+                    0,
+                );
+                match self.handle_exception(func) {
+                    Ok(func) => Value::from_js_value(self.clone(), func),
+                    Err(_) => return source,
+                }
+            };
+            if globals.set(COUNTER, func).is_err() {
+                return source;
+            }
+        }
+
+        let name = file_name.to_string_lossy();
+        let Some(mut res) = instrument::instrument(&source, collector.files.len()) else {
+            return source;
+        };
+        for point in &mut res.points {
+            point.start.line += line_offset;
+            point.end.line += line_offset;
+        }
+        // Evaluating the same source again accumulates into the same counters.
+        if let Some(file) = collector.find(&name, &res.points) {
+            res = instrument::instrument(&source, file).unwrap();
+        } else {
+            collector.add(name.into_owned(), res.points);
+        }
+        res.source
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{context::EvalOptions, CatchResultExt, Context, Runtime, StdString};
+
+    fn options(name: &str) -> EvalOptions {
+        EvalOptions::default().filename(name)
+    }
+
+    #[test]
+    fn functions_and_lines() {
+        let rt = Runtime::new().unwrap();
+        rt.enable_coverage();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let source = r#"
+function used(a) {
+    if (a) {
+        return 1;
+    }
+    return 2;
+}
+function unused() {
+    return 3;
+}
+used(false);
+used(false);
+"#;
+            let _: () = ctx.eval_with_options(source, options("test.js")).unwrap();
+        });
+        let coverage = rt.coverage().unwrap();
+        let file = &coverage.files[0];
+        assert_eq!(file.name, "test.js");
+        let functions: Vec<_> = file
+            .functions
+            .iter()
+            .map(|x| (x.name.as_str(), x.start.line, x.hits))
+            .collect();
+        assert_eq!(functions, [("used", 2, 2), ("unused", 8, 0)]);
+        assert_eq!(
+            file.lines(),
+            [
+                (2, 1),
+                (3, 2),
+                (4, 0),
+                (6, 2),
+                (8, 1),
+                (9, 0),
+                (11, 1),
+                (12, 1)
+            ]
+        );
+
+        let lcov = coverage.to_lcov();
+        assert!(lcov.contains("SF:test.js\n"));
+        assert!(lcov.contains("FNDA:2,used\nFNDA:0,unused\nFNF:2\nFNH:1\n"));
+        assert!(lcov.contains("DA:4,0\n"));
+        assert!(lcov.ends_with("LF:8\nLH:6\nend_of_record\n"));
+
+        ctx.with(|ctx| {
+            let json = ctx.json_parse(coverage.to_istanbul()).unwrap();
+            let json: crate::Object = json.get().unwrap();
+            let file: crate::Object = json.get("test.js").unwrap();
+            let name: StdString = file
+                .get::<_, crate::Object>("fnMap")
+                .unwrap()
+                .get::<_, crate::Object>("1")
+                .unwrap()
+                .get("name")
+                .unwrap();
+            assert_eq!(name, "unused");
+            let hits: u32 = file.get::<_, crate::Object>("f").unwrap().get("0").unwrap();
+            assert_eq!(hits, 2);
+        });
+    }
+
+    #[test]
+    fn preserves_semantics() {
+        let sources = [
+            "'use strict'\nfunction f() { 'use strict'; return this === undefined }\nf()",
+            "function f() { 'use strict'\n return this === undefined }; f()",
+            "var a = { b() { return 1 }, get c() { return 2 }, ['d']() { return 3 } }; a.b() + a.c + a.d()",
+            "class A { constructor(x) { this.x = x } get y() { return this.x * 2 } static z() { return 1 } }; new A(2).y + A.z()",
+            "let x = 0; for (let i = 0; i < 3; i++) { x += i } do { x++ } while (x < 10); x",
+            "let r = 1 / 2 / 1; let s = /a[/]b/g.test('a/b'); `${r} ${s} ${`${1 + { a: 2 }.a}`}`",
+            "let x = 1; switch (x) { case 1: { x = 5; break } default: x = 2 }; x",
+            "let f = (a) => { return a * 2 }, g = a => a + 1; label: for (const i of [1]) { if (i) { continue label } else { f(1) } } f(g(1))",
+            "let x; try { throw 1 } catch (e) { x = e } finally { x += 1 } if (x) x++; else x--; x",
+            "var o = { if: 1, function: 2 }; o.if + o.function",
+            "(function() { return typeof function() {} })()",
+            "let a = [1, 2, 3].map(function (x) { return x * 2 }).reduce((a, b) => a + b); a",
+            "let s = 'a}b', n = 0; if (s) /}/.test(s) && n++; while (n < 2) /'/.test(\"'\") && n++; n",
+            "let n = 0; { n = 1 } /}/.test('}') && n++; function f() {}\n/'/.test(\"'\") && n++; n",
+            "let q = { valueOf() { return 8 } } / 2 / 1; let r = (q) / 2 / 1; `${q} ${r}`",
+        ];
+        fn eval(rt: &Runtime, idx: usize, source: &str) -> StdString {
+            // Every source gets a new context as they declare the same globals.
+            let ctx = Context::full(rt).unwrap();
+            ctx.with(|ctx| {
+                ctx.eval_with_options::<crate::convert::Coerced<StdString>, _>(
+                    source,
+                    options(&format!("{idx}.js")),
+                )
+                .catch(&ctx)
+                .unwrap_or_else(|e| panic!("{source}: {e}"))
+                .0
+            })
+        }
+
+        let rt = Runtime::new().unwrap();
+        let expected: Vec<_> = sources
+            .iter()
+            .enumerate()
+            .map(|(idx, source)| eval(&rt, idx, source))
+            .collect();
+        rt.enable_coverage();
+        for (idx, (source, expected)) in sources.iter().zip(&expected).enumerate() {
+            assert_eq!(&eval(&rt, idx, source), expected, "{source}");
+        }
+        let coverage = rt.coverage().unwrap();
+        assert_eq!(coverage.files.len(), sources.len());
+        assert!(coverage
+            .files
+            .iter()
+            .all(|x| x.statements.iter().any(|x| x.hits > 0)));
+    }
+}
@@ -0,0 +1,714 @@
+//! A small JavaScript tokenizer which inserts coverage counters into sources.
+//!
+//! The instrumentation does not need a full parser, it only tracks enough of the structure of the
+//! source to find function bodies and statement boundaries. Counters are only inserted where it
+//! is certain that a statement can start, sources which can't be tokenized are left alone.
+
+use std::fmt::Write;
+
+use super::{Position, COUNTER};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PointKind {
+    Function(String),
+    Statement,
+}
+
+/// A location in the source where a counter was inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Point {
+    pub kind: PointKind,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// An instrumented source and the locations of its counters.
+pub(crate) struct Instrumented {
+    pub source: Vec<u8>,
+    pub points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Template {
+    Full,
+    Head,
+    Middle,
+    Tail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Ident,
+    Number,
+    String,
+    Template(Template),
+    Regex,
+    Punct,
+}
+
+/// What an open brace belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Brace {
+    /// A substitution of a template.
+    Template,
+    /// A block of statements or the body of a function or class.
+    Block,
+    /// An object literal or pattern.
+    Object,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: Kind,
+    start: usize,
+    end: usize,
+    start_pos: Position,
+    end_pos: Position,
+    newline_before: bool,
+}
+
+/// Keywords after which a `/` starts a regular expression instead of a division.
+const REGEX_KEYWORDS: &[&[u8]] = &[
+    b"return",
+    b"typeof",
+    b"instanceof",
+    b"in",
+    b"of",
+    b"new",
+    b"delete",
+    b"void",
+    b"throw",
+    b"case",
+    b"do",
+    b"else",
+    b"yield",
+    b"await",
+];
+
+fn is_ident_start(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || matches!(byte, b'_' | b'$' | b'#' | b'\\') || byte >= 0x80
+}
+
+fn is_ident_continue(byte: u8) -> bool {
+    is_ident_start(byte) || byte.is_ascii_digit()
+}
+
+fn skip_string(src: &[u8], mut idx: usize) -> Option<usize> {
+    let quote = src[idx];
+    idx += 1;
+    loop {
+        match *src.get(idx)? {
+            b'\\' => idx += 2,
+            b'\n' => return None,
+            x if x == quote => return Some(idx + 1),
+            _ => idx += 1,
+        }
+    }
+}
+
+/// Skip the content of a template, returns whether it ended with a substitution.
+fn skip_template(src: &[u8], mut idx: usize) -> Option<(usize, bool)> {
+    loop {
+        match *src.get(idx)? {
+            b'\\' => idx += 2,
+            b'`' => return Some((idx + 1, false)),
+            b'$' if src.get(idx + 1) == Some(&b'{') => return Some((idx + 2, true)),
+            _ => idx += 1,
+        }
+    }
+}
+
+fn skip_regex(src: &[u8], mut idx: usize) -> Option<usize> {
+    let mut class = false;
+    idx += 1;
+    loop {
+        match *src.get(idx)? {
+            b'\\' => idx += 2,
+            b'\n' => return None,
+            b'[' => {
+                class = true;
+                idx += 1
+            }
+            b']' => {
+                class = false;
+                idx += 1
+            }
+            b'/' if !class => break,
+            _ => idx += 1,
+        }
+    }
+    idx += 1;
+    while src
+        .get(idx)
+        .map(|x| x.is_ascii_alphabetic())
+        .unwrap_or(false)
+    {
+        idx += 1;
+    }
+    Some(idx)
+}
+
+fn skip_number(src: &[u8], mut idx: usize) -> usize {
+    let hex = src[idx..].starts_with(b"0x") || src[idx..].starts_with(b"0X");
+    while let Some(&byte) = src.get(idx) {
+        if !(byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'.') {
+            break;
+        }
+        idx += 1;
+        if !hex && matches!(byte, b'e' | b'E') && matches!(src.get(idx), Some(b'+' | b'-')) {
+            idx += 1;
+        }
+    }
+    idx
+}
+
+/// Returns whether a `/` after the token starts a regular expression, `statement_head` is whether
+/// the token closes the head of a control statement like `if (x)` or a block, after which a
+/// statement starts.
+fn regex_allowed(src: &[u8], prev: Option<&Token>, statement_head: bool) -> bool {
+    let Some(prev) = prev else {
+        return true;
+    };
+    let text = &src[prev.start..prev.end];
+    match prev.kind {
+        Kind::Punct if matches!(text, b")" | b"}") => statement_head,
+        Kind::Punct => !matches!(text, b"]" | b"++" | b"--"),
+        Kind::Ident => REGEX_KEYWORDS.contains(&text),
+        Kind::Template(Template::Head | Template::Middle) => true,
+        _ => false,
+    }
+}
+
+/// Returns whether a `(` after the tokens starts the head of a control statement.
+fn control_head(src: &[u8], tokens: &[Token]) -> bool {
+    let text = |token: &Token| &src[token.start..token.end];
+    match tokens {
+        [.., for_, prev] if text(prev) == b"await" => text(for_) == b"for",
+        [.., prev] => {
+            prev.kind == Kind::Ident && matches!(text(prev), b"if" | b"while" | b"for" | b"with")
+        }
+        [] => false,
+    }
+}
+
+/// Returns whether a `{` after the token opens a block rather than an object literal.
+fn block_allowed(src: &[u8], prev: Option<&Token>) -> bool {
+    let Some(prev) = prev else {
+        return true;
+    };
+    let text = &src[prev.start..prev.end];
+    match prev.kind {
+        // An object literal can't follow a closing parenthesis or brace, so these are the bodies
+        // of statements, functions and methods or blocks after a statement.
+        Kind::Punct => matches!(text, b";" | b"{" | b"}" | b")" | b"=>"),
+        // Keywords like `return` are followed by expressions, other identifiers by blocks like
+        // `else {`, `try {` or class bodies.
+        Kind::Ident => !REGEX_KEYWORDS.contains(&text) || matches!(text, b"else" | b"do"),
+        _ => false,
+    }
+}
+
+fn tokenize(src: &[u8]) -> Option<Vec<Token>> {
+    let mut line_starts = vec![0];
+    line_starts.extend(
+        src.iter()
+            .enumerate()
+            .filter(|(_, &x)| x == b'\n')
+            .map(|(idx, _)| idx + 1),
+    );
+    let position = |idx: usize| {
+        let line = line_starts.partition_point(|&x| x <= idx);
+        Position {
+            line: line as u32,
+            column: (idx - line_starts[line - 1] + 1) as u32,
+        }
+    };
+
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut braces = Vec::new();
+    // Whether each open parenthesis is the head of a control statement.
+    let mut parens = Vec::new();
+    // Whether the last token closes the head of a control statement or a block.
+    let mut statement_head = false;
+    let mut newline = false;
+    let mut idx = 0;
+    if src.starts_with(b"#!") {
+        idx = src.iter().position(|&x| x == b'\n').unwrap_or(src.len());
+    }
+    while let Some(&byte) = src.get(idx) {
+        let next = src.get(idx + 1).copied();
+        match byte {
+            b'\n' => {
+                newline = true;
+                idx += 1;
+                continue;
+            }
+            x if x.is_ascii_whitespace() => {
+                idx += 1;
+                continue;
+            }
+            b'/' if next == Some(b'/') => {
+                idx += src[idx..]
+                    .iter()
+                    .position(|&x| x == b'\n')
+                    .unwrap_or(src.len() - idx);
+                continue;
+            }
+            b'/' if next == Some(b'*') => {
+                let len = src[idx + 2..].windows(2).position(|x| x == b"*/")?;
+                newline |= src[idx..idx + len + 4].contains(&b'\n');
+                idx += len + 4;
+                continue;
+            }
+            _ => {}
+        }
+
+        let start = idx;
+        let mut closes_head = false;
+        let kind = match byte {
+            b'"' | b'\'' => {
+                idx = skip_string(src, idx)?;
+                Kind::String
+            }
+            b'`' => {
+                let (end, substitution) = skip_template(src, idx + 1)?;
+                idx = end;
+                if substitution {
+                    braces.push(Brace::Template);
+                    Kind::Template(Template::Head)
+                } else {
+                    Kind::Template(Template::Full)
+                }
+            }
+            b'}' if braces.last() == Some(&Brace::Template) => {
+                braces.pop();
+                let (end, substitution) = skip_template(src, idx + 1)?;
+                idx = end;
+                if substitution {
+                    braces.push(Brace::Template);
+                    Kind::Template(Template::Middle)
+                } else {
+                    Kind::Template(Template::Tail)
+                }
+            }
+            b'0'..=b'9' => {
+                idx = skip_number(src, idx);
+                Kind::Number
+            }
+            b'.' if next.map(|x| x.is_ascii_digit()).unwrap_or(false) => {
+                idx = skip_number(src, idx);
+                Kind::Number
+            }
+            x if is_ident_start(x) => {
+                idx += 1;
+                while src
+                    .get(idx)
+                    .copied()
+                    .map(is_ident_continue)
+                    .unwrap_or(false)
+                {
+                    idx += 1;
+                }
+                Kind::Ident
+            }
+            b'/' if regex_allowed(src, tokens.last(), statement_head) => {
+                idx = skip_regex(src, idx)?;
+                Kind::Regex
+            }
+            _ => {
+                let rest = &src[idx..];
+                idx += if rest.starts_with(b"...") {
+                    3
+                } else if [&b"=>"[..], b"++", b"--"]
+                    .iter()
+                    .any(|x| rest.starts_with(x))
+                    || (rest.starts_with(b"?.")
+                        && !src
+                            .get(idx + 2)
+                            .map(|x| x.is_ascii_digit())
+                            .unwrap_or(false))
+                {
+                    2
+                } else {
+                    1
+                };
+                match byte {
+                    b'{' if block_allowed(src, tokens.last()) => braces.push(Brace::Block),
+                    b'{' => braces.push(Brace::Object),
+                    b'}' => closes_head = braces.pop()? == Brace::Block,
+                    b'(' => parens.push(control_head(src, &tokens)),
+                    b')' => closes_head = parens.pop()?,
+                    _ => {}
+                }
+                Kind::Punct
+            }
+        };
+        tokens.push(Token {
+            kind,
+            start,
+            end: idx,
+            start_pos: position(start),
+            end_pos: position(idx),
+            newline_before: newline,
+        });
+        statement_head = closes_head;
+        newline = false;
+    }
+    Some(tokens)
+}
+
+/// What a pair of parentheses belongs to.
+enum Owner {
+    /// The condition of a control statement like `if` or `for`.
+    Control,
+    Switch,
+    /// The parameters of a function.
+    Function {
+        name: String,
+        start: Position,
+        declaration: bool,
+    },
+    Other,
+}
+
+enum Frame {
+    Paren(Owner),
+    Bracket,
+    Template,
+    /// A block containing statements.
+    Block {
+        /// Whether the end of the block is the end of a statement.
+        statement: bool,
+        /// The function point if the block is a function body.
+        function: Option<usize>,
+        /// The statement point of the current statement in the block.
+        current: Option<usize>,
+    },
+    Switch,
+    /// An object literal, class body or destructuring pattern.
+    Object,
+}
+
+/// The state of the start of a function body.
+#[derive(Clone, Copy)]
+enum BodyStart {
+    None,
+    /// The function counter still has to be inserted.
+    Pending(usize),
+    /// A directive followed by a semicolon was found.
+    Directive(usize),
+    /// A directive without a semicolon was found, one has to be inserted with the counter.
+    AsiDirective(usize),
+}
+
+/// Tokens which can't start a statement, or for which a statement before them changes meaning.
+fn can_start_statement(kind: Kind, text: &[u8]) -> bool {
+    match kind {
+        Kind::Punct => !matches!(text, b"}" | b")" | b"]" | b"," | b";" | b":"),
+        Kind::Ident => !matches!(
+            text,
+            b"else" | b"catch" | b"finally" | b"while" | b"case" | b"default"
+        ),
+        Kind::String | Kind::Template(Template::Middle | Template::Tail) => false,
+        _ => true,
+    }
+}
+
+/// Insert coverage counters into a source, returns `None` if the source could not be tokenized.
+pub(crate) fn instrument(src: &[u8], file: usize) -> Option<Instrumented> {
+    let tokens = tokenize(src)?;
+    let text = |token: &Token| &src[token.start..token.end];
+    let is_punct = |token: Option<&Token>, punct: &[u8]| {
+        token
+            .map(|x| x.kind == Kind::Punct && text(x) == punct)
+            .unwrap_or(false)
+    };
+
+    let mut frames = vec![Frame::Block {
+        statement: true,
+        function: None,
+        current: None,
+    }];
+    let mut points: Vec<Point> = Vec::new();
+    let mut insertions: Vec<(usize, String)> = Vec::new();
+    let mut statement_start = true;
+    let mut body_start = BodyStart::None;
+    let mut pending_function: Option<Owner> = None;
+    let mut pending_class = None;
+    let mut last_owner = None;
+    let mut anonymous = 0;
+
+    for (idx, token) in tokens.iter().enumerate() {
+        let token_text = text(token);
+        let prev = idx.checked_sub(1).map(|x| &tokens[x]);
+        let prev_text = prev.map(text).unwrap_or_default();
+        // Keywords used as property names don't count.
+        let keyword = token.kind == Kind::Ident && !is_punct(prev, b".") && !is_punct(prev, b"?.");
+        let prev_end = prev.map(|x| x.end_pos).unwrap_or(token.start_pos);
+        let mut at_statement = statement_start;
+        statement_start = false;
+
+        let mut insert = String::new();
+        match body_start {
+            BodyStart::Pending(id) if token.kind == Kind::String => {
+                // Directives have to stay the first statements of a function body.
+                let next = tokens.get(idx + 1);
+                body_start = if is_punct(next, b";") {
+                    BodyStart::Directive(id)
+                } else if next
+                    .map(|x| {
+                        x.newline_before
+                            && ((x.kind == Kind::Ident
+                                && !matches!(text(x), b"in" | b"instanceof"))
+                                || is_punct(Some(x), b"}"))
+                    })
+                    .unwrap_or(false)
+                {
+                    BodyStart::AsiDirective(id)
+                } else {
+                    write!(insert, "{COUNTER}({file},{id});").unwrap();
+                    BodyStart::None
+                };
+                at_statement = false;
+            }
+            BodyStart::Directive(id) => {
+                body_start = BodyStart::Pending(id);
+                at_statement = false;
+            }
+            BodyStart::AsiDirective(id) | BodyStart::Pending(id) => {
+                if let BodyStart::AsiDirective(_) = body_start {
+                    insert.push(';');
+                    at_statement = true;
+                }
+                write!(insert, "{COUNTER}({file},{id});").unwrap();
+                body_start = BodyStart::None;
+            }
+            BodyStart::None => {}
+        }
+        if at_statement && can_start_statement(token.kind, token_text) {
+            let id = points.len();
+            points.push(Point {
+                kind: PointKind::Statement,
+                start: token.start_pos,
+                end: token.end_pos,
+            });
+            if let Some(Frame::Block { current, .. }) = frames.last_mut() {
+                if let Some(current) = current.replace(id) {
+                    points[current].end = prev_end;
+                }
+            }
+            write!(insert, "{COUNTER}({file},{id});").unwrap();
+        }
+        if !insert.is_empty() {
+            insertions.push((token.start, insert));
+        }
+
+        match (token.kind, token_text) {
+            (Kind::Ident, b"function") if keyword => {
+                let mut next = tokens.get(idx + 1);
+                if is_punct(next, b"*") {
+                    next = tokens.get(idx + 2);
+                }
+                let name = match next {
+                    Some(next) if next.kind == Kind::Ident => {
+                        String::from_utf8_lossy(text(next)).into_owned()
+                    }
+                    _ => {
+                        anonymous += 1;
+                        format!("(anonymous_{anonymous})")
+                    }
+                };
+                pending_function = Some(Owner::Function {
+                    name,
+                    start: token.start_pos,
+                    declaration: at_statement,
+                });
+            }
+            (Kind::Ident, b"class") if keyword => pending_class = Some(frames.len()),
+            (Kind::Punct, b"(") => {
+                let prev_keyword = prev.map(|x| x.kind == Kind::Ident).unwrap_or(false);
+                let owner = if let Some(owner) = pending_function.take() {
+                    owner
+                } else if matches!(frames.last(), Some(Frame::Object))
+                    && prev
+                        .map(|x| {
+                            matches!(x.kind, Kind::Ident | Kind::String | Kind::Number)
+                                || is_punct(Some(x), b"]")
+                        })
+                        .unwrap_or(false)
+                {
+                    // A method of a class or object literal.
+                    let prev = prev.unwrap();
+                    Owner::Function {
+                        name: if prev.kind == Kind::Punct {
+                            "(computed)".to_string()
+                        } else {
+                            String::from_utf8_lossy(prev_text).into_owned()
+                        },
+                        start: prev.start_pos,
+                        declaration: false,
+                    }
+                } else if prev_keyword
+                    && (matches!(prev_text, b"if" | b"for" | b"while" | b"with" | b"catch")
+                        || prev_text == b"await" && idx >= 2 && text(&tokens[idx - 2]) == b"for")
+                {
+                    Owner::Control
+                } else if prev_keyword && prev_text == b"switch" {
+                    Owner::Switch
+                } else {
+                    Owner::Other
+                };
+                frames.push(Frame::Paren(owner));
+            }
+            (Kind::Punct, b")") => match frames.pop() {
+                Some(Frame::Paren(owner)) => last_owner = Some(owner),
+                _ => return None,
+            },
+            (Kind::Punct, b"[") => frames.push(Frame::Bracket),
+            (Kind::Punct, b"]") => match frames.pop() {
+                Some(Frame::Bracket) => {}
+                _ => return None,
+            },
+            (Kind::Punct, b"{") => {
+                let block = |statement| Frame::Block {
+                    statement,
+                    function: None,
+                    current: None,
+                };
+                let mut function = None;
+                let frame = if pending_class == Some(frames.len()) {
+                    pending_class = None;
+                    Frame::Object
+                } else if is_punct(prev, b")") {
+                    match last_owner.take() {
+                        Some(Owner::Control) => block(true),
+                        Some(Owner::Switch) => Frame::Switch,
+                        Some(Owner::Function {
+                            name,
+                            start,
+                            declaration,
+                        }) => {
+                            function = Some((name, start));
+                            block(declaration)
+                        }
+                        _ => Frame::Object,
+                    }
+                } else if is_punct(prev, b"=>") {
+                    anonymous += 1;
+                    function = Some((format!("(anonymous_{anonymous})"), prev.unwrap().start_pos));
+                    block(false)
+                } else if prev.map(|x| x.kind == Kind::Ident).unwrap_or(false)
+                    && matches!(prev_text, b"else" | b"try" | b"finally" | b"do" | b"catch")
+                    || at_statement
+                {
+                    block(true)
+                } else {
+                    Frame::Object
+                };
+                let frame = match (frame, function) {
+                    (Frame::Block { statement, .. }, Some((name, start))) => {
+                        let id = points.len();
+                        points.push(Point {
+                            kind: PointKind::Function(name),
+                            start,
+                            end: token.end_pos,
+                        });
+                        body_start = BodyStart::Pending(id);
+                        Frame::Block {
+                            statement,
+                            function: Some(id),
+                            current: None,
+                        }
+                    }
+                    (frame, _) => frame,
+                };
+                statement_start = matches!(frame, Frame::Block { .. });
+                frames.push(frame);
+            }
+            (Kind::Punct, b"}") => match frames.pop() {
+                Some(Frame::Block {
+                    statement,
+                    function,
+                    current,
+                }) => {
+                    if let Some(current) = current {
+                        points[current].end = prev_end;
+                    }
+                    if let Some(function) = function {
+                        points[function].end = token.end_pos;
+                    }
+                    statement_start =
+                        statement && matches!(frames.last(), Some(Frame::Block { .. }));
+                }
+                Some(Frame::Switch | Frame::Object) => {}
+                _ => return None,
+            },
+            (Kind::Template(Template::Head), _) => frames.push(Frame::Template),
+            (Kind::Template(Template::Middle), _)
+                if !matches!(frames.last(), Some(Frame::Template)) =>
+            {
+                return None
+            }
+            (Kind::Template(Template::Tail), _) => match frames.pop() {
+                Some(Frame::Template) => {}
+                _ => return None,
+            },
+            (Kind::Punct, b";") => {
+                statement_start = matches!(frames.last(), Some(Frame::Block { .. }));
+            }
+            _ => {}
+        }
+    }
+
+    match frames.as_slice() {
+        [Frame::Block { current, .. }] => {
+            if let (Some(current), Some(last)) = (current, tokens.last()) {
+                points[*current].end = last.end_pos;
+            }
+        }
+        _ => return None,
+    }
+
+    let mut source = Vec::with_capacity(src.len() + insertions.len() * 24);
+    let mut last = 0;
+    for (idx, insert) in insertions {
+        source.extend_from_slice(&src[last..idx]);
+        source.extend_from_slice(insert.as_bytes());
+        last = idx;
+    }
+    source.extend_from_slice(&src[last..]);
+    Some(Instrumented { source, points })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tokenize, Kind};
+
+    fn regexes(src: &str) -> Vec<&str> {
+        tokenize(src.as_bytes())
+            .unwrap()
+            .into_iter()
+            .filter(|x| x.kind == Kind::Regex)
+            .map(|x| &src[x.start..x.end])
+            .collect()
+    }
+
+    #[test]
+    fn regex_or_division() {
+        assert_eq!(regexes("if (x) /re/.test(s)"), ["/re/"]);
+        assert_eq!(regexes("while (f(a)) /'/g.exec(c)"), ["/'/g"]);
+        assert_eq!(
+            regexes("for (;;) /a/; for await (const x of y) /b/"),
+            ["/a/", "/b/"]
+        );
+        assert_eq!(regexes("with (o) /}/"), ["/}/"]);
+        assert_eq!(regexes("f(x) / 2 / 3; (a) / b / c"), Vec::<&str>::new());
+        assert_eq!(regexes("{ a() } /a/.test(b)"), ["/a/"]);
+        assert_eq!(regexes("function f() {}\n/a/.test(b)"), ["/a/"]);
+        assert_eq!(regexes("if (x) {} else {} /a/"), ["/a/"]);
+        assert_eq!(regexes("x = { a: 1 } / 2 / 1"), Vec::<&str>::new());
+        assert_eq!(regexes("`${ { a: 1 }.a / 2 / 1 }` / 2"), Vec::<&str>::new());
+        assert_eq!(regexes("x = a[0] / 2 / 1"), Vec::<&str>::new());
+        assert_eq!(regexes("return /a/"), ["/a/"]);
+    }
+}
@@ -0,0 +1,1053 @@
+//! A debugger for scripts, speaking the [Debug Adapter Protocol].
+//!
+//! A [`Debugger`] is attached to a runtime with
+//! [`Runtime::set_debugger`](crate::Runtime::set_debugger) and serves one client at a time,
+//! editors connect to it like to any other debug adapter. It supports breakpoints with
+//! conditions, pausing, stepping in, over and out of functions, stack traces, the local, closure
+//! and global variables of every frame and evaluating expressions.
+//!
+//! While the runtime is paused the thread running it blocks in the interpreter until the client
+//! resumes it, so the runtime should be attached to at most one debugger and the debugger is
+//! best used with a single runtime.
+//!
+//! ```no_run
+//! # use rquickjs::{Runtime, Context, debugger::Debugger};
+//! let debugger = Debugger::listen("127.0.0.1:4711").unwrap();
+//! // Wait for the client to set its breakpoints before running the scripts.
+//! debugger.wait_for_client();
+//! let rt = Runtime::new().unwrap();
+//! rt.set_debugger(Some(debugger));
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let _: i32 = ctx.eval("1 + 1").unwrap();
+//! });
+//! ```
+//!
+//! Stepping and breakpoints work on lines, a line is reached when a function starts executing
+//! code of a line other than the previous one. Expressions are evaluated in the global scope,
+//! except for variables and property paths like `point.x` which are resolved starting from the
+//! variables of the selected frame. The `debugger` statement is ignored.
+//!
+//! [Debug Adapter Protocol]: https://microsoft.github.io/debug-adapter-protocol/
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, ToSocketAddrs},
+    result::Result as StdResult,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread,
+};
+
+use crate::{
+    convert::Coerced, object::Filter, qjs, Atom, CatchResultExt, Ctx, FromJs, InspectOptions,
+    Object, Result, StdString, Value,
+};
+
+mod json;
+
+use json::Json;
+
+/// The id of the only thread reported to the client.
+const THREAD_ID: i64 = 1;
+
+/// A debugger serving a Debug Adapter Protocol client.
+///
+/// Cloning a debugger returns a new handle to the same debugger.
+#[derive(Clone)]
+pub struct Debugger {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    /// Notified when the client sent a command for the paused runtime or finished attaching.
+    signal: Condvar,
+    writer: Mutex<Option<Box<dyn Write + Send>>>,
+    seq: AtomicI64,
+}
+
+#[derive(Default)]
+struct State {
+    connected: bool,
+    configured: bool,
+    /// Breakpoints by the path of their source.
+    breakpoints: HashMap<StdString, Vec<Breakpoint>>,
+    /// The lines of all breakpoints, for quickly skipping lines without any.
+    lines: HashSet<u32>,
+    pause: bool,
+    step: Option<Step>,
+    terminate: bool,
+    paused: bool,
+    /// The requests to be answered by the paused runtime.
+    commands: VecDeque<Json>,
+}
+
+struct Breakpoint {
+    line: u32,
+    condition: Option<StdString>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepKind {
+    In,
+    Over,
+    Out,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    kind: StepKind,
+    depth: i32,
+}
+
+impl State {
+    fn set_breakpoints(&mut self, source: StdString, breakpoints: Vec<Breakpoint>) {
+        if breakpoints.is_empty() {
+            self.breakpoints.remove(&source);
+        } else {
+            self.breakpoints.insert(source, breakpoints);
+        }
+        self.lines = self
+            .breakpoints
+            .values()
+            .flatten()
+            .map(|breakpoint| breakpoint.line)
+            .collect();
+    }
+
+    fn breakpoint(&self, file: &str, line: u32) -> Option<&Breakpoint> {
+        self.breakpoints
+            .iter()
+            .filter(|(source, _)| same_source(source, file))
+            .flat_map(|(_, breakpoints)| breakpoints)
+            .find(|breakpoint| breakpoint.line == line)
+    }
+
+    fn disconnect(&mut self) {
+        self.configured = false;
+        self.breakpoints.clear();
+        self.lines.clear();
+        self.pause = false;
+        self.step = None;
+        if self.paused {
+            // Resumes the runtime without a response.
+            self.commands
+                .push_back(Json::object([("command", "disconnect".into())]));
+        }
+    }
+}
+
+/// Returns whether a source path set by the client refers to the file name of a script, which
+/// may be relative.
+fn same_source(source: &str, file: &str) -> bool {
+    fn is_suffix(path: &str, suffix: &str) -> bool {
+        path.strip_suffix(suffix)
+            .map(|rest| rest.ends_with(['/', '\\']))
+            .unwrap_or(false)
+    }
+    let file = file.strip_prefix("./").unwrap_or(file);
+    source == file || is_suffix(source, file) || is_suffix(file, source)
+}
+
+impl Debugger {
+    fn new() -> Self {
+        Debugger {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State::default()),
+                signal: Condvar::new(),
+                writer: Mutex::new(None),
+                seq: AtomicI64::new(1),
+            }),
+        }
+    }
+
+    /// Create a debugger accepting clients on a TCP socket, one after another.
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let debugger = Self::new();
+        let shared = debugger.shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if let Ok(writer) = stream.try_clone() {
+                    shared.serve(stream, writer);
+                }
+            }
+        });
+        Ok(debugger)
+    }
+
+    /// Create a debugger serving a client on the standard input and output of the process.
+    ///
+    /// Nothing else may write to the standard output while the client is connected.
+    pub fn stdio() -> Self {
+        Self::with_transport(io::stdin(), io::stdout())
+    }
+
+    /// Create a debugger serving a client which sends its messages to the reader and receives
+    /// the messages of the debugger from the writer.
+    pub fn with_transport<R, W>(reader: R, writer: W) -> Self
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        let debugger = Self::new();
+        let shared = debugger.shared.clone();
+        thread::spawn(move || shared.serve(reader, writer));
+        debugger
+    }
+
+    /// Block until a client attached and finished setting its breakpoints.
+    pub fn wait_for_client(&self) {
+        let mut state = self.shared.lock();
+        while !state.configured {
+            state = self.shared.signal.wait(state).unwrap();
+        }
+    }
+
+    /// Called when a function reaches a new line, returns whether to interrupt the runtime.
+    pub(crate) fn hook(&self, ctx: &Ctx, file: qjs::JSAtom, line: u32) -> bool {
+        let mut state = self.shared.lock();
+        if state.terminate {
+            state.terminate = false;
+            return true;
+        }
+        if !state.pause && state.step.is_none() && !state.lines.contains(&line) {
+            return false;
+        }
+
+        let file = unsafe { Atom::from_atom_val_dup(ctx.clone(), file) }
+            .to_string()
+            .unwrap_or_default();
+        let mut pause = Pause {
+            ctx: ctx.clone(),
+            values: Vec::new(),
+        };
+        let reason = if state.pause {
+            "pause"
+        } else if state.step.map(|step| step.reached(ctx)).unwrap_or(false) {
+            "step"
+        } else if let Some(breakpoint) = state.breakpoint(&file, line) {
+            if let Some(condition) = breakpoint.condition.clone() {
+                drop(state);
+                // Stop on conditions which fail to evaluate, so that they can be corrected.
+                let stop = pause
+                    .evaluate(&condition, 0)
+                    .map(|value| {
+                        Coerced::<bool>::from_js(ctx, value)
+                            .map(|x| x.0)
+                            .unwrap_or(true)
+                    })
+                    .unwrap_or(true);
+                if !stop {
+                    return false;
+                }
+                state = self.shared.lock();
+            }
+            "breakpoint"
+        } else {
+            return false;
+        };
+
+        state.pause = false;
+        state.step = None;
+        state.paused = true;
+        drop(state);
+        self.shared.event(
+            "stopped",
+            Json::object([
+                ("reason", reason.into()),
+                ("threadId", THREAD_ID.into()),
+                ("allThreadsStopped", true.into()),
+            ]),
+        );
+        pause.run(&self.shared);
+
+        let mut state = self.shared.lock();
+        state.paused = false;
+        for request in std::mem::take(&mut state.commands) {
+            self.shared
+                .respond(&request, Err("the runtime is not paused".into()));
+        }
+        std::mem::take(&mut state.terminate)
+    }
+}
+
+impl Step {
+    fn new(ctx: &Ctx, kind: StepKind) -> Self {
+        Step {
+            kind,
+            depth: unsafe { qjs::JS_GetDebugStackDepth(ctx.as_ptr()) },
+        }
+    }
+
+    fn reached(&self, ctx: &Ctx) -> bool {
+        let depth = || unsafe { qjs::JS_GetDebugStackDepth(ctx.as_ptr()) };
+        match self.kind {
+            StepKind::In => true,
+            StepKind::Over => depth() <= self.depth,
+            StepKind::Out => depth() < self.depth,
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn serve<R: Read, W: Write + Send + 'static>(&self, reader: R, writer: W) {
+        *self.writer.lock().unwrap() = Some(Box::new(writer));
+        self.lock().connected = true;
+        let mut reader = BufReader::new(reader);
+        while let Ok(Some(request)) = read_message(&mut reader) {
+            if request.get("type").as_str() == Some("request") {
+                self.handle(request);
+            }
+        }
+        let mut state = self.lock();
+        state.connected = false;
+        state.disconnect();
+        self.signal.notify_all();
+        drop(state);
+        *self.writer.lock().unwrap() = None;
+    }
+
+    fn handle(&self, request: Json) {
+        let args = request.get("arguments");
+        let res = match request.get("command").as_str().unwrap_or_default() {
+            "initialize" => {
+                self.respond(
+                    &request,
+                    Ok(Json::object([
+                        ("supportsConfigurationDoneRequest", true.into()),
+                        ("supportsConditionalBreakpoints", true.into()),
+                        ("supportsEvaluateForHovers", true.into()),
+                        ("supportTerminateDebuggee", true.into()),
+                    ])),
+                );
+                self.event("initialized", Json::object([]));
+                return;
+            }
+            "attach" | "launch" => {
+                if args.get("stopOnEntry").as_bool() == Some(true) {
+                    self.lock().pause = true;
+                }
+                Ok(Json::Null)
+            }
+            "setBreakpoints" => {
+                let source = args.get("source");
+                let path = source.get("path").as_str().or(source.get("name").as_str());
+                let breakpoints: Vec<_> = args
+                    .get("breakpoints")
+                    .as_array()
+                    .iter()
+                    .filter_map(|breakpoint| {
+                        Some(Breakpoint {
+                            line: breakpoint.get("line").as_i64()?.try_into().ok()?,
+                            condition: breakpoint
+                                .get("condition")
+                                .as_str()
+                                .filter(|x| !x.trim().is_empty())
+                                .map(Into::into),
+                        })
+                    })
+                    .collect();
+                let body = breakpoints
+                    .iter()
+                    .map(|breakpoint| {
+                        Json::object([
+                            ("verified", path.is_some().into()),
+                            ("line", breakpoint.line.into()),
+                        ])
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(path) = path {
+                    self.lock().set_breakpoints(path.into(), breakpoints);
+                }
+                Ok(Json::object([("breakpoints", body.into())]))
+            }
+            "setExceptionBreakpoints" => Ok(Json::object([("breakpoints", Vec::new().into())])),
+            "configurationDone" => {
+                self.lock().configured = true;
+                self.signal.notify_all();
+                Ok(Json::Null)
+            }
+            "threads" => Ok(Json::object([(
+                "threads",
+                vec![Json::object([
+                    ("id", THREAD_ID.into()),
+                    ("name", "main".into()),
+                ])]
+                .into(),
+            )])),
+            "pause" => {
+                self.lock().pause = true;
+                Ok(Json::Null)
+            }
+            "disconnect" => {
+                let mut state = self.lock();
+                state.disconnect();
+                state.terminate = args.get("terminateDebuggee").as_bool() == Some(true);
+                self.signal.notify_all();
+                Ok(Json::Null)
+            }
+            "continue" | "next" | "stepIn" | "stepOut" | "stackTrace" | "scopes" | "variables"
+            | "evaluate" => {
+                let mut state = self.lock();
+                if state.paused {
+                    state.commands.push_back(request);
+                    self.signal.notify_all();
+                    return;
+                }
+                Err("the runtime is not paused".into())
+            }
+            command => Err(format!("unsupported request '{command}'")),
+        };
+        self.respond(&request, res);
+    }
+
+    fn send(&self, mut fields: Vec<(StdString, Json)>) {
+        let seq = self.seq.fetch_add(1, Ordering::Relaxed);
+        fields.insert(0, ("seq".into(), seq.into()));
+        let body = Json::Object(fields).to_string();
+        if let Some(writer) = self.writer.lock().unwrap().as_mut() {
+            // A failed write means the client is gone, which the reader notices.
+            let _ = write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+                .and_then(|_| writer.flush());
+        }
+    }
+
+    fn respond(&self, request: &Json, res: StdResult<Json, StdString>) {
+        let Some(request_seq) = request.get("seq").as_i64() else {
+            return;
+        };
+        let mut fields = vec![
+            ("type".into(), "response".into()),
+            ("request_seq".into(), request_seq.into()),
+            ("command".into(), request.get("command").clone()),
+        ];
+        match res {
+            Ok(body) => {
+                fields.push(("success".into(), true.into()));
+                fields.push(("body".into(), body));
+            }
+            Err(message) => {
+                fields.push(("success".into(), false.into()));
+                fields.push(("message".into(), message.into()));
+            }
+        }
+        self.send(fields);
+    }
+
+    fn event(&self, event: &str, body: Json) {
+        self.send(vec![
+            ("type".into(), "event".into()),
+            ("event".into(), event.into()),
+            ("body".into(), body),
+        ]);
+    }
+}
+
+/// Reads a message framed with a `Content-Length` header, `None` at the end of the input.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut length = None;
+    loop {
+        let mut line = StdString::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if let Some(length) = length {
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body)?;
+                let message = StdString::from_utf8(body)
+                    .ok()
+                    .and_then(|body| Json::parse(&body));
+                return Ok(Some(message.unwrap_or(Json::Null)));
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+}
+
+/// The state of a paused runtime, the references handed out to the client are indices into the
+/// values and are valid until the runtime resumes.
+struct Pause<'js> {
+    ctx: Ctx<'js>,
+    values: Vec<Value<'js>>,
+}
+
+impl<'js> Pause<'js> {
+    /// Answer requests until the client resumes the runtime.
+    fn run(&mut self, shared: &Shared) {
+        loop {
+            let mut state = shared.lock();
+            let request = loop {
+                if let Some(request) = state.commands.pop_front() {
+                    break request;
+                }
+                state = shared.signal.wait(state).unwrap();
+            };
+            drop(state);
+
+            let args = request.get("arguments");
+            let level = args.get("frameId").as_i64().unwrap_or(0) as i32;
+            let step = match request.get("command").as_str().unwrap_or_default() {
+                "disconnect" => return,
+                "continue" => None,
+                "next" => Some(StepKind::Over),
+                "stepIn" => Some(StepKind::In),
+                "stepOut" => Some(StepKind::Out),
+                command => {
+                    let res = match command {
+                        "stackTrace" => self.stack_trace(),
+                        "scopes" => self.scopes(level),
+                        "variables" => {
+                            self.variables(args.get("variablesReference").as_i64().unwrap_or(0))
+                        }
+                        _ => self.evaluate_request(args.get("expression").as_str(), level),
+                    };
+                    shared.respond(&request, res.map_err(|error| error.to_string()));
+                    continue;
+                }
+            };
+            shared.lock().step = step.map(|kind| Step::new(&self.ctx, kind));
+            let body = match step {
+                None => Json::object([("allThreadsContinued", true.into())]),
+                Some(_) => Json::Null,
+            };
+            shared.respond(&request, Ok(body));
+            return;
+        }
+    }
+
+    /// Returns a reference for the client to request the properties of the value, 0 if it
+    /// has none.
+    fn reference(&mut self, value: &Value<'js>) -> usize {
+        if value.is_object() {
+            self.values.push(value.clone());
+            self.values.len()
+        } else {
+            0
+        }
+    }
+
+    fn frame(&self, level: i32) -> Result<Option<Object<'js>>> {
+        unsafe {
+            let val = qjs::JS_GetDebugFrame(self.ctx.as_ptr(), level);
+            let val = self.ctx.handle_exception(val)?;
+            Ok(Value::from_js_value(self.ctx.clone(), val).into_object())
+        }
+    }
+
+    fn frame_variables(&self, level: i32, closure: bool) -> Result<Option<Object<'js>>> {
+        unsafe {
+            let val = qjs::JS_GetDebugFrameVariables(self.ctx.as_ptr(), level, closure as _);
+            let val = self.ctx.handle_exception(val)?;
+            Ok(Value::from_js_value(self.ctx.clone(), val).into_object())
+        }
+    }
+
+    fn stack_trace(&mut self) -> StdResult<Json, StdString> {
+        let res = (|| {
+            let depth = unsafe { qjs::JS_GetDebugStackDepth(self.ctx.as_ptr()) };
+            let mut frames = Vec::new();
+            for level in 0..depth {
+                let Some(frame) = self.frame(level)? else {
+                    break;
+                };
+                let name: StdString = frame.get("functionName")?;
+                let name = if name.is_empty() {
+                    "<anonymous>".into()
+                } else {
+                    name
+                };
+                let file: Option<StdString> = frame.get("fileName")?;
+                let line: Option<u32> = frame.get("lineNumber")?;
+                let column: Option<u32> = frame.get("columnNumber")?;
+                let mut fields = vec![
+                    ("id".into(), i64::from(level).into()),
+                    ("name".into(), name.into()),
+                    ("line".into(), line.unwrap_or(0).into()),
+                    ("column".into(), column.unwrap_or(0).into()),
+                ];
+                match file {
+                    Some(file) => fields.push((
+                        "source".into(),
+                        Json::object([
+                            ("name", file.rsplit(['/', '\\']).next().unwrap_or("").into()),
+                            ("path", file.as_str().into()),
+                        ]),
+                    )),
+                    None => fields.push(("presentationHint".into(), "subtle".into())),
+                }
+                frames.push(Json::Object(fields));
+            }
+            Ok(Json::object([
+                ("totalFrames", frames.len().into()),
+                ("stackFrames", frames.into()),
+            ]))
+        })();
+        res.map_err(|error: crate::Error| error.to_string())
+    }
+
+    fn scopes(&mut self, level: i32) -> StdResult<Json, StdString> {
+        let res = (|| {
+            let mut scopes = Vec::new();
+            let locals = self.frame_variables(level, false)?;
+            let closure = self.frame_variables(level, true)?;
+            let globals = Some(self.ctx.globals());
+            for (name, hint, object) in [
+                ("Locals", "locals", locals),
+                ("Closure", "locals", closure),
+                ("Globals", "globals", globals),
+            ] {
+                let Some(object) = object else { continue };
+                if hint == "locals" && object.is_empty() {
+                    continue;
+                }
+                let reference = self.reference(object.as_value());
+                scopes.push(Json::object([
+                    ("name", name.into()),
+                    ("presentationHint", hint.into()),
+                    ("variablesReference", reference.into()),
+                    ("expensive", (hint == "globals").into()),
+                ]));
+            }
+            Ok(Json::object([("scopes", scopes.into())]))
+        })();
+        res.map_err(|error: crate::Error| error.to_string())
+    }
+
+    fn variables(&mut self, reference: i64) -> StdResult<Json, StdString> {
+        let object = usize::try_from(reference - 1)
+            .ok()
+            .and_then(|index| self.values.get(index))
+            .and_then(|value| value.as_object())
+            .cloned()
+            .ok_or_else(|| "invalid variables reference".to_string())?;
+        let res = (|| {
+            let mut variables = Vec::new();
+            for key in object.own_keys::<Atom>(Filter::new().string().symbol().enum_only()) {
+                let key = key?;
+                let Some(desc) = object.get_own_property_descriptor(key.clone())? else {
+                    continue;
+                };
+                let name = match key.to_value()?.as_symbol() {
+                    Some(symbol) => symbol.description()?.as_string().map_or_else(
+                        || Ok("Symbol()".to_string()),
+                        |x| x.to_string().map(|x| format!("Symbol({x})")),
+                    )?,
+                    None => key.to_string()?,
+                };
+                let variable = match desc.value {
+                    Some(value) if !desc.is_accessor() => self.variable(name, &value),
+                    _ => Json::object([
+                        ("name", name.into()),
+                        ("value", "[Getter/Setter]".into()),
+                        ("variablesReference", 0usize.into()),
+                    ]),
+                };
+                variables.push(variable);
+            }
+            Ok(Json::object([("variables", variables.into())]))
+        })();
+        res.map_err(|error: crate::Error| error.to_string())
+    }
+
+    fn variable(&mut self, name: StdString, value: &Value<'js>) -> Json {
+        Json::object([
+            ("name", name.into()),
+            ("value", describe(value).into()),
+            ("type", value.type_name().into()),
+            ("variablesReference", self.reference(value).into()),
+        ])
+    }
+
+    fn evaluate_request(
+        &mut self,
+        expression: Option<&str>,
+        level: i32,
+    ) -> StdResult<Json, StdString> {
+        let value = self.evaluate(expression.unwrap_or_default(), level)?;
+        Ok(Json::object([
+            ("result", describe(&value).into()),
+            ("type", value.type_name().into()),
+            ("variablesReference", self.reference(&value).into()),
+        ]))
+    }
+
+    /// Evaluate an expression, resolving variable paths in the frame at the level.
+    fn evaluate(&self, expression: &str, level: i32) -> StdResult<Value<'js>, StdString> {
+        let ctx = &self.ctx;
+        if let Some(value) = self
+            .lookup(expression.trim(), level)
+            .catch(ctx)
+            .map_err(|error| error.to_string())?
+        {
+            return Ok(value);
+        }
+        ctx.eval(expression)
+            .catch(ctx)
+            .map_err(|error| error.to_string())
+    }
+
+    fn lookup(&self, expression: &str, level: i32) -> Result<Option<Value<'js>>> {
+        let mut path = expression.split('.');
+        if !expression.split('.').all(is_identifier) {
+            return Ok(None);
+        }
+        let Some(first) = path.next() else {
+            return Ok(None);
+        };
+        let mut value = None;
+        for closure in [false, true] {
+            let Some(variables) = self.frame_variables(level, closure)? else {
+                return Ok(None);
+            };
+            if let Some(desc) = variables.get_own_property_descriptor(first)? {
+                value = desc.value;
+                break;
+            }
+        }
+        let Some(mut value) = value else {
+            return Ok(None);
+        };
+        for name in path {
+            value = match value.as_object() {
+                Some(object) => object.get(name)?,
+                None => Value::new_undefined(self.ctx.clone()),
+            };
+        }
+        Ok(Some(value))
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn describe(value: &Value) -> StdString {
+    value
+        .inspect(InspectOptions {
+            depth: 0,
+            max_array_length: 10,
+            max_string_length: 1000,
+            break_length: usize::MAX,
+            show_hidden: false,
+        })
+        .unwrap_or_else(|_| value.type_name().into())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{self, BufReader, Read, Write},
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+    };
+
+    use super::{json::Json, read_message, same_source, Debugger};
+    use crate::{context::EvalOptions, Context, Error, Runtime};
+
+    struct ChannelReader {
+        rx: Receiver<Vec<u8>>,
+        buf: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            if self.pos == self.buf.len() {
+                match self.rx.recv() {
+                    Ok(buf) => {
+                        self.buf = buf;
+                        self.pos = 0;
+                    }
+                    Err(_) => return Ok(0),
+                }
+            }
+            let len = out.len().min(self.buf.len() - self.pos);
+            out[..len].copy_from_slice(&self.buf[self.pos..self.pos + len]);
+            self.pos += len;
+            Ok(len)
+        }
+    }
+
+    struct ChannelWriter(Sender<Vec<u8>>);
+
+    impl Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let _ = self.0.send(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn channel() -> (ChannelWriter, ChannelReader) {
+        let (tx, rx) = mpsc::channel();
+        (
+            ChannelWriter(tx),
+            ChannelReader {
+                rx,
+                buf: Vec::new(),
+                pos: 0,
+            },
+        )
+    }
+
+    struct Client {
+        writer: ChannelWriter,
+        reader: BufReader<ChannelReader>,
+        seq: i64,
+        events: Vec<Json>,
+    }
+
+    impl Client {
+        fn connect() -> (Debugger, Self) {
+            let (client_writer, debugger_reader) = channel();
+            let (debugger_writer, client_reader) = channel();
+            let debugger = Debugger::with_transport(debugger_reader, debugger_writer);
+            let client = Client {
+                writer: client_writer,
+                reader: BufReader::new(client_reader),
+                seq: 0,
+                events: Vec::new(),
+            };
+            (debugger, client)
+        }
+
+        fn request(&mut self, command: &str, arguments: Json) -> Json {
+            self.seq += 1;
+            let body = Json::object([
+                ("seq", self.seq.into()),
+                ("type", "request".into()),
+                ("command", command.into()),
+                ("arguments", arguments),
+            ])
+            .to_string();
+            write!(
+                self.writer,
+                "Content-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            loop {
+                let message = read_message(&mut self.reader).unwrap().unwrap();
+                if message.get("type").as_str() == Some("event") {
+                    self.events.push(message);
+                } else if message.get("request_seq").as_i64() == Some(self.seq) {
+                    return message;
+                }
+            }
+        }
+
+        fn success(&mut self, command: &str, arguments: Json) -> Json {
+            let res = self.request(command, arguments);
+            assert_eq!(res.get("success"), &Json::Bool(true), "{res}");
+            res.get("body").clone()
+        }
+
+        fn event(&mut self, event: &str) -> Json {
+            loop {
+                if let Some(index) = self
+                    .events
+                    .iter()
+                    .position(|x| x.get("event").as_str() == Some(event))
+                {
+                    return self.events.remove(index).get("body").clone();
+                }
+                let message = read_message(&mut self.reader).unwrap().unwrap();
+                self.events.push(message);
+            }
+        }
+
+        fn attach(&mut self, breakpoints: Json) {
+            self.success("initialize", Json::object([]));
+            self.event("initialized");
+            self.success("attach", Json::object([]));
+            self.success(
+                "setBreakpoints",
+                Json::object([
+                    (
+                        "source",
+                        Json::object([("path", "/home/user/scripts/test.js".into())]),
+                    ),
+                    ("breakpoints", breakpoints),
+                ]),
+            );
+            self.success("configurationDone", Json::object([]));
+        }
+
+        fn variables(&mut self, reference: &Json) -> Vec<(String, String)> {
+            let body = self.success(
+                "variables",
+                Json::object([("variablesReference", reference.clone())]),
+            );
+            body.get("variables")
+                .as_array()
+                .iter()
+                .map(|x| {
+                    (
+                        x.get("name").as_str().unwrap().to_string(),
+                        x.get("value").as_str().unwrap().to_string(),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    fn breakpoint(line: i64, condition: Option<&str>) -> Json {
+        Json::object([("line", line.into()), ("condition", condition.into())])
+    }
+
+    #[test]
+    fn source_paths() {
+        assert!(same_source("/scripts/test.js", "test.js"));
+        assert!(same_source("/scripts/test.js", "./test.js"));
+        assert!(same_source("test.js", "/scripts/test.js"));
+        assert!(!same_source("/scripts/my_test.js", "test.js"));
+        assert!(!same_source("/scripts/test.js", "other.js"));
+    }
+
+    #[test]
+    fn breakpoints_and_stepping() {
+        let (debugger, mut client) = Client::connect();
+        let client = thread::spawn(move || {
+            client.attach(vec![breakpoint(4, None)].into());
+
+            let stopped = client.event("stopped");
+            assert_eq!(stopped.get("reason").as_str(), Some("breakpoint"));
+            let trace = client.success("stackTrace", Json::object([("threadId", 1i64.into())]));
+            let frames = trace.get("stackFrames").as_array();
+            assert_eq!(frames[0].get("name").as_str(), Some("add"));
+            assert_eq!(frames[0].get("line").as_i64(), Some(4));
+            assert_eq!(
+                frames[0].get("source").get("path").as_str(),
+                Some("test.js")
+            );
+            assert_eq!(frames[1].get("line").as_i64(), Some(8));
+
+            let scopes = client.success("scopes", Json::object([("frameId", 0i64.into())]));
+            let scopes = scopes.get("scopes").as_array();
+            assert_eq!(scopes[0].get("name").as_str(), Some("Locals"));
+            assert_eq!(scopes[1].get("name").as_str(), Some("Closure"));
+            let locals = client.variables(scopes[0].get("variablesReference"));
+            assert!(locals.contains(&("a".into(), "1".into())));
+            assert!(locals.contains(&("point".into(), "{ x: 1, y: 2 }".into())));
+            assert!(!locals.iter().any(|(name, _)| name == "sum"));
+            let closure = client.variables(scopes[1].get("variablesReference"));
+            assert_eq!(closure, [("offset".into(), "10".into())]);
+
+            let res = client.success(
+                "evaluate",
+                Json::object([("expression", "point.y".into()), ("frameId", 0i64.into())]),
+            );
+            assert_eq!(res.get("result").as_str(), Some("2"));
+            let point = client.success(
+                "evaluate",
+                Json::object([("expression", "point".into()), ("frameId", 0i64.into())]),
+            );
+            let point = client.variables(point.get("variablesReference"));
+            assert_eq!(point, [("x".into(), "1".into()), ("y".into(), "2".into())]);
+            let res = client.request(
+                "evaluate",
+                Json::object([("expression", "missing".into()), ("frameId", 0i64.into())]),
+            );
+            assert_eq!(res.get("success"), &Json::Bool(false));
+
+            client.success("next", Json::object([("threadId", 1i64.into())]));
+            let stopped = client.event("stopped");
+            assert_eq!(stopped.get("reason").as_str(), Some("step"));
+            let trace = client.success("stackTrace", Json::object([("threadId", 1i64.into())]));
+            let frames = trace.get("stackFrames").as_array();
+            assert_eq!(frames[0].get("line").as_i64(), Some(5));
+            let res = client.success(
+                "evaluate",
+                Json::object([("expression", "sum".into()), ("frameId", 0i64.into())]),
+            );
+            assert_eq!(res.get("result").as_str(), Some("13"));
+
+            client.success("continue", Json::object([("threadId", 1i64.into())]));
+            let res = client.request("stackTrace", Json::object([]));
+            assert_eq!(res.get("success"), &Json::Bool(false));
+        });
+
+        debugger.wait_for_client();
+        let rt = Runtime::new().unwrap();
+        rt.set_debugger(Some(debugger));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let res: i32 = ctx
+                .eval_with_options(
+                    r#"function make(offset) {
+                        return function add(a, b) {
+                            const point = { x: a, y: b };
+                            const sum = point.x + point.y + offset;
+                            return sum;
+                        };
+                    }
+                    make(10)(1, 2)
+                    "#,
+                    EvalOptions::default().filename("test.js"),
+                )
+                .unwrap();
+            assert_eq!(res, 13);
+        });
+        client.join().unwrap();
+    }
+
+    #[test]
+    fn condition_and_terminate() {
+        let (debugger, mut client) = Client::connect();
+        let client = thread::spawn(move || {
+            client.attach(vec![breakpoint(3, Some("i == 5"))].into());
+
+            let stopped = client.event("stopped");
+            assert_eq!(stopped.get("reason").as_str(), Some("breakpoint"));
+            let res = client.success("evaluate", Json::object([("expression", "i".into())]));
+            assert_eq!(res.get("result").as_str(), Some("5"));
+
+            client.success(
+                "disconnect",
+                Json::object([("terminateDebuggee", true.into())]),
+            );
+        });
+
+        debugger.wait_for_client();
+        let rt = Runtime::new().unwrap();
+        rt.set_debugger(Some(debugger));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let res = ctx.eval_with_options::<(), _>(
+                r#"let i = 0;
+                while (true) {
+                    i++;
+                }
+                "#,
+                EvalOptions::default().filename("test.js"),
+            );
+            assert!(matches!(res, Err(Error::Exception)));
+            let i: i32 = ctx.eval("i").unwrap();
+            assert_eq!(i, 5);
+        });
+        client.join().unwrap();
+    }
+}
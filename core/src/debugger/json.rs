@@ -0,0 +1,331 @@
+//! The minimal JSON support needed for Debug Adapter Protocol messages.
+
+use std::fmt::{self, Write};
+
+use crate::StdString;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(StdString),
+    Array(Vec<Json>),
+    Object(Vec<(StdString, Json)>),
+}
+
+impl Json {
+    /// Build an object from its fields.
+    pub fn object<const N: usize>(fields: [(&str, Json); N]) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
+
+    pub fn parse(source: &str) -> Option<Self> {
+        let mut parser = Parser {
+            bytes: source.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.whitespace();
+        (parser.pos == parser.bytes.len()).then_some(value)
+    }
+
+    /// Returns the field of an object, `Null` if there is no such field.
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .unwrap_or(&Json::Null),
+            _ => &Json::Null,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Json::Number(x) if x.fract() == 0.0 => Some(x as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(x) => x,
+            _ => &[],
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+
+impl From<i64> for Json {
+    fn from(value: i64) -> Self {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<u32> for Json {
+    fn from(value: u32) -> Self {
+        Json::Number(value.into())
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.into())
+    }
+}
+
+impl From<StdString> for Json {
+    fn from(value: StdString) -> Self {
+        Json::String(value)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(value: Vec<Json>) -> Self {
+        Json::Array(value)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Json::Null)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(x) => write!(f, "{x}"),
+            Json::Number(x) if x.is_finite() => write!(f, "{x}"),
+            Json::Number(_) => f.write_str("null"),
+            Json::String(x) => write_string(f, x),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.bytes.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, literal: &str) -> bool {
+        if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.whitespace();
+        match *self.bytes.get(self.pos)? {
+            b'n' if self.eat("null") => Some(Json::Null),
+            b't' if self.eat("true") => Some(Json::Bool(true)),
+            b'f' if self.eat("false") => Some(Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.eat("]") {
+                    return Some(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.whitespace();
+                    if self.eat("]") {
+                        return Some(Json::Array(items));
+                    }
+                    if !self.eat(",") {
+                        return None;
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.whitespace();
+                if self.eat("}") {
+                    return Some(Json::Object(fields));
+                }
+                loop {
+                    self.whitespace();
+                    let key = self.string()?;
+                    self.whitespace();
+                    if !self.eat(":") {
+                        return None;
+                    }
+                    fields.push((key, self.value()?));
+                    self.whitespace();
+                    if self.eat("}") {
+                        return Some(Json::Object(fields));
+                    }
+                    if !self.eat(",") {
+                        return None;
+                    }
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                let start = self.pos;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+                    self.bytes.get(self.pos)
+                {
+                    self.pos += 1;
+                }
+                let number = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+                number.parse().ok().map(Json::Number)
+            }
+            _ => None,
+        }
+    }
+
+    fn string(&mut self) -> Option<StdString> {
+        if !self.eat("\"") {
+            return None;
+        }
+        let mut res = Vec::new();
+        loop {
+            let byte = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            match byte {
+                b'"' => return StdString::from_utf8(res).ok(),
+                b'\\' => {
+                    let escape = *self.bytes.get(self.pos)?;
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let high = self.hex4()?;
+                            if (0xD800..0xDC00).contains(&high) && self.eat("\\u") {
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return None;
+                                }
+                                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                                char::from_u32(code)?
+                            } else {
+                                char::from_u32(high).unwrap_or('\u{FFFD}')
+                            }
+                        }
+                        _ => return None,
+                    };
+                    res.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => res.push(byte),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = std::str::from_utf8(self.bytes.get(self.pos..self.pos + 4)?).ok()?;
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Json;
+
+    #[test]
+    fn round_trip() {
+        let source = r#"{"seq":1,"type":"request","arguments":{"lines":[1,2.5,-3e2],"ok":true,"none":null,"text":"a\"b\\c\né😀"}}"#;
+        let json = Json::parse(source).unwrap();
+        assert_eq!(json.get("seq").as_i64(), Some(1));
+        assert_eq!(json.get("type").as_str(), Some("request"));
+        let args = json.get("arguments");
+        assert_eq!(args.get("lines").as_array().len(), 3);
+        assert_eq!(args.get("ok").as_bool(), Some(true));
+        assert_eq!(args.get("text").as_str(), Some("a\"b\\c\né😀"));
+        assert_eq!(args.get("missing"), &Json::Null);
+        assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+
+        assert!(Json::parse("{\"a\":}").is_none());
+        assert!(Json::parse("[1,2] 3").is_none());
+    }
+}
@@ -0,0 +1,251 @@
+//! Events emitted between Rust and JavaScript.
+//!
+//! An [`EventEmitter`] is an object with the `on`, `once`, `off` and `emit` methods of the
+//! `EventEmitter` of Node.js. Rust code emits events into the context with
+//! [`EventEmitter::emit`], which queues the call of the listeners as a job. With the `stream`
+//! feature it subscribes to the events emitted by scripts as a `Stream` with
+//! `EventEmitter::subscribe`.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, events::EventEmitter};
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let host = EventEmitter::new(ctx.clone()).unwrap();
+//!     ctx.globals().set("host", host.clone()).unwrap();
+//!     ctx.eval::<(), _>("host.on('listen', (port) => { globalThis.port = port })")
+//!         .unwrap();
+//!     host.emit("listen", 8080).unwrap();
+//! });
+//! while rt.is_job_pending() {
+//!     rt.execute_pending_job().unwrap();
+//! }
+//! ctx.with(|ctx| assert_eq!(ctx.globals().get::<_, u16>("port").unwrap(), 8080));
+//! ```
+
+use crate::{function::This, Ctx, Error, FromJs, Function, IntoJs, Object, Result, Value};
+
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "stream")))]
+pub use stream::EventStream;
+
+/// Creates the object of an emitter, with its listeners stored by event name.
+const EMITTER: &str = r#"
+(() => {
+    const listeners = new Map();
+    const add = (name, listener, once) => {
+        if (typeof listener !== "function") {
+            throw new TypeError("The listener must be a function");
+        }
+        let list = listeners.get(name);
+        if (list === undefined) {
+            list = [];
+            listeners.set(name, list);
+        }
+        list.push({ listener, once });
+    };
+    const remove = (name, entry) => {
+        const list = listeners.get(name);
+        const index = list === undefined ? -1 : list.indexOf(entry);
+        if (index >= 0) {
+            list.splice(index, 1);
+            if (list.length === 0) {
+                listeners.delete(name);
+            }
+        }
+    };
+    return {
+        on(name, listener) {
+            add(name, listener, false);
+            return this;
+        },
+        once(name, listener) {
+            add(name, listener, true);
+            return this;
+        },
+        off(name, listener) {
+            const list = listeners.get(name);
+            const entry = list === undefined ? undefined : list.find((x) => x.listener === listener);
+            remove(name, entry);
+            return this;
+        },
+        emit(name, ...args) {
+            const list = listeners.get(name);
+            if (list === undefined) {
+                return false;
+            }
+            // Listeners added or removed by a listener don't change the current emission.
+            for (const entry of [...list]) {
+                if (entry.once) {
+                    remove(name, entry);
+                }
+                entry.listener.apply(this, args);
+            }
+            return true;
+        },
+        listenerCount(name) {
+            const list = listeners.get(name);
+            return list === undefined ? 0 : list.length;
+        },
+    };
+})()
+"#;
+
+/// An object emitting named events to the listeners registered by Rust and JavaScript.
+///
+/// Any object with `on`, `off` and `emit` methods can be converted into an emitter, so the
+/// emitters implemented by scripts can be used from Rust as well.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventEmitter<'js>(Object<'js>);
+
+impl<'js> EventEmitter<'js> {
+    /// Create an emitter without listeners.
+    pub fn new(ctx: Ctx<'js>) -> Result<Self> {
+        ctx.eval(EMITTER).map(EventEmitter)
+    }
+
+    /// Reference to the object of the emitter.
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert the emitter into its object.
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Emit an event with the payload as argument of the listeners.
+    ///
+    /// The listeners are called by a job, when the pending jobs of the runtime are executed,
+    /// so this can be called while a script is running. An exception thrown by a listener is
+    /// returned by the execution of the job.
+    pub fn emit<T: IntoJs<'js>>(&self, name: &str, payload: T) -> Result<()> {
+        let emit: Function = self.0.get("emit")?;
+        emit.defer_on(self.0.clone(), (name, payload))
+    }
+
+    /// Register a function listening to an event, called with the arguments of every emission.
+    pub fn on(&self, name: &str, listener: Function<'js>) -> Result<()> {
+        let on: Function = self.0.get("on")?;
+        on.call((This(self.0.clone()), name, listener))
+    }
+
+    /// Remove a listener registered with [`EventEmitter::on`] or by a script.
+    pub fn off(&self, name: &str, listener: Function<'js>) -> Result<()> {
+        let off: Function = self.0.get("off")?;
+        off.call((This(self.0.clone()), name, listener))
+    }
+}
+
+impl<'js> FromJs<'js> for EventEmitter<'js> {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let type_name = value.type_name();
+        let object = Object::from_js(ctx, value)?;
+        for method in ["on", "off", "emit"] {
+            if !object.get::<_, Value>(method)?.is_function() {
+                return Err(Error::new_from_js_message(
+                    type_name,
+                    "EventEmitter",
+                    format!("the object has no `{}` method", method),
+                ));
+            }
+        }
+        Ok(EventEmitter(object))
+    }
+}
+
+impl<'js> IntoJs<'js> for EventEmitter<'js> {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.0.into_js(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EventEmitter;
+    use crate::{Context, Function, Runtime, StdString};
+
+    fn run_jobs(rt: &Runtime) {
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+    }
+
+    #[test]
+    fn listeners() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let emitter = EventEmitter::new(ctx.clone()).unwrap();
+            ctx.globals().set("emitter", emitter.clone()).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                    globalThis.log = [];
+                    const add = (x) => log.push("on " + x);
+                    emitter
+                        .on("data", add)
+                        .once("data", (x) => log.push("once " + x))
+                        .on("other", () => log.push("other"));
+                    log.push(emitter.emit("data", 1), emitter.emit("missing"));
+                    emitter.emit("data", 2);
+                    emitter.off("data", add);
+                    log.push(emitter.emit("data", 3), emitter.listenerCount("other"));
+                "#,
+            )
+            .unwrap();
+            let log: Vec<StdString> = ctx.eval("log.map(String)").unwrap();
+            assert_eq!(
+                log,
+                ["on 1", "once 1", "true", "false", "on 2", "false", "1"]
+            );
+
+            let listener = Function::new(ctx.clone(), |x: i32| x).unwrap();
+            emitter.on("rust", listener.clone()).unwrap();
+            emitter.off("rust", listener).unwrap();
+            let count: usize = ctx.eval("emitter.listenerCount('rust')").unwrap();
+            assert_eq!(count, 0);
+
+            ctx.eval::<(), _>("emitter.on('number', (x) => log.push(x * 2))")
+                .unwrap();
+            emitter.emit("number", 21).unwrap();
+            // The listeners are called by a job.
+            let len: usize = ctx.eval("log.length").unwrap();
+            assert_eq!(len, 7);
+        });
+        run_jobs(&rt);
+        ctx.with(|ctx| {
+            let last: i32 = ctx.eval("log[log.length - 1]").unwrap();
+            assert_eq!(last, 42);
+        });
+    }
+
+    #[test]
+    fn script_emitter() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let emitter: EventEmitter = ctx
+                .eval(
+                    r#"
+                        globalThis.emitter = {
+                            received: [],
+                            on() {},
+                            off() {},
+                            emit(name, x) { this.received.push(name + " " + x) },
+                        };
+                        emitter
+                    "#,
+                )
+                .unwrap();
+            emitter.emit("ready", true).unwrap();
+            assert!(ctx.eval::<EventEmitter, _>("({ on() {} })").is_err());
+        });
+        run_jobs(&rt);
+        ctx.with(|ctx| {
+            let received: Vec<StdString> = ctx.eval("emitter.received").unwrap();
+            assert_eq!(received, ["ready true"]);
+        });
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use super::EventEmitter;
+use crate::{function::Opt, Ctx, FromJs, Function, Result, StdString, Value};
+
+#[derive(Default)]
+struct Queue<'js> {
+    payloads: VecDeque<Value<'js>>,
+    waker: Option<Waker>,
+}
+
+/// The events of an emitter as a [`Stream`], returned by [`EventEmitter::subscribe`].
+///
+/// Every emission yields its first argument converted into `T`. The payloads are queued until
+/// the stream is polled, and the stream never ends. Dropping the stream removes its listener.
+pub struct EventStream<'js, T> {
+    emitter: EventEmitter<'js>,
+    name: StdString,
+    listener: Function<'js>,
+    queue: Rc<RefCell<Queue<'js>>>,
+    marker: PhantomData<T>,
+}
+
+impl<'js> EventEmitter<'js> {
+    /// Subscribe to an event, returning the payloads of the emissions as a stream.
+    ///
+    /// ```
+    /// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, events::EventEmitter};
+    /// # use futures_rs::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let rt = AsyncRuntime::new().unwrap();
+    /// let ctx = AsyncContext::full(&rt).await.unwrap();
+    /// async_with!(ctx => |ctx| {
+    ///     let plugin = EventEmitter::new(ctx.clone()).unwrap();
+    ///     let mut logs = plugin.subscribe::<String>("log").unwrap();
+    ///     ctx.globals().set("plugin", plugin).unwrap();
+    ///     ctx.eval::<(), _>("plugin.emit('log', 'loaded')").unwrap();
+    ///     assert_eq!(logs.next().await.unwrap().unwrap(), "loaded");
+    /// })
+    /// .await;
+    /// # }
+    /// ```
+    pub fn subscribe<T: FromJs<'js>>(&self, name: &str) -> Result<EventStream<'js, T>> {
+        let queue = Rc::new(RefCell::new(Queue::default()));
+        let listener_queue = queue.clone();
+        let listener = Function::new(
+            self.0.ctx().clone(),
+            move |ctx: Ctx<'js>, payload: Opt<Value<'js>>| {
+                let mut queue = listener_queue.borrow_mut();
+                let payload = payload.0.unwrap_or_else(|| Value::new_undefined(ctx));
+                queue.payloads.push_back(payload);
+                if let Some(waker) = queue.waker.take() {
+                    waker.wake();
+                }
+            },
+        )?;
+        self.on(name, listener.clone())?;
+        Ok(EventStream {
+            emitter: self.clone(),
+            name: name.into(),
+            listener,
+            queue,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'js, T: FromJs<'js>> Stream for EventStream<'js, T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut queue = self.queue.borrow_mut();
+        match queue.payloads.pop_front() {
+            Some(payload) => {
+                let ctx = self.emitter.0.ctx();
+                Poll::Ready(Some(T::from_js(ctx, payload)))
+            }
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<'js, T> Drop for EventStream<'js, T> {
+    fn drop(&mut self) {
+        if self.emitter.off(&self.name, self.listener.clone()).is_err() {
+            self.emitter.0.ctx().catch();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use futures_rs::StreamExt;
+
+    use super::EventEmitter;
+    use crate::{async_with, AsyncContext, AsyncRuntime, Error};
+
+    #[tokio::test]
+    async fn subscribe() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            let emitter = EventEmitter::new(ctx.clone()).unwrap();
+            let mut numbers = emitter.subscribe::<i32>("number").unwrap();
+            ctx.globals().set("emitter", emitter.clone()).unwrap();
+            ctx.eval::<(), _>(
+                r#"
+                    emitter.emit("number", 1);
+                    emitter.emit("other", 2);
+                    emitter.emit("number", "three");
+                "#,
+            )
+            .unwrap();
+            // Events emitted from Rust are seen by Rust subscribers too, once the job ran.
+            emitter.emit("number", 4).unwrap();
+            assert_eq!(numbers.next().await.unwrap().unwrap(), 1);
+            assert!(matches!(
+                numbers.next().await.unwrap(),
+                Err(Error::FromJs { .. })
+            ));
+            assert_eq!(numbers.next().await.unwrap().unwrap(), 4);
+
+            drop(numbers);
+            let count: usize = ctx.eval("emitter.listenerCount('number')").unwrap();
+            assert_eq!(count, 0);
+        })
+        .await;
+    }
+}
@@ -0,0 +1,519 @@
+//! A native `fs` module with the file system access provided by the embedder.
+//!
+//! [`FsModule`] provides `readFileSync`, `writeFileSync`, `readdirSync` and `statSync`, and the
+//! same operations returning promises in `promises`, like the `fs` module of Node.js. Every
+//! operation goes through the [`FsBackend`] stored in the runtime with
+//! [`Runtime::set_userdata`](crate::Runtime::set_userdata), so the embedder decides which files
+//! scripts can access. [`DirFs`] is a backend which only gives access to the files in a
+//! directory, optionally read only.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, Module, fs::{DirFs, Fs, FsModule}};
+//! # let dir = std::env::temp_dir().join(format!("rquickjs-fs-doc-{}", std::process::id()));
+//! # std::fs::create_dir_all(&dir).unwrap();
+//! # std::fs::write(dir.join("config.json"), r#"{ "port": 8080 }"#).unwrap();
+//! let rt = Runtime::new().unwrap();
+//! rt.set_userdata(Fs::new(DirFs::new(&dir).unwrap().read_only()));
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     Module::declare_def::<FsModule, _>(ctx.clone(), "fs").unwrap();
+//!     let module = Module::evaluate(
+//!         ctx.clone(),
+//!         "main",
+//!         r#"
+//!         import { readFileSync } from "fs";
+//!         export const port = JSON.parse(readFileSync("config.json", "utf8")).port;
+//!     "#,
+//!     )
+//!     .unwrap();
+//!     assert_eq!(module.get::<_, u16>("port").unwrap(), 8080);
+//! });
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+//!
+//! The backends are synchronous, the promises returned by the functions in `promises` are
+//! already settled when they are returned.
+
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    function::Opt,
+    globals::{buffer_source, settled},
+    markers::ParallelSend,
+    module::{Declarations, Exports, ModuleDef},
+    Ctx, Error, Exception, FromJs, Function, Object, Result, StdString, TypedArray, Value,
+};
+
+/// The kind of a file system entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+    /// Anything else, like a device or a socket.
+    Other,
+}
+
+/// The metadata of a file system entry returned by [`FsBackend::stat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub kind: FileKind,
+    /// The size in bytes.
+    pub size: u64,
+    /// The time of the last modification, if known.
+    pub modified: Option<SystemTime>,
+}
+
+/// The file system accessed by the [`FsModule`].
+///
+/// The paths are the ones given by the scripts, unchanged. The returned errors are thrown as
+/// errors with a Node.js like `code`, `ENOENT` for [`io::ErrorKind::NotFound`], `EACCES` for
+/// [`io::ErrorKind::PermissionDenied`], `EEXIST` for [`io::ErrorKind::AlreadyExists`] and
+/// `EIO` for other errors.
+pub trait FsBackend: ParallelSend + 'static {
+    /// Read the content of a file.
+    fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+
+    /// Replace the content of a file, creating it if it doesn't exist.
+    fn write(&self, path: &str, data: &[u8]) -> io::Result<()>;
+
+    /// List the names of the entries of a directory.
+    fn read_dir(&self, path: &str) -> io::Result<Vec<StdString>>;
+
+    /// Read the metadata of a file system entry.
+    fn stat(&self, path: &str) -> io::Result<FileStat>;
+}
+
+/// The file system backend of a runtime, stored with
+/// [`Runtime::set_userdata`](crate::Runtime::set_userdata).
+pub struct Fs(Box<dyn FsBackend>);
+
+impl Fs {
+    pub fn new<B: FsBackend>(backend: B) -> Self {
+        Fs(Box::new(backend))
+    }
+
+    fn get<'a>(ctx: &'a Ctx<'_>) -> Result<&'a dyn FsBackend> {
+        ctx.userdata::<Fs>()
+            .map(|fs| &*fs.0)
+            .ok_or_else(|| Exception::throw_internal(ctx, "No file system backend was set"))
+    }
+}
+
+/// A backend giving access to the files in a directory.
+///
+/// Paths are relative to the directory, also absolute ones, `..` components and symbolic
+/// links can't leave it. Paths through dangling symbolic links are rejected, as the target
+/// created by writing them could be outside of the directory.
+#[derive(Debug, Clone)]
+pub struct DirFs {
+    root: PathBuf,
+    read_only: bool,
+}
+
+impl DirFs {
+    /// Give access to the files in the directory.
+    pub fn new<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        Ok(DirFs {
+            root: root.as_ref().canonicalize()?,
+            read_only: false,
+        })
+    }
+
+    /// Reject all writes.
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    fn resolve(&self, path: &str) -> io::Result<PathBuf> {
+        let mut resolved = self.root.clone();
+        let mut depth = 0usize;
+        for component in Path::new(path).components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+                Component::ParentDir => {
+                    depth = depth.checked_sub(1).ok_or_else(outside_root)?;
+                    resolved.pop();
+                }
+                Component::Normal(name) => {
+                    depth += 1;
+                    resolved.push(name);
+                }
+            }
+        }
+        // Symbolic links can point outside of the root, the existing part of the path must
+        // stay inside of it once they are followed. Links are existing entries, also when they
+        // are dangling, and the missing part can't contain any.
+        let mut existing = resolved.as_path();
+        let mut missing = Vec::new();
+        loop {
+            match fs::symlink_metadata(existing) {
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    missing.extend(existing.file_name());
+                    existing = existing.parent().ok_or_else(outside_root)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let mut canonical = existing.canonicalize().map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "permission denied, the path contains a dangling symbolic link",
+            ),
+            _ => e,
+        })?;
+        if !canonical.starts_with(&self.root) {
+            return Err(outside_root());
+        }
+        canonical.extend(missing.into_iter().rev());
+        Ok(canonical)
+    }
+}
+
+fn outside_root() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        "permission denied, the path is outside of the root",
+    )
+}
+
+impl FsBackend for DirFs {
+    fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.resolve(path)?)
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> io::Result<()> {
+        if self.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "read-only file system",
+            ));
+        }
+        fs::write(self.resolve(path)?, data)
+    }
+
+    fn read_dir(&self, path: &str) -> io::Result<Vec<StdString>> {
+        let mut names = fs::read_dir(self.resolve(path)?)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<_>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    fn stat(&self, path: &str) -> io::Result<FileStat> {
+        let metadata = fs::metadata(self.resolve(path)?)?;
+        let kind = if metadata.is_file() {
+            FileKind::File
+        } else if metadata.is_dir() {
+            FileKind::Directory
+        } else {
+            FileKind::Other
+        };
+        Ok(FileStat {
+            kind,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Throws an error for a failed operation, like `ENOENT: no such file, open 'a.txt'`.
+fn throw_io(ctx: &Ctx<'_>, error: io::Error, syscall: &str, path: &str) -> Error {
+    let code = match error.kind() {
+        io::ErrorKind::NotFound => "ENOENT",
+        io::ErrorKind::PermissionDenied => "EACCES",
+        io::ErrorKind::AlreadyExists => "EEXIST",
+        _ => "EIO",
+    };
+    let message = format!("{}: {}, {} '{}'", code, error, syscall, path);
+    let exception = match Exception::from_message(ctx.clone(), &message) {
+        Ok(x) => x,
+        Err(e) => return e,
+    };
+    let res = (|| {
+        exception.set("code", code)?;
+        exception.set("syscall", syscall)?;
+        exception.set("path", path)
+    })();
+    match res {
+        Ok(()) => exception.throw(),
+        Err(e) => e,
+    }
+}
+
+fn read_file<'js>(ctx: Ctx<'js>, path: StdString, options: Opt<Value<'js>>) -> Result<Value<'js>> {
+    let encoding = match options.0 {
+        Some(options) if options.is_object() => options
+            .into_object()
+            .unwrap()
+            .get::<_, Option<StdString>>("encoding")?,
+        Some(options) => Option::<StdString>::from_js(&ctx, options)?,
+        None => None,
+    };
+    let bytes = Fs::get(&ctx)?
+        .read(&path)
+        .map_err(|e| throw_io(&ctx, e, "open", &path))?;
+    match encoding.as_deref() {
+        None => Ok(TypedArray::<u8>::new(ctx, bytes)?.into_value()),
+        Some("utf8" | "utf-8") => {
+            let text = StdString::from_utf8(bytes)
+                .unwrap_or_else(|e| StdString::from_utf8_lossy(e.as_bytes()).into_owned());
+            Ok(crate::String::from_str(ctx, &text)?.into_value())
+        }
+        Some(encoding) => Err(Exception::throw_type(
+            &ctx,
+            &format!("Unsupported encoding '{}'", encoding),
+        )),
+    }
+}
+
+fn write_file<'js>(ctx: Ctx<'js>, path: StdString, data: Value<'js>) -> Result<()> {
+    let bytes = match data.as_string() {
+        Some(text) => text.to_string()?.into_bytes(),
+        None => buffer_source(&ctx, Some(data))?,
+    };
+    Fs::get(&ctx)?
+        .write(&path, &bytes)
+        .map_err(|e| throw_io(&ctx, e, "open", &path))
+}
+
+fn read_dir(ctx: Ctx<'_>, path: StdString) -> Result<Vec<StdString>> {
+    Fs::get(&ctx)?
+        .read_dir(&path)
+        .map_err(|e| throw_io(&ctx, e, "scandir", &path))
+}
+
+fn stat(ctx: Ctx<'_>, path: StdString) -> Result<Object<'_>> {
+    let stat = Fs::get(&ctx)?
+        .stat(&path)
+        .map_err(|e| throw_io(&ctx, e, "stat", &path))?;
+    let object = Object::new(ctx.clone())?;
+    object.set("size", stat.size as f64)?;
+    let modified = stat
+        .modified
+        .and_then(|x| x.duration_since(UNIX_EPOCH).ok())
+        .map(|x| x.as_secs_f64() * 1000.0);
+    object.set("mtimeMs", modified)?;
+    let kind = stat.kind;
+    let is_file = Function::new(ctx.clone(), move || kind == FileKind::File)?;
+    object.set("isFile", is_file.with_name("isFile")?)?;
+    let is_directory = Function::new(ctx.clone(), move || kind == FileKind::Directory)?;
+    object.set("isDirectory", is_directory.with_name("isDirectory")?)?;
+    Ok(object)
+}
+
+/// The `fs` module, declared with [`Module::declare_def`](crate::Module::declare_def) or a
+/// [`ModuleLoader`](crate::loader::ModuleLoader).
+///
+/// Importing the module fails when no [`Fs`] is stored in the runtime.
+pub struct FsModule;
+
+impl ModuleDef for FsModule {
+    fn declare(declare: &mut Declarations) -> Result<()> {
+        for name in [
+            "readFileSync",
+            "writeFileSync",
+            "readdirSync",
+            "statSync",
+            "promises",
+            "default",
+        ] {
+            declare.declare(name)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, exports: &mut Exports<'js>) -> Result<()> {
+        Fs::get(ctx)?;
+        let read_file_sync = Function::new(ctx.clone(), read_file)?.with_name("readFileSync")?;
+        let write_file_sync = Function::new(ctx.clone(), write_file)?.with_name("writeFileSync")?;
+        let read_dir_sync = Function::new(ctx.clone(), read_dir)?.with_name("readdirSync")?;
+        let stat_sync = Function::new(ctx.clone(), stat)?.with_name("statSync")?;
+
+        let promises = Object::new(ctx.clone())?;
+        let func = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, path: StdString, options: Opt<Value<'js>>| {
+                settled(&ctx, read_file(ctx.clone(), path, options))
+            },
+        )?;
+        promises.set("readFile", func.with_name("readFile")?)?;
+        let func = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, path: StdString, data: Value<'js>| {
+                settled(&ctx, write_file(ctx.clone(), path, data))
+            },
+        )?;
+        promises.set("writeFile", func.with_name("writeFile")?)?;
+        let func = Function::new(ctx.clone(), |ctx: Ctx<'js>, path: StdString| {
+            settled(&ctx, read_dir(ctx.clone(), path))
+        })?;
+        promises.set("readdir", func.with_name("readdir")?)?;
+        let func = Function::new(ctx.clone(), |ctx: Ctx<'js>, path: StdString| {
+            settled(&ctx, stat(ctx.clone(), path))
+        })?;
+        promises.set("stat", func.with_name("stat")?)?;
+
+        let default = Object::new(ctx.clone())?;
+        default.set("readFileSync", read_file_sync.clone())?;
+        default.set("writeFileSync", write_file_sync.clone())?;
+        default.set("readdirSync", read_dir_sync.clone())?;
+        default.set("statSync", stat_sync.clone())?;
+        default.set("promises", promises.clone())?;
+
+        exports.export("readFileSync", read_file_sync)?;
+        exports.export("writeFileSync", write_file_sync)?;
+        exports.export("readdirSync", read_dir_sync)?;
+        exports.export("statSync", stat_sync)?;
+        exports.export("promises", promises)?;
+        exports.export("default", default)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use super::{DirFs, Fs, FsModule};
+    use crate::{CatchResultExt, Context, Module, Runtime, StdString};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rquickjs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("root/data")).unwrap();
+        fs::write(dir.join("root/data/a.txt"), "hello").unwrap();
+        fs::write(dir.join("secret.txt"), "secret").unwrap();
+        dir
+    }
+
+    fn run(fs: DirFs, source: &str) -> StdString {
+        let rt = Runtime::new().unwrap();
+        rt.set_userdata(Fs::new(fs));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            Module::declare_def::<FsModule, _>(ctx.clone(), "fs").unwrap();
+            let _ = Module::evaluate(ctx.clone(), "main", source)
+                .catch(&ctx)
+                .unwrap();
+        });
+        while rt.is_job_pending() {
+            rt.execute_pending_job().unwrap();
+        }
+        ctx.with(|ctx| ctx.globals().get("res").unwrap())
+    }
+
+    #[test]
+    fn read_write() {
+        let dir = temp_dir("fs-read-write");
+        let res = run(
+            DirFs::new(dir.join("root")).unwrap(),
+            r#"
+                import fs, { readFileSync, writeFileSync, readdirSync, statSync } from "fs";
+                writeFileSync("/data/b.txt", "written");
+                writeFileSync("data/c.bin", new Uint8Array([1, 2, 3]));
+                const codes = [];
+                for (const path of ["../secret.txt", "data/../../secret.txt", "missing.txt"]) {
+                    try {
+                        readFileSync(path);
+                    } catch (e) {
+                        codes.push(e.code);
+                    }
+                }
+                const stat = statSync("data");
+                globalThis.res = [
+                    readFileSync("data/a.txt", "utf8"),
+                    readFileSync("data/b.txt", { encoding: "utf-8" }),
+                    readFileSync("data/c.bin").join(""),
+                    readdirSync("data").join(),
+                    stat.isDirectory(),
+                    stat.isFile(),
+                    statSync("data/a.txt").size,
+                    codes.join(),
+                    fs.readFileSync === readFileSync,
+                ].join(" ");
+                fs.promises.readFile("data/a.txt", "utf8").then((x) => { globalThis.res += " " + x });
+                fs.promises.stat("missing").catch((e) => { globalThis.res += " " + e.code });
+            "#,
+        );
+        assert_eq!(
+            res,
+            "hello written 123 a.txt,b.txt,c.bin true false 5 EACCES,EACCES,ENOENT true hello ENOENT"
+        );
+        assert_eq!(fs::read(dir.join("root/data/c.bin")).unwrap(), [1, 2, 3]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_only() {
+        let dir = temp_dir("fs-read-only");
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("secret.txt"), dir.join("root/link.txt")).unwrap();
+        let res = run(
+            DirFs::new(dir.join("root")).unwrap().read_only(),
+            r#"
+                import { readFileSync, writeFileSync } from "fs";
+                let res = readFileSync("data/a.txt", "utf8");
+                try {
+                    writeFileSync("data/a.txt", "changed");
+                } catch (e) {
+                    res += " " + e.message;
+                }
+                try {
+                    readFileSync("link.txt");
+                    res += " linked";
+                } catch (e) {
+                    res += " " + e.code;
+                }
+                globalThis.res = res;
+            "#,
+        );
+        let link = if cfg!(unix) { "EACCES" } else { "ENOENT" };
+        assert_eq!(
+            res,
+            format!(
+                "hello EACCES: read-only file system, open 'data/a.txt' {}",
+                link
+            )
+        );
+        assert_eq!(fs::read(dir.join("root/data/a.txt")).unwrap(), b"hello");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dangling_links() {
+        use std::os::unix::fs::symlink;
+
+        let dir = temp_dir("fs-dangling-links");
+        symlink(dir.join("outside.txt"), dir.join("root/dangling")).unwrap();
+        symlink(dir.join("missing"), dir.join("root/dangling_dir")).unwrap();
+        symlink(dir.join("root/data"), dir.join("root/data_link")).unwrap();
+        let res = run(
+            DirFs::new(dir.join("root")).unwrap(),
+            r#"
+                import { readFileSync, writeFileSync } from "fs";
+                const codes = [];
+                for (const path of ["dangling", "dangling_dir/new.txt", "data_link/new.txt"]) {
+                    try {
+                        writeFileSync(path, "written");
+                        codes.push("ok");
+                    } catch (e) {
+                        codes.push(e.code);
+                    }
+                }
+                codes.push(readFileSync("data/new.txt", "utf8"));
+                globalThis.res = codes.join();
+            "#,
+        );
+        assert_eq!(res, "EACCES,EACCES,ok,written");
+        assert!(!dir.join("outside.txt").exists());
+        assert!(!dir.join("missing").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+//! Optional implementations of web platform globals.
+//!
+//! QuickJS only implements the ECMAScript standard library, these modules provide commonly used
+//! globals from the web platform, and a `process` global for command line scripts. Every set of
+//! globals is behind its own feature and installed into a context with a single `init_*` call.
+
+#[cfg(any(
+    feature = "text-encoding",
+    feature = "fetch",
+    feature = "crypto",
+    feature = "fs"
+))]
+use crate::{ArrayBuffer, Ctx, Exception, Result, Value};
+#[cfg(any(feature = "fetch", feature = "crypto", feature = "fs"))]
+use crate::{CatchResultExt, CaughtError, IntoJs, Object};
+
+#[cfg(feature = "text-encoding")]
+mod text_encoding;
+#[cfg(feature = "text-encoding")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "text-encoding")))]
+pub use text_encoding::{init_text_encoding, TextDecoder, TextEncoder};
+
+#[cfg(feature = "url")]
+pub(crate) mod url;
+#[cfg(feature = "url")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "url")))]
+pub use url::{init_url, Url, UrlSearchParams};
+
+#[cfg(feature = "fetch")]
+mod fetch;
+#[cfg(feature = "reqwest")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "reqwest")))]
+pub use fetch::ReqwestBackend;
+#[cfg(feature = "fetch")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "fetch")))]
+pub use fetch::{
+    init_fetch, Headers, HttpBackend, HttpError, HttpFuture, HttpRequest, HttpResponse, Response,
+};
+
+#[cfg(feature = "crypto")]
+mod crypto;
+#[cfg(feature = "crypto")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "crypto")))]
+pub use crypto::init_crypto;
+
+#[cfg(feature = "performance")]
+mod performance;
+#[cfg(feature = "performance")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "performance")))]
+pub use performance::{init_performance, EntryType, Performance, PerformanceEntry};
+
+#[cfg(feature = "process")]
+mod process;
+#[cfg(feature = "process")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "process")))]
+pub use process::{init_process, ExitHandler, ProcessOptions};
+
+/// Returns a copy of the bytes of an `ArrayBuffer` or a view on one.
+#[cfg(any(
+    feature = "text-encoding",
+    feature = "fetch",
+    feature = "crypto",
+    feature = "fs"
+))]
+pub(crate) fn buffer_source<'js>(ctx: &Ctx<'js>, value: Option<Value<'js>>) -> Result<Vec<u8>> {
+    let Some(value) = value.filter(|x| !x.is_undefined()) else {
+        return Ok(Vec::new());
+    };
+    let invalid =
+        || Exception::throw_type(ctx, "The provided value is not an ArrayBuffer or a view");
+    let object = value.into_object().ok_or_else(invalid)?;
+    if let Some(buffer) = ArrayBuffer::from_object(object.clone()) {
+        return Ok(buffer.as_bytes().unwrap_or_default().to_vec());
+    }
+    let buffer: Option<ArrayBuffer> = object.get("buffer").ok();
+    let buffer = buffer.ok_or_else(invalid)?;
+    let offset: usize = object.get("byteOffset")?;
+    let len: usize = object.get("byteLength")?;
+    let bytes = buffer.as_bytes().unwrap_or_default();
+    Ok(bytes.get(offset..offset + len).unwrap_or_default().to_vec())
+}
+
+/// Returns a promise which is already settled with the result.
+#[cfg(any(feature = "fetch", feature = "crypto", feature = "fs"))]
+pub(crate) fn settled<'js, T: IntoJs<'js>>(
+    ctx: &Ctx<'js>,
+    result: Result<T>,
+) -> Result<Object<'js>> {
+    let (promise, resolve, reject) = ctx.promise()?;
+    match result.and_then(|x| x.into_js(ctx)).catch(ctx) {
+        Ok(x) => resolve.call::<_, ()>((x,))?,
+        Err(CaughtError::Exception(e)) => reject.call::<_, ()>((e,))?,
+        Err(CaughtError::Value(e)) => reject.call::<_, ()>((e,))?,
+        Err(CaughtError::Error(e)) => return Err(e),
+    }
+    Ok(promise)
+}
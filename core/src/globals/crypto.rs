@@ -0,0 +1,243 @@
+use std::{fmt::Write, io, slice};
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::{
+    atom::PredefinedAtom, convert::Coerced, ArrayBuffer, Ctx, Exception, Function, Object, Result,
+    StdString, Value,
+};
+
+use super::{buffer_source, settled};
+
+/// The maximum number of bytes `getRandomValues` fills at once.
+const MAX_RANDOM_BYTES: usize = 65536;
+
+/// Install the `crypto` global into a context.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, globals};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     globals::init_crypto(&ctx).unwrap();
+///     let res: usize = ctx
+///         .eval("crypto.getRandomValues(new Uint8Array(16)).length")
+///         .unwrap();
+///     assert_eq!(res, 16);
+/// });
+/// ```
+///
+/// This provides `crypto.getRandomValues`, `crypto.randomUUID` and `crypto.subtle.digest` with
+/// the SHA-1, SHA-256, SHA-384 and SHA-512 algorithms of the `sha1` and `sha2` crates. The
+/// random values come from the operating system through the `getrandom` crate.
+pub fn init_crypto<'js>(ctx: &Ctx<'js>) -> Result<()> {
+    let crypto = Object::new(ctx.clone())?;
+
+    let get_random_values = Function::new(ctx.clone(), get_random_values)?;
+    crypto.set(
+        "getRandomValues",
+        get_random_values.with_name("getRandomValues")?,
+    )?;
+
+    let random_uuid = Function::new(ctx.clone(), |ctx: Ctx| -> Result<StdString> {
+        let mut bytes = [0; 16];
+        fill_random(&ctx, &mut bytes)?;
+        // Set the version to 4 and the variant to RFC 4122.
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let mut res = StdString::with_capacity(36);
+        for (i, byte) in bytes.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                res.push('-');
+            }
+            write!(res, "{:02x}", byte).unwrap();
+        }
+        Ok(res)
+    })?;
+    crypto.set("randomUUID", random_uuid.with_name("randomUUID")?)?;
+
+    let subtle = Object::new(ctx.clone())?;
+    let digest = Function::new(
+        ctx.clone(),
+        |ctx: Ctx<'js>, algorithm: Value<'js>, data: Value<'js>| {
+            let res = digest(&ctx, algorithm, data);
+            settled(&ctx, res)
+        },
+    )?;
+    subtle.set("digest", digest.with_name("digest")?)?;
+    crypto.set("subtle", subtle)?;
+
+    ctx.globals().set("crypto", crypto)
+}
+
+/// Fill a buffer with cryptographically secure random bytes from the operating system.
+fn fill_random(ctx: &Ctx, buf: &mut [u8]) -> Result<()> {
+    os_random(buf)
+        .map_err(|e| Exception::throw_message(ctx, &format!("Failed to get random values: {}", e)))
+}
+
+#[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+fn os_random(buf: &mut [u8]) -> io::Result<()> {
+    getrandom::getrandom(buf).map_err(io::Error::from)
+}
+
+#[cfg(all(feature = "wasm", target_family = "wasm", target_os = "unknown"))]
+fn os_random(buf: &mut [u8]) -> io::Result<()> {
+    if crate::wasm::fill_random(buf) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the random source of the host failed or wasn't set with `wasm::init`",
+        ))
+    }
+}
+
+#[cfg(all(not(feature = "wasm"), target_family = "wasm", target_os = "unknown"))]
+fn os_random(_buf: &mut [u8]) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "no random source is available on this platform",
+    ))
+}
+
+fn get_random_values<'js>(ctx: Ctx<'js>, array: Object<'js>) -> Result<Object<'js>> {
+    let tag: Option<StdString> = array.get(PredefinedAtom::SymbolToStringTag)?;
+    let integer = matches!(
+        tag.as_deref(),
+        Some(
+            "Int8Array"
+                | "Uint8Array"
+                | "Uint8ClampedArray"
+                | "Int16Array"
+                | "Uint16Array"
+                | "Int32Array"
+                | "Uint32Array"
+                | "BigInt64Array"
+                | "BigUint64Array"
+        )
+    );
+    if !integer {
+        return Err(Exception::throw_type(
+            &ctx,
+            "The provided value is not an integer typed array",
+        ));
+    }
+    let buffer: ArrayBuffer = array.get("buffer")?;
+    let offset: usize = array.get("byteOffset")?;
+    let len: usize = array.get("byteLength")?;
+    if len > MAX_RANDOM_BYTES {
+        return Err(Exception::throw_range(
+            &ctx,
+            &format!(
+                "The typed array's byte length ({}) exceeds the number of bytes of entropy available ({})",
+                len, MAX_RANDOM_BYTES
+            ),
+        ));
+    }
+    let raw = buffer
+        .as_raw()
+        .filter(|raw| offset + len <= raw.len)
+        .ok_or_else(|| Exception::throw_type(&ctx, "The typed array is detached"))?;
+    let bytes = unsafe { slice::from_raw_parts_mut(raw.ptr.as_ptr().add(offset), len) };
+    fill_random(&ctx, bytes)?;
+    Ok(array)
+}
+
+fn digest<'js>(
+    ctx: &Ctx<'js>,
+    algorithm: Value<'js>,
+    data: Value<'js>,
+) -> Result<ArrayBuffer<'js>> {
+    let name: Coerced<StdString> = match algorithm.as_object() {
+        Some(algorithm) => algorithm.get("name")?,
+        None => algorithm.get()?,
+    };
+    let hash = match name.0.to_ascii_uppercase().as_str() {
+        "SHA-1" => hash::<Sha1>,
+        "SHA-256" => hash::<Sha256>,
+        "SHA-384" => hash::<Sha384>,
+        "SHA-512" => hash::<Sha512>,
+        _ => {
+            return Err(Exception::throw_type(
+                ctx,
+                &format!("Unrecognized algorithm name: {}", name.0),
+            ))
+        }
+    };
+    let data = buffer_source(ctx, Some(data))?;
+    ArrayBuffer::new(ctx.clone(), hash(&data))
+}
+
+fn hash<D: Digest>(data: &[u8]) -> Vec<u8> {
+    D::digest(data).to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{globals::init_crypto, test_with, CatchResultExt, Context, Runtime, StdString};
+
+    #[test]
+    fn random() {
+        test_with(|ctx| {
+            init_crypto(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const bytes = new Uint8Array(64);
+                    const errors = [];
+                    try { crypto.getRandomValues(new Float64Array(1)) } catch (e) { errors.push(e.name) }
+                    try { crypto.getRandomValues(new Uint8Array(65537)) } catch (e) { errors.push(e.name) }
+                    [
+                        String(crypto.getRandomValues(bytes) === bytes),
+                        String(bytes.some((x) => x !== 0)),
+                        String(/^[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$/.test(crypto.randomUUID())),
+                        String(crypto.randomUUID() !== crypto.randomUUID()),
+                        ...errors,
+                    ]
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                ["true", "true", "true", "true", "TypeError", "RangeError"]
+            );
+        })
+    }
+
+    #[test]
+    fn digest() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            init_crypto(&ctx).unwrap();
+            let _: () = ctx
+                .eval(
+                    r#"
+                    const hex = (buffer) => Array.from(new Uint8Array(buffer), (x) => x.toString(16).padStart(2, '0')).join('');
+                    const data = new Uint8Array([0x61, 0x62, 0x63]);
+                    globalThis.res = [];
+                    crypto.subtle.digest('SHA-256', data).then((x) => res.push(hex(x)));
+                    crypto.subtle.digest({ name: 'sha-1' }, data.buffer).then((x) => res.push(hex(x)));
+                    crypto.subtle.digest('MD5', data).catch((e) => res.push(e.name));
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+        });
+        while rt.execute_pending_job().unwrap() {}
+        ctx.with(|ctx| {
+            let res: Vec<StdString> = ctx.globals().get("res").unwrap();
+            assert_eq!(
+                res,
+                [
+                    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+                    "a9993e364706816aba3e25717850c26c9cd0d89d",
+                    "TypeError",
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,806 @@
+use std::{error::Error as StdError, future::Future, pin::Pin, rc::Rc};
+
+use crate::{
+    atom::PredefinedAtom,
+    class::{ClassId, JsClass, Trace, Tracer, Writable},
+    convert::Coerced,
+    function::{Opt, This},
+    object::Accessor,
+    promise::Promised,
+    value::Constructor,
+    ArrayBuffer, Class, Ctx, Exception, Function, Object, Result, StdString, Value,
+};
+
+use super::{
+    buffer_source, settled,
+    url::{array_iterator, init_list, serialize_form, UrlSearchParams},
+};
+
+/// A request made by a script with `fetch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    /// The method, standard methods are uppercase.
+    pub method: StdString,
+    /// The absolute URL.
+    pub url: StdString,
+    /// The headers, with lowercase names.
+    pub headers: Vec<(StdString, StdString)>,
+    /// The body, `None` for requests without one.
+    pub body: Option<Vec<u8>>,
+}
+
+/// The response to an [`HttpRequest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: StdString,
+    pub headers: Vec<(StdString, StdString)>,
+    pub body: Vec<u8>,
+}
+
+impl Default for HttpResponse {
+    fn default() -> Self {
+        HttpResponse {
+            status: 200,
+            status_text: StdString::new(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+/// The error returned by an [`HttpBackend`] when the request failed, `fetch` rejects with a
+/// `TypeError` containing its message.
+pub type HttpError = Box<dyn StdError + Send + Sync>;
+
+/// The future returned by an [`HttpBackend`].
+pub type HttpFuture = Pin<Box<dyn Future<Output = std::result::Result<HttpResponse, HttpError>>>>;
+
+/// The backend performing the network requests of `fetch`.
+///
+/// This is implemented for closures returning a future, which makes it possible to restrict or
+/// mock the network access of scripts.
+pub trait HttpBackend {
+    fn fetch(&self, request: HttpRequest) -> HttpFuture;
+}
+
+impl<F, R> HttpBackend for F
+where
+    F: Fn(HttpRequest) -> R,
+    R: Future<Output = std::result::Result<HttpResponse, HttpError>> + 'static,
+{
+    fn fetch(&self, request: HttpRequest) -> HttpFuture {
+        Box::pin(self(request))
+    }
+}
+
+/// An [`HttpBackend`] making the requests with a [`reqwest::Client`].
+///
+/// The client uses Tokio, so the [`AsyncRuntime`](crate::AsyncRuntime) must be driven within a
+/// Tokio runtime. Redirects are followed according to the policy of the client.
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestBackend {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestBackend {
+    /// Create a backend making the requests with the client.
+    pub fn new(client: reqwest::Client) -> Self {
+        ReqwestBackend { client }
+    }
+
+    /// The client making the requests.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl HttpBackend for ReqwestBackend {
+    fn fetch(&self, request: HttpRequest) -> HttpFuture {
+        let client = self.client.clone();
+        Box::pin(async move {
+            let method = reqwest::Method::from_bytes(request.method.as_bytes())?;
+            let mut builder = client.request(method, request.url.as_str());
+            for (name, value) in request.headers {
+                builder = builder.header(name, value);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+            let response = builder.send().await?;
+            let status = response.status();
+            let headers = response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = StdString::from_utf8_lossy(value.as_bytes()).into_owned();
+                    (name.as_str().into(), value)
+                })
+                .collect();
+            let body = response.bytes().await?.to_vec();
+            Ok(HttpResponse {
+                status: status.as_u16(),
+                status_text: status.canonical_reason().unwrap_or_default().into(),
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+/// Install the `fetch` function, and the `Headers` and `Response` classes, into a context.
+///
+/// All requests are made with the backend, the promises returned by `fetch` are resolved by the
+/// [`AsyncRuntime`](crate::AsyncRuntime) when the backend finishes.
+///
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, promise::Promise};
+/// # use rquickjs::globals::{self, HttpRequest, HttpResponse, HttpError};
+/// # async fn run() {
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     globals::init_fetch(&ctx, |request: HttpRequest| async move {
+///         Ok::<_, HttpError>(HttpResponse {
+///             body: format!("{} {}", request.method, request.url).into_bytes(),
+///             ..Default::default()
+///         })
+///     })
+///     .unwrap();
+///     let res: Promise<String> = ctx
+///         .eval("fetch('https://example.com').then((res) => res.text())")
+///         .unwrap();
+///     assert_eq!(res.await.unwrap(), "GET https://example.com/");
+/// })
+/// .await;
+/// # }
+/// ```
+pub fn init_fetch<'js, B>(ctx: &Ctx<'js>, backend: B) -> Result<()>
+where
+    B: HttpBackend + 'static,
+{
+    let globals = ctx.globals();
+    Class::<Headers>::define(&globals)?;
+    Class::<Response>::define(&globals)?;
+    let backend: Rc<dyn HttpBackend> = Rc::new(backend);
+    let fetch = Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, input: Coerced<StdString>, init: Opt<Object<'js>>| {
+            let request = new_request(&ctx, &input.0, init.0);
+            let backend = backend.clone();
+            Promised(async move {
+                let request = request?;
+                let url = request.url.clone();
+                let response = backend
+                    .fetch(request)
+                    .await
+                    .map_err(|e| Exception::throw_type(&ctx, &format!("fetch failed: {}", e)))?;
+                let headers = response
+                    .headers
+                    .into_iter()
+                    .map(|(name, value)| normalize(&ctx, &name, &value))
+                    .collect::<Result<_>>()?;
+                let response = Response {
+                    status: response.status,
+                    status_text: response.status_text,
+                    headers: Class::instance(ctx.clone(), Headers { list: headers })?,
+                    url,
+                    body: Some(response.body),
+                };
+                Class::instance(ctx, response)
+            })
+        },
+    )?
+    .with_name("fetch")?;
+    globals.set("fetch", fetch)
+}
+
+fn new_request<'js>(ctx: &Ctx<'js>, input: &str, init: Option<Object<'js>>) -> Result<HttpRequest> {
+    let url = ::url::Url::parse(input)
+        .map_err(|_| Exception::throw_type(ctx, &format!("Invalid URL: {}", input)))?
+        .into();
+    let mut request = HttpRequest {
+        method: "GET".into(),
+        url,
+        headers: Vec::new(),
+        body: None,
+    };
+    let Some(init) = init else {
+        return Ok(request);
+    };
+    if let Some(method) = init.get::<_, Option<Coerced<StdString>>>("method")? {
+        request.method = normalize_method(ctx, method.0)?;
+    }
+    if let Some(headers) = init.get::<_, Option<Value>>("headers")? {
+        request.headers = headers_init(ctx, headers)?;
+    }
+    if let Some(body) = init.get::<_, Option<Value>>("body")? {
+        if request.method == "GET" || request.method == "HEAD" {
+            return Err(Exception::throw_type(
+                ctx,
+                "Request with GET/HEAD method cannot have body",
+            ));
+        }
+        let (body, content_type) = extract_body(ctx, body)?;
+        if let Some(content_type) = content_type {
+            if !request.headers.iter().any(|(n, _)| n == "content-type") {
+                request
+                    .headers
+                    .push(("content-type".into(), content_type.into()));
+            }
+        }
+        request.body = Some(body);
+    }
+    Ok(request)
+}
+
+fn is_token(x: &str) -> bool {
+    !x.is_empty()
+        && x.bytes()
+            .all(|x| x.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&x))
+}
+
+fn normalize_method(ctx: &Ctx, method: StdString) -> Result<StdString> {
+    if !is_token(&method) {
+        return Err(Exception::throw_type(
+            ctx,
+            &format!("'{}' is not a valid HTTP method", method),
+        ));
+    }
+    let upper = method.to_ascii_uppercase();
+    match upper.as_str() {
+        "CONNECT" | "TRACE" | "TRACK" => Err(Exception::throw_type(
+            ctx,
+            &format!("'{}' HTTP method is unsupported", method),
+        )),
+        "DELETE" | "GET" | "HEAD" | "OPTIONS" | "POST" | "PUT" => Ok(upper),
+        _ => Ok(method),
+    }
+}
+
+/// Validate a header, returning it with a lowercase name and the value trimmed.
+fn normalize(ctx: &Ctx, name: &str, value: &str) -> Result<(StdString, StdString)> {
+    let value = value.trim_matches(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+    if !is_token(name) {
+        return Err(Exception::throw_type(
+            ctx,
+            &format!("'{}' is not a valid header name", name),
+        ));
+    }
+    if value.contains(['\0', '\n', '\r']) {
+        return Err(Exception::throw_type(
+            ctx,
+            &format!("'{}' is not a valid value for header '{}'", value, name),
+        ));
+    }
+    Ok((name.to_ascii_lowercase(), value.into()))
+}
+
+fn headers_init<'js>(ctx: &Ctx<'js>, init: Value<'js>) -> Result<Vec<(StdString, StdString)>> {
+    if init.is_undefined() {
+        return Ok(Vec::new());
+    }
+    if !init.is_object() {
+        return Err(Exception::throw_type(
+            ctx,
+            "Headers must be an object or a sequence of name-value pairs",
+        ));
+    }
+    init_list(ctx, init)?
+        .into_iter()
+        .map(|(name, value)| normalize(ctx, &name, &value))
+        .collect()
+}
+
+/// Returns the bytes of a body and the content type it implies.
+fn extract_body<'js>(ctx: &Ctx<'js>, body: Value<'js>) -> Result<(Vec<u8>, Option<&'static str>)> {
+    if body.is_undefined() || body.is_null() {
+        return Ok((Vec::new(), None));
+    }
+    if let Some(object) = body.as_object() {
+        if let Some(params) = Class::<UrlSearchParams>::from_object(object.clone()) {
+            let body = serialize_form(params.borrow().pairs());
+            return Ok((
+                body.into_bytes(),
+                Some("application/x-www-form-urlencoded;charset=UTF-8"),
+            ));
+        }
+        if ArrayBuffer::from_object(object.clone()).is_some() || object.contains_key("buffer")? {
+            return Ok((buffer_source(ctx, Some(body))?, None));
+        }
+    }
+    let body: Coerced<StdString> = body.get()?;
+    Ok((body.0.into_bytes(), Some("text/plain;charset=UTF-8")))
+}
+
+/// A `Headers` object, a list of HTTP headers.
+#[derive(Debug, Clone, Default)]
+pub struct Headers {
+    list: Vec<(StdString, StdString)>,
+}
+
+impl Headers {
+    /// Returns the headers, with lowercase names, in the order they were added.
+    pub fn pairs(&self) -> &[(StdString, StdString)] {
+        &self.list
+    }
+
+    /// Returns the value of a header, the values of headers appearing multiple times are
+    /// combined.
+    pub fn get(&self, name: &str) -> Option<StdString> {
+        let name = name.to_ascii_lowercase();
+        let mut values = self.list.iter().filter(|(n, _)| *n == name).map(|(_, v)| v);
+        let first = values.next()?.clone();
+        Some(values.fold(first, |mut res, x| {
+            res.push_str(", ");
+            res.push_str(x);
+            res
+        }))
+    }
+
+    /// Returns the headers sorted by name and combined, the way they are iterated.
+    fn sorted(&self) -> Vec<(StdString, StdString)> {
+        let mut names: Vec<&StdString> = self.list.iter().map(|(n, _)| n).collect();
+        names.sort();
+        names.dedup();
+        let mut res = Vec::with_capacity(names.len());
+        for name in names {
+            if name == "set-cookie" {
+                let cookies = self.list.iter().filter(|(n, _)| n == name);
+                res.extend(cookies.cloned());
+            } else {
+                res.push((name.clone(), self.get(name).unwrap_or_default()));
+            }
+        }
+        res
+    }
+}
+
+impl<'js> Trace<'js> for Headers {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+impl<'js> JsClass<'js> for Headers {
+    const NAME: &'static str = "Headers";
+
+    type Mutable = Writable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        type ThisHeaders<'js> = This<Class<'js, Headers>>;
+
+        let proto = Object::new(ctx.clone())?;
+
+        let append = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>,
+             this: ThisHeaders<'js>,
+             name: Coerced<StdString>,
+             value: Coerced<StdString>|
+             -> Result<()> {
+                let header = normalize(&ctx, &name.0, &value.0)?;
+                this.borrow_mut().list.push(header);
+                Ok(())
+            },
+        )?;
+        proto.set("append", append.with_name("append")?)?;
+
+        let delete = Function::new(
+            ctx.clone(),
+            |this: ThisHeaders<'js>, name: Coerced<StdString>| {
+                let name = name.0.to_ascii_lowercase();
+                this.borrow_mut().list.retain(|(n, _)| *n != name);
+            },
+        )?;
+        proto.set("delete", delete.with_name("delete")?)?;
+
+        let get = Function::new(
+            ctx.clone(),
+            |this: ThisHeaders<'js>, name: Coerced<StdString>| this.borrow().get(&name.0),
+        )?;
+        proto.set("get", get.with_name("get")?)?;
+
+        let get_set_cookie = Function::new(ctx.clone(), |this: ThisHeaders<'js>| {
+            let headers = this.borrow();
+            let cookies = headers.list.iter().filter(|(n, _)| n == "set-cookie");
+            cookies.map(|(_, v)| v.clone()).collect::<Vec<_>>()
+        })?;
+        proto.set("getSetCookie", get_set_cookie.with_name("getSetCookie")?)?;
+
+        let has = Function::new(
+            ctx.clone(),
+            |this: ThisHeaders<'js>, name: Coerced<StdString>| {
+                let name = name.0.to_ascii_lowercase();
+                this.borrow().list.iter().any(|(n, _)| *n == name)
+            },
+        )?;
+        proto.set("has", has.with_name("has")?)?;
+
+        let set = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>,
+             this: ThisHeaders<'js>,
+             name: Coerced<StdString>,
+             value: Coerced<StdString>|
+             -> Result<()> {
+                let (name, value) = normalize(&ctx, &name.0, &value.0)?;
+                let mut headers = this.borrow_mut();
+                let mut value = Some(value);
+                headers.list.retain_mut(|(n, v)| {
+                    if *n != name {
+                        return true;
+                    }
+                    match value.take() {
+                        Some(value) => {
+                            *v = value;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+                if let Some(value) = value {
+                    headers.list.push((name, value));
+                }
+                Ok(())
+            },
+        )?;
+        proto.set("set", set.with_name("set")?)?;
+
+        let for_each = Function::new(
+            ctx.clone(),
+            |this: ThisHeaders<'js>, callback: Function<'js>, this_arg: Opt<Value<'js>>| {
+                let this_arg = this_arg
+                    .0
+                    .unwrap_or_else(|| Value::new_undefined(callback.ctx().clone()));
+                let headers = this.borrow().sorted();
+                for (name, value) in headers {
+                    let args = (This(this_arg.clone()), value, name, this.0.clone());
+                    callback.call::<_, Value>(args)?;
+                }
+                Result::Ok(())
+            },
+        )?;
+        proto.set("forEach", for_each.with_name("forEach")?)?;
+
+        let keys = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisHeaders<'js>| {
+            let names = this.borrow().sorted().into_iter().map(|(n, _)| n);
+            array_iterator(&ctx, names)
+        })?;
+        proto.set("keys", keys.with_name("keys")?)?;
+
+        let values = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisHeaders<'js>| {
+            let values = this.borrow().sorted().into_iter().map(|(_, v)| v);
+            array_iterator(&ctx, values)
+        })?;
+        proto.set("values", values.with_name("values")?)?;
+
+        let entries = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisHeaders<'js>| {
+            let pairs = this.borrow().sorted().into_iter().map(|(n, v)| vec![n, v]);
+            array_iterator(&ctx, pairs)
+        })?
+        .with_name("entries")?;
+        proto.set("entries", entries.clone())?;
+        proto.set(PredefinedAtom::SymbolIterator, entries)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Constructor::new_class::<Headers, _, _>(
+            ctx.clone(),
+            |ctx: Ctx<'js>, init: Opt<Value<'js>>| {
+                let list = match init.0 {
+                    Some(init) => headers_init(&ctx, init)?,
+                    None => Vec::new(),
+                };
+                Class::instance(ctx.clone(), Headers { list })
+            },
+        )
+        .map(Some)
+    }
+}
+
+/// A `Response` object, the response to a request made with `fetch`.
+pub struct Response<'js> {
+    status: u16,
+    status_text: StdString,
+    headers: Class<'js, Headers>,
+    url: StdString,
+    /// The body, `None` once it was read.
+    body: Option<Vec<u8>>,
+}
+
+impl<'js> Response<'js> {
+    fn take_body(&mut self, ctx: &Ctx<'js>) -> Result<Vec<u8>> {
+        self.body
+            .take()
+            .ok_or_else(|| Exception::throw_type(ctx, "Body has already been used"))
+    }
+}
+
+impl<'js> Trace<'js> for Response<'js> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.headers.trace(tracer)
+    }
+}
+
+impl<'js> JsClass<'js> for Response<'js> {
+    const NAME: &'static str = "Response";
+
+    type Mutable = Writable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        type ThisResponse<'js> = This<Class<'js, Response<'js>>>;
+
+        let proto = Object::new(ctx.clone())?;
+        proto.prop(
+            "status",
+            Accessor::from(|this: ThisResponse<'js>| this.borrow().status).configurable(),
+        )?;
+        proto.prop(
+            "statusText",
+            Accessor::from(|this: ThisResponse<'js>| this.borrow().status_text.clone())
+                .configurable(),
+        )?;
+        proto.prop(
+            "ok",
+            Accessor::from(|this: ThisResponse<'js>| (200..300).contains(&this.borrow().status))
+                .configurable(),
+        )?;
+        proto.prop(
+            "headers",
+            Accessor::from(|this: ThisResponse<'js>| this.borrow().headers.clone()).configurable(),
+        )?;
+        proto.prop(
+            "url",
+            Accessor::from(|this: ThisResponse<'js>| this.borrow().url.clone()).configurable(),
+        )?;
+        proto.prop(
+            "bodyUsed",
+            Accessor::from(|this: ThisResponse<'js>| this.borrow().body.is_none()).configurable(),
+        )?;
+
+        let text = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisResponse<'js>| {
+            let body = this.borrow_mut().take_body(&ctx);
+            settled(
+                &ctx,
+                body.map(|x| StdString::from_utf8_lossy(&x).into_owned()),
+            )
+        })?;
+        proto.set("text", text.with_name("text")?)?;
+
+        let json = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisResponse<'js>| {
+            let body = this.borrow_mut().take_body(&ctx);
+            settled(&ctx, body.and_then(|x| ctx.json_parse(x)))
+        })?;
+        proto.set("json", json.with_name("json")?)?;
+
+        let array_buffer = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisResponse<'js>| {
+            let body = this.borrow_mut().take_body(&ctx);
+            settled(&ctx, body.and_then(|x| ArrayBuffer::new(ctx.clone(), x)))
+        })?;
+        proto.set("arrayBuffer", array_buffer.with_name("arrayBuffer")?)?;
+
+        let clone = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, this: ThisResponse<'js>| -> Result<Class<'js, Response<'js>>> {
+                let this = this.borrow();
+                if this.body.is_none() {
+                    return Err(Exception::throw_type(&ctx, "Body has already been used"));
+                }
+                let headers = this.headers.borrow().clone();
+                let response = Response {
+                    status: this.status,
+                    status_text: this.status_text.clone(),
+                    headers: Class::instance(ctx.clone(), headers)?,
+                    url: this.url.clone(),
+                    body: this.body.clone(),
+                };
+                Class::instance(ctx, response)
+            },
+        )?;
+        proto.set("clone", clone.with_name("clone")?)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Constructor::new_class::<Response, _, _>(
+            ctx.clone(),
+            |ctx: Ctx<'js>, body: Opt<Value<'js>>, init: Opt<Object<'js>>| {
+                let (body, content_type) = match body.0 {
+                    Some(body) => extract_body(&ctx, body)?,
+                    None => (Vec::new(), None),
+                };
+                let mut response = Response {
+                    status: 200,
+                    status_text: StdString::new(),
+                    headers: Class::instance(ctx.clone(), Headers::default())?,
+                    url: StdString::new(),
+                    body: Some(body),
+                };
+                if let Some(init) = init.0 {
+                    if let Some(status) = init.get::<_, Option<Coerced<f64>>>("status")? {
+                        if !(200.0..=599.0).contains(&status.0) || status.0.fract() != 0.0 {
+                            return Err(Exception::throw_range(
+                                &ctx,
+                                "The status provided is outside the range [200, 599]",
+                            ));
+                        }
+                        response.status = status.0 as u16;
+                    }
+                    if let Some(text) = init.get::<_, Option<Coerced<StdString>>>("statusText")? {
+                        response.status_text = text.0;
+                    }
+                    if let Some(headers) = init.get::<_, Option<Value>>("headers")? {
+                        response.headers.borrow_mut().list = headers_init(&ctx, headers)?;
+                    }
+                }
+                if let Some(content_type) = content_type {
+                    let mut headers = response.headers.borrow_mut();
+                    if headers.get("content-type").is_none() {
+                        headers
+                            .list
+                            .push(("content-type".into(), content_type.into()));
+                    }
+                }
+                Class::instance(ctx.clone(), response)
+            },
+        )
+        .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        async_with,
+        globals::{init_fetch, HttpError, HttpRequest, HttpResponse},
+        promise::Promise,
+        AsyncContext, AsyncRuntime, CatchResultExt, StdString,
+    };
+
+    async fn echo(request: HttpRequest) -> Result<HttpResponse, HttpError> {
+        if request.url.contains("fail") {
+            return Err("connection refused".into());
+        }
+        let mut headers = request.headers;
+        headers.push(("X-Method".into(), request.method));
+        headers.push(("Set-Cookie".into(), "a=1".into()));
+        headers.push(("Set-Cookie".into(), "b=2".into()));
+        Ok(HttpResponse {
+            status: 201,
+            status_text: "Created".into(),
+            headers,
+            body: request.body.unwrap_or_default(),
+        })
+    }
+
+    #[tokio::test]
+    async fn fetch() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            init_fetch(&ctx, echo).unwrap();
+            let res: Promise<Vec<StdString>> = ctx
+                .eval(
+                    r#"
+                (async () => {
+                    const res = await fetch('HTTPS://example.com/a/../b', {
+                        method: 'post',
+                        headers: [['X-Test', ' a '], ['x-test', 'b']],
+                        body: '{"x":1}',
+                    });
+                    const copy = res.clone();
+                    const out = [
+                        `${res.status} ${res.statusText} ${res.ok} ${res.url}`,
+                        res.headers.get('X-Test'),
+                        res.headers.get('content-type'),
+                        res.headers.get('x-method'),
+                        [...res.headers.keys()].join(),
+                        (await res.json()).x.toString(),
+                        String(res.bodyUsed),
+                        await copy.text(),
+                    ];
+                    await res.text().catch((e) => out.push(e.name));
+                    await fetch('http://fail/').catch((e) => out.push(e.message));
+                    await fetch('nope').catch((e) => out.push(e.name));
+                    await fetch('http://h/', { body: 'x' }).catch((e) => out.push(e.name));
+
+                    const response = new Response(new Uint8Array([104, 105]), { status: 404, headers: { 'X-A': 'b' } });
+                    const buffer = await response.arrayBuffer();
+                    out.push(`${response.status} ${response.ok} ${response.headers.get('x-a')} ${buffer.byteLength}`);
+                    return out;
+                })()
+            "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            let res = res.await.catch(&ctx).unwrap();
+            assert_eq!(
+                res,
+                [
+                    "201 Created true https://example.com/b",
+                    "a, b",
+                    "text/plain;charset=UTF-8",
+                    "POST",
+                    "content-type,set-cookie,set-cookie,x-method,x-test",
+                    "1",
+                    "true",
+                    r#"{"x":1}"#,
+                    "TypeError",
+                    "fetch failed: connection refused",
+                    "TypeError",
+                    "TypeError",
+                    "404 false b 2",
+                ]
+            );
+        })
+        .await
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn reqwest() {
+        use std::{
+            io::{BufRead, BufReader, Write},
+            net::TcpListener,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = StdString::new();
+            reader.read_line(&mut line).unwrap();
+            let request = line.trim_end().to_owned();
+            while line != "\r\n" {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+            }
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 202 Accepted\r\ncontent-length: {}\r\nx-test: a\r\nconnection: close\r\n\r\n{}",
+                request.len(),
+                request
+            )
+            .unwrap();
+        });
+
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            init_fetch(&ctx, crate::globals::ReqwestBackend::default()).unwrap();
+            let res: Promise<Vec<StdString>> = ctx
+                .eval(format!(
+                    r#"
+                (async () => {{
+                    const res = await fetch('http://{}/a?b');
+                    return [`${{res.status}} ${{res.statusText}}`, res.headers.get('x-test'), await res.text()];
+                }})()
+            "#,
+                    addr
+                ))
+                .catch(&ctx)
+                .unwrap();
+            let res = res.await.catch(&ctx).unwrap();
+            assert_eq!(res, ["202 Accepted", "a", "GET /a?b HTTP/1.1"]);
+        })
+        .await;
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,391 @@
+use crate::{
+    clock::{self, Instant},
+    function::Opt,
+    Ctx, Exception, Function, IntoJs, Mut, Object, Ref, Result, StdString, Value,
+};
+
+/// The type of a recorded performance entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    /// An entry recorded with `performance.mark`.
+    Mark,
+    /// An entry recorded with `performance.measure`.
+    Measure,
+}
+
+impl EntryType {
+    /// The name of the entry type in JavaScript.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntryType::Mark => "mark",
+            EntryType::Measure => "measure",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "mark" => Some(EntryType::Mark),
+            "measure" => Some(EntryType::Measure),
+            _ => None,
+        }
+    }
+}
+
+/// A mark or measure recorded by a script.
+///
+/// Times are in milliseconds relative to the time origin of the [`Performance`] which recorded
+/// the entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerformanceEntry {
+    pub name: StdString,
+    pub entry_type: EntryType,
+    pub start_time: f64,
+    pub duration: f64,
+}
+
+impl<'js> IntoJs<'js> for PerformanceEntry {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let entry = Object::new(ctx.clone())?;
+        entry.set("name", self.name)?;
+        entry.set("entryType", self.entry_type.as_str())?;
+        entry.set("startTime", self.start_time)?;
+        entry.set("duration", self.duration)?;
+        Ok(entry.into_value())
+    }
+}
+
+/// A handle to the `performance` global of a context.
+///
+/// The handle shares the recorded entries with the context, so marks and measures made by
+/// scripts can be read after the scripts have run.
+#[derive(Clone)]
+pub struct Performance {
+    start: Instant,
+    time_origin: f64,
+    entries: Ref<Mut<Vec<PerformanceEntry>>>,
+}
+
+impl Performance {
+    fn new() -> Self {
+        Performance {
+            start: Instant::now(),
+            time_origin: clock::unix_millis(),
+            entries: Ref::new(Mut::new(Vec::new())),
+        }
+    }
+
+    /// The number of milliseconds elapsed since the time origin.
+    pub fn now(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+
+    /// The time origin in milliseconds since the unix epoch.
+    pub fn time_origin(&self) -> f64 {
+        self.time_origin
+    }
+
+    /// Returns a copy of all recorded entries in the order they were recorded.
+    pub fn entries(&self) -> Vec<PerformanceEntry> {
+        self.entries.lock().clone()
+    }
+
+    /// Removes all recorded entries and returns them.
+    pub fn take_entries(&self) -> Vec<PerformanceEntry> {
+        std::mem::take(&mut *self.entries.lock())
+    }
+
+    fn record(&self, entry: PerformanceEntry) -> PerformanceEntry {
+        self.entries.lock().push(entry.clone());
+        entry
+    }
+
+    fn filter(&self, name: Option<&str>, entry_type: Option<EntryType>) -> Vec<PerformanceEntry> {
+        self.entries
+            .lock()
+            .iter()
+            .filter(|x| name.map(|name| x.name == name).unwrap_or(true))
+            .filter(|x| entry_type.map(|ty| x.entry_type == ty).unwrap_or(true))
+            .cloned()
+            .collect()
+    }
+
+    fn clear(&self, name: Option<&str>, entry_type: EntryType) {
+        self.entries.lock().retain(|x| {
+            x.entry_type != entry_type || name.map(|name| x.name != name).unwrap_or(false)
+        })
+    }
+
+    /// Resolves a mark name or a timestamp to a time.
+    fn time(&self, ctx: &Ctx, value: &Value) -> Result<f64> {
+        if let Some(time) = value.as_number() {
+            if time < 0.0 {
+                return Err(Exception::throw_type(
+                    ctx,
+                    "Cannot use a negative value as a timestamp",
+                ));
+            }
+            return Ok(time);
+        }
+        let name: StdString = value.get()?;
+        self.entries
+            .lock()
+            .iter()
+            .rev()
+            .find(|x| x.entry_type == EntryType::Mark && x.name == name)
+            .map(|x| x.start_time)
+            .ok_or_else(|| {
+                Exception::throw_syntax(ctx, &format!("The mark '{}' does not exist", name))
+            })
+    }
+
+    fn mark<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        name: StdString,
+        options: Option<Object<'js>>,
+    ) -> Result<PerformanceEntry> {
+        let start: Option<Value> = match options {
+            Some(options) => options.get("startTime")?,
+            None => None,
+        };
+        let start_time = match start.filter(|x| !x.is_undefined()) {
+            Some(start) => start
+                .as_number()
+                .filter(|x| *x >= 0.0)
+                .ok_or_else(|| Exception::throw_type(ctx, "startTime must be a positive number"))?,
+            None => self.now(),
+        };
+        Ok(self.record(PerformanceEntry {
+            name,
+            entry_type: EntryType::Mark,
+            start_time,
+            duration: 0.0,
+        }))
+    }
+
+    fn measure<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        name: StdString,
+        start: Option<Value<'js>>,
+        end: Option<Value<'js>>,
+    ) -> Result<PerformanceEntry> {
+        let defined = |x: Option<Value<'js>>| x.filter(|x| !(x.is_undefined() || x.is_null()));
+        let (start, end) = match defined(start) {
+            Some(start) if start.is_object() => {
+                if defined(end).is_some() {
+                    return Err(Exception::throw_type(
+                        ctx,
+                        "The end mark must not be given with measure options",
+                    ));
+                }
+                let options = start.into_object().unwrap();
+                let start: Option<Value> = defined(options.get("start")?);
+                let end: Option<Value> = defined(options.get("end")?);
+                let duration: Option<Value> = defined(options.get("duration")?);
+                let duration = duration
+                    .map(|x| {
+                        x.as_number()
+                            .ok_or_else(|| Exception::throw_type(ctx, "duration must be a number"))
+                    })
+                    .transpose()?;
+                let start = start.map(|x| self.time(ctx, &x)).transpose()?;
+                let end = end.map(|x| self.time(ctx, &x)).transpose()?;
+                match (start, end, duration) {
+                    (Some(_), Some(_), Some(_)) => {
+                        return Err(Exception::throw_type(
+                            ctx,
+                            "start, end and duration must not all be given",
+                        ))
+                    }
+                    (Some(start), None, Some(duration)) => (start, start + duration),
+                    (None, Some(end), Some(duration)) => (end - duration, end),
+                    (start, end, _) => (start.unwrap_or(0.0), end.unwrap_or_else(|| self.now())),
+                }
+            }
+            start => {
+                let start = start.map(|x| self.time(ctx, &x)).transpose()?;
+                let end = defined(end).map(|x| self.time(ctx, &x)).transpose()?;
+                (start.unwrap_or(0.0), end.unwrap_or_else(|| self.now()))
+            }
+        };
+        Ok(self.record(PerformanceEntry {
+            name,
+            entry_type: EntryType::Measure,
+            start_time: start,
+            duration: end - start,
+        }))
+    }
+}
+
+/// Install the `performance` global into a context.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, globals};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// let performance = ctx.with(|ctx| {
+///     let performance = globals::init_performance(&ctx).unwrap();
+///     let _: () = ctx
+///         .eval(
+///             r#"
+///             performance.mark("start");
+///             for (let i = 0; i < 1000; i++) {}
+///             performance.measure("loop", "start");
+///         "#,
+///         )
+///         .unwrap();
+///     performance
+/// });
+/// let entries = performance.take_entries();
+/// assert_eq!(entries[1].name, "loop");
+/// assert!(entries[1].duration >= 0.0);
+/// ```
+///
+/// This provides `performance.now`, `performance.timeOrigin`, `mark`, `measure` and the methods
+/// to read and clear the recorded entries. The returned handle reads the same entries from Rust.
+pub fn init_performance<'js>(ctx: &Ctx<'js>) -> Result<Performance> {
+    let handle = Performance::new();
+    let performance = Object::new(ctx.clone())?;
+    performance.set("timeOrigin", handle.time_origin())?;
+
+    let this = handle.clone();
+    let now = Function::new(ctx.clone(), move || this.now())?;
+    performance.set("now", now.with_name("now")?)?;
+
+    let this = handle.clone();
+    let mark = Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, name: StdString, options: Opt<Object<'js>>| {
+            this.mark(&ctx, name, options.0)
+        },
+    )?;
+    performance.set("mark", mark.with_name("mark")?)?;
+
+    let this = handle.clone();
+    let measure = Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, name: StdString, start: Opt<Value<'js>>, end: Opt<Value<'js>>| {
+            this.measure(&ctx, name, start.0, end.0)
+        },
+    )?;
+    performance.set("measure", measure.with_name("measure")?)?;
+
+    let this = handle.clone();
+    let get_entries = Function::new(ctx.clone(), move || this.entries())?;
+    performance.set("getEntries", get_entries.with_name("getEntries")?)?;
+
+    let this = handle.clone();
+    let get_entries_by_name = Function::new(
+        ctx.clone(),
+        move |name: StdString, entry_type: Opt<StdString>| match entry_type.0 {
+            Some(ty) => match EntryType::parse(&ty) {
+                Some(ty) => this.filter(Some(&name), Some(ty)),
+                None => Vec::new(),
+            },
+            None => this.filter(Some(&name), None),
+        },
+    )?;
+    performance.set(
+        "getEntriesByName",
+        get_entries_by_name.with_name("getEntriesByName")?,
+    )?;
+
+    let this = handle.clone();
+    let get_entries_by_type =
+        Function::new(
+            ctx.clone(),
+            move |entry_type: StdString| match EntryType::parse(&entry_type) {
+                Some(ty) => this.filter(None, Some(ty)),
+                None => Vec::new(),
+            },
+        )?;
+    performance.set(
+        "getEntriesByType",
+        get_entries_by_type.with_name("getEntriesByType")?,
+    )?;
+
+    let this = handle.clone();
+    let clear_marks = Function::new(ctx.clone(), move |name: Opt<StdString>| {
+        this.clear(name.0.as_deref(), EntryType::Mark)
+    })?;
+    performance.set("clearMarks", clear_marks.with_name("clearMarks")?)?;
+
+    let this = handle.clone();
+    let clear_measures = Function::new(ctx.clone(), move |name: Opt<StdString>| {
+        this.clear(name.0.as_deref(), EntryType::Measure)
+    })?;
+    performance.set("clearMeasures", clear_measures.with_name("clearMeasures")?)?;
+
+    ctx.globals().set("performance", performance)?;
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{globals::init_performance, test_with, CatchResultExt, StdString};
+
+    use super::EntryType;
+
+    #[test]
+    fn performance() {
+        test_with(|ctx| {
+            let performance = init_performance(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const errors = [];
+                    const a = performance.now();
+                    const b = performance.now();
+                    performance.mark("a", { startTime: 10 });
+                    performance.mark("b", { startTime: 25 });
+                    const m = performance.measure("a to b", "a", "b");
+                    performance.measure("span", { start: 5, duration: 3 });
+                    performance.measure("since a", "a");
+                    try { performance.measure("none", "missing") } catch (e) { errors.push(e.name) }
+                    try { performance.mark("neg", { startTime: -1 }) } catch (e) { errors.push(e.name) }
+                    [
+                        String(b >= a),
+                        String(performance.timeOrigin > 0),
+                        String(m.duration),
+                        m.entryType,
+                        String(performance.getEntriesByType("mark").length),
+                        String(performance.getEntriesByName("span", "measure")[0].startTime),
+                        String(performance.getEntriesByName("span", "mark").length),
+                        ...errors,
+                    ]
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "true",
+                    "true",
+                    "15",
+                    "measure",
+                    "2",
+                    "5",
+                    "0",
+                    "SyntaxError",
+                    "TypeError"
+                ]
+            );
+
+            let entries = performance.entries();
+            assert_eq!(entries.len(), 5);
+            assert_eq!(entries[3].name, "span");
+            assert_eq!(entries[3].duration, 3.0);
+
+            let _: () = ctx
+                .eval(r#"performance.clearMarks("a"); performance.clearMeasures()"#)
+                .unwrap();
+            let entries = performance.take_entries();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].name, "b");
+            assert_eq!(entries[0].entry_type, EntryType::Mark);
+            assert!(performance.entries().is_empty());
+        })
+    }
+}
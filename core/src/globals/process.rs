@@ -0,0 +1,229 @@
+use std::{cell::RefCell, env, fmt};
+
+use crate::{function::Opt, qjs, Ctx, Error, Exception, Function, Object, Result, StdString};
+
+/// The type of the handler called when a script calls `process.exit`.
+pub type ExitHandler = Box<dyn FnMut(i32) + 'static>;
+
+/// The environment exposed to scripts by [`init_process`].
+///
+/// Nothing of the host process is exposed unless it is allowed explicitly.
+///
+/// ```
+/// # use rquickjs::globals::ProcessOptions;
+/// let options = ProcessOptions::default()
+///     .allow_var("HOME")
+///     .allow_prefix("APP_")
+///     .var("MODE", "test")
+///     .args(["tool.js", "--verbose"]);
+/// ```
+#[derive(Default)]
+pub struct ProcessOptions {
+    /// The names of the environment variables of the host process which are exposed.
+    pub vars: Vec<StdString>,
+    /// The prefixes of the names of environment variables of the host process which are exposed.
+    pub prefixes: Vec<StdString>,
+    /// Variables set in `process.env` in addition to the ones of the host process.
+    pub overrides: Vec<(StdString, StdString)>,
+    /// The arguments in `process.argv`.
+    pub args: Vec<StdString>,
+    /// The handler called when a script calls `process.exit`.
+    pub exit: Option<ExitHandler>,
+}
+
+impl ProcessOptions {
+    /// Expose the environment variable with the given name.
+    pub fn allow_var<S: Into<StdString>>(mut self, name: S) -> Self {
+        self.vars.push(name.into());
+        self
+    }
+
+    /// Expose the environment variables whose name starts with the given prefix.
+    pub fn allow_prefix<S: Into<StdString>>(mut self, prefix: S) -> Self {
+        self.prefixes.push(prefix.into());
+        self
+    }
+
+    /// Set a variable in `process.env`, taking precedence over the host process.
+    pub fn var<K: Into<StdString>, V: Into<StdString>>(mut self, name: K, value: V) -> Self {
+        self.overrides.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the arguments in `process.argv`.
+    pub fn args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<StdString>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the handler called with the exit code when a script calls `process.exit`.
+    pub fn on_exit<F: FnMut(i32) + 'static>(mut self, handler: F) -> Self {
+        self.exit = Some(Box::new(handler));
+        self
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        self.vars.iter().any(|x| x == name) || self.prefixes.iter().any(|x| name.starts_with(&**x))
+    }
+
+    /// Returns the exposed environment variables, sorted by name.
+    fn env(&self) -> Vec<(StdString, StdString)> {
+        let mut vars: Vec<_> = env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(name, _)| self.is_allowed(name))
+            .filter(|(name, _)| !self.overrides.iter().any(|(x, _)| x == name))
+            .chain(self.overrides.iter().cloned())
+            .collect();
+        vars.sort();
+        vars
+    }
+}
+
+impl fmt::Debug for ProcessOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcessOptions")
+            .field("vars", &self.vars)
+            .field("prefixes", &self.prefixes)
+            .field("overrides", &self.overrides)
+            .field("args", &self.args)
+            .field("exit", &self.exit.is_some())
+            .finish()
+    }
+}
+
+/// Install the `process` global into a context.
+///
+/// ```
+/// # use std::{cell::Cell, rc::Rc};
+/// # use rquickjs::{Runtime, Context, globals::{self, ProcessOptions}};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// let code = Rc::new(Cell::new(None));
+/// let exit_code = code.clone();
+/// let options = ProcessOptions::default()
+///     .var("GREETING", "hello")
+///     .args(["greet.js", "world"])
+///     .on_exit(move |code| exit_code.set(Some(code)));
+/// ctx.with(|ctx| {
+///     globals::init_process(&ctx, options).unwrap();
+///     let res = ctx.eval::<(), _>(
+///         r#"
+///         globalThis.message = `${process.env.GREETING} ${process.argv[1]}`;
+///         try {
+///             process.exit(2);
+///         } finally {
+///             globalThis.message = "unreachable";
+///         }
+///     "#,
+///     );
+///     assert!(res.is_err());
+///     assert_eq!(ctx.globals().get::<_, String>("message").unwrap(), "hello world");
+/// });
+/// assert_eq!(code.get(), Some(2));
+/// ```
+///
+/// This provides `process.env` with the environment variables allowed by the options, read when
+/// the global is installed, `process.argv` and `process.exit`. Changes to `process.env` are only
+/// visible to scripts. Calling `process.exit` calls the exit handler, if any, and stops the script
+/// with an uncatchable exception, the evaluation then returns [`Error::Exception`].
+pub fn init_process<'js>(ctx: &Ctx<'js>, options: ProcessOptions) -> Result<()> {
+    let process = Object::new(ctx.clone())?;
+
+    let env = Object::new(ctx.clone())?;
+    for (name, value) in options.env() {
+        env.set(name, value)?;
+    }
+    process.set("env", env)?;
+    process.set("argv", options.args)?;
+
+    let handler = RefCell::new(options.exit);
+    let exit = Function::new(
+        ctx.clone(),
+        move |ctx: Ctx<'js>, code: Opt<i32>| -> Result<()> {
+            let code = code.0.unwrap_or(0);
+            if let Some(handler) = handler.borrow_mut().as_mut() {
+                handler(code);
+            }
+            Err(throw_exit(&ctx, code))
+        },
+    )?;
+    process.set("exit", exit.with_name("exit")?)?;
+
+    ctx.globals().set("process", process)?;
+    Ok(())
+}
+
+/// Throws an uncatchable error stopping the running script.
+fn throw_exit(ctx: &Ctx<'_>, code: i32) -> Error {
+    let error = Exception::throw_internal(ctx, &format!("process.exit({}) was called", code));
+    unsafe {
+        let exception = qjs::JS_GetException(ctx.as_ptr());
+        qjs::JS_SetUncatchableError(ctx.as_ptr(), exception, 1);
+        qjs::JS_Throw(ctx.as_ptr(), exception);
+    }
+    error
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::{init_process, ProcessOptions};
+    use crate::{test_with, Error, StdString};
+
+    #[test]
+    fn process_env() {
+        std::env::set_var("RQUICKJS_TEST_ALLOWED", "yes");
+        std::env::set_var("RQUICKJS_TEST_PREFIX_A", "a");
+        std::env::set_var("RQUICKJS_TEST_SECRET", "secret");
+        test_with(|ctx| {
+            let codes = Rc::new(RefCell::new(Vec::new()));
+            let exit_codes = codes.clone();
+            let options = ProcessOptions::default()
+                .allow_var("RQUICKJS_TEST_ALLOWED")
+                .allow_prefix("RQUICKJS_TEST_PREFIX_")
+                .var("RQUICKJS_TEST_ALLOWED", "overridden")
+                .args(["main.js", "--flag"])
+                .on_exit(move |code| exit_codes.borrow_mut().push(code));
+            init_process(&ctx, options).unwrap();
+
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                        [
+                            process.env.RQUICKJS_TEST_ALLOWED,
+                            process.env.RQUICKJS_TEST_PREFIX_A,
+                            String(process.env.RQUICKJS_TEST_SECRET),
+                            process.argv.join(" "),
+                        ].join(",")
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(res, "overridden,a,undefined,main.js --flag");
+
+            let res = ctx.eval::<(), _>(
+                r#"
+                    try {
+                        process.exit(3);
+                    } catch (e) {
+                        globalThis.caught = true;
+                    }
+                "#,
+            );
+            assert!(matches!(res, Err(Error::Exception)));
+            let message: StdString = ctx.catch().as_exception().unwrap().message().unwrap();
+            assert_eq!(message, "process.exit(3) was called");
+            assert!(!ctx.globals().contains_key("caught").unwrap());
+
+            // The script can run again after exiting.
+            assert!(ctx.eval::<(), _>("process.exit()").is_err());
+            ctx.catch();
+            assert_eq!(*codes.borrow(), [3, 0]);
+            assert_eq!(ctx.eval::<i32, _>("1").unwrap(), 1);
+        });
+    }
+}
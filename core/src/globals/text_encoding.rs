@@ -0,0 +1,566 @@
+use std::{char, mem, ptr, slice, str};
+
+use crate::{
+    class::{ClassId, JsClass, Readable, Trace, Tracer, Writable},
+    convert::Coerced,
+    function::{Opt, This},
+    object::Accessor,
+    qjs,
+    value::Constructor,
+    Class, Ctx, Exception, Function, Object, Result, StdString, String, TypedArray, Value,
+};
+
+use super::buffer_source;
+
+/// Install the `TextEncoder` and `TextDecoder` globals into a context.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, globals};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     globals::init_text_encoding(&ctx).unwrap();
+///     let res: String = ctx
+///         .eval("new TextDecoder().decode(new TextEncoder().encode('héllo'))")
+///         .unwrap();
+///     assert_eq!(res, "héllo");
+/// });
+/// ```
+///
+/// The decoder supports the `utf-8`, `utf-16le`, `utf-16be` and `windows-1252` encodings. With
+/// the `encoding_rs` feature the other encodings of the standard, like `shift_jis` or `gbk`, are
+/// decoded by the `encoding_rs` crate.
+pub fn init_text_encoding(ctx: &Ctx) -> Result<()> {
+    let globals = ctx.globals();
+    Class::<TextEncoder>::define(&globals)?;
+    Class::<TextDecoder>::define(&globals)?;
+    Ok(())
+}
+
+/// Returns the content of a string with lone surrogates replaced by U+FFFD.
+fn usv_string(string: &String) -> Result<StdString> {
+    let ctx = string.ctx();
+    let mut len = mem::MaybeUninit::uninit();
+    let ptr = unsafe { qjs::JS_ToCStringLen(ctx.as_ptr(), len.as_mut_ptr(), string.as_js_value()) };
+    if ptr.is_null() {
+        return Err(ctx.raise_exception());
+    }
+    let bytes: &[u8] = unsafe { slice::from_raw_parts(ptr as _, len.assume_init() as _) };
+    // QuickJS encodes lone surrogates like other code points, which is the only way the string
+    // can be invalid UTF-8.
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let [byte, tail @ ..] = rest {
+        match rest {
+            [0xED, 0xA0..=0xBF, _, tail @ ..] => {
+                res.extend_from_slice("\u{FFFD}".as_bytes());
+                rest = tail;
+            }
+            _ => {
+                res.push(*byte);
+                rest = tail;
+            }
+        }
+    }
+    unsafe { qjs::JS_FreeCString(ctx.as_ptr(), ptr) };
+    Ok(StdString::from_utf8(res)
+        .unwrap_or_else(|e| StdString::from_utf8_lossy(e.as_bytes()).into_owned()))
+}
+
+/// A `TextEncoder` which encodes strings into UTF-8.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextEncoder;
+
+impl<'js> Trace<'js> for TextEncoder {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+impl<'js> JsClass<'js> for TextEncoder {
+    const NAME: &'static str = "TextEncoder";
+
+    type Mutable = Readable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        let proto = Object::new(ctx.clone())?;
+        proto.prop("encoding", Accessor::from(|| "utf-8").configurable())?;
+
+        let encode = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, input: Opt<Coerced<String<'js>>>| -> Result<TypedArray<'js, u8>> {
+                let input = match input.0 {
+                    Some(input) => usv_string(&input.0)?,
+                    None => StdString::new(),
+                };
+                TypedArray::new(ctx, input.into_bytes())
+            },
+        )?
+        .with_name("encode")?;
+        proto.set("encode", encode)?;
+
+        let encode_into = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>,
+             source: Coerced<String<'js>>,
+             dest: TypedArray<'js, u8>|
+             -> Result<Object<'js>> {
+                let source = usv_string(&source.0)?;
+                let dest = dest
+                    .as_raw()
+                    .ok_or_else(|| Exception::throw_type(&ctx, "The destination is detached"))?;
+                let mut read = 0;
+                let mut written = 0;
+                for c in source.chars() {
+                    let len = c.len_utf8();
+                    if written + len > dest.len {
+                        break;
+                    }
+                    let mut buf = [0; 4];
+                    c.encode_utf8(&mut buf);
+                    unsafe {
+                        ptr::copy_nonoverlapping(buf.as_ptr(), dest.ptr.as_ptr().add(written), len)
+                    };
+                    written += len;
+                    read += c.len_utf16();
+                }
+                let res = Object::new(ctx)?;
+                res.set("read", read)?;
+                res.set("written", written)?;
+                Ok(res)
+            },
+        )?
+        .with_name("encodeInto")?;
+        proto.set("encodeInto", encode_into)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Constructor::new_class::<TextEncoder, _, _>(ctx.clone(), |ctx: Ctx<'js>| {
+            Class::instance(ctx, TextEncoder)
+        })
+        .map(Some)
+    }
+}
+
+/// The encodings supported by [`TextDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    #[cfg(not(feature = "encoding_rs"))]
+    Windows1252,
+    #[cfg(feature = "encoding_rs")]
+    Legacy(&'static encoding_rs::Encoding),
+}
+
+impl Encoding {
+    fn from_label(label: &str) -> Option<Self> {
+        let label = label
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .to_ascii_lowercase();
+        Some(match label.as_str() {
+            "unicode-1-1-utf-8" | "unicode11utf8" | "unicode20utf8" | "utf-8" | "utf8"
+            | "x-unicode20utf8" => Encoding::Utf8,
+            "csunicode" | "iso-10646-ucs-2" | "ucs-2" | "unicode" | "unicodefeff" | "utf-16"
+            | "utf-16le" => Encoding::Utf16Le,
+            "unicodefffe" | "utf-16be" => Encoding::Utf16Be,
+            #[cfg(feature = "encoding_rs")]
+            label => Encoding::Legacy(encoding_rs::Encoding::for_label_no_replacement(
+                label.as_bytes(),
+            )?),
+            #[cfg(not(feature = "encoding_rs"))]
+            "ansi_x3.4-1968" | "ascii" | "cp1252" | "cp819" | "csisolatin1" | "ibm819"
+            | "iso-8859-1" | "iso-ir-100" | "iso8859-1" | "iso88591" | "iso_8859-1"
+            | "iso_8859-1:1987" | "l1" | "latin1" | "us-ascii" | "windows-1252" | "x-cp1252" => {
+                Encoding::Windows1252
+            }
+            #[cfg(not(feature = "encoding_rs"))]
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> StdString {
+        match self {
+            Encoding::Utf8 => "utf-8".into(),
+            Encoding::Utf16Le => "utf-16le".into(),
+            Encoding::Utf16Be => "utf-16be".into(),
+            #[cfg(not(feature = "encoding_rs"))]
+            Encoding::Windows1252 => "windows-1252".into(),
+            #[cfg(feature = "encoding_rs")]
+            Encoding::Legacy(encoding) => encoding.name().to_ascii_lowercase(),
+        }
+    }
+
+    fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"\xEF\xBB\xBF",
+            Encoding::Utf16Le => b"\xFF\xFE",
+            Encoding::Utf16Be => b"\xFE\xFF",
+            #[cfg(not(feature = "encoding_rs"))]
+            Encoding::Windows1252 => b"",
+            #[cfg(feature = "encoding_rs")]
+            Encoding::Legacy(_) => b"",
+        }
+    }
+}
+
+/// The code points of the bytes 0x80 to 0x9F in windows-1252, the other bytes map to the code
+/// point with the same value.
+#[cfg(not(feature = "encoding_rs"))]
+const WINDOWS_1252: [u16; 32] = [
+    0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008D, 0x017D, 0x008F, 0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178,
+];
+
+/// A `TextDecoder` which decodes bytes into strings.
+#[derive(Debug)]
+pub struct TextDecoder {
+    encoding: Encoding,
+    fatal: bool,
+    ignore_bom: bool,
+    /// Whether the start of the stream, which might contain a BOM, was already decoded.
+    started: bool,
+    /// The bytes of an incomplete character at the end of the last streamed chunk.
+    pending: Vec<u8>,
+    /// The state of a legacy encoding between streamed chunks.
+    #[cfg(feature = "encoding_rs")]
+    decoder: Option<encoding_rs::Decoder>,
+}
+
+impl TextDecoder {
+    fn decode(&mut self, ctx: &Ctx, input: Vec<u8>, stream: bool) -> Result<StdString> {
+        let mut bytes = mem::take(&mut self.pending);
+        bytes.extend(input);
+        let mut data = bytes.as_slice();
+        if !self.started && !self.ignore_bom {
+            let bom = self.encoding.bom();
+            if stream && data.len() < bom.len() && bom.starts_with(data) {
+                // Not enough data to know if the stream starts with a BOM.
+                self.pending = bytes;
+                return Ok(StdString::new());
+            }
+            if let Some(rest) = data.strip_prefix(bom) {
+                data = rest;
+            }
+        }
+        self.started = stream;
+
+        let invalid = || Exception::throw_type(ctx, "The encoded data was not valid");
+        let mut res = StdString::with_capacity(data.len());
+        match self.encoding {
+            Encoding::Utf8 => loop {
+                match str::from_utf8(data) {
+                    Ok(s) => {
+                        res.push_str(s);
+                        break;
+                    }
+                    Err(e) => {
+                        let (valid, rest) = data.split_at(e.valid_up_to());
+                        res.push_str(unsafe { str::from_utf8_unchecked(valid) });
+                        match e.error_len() {
+                            None if stream => {
+                                self.pending = rest.to_vec();
+                                break;
+                            }
+                            _ if self.fatal => return Err(invalid()),
+                            Some(len) => {
+                                res.push(char::REPLACEMENT_CHARACTER);
+                                data = &rest[len..];
+                            }
+                            None => {
+                                res.push(char::REPLACEMENT_CHARACTER);
+                                break;
+                            }
+                        }
+                    }
+                }
+            },
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let unit = |x: &[u8]| match self.encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([x[0], x[1]]),
+                    _ => u16::from_be_bytes([x[0], x[1]]),
+                };
+                let mut end = data.len() - data.len() % 2;
+                // A high surrogate at the end might be completed by the next chunk.
+                if stream && end >= 2 && (0xD800..0xDC00).contains(&unit(&data[end - 2..])) {
+                    end -= 2;
+                }
+                let (data, pending) = data.split_at(end);
+                for c in char::decode_utf16(data.chunks_exact(2).map(unit)) {
+                    match c {
+                        Ok(c) => res.push(c),
+                        Err(_) if self.fatal => return Err(invalid()),
+                        Err(_) => res.push(char::REPLACEMENT_CHARACTER),
+                    }
+                }
+                if stream {
+                    self.pending = pending.to_vec();
+                } else if !pending.is_empty() {
+                    if self.fatal {
+                        return Err(invalid());
+                    }
+                    res.push(char::REPLACEMENT_CHARACTER);
+                }
+            }
+            #[cfg(not(feature = "encoding_rs"))]
+            Encoding::Windows1252 => res.extend(data.iter().map(|&x| match x {
+                0x80..=0x9F => char::from_u32(WINDOWS_1252[x as usize - 0x80] as u32).unwrap(),
+                x => x as char,
+            })),
+            #[cfg(feature = "encoding_rs")]
+            Encoding::Legacy(encoding) => {
+                use encoding_rs::{CoderResult, DecoderResult};
+
+                let last = !stream;
+                let fatal = self.fatal;
+                let decoder = self
+                    .decoder
+                    .get_or_insert_with(|| encoding.new_decoder_without_bom_handling());
+                loop {
+                    res.reserve(
+                        decoder
+                            .max_utf8_buffer_length(data.len())
+                            .unwrap_or(data.len()),
+                    );
+                    let (done, read) = if fatal {
+                        let (result, read) =
+                            decoder.decode_to_string_without_replacement(data, &mut res, last);
+                        if let DecoderResult::Malformed(..) = result {
+                            self.decoder = None;
+                            return Err(invalid());
+                        }
+                        (result == DecoderResult::InputEmpty, read)
+                    } else {
+                        let (result, read, _) = decoder.decode_to_string(data, &mut res, last);
+                        (result == CoderResult::InputEmpty, read)
+                    };
+                    data = &data[read..];
+                    if done {
+                        break;
+                    }
+                }
+                if last {
+                    self.decoder = None;
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl<'js> Trace<'js> for TextDecoder {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+fn flag<'js>(options: &Option<Object<'js>>, name: &str) -> Result<bool> {
+    Ok(match options {
+        Some(options) => options
+            .get::<_, Option<Coerced<bool>>>(name)?
+            .map(|x| x.0)
+            .unwrap_or(false),
+        None => false,
+    })
+}
+
+impl<'js> JsClass<'js> for TextDecoder {
+    const NAME: &'static str = "TextDecoder";
+
+    type Mutable = Writable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        let proto = Object::new(ctx.clone())?;
+        proto.prop(
+            "encoding",
+            Accessor::from(|this: This<Class<'js, TextDecoder>>| this.borrow().encoding.name())
+                .configurable(),
+        )?;
+        proto.prop(
+            "fatal",
+            Accessor::from(|this: This<Class<'js, TextDecoder>>| this.borrow().fatal)
+                .configurable(),
+        )?;
+        proto.prop(
+            "ignoreBOM",
+            Accessor::from(|this: This<Class<'js, TextDecoder>>| this.borrow().ignore_bom)
+                .configurable(),
+        )?;
+
+        let decode = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>,
+             this: This<Class<'js, TextDecoder>>,
+             input: Opt<Value<'js>>,
+             options: Opt<Object<'js>>|
+             -> Result<StdString> {
+                let input = buffer_source(&ctx, input.0)?;
+                let stream = flag(&options.0, "stream")?;
+                this.borrow_mut().decode(&ctx, input, stream)
+            },
+        )?
+        .with_name("decode")?;
+        proto.set("decode", decode)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Constructor::new_class::<TextDecoder, _, _>(
+            ctx.clone(),
+            |ctx: Ctx<'js>, label: Opt<Coerced<StdString>>, options: Opt<Object<'js>>| {
+                let encoding = match label.0 {
+                    Some(label) => Encoding::from_label(&label.0).ok_or_else(|| {
+                        Exception::throw_range(
+                            &ctx,
+                            &format!("The encoding label provided ('{}') is invalid", label.0),
+                        )
+                    })?,
+                    None => Encoding::Utf8,
+                };
+                let decoder = TextDecoder {
+                    encoding,
+                    fatal: flag(&options.0, "fatal")?,
+                    ignore_bom: flag(&options.0, "ignoreBOM")?,
+                    started: false,
+                    pending: Vec::new(),
+                    #[cfg(feature = "encoding_rs")]
+                    decoder: None,
+                };
+                Class::instance(ctx.clone(), decoder)
+            },
+        )
+        .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{globals::init_text_encoding, test_with, CatchResultExt, StdString};
+
+    #[test]
+    fn encode() {
+        test_with(|ctx| {
+            init_text_encoding(&ctx).unwrap();
+            let res: Vec<u8> = ctx
+                .eval("Array.from(new TextEncoder().encode('a\\u00e9\\u{1F600}\\ud800'))")
+                .unwrap();
+            assert_eq!(res, "aé😀\u{FFFD}".as_bytes());
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                    const buf = new Uint8Array(4);
+                    const { read, written } = new TextEncoder().encodeInto('ab\u{1F600}', buf);
+                    `${new TextEncoder().encoding} ${read} ${written} ${buf}`
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, "utf-8 2 2 97,98,0,0");
+        })
+    }
+
+    #[test]
+    fn decode() {
+        test_with(|ctx| {
+            init_text_encoding(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const utf8 = new TextDecoder();
+                    const stream = new TextDecoder('utf-8');
+                    const bytes = new TextEncoder().encode('\ufeffhéllo');
+                    [
+                        utf8.decode(bytes),
+                        new TextDecoder('utf-8', { ignoreBOM: true }).decode(bytes).length.toString(),
+                        utf8.decode(new Uint8Array([0x61, 0xff, 0x62])),
+                        stream.decode(bytes.subarray(0, 5), { stream: true }) + '|' + stream.decode(bytes.subarray(5)),
+                        new TextDecoder(' UTF-16LE ').decode(new Uint16Array([0x68, 0x69]).buffer),
+                        new TextDecoder('utf-16be').decode(new DataView(new Uint8Array([0, 0x68, 0xd8, 0x3d, 0xde, 0x00]).buffer)),
+                        new TextDecoder('latin1').decode(new Uint8Array([0x80, 0xe9])),
+                        new TextDecoder('utf-16le').encoding,
+                    ]
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "héllo",
+                    "6",
+                    "a\u{FFFD}b",
+                    "h|éllo",
+                    "hi",
+                    "h😀",
+                    "€é",
+                    "utf-16le"
+                ]
+            );
+
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const errors = [];
+                    try { new TextDecoder('utf-8', { fatal: true }).decode(new Uint8Array([0xff])) } catch (e) { errors.push(e.name) }
+                    try { new TextDecoder('foo') } catch (e) { errors.push(e.name) }
+                    try { new TextDecoder().decode(1) } catch (e) { errors.push(e.name) }
+                    errors
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(res, ["TypeError", "RangeError", "TypeError"]);
+        })
+    }
+
+    #[cfg(feature = "encoding_rs")]
+    #[test]
+    fn legacy() {
+        test_with(|ctx| {
+            init_text_encoding(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const sjis = new Uint8Array([0x82, 0xa0, 0x82, 0xa2, 0x41]);
+                    const stream = new TextDecoder('sjis');
+                    const errors = [];
+                    try { new TextDecoder('shift_jis', { fatal: true }).decode(new Uint8Array([0x82])) } catch (e) { errors.push(e.name) }
+                    try { new TextDecoder('iso-2022-kr') } catch (e) { errors.push(e.name) }
+                    [
+                        new TextDecoder('shift_jis').decode(sjis),
+                        stream.decode(sjis.subarray(0, 3), { stream: true }) + '|' + stream.decode(sjis.subarray(3)),
+                        new TextDecoder('latin1').decode(new Uint8Array([0x80, 0xe9])),
+                        new TextDecoder('gbk').decode(new Uint8Array([0xc4, 0xe3])),
+                        new TextDecoder('sjis').encoding,
+                        ...errors,
+                    ]
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "あいA",
+                    "あ|いA",
+                    "€é",
+                    "你",
+                    "shift_jis",
+                    "TypeError",
+                    "RangeError"
+                ]
+            );
+        })
+    }
+}
@@ -0,0 +1,585 @@
+use ::url::{form_urlencoded, quirks};
+
+use crate::{
+    atom::PredefinedAtom,
+    class::{ClassId, JsClass, Trace, Tracer, Writable},
+    convert::Coerced,
+    function::{Opt, This},
+    object::Accessor,
+    value::Constructor,
+    Array, Class, Ctx, Exception, Function, Object, Result, StdString, Value,
+};
+
+/// Install the `URL` and `URLSearchParams` globals into a context.
+///
+/// The URLs are parsed by the `url` crate, following the WHATWG URL standard.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, globals};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     globals::init_url(&ctx).unwrap();
+///     let res: String = ctx
+///         .eval("const url = new URL('../b?x=1', 'https://example.com/a/c'); url.searchParams.append('y', '2 3'); url.href")
+///         .unwrap();
+///     assert_eq!(res, "https://example.com/b?x=1&y=2+3");
+/// });
+/// ```
+pub fn init_url(ctx: &Ctx) -> Result<()> {
+    let globals = ctx.globals();
+    Class::<Url>::define(&globals)?;
+    Class::<UrlSearchParams>::define(&globals)?;
+    Ok(())
+}
+
+fn parse_with_base(input: &str, base: Option<&str>) -> Option<::url::Url> {
+    match base {
+        Some(base) => ::url::Url::parse(base).ok()?.join(input).ok(),
+        None => ::url::Url::parse(input).ok(),
+    }
+}
+
+/// Parse `application/x-www-form-urlencoded` name-value pairs.
+pub(crate) fn parse_form(input: &str) -> Vec<(StdString, StdString)> {
+    form_urlencoded::parse(input.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Serialize name-value pairs as `application/x-www-form-urlencoded`.
+pub(crate) fn serialize_form(list: &[(StdString, StdString)]) -> StdString {
+    form_urlencoded::Serializer::new(StdString::new())
+        .extend_pairs(list)
+        .finish()
+}
+
+/// A `URL` object.
+pub struct Url<'js> {
+    record: ::url::Url,
+    /// The `URLSearchParams` object of the URL, created when first accessed.
+    search_params: Option<Class<'js, UrlSearchParams<'js>>>,
+}
+
+impl<'js> Url<'js> {
+    /// Returns the serialization of the URL.
+    pub fn href(&self) -> StdString {
+        self.record.as_str().into()
+    }
+
+    fn update_search_params(&self) {
+        if let Some(params) = &self.search_params {
+            params.borrow_mut().list = parse_form(self.record.query().unwrap_or_default());
+        }
+    }
+}
+
+impl<'js> Trace<'js> for Url<'js> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.search_params.trace(tracer)
+    }
+}
+
+impl<'js> JsClass<'js> for Url<'js> {
+    const NAME: &'static str = "URL";
+
+    type Mutable = Writable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        let proto = Object::new(ctx.clone())?;
+
+        macro_rules! accessor {
+            ($name:literal, $get:ident) => {
+                proto.prop(
+                    $name,
+                    Accessor::from(|this: This<Class<'js, Url<'js>>>| {
+                        StdString::from(quirks::$get(&this.borrow().record))
+                    })
+                    .configurable(),
+                )?;
+            };
+            ($name:literal, $get:ident, $set:ident) => {
+                proto.prop(
+                    $name,
+                    Accessor::from(|this: This<Class<'js, Url<'js>>>| {
+                        StdString::from(quirks::$get(&this.borrow().record))
+                    })
+                    .set(
+                        |this: This<Class<'js, Url<'js>>>, value: Coerced<StdString>| {
+                            // Invalid values are ignored like the standard requires.
+                            let _ = quirks::$set(&mut this.borrow_mut().record, &value.0);
+                        },
+                    )
+                    .configurable(),
+                )?;
+            };
+        }
+
+        proto.prop(
+            "href",
+            Accessor::from(|this: This<Class<'js, Url<'js>>>| this.borrow().href())
+                .set(
+                    |ctx: Ctx<'js>,
+                     this: This<Class<'js, Url<'js>>>,
+                     value: Coerced<StdString>|
+                     -> Result<()> {
+                        let record = parse_with_base(&value.0, None).ok_or_else(|| {
+                            Exception::throw_type(&ctx, &format!("Invalid URL: {}", value.0))
+                        })?;
+                        let mut url = this.borrow_mut();
+                        url.record = record;
+                        url.update_search_params();
+                        Ok(())
+                    },
+                )
+                .configurable(),
+        )?;
+        accessor!("origin", origin);
+        accessor!("protocol", protocol, set_protocol);
+        accessor!("username", username, set_username);
+        accessor!("password", password, set_password);
+        accessor!("host", host, set_host);
+        accessor!("hostname", hostname, set_hostname);
+        accessor!("port", port, set_port);
+        accessor!("pathname", pathname, set_pathname);
+        proto.prop(
+            "search",
+            Accessor::from(|this: This<Class<'js, Url<'js>>>| {
+                StdString::from(quirks::search(&this.borrow().record))
+            })
+            .set(
+                |this: This<Class<'js, Url<'js>>>, value: Coerced<StdString>| {
+                    let mut url = this.borrow_mut();
+                    quirks::set_search(&mut url.record, &value.0);
+                    url.update_search_params();
+                },
+            )
+            .configurable(),
+        )?;
+        accessor!("hash", hash, set_hash);
+        proto.prop(
+            "searchParams",
+            Accessor::from(
+                |ctx: Ctx<'js>,
+                 this: This<Class<'js, Url<'js>>>|
+                 -> Result<Class<'js, UrlSearchParams<'js>>> {
+                    if let Some(params) = &this.borrow().search_params {
+                        return Ok(params.clone());
+                    }
+                    let list = parse_form(this.borrow().record.query().unwrap_or_default());
+                    let params = Class::instance(
+                        ctx,
+                        UrlSearchParams {
+                            list,
+                            url: Some(this.0.clone()),
+                        },
+                    )?;
+                    this.borrow_mut().search_params = Some(params.clone());
+                    Ok(params)
+                },
+            )
+            .configurable(),
+        )?;
+
+        let to_string = Function::new(ctx.clone(), |this: This<Class<'js, Url<'js>>>| {
+            this.borrow().href()
+        })?;
+        proto.set("toString", to_string.clone().with_name("toString")?)?;
+        proto.set("toJSON", to_string.with_name("toJSON")?)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        let constructor = Constructor::new_class::<Url, _, _>(
+            ctx.clone(),
+            |ctx: Ctx<'js>, input: Coerced<StdString>, base: Opt<Coerced<StdString>>| {
+                let base = base.0.map(|x| x.0);
+                let record = parse_with_base(&input.0, base.as_deref()).ok_or_else(|| {
+                    Exception::throw_type(&ctx, &format!("Invalid URL: {}", input.0))
+                })?;
+                Class::instance(
+                    ctx.clone(),
+                    Url {
+                        record,
+                        search_params: None,
+                    },
+                )
+            },
+        )?;
+        let can_parse = Function::new(
+            ctx.clone(),
+            |input: Coerced<StdString>, base: Opt<Coerced<StdString>>| {
+                let base = base.0.map(|x| x.0);
+                parse_with_base(&input.0, base.as_deref()).is_some()
+            },
+        )?
+        .with_name("canParse")?;
+        constructor.set("canParse", can_parse)?;
+        Ok(Some(constructor))
+    }
+}
+
+/// A `URLSearchParams` object.
+pub struct UrlSearchParams<'js> {
+    list: Vec<(StdString, StdString)>,
+    /// The URL the parameters are the query of.
+    url: Option<Class<'js, Url<'js>>>,
+}
+
+impl<'js> UrlSearchParams<'js> {
+    /// Returns the name-value pairs.
+    pub fn pairs(&self) -> &[(StdString, StdString)] {
+        &self.list
+    }
+
+    fn update(&self) {
+        if let Some(url) = &self.url {
+            let query = serialize_form(&self.list);
+            let query = Some(query.as_str()).filter(|x| !x.is_empty());
+            url.borrow_mut().record.set_query(query);
+        }
+    }
+}
+
+impl<'js> Trace<'js> for UrlSearchParams<'js> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.url.trace(tracer)
+    }
+}
+
+/// Returns the name-value pairs from the argument of the `URLSearchParams` constructor.
+pub(super) fn init_list<'js>(
+    ctx: &Ctx<'js>,
+    init: Value<'js>,
+) -> Result<Vec<(StdString, StdString)>> {
+    if let Some(object) = init.as_object() {
+        let iterator: Value = object.get(PredefinedAtom::SymbolIterator)?;
+        if !iterator.is_function() {
+            return object
+                .props::<StdString, Coerced<StdString>>()
+                .map(|x| x.map(|(name, value)| (name, value.0)))
+                .collect();
+        }
+        let from: Function = ctx.globals().get::<_, Object>("Array")?.get("from")?;
+        let pairs: Vec<Array> = from.call((init,))?;
+        return pairs
+            .into_iter()
+            .map(|pair| {
+                if pair.len() != 2 {
+                    return Err(Exception::throw_type(
+                        ctx,
+                        "Each name-value pair must have exactly two items",
+                    ));
+                }
+                let name: Coerced<StdString> = pair.get(0)?;
+                let value: Coerced<StdString> = pair.get(1)?;
+                Ok((name.0, value.0))
+            })
+            .collect();
+    }
+    let init: Coerced<StdString> = init.get()?;
+    Ok(parse_form(init.0.strip_prefix('?').unwrap_or(&init.0)))
+}
+
+/// Returns an array iterator over a snapshot of the values.
+pub(super) fn array_iterator<'js, T>(ctx: &Ctx<'js>, values: T) -> Result<Value<'js>>
+where
+    T: IntoIterator,
+    T::Item: crate::IntoJs<'js>,
+{
+    let array = Array::new(ctx.clone())?;
+    for (i, value) in values.into_iter().enumerate() {
+        array.set(i, value)?;
+    }
+    let values: Function = array.as_object().get("values")?;
+    values.call((This(array),))
+}
+
+impl<'js> JsClass<'js> for UrlSearchParams<'js> {
+    const NAME: &'static str = "URLSearchParams";
+
+    type Mutable = Writable;
+
+    fn class_id() -> &'static ClassId {
+        static ID: ClassId = ClassId::new();
+        &ID
+    }
+
+    fn prototype(ctx: &Ctx<'js>) -> Result<Option<Object<'js>>> {
+        type ThisParams<'js> = This<Class<'js, UrlSearchParams<'js>>>;
+
+        let proto = Object::new(ctx.clone())?;
+        proto.prop(
+            "size",
+            Accessor::from(|this: ThisParams<'js>| this.borrow().list.len()).configurable(),
+        )?;
+
+        let append = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>, value: Coerced<StdString>| {
+                let mut params = this.borrow_mut();
+                params.list.push((name.0, value.0));
+                params.update();
+            },
+        )?;
+        proto.set("append", append.with_name("append")?)?;
+
+        let delete = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>, value: Opt<Coerced<StdString>>| {
+                let mut params = this.borrow_mut();
+                let value = value.0.map(|x| x.0);
+                params
+                    .list
+                    .retain(|(n, v)| *n != name.0 || matches!(&value, Some(x) if x != v));
+                params.update();
+            },
+        )?;
+        proto.set("delete", delete.with_name("delete")?)?;
+
+        let get = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>| {
+                let params = this.borrow();
+                let value = params.list.iter().find(|(n, _)| *n == name.0);
+                value.map(|(_, v)| v.clone())
+            },
+        )?;
+        proto.set("get", get.with_name("get")?)?;
+
+        let get_all = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>| {
+                let params = this.borrow();
+                let values = params.list.iter().filter(|(n, _)| *n == name.0);
+                values.map(|(_, v)| v.clone()).collect::<Vec<_>>()
+            },
+        )?;
+        proto.set("getAll", get_all.with_name("getAll")?)?;
+
+        let has = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>, value: Opt<Coerced<StdString>>| {
+                let value = value.0.map(|x| x.0);
+                this.borrow()
+                    .list
+                    .iter()
+                    .any(|(n, v)| *n == name.0 && (value.is_none() || value.as_ref() == Some(v)))
+            },
+        )?;
+        proto.set("has", has.with_name("has")?)?;
+
+        let set = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>, name: Coerced<StdString>, value: Coerced<StdString>| {
+                let mut params = this.borrow_mut();
+                let mut value = Some(value.0);
+                params.list.retain_mut(|(n, v)| {
+                    if *n != name.0 {
+                        return true;
+                    }
+                    match value.take() {
+                        Some(value) => {
+                            *v = value;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+                if let Some(value) = value {
+                    params.list.push((name.0, value));
+                }
+                params.update();
+            },
+        )?;
+        proto.set("set", set.with_name("set")?)?;
+
+        let sort = Function::new(ctx.clone(), |this: ThisParams<'js>| {
+            let mut params = this.borrow_mut();
+            // Names are compared by their UTF-16 code units, the sort is stable.
+            params
+                .list
+                .sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            params.update();
+        })?;
+        proto.set("sort", sort.with_name("sort")?)?;
+
+        let to_string = Function::new(ctx.clone(), |this: ThisParams<'js>| {
+            serialize_form(&this.borrow().list)
+        })?;
+        proto.set("toString", to_string.with_name("toString")?)?;
+
+        let for_each = Function::new(
+            ctx.clone(),
+            |this: ThisParams<'js>,
+             callback: Function<'js>,
+             this_arg: Opt<Value<'js>>|
+             -> Result<()> {
+                let this_arg = this_arg
+                    .0
+                    .unwrap_or_else(|| Value::new_undefined(callback.ctx().clone()));
+                let mut i = 0;
+                // The list is not borrowed while calling the callback as it may change it.
+                loop {
+                    let pair = this.borrow().list.get(i).cloned();
+                    let Some((name, value)) = pair else {
+                        return Ok(());
+                    };
+                    let _: Value =
+                        callback.call((This(this_arg.clone()), value, name, this.0.clone()))?;
+                    i += 1;
+                }
+            },
+        )?;
+        proto.set("forEach", for_each.with_name("forEach")?)?;
+
+        let keys = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisParams<'js>| {
+            let names: Vec<_> = this.borrow().list.iter().map(|(n, _)| n.clone()).collect();
+            array_iterator(&ctx, names)
+        })?;
+        proto.set("keys", keys.with_name("keys")?)?;
+
+        let values = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisParams<'js>| {
+            let values: Vec<_> = this.borrow().list.iter().map(|(_, v)| v.clone()).collect();
+            array_iterator(&ctx, values)
+        })?;
+        proto.set("values", values.with_name("values")?)?;
+
+        let entries = Function::new(ctx.clone(), |ctx: Ctx<'js>, this: ThisParams<'js>| {
+            let pairs: Vec<_> = this
+                .borrow()
+                .list
+                .iter()
+                .map(|(n, v)| vec![n.clone(), v.clone()])
+                .collect();
+            array_iterator(&ctx, pairs)
+        })?
+        .with_name("entries")?;
+        proto.set("entries", entries.clone())?;
+        proto.set(PredefinedAtom::SymbolIterator, entries)?;
+
+        Ok(Some(proto))
+    }
+
+    fn constructor(ctx: &Ctx<'js>) -> Result<Option<Constructor<'js>>> {
+        Constructor::new_class::<UrlSearchParams, _, _>(
+            ctx.clone(),
+            |ctx: Ctx<'js>, init: Opt<Value<'js>>| {
+                let list = match init.0 {
+                    Some(init) if !init.is_undefined() => init_list(&ctx, init)?,
+                    _ => Vec::new(),
+                };
+                Class::instance(ctx.clone(), UrlSearchParams { list, url: None })
+            },
+        )
+        .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{globals::init_url, test_with, CatchResultExt, StdString};
+
+    #[test]
+    fn url() {
+        test_with(|ctx| {
+            init_url(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const url = new URL('HTTPS://user:pw@Example.COM:8080/a/../b?q=1#h');
+                    const parts = [url.href, url.origin, url.protocol, url.username, url.password, url.host, url.hostname, url.port, url.pathname, url.search, url.hash];
+                    url.port = '443';
+                    url.pathname = 'c d';
+                    url.hash = '';
+                    parts.push(url.toString(), JSON.stringify({ url }));
+                    parts.push(String(URL.canParse('/x', 'http://h')), String(URL.canParse('nope')));
+                    try { new URL('/x') } catch (e) { parts.push(e.name) }
+                    parts
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "https://user:pw@example.com:8080/b?q=1#h",
+                    "https://example.com:8080",
+                    "https:",
+                    "user",
+                    "pw",
+                    "example.com:8080",
+                    "example.com",
+                    "8080",
+                    "/b",
+                    "?q=1",
+                    "#h",
+                    "https://user:pw@example.com/c%20d?q=1",
+                    r#"{"url":"https://user:pw@example.com/c%20d?q=1"}"#,
+                    "true",
+                    "false",
+                    "TypeError",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn search_params() {
+        test_with(|ctx| {
+            init_url(&ctx).unwrap();
+            let res: Vec<StdString> = ctx
+                .eval(
+                    r#"
+                    const params = new URLSearchParams('?b=2&a=1&b=3');
+                    const res = [params.get('b'), params.getAll('b').join(), String(params.has('a', '2')), String(params.size)];
+                    params.set('b', 'x y');
+                    params.append('c', '&');
+                    params.sort();
+                    res.push(params.toString(), [...params].join('|'), [...params.keys()].join());
+                    params.delete('a');
+                    const seen = [];
+                    params.forEach((value, name) => seen.push(name + value));
+                    res.push(seen.join());
+                    res.push(new URLSearchParams({ x: 1, y: 'z' }).toString());
+                    res.push(new URLSearchParams([['k', 'v'], ['k', 'w']]).toString());
+
+                    const url = new URL('http://h/?a=1');
+                    url.searchParams.append('b', '2');
+                    res.push(url.href);
+                    url.search = '?c=3';
+                    res.push(url.searchParams.get('c'));
+                    url.searchParams.delete('c');
+                    res.push(url.href);
+                    res
+                "#,
+                )
+                .catch(&ctx)
+                .unwrap();
+            assert_eq!(
+                res,
+                [
+                    "2",
+                    "2,3",
+                    "false",
+                    "3",
+                    "a=1&b=x+y&c=%26",
+                    "a,1|b,x y|c,&",
+                    "a,b,c",
+                    "bx y,c&",
+                    "x=1&y=z",
+                    "k=v&k=w",
+                    "http://h/?a=1&b=2",
+                    "3",
+                    "http://h/",
+                ]
+            );
+        })
+    }
+}
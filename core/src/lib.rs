@@ -8,9 +8,25 @@
 #![allow(clippy::needless_lifetimes)]
 #![cfg_attr(feature = "doc-cfg", feature(doc_cfg))]
 
-//#[doc(hidden)]
+#[cfg_attr(not(feature = "unsafe-api"), doc(hidden))]
 pub mod qjs {
-    //! Native low-level bindings
+    //! Native low-level bindings to the QuickJS C library.
+    //!
+    //! These allow calling C functions the safe bindings don't cover yet. Use
+    //! [`Ctx::as_raw`](crate::Ctx::as_raw) to get the context pointer,
+    //! [`Value::as_raw`](crate::Value::as_raw) and [`Value::from_raw`](crate::Value::from_raw) to
+    //! move values across the boundary and
+    //! [`Ctx::handle_exception`](crate::Ctx::handle_exception) to turn exceptions returned by the
+    //! C functions into [`Error`](crate::Error)s.
+    //!
+    //! Raw code must follow the rules of the library:
+    //! - Only use the pointers while the runtime is locked, i.e. inside the `with` closure which
+    //!   provided the [`Ctx`](crate::Ctx).
+    //! - Values are reference counted, a `JSValue` returned by a C function is owned and must be
+    //!   either freed or given to [`Value::from_raw`](crate::Value::from_raw)
+    //!   exactly once. Values passed as `JSValueConst` are borrowed.
+    //! - Don't free the context or runtime, or change the opaque pointers, interrupt handler or
+    //!   allocator functions used by the library.
     pub use rquickjs_sys::*;
 }
 
@@ -59,11 +75,14 @@ pub use context::MultiWith;
 pub use context::{Context, Ctx};
 mod persistent;
 mod value;
+#[cfg(feature = "parallel")]
+pub use persistent::PersistentSend;
 pub use persistent::{Outlive, Persistent};
 pub use value::{
-    array, atom, convert, function, module, object, Array, Atom, BigInt, Exception, FromAtom,
-    FromJs, Function, IntoAtom, IntoJs, Module, Null, Object, String, Symbol, Type, Undefined,
-    Value,
+    array, atom, convert, function, iterator, module, object, Array, Atom, BigInt, Date, Exception,
+    FromAtom, FromJs, Function, InspectOptions, IntoAtom, IntoJs, JsError, JsStr, Map, MapEntries,
+    MapIter, Module, Null, Object, ReadOptions, RegExp, Set, String, Symbol, Type, Undefined,
+    Value, WriteOptions,
 };
 
 pub mod class;
@@ -71,7 +90,11 @@ pub use class::Class;
 
 #[cfg(feature = "array-buffer")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "array-buffer")))]
-pub use value::{ArrayBuffer, TypedArray};
+pub use value::{ArrayBuffer, DataView, TypedArray};
+
+#[cfg(feature = "shared-array-buffer")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "shared-array-buffer")))]
+pub use value::{shared_array_buffer, SharedArrayBuffer};
 
 pub(crate) use std::{result::Result as StdResult, string::String as StdString};
 
@@ -87,15 +110,59 @@ pub mod allocator;
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
 pub mod loader;
 
+#[cfg(feature = "source-map")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "source-map")))]
+pub mod source_map;
+
+#[cfg(feature = "coverage")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "coverage")))]
+pub mod coverage;
+
+#[cfg(feature = "worker")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "worker")))]
+pub mod worker;
+
+#[cfg(feature = "fs")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "fs")))]
+pub mod fs;
+
+#[cfg(feature = "events")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "events")))]
+pub mod events;
+
+#[cfg(feature = "testing")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "testing")))]
+pub mod testing;
+
+#[cfg(feature = "os")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "os")))]
+pub mod os;
+
+#[cfg(feature = "debug")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "debug")))]
+pub mod debugger;
+
+#[cfg(feature = "wasm")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "wasm")))]
+pub mod wasm;
+
+mod clock;
+
+pub mod globals;
+
 pub mod prelude {
     //! A group of often used types.
     #[cfg(feature = "multi-ctx")]
     pub use crate::context::MultiWith;
     pub use crate::{
-        convert::{Coerced, FromAtom, FromJs, IntoAtom, IntoJs, IteratorJs, List},
+        convert::{
+            Coerced, FromAtom, FromJs, IntoAtom, IntoJs, IntoMultiValue, IteratorJs, List,
+            MultiValue, Nullable, ResultObject,
+        },
         function::{
             Exhaustive, Flat, Func, FuncArg, IntoArg, IntoArgs, MutFn, OnceFn, Opt, Rest, This,
         },
+        iterator::{Iterable, JsIterator},
         result::{CatchResultExt, ThrowResultExt},
     };
     #[cfg(feature = "futures")]
@@ -1,6 +1,10 @@
 //! Loaders and resolvers for loading JS modules.
 
-use std::{ffi::CStr, ptr};
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::CStr,
+    ptr,
+};
 
 use crate::{module::ModuleData, qjs, Ctx, Module, Result};
 
@@ -10,6 +14,18 @@ pub use builtin_resolver::BuiltinResolver;
 mod file_resolver;
 pub use file_resolver::FileResolver;
 
+mod node_resolver;
+pub use node_resolver::NodeResolver;
+
+#[cfg(feature = "url-loader")]
+mod url_loader;
+#[cfg(feature = "url-loader")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "url-loader")))]
+pub use url_loader::{UrlCache, UrlFetch, UrlLoader, UrlResolver};
+
+mod bytecode_loader;
+pub use bytecode_loader::BytecodeLoader;
+
 mod script_loader;
 pub use script_loader::ScriptLoader;
 
@@ -19,12 +35,26 @@ pub use builtin_loader::BuiltinLoader;
 mod module_loader;
 pub use module_loader::ModuleLoader;
 
+#[cfg(feature = "futures")]
+mod async_loader;
+#[cfg(feature = "futures")]
+pub(crate) use async_loader::AsyncModules;
+#[cfg(feature = "futures")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub use async_loader::{AsyncLoader, AsyncResolver, LoadFuture};
+
 mod compile;
 pub use compile::Compile;
 
-#[cfg(feature = "dyn-load")]
+mod transform;
+#[cfg(feature = "typescript")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "typescript")))]
+pub use transform::TypeScript;
+pub use transform::{SourceTransformer, Transform};
+
+#[cfg(all(feature = "dyn-load", not(target_family = "wasm")))]
 mod native_loader;
-#[cfg(feature = "dyn-load")]
+#[cfg(all(feature = "dyn-load", not(target_family = "wasm")))]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "dyn-load")))]
 pub use native_loader::NativeLoader;
 
@@ -100,6 +130,8 @@ unsafe impl<T: Loader> RawLoader for T {
 struct LoaderOpaque {
     resolver: Box<dyn Resolver>,
     loader: Box<dyn RawLoader>,
+    // { module_name: [importer_name] }
+    importers: HashMap<String, HashSet<String>>,
 }
 
 #[derive(Debug)]
@@ -121,9 +153,16 @@ impl LoaderHolder {
         Self(Box::into_raw(Box::new(LoaderOpaque {
             resolver: Box::new(resolver),
             loader: Box::new(loader),
+            importers: HashMap::new(),
         })))
     }
 
+    /// Returns the names of the modules which imported the module since it was last taken.
+    pub(crate) fn take_importers(&self, name: &str) -> HashSet<String> {
+        let opaque = unsafe { &mut *self.0 };
+        opaque.importers.remove(name).unwrap_or_default()
+    }
+
     pub(crate) fn set_to_runtime(&self, rt: *mut qjs::JSRuntime) {
         unsafe {
             qjs::JS_SetModuleLoaderFunc(
@@ -146,6 +185,13 @@ impl LoaderHolder {
         let name = name.to_str()?;
 
         let name = opaque.resolver.resolve(ctx, base, name)?;
+        if !base.is_empty() {
+            opaque
+                .importers
+                .entry(name.clone())
+                .or_default()
+                .insert(base.into());
+        }
 
         // We should transfer ownership of this string to QuickJS
         Ok(
@@ -270,9 +316,11 @@ loader_impls!(A B C D E F G H);
 
 #[cfg(test)]
 mod test {
-    use crate::{module::ModuleData, Context, Ctx, Error, Result, Runtime};
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-    use super::{Loader, Resolver};
+    use crate::{module::ModuleData, Context, Ctx, Error, Module, Object, Result, Runtime};
+
+    use super::{BuiltinResolver, Loader, Resolver};
 
     struct TestResolver;
 
@@ -351,4 +399,67 @@ mod test {
                 .expect("Unable to resolve");
         })
     }
+
+    struct SharedLoader(Rc<RefCell<HashMap<&'static str, &'static str>>>);
+
+    impl Loader for SharedLoader {
+        fn load<'js>(&mut self, _ctx: &Ctx<'js>, name: &str) -> Result<ModuleData> {
+            let source = *self.0.borrow().get(name).ok_or(Error::new_loading(name))?;
+            Ok(ModuleData::source(name, source))
+        }
+    }
+
+    #[test]
+    fn invalidate_module() {
+        let sources = Rc::new(RefCell::new(HashMap::from([
+            ("a", "import { b } from 'b'; export const value = b + 1;"),
+            ("b", "export const b = 1;"),
+            ("c", "import { b } from 'b'; export const value = b;"),
+            ("d", "export const value = 'd';"),
+        ])));
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        rt.set_loader(
+            BuiltinResolver::default()
+                .with_module("a")
+                .with_module("b")
+                .with_module("c")
+                .with_module("d"),
+            SharedLoader(sources.clone()),
+        );
+        let import = |name: &str| {
+            ctx.with(|ctx| {
+                Module::import::<Object, _>(&ctx, name)
+                    .unwrap()
+                    .get::<_, i32>("value")
+                    .unwrap()
+            })
+        };
+        assert_eq!(import("a"), 2);
+        assert_eq!(import("c"), 1);
+
+        sources.borrow_mut().insert("b", "export const b = 10;");
+        assert_eq!(import("a"), 2);
+        let mut invalidated = rt.invalidate_module("b").unwrap();
+        invalidated.sort();
+        assert_eq!(invalidated, ["a", "b", "c"]);
+        assert_eq!(import("a"), 11);
+        assert_eq!(import("c"), 10);
+
+        assert!(rt.invalidate_module("d").unwrap().is_empty());
+
+        // The invalidated modules are freed, so reloading doesn't grow the heap.
+        let reload = || {
+            rt.invalidate_module("b").unwrap();
+            assert_eq!(import("a"), 11);
+        };
+        reload();
+        rt.run_gc();
+        let allocations = rt.memory_usage().malloc_count;
+        for _ in 0..10 {
+            reload();
+        }
+        rt.run_gc();
+        assert_eq!(rt.memory_usage().malloc_count, allocations);
+    }
 }
@@ -0,0 +1,310 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::CString,
+    future::{self, Future},
+    pin::Pin,
+    sync::{Arc, Mutex as SyncMutex},
+};
+
+use async_lock::Mutex;
+
+use crate::{module::ModuleData, qjs, Atom, Ctx, Error, Result};
+
+use super::{Loader, Resolver};
+
+/// The boxed future returned by the async resolvers and loaders.
+pub type LoadFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + 'a>>;
+
+/// Async module resolver interface, set with
+/// [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader).
+///
+/// All [`Resolver`]s are async resolvers which resolve immediately.
+#[cfg_attr(
+    feature = "doc-cfg",
+    doc(cfg(all(feature = "loader", feature = "futures")))
+)]
+pub trait AsyncResolver {
+    /// Normalize module name
+    fn resolve<'a, 'js>(
+        &'a mut self,
+        ctx: &'a Ctx<'js>,
+        base: &'a str,
+        name: &'a str,
+    ) -> LoadFuture<'a, String>;
+}
+
+impl<T: Resolver> AsyncResolver for T {
+    fn resolve<'a, 'js>(
+        &'a mut self,
+        ctx: &'a Ctx<'js>,
+        base: &'a str,
+        name: &'a str,
+    ) -> LoadFuture<'a, String> {
+        Box::pin(future::ready(Resolver::resolve(self, ctx, base, name)))
+    }
+}
+
+/// Async module loader interface, set with
+/// [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader).
+///
+/// All [`Loader`]s are async loaders which load immediately.
+///
+/// ```
+/// # use rquickjs::{Ctx, Result, Error, loader::{AsyncLoader, LoadFuture}, module::ModuleData};
+/// struct Remote;
+///
+/// impl AsyncLoader for Remote {
+///     fn load<'a, 'js>(&'a mut self, _ctx: &'a Ctx<'js>, name: &'a str) -> LoadFuture<'a, ModuleData> {
+///         Box::pin(async move {
+///             // Fetch the source with async I/O...
+///             # let source = "export default 1";
+///             Ok(ModuleData::source(name, source))
+///         })
+///     }
+/// }
+/// ```
+#[cfg_attr(
+    feature = "doc-cfg",
+    doc(cfg(all(feature = "loader", feature = "futures")))
+)]
+pub trait AsyncLoader {
+    /// Load module by name
+    ///
+    /// The returned module data should have the given name.
+    fn load<'a, 'js>(&'a mut self, ctx: &'a Ctx<'js>, name: &'a str) -> LoadFuture<'a, ModuleData>;
+}
+
+impl<T: Loader> AsyncLoader for T {
+    fn load<'a, 'js>(&'a mut self, ctx: &'a Ctx<'js>, name: &'a str) -> LoadFuture<'a, ModuleData> {
+        Box::pin(future::ready(Loader::load(self, ctx, name)))
+    }
+}
+
+/// The state of an async loader shared with the synchronous loader installed in the runtime.
+pub(crate) struct AsyncModules {
+    resolver: Mutex<Box<dyn AsyncResolver>>,
+    loader: Mutex<Box<dyn AsyncLoader>>,
+    resolved: SyncMutex<Resolved>,
+}
+
+#[derive(Default)]
+struct Resolved {
+    // { (base, name): resolved_name }
+    names: HashMap<(String, String), String>,
+    modules: HashSet<String>,
+}
+
+impl AsyncModules {
+    pub fn new<R, L>(resolver: R, loader: L) -> Self
+    where
+        R: AsyncResolver + 'static,
+        L: AsyncLoader + 'static,
+    {
+        AsyncModules {
+            resolver: Mutex::new(Box::new(resolver)),
+            loader: Mutex::new(Box::new(loader)),
+            resolved: SyncMutex::new(Resolved::default()),
+        }
+    }
+
+    /// Returns the synchronous resolver and loader for the modules fetched by this loader.
+    pub fn prefetched(self: &Arc<Self>) -> Prefetched {
+        Prefetched(self.clone())
+    }
+
+    fn lookup(&self, base: &str, name: &str) -> Option<String> {
+        let resolved = self.resolved.lock().unwrap();
+        resolved
+            .names
+            .get(&(base.to_owned(), name.to_owned()))
+            .cloned()
+    }
+
+    fn record(&self, base: String, name: String, resolved: String) {
+        let mut state = self.resolved.lock().unwrap();
+        state.modules.insert(resolved.clone());
+        state.names.insert((base, name), resolved);
+    }
+
+    /// Fetch and declare the module and all the modules it depends on which are not loaded
+    /// yet, returning the name of the module.
+    ///
+    /// The runtime lock is released while awaiting the resolver and the loader so no
+    /// unevaluated module is held across an await.
+    pub async fn prefetch<'js>(&self, ctx: &Ctx<'js>, specifier: &str) -> Result<String> {
+        let mut root = None;
+        let mut pending = vec![(String::new(), specifier.to_owned())];
+        let mut visited = HashSet::new();
+        while let Some((base, name)) = pending.pop() {
+            let resolved = match self.lookup(&base, &name) {
+                Some(resolved) => resolved,
+                None => {
+                    let resolved = self
+                        .resolver
+                        .lock()
+                        .await
+                        .resolve(ctx, &base, &name)
+                        .await?;
+                    self.record(base.clone(), name.clone(), resolved.clone());
+                    resolved
+                }
+            };
+
+            let mut module = find_loaded(ctx, &resolved)?;
+            if module.is_null() {
+                let data = self.loader.lock().await.load(ctx, &resolved).await?;
+                // The module could have been loaded by another import while awaiting.
+                module = find_loaded(ctx, &resolved)?;
+                if module.is_null() {
+                    let declared = unsafe { data.unsafe_declare_unresolved(ctx.clone())? };
+                    let declared_name = declared.name::<String>()?;
+                    if declared_name != resolved {
+                        self.record(base.clone(), name.clone(), declared_name);
+                    }
+                    module = declared.as_module_def().as_ptr();
+                }
+            }
+
+            let name = unsafe { module_name(ctx, module)? };
+            if root.is_none() {
+                root = Some(name.clone());
+            }
+            if visited.insert(name.clone()) {
+                for request in unsafe { module_requests(ctx, module)? } {
+                    pending.push((name.clone(), request));
+                }
+            }
+        }
+        Ok(root.unwrap())
+    }
+}
+
+fn find_loaded(ctx: &Ctx<'_>, name: &str) -> Result<*mut qjs::JSModuleDef> {
+    let name = CString::new(name)?;
+    Ok(unsafe { qjs::JS_FindLoadedModule(ctx.as_ptr(), name.as_ptr()) })
+}
+
+unsafe fn module_name(ctx: &Ctx<'_>, module: *mut qjs::JSModuleDef) -> Result<String> {
+    Atom::from_atom_val(ctx.clone(), qjs::JS_GetModuleName(ctx.as_ptr(), module)).to_string()
+}
+
+unsafe fn module_requests(ctx: &Ctx<'_>, module: *mut qjs::JSModuleDef) -> Result<Vec<String>> {
+    let count = qjs::JS_GetModuleRequestCount(module);
+    (0..count)
+        .map(|idx| {
+            Atom::from_atom_val(
+                ctx.clone(),
+                qjs::JS_GetModuleRequest(ctx.as_ptr(), module, idx),
+            )
+            .to_string()
+        })
+        .collect()
+}
+
+/// The resolver and loader installed in the runtime by an async loader.
+///
+/// Every module fetched by the async loader is declared before it is linked, so QuickJS finds
+/// the loaded modules and this loader only has to answer the resolutions.
+pub(crate) struct Prefetched(Arc<AsyncModules>);
+
+impl Resolver for Prefetched {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<String> {
+        if let Some(resolved) = self.0.lookup(base, name) {
+            return Ok(resolved);
+        }
+        // Fetched modules can also be imported by their resolved name.
+        if self.0.resolved.lock().unwrap().modules.contains(name) {
+            return Ok(name.to_owned());
+        }
+        Err(Error::new_resolving_message(base, name, NOT_FETCHED))
+    }
+}
+
+impl Loader for Prefetched {
+    fn load<'js>(&mut self, _ctx: &Ctx<'js>, name: &str) -> Result<ModuleData> {
+        Err(Error::new_loading_message(name, NOT_FETCHED))
+    }
+}
+
+const NOT_FETCHED: &str =
+    "The module was not fetched by the async loader, import it with `Module::import_async`";
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        async_with, loader::BuiltinResolver, module::ModuleData, AsyncContext, AsyncRuntime,
+        CatchResultExt, Ctx, Error, Module, Object,
+    };
+
+    use super::{AsyncLoader, LoadFuture};
+
+    #[derive(Clone, Default)]
+    struct Remote {
+        sources: HashMap<&'static str, &'static str>,
+        fetched: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl AsyncLoader for Remote {
+        fn load<'a, 'js>(
+            &'a mut self,
+            _ctx: &'a Ctx<'js>,
+            name: &'a str,
+        ) -> LoadFuture<'a, ModuleData> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                self.fetched.lock().unwrap().push(name.to_owned());
+                let source = self.sources.get(name).ok_or(Error::new_loading(name))?;
+                Ok(ModuleData::source(name, *source))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn async_import() {
+        let rt = AsyncRuntime::new().unwrap();
+        let remote = Remote {
+            sources: [
+                (
+                    "main",
+                    "import { add } from 'math'; import two from 'two'; export default add(two, 1);",
+                ),
+                ("math", "import two from 'two'; export const add = (a, b) => a + b + two - 2;"),
+                ("two", "export default 2;"),
+                ("broken", "import 'missing';"),
+            ]
+            .into_iter()
+            .collect(),
+            ..Remote::default()
+        };
+        let fetched = remote.fetched.clone();
+        let resolver = BuiltinResolver::default()
+            .with_module("main")
+            .with_module("math")
+            .with_module("two")
+            .with_module("broken")
+            .with_module("missing");
+        rt.set_async_loader(resolver, remote).await;
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let main: Object = Module::import_async(&ctx, "main").await.catch(&ctx).unwrap();
+            assert_eq!(main.get::<_, i32>("default").unwrap(), 3);
+            // Loaded modules aren't fetched again, also not by the engine.
+            let math: Object = Module::import_async(&ctx, "math").await.unwrap();
+            assert!(math.contains_key("add").unwrap());
+            assert_eq!(ctx.eval::<i32, _>("1").unwrap(), 1);
+            assert!(Module::import_async::<Object>(&ctx, "broken").await.is_err());
+            // Modules which weren't fetched can't be loaded synchronously.
+            assert!(Module::import::<Object, _>(&ctx, "unknown").is_err());
+        })
+        .await;
+
+        let fetched = fetched.lock().unwrap();
+        assert_eq!(*fetched, ["main", "two", "math", "broken", "missing"]);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    loader::{util::check_extensions, Loader},
+    module::ModuleData,
+    Ctx, Error, Result,
+};
+
+/// The bytecode module loader
+///
+/// Loads files containing module bytecode which was written by [`Module::write_object`]
+/// (`.qjsbc` files by default). The module should be compiled with the name it is resolved to,
+/// for example by a [`FileResolver`] with the `{}.qjsbc` pattern.
+///
+/// [`Module::write_object`]: crate::Module::write_object
+/// [`FileResolver`]: crate::loader::FileResolver
+#[derive(Debug)]
+pub struct BytecodeLoader {
+    extensions: Vec<String>,
+}
+
+impl BytecodeLoader {
+    /// Create a new bytecode loader
+    ///
+    /// # Safety
+    /// QuickJS does not validate the bytecode it reads. The loaded files must contain bytecode
+    /// written by the same version of QuickJS and for the same endianness, loading any other
+    /// data is undefined behavior.
+    pub unsafe fn new() -> Self {
+        Self {
+            extensions: vec!["qjsbc".into()],
+        }
+    }
+
+    /// Add bytecode file extension
+    pub fn add_extension<X: Into<String>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add bytecode file extension
+    #[must_use]
+    pub fn with_extension<X: Into<String>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+}
+
+impl Loader for BytecodeLoader {
+    fn load<'js>(&mut self, _ctx: &Ctx<'js>, path: &str) -> Result<ModuleData> {
+        if !check_extensions(path, &self.extensions) {
+            return Err(Error::new_loading(path));
+        }
+
+        let bytecode: Vec<_> = std::fs::read(path)?;
+        // Safety: ensured by the creator of the loader.
+        Ok(unsafe { ModuleData::bytecode(path, bytecode) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        loader::{BuiltinResolver, BytecodeLoader},
+        Context, Module, Runtime,
+    };
+
+    #[test]
+    fn load_bytecode() {
+        let dir = std::env::temp_dir()
+            .join(format!("rquickjs-bytecode-loader-{}", std::process::id()))
+            .display()
+            .to_string();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{dir}/lib.qjsbc");
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let bytecode = ctx.with(|ctx| unsafe {
+            Module::unsafe_declare(ctx, path.clone(), "export const value = 6 * 7;")
+                .unwrap()
+                .write_object(false)
+                .unwrap()
+        });
+        std::fs::write(&path, bytecode).unwrap();
+
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default().with_module(path.as_str()),
+            unsafe { BytecodeLoader::new() },
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let module = Module::evaluate(
+                ctx,
+                format!("{dir}/main"),
+                format!("import {{ value }} from {path:?}; export const res = value;"),
+            )
+            .unwrap();
+            assert_eq!(module.get::<_, i32>("res").unwrap(), 42);
+        });
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,485 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{loader::Resolver, Ctx, Error, Object, Result, StdString, Value};
+
+/// The Node.js style module resolver
+///
+/// Relative names are resolved against the importing module, bare names are looked up in the
+/// `node_modules` directories of the importing module and all of its parent directories, and
+/// then in the additional search paths.
+///
+/// Packages are resolved with the `exports` field of their `package.json`, including subpath
+/// patterns and conditions, or with the `module` and `main` fields when there are no exports.
+/// Files are probed with the known extensions and directories with their index files.
+///
+/// ```no_run
+/// # use rquickjs::{Runtime, loader::{NodeResolver, ScriptLoader}};
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(
+///     NodeResolver::default(),
+///     ScriptLoader::default().with_extension("mjs"),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct NodeResolver {
+    paths: Vec<PathBuf>,
+    extensions: Vec<StdString>,
+    main_fields: Vec<StdString>,
+    conditions: Vec<StdString>,
+}
+
+impl NodeResolver {
+    /// Add search path for packages, like a path of `NODE_PATH`
+    pub fn add_path<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.paths.push(path.into());
+        self
+    }
+
+    /// Add search path for packages, like a path of `NODE_PATH`
+    #[must_use]
+    pub fn with_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.add_path(path);
+        self
+    }
+
+    /// Add file extension which is probed when a name has none
+    pub fn add_extension<X: Into<StdString>>(&mut self, extension: X) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Add file extension which is probed when a name has none
+    #[must_use]
+    pub fn with_extension<X: Into<StdString>>(mut self, extension: X) -> Self {
+        self.add_extension(extension);
+        self
+    }
+
+    /// Add `package.json` field which is used as the entry point of a package without exports
+    ///
+    /// The `module` and `main` fields are used by default.
+    pub fn add_main_field<F: Into<StdString>>(&mut self, field: F) -> &mut Self {
+        self.main_fields.push(field.into());
+        self
+    }
+
+    /// Add `package.json` field which is used as the entry point of a package without exports
+    ///
+    /// The `module` and `main` fields are used by default.
+    #[must_use]
+    pub fn with_main_field<F: Into<StdString>>(mut self, field: F) -> Self {
+        self.add_main_field(field);
+        self
+    }
+
+    /// Add condition which is matched in the `exports` field of packages
+    ///
+    /// The `import` and `module` conditions are matched by default, `default` always matches.
+    pub fn add_condition<C: Into<StdString>>(&mut self, condition: C) -> &mut Self {
+        self.conditions.push(condition.into());
+        self
+    }
+
+    /// Add condition which is matched in the `exports` field of packages
+    ///
+    /// The `import` and `module` conditions are matched by default, `default` always matches.
+    #[must_use]
+    pub fn with_condition<C: Into<StdString>>(mut self, condition: C) -> Self {
+        self.add_condition(condition);
+        self
+    }
+
+    fn load_as_file(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_file() {
+            return Some(path.to_path_buf());
+        }
+        self.extensions.iter().find_map(|extension| {
+            let mut file = OsString::from(path);
+            file.push(".");
+            file.push(extension);
+            let file = PathBuf::from(file);
+            file.is_file().then_some(file)
+        })
+    }
+
+    fn load_index(&self, dir: &Path) -> Option<PathBuf> {
+        self.extensions.iter().find_map(|extension| {
+            let file = dir.join(format!("index.{}", extension));
+            file.is_file().then_some(file)
+        })
+    }
+
+    fn load_as_directory<'js>(&self, ctx: &Ctx<'js>, dir: &Path) -> Result<Option<PathBuf>> {
+        if let Some(package) = read_package(ctx, dir)? {
+            for field in &self.main_fields {
+                let main: Option<StdString> = package.get(field.as_str()).ok().flatten();
+                if let Some(main) = main {
+                    let main = normalize(&dir.join(main));
+                    if let Some(path) = self.load_as_file(&main).or_else(|| self.load_index(&main))
+                    {
+                        return Ok(Some(path));
+                    }
+                }
+            }
+        }
+        Ok(self.load_index(dir))
+    }
+
+    fn load_path<'js>(&self, ctx: &Ctx<'js>, path: &Path) -> Result<Option<PathBuf>> {
+        match self.load_as_file(path) {
+            Some(path) => Ok(Some(path)),
+            None => self.load_as_directory(ctx, path),
+        }
+    }
+
+    fn load_package<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        dir: &Path,
+        subpath: &str,
+    ) -> Result<Option<PathBuf>> {
+        if let Some(package) = read_package(ctx, dir)? {
+            let exports: Value = package.get("exports")?;
+            if !(exports.is_undefined() || exports.is_null()) {
+                return self.resolve_exports(dir, exports, subpath).map(Some);
+            }
+        }
+        if subpath == "." {
+            self.load_as_directory(ctx, dir)
+        } else {
+            self.load_path(ctx, &normalize(&dir.join(subpath)))
+        }
+    }
+
+    fn resolve_exports<'js>(
+        &self,
+        dir: &Path,
+        exports: Value<'js>,
+        subpath: &str,
+    ) -> Result<PathBuf> {
+        let not_exported = || {
+            Error::new_resolving_message(
+                dir.to_string_lossy(),
+                subpath,
+                "the subpath is not exported by the package",
+            )
+        };
+        let subpaths = exports.as_object().filter(|exports| {
+            !exports.is_array()
+                && exports
+                    .keys::<StdString>()
+                    .next()
+                    .and_then(|key| key.ok())
+                    .map(|key| key.starts_with('.'))
+                    .unwrap_or(false)
+        });
+        let Some(subpaths) = subpaths else {
+            // The exports only define the main entry point.
+            if subpath != "." {
+                return Err(not_exported());
+            }
+            return self
+                .resolve_target(dir, exports, None)
+                .ok_or_else(not_exported);
+        };
+        if let Ok(target) = subpaths.get::<_, Value>(subpath) {
+            if !target.is_undefined() {
+                return self
+                    .resolve_target(dir, target, None)
+                    .ok_or_else(not_exported);
+            }
+        }
+        // Find the pattern with the longest prefix which matches the subpath.
+        let mut best: Option<(StdString, StdString)> = None;
+        for key in subpaths.keys::<StdString>() {
+            let key = key?;
+            let Some((prefix, suffix)) = key.split_once('*') else {
+                continue;
+            };
+            let matches = subpath.len() >= prefix.len() + suffix.len()
+                && subpath.starts_with(prefix)
+                && subpath.ends_with(suffix);
+            let longer = match &best {
+                Some((best, _)) => best.find('*').unwrap_or(0) < prefix.len(),
+                None => true,
+            };
+            if matches && longer {
+                let star = subpath[prefix.len()..subpath.len() - suffix.len()].to_string();
+                best = Some((key.clone(), star));
+            }
+        }
+        let (key, star) = best.ok_or_else(not_exported)?;
+        let target: Value = subpaths.get(key.as_str())?;
+        self.resolve_target(dir, target, Some(&star))
+            .ok_or_else(not_exported)
+    }
+
+    fn resolve_target(&self, dir: &Path, target: Value, star: Option<&str>) -> Option<PathBuf> {
+        if let Some(target) = target.as_string() {
+            let target = target.to_string().ok()?;
+            if !target.starts_with("./") {
+                return None;
+            }
+            let target = match star {
+                Some(star) => target.replace('*', star),
+                None => target,
+            };
+            let path = normalize(&dir.join(target));
+            return (path.starts_with(dir) && path.is_file()).then_some(path);
+        }
+        let target = target.into_object()?;
+        if target.is_array() {
+            let target = target.into_array()?;
+            return target.iter::<Value>().find_map(|target| {
+                let target = target.ok()?;
+                self.resolve_target(dir, target, star)
+            });
+        }
+        target.props::<StdString, Value>().find_map(|prop| {
+            let (condition, target) = prop.ok()?;
+            if condition == "default" || self.conditions.contains(&condition) {
+                self.resolve_target(dir, target, star)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl Default for NodeResolver {
+    fn default() -> Self {
+        Self {
+            paths: vec![],
+            extensions: vec!["js".into(), "mjs".into()],
+            main_fields: vec!["module".into(), "main".into()],
+            conditions: vec!["import".into(), "module".into()],
+        }
+    }
+}
+
+impl Resolver for NodeResolver {
+    fn resolve<'js>(&mut self, ctx: &Ctx<'js>, base: &str, name: &str) -> Result<StdString> {
+        let dir = match Path::new(base).parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let path = if name.starts_with("./") || name.starts_with("../") || name.starts_with('/') {
+            self.load_path(ctx, &normalize(&dir.join(name)))?
+        } else {
+            let (package, subpath) = split_package(name)
+                .ok_or_else(|| Error::new_resolving_message(base, name, "invalid package name"))?;
+            let mut path = None;
+            let dirs = dir
+                .ancestors()
+                .map(|dir| dir.join("node_modules"))
+                .chain(self.paths.iter().cloned());
+            for dir in dirs {
+                let dir = dir.join(package);
+                if dir.is_dir() {
+                    path = self.load_package(ctx, &normalize(&dir), &subpath)?;
+                    break;
+                }
+            }
+            path
+        };
+        let path = path.ok_or_else(|| Error::new_resolving(base, name))?;
+        Ok(path.to_string_lossy().into_owned())
+    }
+}
+
+/// Splits a bare name into the package name and the subpath in the package.
+fn split_package(name: &str) -> Option<(&str, StdString)> {
+    let mut end = name.find('/').unwrap_or(name.len());
+    if name.starts_with('@') {
+        if end == name.len() {
+            return None;
+        }
+        end += 1 + name[end + 1..].find('/').unwrap_or(name.len() - end - 1);
+    }
+    let (package, rest) = name.split_at(end);
+    if package.is_empty() || package.ends_with('/') {
+        return None;
+    }
+    Some((package, format!(".{}", rest)))
+}
+
+fn read_package<'js>(ctx: &Ctx<'js>, dir: &Path) -> Result<Option<Object<'js>>> {
+    let path = dir.join("package.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let source = fs::read(&path)?;
+    let package = ctx.json_parse(source).map_err(|_| {
+        // Take the syntax error so it isn't left pending on the context.
+        let message = ctx
+            .catch()
+            .into_exception()
+            .and_then(|error| error.message())
+            .map(|message| format!("invalid package.json: {}", message))
+            .unwrap_or_else(|| "invalid package.json".into());
+        Error::new_resolving_message(dir.to_string_lossy(), "package.json", message)
+    })?;
+    Ok(package.into_object())
+}
+
+/// Removes `.` and `..` components of a path where possible.
+fn normalize(path: &Path) -> PathBuf {
+    let mut res = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(res.components().next_back(), Some(Component::Normal(_))) {
+                    res.pop();
+                } else {
+                    res.push("..");
+                }
+            }
+            component => res.push(component),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::Path};
+
+    use crate::{
+        loader::{NodeResolver, Resolver, ScriptLoader},
+        Context, Runtime,
+    };
+
+    use super::split_package;
+
+    fn write(root: &Path, path: &str, source: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+
+    #[test]
+    fn package_names() {
+        assert_eq!(split_package("foo"), Some(("foo", ".".into())));
+        assert_eq!(
+            split_package("foo/bar/baz"),
+            Some(("foo", "./bar/baz".into()))
+        );
+        assert_eq!(split_package("@a/b"), Some(("@a/b", ".".into())));
+        assert_eq!(split_package("@a/b/c"), Some(("@a/b", "./c".into())));
+        assert_eq!(split_package("@a"), None);
+    }
+
+    #[test]
+    fn resolve() {
+        let root =
+            std::env::temp_dir().join(format!("rquickjs-node-resolver-{}", std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        write(&root, "main.js", "");
+        write(&root, "lib/index.js", "");
+        write(&root, "lib/util.mjs", "");
+        write(
+            &root,
+            "node_modules/plain/package.json",
+            r#"{ "main": "lib/entry" }"#,
+        );
+        write(&root, "node_modules/plain/lib/entry.js", "");
+        write(&root, "node_modules/plain/extra.js", "");
+        write(
+            &root,
+            "node_modules/dual/package.json",
+            r#"{ "module": "esm.mjs", "main": "cjs.js" }"#,
+        );
+        write(&root, "node_modules/dual/esm.mjs", "");
+        write(&root, "node_modules/dual/cjs.js", "");
+        write(
+            &root,
+            "node_modules/@scope/cond/package.json",
+            r#"{ "exports": {
+                ".": { "require": "./cjs.js", "import": "./esm.js" },
+                "./feature/*": "./src/*.js",
+                "./feature/private/*": null
+            } }"#,
+        );
+        write(&root, "node_modules/@scope/cond/esm.js", "");
+        write(&root, "node_modules/@scope/cond/cjs.js", "");
+        write(&root, "node_modules/@scope/cond/src/a.js", "");
+        write(&root, "node_modules/@scope/cond/src/private/b.js", "");
+        write(&root, "vendor/extern/index.js", "");
+        write(&root, "node_modules/broken/package.json", "{ main: }");
+        write(&root, "node_modules/broken/index.js", "");
+
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let mut resolver = NodeResolver::default().with_path(root.join("vendor"));
+            let base = root.join("lib/index.js");
+            let base = base.to_str().unwrap();
+            let mut resolve = |name: &str| {
+                resolver
+                    .resolve(&ctx, base, name)
+                    .ok()
+                    .map(|path| Path::new(&path).strip_prefix(&root).unwrap().to_owned())
+                    .map(|path| path.to_str().unwrap().replace('\\', "/"))
+            };
+            assert_eq!(resolve("./util").as_deref(), Some("lib/util.mjs"));
+            assert_eq!(resolve("../main").as_deref(), Some("main.js"));
+            assert_eq!(resolve("../lib").as_deref(), Some("lib/index.js"));
+            assert_eq!(
+                resolve("plain").as_deref(),
+                Some("node_modules/plain/lib/entry.js")
+            );
+            assert_eq!(
+                resolve("plain/extra").as_deref(),
+                Some("node_modules/plain/extra.js")
+            );
+            assert_eq!(
+                resolve("dual").as_deref(),
+                Some("node_modules/dual/esm.mjs")
+            );
+            assert_eq!(
+                resolve("@scope/cond").as_deref(),
+                Some("node_modules/@scope/cond/esm.js")
+            );
+            assert_eq!(
+                resolve("@scope/cond/feature/a").as_deref(),
+                Some("node_modules/@scope/cond/src/a.js")
+            );
+            assert_eq!(resolve("@scope/cond/feature/private/b"), None);
+            assert_eq!(resolve("@scope/cond/cjs.js"), None);
+            assert_eq!(resolve("extern").as_deref(), Some("vendor/extern/index.js"));
+            assert_eq!(resolve("missing"), None);
+            assert_eq!(resolve("./missing"), None);
+            assert_eq!(resolve("broken"), None);
+            assert!(ctx.catch().is_null());
+        });
+
+        write(
+            &root,
+            "node_modules/dual/esm.mjs",
+            "export const value = 'esm';",
+        );
+        write(
+            &root,
+            "main.js",
+            "import { value } from 'dual'; export const res = value;",
+        );
+        rt.set_loader(
+            NodeResolver::default(),
+            ScriptLoader::default().with_extension("mjs"),
+        );
+        ctx.with(|ctx| {
+            let main = root.join("main.js");
+            let source = fs::read(&main).unwrap();
+            let module =
+                crate::Module::evaluate(ctx.clone(), main.to_str().unwrap(), source).unwrap();
+            let value: crate::StdString = module.get("res").unwrap();
+            assert_eq!(value, "esm");
+        });
+        fs::remove_dir_all(&root).ok();
+    }
+}
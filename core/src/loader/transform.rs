@@ -0,0 +1,258 @@
+use crate::{loader::Loader, module::ModuleData, Ctx, Result};
+
+/// A stage of the loading pipeline which rewrites module sources before they are compiled
+///
+/// This is the extension point for compiling languages like TypeScript or JSX to JavaScript.
+/// It is implemented for closures which take the module path and source.
+pub trait SourceTransformer {
+    /// Transform the source of the module at the given path
+    fn transform<'js>(&mut self, ctx: &Ctx<'js>, path: &str, source: Vec<u8>) -> Result<Vec<u8>>;
+}
+
+impl<F> SourceTransformer for F
+where
+    F: FnMut(&str, Vec<u8>) -> Result<Vec<u8>>,
+{
+    fn transform<'js>(&mut self, _ctx: &Ctx<'js>, path: &str, source: Vec<u8>) -> Result<Vec<u8>> {
+        self(path, source)
+    }
+}
+
+/// The transforming loader
+///
+/// Passes the sources loaded by an other loader through a [`SourceTransformer`]. Modules which
+/// are not loaded from source, like native or bytecode modules, are left unchanged.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, loader::{BuiltinLoader, BuiltinResolver, Transform}};
+/// let loader = Transform::new(
+///     BuiltinLoader::default().with_module("lib.ts", "export const value: number = 42;"),
+///     |path: &str, source: Vec<u8>| {
+///         if !path.ends_with(".ts") {
+///             return Ok(source);
+///         }
+///         // a real transformer would compile TypeScript here
+///         let source = String::from_utf8(source)?;
+///         Ok(source.replace(": number", "").into_bytes())
+///     },
+/// );
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(BuiltinResolver::default().with_module("lib.ts"), loader);
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     let module = Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         "import { value } from 'lib.ts'; export const res = value;",
+///     )
+///     .unwrap();
+///     assert_eq!(module.get::<_, i32>("res").unwrap(), 42);
+/// });
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Transform<L, T> {
+    loader: L,
+    transformer: T,
+}
+
+impl<L, T> Transform<L, T> {
+    /// Create a loader which transforms the sources loaded by the given loader
+    pub fn new(loader: L, transformer: T) -> Self {
+        Transform {
+            loader,
+            transformer,
+        }
+    }
+
+    /// Returns the wrapped loader
+    pub fn loader(&mut self) -> &mut L {
+        &mut self.loader
+    }
+
+    /// Returns the transformer
+    pub fn transformer(&mut self) -> &mut T {
+        &mut self.transformer
+    }
+}
+
+impl<L, T> Loader for Transform<L, T>
+where
+    L: Loader,
+    T: SourceTransformer,
+{
+    fn load<'js>(&mut self, ctx: &Ctx<'js>, path: &str) -> Result<ModuleData> {
+        let transformer = &mut self.transformer;
+        self.loader
+            .load(ctx, path)?
+            .map_source(|source| transformer.transform(ctx, path, source))
+    }
+}
+
+/// The TypeScript transformer
+///
+/// Strips the types from the modules with a `.ts`, `.mts` or `.cts` extension using
+/// [swc](https://swc.rs), leaving other modules unchanged. The types are not checked, and TSX
+/// isn't supported.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, loader::{BuiltinLoader, BuiltinResolver, Transform, TypeScript}};
+/// let loader = Transform::new(
+///     BuiltinLoader::default().with_module(
+///         "lib.ts",
+///         "enum Answer { Value = 42 } export const value: number = Answer.Value;",
+///     ),
+///     TypeScript,
+/// );
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(BuiltinResolver::default().with_module("lib.ts"), loader);
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     let module = Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         "import { value } from 'lib.ts'; export const res = value;",
+///     )
+///     .unwrap();
+///     assert_eq!(module.get::<_, i32>("res").unwrap(), 42);
+/// });
+/// ```
+#[cfg(feature = "typescript")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TypeScript;
+
+#[cfg(feature = "typescript")]
+impl SourceTransformer for TypeScript {
+    fn transform<'js>(&mut self, _ctx: &Ctx<'js>, path: &str, source: Vec<u8>) -> Result<Vec<u8>> {
+        if ![".ts", ".mts", ".cts"].iter().any(|x| path.ends_with(x)) {
+            return Ok(source);
+        }
+        let source = crate::StdString::from_utf8(source)?;
+        strip_types(path, source)
+            .map(crate::StdString::into_bytes)
+            .map_err(|e| crate::Error::new_loading_message(path, e))
+    }
+}
+
+#[cfg(feature = "typescript")]
+fn strip_types(
+    path: &str,
+    source: crate::StdString,
+) -> std::result::Result<crate::StdString, crate::StdString> {
+    use swc_core::{
+        common::{
+            comments::SingleThreadedComments, sync::Lrc, FileName, Globals, Mark, SourceMap,
+            Spanned, GLOBALS,
+        },
+        ecma::{
+            ast::EsVersion,
+            codegen::to_code_default,
+            parser::{error::Error, parse_file_as_program, Syntax, TsSyntax},
+            transforms::{
+                base::{fixer::fixer, hygiene::hygiene, resolver},
+                typescript::strip,
+            },
+        },
+    };
+
+    let cm: Lrc<SourceMap> = Default::default();
+    let file = cm.new_source_file(FileName::Custom(path.into()).into(), source);
+    let comments = SingleThreadedComments::default();
+    let error = |e: Error| {
+        let pos = cm.lookup_char_pos(e.span().lo);
+        format!("{}:{}: {}", pos.line, pos.col_display + 1, e.kind().msg())
+    };
+    let mut errors = Vec::new();
+    let program = parse_file_as_program(
+        &file,
+        Syntax::Typescript(TsSyntax::default()),
+        EsVersion::latest(),
+        Some(&comments),
+        &mut errors,
+    )
+    .map_err(error)?;
+    if let Some(e) = errors.into_iter().next() {
+        return Err(error(e));
+    }
+    let program = GLOBALS.set(&Globals::new(), || {
+        let unresolved_mark = Mark::new();
+        let top_level_mark = Mark::new();
+        program
+            .apply(resolver(unresolved_mark, top_level_mark, true))
+            .apply(strip(unresolved_mark, top_level_mark))
+            .apply(hygiene())
+            .apply(fixer(Some(&comments)))
+    });
+    Ok(to_code_default(cm, Some(&comments), &program))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        loader::{BuiltinLoader, BuiltinResolver, Transform},
+        Context, Error, Module, Runtime,
+    };
+
+    #[test]
+    fn transform_error() {
+        let loader = Transform::new(
+            BuiltinLoader::default().with_module("lib", "export const value = 1;"),
+            |path: &str, _source: Vec<u8>| Err(Error::new_loading_message(path, "syntax error")),
+        );
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(BuiltinResolver::default().with_module("lib"), loader);
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            assert!(Module::evaluate(ctx, "main", "import { value } from 'lib';").is_err());
+        });
+    }
+
+    #[cfg(feature = "typescript")]
+    #[test]
+    fn typescript() {
+        use crate::{loader::TypeScript, CatchResultExt};
+
+        let loader = Transform::new(
+            BuiltinLoader::default()
+                .with_module(
+                    "lib.ts",
+                    r#"
+                    import type { Shape } from "./types.ts";
+                    interface Point { x: number; y: number }
+                    export class Square implements Shape {
+                        constructor(private readonly side: number) {}
+                        area(): number { return this.side ** 2; }
+                    }
+                    export const origin = <Point>{ x: 0, y: 0 };
+                    "#,
+                )
+                .with_module("bad.ts", "export const value: = 1;")
+                .with_module("plain.js", "export const value: number = 1;"),
+            TypeScript,
+        );
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            BuiltinResolver::default()
+                .with_module("lib.ts")
+                .with_module("bad.ts")
+                .with_module("plain.js"),
+            loader,
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let module = Module::evaluate(
+                ctx.clone(),
+                "main",
+                "import { Square, origin } from 'lib.ts'; export const res = new Square(3).area() + origin.x;",
+            )
+            .unwrap();
+            assert_eq!(module.get::<_, i32>("res").unwrap(), 9);
+            let err = Module::evaluate(ctx.clone(), "bad", "import 'bad.ts';")
+                .catch(&ctx)
+                .map(|_| ())
+                .unwrap_err();
+            assert!(err.to_string().contains("1:"), "{}", err);
+            // Only TypeScript modules are transformed.
+            assert!(Module::evaluate(ctx.clone(), "plain", "import 'plain.js';").is_err());
+        });
+    }
+}
@@ -0,0 +1,306 @@
+use std::{collections::HashMap, fmt::Display};
+
+use ::url::Url;
+
+use crate::{
+    loader::{Loader, Resolver},
+    module::ModuleData,
+    Ctx, Error, Result, StdString,
+};
+
+/// Fetches the source of a module from a URL.
+///
+/// This is implemented for closures which return the fetched bytes or an error, so any HTTP
+/// client can be used.
+pub trait UrlFetch {
+    /// Fetch the content at an `http` or `https` URL.
+    fn fetch(&mut self, url: &str) -> std::result::Result<Vec<u8>, StdString>;
+}
+
+impl<F, E> UrlFetch for F
+where
+    F: FnMut(&str) -> std::result::Result<Vec<u8>, E>,
+    E: Display,
+{
+    fn fetch(&mut self, url: &str) -> std::result::Result<Vec<u8>, StdString> {
+        self(url).map_err(|e| e.to_string())
+    }
+}
+
+/// A cache of fetched module sources, for example stored on disk.
+pub trait UrlCache {
+    /// Returns the cached source of the module at a URL.
+    fn get(&mut self, url: &str) -> Option<Vec<u8>>;
+
+    /// Store the fetched source of the module at a URL.
+    fn set(&mut self, url: &str, source: &[u8]);
+}
+
+impl UrlCache for HashMap<StdString, Vec<u8>> {
+    fn get(&mut self, url: &str) -> Option<Vec<u8>> {
+        HashMap::get(self, url).cloned()
+    }
+
+    fn set(&mut self, url: &str, source: &[u8]) {
+        self.insert(url.into(), source.to_vec());
+    }
+}
+
+fn parse_http(input: &str, base: Option<&Url>) -> Option<Url> {
+    let url = match base {
+        Some(base) => base.join(input),
+        None => Url::parse(input),
+    };
+    url.ok()
+        .filter(|url| url.scheme() == "http" || url.scheme() == "https")
+}
+
+fn is_under(url: &Url, prefix: &str) -> bool {
+    let Some(prefix) = parse_http(prefix, None) else {
+        return false;
+    };
+    if url.scheme() != prefix.scheme()
+        || url.host() != prefix.host()
+        || url.port_or_known_default() != prefix.port_or_known_default()
+    {
+        return false;
+    }
+    let (Some(mut segments), Some(prefix)) = (url.path_segments(), prefix.path_segments()) else {
+        return false;
+    };
+    prefix
+        .filter(|x| !x.is_empty())
+        .all(|x| segments.next() == Some(x))
+}
+
+/// The URL module resolver
+///
+/// Resolves absolute `http` and `https` URLs, and relative names imported by modules which were
+/// loaded from such a URL.
+#[derive(Debug, Default)]
+pub struct UrlResolver;
+
+impl Resolver for UrlResolver {
+    fn resolve<'js>(&mut self, _ctx: &Ctx<'js>, base: &str, name: &str) -> Result<StdString> {
+        let url = if let Some(url) = parse_http(name, None) {
+            url
+        } else {
+            let relative =
+                name.starts_with("./") || name.starts_with("../") || name.starts_with('/');
+            parse_http(base, None)
+                .filter(|_| relative)
+                .and_then(|base| parse_http(name, Some(&base)))
+                .ok_or_else(|| Error::new_resolving(base, name))?
+        };
+        Ok(url.into())
+    }
+}
+
+/// The URL module loader
+///
+/// Loads modules with `http` and `https` URLs as names, like the names resolved by
+/// [`UrlResolver`]. The network access is provided by the embedder through [`UrlFetch`], and only
+/// the URLs under a prefix added with [`UrlLoader::add_allowed`] are fetched.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Module, loader::{UrlLoader, UrlResolver}};
+/// # use std::collections::HashMap;
+/// let loader = UrlLoader::new(|url: &str| match url {
+///     "https://example.com/lib.mjs" => Ok(b"export const answer = 42;".to_vec()),
+///     _ => Err(format!("{} not found", url)),
+/// })
+/// .with_allowed("https://example.com/")
+/// .with_cache(HashMap::new());
+/// let rt = Runtime::new().unwrap();
+/// rt.set_loader(UrlResolver, loader);
+/// let ctx = Context::full(&rt).unwrap();
+/// ctx.with(|ctx| {
+///     let module = Module::evaluate(
+///         ctx.clone(),
+///         "main",
+///         "import { answer } from 'https://example.com/lib.mjs'; export const res = answer;",
+///     )
+///     .unwrap();
+///     assert_eq!(module.get::<_, i32>("res").unwrap(), 42);
+/// });
+/// ```
+pub struct UrlLoader {
+    fetch: Box<dyn UrlFetch>,
+    allowed: Vec<StdString>,
+    cache: Option<Box<dyn UrlCache>>,
+}
+
+impl UrlLoader {
+    /// Create a loader which fetches sources with the given fetcher.
+    pub fn new<F: UrlFetch + 'static>(fetch: F) -> Self {
+        UrlLoader {
+            fetch: Box::new(fetch),
+            allowed: Vec::new(),
+            cache: None,
+        }
+    }
+
+    /// Allow loading modules from URLs under the prefix
+    ///
+    /// A URL is under the prefix when it has the same scheme, host and port, and its path starts
+    /// with the path segments of the prefix, so `https://example.com/lib` allows
+    /// `https://example.com/lib/a.mjs` but not `https://example.com.evil/` or
+    /// `https://example.com/library.mjs`. No URL is allowed until a prefix was added.
+    pub fn add_allowed<P: Into<StdString>>(&mut self, prefix: P) -> &mut Self {
+        self.allowed.push(prefix.into());
+        self
+    }
+
+    /// Allow loading modules from URLs under the prefix
+    ///
+    /// A URL is under the prefix when it has the same scheme, host and port, and its path starts
+    /// with the path segments of the prefix, so `https://example.com/lib` allows
+    /// `https://example.com/lib/a.mjs` but not `https://example.com.evil/` or
+    /// `https://example.com/library.mjs`. No URL is allowed until a prefix was added.
+    #[must_use]
+    pub fn with_allowed<P: Into<StdString>>(mut self, prefix: P) -> Self {
+        self.add_allowed(prefix);
+        self
+    }
+
+    /// Set the cache which is checked before fetching a module
+    pub fn set_cache<C: UrlCache + 'static>(&mut self, cache: C) -> &mut Self {
+        self.cache = Some(Box::new(cache));
+        self
+    }
+
+    /// Set the cache which is checked before fetching a module
+    #[must_use]
+    pub fn with_cache<C: UrlCache + 'static>(mut self, cache: C) -> Self {
+        self.set_cache(cache);
+        self
+    }
+}
+
+impl Loader for UrlLoader {
+    fn load<'js>(&mut self, _ctx: &Ctx<'js>, name: &str) -> Result<ModuleData> {
+        let url = parse_http(name, None).ok_or_else(|| Error::new_loading(name))?;
+        if !self.allowed.iter().any(|prefix| is_under(&url, prefix)) {
+            return Err(Error::new_loading_message(name, "the URL is not allowed"));
+        }
+        let url = url.to_string();
+        if let Some(source) = self.cache.as_mut().and_then(|cache| cache.get(&url)) {
+            return Ok(ModuleData::source(name, source));
+        }
+        let source = self
+            .fetch
+            .fetch(&url)
+            .map_err(|e| Error::new_loading_message(name, e))?;
+        if let Some(cache) = &mut self.cache {
+            cache.set(&url, &source);
+        }
+        Ok(ModuleData::source(name, source))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    use crate::{
+        loader::{Resolver, UrlLoader, UrlResolver},
+        test_with, Context, Module, Runtime, StdString,
+    };
+
+    #[test]
+    fn resolve() {
+        test_with(|ctx| {
+            let resolve = |base: &str, name: &str| UrlResolver.resolve(&ctx, base, name).ok();
+            assert_eq!(
+                resolve("main", "https://example.com/a/../b.mjs").as_deref(),
+                Some("https://example.com/b.mjs")
+            );
+            assert_eq!(
+                resolve("https://example.com/lib/a.mjs", "./b.mjs").as_deref(),
+                Some("https://example.com/lib/b.mjs")
+            );
+            assert_eq!(
+                resolve("https://example.com/lib/a.mjs", "/c.mjs").as_deref(),
+                Some("https://example.com/c.mjs")
+            );
+            assert_eq!(resolve("https://example.com/lib/a.mjs", "bare"), None);
+            assert_eq!(resolve("main", "./b.mjs"), None);
+            assert_eq!(resolve("main", "file:///b.mjs"), None);
+        })
+    }
+
+    #[test]
+    fn load() {
+        let fetched = Rc::new(RefCell::new(Vec::<StdString>::new()));
+        let log = fetched.clone();
+        let loader = UrlLoader::new(move |url: &str| {
+            log.borrow_mut().push(url.into());
+            match url {
+                "https://example.com/lib/a.mjs" => {
+                    Ok(b"import { b as x } from './b.mjs'; export const a = 1, b = x;".to_vec())
+                }
+                "https://example.com/lib/b.mjs" => Ok(b"export const b = 2;".to_vec()),
+                _ => Err("not found"),
+            }
+        })
+        .with_allowed("https://example.com/lib/")
+        .with_cache(HashMap::new());
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(UrlResolver, loader);
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let module = Module::evaluate(
+                ctx.clone(),
+                "main",
+                "import { a, b } from 'https://example.com/lib/a.mjs'; export const res = a + b;",
+            )
+            .unwrap();
+            assert_eq!(module.get::<_, i32>("res").unwrap(), 3);
+            assert!(Module::evaluate(
+                ctx.clone(),
+                "denied",
+                "import 'https://example.com/other.mjs';"
+            )
+            .is_err());
+            assert!(Module::evaluate(
+                ctx.clone(),
+                "sibling",
+                "import 'https://example.com/library.mjs';"
+            )
+            .is_err());
+            assert!(Module::evaluate(
+                ctx.clone(),
+                "host",
+                "import 'https://example.com.evil/lib/a.mjs';"
+            )
+            .is_err());
+            assert!(Module::evaluate(
+                ctx.clone(),
+                "missing",
+                "import 'https://example.com/lib/missing.mjs';"
+            )
+            .is_err());
+        });
+        assert_eq!(
+            *fetched.borrow(),
+            [
+                "https://example.com/lib/a.mjs",
+                "https://example.com/lib/b.mjs",
+                "https://example.com/lib/missing.mjs"
+            ]
+        );
+    }
+
+    #[test]
+    fn deny_by_default() {
+        let rt = Runtime::new().unwrap();
+        rt.set_loader(
+            UrlResolver,
+            UrlLoader::new(|_: &str| Ok::<_, StdString>(b"export default 1;".to_vec())),
+        );
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            assert!(Module::evaluate(ctx, "main", "import 'https://example.com/a.mjs';").is_err());
+        });
+    }
+}
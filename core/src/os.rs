@@ -0,0 +1,332 @@
+//! A native `os` module with monotonic time, `sleep`, the platform and the hostname.
+//!
+//! [`OsModule`] provides `hrtime`, `sleep`, `platform` and `hostname`, each only when it is
+//! allowed by the [`Os`] stored in the runtime with
+//! [`Runtime::set_userdata`](crate::Runtime::set_userdata). A function which isn't allowed is
+//! exported as `undefined`, so scripts can check if it is available.
+//!
+//! ```
+//! # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Module, promise::Promise};
+//! # use rquickjs::os::{Os, OsModule};
+//! # async fn run() {
+//! let rt = AsyncRuntime::new().unwrap();
+//! rt.set_userdata(
+//!     Os::new()
+//!         .allow_hrtime()
+//!         .allow_sleep(|duration| tokio::time::sleep(duration)),
+//! )
+//! .await;
+//! let ctx = AsyncContext::full(&rt).await.unwrap();
+//! async_with!(ctx => |ctx| {
+//!     Module::declare_def::<OsModule, _>(ctx.clone(), "os").unwrap();
+//!     let module = Module::evaluate(
+//!         ctx.clone(),
+//!         "main",
+//!         r#"
+//!         import { hrtime, sleep, hostname } from "os";
+//!         export const elapsed = (async () => {
+//!             const start = hrtime.bigint();
+//!             await sleep(10);
+//!             return Number(hrtime.bigint() - start) / 1e6;
+//!         })();
+//!         export const hasHostname = hostname !== undefined;
+//!     "#,
+//!     )
+//!     .unwrap();
+//!     assert!(!module.get::<_, bool>("hasHostname").unwrap());
+//!     let elapsed: Promise<f64> = module.get("elapsed").unwrap();
+//!     assert!(elapsed.await.unwrap() >= 10.0);
+//! })
+//! .await;
+//! # }
+//! ```
+
+use std::{env, fmt, future::Future, pin::Pin, time::Duration};
+
+use crate::{
+    clock::Instant,
+    module::{Declarations, Exports, ModuleDef},
+    promise::Promised,
+    BigInt, Ctx, Exception, Function, Object, Result, StdString, Value,
+};
+
+/// The future returned by a [`SleepBackend`].
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/// The timer used by `sleep`.
+///
+/// This is implemented for closures returning a future, like `tokio::time::sleep`, so the
+/// timer comes from the executor driving the [`AsyncRuntime`](crate::AsyncRuntime). It must be
+/// [`Send`], like [`AsyncRuntime::set_userdata`](crate::AsyncRuntime::set_userdata) requires.
+pub trait SleepBackend: Send + 'static {
+    /// Returns a future which finishes after the duration.
+    fn sleep(&self, duration: Duration) -> SleepFuture;
+}
+
+impl<F, R> SleepBackend for F
+where
+    F: Fn(Duration) -> R + Send + 'static,
+    R: Future<Output = ()> + 'static,
+{
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(self(duration))
+    }
+}
+
+/// The functions of the [`OsModule`] allowed in a runtime, stored with
+/// [`Runtime::set_userdata`](crate::Runtime::set_userdata).
+///
+/// Nothing is allowed unless it is allowed explicitly.
+pub struct Os {
+    origin: Instant,
+    hrtime: bool,
+    platform: bool,
+    hostname: Option<StdString>,
+    sleep: Option<Box<dyn SleepBackend>>,
+}
+
+impl Os {
+    pub fn new() -> Self {
+        Os {
+            origin: Instant::now(),
+            hrtime: false,
+            platform: false,
+            hostname: None,
+            sleep: None,
+        }
+    }
+
+    /// Allow `hrtime`, which returns the monotonic time since the options were created.
+    pub fn allow_hrtime(mut self) -> Self {
+        self.hrtime = true;
+        self
+    }
+
+    /// Allow `platform`, which returns the name of the operating system like Node.js.
+    pub fn allow_platform(mut self) -> Self {
+        self.platform = true;
+        self
+    }
+
+    /// Allow `hostname`, which returns the hostname of the host, read when this is called.
+    ///
+    /// The hostname is read from the `HOSTNAME` or `COMPUTERNAME` environment variables, and
+    /// from `/proc/sys/kernel/hostname` or `/etc/hostname` on unix, `localhost` when none of
+    /// them is set. Use [`Os::hostname`] to expose a different name.
+    pub fn allow_hostname(self) -> Self {
+        let name = host_hostname();
+        self.hostname(name)
+    }
+
+    /// Allow `hostname`, which returns the given name.
+    pub fn hostname<S: Into<StdString>>(mut self, name: S) -> Self {
+        self.hostname = Some(name.into());
+        self
+    }
+
+    /// Allow `sleep`, with the timer of the given backend.
+    pub fn allow_sleep<B: SleepBackend>(mut self, backend: B) -> Self {
+        self.sleep = Some(Box::new(backend));
+        self
+    }
+
+    fn get<'a>(ctx: &'a Ctx<'_>) -> Result<&'a Os> {
+        ctx.userdata::<Os>()
+            .ok_or_else(|| Exception::throw_internal(ctx, "No os options were set"))
+    }
+}
+
+impl Default for Os {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Os {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Os")
+            .field("hrtime", &self.hrtime)
+            .field("platform", &self.platform)
+            .field("hostname", &self.hostname)
+            .field("sleep", &self.sleep.is_some())
+            .finish()
+    }
+}
+
+fn host_hostname() -> StdString {
+    let from_env = ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .map(|name| name.trim().to_owned())
+        .find(|name| !name.is_empty());
+    #[cfg(unix)]
+    let from_env = from_env.or_else(|| {
+        ["/proc/sys/kernel/hostname", "/etc/hostname"]
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .map(|name| name.trim().to_owned())
+            .find(|name| !name.is_empty())
+    });
+    from_env.unwrap_or_else(|| "localhost".into())
+}
+
+/// The name of the operating system, with the names used by Node.js where they differ.
+fn platform() -> &'static str {
+    match env::consts::OS {
+        "macos" => "darwin",
+        "windows" => "win32",
+        os => os,
+    }
+}
+
+fn hrtime<'js>(ctx: &Ctx<'js>, origin: Instant) -> Result<Function<'js>> {
+    let func = Function::new(ctx.clone(), move || {
+        let elapsed = origin.elapsed();
+        vec![elapsed.as_secs() as f64, elapsed.subsec_nanos() as f64]
+    })?
+    .with_name("hrtime")?;
+    let bigint = Function::new(ctx.clone(), move |ctx: Ctx<'js>| {
+        let nanos = origin.elapsed().as_nanos().min(u64::MAX as u128) as u64;
+        BigInt::from_u64(ctx, nanos)
+    })?;
+    func.set("bigint", bigint.with_name("bigint")?)?;
+    Ok(func)
+}
+
+fn sleep<'js>(ctx: Ctx<'js>, ms: f64) -> Result<Promised<impl Future<Output = Result<()>>>> {
+    let os = Os::get(&ctx)?;
+    let backend = os
+        .sleep
+        .as_ref()
+        .ok_or_else(|| Exception::throw_internal(&ctx, "sleep is not allowed"))?;
+    let ms = if ms.is_finite() { ms.max(0.0) } else { 0.0 };
+    let timer = backend.sleep(Duration::from_secs_f64(ms / 1000.0));
+    Ok(Promised(async move {
+        timer.await;
+        Ok(())
+    }))
+}
+
+/// The `os` module, declared with [`Module::declare_def`](crate::Module::declare_def) or a
+/// [`ModuleLoader`](crate::loader::ModuleLoader).
+///
+/// Importing the module fails when no [`Os`] is stored in the runtime.
+pub struct OsModule;
+
+impl ModuleDef for OsModule {
+    fn declare(declare: &mut Declarations) -> Result<()> {
+        for name in ["hrtime", "sleep", "platform", "hostname", "default"] {
+            declare.declare(name)?;
+        }
+        Ok(())
+    }
+
+    fn evaluate<'js>(ctx: &Ctx<'js>, exports: &mut Exports<'js>) -> Result<()> {
+        let os = Os::get(ctx)?;
+        let undefined = || Value::new_undefined(ctx.clone());
+
+        let hrtime = if os.hrtime {
+            hrtime(ctx, os.origin)?.into_value()
+        } else {
+            undefined()
+        };
+        let sleep = if os.sleep.is_some() {
+            Function::new(ctx.clone(), sleep)?
+                .with_name("sleep")?
+                .into_value()
+        } else {
+            undefined()
+        };
+        let platform = if os.platform {
+            Function::new(ctx.clone(), platform)?
+                .with_name("platform")?
+                .into_value()
+        } else {
+            undefined()
+        };
+        let hostname = match os.hostname.clone() {
+            Some(name) => Function::new(ctx.clone(), move || name.clone())?
+                .with_name("hostname")?
+                .into_value(),
+            None => undefined(),
+        };
+
+        let default = Object::new(ctx.clone())?;
+        default.set("hrtime", hrtime.clone())?;
+        default.set("sleep", sleep.clone())?;
+        default.set("platform", platform.clone())?;
+        default.set("hostname", hostname.clone())?;
+
+        exports.export("hrtime", hrtime)?;
+        exports.export("sleep", sleep)?;
+        exports.export("platform", platform)?;
+        exports.export("hostname", hostname)?;
+        exports.export("default", default)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Os, OsModule};
+    use crate::{async_with, AsyncContext, AsyncRuntime, CatchResultExt, Module, StdString};
+
+    async fn run(os: Os, source: &str) -> StdString {
+        let rt = AsyncRuntime::new().unwrap();
+        rt.set_userdata(os).await;
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+        async_with!(ctx => |ctx| {
+            Module::declare_def::<OsModule, _>(ctx.clone(), "os").unwrap();
+            let _ = Module::evaluate(ctx.clone(), "main", source)
+                .catch(&ctx)
+                .unwrap();
+        })
+        .await;
+        rt.idle().await;
+        async_with!(ctx => |ctx| { ctx.globals().get("res").unwrap() }).await
+    }
+
+    #[tokio::test]
+    async fn allowed() {
+        let os = Os::new()
+            .allow_hrtime()
+            .allow_platform()
+            .hostname("sandbox")
+            .allow_sleep(|duration| tokio::time::sleep(duration));
+        let res = run(
+            os,
+            r#"
+                import os, { hrtime, sleep, platform, hostname } from "os";
+                const [secs, nanos] = hrtime();
+                const start = hrtime.bigint();
+                let res = [
+                    Number.isInteger(secs) && nanos >= 0 && nanos < 1e9,
+                    typeof start,
+                    hostname(),
+                    typeof platform(),
+                    os.sleep === sleep,
+                ].join(" ");
+                sleep(20).then(() => {
+                    const elapsed = Number(hrtime.bigint() - start) / 1e6;
+                    globalThis.res = res + " " + (elapsed >= 20);
+                });
+            "#,
+        )
+        .await;
+        assert_eq!(res, "true bigint sandbox string true true");
+    }
+
+    #[tokio::test]
+    async fn disallowed() {
+        let res = run(
+            Os::new().allow_platform(),
+            r#"
+                import os, { hrtime, sleep, hostname } from "os";
+                globalThis.res = [typeof hrtime, typeof sleep, typeof hostname, typeof os.platform]
+                    .join(" ");
+            "#,
+        )
+        .await;
+        assert_eq!(res, "undefined undefined undefined function");
+    }
+}
@@ -6,9 +6,26 @@ use crate::{
 };
 use std::{
     fmt,
+    hash::{Hash, Hasher},
     mem::{self, ManuallyDrop},
 };
 
+#[cfg(feature = "parallel")]
+pub use parallel::PersistentSend;
+#[cfg(feature = "parallel")]
+pub(crate) use parallel::{HoldingLock, PendingDrop};
+
+/// Marks the runtime lock as held by the current thread for the duration of the guard.
+#[cfg(not(feature = "parallel"))]
+pub(crate) struct HoldingLock;
+
+#[cfg(not(feature = "parallel"))]
+impl HoldingLock {
+    pub(crate) fn new(_rt: *mut qjs::JSRuntime) -> Self {
+        HoldingLock
+    }
+}
+
 /// The trait to help break lifetime rules when JS objects leaves current context via [`Persistent`] wrapper.
 ///
 /// # Safety
@@ -157,16 +174,54 @@ impl_outlive!(
 /// NOTE: Be careful and ensure that no persistent links outlives the runtime,
 /// otherwise Runtime will abort the process when dropped.
 ///
-#[derive(Eq, PartialEq, Hash)]
+/// With the `parallel` feature enabled persistent JavaScript values, see `PersistentSend`, are
+/// [`Send`] and can be moved to and dropped on any thread. Clones made without holding the
+/// runtime lock share the underlying value, which is only cloned again once it is restored. A
+/// persistent value dropped outside of the runtime lock is freed the next time the runtime is
+/// used.
+///
+/// With the `leak-report` feature enabled every handle records the backtrace of its creation,
+/// so the handles still alive when the runtime is dropped can be reported, see
+/// [`Runtime::set_leak_handler`](crate::Runtime::set_leak_handler).
 pub struct Persistent<T> {
     pub(crate) rt: *mut qjs::JSRuntime,
-    pub(crate) value: T,
+    #[cfg(feature = "leak-report")]
+    tracked: crate::runtime::leak::Tracked,
+    #[cfg(not(feature = "parallel"))]
+    value: T,
+    #[cfg(feature = "parallel")]
+    value: std::sync::Arc<parallel::Shared<T>>,
+}
+
+#[cfg(feature = "parallel")]
+// The value is only accessed while holding the runtime lock, apart from dropping which is
+// deferred to the runtime.
+unsafe impl<T: PersistentSend> Send for Persistent<T> {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: Clone> Clone for Persistent<T> {
+    fn clone(&self) -> Self {
+        Persistent {
+            rt: self.rt,
+            #[cfg(feature = "leak-report")]
+            tracked: self.tracked.clone(),
+            value: self.value.clone(),
+        }
+    }
 }
 
+#[cfg(feature = "parallel")]
 impl<T: Clone> Clone for Persistent<T> {
     fn clone(&self) -> Self {
+        if parallel::is_holding_lock(self.rt) {
+            return Self::new_raw(self.rt, T::clone(self.value()));
+        }
+        // Cloning a JavaScript value requires the runtime lock, so only share the value here.
+        self.value.set_clone_fn(T::clone);
         Persistent {
             rt: self.rt,
+            #[cfg(feature = "leak-report")]
+            tracked: self.tracked.clone(),
             value: self.value.clone(),
         }
     }
@@ -179,14 +234,80 @@ where
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Persistent")
             .field("rt", &self.rt)
-            .field("value", &self.value)
+            .field("value", self.value())
             .finish()
     }
 }
 
+impl<T: PartialEq> PartialEq for Persistent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rt == other.rt && self.value() == other.value()
+    }
+}
+
+impl<T: Eq> Eq for Persistent<T> {}
+
+impl<T: Hash> Hash for Persistent<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rt.hash(state);
+        self.value().hash(state);
+    }
+}
+
 impl<T> Persistent<T> {
+    #[cfg(not(feature = "parallel"))]
     fn new_raw(rt: *mut qjs::JSRuntime, value: T) -> Self {
-        Self { rt, value }
+        Self {
+            rt,
+            #[cfg(feature = "leak-report")]
+            tracked: Self::track(rt),
+            value,
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn new_raw(rt: *mut qjs::JSRuntime, value: T) -> Self {
+        Self {
+            rt,
+            #[cfg(feature = "leak-report")]
+            tracked: Self::track(rt),
+            value: std::sync::Arc::new(parallel::Shared::new(rt, value)),
+        }
+    }
+
+    /// Must only be called while holding the runtime lock.
+    #[cfg(feature = "leak-report")]
+    fn track(rt: *mut qjs::JSRuntime) -> crate::runtime::leak::Tracked {
+        let opaque =
+            unsafe { &*qjs::JS_GetRuntimeOpaque(rt).cast::<crate::runtime::raw::Opaque>() };
+        crate::runtime::leak::Tracked::new(opaque.leaks.clone(), std::any::type_name::<T>())
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn into_value(self) -> T {
+        self.value
+    }
+
+    #[cfg(feature = "parallel")]
+    fn into_value(self) -> T {
+        match std::sync::Arc::try_unwrap(self.value) {
+            Ok(shared) => shared.into_inner(),
+            Err(shared) => shared.take_reference(),
+        }
+    }
+
+    /// Returns a reference to the value.
+    pub(crate) fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Make sure the value holds a reference for every handle, so each of them can be traced.
+    ///
+    /// Must only be called while holding the runtime lock.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn reserve_references(&self) {
+        self.value
+            .reserve_references(std::sync::Arc::strong_count(&self.value))
     }
 
     unsafe fn outlive_transmute<'from, 'to, U>(t: U) -> U::Target<'to>
@@ -220,10 +341,7 @@ impl<T> Persistent<T> {
         let outlived: T::Target<'static> =
             unsafe { Self::outlive_transmute::<'js, 'static, T>(val) };
         let ptr = unsafe { qjs::JS_GetRuntime(ctx.as_ptr()) };
-        Persistent {
-            rt: ptr,
-            value: outlived,
-        }
+        Persistent::new_raw(ptr, outlived)
     }
 
     /// Restore the value of an arbitrary type
@@ -235,7 +353,9 @@ impl<T> Persistent<T> {
         if self.rt != ctx_runtime_ptr {
             return Err(Error::UnrelatedRuntime);
         }
-        Ok(unsafe { Self::outlive_transmute::<'static, 'js, T>(self.value) })
+        // The runtime lock is held so it is fine to clone the value if it is shared.
+        let value = self.into_value();
+        Ok(unsafe { Self::outlive_transmute::<'static, 'js, T>(value) })
     }
 }
 
@@ -260,6 +380,209 @@ where
     }
 }
 
+#[cfg(feature = "parallel")]
+mod parallel {
+    use std::{cell::RefCell, mem::ManuallyDrop, ops::Deref, sync::Mutex};
+
+    use crate::{
+        atom::Atom, class::JsClass, qjs, runtime::raw::Opaque, value::Constructor, Array, BigInt,
+        Class, Date, Function, Map, Object, RegExp, Set, String, Symbol, Value,
+    };
+
+    use super::Persistent;
+
+    /// The marker trait for the values which can be sent to other threads as a [`Persistent`].
+    ///
+    /// # Safety
+    /// The value must be safe to send to other threads once it is only used while holding the
+    /// runtime lock, like the JavaScript values are.
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "parallel")))]
+    pub unsafe trait PersistentSend {}
+
+    macro_rules! persistent_send_impls {
+        ($($type:ident,)*) => {
+            $(
+                unsafe impl PersistentSend for $type<'static> {}
+            )*
+        };
+    }
+
+    persistent_send_impls! {
+        Value,
+        Symbol,
+        String,
+        Object,
+        Array,
+        BigInt,
+        Function,
+        Constructor,
+        Atom,
+        Map,
+        Set,
+        Date,
+        RegExp,
+    }
+
+    #[cfg(feature = "array-buffer")]
+    unsafe impl PersistentSend for crate::ArrayBuffer<'static> {}
+
+    unsafe impl<C: JsClass<'static> + Send> PersistentSend for Class<'static, C> {}
+    unsafe impl<T: PersistentSend> PersistentSend for Persistent<T> {}
+    unsafe impl<T: PersistentSend> PersistentSend for Option<T> {}
+    unsafe impl<T: PersistentSend> PersistentSend for Box<T> {}
+    unsafe impl<T: PersistentSend> PersistentSend for Vec<T> {}
+
+    thread_local! {
+        /// The runtimes whose lock is held by this thread, values of them can be dropped directly.
+        static HOLDING: RefCell<Vec<*mut qjs::JSRuntime>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Marks the runtime lock as held by the current thread for the duration of the guard.
+    pub(crate) struct HoldingLock(());
+
+    impl HoldingLock {
+        pub(crate) fn new(rt: *mut qjs::JSRuntime) -> Self {
+            HOLDING.with(|x| x.borrow_mut().push(rt));
+            HoldingLock(())
+        }
+    }
+
+    impl Drop for HoldingLock {
+        fn drop(&mut self) {
+            HOLDING.with(|x| x.borrow_mut().pop());
+        }
+    }
+
+    pub(crate) fn is_holding_lock(rt: *mut qjs::JSRuntime) -> bool {
+        HOLDING.with(|x| x.borrow().contains(&rt))
+    }
+
+    type CloneFn<T> = fn(&T) -> T;
+
+    /// A persistent value shared between clones.
+    pub(crate) struct Shared<T> {
+        rt: *mut qjs::JSRuntime,
+        value: ManuallyDrop<T>,
+        clone_fn: Mutex<Option<CloneFn<T>>>,
+        /// Additional references to the value, taken while tracing so every handle can be marked.
+        references: Mutex<Vec<T>>,
+    }
+
+    impl<T> Shared<T> {
+        pub fn new(rt: *mut qjs::JSRuntime, value: T) -> Self {
+            Shared {
+                rt,
+                value: ManuallyDrop::new(value),
+                clone_fn: Mutex::new(None),
+                references: Mutex::new(Vec::new()),
+            }
+        }
+
+        pub fn set_clone_fn(&self, clone: CloneFn<T>) {
+            *self.clone_fn.lock().unwrap_or_else(|e| e.into_inner()) = Some(clone);
+        }
+
+        fn clone_fn(&self) -> Option<CloneFn<T>> {
+            *self.clone_fn.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        fn references(&self) -> std::sync::MutexGuard<'_, Vec<T>> {
+            self.references.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        /// Take a reference to the value while there are other handles to it.
+        ///
+        /// Must only be called while holding the runtime lock.
+        pub fn take_reference(&self) -> T {
+            if let Some(x) = self.references().pop() {
+                return x;
+            }
+            let clone = self
+                .clone_fn()
+                .expect("shared persistent value should have a clone function");
+            clone(&self.value)
+        }
+
+        /// Make sure there are as many references to the value as there are handles.
+        ///
+        /// Must only be called while holding the runtime lock.
+        pub fn reserve_references(&self, handles: usize) {
+            let Some(clone) = self.clone_fn() else {
+                return;
+            };
+            let mut references = self.references();
+            while references.len() + 1 < handles {
+                references.push(clone(&self.value));
+            }
+        }
+
+        /// Must only be called while holding the runtime lock.
+        pub fn into_inner(self) -> T {
+            let mut this = ManuallyDrop::new(self);
+            let references = std::mem::take(&mut *this.references());
+            drop(references);
+            unsafe {
+                std::ptr::drop_in_place(&mut this.clone_fn);
+                std::ptr::drop_in_place(&mut this.references);
+                ManuallyDrop::take(&mut this.value)
+            }
+        }
+    }
+
+    impl<T> Deref for Shared<T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.value
+        }
+    }
+
+    impl<T> Drop for Shared<T> {
+        fn drop(&mut self) {
+            let value = unsafe { ManuallyDrop::take(&mut self.value) };
+            let references = std::mem::take(&mut *self.references());
+            if is_holding_lock(self.rt) {
+                return drop((value, references));
+            }
+            // We don't hold the runtime lock, so leave dropping the value to the runtime.
+            let opaque = unsafe { qjs::JS_GetRuntimeOpaque(self.rt).cast::<Opaque>() };
+            let pending = PendingDrop::new((value, references));
+            unsafe { &(*opaque).pending_drops }
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(pending);
+        }
+    }
+    /// A type erased value which still needs to be dropped while holding the runtime lock.
+    pub(crate) struct PendingDrop {
+        ptr: *mut (),
+        drop: unsafe fn(*mut ()),
+    }
+
+    // The value is only dropped while holding the runtime lock.
+    unsafe impl Send for PendingDrop {}
+
+    impl PendingDrop {
+        fn new<T>(value: T) -> Self {
+            unsafe fn drop_box<T>(ptr: *mut ()) {
+                drop(Box::from_raw(ptr.cast::<T>()))
+            }
+            PendingDrop {
+                ptr: Box::into_raw(Box::new(value)).cast(),
+                drop: drop_box::<T>,
+            }
+        }
+
+        /// Drop the value.
+        ///
+        /// # Safety
+        /// The runtime lock of the value must be held.
+        pub unsafe fn run(self) {
+            (self.drop)(self.ptr)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -342,4 +665,51 @@ mod test {
             assert!(eq.as_bool().unwrap());
         });
     }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn send_to_thread() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        let (a, b) = ctx.with(|ctx| {
+            let v: Object = ctx.eval("({ a: 1 })").unwrap();
+            let v = Persistent::save(&ctx, v);
+            (v.clone(), v)
+        });
+
+        let ctx2 = ctx.clone();
+        std::thread::spawn(move || {
+            drop(b);
+            ctx2.with(|ctx| {
+                let obj = a.restore(&ctx).unwrap();
+                assert_eq!(obj.get::<_, i32>("a").unwrap(), 1);
+            });
+        })
+        .join()
+        .unwrap();
+
+        let v = ctx.with(|ctx| {
+            let v: Object = ctx.eval("({})").unwrap();
+            Persistent::save(&ctx, v)
+        });
+        std::thread::spawn(move || drop(v)).join().unwrap();
+        rt.run_gc();
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn drop_holding_lock() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+
+        ctx.with(|ctx| {
+            let v: Object = ctx.eval("({})").unwrap();
+            let v = Persistent::save(&ctx, v);
+            drop(v.clone());
+            drop(v);
+            let pending = unsafe { &(*ctx.get_opaque()).pending_drops };
+            assert!(pending.lock().unwrap().is_empty());
+        });
+    }
 }
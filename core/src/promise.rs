@@ -8,8 +8,13 @@ use std::{
 };
 
 use crate::{
-    atom::PredefinedAtom, function::This, qjs, safe_ref::Ref, CatchResultExt, CaughtError,
-    CaughtResult, Ctx, Exception, FromJs, Function, IntoJs, Object, Result, ThrowResultExt, Value,
+    atom::PredefinedAtom,
+    function::This,
+    markers::ParallelSend,
+    qjs,
+    safe_ref::{Mut, Ref},
+    CatchResultExt, CaughtError, CaughtResult, Ctx, Error, Exception, FromJs, Function, IntoJs,
+    Object, Result, ThrowResultExt, Value,
 };
 
 /// Future-aware promise
@@ -60,6 +65,195 @@ where
     }
 }
 
+impl<'js, T> IntoJs<'js> for Promise<'js, T> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.promise.into_value())
+    }
+}
+
+impl<'js, T> Promise<'js, T>
+where
+    T: FromJs<'js> + 'js,
+{
+    /// Create a new pending promise together with the handles to settle it.
+    ///
+    /// The handles don't borrow the context, with the `parallel` feature they can be sent to
+    /// other threads. The value is converted and the promise settled by a future spawned on the
+    /// context, so the runtime has to be an [`AsyncRuntime`](crate::AsyncRuntime) which is
+    /// driven. The promise stays pending if both handles are dropped without settling it.
+    ///
+    /// ```
+    /// # use rquickjs::{async_with, promise::Promise, AsyncContext, AsyncRuntime};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let rt = AsyncRuntime::new().unwrap();
+    /// let ctx = AsyncContext::full(&rt).await.unwrap();
+    /// async_with!(ctx => |ctx| {
+    ///     let (promise, resolve, _reject) = Promise::<i32>::new(&ctx).unwrap();
+    ///     ctx.globals().set("promise", promise).unwrap();
+    ///     resolve.resolve(21);
+    ///     let doubled: Promise<i32> = ctx.eval("promise.then(x => x * 2)").unwrap();
+    ///     assert_eq!(doubled.await.unwrap(), 42);
+    /// })
+    /// .await;
+    /// # }
+    /// ```
+    pub fn new(ctx: &Ctx<'js>) -> Result<(Self, Resolve, Reject)> {
+        let (promise, resolve, reject) = ctx.promise()?;
+        let channel = Ref::new(Mut::new(Channel {
+            settlement: None,
+            waker: None,
+            handles: 2,
+        }));
+
+        let ctx_clone = ctx.clone();
+        let settle = Settle(channel.clone());
+        ctx.spawn(async move {
+            let Some((rejected, value)) = settle.await else {
+                return;
+            };
+            let err = match value(&ctx_clone).catch(&ctx_clone) {
+                Ok(x) if rejected => reject.call::<_, ()>((x,)),
+                Ok(x) => resolve.call::<_, ()>((x,)),
+                Err(e) => reject_with(&ctx_clone, &reject, e),
+            };
+            if let Err(e) = err {
+                println!("promise handle function returned error:{}", e);
+            }
+        });
+
+        let state = Ref::new(State {
+            waker: Cell::new(None),
+            result: Cell::new(None),
+        });
+        Ok((
+            Promise { state, promise },
+            Resolve(channel.clone()),
+            Reject(channel),
+        ))
+    }
+}
+
+/// The conversion of a settled value, run once the context is available again.
+#[cfg(not(feature = "parallel"))]
+type Settlement = Box<dyn for<'js> FnOnce(&Ctx<'js>) -> Result<Value<'js>>>;
+#[cfg(feature = "parallel")]
+type Settlement = Box<dyn for<'js> FnOnce(&Ctx<'js>) -> Result<Value<'js>> + Send>;
+
+struct Channel {
+    settlement: Option<(bool, Settlement)>,
+    waker: Option<Waker>,
+    /// The amount of settling handles still alive.
+    handles: usize,
+}
+
+impl Channel {
+    fn settle(this: &Mut<Self>, rejected: bool, settlement: Settlement) {
+        let mut channel = this.lock();
+        if channel.handles == 0 || channel.settlement.is_some() {
+            return;
+        }
+        channel.settlement = Some((rejected, settlement));
+        if let Some(waker) = channel.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn release(this: &Mut<Self>) {
+        let mut channel = this.lock();
+        channel.handles -= 1;
+        if channel.handles == 0 {
+            if let Some(waker) = channel.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Resolves to the settlement or `None` if every handle was dropped without settling.
+struct Settle(Ref<Mut<Channel>>);
+
+impl Future for Settle {
+    type Output = Option<(bool, Settlement)>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output> {
+        let mut channel = self.0.lock();
+        if let Some(settlement) = channel.settlement.take() {
+            return Poll::Ready(Some(settlement));
+        }
+        if channel.handles == 0 {
+            return Poll::Ready(None);
+        }
+        channel.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// The handle to fulfill a promise created with [`Promise::new`].
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub struct Resolve(Ref<Mut<Channel>>);
+
+impl Resolve {
+    /// Fulfill the promise with the given value.
+    ///
+    /// Does nothing if the promise was already settled. If the value fails to convert the
+    /// promise is rejected with the conversion error instead.
+    pub fn resolve<V>(self, value: V)
+    where
+        V: for<'js> IntoJs<'js> + ParallelSend + 'static,
+    {
+        Channel::settle(&self.0, false, Box::new(move |ctx| value.into_js(ctx)));
+    }
+}
+
+impl Drop for Resolve {
+    fn drop(&mut self) {
+        Channel::release(&self.0);
+    }
+}
+
+/// The handle to reject a promise created with [`Promise::new`].
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "futures")))]
+pub struct Reject(Ref<Mut<Channel>>);
+
+impl Reject {
+    /// Reject the promise with the given reason.
+    ///
+    /// Does nothing if the promise was already settled.
+    pub fn reject<V>(self, reason: V)
+    where
+        V: for<'js> IntoJs<'js> + ParallelSend + 'static,
+    {
+        Channel::settle(&self.0, true, Box::new(move |ctx| reason.into_js(ctx)));
+    }
+
+    /// Reject the promise with the given error, thrown and caught as if it was returned from a
+    /// function.
+    pub fn reject_error(self, error: Error) {
+        Channel::settle(&self.0, true, Box::new(move |_| Err(error)));
+    }
+}
+
+impl Drop for Reject {
+    fn drop(&mut self) {
+        Channel::release(&self.0);
+    }
+}
+
+/// Reject a promise with a caught error.
+fn reject_with<'js>(ctx: &Ctx<'js>, reject: &Function<'js>, error: CaughtError<'js>) -> Result<()> {
+    match error {
+        CaughtError::Exception(e) => reject.call::<_, ()>((e,)),
+        CaughtError::Value(e) => reject.call::<_, ()>((e,)),
+        CaughtError::Error(e) => {
+            let is_exception = unsafe { qjs::JS_IsException(e.throw(ctx)) };
+            debug_assert!(is_exception);
+            let e = ctx.catch();
+            reject.call::<_, ()>((e,))
+        }
+    }
+}
+
 impl<'js, T> Future for Promise<'js, T>
 where
     T: FromJs<'js> + 'js,
@@ -89,7 +283,7 @@ where
                     };
                 state.resolve(Err(e))
             });
-            then.call((This(self.promise.clone()), resolve, reject))?;
+            then.call::<_, ()>((This(self.promise.clone()), resolve, reject))?;
         };
         Poll::Pending
     }
@@ -115,19 +309,20 @@ where
         let (promise, resolve, reject) = ctx.promise()?;
         let ctx_clone = ctx.clone();
 
+        let cancellation = unsafe { (*ctx.get_opaque()).cancellation.clone() };
+
         let future = async move {
-            let err = match self.0.await.into_js(&ctx_clone).catch(&ctx_clone) {
+            let res = match cancellation {
+                Some(cancellation) => cancellation.guard(self.0).await,
+                None => Some(self.0.await),
+            };
+            let res = match res {
+                Some(x) => x.into_js(&ctx_clone),
+                None => Err(Error::Cancelled),
+            };
+            let err = match res.catch(&ctx_clone) {
                 Ok(x) => resolve.call::<_, ()>((x,)),
-                Err(e) => match e {
-                    CaughtError::Exception(e) => reject.call::<_, ()>((e,)),
-                    CaughtError::Value(e) => reject.call::<_, ()>((e,)),
-                    CaughtError::Error(e) => {
-                        let is_exception = unsafe { qjs::JS_IsException(e.throw(&ctx_clone)) };
-                        debug_assert!(is_exception);
-                        let e = ctx_clone.catch();
-                        reject.call::<_, ()>((e,))
-                    }
-                },
+                Err(e) => reject_with(&ctx_clone, &reject, e),
             };
             // TODO figure out something better to do here.
             if let Err(e) = err {
@@ -206,6 +401,79 @@ mod test {
         .await
     }
 
+    #[tokio::test]
+    async fn cancelled_promised() {
+        let rt = AsyncRuntime::new().unwrap();
+        let token = rt.cancellation_token().await;
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+        });
+
+        async_with!(ctx => |ctx| {
+            let promise: Promise<()> = Promised::from(std::future::pending::<()>())
+                .into_js(&ctx)
+                .unwrap()
+                .get()
+                .unwrap();
+            match promise.await.catch(&ctx) {
+                Err(CaughtError::Exception(e)) => {
+                    assert_eq!(e.message().as_deref(), Some("Evaluation was cancelled"))
+                }
+                _ => panic!(),
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn deferred() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let (promise, resolve, reject) = Promise::<i32>::new(&ctx).unwrap();
+            resolve.resolve(1);
+            reject.reject("ignored");
+            assert_eq!(promise.await.catch(&ctx).unwrap(), 1);
+
+            let (promise, _, reject) = Promise::<()>::new(&ctx).unwrap();
+            reject.reject(42);
+            match promise.await.catch(&ctx) {
+                Err(CaughtError::Value(v)) => assert_eq!(v.as_int(), Some(42)),
+                _ => panic!(),
+            }
+
+            let (promise, _, reject) = Promise::<()>::new(&ctx).unwrap();
+            reject.reject_error(Error::new_from_js("value", "thing"));
+            match promise.await.catch(&ctx) {
+                Err(CaughtError::Exception(e)) => {
+                    assert!(e.message().unwrap().contains("thing"))
+                }
+                _ => panic!(),
+            }
+
+            // Dropping both handles leaves the promise pending without leaking the task.
+            let (promise, _, _) = Promise::<()>::new(&ctx).unwrap();
+            ctx.globals().set("pending", promise).unwrap();
+        })
+        .await;
+        rt.idle().await;
+
+        #[cfg(feature = "parallel")]
+        async_with!(ctx => |ctx| {
+            let (promise, resolve, _) = Promise::<String>::new(&ctx).unwrap();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                resolve.resolve("from a thread");
+            });
+            assert_eq!(promise.await.catch(&ctx).unwrap(), "from a thread");
+        })
+        .await;
+    }
+
     #[tokio::test]
     async fn promised() {
         let rt = AsyncRuntime::new().unwrap();
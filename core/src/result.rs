@@ -12,7 +12,8 @@ use std::{
 #[cfg(feature = "futures")]
 use crate::context::AsyncContext;
 use crate::{
-    atom::PredefinedAtom, qjs, Context, Ctx, Exception, Object, StdResult, StdString, Type, Value,
+    atom::PredefinedAtom, qjs, Context, Ctx, Exception, JsError, Object, StdResult, StdString,
+    Type, Value,
 };
 
 #[cfg(feature = "array-buffer")]
@@ -62,6 +63,13 @@ pub enum Error {
     Allocation,
     /// A module defined two exported values with the same name.
     DuplicateExports,
+    /// A [`ModuleDef`](crate::module::ModuleDef) declared exports which are duplicated, can't
+    /// be imported by their name or shadow globals, see
+    /// [`ExportIssue`](crate::module::ExportIssue).
+    InvalidExports {
+        module: StdString,
+        issues: Vec<crate::module::ExportIssue>,
+    },
     /// Found a string with a internal null byte while converting
     /// to C string.
     InvalidString(NulError),
@@ -121,6 +129,17 @@ pub enum Error {
     AsSlice(AsSliceError),
     /// Error when restoring a Persistent in a runtime other than the original runtime.
     UnrelatedRuntime,
+    /// The evaluation was aborted because the runtime's
+    /// [`CancellationToken`](crate::runtime::CancellationToken) was cancelled.
+    ///
+    /// When returned from a callback the error is thrown as an uncatchable exception.
+    Cancelled,
+    /// A metered evaluation used up its fuel, see [`Ctx::eval_with_fuel`].
+    ///
+    /// When returned from a callback the error is thrown as an uncatchable exception.
+    OutOfFuel,
+    /// A Rust error which is thrown as an instance of its own error class.
+    Js(Box<dyn JsError + Send + Sync>),
     /// An error from QuickJS from which the specifics are unknown.
     /// Should eventually be removed as development progresses.
     Unknown,
@@ -251,6 +270,16 @@ impl Error {
         matches!(self, Self::IntoJs { .. })
     }
 
+    /// Returns whether the evaluation was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// Returns whether a metered evaluation ran out of fuel
+    pub fn is_out_of_fuel(&self) -> bool {
+        matches!(self, Self::OutOfFuel)
+    }
+
     /// Return whether the error is an function args mismatch error
     pub fn is_num_args(&self) -> bool {
         matches!(self, Self::TooManyArgs { .. } | Self::MissingArgs { .. })
@@ -296,6 +325,18 @@ impl Error {
                 let message = self.to_cstring();
                 unsafe { qjs::JS_ThrowInternalError(ctx.as_ptr(), message.as_ptr()) }
             }
+            Cancelled | OutOfFuel => unsafe {
+                let message = self.to_cstring();
+                qjs::JS_ThrowInternalError(ctx.as_ptr(), message.as_ptr());
+                // Make sure the script can't catch the error and continue running.
+                let exception = qjs::JS_GetException(ctx.as_ptr());
+                qjs::JS_SetUncatchableError(ctx.as_ptr(), exception, 1);
+                qjs::JS_Throw(ctx.as_ptr(), exception)
+            },
+            Js(error) => {
+                crate::Exception::throw_js_error(ctx, &**error);
+                qjs::JS_EXCEPTION
+            }
             error => {
                 unsafe {
                     let value = qjs::JS_NewError(ctx.as_ptr());
@@ -331,6 +372,17 @@ impl Display for Error {
             DuplicateExports => {
                 "Tried to export two values with the same name from one module".fmt(f)?
             }
+            InvalidExports { module, issues } => {
+                "Invalid exports declared by module '".fmt(f)?;
+                module.fmt(f)?;
+                "': ".fmt(f)?;
+                for (index, issue) in issues.iter().enumerate() {
+                    if index > 0 {
+                        ", ".fmt(f)?;
+                    }
+                    issue.fmt(f)?;
+                }
+            }
             InvalidString(error) => {
                 "String contained internal null bytes: ".fmt(f)?;
                 error.fmt(f)?;
@@ -345,6 +397,8 @@ impl Display for Error {
             }
             Unknown => "QuickJS library created a unknown error".fmt(f)?,
             Exception => "Exception generated by QuickJS".fmt(f)?,
+            Cancelled => "Evaluation was cancelled".fmt(f)?,
+            OutOfFuel => "Evaluation ran out of fuel".fmt(f)?,
             FromJs { from, to, message } => {
                 "Error converting from js '".fmt(f)?;
                 from.fmt(f)?;
@@ -434,6 +488,11 @@ impl Display for Error {
                 x.fmt(f)?;
             }
             UnrelatedRuntime => "Restoring Persistent in an unrelated runtime".fmt(f)?,
+            Js(error) => {
+                error.name().fmt(f)?;
+                ": ".fmt(f)?;
+                error.fmt(f)?;
+            }
         }
         Ok(())
     }
@@ -651,26 +710,59 @@ impl<'js> Ctx<'js> {
     ///
     /// # Safety
     /// Assumes to have ownership of the [`JSValue`]
+    #[cfg(not(feature = "unsafe-api"))]
     pub(crate) unsafe fn handle_exception(&self, js_val: qjs::JSValue) -> Result<qjs::JSValue> {
+        self.check_exception(js_val)
+    }
+
+    /// Handle a possible exception returned by a raw C function.
+    ///
+    /// Returns the value if it is not an exception. Otherwise returns [`Error::Exception`] with
+    /// the exception left pending so it can be retrieved with [`Ctx::catch`], or continues a
+    /// panic raised by a Rust callback, like the safe API does.
+    ///
+    /// # Safety
+    /// Assumes to have ownership of the value, which must belong to the runtime of this context.
+    #[cfg(feature = "unsafe-api")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "unsafe-api")))]
+    pub unsafe fn handle_exception(&self, js_val: qjs::JSValue) -> Result<qjs::JSValue> {
+        self.check_exception(js_val)
+    }
+
+    unsafe fn check_exception(&self, js_val: qjs::JSValue) -> Result<qjs::JSValue> {
         if qjs::JS_VALUE_GET_NORM_TAG(js_val) != qjs::JS_TAG_EXCEPTION {
             Ok(js_val)
         } else {
-            if let Some(x) = (*self.get_opaque()).panic.take() {
-                panic::resume_unwind(x)
-            }
-            Err(Error::Exception)
+            Err(self.raise_exception())
         }
     }
 
     /// Returns [`Error::Exception`] if there is no existing panic,
     /// otherwise continues panicking.
+    ///
+    /// Returns [`Error::OutOfFuel`] or [`Error::Cancelled`] instead if the evaluation was
+    /// aborted for those reasons, the pending exception is then cleared.
     pub(crate) fn raise_exception(&self) -> Error {
         // Safety
         unsafe {
-            if let Some(x) = (*self.get_opaque()).panic.take() {
+            let opaque = &mut *self.get_opaque();
+            if let Some(x) = opaque.panic.take() {
                 panic::resume_unwind(x)
             }
-            Error::Exception
+            let error = if opaque.fuel.as_ref().map(|x| x.exhausted).unwrap_or(false) {
+                Error::OutOfFuel
+            } else if opaque
+                .cancellation
+                .as_ref()
+                .map(|x| x.is_cancelled())
+                .unwrap_or(false)
+            {
+                Error::Cancelled
+            } else {
+                return Error::Exception;
+            };
+            qjs::JS_FreeValue(self.as_ptr(), qjs::JS_GetException(self.as_ptr()));
+            error
         }
     }
 }
@@ -5,6 +5,35 @@ pub(crate) mod raw;
 mod base;
 pub use base::{Runtime, WeakRuntime};
 
+mod cancellation;
+pub use cancellation::CancellationToken;
+
+mod fuel;
+pub(crate) use fuel::metered;
+
+mod compile_cache;
+pub(crate) use compile_cache::{cache_header, cache_key};
+pub use compile_cache::{CompileCache, MemoryCompileCache};
+
+mod diagnostics;
+pub use diagnostics::{
+    ArrayStats, AtomInfo, AtomKind, CountSize, Diagnostics, FunctionStats, HeapDump, ObjectInfo,
+    ShapeInfo,
+};
+
+mod gc;
+pub use gc::{GcHandler, GcStats};
+
+#[cfg(feature = "leak-report")]
+pub(crate) mod leak;
+#[cfg(feature = "leak-report")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-report")))]
+pub use leak::{LeakHandler, LeakReport, LeakedValue};
+
+mod profiler;
+pub(crate) use profiler::profile;
+pub use profiler::{ProfileEvent, ProfileHandler, ProfileKind};
+
 /// The type of the interrupt handler.
 #[cfg(not(feature = "parallel"))]
 pub type InterruptHandler = Box<dyn FnMut() -> bool + 'static>;
@@ -12,6 +41,13 @@ pub type InterruptHandler = Box<dyn FnMut() -> bool + 'static>;
 #[cfg(feature = "parallel")]
 pub type InterruptHandler = Box<dyn FnMut() -> bool + Send + 'static>;
 
+/// The type of the clock used by `Date`, returns the milliseconds since the unix epoch.
+#[cfg(not(feature = "parallel"))]
+pub type Clock = Box<dyn FnMut() -> f64 + 'static>;
+/// The type of the clock used by `Date`, returns the milliseconds since the unix epoch.
+#[cfg(feature = "parallel")]
+pub type Clock = Box<dyn FnMut() -> f64 + Send + 'static>;
+
 #[cfg(feature = "futures")]
 mod r#async;
 #[cfg(feature = "futures")]
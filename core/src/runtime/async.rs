@@ -6,22 +6,29 @@ use std::{
 };
 
 #[cfg(feature = "parallel")]
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::{
+    mem,
+    sync::mpsc::{self, Receiver, Sender},
+};
 
 use async_lock::Mutex;
 
 #[cfg(feature = "allocator")]
 use crate::allocator::Allocator;
 #[cfg(feature = "loader")]
-use crate::loader::{RawLoader, Resolver};
+use crate::loader::{AsyncLoader, AsyncModules, AsyncResolver, RawLoader, Resolver};
 #[cfg(feature = "parallel")]
 use crate::qjs;
-use crate::{context::AsyncContext, result::AsyncJobException, Ctx, Error, Exception, Result};
+use crate::{
+    context::AsyncContext, markers::ParallelSend, result::AsyncJobException, Ctx, Error, Exception,
+    Result,
+};
 
 use super::{
     raw::{Opaque, RawRuntime},
     spawner::DriveFuture,
-    InterruptHandler, MemoryUsage,
+    CancellationToken, Clock, CompileCache, Diagnostics, GcHandler, HeapDump, InterruptHandler,
+    MemoryUsage, ProfileHandler,
 };
 
 #[derive(Debug)]
@@ -35,6 +42,12 @@ impl InnerRuntime {
     pub fn drop_pending(&self) {
         #[cfg(feature = "parallel")]
         while let Ok(x) = self.drop_recv.try_recv() {
+            // Futures spawned from the context can't outlive it.
+            let cancelled = unsafe { self.runtime.get_opaque() }
+                .spawner
+                .as_ref()
+                .map(|s| s.cancel(x));
+            mem::drop(cancelled);
             unsafe { qjs::JS_FreeContext(x.as_ptr()) }
         }
     }
@@ -174,6 +187,25 @@ impl AsyncRuntime {
         }
     }
 
+    /// Returns the cancellation token of the runtime.
+    ///
+    /// Cancelling the token aborts the code currently running in the runtime with
+    /// [`Error::Cancelled`] and rejects the promises of in-flight native futures.
+    pub async fn cancellation_token(&self) -> CancellationToken {
+        unsafe { self.inner.lock().await.runtime.cancellation_token() }
+    }
+
+    /// Attach a debugger to the runtime, or detach it with `None`.
+    ///
+    /// See the [`debugger`](crate::debugger) module for details.
+    #[cfg(feature = "debug")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "debug")))]
+    pub async fn set_debugger(&self, debugger: Option<crate::debugger::Debugger>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_debugger(debugger);
+        }
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -187,6 +219,34 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set a module loader which fetches modules asynchronously
+    ///
+    /// Modules are fetched by [`Module::import_async`](crate::Module::import_async), which
+    /// awaits the resolver and loader for the module and all its dependencies without holding
+    /// the runtime lock before linking and evaluating them. This replaces the module loader set
+    /// with [`AsyncRuntime::set_loader`].
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub async fn set_async_loader<R, L>(&self, resolver: R, loader: L)
+    where
+        R: AsyncResolver + ParallelSend + 'static,
+        L: AsyncLoader + ParallelSend + 'static,
+    {
+        let modules = AsyncModules::new(resolver, loader);
+        unsafe {
+            self.inner.lock().await.runtime.set_async_loader(modules);
+        }
+    }
+
+    /// Invalidate a loaded module, and the modules which imported it, in all the contexts
+    ///
+    /// See [`Runtime::invalidate_module`](crate::Runtime::invalidate_module).
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub async fn invalidate_module(&self, name: &str) -> Result<Vec<String>> {
+        unsafe { self.inner.lock().await.runtime.invalidate_module(name) }
+    }
+
     /// Set the info of the runtime
     pub async fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
@@ -196,6 +256,103 @@ impl AsyncRuntime {
         Ok(())
     }
 
+    /// Set a closure which is called before and after every call to a native Rust function and
+    /// every script evaluation, reporting the name, wall time and exception status.
+    pub async fn set_profiler(&self, profiler: Option<ProfileHandler>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_profiler(profiler);
+        }
+    }
+
+    /// Set a cache for the bytecode of scripts evaluated in global scope, keyed by a hash of
+    /// their source, so evaluating the same script again, in any context of the runtime, skips
+    /// the parser.
+    ///
+    /// Modules and scripts compiled without being run are not cached. See [`CompileCache`] for
+    /// persisting the bytecode between runs, and the requirements on the stored entries.
+    pub async fn set_compile_cache<C: CompileCache + ParallelSend + 'static>(&self, cache: C) {
+        unsafe {
+            self.inner
+                .lock()
+                .await
+                .runtime
+                .set_compile_cache(Some(Box::new(cache)));
+        }
+    }
+
+    /// Remove the compile cache, scripts are parsed on every evaluation again.
+    pub async fn remove_compile_cache(&self) {
+        unsafe {
+            self.inner.lock().await.runtime.set_compile_cache(None);
+        }
+    }
+
+    /// Set the clock used by `Date.now()` and `new Date()`, returning the milliseconds since the
+    /// unix epoch, or go back to the system clock with `None`.
+    ///
+    /// Together with [`set_random_seed`](Self::set_random_seed) this makes the execution of
+    /// scripts reproducible, for example for tests or for replaying a recorded run. The clock
+    /// should not panic, as the time can't fail a panic is only continued by the next exception.
+    pub async fn set_clock(&self, clock: Option<Clock>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_clock(clock);
+        }
+    }
+
+    /// Seed `Math.random` of the contexts created afterwards, so the numbers they generate are
+    /// the same in every run, or go back to seeding from the time with `None`.
+    ///
+    /// Existing contexts can be reseeded with [`Ctx::set_random_seed`](crate::Ctx::set_random_seed).
+    pub async fn set_random_seed(&self, seed: Option<u64>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_random_seed(seed);
+        }
+    }
+
+    /// Enable or disable adding a frame like `at native name (src/lib.rs:10:5)` to the stack of
+    /// exceptions passing through native Rust functions, with the location the function was
+    /// created at.
+    ///
+    /// Disabled by default, as it makes every exception crossing a native function more costly.
+    pub async fn set_native_stack_frames(&self, enabled: bool) {
+        unsafe {
+            self.inner
+                .lock()
+                .await
+                .runtime
+                .set_native_stack_frames(enabled);
+        }
+    }
+
+    /// Set the maximum amount of native Rust functions which can run nested in each other, like
+    /// a native function calling JavaScript which calls a native function again.
+    ///
+    /// Calling a native function beyond the limit throws a `RangeError` instead, which prevents
+    /// deeply recursive calls between Rust and JavaScript from overflowing the stack. The
+    /// current depth is returned by [`Ctx::native_call_depth`](crate::Ctx::native_call_depth).
+    /// There is no limit by default, `None` removes the limit.
+    pub async fn set_max_native_recursion(&self, depth: Option<usize>) {
+        unsafe {
+            self.inner
+                .lock()
+                .await
+                .runtime
+                .set_max_native_recursion(depth);
+        }
+    }
+
+    /// Store user data in the runtime, returning the previous value of the same type.
+    ///
+    /// The data can be accessed from native callbacks with [`Ctx::userdata`].
+    pub async fn set_userdata<T: Send + 'static>(&self, value: T) -> Option<T> {
+        unsafe { self.inner.lock().await.runtime.set_userdata(value) }
+    }
+
+    /// Remove user data from the runtime.
+    pub async fn remove_userdata<T: Send + 'static>(&self) -> Option<T> {
+        unsafe { self.inner.lock().await.runtime.remove_userdata() }
+    }
+
     /// Set a limit on the max amount of memory the runtime will use.
     ///
     /// Setting the limit to 0 is equivalent to unlimited memory.
@@ -217,6 +374,15 @@ impl AsyncRuntime {
         }
     }
 
+    /// Set whether `Atomics.wait` is allowed to block the thread running the runtime.
+    ///
+    /// Blocking is disallowed by default.
+    pub async fn set_can_block(&self, can_block: bool) {
+        unsafe {
+            self.inner.lock().await.runtime.set_can_block(can_block);
+        }
+    }
+
     /// Set a memory threshold for garbage collection.
     pub async fn set_gc_threshold(&self, threshold: usize) {
         unsafe {
@@ -224,6 +390,49 @@ impl AsyncRuntime {
         }
     }
 
+    /// Get the memory threshold for garbage collection.
+    ///
+    /// A collection cycle runs when the allocated memory exceeds the threshold, afterwards the
+    /// threshold is raised to one and a half times the memory still in use.
+    pub async fn gc_threshold(&self) -> usize {
+        unsafe { self.inner.lock().await.runtime.gc_threshold() }
+    }
+
+    /// Set a closure which is called after every garbage collection cycle, automatic or run
+    /// with [`AsyncRuntime::run_gc`], with the duration of the cycle and the freed memory.
+    ///
+    /// The freed memory is taken from the allocation tracking of QuickJS, which custom
+    /// allocators don't update, so it is 0 with the "rust-alloc" or "allocator" features.
+    ///
+    /// The handler is not able to interact with the runtime.
+    pub async fn set_gc_handler(&self, handler: Option<GcHandler>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_gc_handler(handler);
+        }
+    }
+
+    /// Returns the handles of [`Persistent`](crate::Persistent) values of the runtime which are
+    /// still alive, with the backtraces of their creation.
+    #[cfg(feature = "leak-report")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-report")))]
+    pub async fn leak_report(&self) -> super::LeakReport {
+        unsafe { self.inner.lock().await.runtime.leak_report() }
+    }
+
+    /// Set a closure which is called when the runtime is dropped while [`Persistent`] values of
+    /// it are still alive, `None` to print the report to stderr.
+    ///
+    /// See [`Runtime::set_leak_handler`](crate::Runtime::set_leak_handler).
+    ///
+    /// [`Persistent`]: crate::Persistent
+    #[cfg(feature = "leak-report")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-report")))]
+    pub async fn set_leak_handler(&self, handler: Option<super::LeakHandler>) {
+        unsafe {
+            self.inner.lock().await.runtime.set_leak_handler(handler);
+        }
+    }
+
     /// Manually run the garbage collection.
     ///
     /// Most QuickJS values are reference counted and
@@ -243,6 +452,35 @@ impl AsyncRuntime {
         unsafe { self.inner.lock().await.runtime.memory_usage() }
     }
 
+    /// Get structured statistics about the heap composition of the runtime.
+    pub async fn diagnostics(&self) -> Diagnostics {
+        unsafe { self.inner.lock().await.runtime.memory_usage() }.into()
+    }
+
+    /// Get a listing of all atoms, shapes and objects of the runtime.
+    ///
+    /// This walks the whole heap, use [`AsyncRuntime::diagnostics`] for regular monitoring.
+    pub async fn heap_dump(&self) -> HeapDump {
+        unsafe { self.inner.lock().await.runtime.heap_dump() }
+    }
+
+    /// Enable code coverage collection.
+    ///
+    /// Scripts and modules evaluated after coverage is enabled are instrumented, see
+    /// [`coverage`](crate::coverage) for details.
+    #[cfg(feature = "coverage")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "coverage")))]
+    pub async fn enable_coverage(&self) {
+        unsafe { self.inner.lock().await.runtime.enable_coverage() }
+    }
+
+    /// Returns the coverage collected so far, `None` if coverage is not enabled.
+    #[cfg(feature = "coverage")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "coverage")))]
+    pub async fn coverage(&self) -> Option<crate::coverage::Coverage> {
+        unsafe { self.inner.lock().await.runtime.coverage() }
+    }
+
     /// Test for pending jobs
     ///
     /// Returns true when at least one job is pending.
@@ -311,6 +549,8 @@ impl AsyncRuntime {
                 .drive()
                 .await
             {
+                // Contexts might have been dropped by the futures.
+                lock.drop_pending();
                 continue;
             }
 
@@ -489,4 +729,80 @@ mod test {
         }).await;
 
     });
+
+    async_test_case!(cancel_spawned => (rt,ctx){
+        use std::sync::{Arc, atomic::{Ordering,AtomicBool}};
+
+        struct Guard(Arc<AtomicBool>);
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let other = crate::AsyncContext::full(&rt).await.unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let other_dropped = Arc::new(AtomicBool::new(false));
+
+        let guard = Guard(dropped.clone());
+        async_with!(&ctx => |ctx|{
+            ctx.spawn(async move {
+                let _guard = guard;
+                std::future::pending::<()>().await;
+            });
+        }).await;
+        let guard = Guard(other_dropped.clone());
+        async_with!(&other => |ctx|{
+            let ctx_clone = ctx.clone();
+            ctx.spawn(async move {
+                let _guard = guard;
+                let _ctx = ctx_clone;
+                std::future::pending::<()>().await;
+            });
+        }).await;
+        assert!(rt.is_job_pending().await);
+
+        std::mem::drop(other);
+        assert!(!dropped.load(Ordering::SeqCst));
+        assert!(other_dropped.load(Ordering::SeqCst));
+
+        std::mem::drop(ctx);
+        assert!(dropped.load(Ordering::SeqCst));
+        // Nothing is left for the runtime to wait on.
+        assert!(!rt.is_job_pending().await);
+        tokio::time::timeout(Duration::from_millis(500), rt.idle()).await.unwrap();
+    });
+
+    async_test_case!(cancel_spawned_from_spawned => (rt,ctx){
+        use std::sync::{Arc, atomic::{Ordering,AtomicBool}};
+
+        struct Guard(Arc<AtomicBool>);
+        impl Drop for Guard {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let other = crate::AsyncContext::full(&rt).await.unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        let guard = Guard(dropped.clone());
+        async_with!(&other => |ctx|{
+            let ctx_clone = ctx.clone();
+            ctx.spawn(async move {
+                let _guard = guard;
+                let _ctx = ctx_clone;
+                std::future::pending::<()>().await;
+            });
+        }).await;
+        async_with!(&ctx => |ctx|{
+            ctx.spawn(async move {
+                tokio::task::yield_now().await;
+                std::mem::drop(other);
+            });
+        }).await;
+
+        tokio::time::timeout(Duration::from_millis(500), rt.idle()).await.unwrap();
+        assert!(dropped.load(Ordering::SeqCst));
+    });
 }
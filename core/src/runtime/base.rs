@@ -2,7 +2,7 @@
 
 #[cfg(feature = "loader")]
 use crate::loader::{RawLoader, Resolver};
-use crate::{result::JobException, Context, Error, Mut, Ref, Result, Weak};
+use crate::{markers::ParallelSend, result::JobException, Context, Error, Mut, Ref, Result, Weak};
 use std::{ffi::CString, ptr::NonNull, result::Result as StdResult};
 
 #[cfg(feature = "allocator")]
@@ -10,7 +10,8 @@ use crate::allocator::Allocator;
 
 use super::{
     raw::{Opaque, RawRuntime},
-    InterruptHandler, MemoryUsage,
+    CancellationToken, Clock, CompileCache, Diagnostics, GcHandler, HeapDump, InterruptHandler,
+    MemoryUsage, ProfileHandler,
 };
 
 /// A weak handle to the runtime.
@@ -80,6 +81,25 @@ impl Runtime {
         }
     }
 
+    /// Returns the cancellation token of the runtime.
+    ///
+    /// Cancelling the token aborts the code currently running in the runtime with
+    /// [`Error::Cancelled`] and rejects the promises of in-flight native futures.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        unsafe { self.inner.lock().cancellation_token() }
+    }
+
+    /// Attach a debugger to the runtime, or detach it with `None`.
+    ///
+    /// See the [`debugger`](crate::debugger) module for details.
+    #[cfg(feature = "debug")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "debug")))]
+    pub fn set_debugger(&self, debugger: Option<crate::debugger::Debugger>) {
+        unsafe {
+            self.inner.lock().set_debugger(debugger);
+        }
+    }
+
     /// Set the module loader
     #[cfg(feature = "loader")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
@@ -93,6 +113,24 @@ impl Runtime {
         }
     }
 
+    /// Invalidate a loaded module, and the modules which imported it, in all the contexts
+    ///
+    /// The next import of an invalidated module, for example with [`Module::import`], calls the
+    /// loader again and links the new module into the reloaded importers. Modules which were not
+    /// invalidated keep the instances they already imported.
+    ///
+    /// The invalidated modules are freed as soon as no module which is still valid imports them, so
+    /// reloading doesn't leak the previous instances.
+    ///
+    /// Returns the names of the invalidated modules.
+    ///
+    /// [`Module::import`]: crate::Module::import
+    #[cfg(feature = "loader")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "loader")))]
+    pub fn invalidate_module(&self, name: &str) -> Result<Vec<String>> {
+        unsafe { self.inner.lock().invalidate_module(name) }
+    }
+
     /// Set the info of the runtime
     pub fn set_info<S: Into<Vec<u8>>>(&self, info: S) -> Result<()> {
         let string = CString::new(info)?;
@@ -102,6 +140,102 @@ impl Runtime {
         Ok(())
     }
 
+    /// Set a closure which is called before and after every call to a native Rust function and
+    /// every script evaluation, reporting the name, wall time and exception status.
+    ///
+    /// The profiler is not able to interact with the runtime.
+    pub fn set_profiler(&self, profiler: Option<ProfileHandler>) {
+        unsafe {
+            self.inner.lock().set_profiler(profiler);
+        }
+    }
+
+    /// Set a cache for the bytecode of scripts evaluated in global scope, keyed by a hash of
+    /// their source, so evaluating the same script again, in any context of the runtime, skips
+    /// the parser.
+    ///
+    /// Modules and scripts compiled without being run are not cached. See [`CompileCache`] for
+    /// persisting the bytecode between runs, and the requirements on the stored entries.
+    pub fn set_compile_cache<C: CompileCache + ParallelSend + 'static>(&self, cache: C) {
+        unsafe {
+            self.inner.lock().set_compile_cache(Some(Box::new(cache)));
+        }
+    }
+
+    /// Remove the compile cache, scripts are parsed on every evaluation again.
+    pub fn remove_compile_cache(&self) {
+        unsafe {
+            self.inner.lock().set_compile_cache(None);
+        }
+    }
+
+    /// Set the clock used by `Date.now()` and `new Date()`, returning the milliseconds since the
+    /// unix epoch, or go back to the system clock with `None`.
+    ///
+    /// Together with [`set_random_seed`](Self::set_random_seed) this makes the execution of
+    /// scripts reproducible, for example for tests or for replaying a recorded run. The clock
+    /// should not panic, as the time can't fail a panic is only continued by the next exception.
+    pub fn set_clock(&self, clock: Option<Clock>) {
+        unsafe {
+            self.inner.lock().set_clock(clock);
+        }
+    }
+
+    /// Seed `Math.random` of the contexts created afterwards, so the numbers they generate are
+    /// the same in every run, or go back to seeding from the time with `None`.
+    ///
+    /// Existing contexts can be reseeded with [`Ctx::set_random_seed`](crate::Ctx::set_random_seed).
+    pub fn set_random_seed(&self, seed: Option<u64>) {
+        unsafe {
+            self.inner.lock().set_random_seed(seed);
+        }
+    }
+
+    /// Enable or disable adding a frame like `at native name (src/lib.rs:10:5)` to the stack of
+    /// exceptions passing through native Rust functions, with the location the function was
+    /// created at.
+    ///
+    /// Disabled by default, as it makes every exception crossing a native function more costly.
+    pub fn set_native_stack_frames(&self, enabled: bool) {
+        unsafe {
+            self.inner.lock().set_native_stack_frames(enabled);
+        }
+    }
+
+    /// Set the maximum amount of native Rust functions which can run nested in each other, like
+    /// a native function calling JavaScript which calls a native function again.
+    ///
+    /// Calling a native function beyond the limit throws a `RangeError` instead, which prevents
+    /// deeply recursive calls between Rust and JavaScript from overflowing the stack. The
+    /// current depth is returned by [`Ctx::native_call_depth`](crate::Ctx::native_call_depth).
+    /// There is no limit by default, `None` removes the limit.
+    pub fn set_max_native_recursion(&self, depth: Option<usize>) {
+        unsafe {
+            self.inner.lock().set_max_native_recursion(depth);
+        }
+    }
+
+    /// Store user data in the runtime, returning the previous value of the same type.
+    ///
+    /// The data can be accessed from native callbacks with [`Ctx::userdata`](crate::Ctx::userdata).
+    ///
+    /// # Panic
+    /// Panics if called while the runtime is in use on the same thread, for example from inside
+    /// [`Context::with`]. With the `parallel` feature enabled this deadlocks instead.
+    pub fn set_userdata<T: ParallelSend + 'static>(&self, value: T) -> Option<T> {
+        // Holding the runtime lock guarantees that no references from `Ctx::userdata` exist.
+        unsafe { self.inner.lock().set_userdata(value) }
+    }
+
+    /// Remove user data from the runtime.
+    ///
+    /// # Panic
+    /// Panics if called while the runtime is in use on the same thread, for example from inside
+    /// [`Context::with`]. With the `parallel` feature enabled this deadlocks instead.
+    pub fn remove_userdata<T: ParallelSend + 'static>(&self) -> Option<T> {
+        unsafe { self.inner.lock().remove_userdata() }
+    }
+
     /// Set a limit on the max amount of memory the runtime will use.
     ///
     /// Setting the limit to 0 is equivalent to unlimited memory.
@@ -123,6 +257,15 @@ impl Runtime {
         }
     }
 
+    /// Set whether `Atomics.wait` is allowed to block the thread running the runtime.
+    ///
+    /// Blocking is disallowed by default.
+    pub fn set_can_block(&self, can_block: bool) {
+        unsafe {
+            self.inner.lock().set_can_block(can_block);
+        }
+    }
+
     /// Set a memory threshold for garbage collection.
     pub fn set_gc_threshold(&self, threshold: usize) {
         unsafe {
@@ -130,6 +273,27 @@ impl Runtime {
         }
     }
 
+    /// Get the memory threshold for garbage collection.
+    ///
+    /// A collection cycle runs when the allocated memory exceeds the threshold, afterwards the
+    /// threshold is raised to one and a half times the memory still in use.
+    pub fn gc_threshold(&self) -> usize {
+        unsafe { self.inner.lock().gc_threshold() }
+    }
+
+    /// Set a closure which is called after every garbage collection cycle, automatic or run
+    /// with [`Runtime::run_gc`], with the duration of the cycle and the freed memory.
+    ///
+    /// The freed memory is taken from the allocation tracking of QuickJS, which custom
+    /// allocators don't update, so it is 0 with the "rust-alloc" or "allocator" features.
+    ///
+    /// The handler is not able to interact with the runtime.
+    pub fn set_gc_handler(&self, handler: Option<GcHandler>) {
+        unsafe {
+            self.inner.lock().set_gc_handler(handler);
+        }
+    }
+
     /// Manually run the garbage collection.
     ///
     /// Most of QuickJS values are reference counted and
@@ -147,6 +311,57 @@ impl Runtime {
         unsafe { self.inner.lock().memory_usage() }
     }
 
+    /// Get structured statistics about the heap composition of the runtime.
+    pub fn diagnostics(&self) -> Diagnostics {
+        unsafe { self.inner.lock().memory_usage() }.into()
+    }
+
+    /// Get a listing of all atoms, shapes and objects of the runtime.
+    ///
+    /// This walks the whole heap, use [`Runtime::diagnostics`] for regular monitoring.
+    pub fn heap_dump(&self) -> HeapDump {
+        unsafe { self.inner.lock().heap_dump() }
+    }
+
+    /// Enable code coverage collection.
+    ///
+    /// Scripts and modules evaluated after coverage is enabled are instrumented, see
+    /// [`coverage`](crate::coverage) for details.
+    #[cfg(feature = "coverage")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "coverage")))]
+    pub fn enable_coverage(&self) {
+        unsafe { self.inner.lock().enable_coverage() }
+    }
+
+    /// Returns the coverage collected so far, `None` if coverage is not enabled.
+    #[cfg(feature = "coverage")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "coverage")))]
+    pub fn coverage(&self) -> Option<crate::coverage::Coverage> {
+        unsafe { self.inner.lock().coverage() }
+    }
+
+    /// Returns the handles of [`Persistent`](crate::Persistent) values of the runtime which are
+    /// still alive, with the backtraces of their creation.
+    #[cfg(feature = "leak-report")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-report")))]
+    pub fn leak_report(&self) -> super::LeakReport {
+        unsafe { self.inner.lock().leak_report() }
+    }
+
+    /// Set a closure which is called when the runtime is dropped while [`Persistent`] values of
+    /// it are still alive, `None` to print the report to stderr.
+    ///
+    /// Freeing a runtime with persistent values aborts the process, the handler is called
+    /// before that with the handles which kept the values alive. It is not able to interact
+    /// with the runtime.
+    ///
+    /// [`Persistent`]: crate::Persistent
+    #[cfg(feature = "leak-report")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "leak-report")))]
+    pub fn set_leak_handler(&self, handler: Option<super::LeakHandler>) {
+        unsafe { self.inner.lock().set_leak_handler(handler) }
+    }
+
     /// Test for pending jobs
     ///
     /// Returns true when at least one job is pending.
@@ -197,4 +412,95 @@ mod test {
         rt.set_gc_threshold(0xFF);
         rt.run_gc();
     }
+
+    #[test]
+    fn userdata() {
+        use std::sync::atomic::{AtomicI32, Ordering};
+
+        struct Counter(AtomicI32);
+
+        let rt = Runtime::new().unwrap();
+        assert!(rt.set_userdata(Counter(AtomicI32::new(1))).is_none());
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let counter = ctx.userdata::<Counter>().unwrap();
+            counter.0.fetch_add(1, Ordering::Relaxed);
+            assert!(ctx.userdata::<i32>().is_none());
+        });
+        let counter = rt.remove_userdata::<Counter>().unwrap();
+        assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+        assert!(rt.remove_userdata::<Counter>().is_none());
+    }
+
+    #[test]
+    fn cancellation() {
+        use crate::{function::Func, CatchResultExt};
+
+        let rt = Runtime::new().unwrap();
+        let token = rt.cancellation_token();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let res: i32 = ctx.eval("1 + 1").unwrap();
+            assert_eq!(res, 2);
+
+            let remote = token.clone();
+            let handle = std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                remote.cancel();
+            });
+            let err = ctx.eval::<(), _>("while(true) {}").unwrap_err();
+            handle.join().unwrap();
+            assert!(err.is_cancelled());
+            assert!(ctx.catch().is_null());
+
+            // Scripts can't catch the cancellation.
+            ctx.globals()
+                .set(
+                    "native",
+                    Func::from(|| -> Result<()> { Err(Error::Cancelled) }),
+                )
+                .unwrap();
+            let err = ctx
+                .eval::<(), _>("try { native() } catch(e) {}")
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(matches!(err, crate::CaughtError::Error(Error::Cancelled)));
+        });
+        assert!(token.is_cancelled());
+        assert!(rt.cancellation_token().is_cancelled());
+    }
+
+    #[test]
+    fn deterministic() {
+        let run = || {
+            let rt = Runtime::new().unwrap();
+            let mut time = 1_000_000.0;
+            rt.set_clock(Some(Box::new(move || {
+                time += 1.5;
+                time
+            })));
+            rt.set_random_seed(Some(42));
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                let values: Vec<f64> = ctx
+                    .eval("[Math.random(), Math.random(), Date.now(), new Date().getTime()]")
+                    .unwrap();
+                assert_eq!(values[2..], [1_000_001.0, 1_000_003.0]);
+                let date = crate::value::Date::now(ctx.clone()).unwrap();
+                assert_eq!(date.get_time().unwrap(), 1_000_004.0);
+
+                ctx.set_random_seed(42);
+                let first: f64 = ctx.eval("Math.random()").unwrap();
+                assert_eq!(first, values[0]);
+                values
+            })
+        };
+        assert_eq!(run(), run());
+
+        let rt = Runtime::new().unwrap();
+        rt.set_clock(Some(Box::new(|| 0.0)));
+        rt.set_clock(None);
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| assert!(ctx.eval::<f64, _>("Date.now()").unwrap() > 0.0));
+    }
 }
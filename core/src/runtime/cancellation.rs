@@ -0,0 +1,76 @@
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::Waker,
+};
+
+#[cfg(feature = "futures")]
+use std::{future::Future, pin::pin, task::Poll};
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// A handle to cancel the code running in a runtime, returned by
+/// [`Runtime::cancellation_token`](crate::Runtime::cancellation_token).
+///
+/// The token can be cloned and sent to other threads. After [`cancel`](Self::cancel) is called
+/// the interrupt handler aborts the running evaluation, which then returns
+/// [`Error::Cancelled`](crate::Error::Cancelled), and the promises of in-flight native futures
+/// are rejected.
+///
+/// Cancelling is permanent, any code still run in the runtime afterwards is aborted as well.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<Inner>);
+
+impl CancellationToken {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the evaluations in the runtime the token belongs to.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        let wakers = mem::take(&mut *self.0.wakers.lock().unwrap());
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Returns whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    #[cfg(feature = "futures")]
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.0.wakers.lock().unwrap();
+        if !wakers.iter().any(|x| x.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    /// Run a future until it completes, returns `None` if the token is cancelled first.
+    #[cfg(feature = "futures")]
+    pub(crate) async fn guard<F: Future>(&self, future: F) -> Option<F::Output> {
+        let mut future = pin!(future);
+        std::future::poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            if let Poll::Ready(x) = future.as_mut().poll(cx) {
+                return Poll::Ready(Some(x));
+            }
+            self.register(cx.waker());
+            // The token might have been cancelled before the waker was registered.
+            if self.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
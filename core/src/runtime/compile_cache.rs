@@ -0,0 +1,251 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::CStr,
+};
+
+/// A store for the bytecode of evaluated scripts, set with
+/// [`Runtime::set_compile_cache`](crate::Runtime::set_compile_cache).
+///
+/// Scripts evaluated in global scope are looked up by a hash of their source, file name, line
+/// offset and evaluation flags before they are parsed. On a hit the parser is skipped and the
+/// stored bytecode is run instead, on a miss the script is compiled and its bytecode inserted.
+/// The entries start with the script they were compiled from, an entry of a different script
+/// with the same key is a miss.
+///
+/// The key is stable between runs, so an implementation may persist the entries, for example
+/// on disk. Bytecode is only valid for the QuickJS version and the endianness it was compiled
+/// with, an entry of a different version is a miss and replaced in the cache.
+///
+/// # Safety
+/// QuickJS doesn't validate bytecode, so reading corrupted or crafted bytecode is undefined
+/// behavior. [`CompileCache::get`] must only return an entry given to [`CompileCache::insert`]
+/// by a runtime of this QuickJS build, unmodified. Persisted entries must be stored where
+/// nothing else can change them.
+pub unsafe trait CompileCache {
+    /// Returns the entry stored for the key, if any.
+    fn get(&mut self, key: u64) -> Option<Vec<u8>>;
+
+    /// Store the entry of a script.
+    fn insert(&mut self, key: u64, entry: Vec<u8>);
+}
+
+/// A compile cache which keeps the entries in memory.
+///
+/// The cache holds a limited amount of scripts, inserting more removes the oldest ones.
+#[derive(Debug, Clone)]
+pub struct MemoryCompileCache {
+    entries: HashMap<u64, Vec<u8>>,
+    order: VecDeque<u64>,
+    limit: usize,
+}
+
+impl MemoryCompileCache {
+    /// The amount of scripts held by a cache created with [`MemoryCompileCache::new`].
+    pub const DEFAULT_LIMIT: usize = 256;
+
+    /// Create an empty cache holding at most [`MemoryCompileCache::DEFAULT_LIMIT`] scripts.
+    pub fn new() -> Self {
+        Self::with_limit(Self::DEFAULT_LIMIT)
+    }
+
+    /// Create an empty cache holding at most `limit` scripts.
+    pub fn with_limit(limit: usize) -> Self {
+        MemoryCompileCache {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            limit,
+        }
+    }
+
+    /// Returns the amount of cached scripts.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the maximum amount of cached scripts.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Remove all cached scripts.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+impl Default for MemoryCompileCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The entries are only ever given by the runtime.
+unsafe impl CompileCache for MemoryCompileCache {
+    fn get(&mut self, key: u64) -> Option<Vec<u8>> {
+        self.entries.get(&key).cloned()
+    }
+
+    fn insert(&mut self, key: u64, entry: Vec<u8>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.entries.insert(key, entry).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.limit {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Returns the cache key of a script.
+///
+/// This is a 64 bit FNV-1a hash, unlike the hasher of the standard library it doesn't change
+/// between Rust versions which matters for persisted caches.
+pub(crate) fn cache_key(source: &[u8], file_name: &CStr, line_offset: u32, flag: i32) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+    write(&flag.to_le_bytes());
+    write(&line_offset.to_le_bytes());
+    write(file_name.to_bytes_with_nul());
+    write(source);
+    hash
+}
+
+/// Returns what identifies a script in its cache entry, stored in front of the bytecode as
+/// different scripts can have the same key.
+pub(crate) fn cache_header(
+    source: &[u8],
+    file_name: &CStr,
+    line_offset: u32,
+    flag: i32,
+) -> Vec<u8> {
+    let file_name = file_name.to_bytes_with_nul();
+    let len = 8 + file_name.len() + source.len();
+    let mut header = Vec::with_capacity(8 + len);
+    // The length comes first so a header can't be the prefix of another one.
+    header.extend_from_slice(&(len as u64).to_le_bytes());
+    header.extend_from_slice(&flag.to_le_bytes());
+    header.extend_from_slice(&line_offset.to_le_bytes());
+    header.extend_from_slice(file_name);
+    header.extend_from_slice(source);
+    header
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::*;
+
+    #[derive(Default)]
+    struct Counts {
+        hits: usize,
+        inserts: usize,
+        cache: MemoryCompileCache,
+    }
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Counts>>);
+
+    unsafe impl CompileCache for Shared {
+        fn get(&mut self, key: u64) -> Option<Vec<u8>> {
+            let mut counts = self.0.lock().unwrap();
+            let res = counts.cache.get(key);
+            counts.hits += res.is_some() as usize;
+            res
+        }
+
+        fn insert(&mut self, key: u64, bytecode: Vec<u8>) {
+            let mut counts = self.0.lock().unwrap();
+            counts.inserts += 1;
+            counts.cache.insert(key, bytecode);
+        }
+    }
+
+    #[test]
+    fn shared_between_contexts() {
+        let rt = Runtime::new().unwrap();
+        let shared = Shared::default();
+        rt.set_compile_cache(shared.clone());
+        let source = "function double(x) { return x * 2 }; double(globalThis.input)";
+        for input in [2, 3] {
+            let ctx = Context::full(&rt).unwrap();
+            ctx.with(|ctx| {
+                ctx.globals().set("input", input).unwrap();
+                assert_eq!(ctx.eval::<i32, _>(source).unwrap(), input * 2);
+                assert!(ctx.eval::<(), _>("throw new Error('cached')").is_err());
+                let error = ctx.catch().into_exception().unwrap();
+                assert_eq!(error.message().as_deref(), Some("cached"));
+            });
+        }
+        {
+            let counts = shared.0.lock().unwrap();
+            assert_eq!((counts.hits, counts.inserts, counts.cache.len()), (2, 2, 2));
+        }
+
+        // Bytecode which doesn't load is replaced.
+        let flag = (qjs::JS_EVAL_TYPE_GLOBAL | qjs::JS_EVAL_FLAG_STRICT) as i32;
+        let key = cache_key(b"6 * 7", cstr!("eval_script"), 0, flag);
+        shared.0.lock().unwrap().cache.insert(key, vec![0xFF; 4]);
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            assert_eq!(ctx.eval::<i32, _>("6 * 7").unwrap(), 42);
+            assert!(ctx.eval::<(), _>("syntax error").is_err());
+            ctx.catch();
+        });
+        {
+            let counts = shared.0.lock().unwrap();
+            assert_eq!((counts.hits, counts.inserts), (3, 3));
+            assert_ne!(counts.cache.entries[&key], [0xFF; 4]);
+        }
+
+        // An entry of a different script with the same key isn't run.
+        let other = cache_key(b"1 + 1", cstr!("eval_script"), 0, flag);
+        {
+            let mut counts = shared.0.lock().unwrap();
+            let entry = counts.cache.entries[&key].clone();
+            counts.cache.insert(other, entry);
+        }
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| assert_eq!(ctx.eval::<i32, _>("1 + 1").unwrap(), 2));
+
+        rt.remove_compile_cache();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| assert_eq!(ctx.eval::<i32, _>("1 + 1").unwrap(), 2));
+        assert_eq!(shared.0.lock().unwrap().inserts, 4);
+    }
+
+    #[test]
+    fn memory_limit() {
+        let mut cache = MemoryCompileCache::with_limit(2);
+        for key in 0..3 {
+            cache.insert(key, vec![key as u8]);
+        }
+        cache.insert(2, vec![3]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(0), None);
+        assert_eq!(cache.get(1), Some(vec![1]));
+        assert_eq!(cache.get(2), Some(vec![3]));
+        assert_eq!(
+            MemoryCompileCache::new().limit(),
+            MemoryCompileCache::DEFAULT_LIMIT
+        );
+    }
+}
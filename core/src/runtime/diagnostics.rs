@@ -0,0 +1,316 @@
+use std::{collections::BTreeMap, ffi::CStr};
+
+use super::MemoryUsage;
+use crate::{qjs, StdString};
+
+/// The number and total size of a kind of allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CountSize {
+    /// The number of allocations.
+    pub count: usize,
+    /// The total size in bytes.
+    pub size: usize,
+}
+
+impl CountSize {
+    fn new(count: i64, size: i64) -> Self {
+        CountSize {
+            count: count.max(0) as usize,
+            size: size.max(0) as usize,
+        }
+    }
+}
+
+/// Statistics about the JavaScript functions of a runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FunctionStats {
+    /// The number and size of the JavaScript function objects.
+    pub functions: CountSize,
+    /// The total size of the function bytecode in bytes.
+    pub code_size: usize,
+    /// The number and size of the tables mapping bytecode to source lines.
+    pub line_tables: CountSize,
+    /// The number of native C functions.
+    pub native_count: usize,
+}
+
+/// Statistics about the arrays of a runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ArrayStats {
+    /// The number of arrays.
+    pub count: usize,
+    /// The number of arrays using the fast, densely packed, representation.
+    pub fast_count: usize,
+    /// The total number of elements in fast arrays.
+    pub fast_elements: usize,
+}
+
+/// Structured statistics about the heap of a runtime.
+///
+/// Returned by [`Runtime::diagnostics`](crate::Runtime::diagnostics), this contains the same
+/// data QuickJS prints in its memory dumps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Diagnostics {
+    /// The memory allocated by the allocator.
+    pub malloc: CountSize,
+    /// The memory limit in bytes, `None` if unlimited.
+    pub malloc_limit: Option<usize>,
+    /// The memory in use by QuickJS.
+    pub memory_used: CountSize,
+    /// The atoms, interned strings used for property keys.
+    pub atoms: CountSize,
+    /// The strings.
+    pub strings: CountSize,
+    /// The objects.
+    pub objects: CountSize,
+    /// The object properties.
+    pub properties: CountSize,
+    /// The object shapes, which are shared between objects with the same layout.
+    pub shapes: CountSize,
+    /// The functions.
+    pub functions: FunctionStats,
+    /// The arrays.
+    pub arrays: ArrayStats,
+    /// The binary objects, like array buffers.
+    pub binary_objects: CountSize,
+}
+
+impl From<MemoryUsage> for Diagnostics {
+    fn from(usage: MemoryUsage) -> Self {
+        Diagnostics {
+            malloc: CountSize::new(usage.malloc_count, usage.malloc_size),
+            malloc_limit: usize::try_from(usage.malloc_limit)
+                .ok()
+                .filter(|&limit| limit != usize::MAX),
+            memory_used: CountSize::new(usage.memory_used_count, usage.memory_used_size),
+            atoms: CountSize::new(usage.atom_count, usage.atom_size),
+            strings: CountSize::new(usage.str_count, usage.str_size),
+            objects: CountSize::new(usage.obj_count, usage.obj_size),
+            properties: CountSize::new(usage.prop_count, usage.prop_size),
+            shapes: CountSize::new(usage.shape_count, usage.shape_size),
+            functions: FunctionStats {
+                functions: CountSize::new(usage.js_func_count, usage.js_func_size),
+                code_size: usage.js_func_code_size.max(0) as usize,
+                line_tables: CountSize::new(
+                    usage.js_func_pc2line_count,
+                    usage.js_func_pc2line_size,
+                ),
+                native_count: usage.c_func_count.max(0) as usize,
+            },
+            arrays: ArrayStats {
+                count: usage.array_count.max(0) as usize,
+                fast_count: usage.fast_array_count.max(0) as usize,
+                fast_elements: usage.fast_array_elements.max(0) as usize,
+            },
+            binary_objects: CountSize::new(usage.binary_object_count, usage.binary_object_size),
+        }
+    }
+}
+
+/// The kind of an [`AtomInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtomKind {
+    /// An interned string.
+    String,
+    /// A symbol from the global registry, created with `Symbol.for`.
+    GlobalSymbol,
+    /// A symbol.
+    Symbol,
+    /// The name of a private class member.
+    Private,
+}
+
+/// An atom in a [`HeapDump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomInfo {
+    /// The string of the atom or the description of the symbol, truncated to 63 bytes.
+    pub name: StdString,
+    /// The kind of the atom.
+    pub kind: AtomKind,
+    /// The number of references to the atom.
+    pub ref_count: usize,
+}
+
+/// A shape in a [`HeapDump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShapeInfo {
+    /// The number of references to the shape, each object using the shape holds one.
+    pub ref_count: usize,
+    /// The number of properties of the shape.
+    pub properties: usize,
+    /// The number of properties the shape has room for.
+    pub capacity: usize,
+    /// Whether the shape is shared between objects with the same layout, objects with a lot
+    /// of properties get a shape of their own.
+    pub hashed: bool,
+}
+
+/// An object in a [`HeapDump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectInfo {
+    /// The name of the class of the object, truncated to 63 bytes.
+    pub class: StdString,
+    /// The number of references to the object.
+    pub ref_count: usize,
+    /// The number of properties of the object's shape.
+    pub properties: usize,
+}
+
+/// A detailed listing of the atoms, shapes and objects of a runtime.
+///
+/// Returned by [`Runtime::heap_dump`](crate::Runtime::heap_dump), this contains the data QuickJS
+/// prints with its shape, atom and object dump flags. Unlike [`Diagnostics`] collecting it walks
+/// the whole heap, so it is meant for occasional inspection.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeapDump {
+    /// The atoms, including the predefined ones.
+    pub atoms: Vec<AtomInfo>,
+    /// The shapes.
+    pub shapes: Vec<ShapeInfo>,
+    /// The objects.
+    pub objects: Vec<ObjectInfo>,
+}
+
+impl HeapDump {
+    /// Returns the number of objects per class name.
+    pub fn objects_by_class(&self) -> BTreeMap<&str, usize> {
+        let mut classes = BTreeMap::new();
+        for object in &self.objects {
+            *classes.entry(object.class.as_str()).or_default() += 1;
+        }
+        classes
+    }
+
+    pub(crate) unsafe fn collect(rt: *mut qjs::JSRuntime) -> Self {
+        unsafe extern "C" fn atom(
+            opaque: *mut qjs::c_void,
+            name: *const qjs::c_char,
+            atom_type: qjs::c_int,
+            ref_count: qjs::c_int,
+        ) {
+            let kind = match atom_type {
+                2 => AtomKind::GlobalSymbol,
+                3 => AtomKind::Symbol,
+                4 => AtomKind::Private,
+                _ => AtomKind::String,
+            };
+            (*opaque.cast::<HeapDump>()).atoms.push(AtomInfo {
+                name: CStr::from_ptr(name).to_string_lossy().into_owned(),
+                kind,
+                ref_count: ref_count.max(0) as usize,
+            });
+        }
+
+        unsafe extern "C" fn shape(
+            opaque: *mut qjs::c_void,
+            ref_count: qjs::c_int,
+            prop_count: qjs::c_int,
+            prop_size: qjs::c_int,
+            is_hashed: qjs::c_int,
+        ) {
+            (*opaque.cast::<HeapDump>()).shapes.push(ShapeInfo {
+                ref_count: ref_count.max(0) as usize,
+                properties: prop_count.max(0) as usize,
+                capacity: prop_size.max(0) as usize,
+                hashed: is_hashed != 0,
+            });
+        }
+
+        unsafe extern "C" fn object(
+            opaque: *mut qjs::c_void,
+            class_name: *const qjs::c_char,
+            ref_count: qjs::c_int,
+            prop_count: qjs::c_int,
+        ) {
+            (*opaque.cast::<HeapDump>()).objects.push(ObjectInfo {
+                class: CStr::from_ptr(class_name).to_string_lossy().into_owned(),
+                ref_count: ref_count.max(0) as usize,
+                properties: prop_count.max(0) as usize,
+            });
+        }
+
+        let mut dump = HeapDump::default();
+        let opaque = (&mut dump as *mut HeapDump).cast();
+        qjs::JS_DumpAtomsTo(rt, Some(atom), opaque);
+        qjs::JS_DumpShapesTo(rt, Some(shape), opaque);
+        qjs::JS_DumpObjectsTo(rt, Some(object), opaque);
+        dump
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AtomKind;
+    use crate::{Context, Runtime};
+
+    #[test]
+    fn diagnostics() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let before = rt.diagnostics();
+        ctx.with(|ctx| {
+            let _: () = ctx
+                .eval(
+                    "globalThis.data = Array.from({ length: 100 }, (_, i) => ({ i, s: 'x' + i }))",
+                )
+                .unwrap();
+        });
+        let after = rt.diagnostics();
+        assert!(after.objects.count >= before.objects.count + 100);
+        assert!(after.arrays.fast_elements >= before.arrays.fast_elements + 100);
+        assert!(after.strings.count > before.strings.count);
+
+        // The memory limit is not used with custom allocators.
+        #[cfg(not(feature = "rust-alloc"))]
+        {
+            rt.set_memory_limit(1 << 30);
+            assert_eq!(rt.diagnostics().malloc_limit, Some(1 << 30));
+        }
+    }
+
+    #[test]
+    fn heap_dump() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let before = rt.heap_dump();
+        ctx.with(|ctx| {
+            let _: () = ctx
+                .eval(
+                    r#"
+                    globalThis.points = Array.from({ length: 10 }, (_, i) => ({ x: i, y: i }));
+                    globalThis.maps = [new Map(), new Map()];
+                    globalThis.sym = Symbol("rquickjs_dump_symbol");
+                    globalThis.rquickjs_dump_atom = 1;
+                    "#,
+                )
+                .unwrap();
+        });
+        let after = rt.heap_dump();
+
+        let classes = after.objects_by_class();
+        assert_eq!(
+            classes["Map"],
+            before.objects_by_class().get("Map").unwrap_or(&0) + 2
+        );
+        assert!(classes["Object"] >= 10);
+        assert!(after
+            .objects
+            .iter()
+            .any(|x| x.class == "Array" && x.properties >= 1));
+
+        // The points share a single shape with two properties.
+        assert!(after
+            .shapes
+            .iter()
+            .any(|x| x.hashed && x.properties == 2 && x.ref_count >= 10));
+
+        assert!(after
+            .atoms
+            .iter()
+            .any(|x| x.name == "rquickjs_dump_atom" && x.kind == AtomKind::String));
+        assert!(after
+            .atoms
+            .iter()
+            .any(|x| x.name == "rquickjs_dump_symbol" && x.kind == AtomKind::Symbol));
+    }
+}
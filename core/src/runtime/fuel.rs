@@ -0,0 +1,92 @@
+use std::{mem, os::raw::c_int};
+
+use crate::{qjs, Ctx};
+
+/// The amount of interrupt polls between interrupt handler calls QuickJS uses by default.
+const DEFAULT_INTERVAL: u64 = 10000;
+
+/// The fuel budget of an evaluation.
+pub(crate) struct Fuel {
+    /// The polls left including the ones of the current interval.
+    remaining: u64,
+    /// The polls between the previous and the next call of the interrupt handler.
+    interval: u64,
+    /// Whether the budget was used up.
+    pub exhausted: bool,
+}
+
+impl Fuel {
+    /// Called by the interrupt handler, returns whether the evaluation should be interrupted.
+    pub unsafe fn poll(&mut self, rt: *mut qjs::JSRuntime) -> bool {
+        if !self.exhausted {
+            self.remaining -= self.interval;
+            self.exhausted = self.remaining == 0;
+            self.interval = self.remaining.clamp(1, DEFAULT_INTERVAL);
+            qjs::JS_SetInterruptInterval(rt, self.interval as c_int);
+        }
+        self.exhausted
+    }
+}
+
+/// Restores the previous fuel state when metering ends, even if the metered closure panics.
+struct Metering<'a, 'js> {
+    ctx: &'a Ctx<'js>,
+    budget: u64,
+    prev: Option<Option<Fuel>>,
+    prev_interval: c_int,
+    prev_counter: c_int,
+}
+
+impl Metering<'_, '_> {
+    /// Stop metering, returns the consumed fuel.
+    unsafe fn finish(&mut self) -> u64 {
+        let Some(prev) = self.prev.take() else {
+            return 0;
+        };
+        let rt = qjs::JS_GetRuntime(self.ctx.as_ptr());
+        let opaque = &mut *self.ctx.get_opaque();
+        let fuel =
+            mem::replace(&mut opaque.fuel, prev).expect("fuel budget removed while metering");
+        let left = qjs::JS_SetInterruptCounter(self.ctx.as_ptr(), self.prev_counter);
+        qjs::JS_SetInterruptInterval(rt, self.prev_interval);
+        super::raw::update_interrupt_handler(rt);
+        let polled = fuel.interval.saturating_sub(left.max(0) as u64);
+        (self.budget - fuel.remaining + polled).min(self.budget - 1)
+    }
+}
+
+impl Drop for Metering<'_, '_> {
+    fn drop(&mut self) {
+        unsafe { self.finish() };
+    }
+}
+
+/// Run a closure with the given fuel budget, returns its result and the fuel it consumed.
+///
+/// One unit of fuel is consumed every time the interpreter polls for interrupts, which it does
+/// for every function call and every taken jump.
+pub(crate) fn metered<R>(ctx: &Ctx, fuel: u64, f: impl FnOnce() -> R) -> (R, u64) {
+    // The budget includes the poll which detects that the fuel ran out.
+    let budget = fuel.saturating_add(1);
+    let interval = budget.min(DEFAULT_INTERVAL);
+    let mut metering = unsafe {
+        let rt = qjs::JS_GetRuntime(ctx.as_ptr());
+        let opaque = &mut *ctx.get_opaque();
+        let prev = opaque.fuel.replace(Fuel {
+            remaining: budget,
+            interval,
+            exhausted: false,
+        });
+        super::raw::update_interrupt_handler(rt);
+        Metering {
+            ctx,
+            budget,
+            prev: Some(prev),
+            prev_interval: qjs::JS_SetInterruptInterval(rt, interval as c_int),
+            prev_counter: qjs::JS_SetInterruptCounter(ctx.as_ptr(), interval as c_int),
+        }
+    };
+    let res = f();
+    let consumed = unsafe { metering.finish() };
+    (res, consumed)
+}
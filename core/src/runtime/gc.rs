@@ -0,0 +1,128 @@
+use std::{os::raw::c_int, panic, time::Duration};
+
+use super::raw::Opaque;
+use crate::{clock::Instant, qjs};
+
+/// The statistics of a garbage collection cycle, reported to the handler set with
+/// [`Runtime::set_gc_handler`](crate::Runtime::set_gc_handler).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GcStats {
+    /// The wall time the cycle took.
+    pub duration: Duration,
+    /// The amount of bytes freed by the cycle.
+    pub freed: usize,
+    /// The amount of bytes allocated after the cycle.
+    pub heap_size: usize,
+}
+
+/// The type of the garbage collection handler.
+#[cfg(not(feature = "parallel"))]
+pub type GcHandler = Box<dyn FnMut(GcStats) + 'static>;
+/// The type of the garbage collection handler.
+#[cfg(feature = "parallel")]
+pub type GcHandler = Box<dyn FnMut(GcStats) + Send + 'static>;
+
+/// The garbage collection handler of a runtime and the state of the running cycle.
+pub(crate) struct GcMonitor {
+    handler: GcHandler,
+    start: Option<(Instant, usize)>,
+}
+
+/// Set the handler called after every garbage collection cycle, `None` to remove it.
+///
+/// # Safety
+/// The runtime must be valid and have an [`Opaque`].
+pub(crate) unsafe fn set_gc_handler(rt: *mut qjs::JSRuntime, handler: Option<GcHandler>) {
+    unsafe extern "C" fn gc_hook_trampoline(
+        _rt: *mut qjs::JSRuntime,
+        phase: c_int,
+        malloc_size: qjs::size_t,
+        opaque: *mut ::std::os::raw::c_void,
+    ) {
+        let opaque = &mut *(opaque as *mut Opaque);
+        let malloc_size = malloc_size as usize;
+        let Some(monitor) = opaque.gc_monitor.as_mut() else {
+            return;
+        };
+        if phase as u32 == qjs::JS_GC_PHASE_START {
+            monitor.start = Some((Instant::now(), malloc_size));
+            return;
+        }
+        let Some((start, size)) = monitor.start.take() else {
+            return;
+        };
+        let stats = GcStats {
+            duration: start.elapsed(),
+            freed: size.saturating_sub(malloc_size),
+            heap_size: malloc_size,
+        };
+        if let Err(panic) =
+            panic::catch_unwind(panic::AssertUnwindSafe(|| (monitor.handler)(stats)))
+        {
+            // The collection can't fail, the panic is continued by the next exception.
+            opaque.panic = Some(panic);
+        }
+    }
+
+    let opaque = qjs::JS_GetRuntimeOpaque(rt);
+    qjs::JS_SetGCHook(
+        rt,
+        handler.as_ref().map(|_| gc_hook_trampoline as _),
+        opaque,
+    );
+    (*(opaque as *mut Opaque)).gc_monitor = handler.map(|handler| GcMonitor {
+        handler,
+        start: None,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, Runtime};
+
+    #[test]
+    fn gc_handler() {
+        let rt = Runtime::new().unwrap();
+        rt.set_gc_threshold(1 << 30);
+        assert_eq!(rt.gc_threshold(), 1 << 30);
+
+        let cycles = Arc::new(Mutex::new(Vec::new()));
+        let cycles_clone = cycles.clone();
+        rt.set_gc_handler(Some(Box::new(move |stats| {
+            cycles_clone.lock().unwrap().push(stats);
+        })));
+
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                    for (let i = 0; i < 1000; i++) {
+                        const a = { data: new Array(100).fill(i) };
+                        const b = { a };
+                        a.b = b;
+                    }
+                "#,
+            )
+            .unwrap();
+        });
+        assert!(cycles.lock().unwrap().is_empty());
+        rt.run_gc();
+
+        {
+            let cycles = cycles.lock().unwrap();
+            assert_eq!(cycles.len(), 1);
+            // Custom allocators don't report the allocated memory to QuickJS.
+            #[cfg(not(feature = "allocator"))]
+            {
+                assert!(cycles[0].freed > 100_000);
+                assert!(cycles[0].heap_size > 0);
+            }
+        }
+
+        rt.set_gc_handler(None);
+        rt.run_gc();
+        assert_eq!(cycles.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// A handle of a [`Persistent`](crate::Persistent) value which is still alive, part of a
+/// [`LeakReport`].
+#[derive(Debug, Clone)]
+pub struct LeakedValue {
+    type_name: &'static str,
+    backtrace: Arc<Backtrace>,
+}
+
+impl LeakedValue {
+    /// The name of the type of the value, like `rquickjs_core::value::function::Function<'_>`.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The backtrace of the creation of the handle, by saving or cloning the persistent value.
+    pub fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+/// The persistent values of a runtime which are still alive, returned by
+/// [`Runtime::leak_report`](crate::Runtime::leak_report) and given to the handler set with
+/// [`Runtime::set_leak_handler`](crate::Runtime::set_leak_handler).
+///
+/// The values are ordered by creation, the report displays the amount of values of each type
+/// followed by the backtraces.
+#[derive(Debug, Clone, Default)]
+pub struct LeakReport {
+    values: Vec<LeakedValue>,
+}
+
+impl LeakReport {
+    /// The values which are still alive.
+    pub fn values(&self) -> &[LeakedValue] {
+        &self.values
+    }
+
+    /// Returns the amount of values which are still alive.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether no value is alive.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut types = BTreeMap::<&str, usize>::new();
+        for value in &self.values {
+            *types.entry(value.type_name).or_default() += 1;
+        }
+        let plural = if self.values.len() == 1 { "" } else { "s" };
+        writeln!(
+            f,
+            "{} persistent value{} still alive:",
+            self.values.len(),
+            plural
+        )?;
+        for (name, count) in types {
+            writeln!(f, "  {} {}", count, name)?;
+        }
+        for (index, value) in self.values.iter().enumerate() {
+            writeln!(f, "#{} {} created at:", index, value.type_name)?;
+            writeln!(f, "{}", value.backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// The type of the leak handler.
+#[cfg(not(feature = "parallel"))]
+pub type LeakHandler = Box<dyn FnOnce(LeakReport) + 'static>;
+/// The type of the leak handler.
+#[cfg(feature = "parallel")]
+pub type LeakHandler = Box<dyn FnOnce(LeakReport) + Send + 'static>;
+
+#[derive(Default)]
+struct Handles {
+    next: u64,
+    values: BTreeMap<u64, LeakedValue>,
+}
+
+/// The handles of persistent values alive in a runtime.
+///
+/// It is shared with the handles, so they can be dropped after the runtime without touching it.
+#[derive(Default)]
+pub(crate) struct LeakRegistry(Mutex<Handles>);
+
+impl LeakRegistry {
+    fn handles(&self) -> std::sync::MutexGuard<'_, Handles> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn report(&self) -> LeakReport {
+        LeakReport {
+            values: self.handles().values.values().cloned().collect(),
+        }
+    }
+}
+
+/// Registers a handle of a persistent value for as long as it is alive.
+pub(crate) struct Tracked {
+    registry: Arc<LeakRegistry>,
+    type_name: &'static str,
+    id: u64,
+}
+
+impl Tracked {
+    pub fn new(registry: Arc<LeakRegistry>, type_name: &'static str) -> Self {
+        let value = LeakedValue {
+            type_name,
+            backtrace: Arc::new(Backtrace::force_capture()),
+        };
+        let id = {
+            let mut handles = registry.handles();
+            let id = handles.next;
+            handles.next += 1;
+            handles.values.insert(id, value);
+            id
+        };
+        Tracked {
+            registry,
+            type_name,
+            id,
+        }
+    }
+}
+
+impl Clone for Tracked {
+    fn clone(&self) -> Self {
+        Tracked::new(self.registry.clone(), self.type_name)
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.registry.handles().values.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{Context, Function, Persistent, Runtime};
+
+    #[test]
+    fn leak_report() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let func = ctx.with(|ctx| {
+            let func: Function = ctx.eval("() => 1").unwrap();
+            Persistent::save(&ctx, func)
+        });
+        let copy = func.clone();
+        let report = rt.leak_report();
+        assert_eq!(report.len(), 2);
+        assert!(report.values()[0].type_name().ends_with("Function<'_>"));
+        let text = report.to_string();
+        assert!(
+            text.starts_with("2 persistent values still alive:\n"),
+            "{}",
+            text
+        );
+        assert!(text.contains("#1 "), "{}", text);
+
+        drop(copy);
+        ctx.with(|ctx| {
+            let func = func.restore(&ctx).unwrap();
+            assert_eq!(func.call::<_, i32>(()).unwrap(), 1);
+        });
+        assert!(rt.leak_report().is_empty());
+    }
+
+    #[test]
+    fn leak_handler() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let run = |leak: bool| {
+            let rt = Runtime::new().unwrap();
+            let reports = reports.clone();
+            rt.set_leak_handler(Some(Box::new(move |report| {
+                reports.lock().unwrap().push(report);
+            })));
+            let ctx = Context::full(&rt).unwrap();
+            let number = ctx.with(|ctx| Persistent::save(&ctx, 42i32));
+            drop(ctx);
+            if leak {
+                drop(rt);
+                // Dropping it after the runtime isn't allowed.
+                std::mem::forget(number);
+            } else {
+                drop(number);
+            }
+        };
+        run(false);
+        assert!(reports.lock().unwrap().is_empty());
+        run(true);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].values()[0].type_name(), "i32");
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use super::raw::Opaque;
+use crate::clock::Instant;
+
+/// The kind of operation reported to a profiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProfileKind {
+    /// A call from JavaScript to a native Rust function.
+    NativeCall,
+    /// Evaluation of a script.
+    Eval,
+}
+
+/// An event reported to the profiler set with
+/// [`Runtime::set_profiler`](crate::Runtime::set_profiler).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileEvent<'a> {
+    /// The operation is about to start.
+    Enter {
+        kind: ProfileKind,
+        /// The name of the native function or the file name of the script.
+        name: &'a str,
+    },
+    /// The operation finished.
+    Exit {
+        kind: ProfileKind,
+        /// The name of the native function or the file name of the script.
+        name: &'a str,
+        /// The wall time spent in the operation, including nested operations.
+        elapsed: Duration,
+        /// Whether the operation finished with an exception.
+        exception: bool,
+    },
+}
+
+/// The type of the profile handler.
+#[cfg(not(feature = "parallel"))]
+pub type ProfileHandler = Box<dyn FnMut(ProfileEvent) + 'static>;
+/// The type of the profile handler.
+#[cfg(feature = "parallel")]
+pub type ProfileHandler = Box<dyn FnMut(ProfileEvent) + Send + 'static>;
+
+/// Run an operation, reporting it to the profiler of the runtime if there is one.
+///
+/// # Safety
+/// The opaque must be valid and the runtime lock must be held.
+pub(crate) unsafe fn profile<R, N, F, E>(
+    opaque: *mut Opaque,
+    kind: ProfileKind,
+    name: N,
+    f: F,
+    exception: E,
+) -> R
+where
+    N: FnOnce() -> String,
+    F: FnOnce() -> R,
+    E: FnOnce(&R) -> bool,
+{
+    // The profiler is not borrowed while running the operation as nested operations report to
+    // the profiler too.
+    if (*opaque).profiler.is_none() {
+        return f();
+    }
+    let name = name();
+    if let Some(profiler) = (*opaque).profiler.as_mut() {
+        profiler(ProfileEvent::Enter { kind, name: &name });
+    }
+    let start = Instant::now();
+    let res = f();
+    let elapsed = start.elapsed();
+    let exception = exception(&res);
+    if let Some(profiler) = (*opaque).profiler.as_mut() {
+        profiler(ProfileEvent::Exit {
+            kind,
+            name: &name,
+            elapsed,
+            exception,
+        });
+    }
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{
+        runtime::{ProfileEvent, ProfileKind},
+        Context, Function, Runtime,
+    };
+
+    #[test]
+    fn profile_calls() {
+        let rt = Runtime::new().unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        rt.set_profiler(Some(Box::new(move |event| {
+            if let ProfileEvent::Exit {
+                kind,
+                name,
+                exception,
+                ..
+            } = event
+            {
+                events_clone
+                    .lock()
+                    .unwrap()
+                    .push((kind, name.to_string(), exception));
+            }
+        })));
+
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let func = Function::new(ctx.clone(), |a: i32| a * 2)
+                .unwrap()
+                .with_name("double")
+                .unwrap();
+            ctx.globals().set("double", func).unwrap();
+            let _: i32 = ctx
+                .eval_with_options(
+                    "double(2)",
+                    crate::context::EvalOptions::default().filename("main.js"),
+                )
+                .unwrap();
+            let _ = ctx.eval::<(), _>("double('a')");
+
+            // The name getter is not invoked while profiling.
+            let res: i32 = ctx
+                .eval(
+                    r#"
+                    let calls = 0;
+                    Object.defineProperty(double, "name", { get() { calls++; return "x" } });
+                    double(1);
+                    calls
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(res, 0);
+        });
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events[..2],
+            [
+                (ProfileKind::NativeCall, "double".to_string(), false),
+                (ProfileKind::Eval, "main.js".to_string(), false),
+            ]
+        );
+        assert_eq!(
+            events[2..4],
+            [
+                (ProfileKind::NativeCall, "double".to_string(), true),
+                (ProfileKind::Eval, "eval_script".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            events[4..],
+            [
+                (ProfileKind::NativeCall, "".to_string(), false),
+                (ProfileKind::Eval, "eval_script".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "profiler panicked")]
+    fn panicking_profiler() {
+        let rt = Runtime::new().unwrap();
+        rt.set_profiler(Some(Box::new(|event| {
+            if let ProfileEvent::Enter {
+                kind: ProfileKind::NativeCall,
+                ..
+            } = event
+            {
+                panic!("profiler panicked");
+            }
+        })));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let func = Function::new(ctx.clone(), || 1).unwrap();
+            ctx.globals().set("one", func).unwrap();
+            // The panic continues once the call returns to Rust instead of aborting.
+            let _ = ctx.eval::<i32, _>("one()");
+        });
+    }
+}
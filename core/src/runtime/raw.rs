@@ -1,17 +1,31 @@
 use std::{
-    any::Any, ffi::CString, marker::PhantomData, mem, panic, ptr::NonNull,
+    any::{Any, TypeId},
+    collections::HashMap,
+    ffi::CString,
+    marker::PhantomData,
+    mem, panic,
+    ptr::NonNull,
     result::Result as StdResult,
 };
 
 #[cfg(feature = "allocator")]
 use crate::allocator::{Allocator, AllocatorHolder};
 #[cfg(feature = "loader")]
-use crate::loader::{LoaderHolder, RawLoader, Resolver};
-use crate::qjs;
+use crate::{
+    loader::{LoaderHolder, RawLoader, Resolver},
+    Error, Result,
+};
+use crate::{persistent::HoldingLock, qjs};
+#[cfg(feature = "loader")]
+use std::collections::HashSet;
 
 #[cfg(feature = "futures")]
 use super::spawner::Spawner;
-use super::InterruptHandler;
+use super::{
+    fuel::Fuel,
+    gc::{self, GcHandler, GcMonitor},
+    CancellationToken, Clock, CompileCache, HeapDump, InterruptHandler, ProfileHandler,
+};
 
 /// Opaque book keeping data for Rust.
 pub(crate) struct Opaque<'js> {
@@ -21,9 +35,73 @@ pub(crate) struct Opaque<'js> {
     /// The user provided interrupt handler, if any.
     pub interrupt_handler: Option<InterruptHandler>,
 
+    /// The cancellation token of the runtime, if one was requested.
+    pub cancellation: Option<CancellationToken>,
+
+    /// The fuel budget of the running metered evaluation, if any.
+    pub fuel: Option<Fuel>,
+
+    /// The user provided profiler, if any.
+    pub profiler: Option<ProfileHandler>,
+
+    /// The user provided clock for `Date`, if any.
+    pub clock: Option<Clock>,
+
+    /// The user provided garbage collection handler, if any.
+    pub gc_monitor: Option<GcMonitor>,
+
+    /// The user provided compile cache, if any.
+    pub compile_cache: Option<Box<dyn CompileCache>>,
+
+    /// Whether exceptions passing through native functions get a frame for them in their stack.
+    pub native_stack_frames: bool,
+
+    /// The amount of Rust class finalizers currently running.
+    pub finalizing: usize,
+
+    /// The amount of native functions currently running.
+    pub native_depth: usize,
+
+    /// The maximum amount of nested native function calls, if limited.
+    pub max_native_recursion: Option<usize>,
+
+    /// User data stored in the runtime, keyed by type.
+    pub userdata: HashMap<TypeId, Box<dyn Any>>,
+
+    /// The classes registered in the runtime.
+    pub classes: Vec<crate::class::RegisteredClass>,
+
+    /// Source maps registered for generated files.
+    #[cfg(feature = "source-map")]
+    pub source_maps: crate::source_map::SourceMaps,
+
+    /// The collected coverage, if coverage is enabled.
+    #[cfg(feature = "coverage")]
+    pub coverage: Option<crate::coverage::Collector>,
+
+    /// The handles of the persistent values which are alive.
+    #[cfg(feature = "leak-report")]
+    pub leaks: std::sync::Arc<super::leak::LeakRegistry>,
+
+    /// The user provided handler for persistent values alive when the runtime is dropped.
+    #[cfg(feature = "leak-report")]
+    pub leak_handler: Option<super::leak::LeakHandler>,
+
+    /// The attached debugger, if any.
+    #[cfg(feature = "debug")]
+    pub debugger: Option<crate::debugger::Debugger>,
+
+    /// The async module loader, if any.
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    pub async_loader: Option<std::sync::Arc<crate::loader::AsyncModules>>,
+
     #[cfg(feature = "futures")]
     pub spawner: Option<Spawner<'js>>,
 
+    /// Persistent values dropped without holding the runtime lock.
+    #[cfg(feature = "parallel")]
+    pub pending_drops: std::sync::Mutex<Vec<crate::persistent::PendingDrop>>,
+
     _marker: PhantomData<&'js ()>,
 }
 
@@ -32,8 +110,34 @@ impl<'js> Opaque<'js> {
         Opaque {
             panic: None,
             interrupt_handler: None,
+            cancellation: None,
+            fuel: None,
+            profiler: None,
+            clock: None,
+            gc_monitor: None,
+            compile_cache: None,
+            native_stack_frames: false,
+            finalizing: 0,
+            native_depth: 0,
+            max_native_recursion: None,
+            userdata: HashMap::new(),
+            classes: Vec::new(),
+            #[cfg(feature = "source-map")]
+            source_maps: Default::default(),
+            #[cfg(feature = "coverage")]
+            coverage: None,
+            #[cfg(feature = "leak-report")]
+            leaks: Default::default(),
+            #[cfg(feature = "leak-report")]
+            leak_handler: None,
+            #[cfg(feature = "debug")]
+            debugger: None,
+            #[cfg(all(feature = "loader", feature = "futures"))]
+            async_loader: None,
             #[cfg(feature = "futures")]
             spawner: None,
+            #[cfg(feature = "parallel")]
+            pending_drops: std::sync::Mutex::new(Vec::new()),
             _marker: PhantomData,
         }
     }
@@ -43,8 +147,34 @@ impl<'js> Opaque<'js> {
         Opaque {
             panic: None,
             interrupt_handler: None,
+            cancellation: None,
+            fuel: None,
+            profiler: None,
+            clock: None,
+            gc_monitor: None,
+            compile_cache: None,
+            native_stack_frames: false,
+            finalizing: 0,
+            native_depth: 0,
+            max_native_recursion: None,
+            userdata: HashMap::new(),
+            classes: Vec::new(),
+            #[cfg(feature = "source-map")]
+            source_maps: Default::default(),
+            #[cfg(feature = "coverage")]
+            coverage: None,
+            #[cfg(feature = "leak-report")]
+            leaks: Default::default(),
+            #[cfg(feature = "leak-report")]
+            leak_handler: None,
+            #[cfg(feature = "debug")]
+            debugger: None,
+            #[cfg(all(feature = "loader", feature = "futures"))]
+            async_loader: None,
             #[cfg(feature = "futures")]
             spawner: Some(Spawner::new()),
+            #[cfg(feature = "parallel")]
+            pending_drops: std::sync::Mutex::new(Vec::new()),
             _marker: PhantomData,
         }
     }
@@ -79,10 +209,23 @@ unsafe impl Send for RawRuntime {}
 impl Drop for RawRuntime {
     fn drop(&mut self) {
         unsafe {
-            let ptr = qjs::JS_GetRuntimeOpaque(self.rt.as_ptr());
-            let opaque: Box<Opaque> = Box::from_raw(ptr as *mut _);
+            let ptr = qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()).cast::<Opaque>();
+            // Free the JavaScript values held by the opaque while the runtime is still alive.
+            (*ptr).interrupt_handler = None;
+            (*ptr).clock = None;
+            gc::set_gc_handler(self.rt.as_ptr(), None);
+            (*ptr).userdata.clear();
+            #[cfg(feature = "futures")]
+            {
+                (*ptr).spawner = None;
+            }
+            self.drop_pending();
+            #[cfg(feature = "leak-report")]
+            self.report_leaks();
+            qjs::JS_FreeRuntime(self.rt.as_ptr());
+            // Finalizers run while freeing the runtime still use the opaque.
+            let opaque: Box<Opaque> = Box::from_raw(ptr);
             mem::drop(opaque);
-            qjs::JS_FreeRuntime(self.rt.as_ptr())
         }
     }
 }
@@ -103,6 +246,13 @@ impl RawRuntime {
 
         let opaque = Box::into_raw(Box::new(opaque));
         unsafe { qjs::JS_SetRuntimeOpaque(rt.as_ptr(), opaque as *mut _) };
+        #[cfg(feature = "shared-array-buffer")]
+        unsafe {
+            qjs::JS_SetSharedArrayBufferFunctions(
+                rt.as_ptr(),
+                &crate::shared_array_buffer::SharedMemory::FUNCTIONS,
+            )
+        };
 
         Some(RawRuntime {
             rt,
@@ -128,6 +278,13 @@ impl RawRuntime {
 
         let opaque = Box::into_raw(Box::new(opaque));
         unsafe { qjs::JS_SetRuntimeOpaque(rt.as_ptr(), opaque as *mut _) };
+        #[cfg(feature = "shared-array-buffer")]
+        unsafe {
+            qjs::JS_SetSharedArrayBufferFunctions(
+                rt.as_ptr(),
+                &crate::shared_array_buffer::SharedMemory::FUNCTIONS,
+            )
+        };
 
         Some(RawRuntime {
             rt,
@@ -143,6 +300,34 @@ impl RawRuntime {
         unsafe {
             qjs::JS_UpdateStackTop(self.rt.as_ptr());
         }
+        self.drop_pending();
+    }
+
+    /// Drop the persistent values which were dropped without holding the runtime lock.
+    ///
+    /// Must only be called while holding the runtime lock.
+    pub fn drop_pending(&self) {
+        #[cfg(feature = "parallel")]
+        unsafe {
+            let opaque = qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()).cast::<Opaque>();
+            loop {
+                // Dropping a value might cause more values to be dropped so don't hold the lock.
+                let pending = mem::take(
+                    &mut *(*opaque)
+                        .pending_drops
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner()),
+                );
+                if pending.is_empty() {
+                    break;
+                }
+                pending.into_iter().for_each(|x| x.run());
+            }
+        }
+    }
+
+    pub unsafe fn get_opaque<'js>(&self) -> &Opaque<'js> {
+        &*(qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()) as *const _)
     }
 
     pub unsafe fn get_opaque_mut<'js>(&mut self) -> &mut Opaque<'js> {
@@ -156,6 +341,7 @@ impl RawRuntime {
     pub fn execute_pending_job(&mut self) -> StdResult<bool, *mut qjs::JSContext> {
         let mut ctx_ptr = mem::MaybeUninit::<*mut qjs::JSContext>::uninit();
         self.update_stack_top();
+        let _holding = HoldingLock::new(self.rt.as_ptr());
         let result = unsafe { qjs::JS_ExecutePendingJob(self.rt.as_ptr(), ctx_ptr.as_mut_ptr()) };
         if result == 0 {
             // no jobs executed
@@ -177,6 +363,42 @@ impl RawRuntime {
         let loader = LoaderHolder::new(resolver, loader);
         loader.set_to_runtime(self.rt.as_ptr());
         self.loader = Some(loader);
+        #[cfg(feature = "futures")]
+        {
+            self.get_opaque_mut().async_loader = None;
+        }
+    }
+
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub unsafe fn set_async_loader(&mut self, modules: crate::loader::AsyncModules) {
+        let modules = std::sync::Arc::new(modules);
+        self.set_loader(modules.prefetched(), modules.prefetched());
+        self.get_opaque_mut().async_loader = Some(modules);
+    }
+
+    #[cfg(feature = "loader")]
+    pub unsafe fn invalidate_module(&mut self, name: &str) -> Result<Vec<String>> {
+        let mut pending = vec![name.to_owned()];
+        let mut visited = HashSet::new();
+        let mut invalidated = Vec::new();
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            let c_name = CString::new(name.as_str())?;
+            let count = qjs::JS_InvalidateModule(self.rt.as_ptr(), c_name.as_ptr());
+            if count < 0 {
+                return Err(Error::Allocation);
+            }
+            if let Some(loader) = &self.loader {
+                pending.extend(loader.take_importers(&name));
+            }
+            if count > 0 {
+                invalidated.push(name);
+            }
+        }
+        Ok(invalidated)
     }
 
     /// Set the info of the runtime
@@ -185,6 +407,132 @@ impl RawRuntime {
         self.info = Some(info);
     }
 
+    /// Set a closure which is called before and after native function calls and evaluations.
+    pub unsafe fn set_profiler(&mut self, profiler: Option<ProfileHandler>) {
+        self.get_opaque_mut().profiler = profiler;
+    }
+
+    /// Set the handler for the persistent values alive when the runtime is dropped.
+    #[cfg(feature = "leak-report")]
+    pub unsafe fn set_leak_handler(&mut self, handler: Option<super::leak::LeakHandler>) {
+        self.get_opaque_mut().leak_handler = handler;
+    }
+
+    /// Returns the persistent values which are alive.
+    #[cfg(feature = "leak-report")]
+    pub unsafe fn leak_report(&self) -> super::leak::LeakReport {
+        self.get_opaque().leaks.report()
+    }
+
+    /// Report the persistent values still alive, before freeing the runtime aborts on them.
+    #[cfg(feature = "leak-report")]
+    unsafe fn report_leaks(&mut self) {
+        // Values only referenced by unreachable cycles, like closures referencing each other,
+        // release their persistent values when they are collected.
+        qjs::JS_RunGC(self.rt.as_ptr());
+        let opaque = self.get_opaque_mut();
+        let report = opaque.leaks.report();
+        let handler = opaque.leak_handler.take();
+        if report.is_empty() {
+            return;
+        }
+        match handler {
+            Some(handler) => handler(report),
+            None => eprintln!("rquickjs: runtime dropped while {}", report),
+        }
+    }
+
+    /// Set the cache for the bytecode of evaluated scripts, `None` to always parse them.
+    pub unsafe fn set_compile_cache(&mut self, cache: Option<Box<dyn CompileCache>>) {
+        self.get_opaque_mut().compile_cache = cache;
+    }
+
+    /// Set the clock used by `Date`, `None` to use the system clock.
+    pub unsafe fn set_clock(&mut self, clock: Option<Clock>) {
+        unsafe extern "C" fn clock_trampoline(opaque: *mut ::std::os::raw::c_void) -> i64 {
+            let opaque = &mut *(opaque as *mut Opaque);
+            match panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                opaque
+                    .clock
+                    .as_mut()
+                    .map(|clock| clock())
+                    .unwrap_or_default()
+            })) {
+                Ok(millis) => millis as i64,
+                Err(panic) => {
+                    // The time can't fail, the panic is continued by the next exception.
+                    opaque.panic = Some(panic);
+                    0
+                }
+            }
+        }
+
+        qjs::JS_SetDateNowFunc(
+            self.rt.as_ptr(),
+            clock.as_ref().map(|_| clock_trampoline as _),
+            qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()),
+        );
+        self.get_opaque_mut().clock = clock;
+    }
+
+    /// Set the seed of `Math.random` in contexts created afterwards.
+    pub unsafe fn set_random_seed(&mut self, seed: Option<u64>) {
+        qjs::JS_SetRandomSeed(
+            self.rt.as_ptr(),
+            seed.is_some() as _,
+            seed.unwrap_or_default(),
+        );
+    }
+
+    /// Enable or disable adding frames for native functions to the stack of exceptions.
+    pub unsafe fn set_native_stack_frames(&mut self, enabled: bool) {
+        self.get_opaque_mut().native_stack_frames = enabled;
+    }
+
+    /// Set the maximum amount of nested native function calls, `None` for no limit.
+    pub unsafe fn set_max_native_recursion(&mut self, depth: Option<usize>) {
+        self.get_opaque_mut().max_native_recursion = depth;
+    }
+
+    /// Start instrumenting evaluated sources for coverage.
+    #[cfg(feature = "coverage")]
+    pub unsafe fn enable_coverage(&mut self) {
+        self.get_opaque_mut()
+            .coverage
+            .get_or_insert_with(Default::default);
+    }
+
+    /// Returns the collected coverage, `None` if coverage is not enabled.
+    #[cfg(feature = "coverage")]
+    pub unsafe fn coverage(&mut self) -> Option<crate::coverage::Coverage> {
+        self.get_opaque_mut()
+            .coverage
+            .as_ref()
+            .map(|x| x.coverage())
+    }
+
+    /// Store user data in the runtime, returning the previous value of the same type.
+    ///
+    /// # Safety
+    /// No references to user data of the same type may be held.
+    pub unsafe fn set_userdata<T: 'static>(&mut self, value: T) -> Option<T> {
+        self.get_opaque_mut()
+            .userdata
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .map(|x| *x.downcast().unwrap())
+    }
+
+    /// Remove user data from the runtime.
+    ///
+    /// # Safety
+    /// No references to user data of the same type may be held.
+    pub unsafe fn remove_userdata<T: 'static>(&mut self) -> Option<T> {
+        self.get_opaque_mut()
+            .userdata
+            .remove(&TypeId::of::<T>())
+            .map(|x| *x.downcast().unwrap())
+    }
+
     /// Set a limit on the max amount of memory the runtime will use.
     ///
     /// Setting the limit to 0 is equivalent to unlimited memory.
@@ -202,11 +550,26 @@ impl RawRuntime {
         qjs::JS_SetMaxStackSize(self.rt.as_ptr(), limit as _);
     }
 
+    /// Set whether `Atomics.wait` is allowed to block.
+    pub unsafe fn set_can_block(&mut self, can_block: bool) {
+        qjs::JS_SetCanBlock(self.rt.as_ptr(), can_block as _);
+    }
+
     /// Set a memory threshold for garbage collection.
     pub unsafe fn set_gc_threshold(&self, threshold: usize) {
         qjs::JS_SetGCThreshold(self.rt.as_ptr(), threshold as _);
     }
 
+    /// Get the memory threshold for garbage collection.
+    pub unsafe fn gc_threshold(&self) -> usize {
+        qjs::JS_GetGCThreshold(self.rt.as_ptr()) as _
+    }
+
+    /// Set a closure which is called after every garbage collection cycle.
+    pub unsafe fn set_gc_handler(&mut self, handler: Option<GcHandler>) {
+        gc::set_gc_handler(self.rt.as_ptr(), handler);
+    }
+
     /// Manually run the garbage collection.
     ///
     /// Most of QuickJS values are reference counted and
@@ -214,7 +577,12 @@ impl RawRuntime {
     /// references. The garbage collector is only for collecting
     /// cyclic references.
     pub unsafe fn run_gc(&mut self) {
+        self.drop_pending();
+        let _holding = HoldingLock::new(self.rt.as_ptr());
         qjs::JS_RunGC(self.rt.as_ptr());
+        if let Some(panic) = self.get_opaque_mut().panic.take() {
+            panic::resume_unwind(panic);
+        }
     }
 
     /// Get memory usage stats
@@ -224,36 +592,117 @@ impl RawRuntime {
         stats.assume_init()
     }
 
+    pub unsafe fn heap_dump(&mut self) -> HeapDump {
+        HeapDump::collect(self.rt.as_ptr())
+    }
+
     /// Set a closure which is regularly called by the engine when it is executing code.
     /// If the provided closure returns `true` the interpreter will raise and uncatchable
     /// exception and return control flow to the caller.
     pub unsafe fn set_interrupt_handler(&mut self, handler: Option<InterruptHandler>) {
-        unsafe extern "C" fn interrupt_handler_trampoline(
-            _rt: *mut qjs::JSRuntime,
+        self.get_opaque_mut().interrupt_handler = handler;
+        update_interrupt_handler(self.rt.as_ptr());
+    }
+
+    /// Returns the cancellation token of the runtime, creating it if necessary.
+    pub unsafe fn cancellation_token(&mut self) -> CancellationToken {
+        let token = self
+            .get_opaque_mut()
+            .cancellation
+            .get_or_insert_with(CancellationToken::new)
+            .clone();
+        update_interrupt_handler(self.rt.as_ptr());
+        token
+    }
+
+    /// Attach a debugger, which is called whenever a function reaches a new line.
+    #[cfg(feature = "debug")]
+    pub unsafe fn set_debugger(&mut self, debugger: Option<crate::debugger::Debugger>) {
+        unsafe extern "C" fn debug_hook_trampoline(
+            ctx: *mut qjs::JSContext,
             opaque: *mut ::std::os::raw::c_void,
+            filename: qjs::JSAtom,
+            line: ::std::os::raw::c_int,
         ) -> ::std::os::raw::c_int {
             let should_interrupt = match panic::catch_unwind(move || {
-                let opaque = &mut *(opaque as *mut Opaque);
-                opaque.interrupt_handler.as_mut().expect("handler is set")()
+                let opaque = opaque as *mut Opaque;
+                // The debugger is taken while it runs, so the code it evaluates while the
+                // runtime is paused doesn't reach it again.
+                let Some(debugger) = (*opaque).debugger.take() else {
+                    return false;
+                };
+                let should_interrupt =
+                    debugger.hook(&crate::Ctx::from_ptr(ctx), filename, line as u32);
+                (*opaque).debugger = Some(debugger);
+                should_interrupt
             }) {
                 Ok(should_interrupt) => should_interrupt,
                 Err(panic) => {
                     let opaque = &mut *(opaque as *mut Opaque);
                     opaque.panic = Some(panic);
-                    // Returning true here will cause the interpreter to raise an un-catchable exception.
-                    // The Rust code that is running the interpreter will see that exception and continue
-                    // the panic handling. See crate::result::{handle_exception, handle_panic} for details.
                     true
                 }
             };
             should_interrupt as _
         }
 
-        qjs::JS_SetInterruptHandler(
+        qjs::JS_SetDebugHook(
             self.rt.as_ptr(),
-            handler.as_ref().map(|_| interrupt_handler_trampoline as _),
+            debugger.as_ref().map(|_| debug_hook_trampoline as _),
             qjs::JS_GetRuntimeOpaque(self.rt.as_ptr()),
         );
-        self.get_opaque_mut().interrupt_handler = handler;
+        self.get_opaque_mut().debugger = debugger;
     }
 }
+
+/// Install the interrupt handler if anything needs to interrupt the interpreter.
+pub(crate) unsafe fn update_interrupt_handler(rt: *mut qjs::JSRuntime) {
+    unsafe extern "C" fn interrupt_handler_trampoline(
+        rt: *mut qjs::JSRuntime,
+        opaque: *mut ::std::os::raw::c_void,
+    ) -> ::std::os::raw::c_int {
+        let should_interrupt = match panic::catch_unwind(move || {
+            let opaque = &mut *(opaque as *mut Opaque);
+            if let Some(fuel) = opaque.fuel.as_mut() {
+                if fuel.poll(rt) {
+                    return true;
+                }
+            }
+            if opaque
+                .cancellation
+                .as_ref()
+                .map(CancellationToken::is_cancelled)
+                .unwrap_or(false)
+            {
+                return true;
+            }
+            opaque
+                .interrupt_handler
+                .as_mut()
+                .map(|handler| handler())
+                .unwrap_or(false)
+        }) {
+            Ok(should_interrupt) => should_interrupt,
+            Err(panic) => {
+                let opaque = &mut *(opaque as *mut Opaque);
+                opaque.panic = Some(panic);
+                // Returning true here will cause the interpreter to raise an un-catchable exception.
+                // The Rust code that is running the interpreter will see that exception and continue
+                // the panic handling. See crate::result::{handle_exception, handle_panic} for details.
+                true
+            }
+        };
+        should_interrupt as _
+    }
+
+    let opaque = qjs::JS_GetRuntimeOpaque(rt);
+    let needed = {
+        let opaque = &*(opaque as *const Opaque);
+        opaque.interrupt_handler.is_some() || opaque.cancellation.is_some() || opaque.fuel.is_some()
+    };
+    qjs::JS_SetInterruptHandler(
+        rt,
+        needed.then_some(interrupt_handler_trampoline as _),
+        opaque,
+    );
+}
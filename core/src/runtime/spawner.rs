@@ -2,23 +2,30 @@ use std::{
     cell::RefCell,
     future::Future,
     pin::{pin, Pin},
+    ptr::NonNull,
     task::ready,
     task::{Poll, Waker},
 };
 
 use async_lock::futures::LockArc;
 
-use crate::AsyncRuntime;
+use crate::{persistent::HoldingLock, qjs, AsyncRuntime};
 
 use super::{AsyncWeakRuntime, InnerRuntime};
 
 type FuturesVec<T> = RefCell<Vec<Option<T>>>;
 
+/// A future spawned from a context.
+struct Spawned<'js> {
+    ctx: NonNull<qjs::JSContext>,
+    future: Pin<Box<dyn Future<Output = ()> + 'js>>,
+}
+
 /// A structure to hold futures spawned inside the runtime.
 ///
 /// TODO: change future lookup in poll from O(n) to O(1).
 pub struct Spawner<'js> {
-    futures: FuturesVec<Pin<Box<dyn Future<Output = ()> + 'js>>>,
+    futures: FuturesVec<Spawned<'js>>,
     wakeup: Vec<Waker>,
 }
 
@@ -30,12 +37,28 @@ impl<'js> Spawner<'js> {
         }
     }
 
-    pub fn push<F>(&mut self, f: F)
+    pub fn push<F>(&mut self, ctx: NonNull<qjs::JSContext>, f: F)
     where
         F: Future<Output = ()> + 'js,
     {
         self.wakeup.drain(..).for_each(Waker::wake);
-        self.futures.borrow_mut().push(Some(Box::pin(f)))
+        self.futures.borrow_mut().push(Some(Spawned {
+            ctx,
+            future: Box::pin(f),
+        }))
+    }
+
+    /// Removes the futures spawned from the given context.
+    ///
+    /// The futures are returned instead of dropped, as dropping them might use the spawner.
+    pub fn cancel(&self, ctx: NonNull<qjs::JSContext>) -> Vec<impl Future<Output = ()> + 'js> {
+        let mut cancelled = Vec::new();
+        for slot in self.futures.borrow_mut().iter_mut() {
+            if slot.as_ref().map(|x| x.ctx == ctx).unwrap_or(false) {
+                cancelled.extend(slot.take().map(|x| x.future));
+            }
+        }
+        cancelled
     }
 
     pub fn listen(&mut self, wake: Waker) {
@@ -48,7 +71,7 @@ impl<'js> Spawner<'js> {
     }
 
     pub fn is_empty(&mut self) -> bool {
-        self.futures.borrow().is_empty()
+        self.futures.borrow().iter().all(Option::is_none)
     }
 }
 
@@ -64,15 +87,25 @@ impl<'a, 'js> Future for SpawnFuture<'a, 'js> {
     type Output = bool;
 
     fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
-        if self.0.futures.borrow().is_empty() {
+        if self.0.futures.borrow().iter().all(Option::is_none) {
+            self.0.futures.borrow_mut().clear();
             return Poll::Ready(false);
         }
 
         let mut i = 0;
         let mut did_complete = false;
         while i < self.0.futures.borrow().len() {
-            let mut borrow = self.0.futures.borrow_mut()[i].take().unwrap();
-            if borrow.as_mut().poll(cx).is_pending() {
+            // The slot is empty if the future was cancelled while polling an other one.
+            let Some(mut borrow) = self.0.futures.borrow_mut()[i].take() else {
+                did_complete = true;
+                i += 1;
+                continue;
+            };
+            let pending = {
+                let _holding = HoldingLock::new(unsafe { qjs::JS_GetRuntime(borrow.ctx.as_ptr()) });
+                borrow.future.as_mut().poll(cx).is_pending()
+            };
+            if pending {
                 // put back.
                 self.0.futures.borrow_mut()[i] = Some(borrow);
             } else {
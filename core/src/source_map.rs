@@ -0,0 +1,521 @@
+//! Source map support for rewriting error stacks.
+//!
+//! Most code evaluated by QuickJS is bundled or transpiled, which makes the locations in error
+//! stacks hard to read. Source maps registered with [`Ctx::register_source_map`] are used to
+//! rewrite the stack of exceptions, as returned by [`Exception::mapped_stack`], to the original
+//! locations.
+//!
+//! Source maps included inline in a script or module, with a
+//! `//# sourceMappingURL=data:...;base64,...` comment, are linked automatically when the source
+//! is evaluated or declared. When evaluating a file with [`Ctx::eval_file`] a relative source map
+//! URL is also loaded from disk, as long as it refers to a file in the directory of the script or
+//! below it.
+//!
+//! A linked source map only applies to the source which contained it: evaluating an other source
+//! with the same file name replaces or removes it. Source maps registered with
+//! [`Ctx::register_source_map`] take precedence, so they can't be overridden by a script.
+//!
+//! [`Exception::mapped_stack`]: crate::Exception::mapped_stack
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Component, Path},
+};
+
+use crate::{CatchResultExt, Ctx, Error, Object, Result, StdString};
+
+/// A location in an original source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location<'a> {
+    /// The original source file.
+    pub source: &'a str,
+    /// The line in the original source, starting at 1.
+    pub line: u32,
+    /// The column in the original source, starting at 1.
+    pub column: u32,
+    /// The original name of the symbol at the location, if any.
+    pub name: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    column: u32,
+    source: u32,
+    line: u32,
+    source_column: u32,
+    name: Option<u32>,
+}
+
+/// A parsed version 3 source map.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceMap {
+    sources: Vec<StdString>,
+    names: Vec<StdString>,
+    lines: Vec<Vec<Segment>>,
+}
+
+impl SourceMap {
+    /// Parse a source map from its JSON representation.
+    pub fn parse(ctx: &Ctx, json: &str) -> Result<Self> {
+        let obj: Object = ctx.json_parse(json)?.get()?;
+        let root: Option<StdString> = obj.get("sourceRoot")?;
+        let root = root.unwrap_or_default();
+        let sources: Vec<Option<StdString>> = obj.get("sources")?;
+        let sources = sources
+            .into_iter()
+            .map(|source| {
+                let source = source.unwrap_or_default();
+                if root.is_empty() || root.ends_with('/') {
+                    format!("{root}{source}")
+                } else {
+                    format!("{root}/{source}")
+                }
+            })
+            .collect();
+        let names: Option<Vec<StdString>> = obj.get("names")?;
+        let mappings: StdString = obj.get("mappings")?;
+        let mut map = SourceMap {
+            sources,
+            names: names.unwrap_or_default(),
+            lines: Vec::new(),
+        };
+        map.lines = decode_mappings(&mappings, &map)
+            .ok_or_else(|| Error::new_from_js_message("string", "SourceMap", "invalid mappings"))?;
+        Ok(map)
+    }
+
+    /// Returns the original location of a generated location, lines and columns start at 1.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<Location<'_>> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let idx = segments.partition_point(|x| x.column <= column);
+        let segment = segments.get(idx.checked_sub(1)?)?;
+        Some(Location {
+            source: &self.sources[segment.source as usize],
+            line: segment.line + 1,
+            column: segment.source_column + 1,
+            name: segment.name.map(|x| self.names[x as usize].as_str()),
+        })
+    }
+}
+
+fn decode_mappings(mappings: &str, map: &SourceMap) -> Option<Vec<Vec<Segment>>> {
+    let mut source = 0i64;
+    let mut line = 0i64;
+    let mut source_column = 0i64;
+    let mut name = 0i64;
+    let mut lines = Vec::new();
+    for mapping_line in mappings.split(';') {
+        let mut column = 0i64;
+        let mut segments = Vec::new();
+        for segment in mapping_line.split(',').filter(|x| !x.is_empty()) {
+            let mut bytes = segment.bytes();
+            column += decode_vlq(&mut bytes)?;
+            // Segments without a source location don't map to anything.
+            if bytes.len() == 0 {
+                continue;
+            }
+            source += decode_vlq(&mut bytes)?;
+            line += decode_vlq(&mut bytes)?;
+            source_column += decode_vlq(&mut bytes)?;
+            let has_name = bytes.len() != 0;
+            if has_name {
+                name += decode_vlq(&mut bytes)?;
+            }
+            if source < 0 || source as usize >= map.sources.len() {
+                return None;
+            }
+            if has_name && (name < 0 || name as usize >= map.names.len()) {
+                return None;
+            }
+            segments.push(Segment {
+                column: column.try_into().ok()?,
+                source: source as u32,
+                line: line.try_into().ok()?,
+                source_column: source_column.try_into().ok()?,
+                name: has_name.then_some(name as u32),
+            });
+        }
+        segments.sort_by_key(|x| x.column);
+        lines.push(segments);
+    }
+    Some(lines)
+}
+
+fn base64_value(byte: u8) -> Option<u8> {
+    Some(match byte {
+        b'A'..=b'Z' => byte - b'A',
+        b'a'..=b'z' => byte - b'a' + 26,
+        b'0'..=b'9' => byte - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    })
+}
+
+fn decode_vlq(bytes: &mut impl Iterator<Item = u8>) -> Option<i64> {
+    let mut value = 0i64;
+    let mut shift = 0;
+    loop {
+        let digit = base64_value(bytes.next()?)? as i64;
+        value |= (digit & 0x1f) << shift;
+        if digit & 0x20 == 0 {
+            break;
+        }
+        shift += 5;
+        if shift > 60 {
+            return None;
+        }
+    }
+    Some(if value & 1 == 1 {
+        -(value >> 1)
+    } else {
+        value >> 1
+    })
+}
+
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=');
+    let mut res = Vec::with_capacity(data.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for byte in data.bytes() {
+        acc = (acc << 6) | base64_value(byte)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    Some(res)
+}
+
+/// Returns the URL of the `sourceMappingURL` comment in the last lines of a source.
+fn source_mapping_url(source: &[u8]) -> Option<&str> {
+    const PREFIXES: [&[u8]; 2] = [b"//# sourceMappingURL=", b"//@ sourceMappingURL="];
+    source
+        .split(|&x| x == b'\n')
+        .rev()
+        .map(trim_ascii)
+        .find(|line| !line.is_empty())
+        .and_then(|line| {
+            PREFIXES
+                .iter()
+                .find_map(|prefix| line.strip_prefix(*prefix))
+        })
+        .and_then(|url| std::str::from_utf8(url).ok())
+}
+
+fn trim_ascii(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+/// The source maps of a runtime, by generated file name.
+#[derive(Debug, Default)]
+pub(crate) struct SourceMaps {
+    /// The source maps registered by the embedder.
+    registered: HashMap<StdString, SourceMap>,
+    /// The source maps linked by the last source evaluated with the file name.
+    linked: HashMap<StdString, SourceMap>,
+}
+
+impl SourceMaps {
+    fn get(&self, file_name: &str) -> Option<&SourceMap> {
+        self.registered
+            .get(file_name)
+            .or_else(|| self.linked.get(file_name))
+    }
+}
+
+impl<'js> Ctx<'js> {
+    /// Register a source map for the given generated file name.
+    ///
+    /// The file name is matched against the file names in error stacks. The source map is used
+    /// instead of the source maps linked by sources with the same file name.
+    pub fn register_source_map<N: Into<StdString>>(&self, file_name: N, map: SourceMap) {
+        unsafe {
+            (*self.get_opaque())
+                .source_maps
+                .registered
+                .insert(file_name.into(), map)
+        };
+    }
+
+    /// Rewrite the locations in a stack trace for which a source map is registered.
+    pub fn map_stack(&self, stack: &str) -> StdString {
+        let maps = unsafe { &(*self.get_opaque()).source_maps };
+        let mut res = StdString::with_capacity(stack.len());
+        for (idx, line) in stack.lines().enumerate() {
+            if idx != 0 {
+                res.push('\n');
+            }
+            match map_stack_line(maps, line) {
+                Some(line) => res.push_str(&line),
+                None => res.push_str(line),
+            }
+        }
+        if stack.ends_with('\n') {
+            res.push('\n');
+        }
+        res
+    }
+
+    /// Link the source map referenced by a `sourceMappingURL` comment to the file name, replacing
+    /// the source map of the previous source with the file name.
+    ///
+    /// Failing to load the source map is not an error as the script can still be evaluated.
+    pub(crate) fn register_linked_source_map(
+        &self,
+        file_name: &str,
+        source: &[u8],
+        dir: Option<&Path>,
+    ) {
+        let linked = unsafe { &mut (*self.get_opaque()).source_maps.linked };
+        linked.remove(file_name);
+        let Some(url) = source_mapping_url(source) else {
+            return;
+        };
+        let json = if let Some(data) = url.strip_prefix("data:") {
+            let Some((_, data)) = data.split_once(";base64,") else {
+                return;
+            };
+            decode_base64(data).and_then(|x| StdString::from_utf8(x).ok())
+        } else if !url.contains("://") {
+            dir.and_then(|dir| read_linked_source_map(dir, url))
+        } else {
+            None
+        };
+        // Parsing invalid JSON throws, which must not be left pending for the next evaluation.
+        if let Some(map) = json.and_then(|json| SourceMap::parse(self, &json).catch(self).ok()) {
+            unsafe { &mut (*self.get_opaque()).source_maps.linked }.insert(file_name.into(), map);
+        }
+    }
+}
+
+/// Reads a source map referenced by a relative URL, which must not leave the directory of the
+/// script as the URL comes from the script.
+fn read_linked_source_map(dir: &Path, url: &str) -> Option<StdString> {
+    let path = Path::new(url);
+    if !path
+        .components()
+        .all(|x| matches!(x, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    // Symbolic links can still point elsewhere.
+    let dir = dir.canonicalize().ok()?;
+    let path = dir.join(path).canonicalize().ok()?;
+    if !path.starts_with(&dir) {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+fn map_stack_line(maps: &SourceMaps, line: &str) -> Option<StdString> {
+    // Stack lines have the form `    at name (file:line:column)` or `    at file:line:column`.
+    let (prefix, location, suffix) = match line.strip_suffix(')') {
+        Some(line) => {
+            let start = line.rfind('(')? + 1;
+            (&line[..start], &line[start..], ")")
+        }
+        None => {
+            let start = line.find("at ")? + 3;
+            (&line[..start], &line[start..], "")
+        }
+    };
+    let mut parts = location.rsplitn(3, ':');
+    let (column, line_num, file) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(column), Some(line_num), Some(file)) => (column.parse().ok()?, line_num, file),
+        // Stacks don't always contain a column.
+        (Some(line_num), Some(file), None) => (1, line_num, file),
+        _ => return None,
+    };
+    let loc = maps.get(file)?.lookup(line_num.parse().ok()?, column)?;
+    let mut res = StdString::from(prefix);
+    write!(res, "{}:{}:{}{}", loc.source, loc.line, loc.column, suffix).ok()?;
+    Some(res)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::{context::EvalOptions, source_map::SourceMap, test_with, CatchResultExt};
+
+    // Generated for `bundle.js`, mapping the second line to line 3 of `src/main.ts`.
+    const MAP: &str = r#"{"version":3,"sources":["main.ts"],"sourceRoot":"src","names":["fail"],"mappings":";AAEAA,MAAM"}"#;
+
+    #[test]
+    fn lookup() {
+        test_with(|ctx| {
+            let map = SourceMap::parse(&ctx, MAP).unwrap();
+            let loc = map.lookup(2, 1).unwrap();
+            assert_eq!(loc.source, "src/main.ts");
+            assert_eq!((loc.line, loc.column), (3, 1));
+            assert_eq!(loc.name, Some("fail"));
+            let loc = map.lookup(2, 10).unwrap();
+            assert_eq!((loc.line, loc.column), (3, 7));
+            assert!(map.lookup(1, 1).is_none());
+            assert!(SourceMap::parse(&ctx, r#"{"sources":[],"mappings":"A!"}"#).is_err());
+        })
+    }
+
+    #[test]
+    fn inline_source_map() {
+        test_with(|ctx| {
+            let map = super::decode_base64("YWJj").unwrap();
+            assert_eq!(map, b"abc");
+
+            let encoded = base64_encode(MAP.as_bytes());
+            let source = format!(
+                "\nthrow new Error('oops')\n//# sourceMappingURL=data:application/json;base64,{encoded}\n"
+            );
+            let err = ctx
+                .eval_with_options::<(), _>(source, EvalOptions::default().filename("bundle.js"))
+                .catch(&ctx)
+                .unwrap_err();
+            let crate::CaughtError::Exception(err) = err else {
+                panic!("expected an exception");
+            };
+            let stack = err.mapped_stack().unwrap();
+            assert!(stack.contains("src/main.ts:3:"), "{stack}");
+            assert!(err.to_string().contains("src/main.ts:3:"));
+        })
+    }
+
+    #[test]
+    fn module_source_map() {
+        test_with(|ctx| {
+            let encoded = base64_encode(MAP.as_bytes());
+            let source = format!(
+                "\nthrow new Error('oops')\n//# sourceMappingURL=data:application/json;base64,{encoded}\n"
+            );
+            let err = crate::Module::evaluate(ctx.clone(), "bundle.mjs", source)
+                .catch(&ctx)
+                .map(|_| ())
+                .unwrap_err();
+            let crate::CaughtError::Exception(err) = err else {
+                panic!("expected an exception");
+            };
+            let stack = err.mapped_stack().unwrap();
+            assert!(stack.contains("src/main.ts:3:"), "{stack}");
+        })
+    }
+
+    #[test]
+    fn scoped_source_map() {
+        test_with(|ctx| {
+            let stack = |name: &str, source: &str| {
+                let err = ctx
+                    .eval_with_options::<(), _>(source, EvalOptions::default().filename(name))
+                    .catch(&ctx)
+                    .unwrap_err();
+                let crate::CaughtError::Exception(err) = err else {
+                    panic!("expected an exception");
+                };
+                err.mapped_stack().unwrap()
+            };
+            let encoded = base64_encode(MAP.as_bytes());
+            let linked = format!(
+                "\nthrow new Error('oops')\n//# sourceMappingURL=data:application/json;base64,{encoded}\n"
+            );
+            let plain = "\nthrow new Error('oops')\n";
+
+            assert!(stack("linked.js", &linked).contains("src/main.ts:3:"));
+            // An other source with the same name doesn't use the map of the previous one.
+            let res = stack("linked.js", plain);
+            assert!(res.contains("linked.js:2:"), "{res}");
+
+            // Scripts can't override a registered source map.
+            let other = MAP.replace("main.ts", "other.ts");
+            ctx.register_source_map("registered.js", SourceMap::parse(&ctx, &other).unwrap());
+            let res = stack("registered.js", &linked);
+            assert!(res.contains("src/other.ts:3:"), "{res}");
+        })
+    }
+
+    #[test]
+    fn invalid_inline_source_map() {
+        test_with(|ctx| {
+            let encoded = base64_encode(b"{ not json");
+            let source =
+                format!("1\n//# sourceMappingURL=data:application/json;base64,{encoded}\n");
+            let res: i32 = ctx
+                .eval_with_options(source, EvalOptions::default().filename("invalid.js"))
+                .unwrap();
+            assert_eq!(res, 1);
+            let res: i32 = ctx.eval("2").unwrap();
+            assert_eq!(res, 2);
+            assert!(ctx.catch().is_null());
+        })
+    }
+
+    #[test]
+    fn linked_source_map() {
+        let dir = std::env::temp_dir().join(format!("rquickjs-source-map-{}", std::process::id()));
+        let scripts = dir.join("scripts");
+        fs::create_dir_all(scripts.join("maps")).unwrap();
+        fs::write(scripts.join("maps/inside.js.map"), MAP).unwrap();
+        fs::write(dir.join("outside.js.map"), MAP).unwrap();
+        for (name, url) in [
+            ("inside.js", "maps/inside.js.map"),
+            ("outside.js", "../outside.js.map"),
+            (
+                "absolute.js",
+                &*dir.join("outside.js.map").to_string_lossy(),
+            ),
+        ] {
+            let source = format!("\nthrow new Error('oops')\n//# sourceMappingURL={url}\n");
+            fs::write(scripts.join(name), source).unwrap();
+        }
+
+        test_with(|ctx| {
+            let stack = |name: &str| {
+                let err = ctx
+                    .eval_file::<(), _>(scripts.join(name))
+                    .catch(&ctx)
+                    .unwrap_err();
+                let crate::CaughtError::Exception(err) = err else {
+                    panic!("expected an exception");
+                };
+                err.mapped_stack().unwrap()
+            };
+            assert!(stack("inside.js").contains("src/main.ts:3:"));
+            let outside = stack("outside.js");
+            assert!(outside.contains("outside.js:2:"), "{outside}");
+            let absolute = stack("absolute.js");
+            assert!(absolute.contains("absolute.js:2:"), "{absolute}");
+        });
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn base64_encode(data: &[u8]) -> String {
+        const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut res = String::new();
+        for chunk in data.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - i * 8));
+            for i in 0..=chunk.len() {
+                res.push(CHARS[(n >> (18 - i * 6)) as usize & 63] as char);
+            }
+        }
+        let padding = (3 - data.len() % 3) % 3;
+        res + &"=".repeat(padding)
+    }
+}
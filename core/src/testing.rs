@@ -0,0 +1,273 @@
+//! Helpers for property testing and fuzzing code which handles arbitrary JavaScript values.
+//!
+//! [`ArbitraryValue`] implements [`Arbitrary`], generating descriptions of random values:
+//! primitives with edge cases like `NaN` and `-0`, strings, big integers, arrays, objects with
+//! keys like `__proto__` and `length`, array buffers and typed arrays. Containers can reference
+//! each other, so the values are graphs with shared objects and cycles. A description is turned
+//! into a value of a context with [`IntoJs`], which makes it possible to test
+//! [`FromJs`](crate::FromJs) implementations and native functions against adversarial inputs,
+//! for example in a `cargo fuzz` target. The `arbitrary` crate is reexported to use the same
+//! version.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, IntoJs};
+//! use rquickjs::testing::{arbitrary::{Arbitrary, Unstructured}, ArbitraryValue};
+//! # let rt = Runtime::new().unwrap();
+//! # let ctx = Context::full(&rt).unwrap();
+//! // The input of the fuzzer.
+//! let data = [7u8, 3, 42, 1, 0, 255, 12, 9, 100, 4, 8, 15, 16, 23, 42];
+//! let value = ArbitraryValue::arbitrary(&mut Unstructured::new(&data)).unwrap();
+//! ctx.with(|ctx| {
+//!     let value = value.into_js(&ctx).unwrap();
+//!     // Conversions may fail, but they must not panic.
+//!     let _ = ctx.json_stringify(value);
+//!     ctx.catch();
+//! });
+//! ```
+
+pub use arbitrary;
+use arbitrary::{Arbitrary, Unstructured};
+
+use crate::{
+    object::PropertyDescriptor, Array, ArrayBuffer, BigInt, Ctx, IntoJs, Object, Result, StdString,
+    TypedArray, Value,
+};
+
+/// The maximum nesting of the generated arrays and objects.
+const MAX_DEPTH: usize = 4;
+
+/// The maximum amount of elements or properties of a generated array or object.
+const MAX_LEN: usize = 8;
+
+/// Keys with a special meaning to some objects or conversions.
+const SPECIAL_KEYS: [&str; 8] = [
+    "__proto__",
+    "constructor",
+    "prototype",
+    "length",
+    "0",
+    "then",
+    "toString",
+    "valueOf",
+];
+
+/// Floats which are edge cases of conversions.
+const SPECIAL_FLOATS: [f64; 8] = [
+    f64::NAN,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    -0.0,
+    f64::EPSILON,
+    f64::MAX,
+    9007199254740993.0,
+    4294967296.0,
+];
+
+/// The description of a random JavaScript value, converted into a value with [`IntoJs`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArbitraryValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Int(i32),
+    Float(f64),
+    String(StdString),
+    BigInt(i64),
+    Array(Vec<ArbitraryValue>),
+    Object(Vec<(StdString, ArbitraryValue)>),
+    ArrayBuffer(Vec<u8>),
+    Uint8Array(Vec<u8>),
+    Int32Array(Vec<i32>),
+    Float64Array(Vec<f64>),
+    /// A reference to an array or object created before this value, by index in the order
+    /// they are created, modulo their amount. Referencing a container which contains the
+    /// reference creates a cycle. Without containers the reference is `undefined`.
+    Reference(usize),
+}
+
+impl ArbitraryValue {
+    /// Generate a value with at most `depth` levels of nested arrays and objects.
+    pub fn arbitrary_with_depth(u: &mut Unstructured<'_>, depth: usize) -> arbitrary::Result<Self> {
+        // The containers come last, so they aren't chosen without depth left.
+        let choices = if depth == 0 { 12 } else { 14 };
+        Ok(match u.choose_index(choices)? {
+            0 => ArbitraryValue::Undefined,
+            1 => ArbitraryValue::Null,
+            2 => ArbitraryValue::Bool(u.arbitrary()?),
+            3 => ArbitraryValue::Int(u.arbitrary()?),
+            4 => ArbitraryValue::Float(if u.arbitrary()? {
+                *u.choose(&SPECIAL_FLOATS)?
+            } else {
+                u.arbitrary()?
+            }),
+            5 => ArbitraryValue::String(u.arbitrary()?),
+            6 => ArbitraryValue::BigInt(u.arbitrary()?),
+            7 => ArbitraryValue::ArrayBuffer(collect(u, |u| u.arbitrary())?),
+            8 => ArbitraryValue::Uint8Array(collect(u, |u| u.arbitrary())?),
+            9 => ArbitraryValue::Int32Array(collect(u, |u| u.arbitrary())?),
+            10 => ArbitraryValue::Float64Array(collect(u, |u| u.arbitrary())?),
+            11 => ArbitraryValue::Reference(u.arbitrary()?),
+            12 => ArbitraryValue::Array(collect(u, |u| Self::arbitrary_with_depth(u, depth - 1))?),
+            _ => ArbitraryValue::Object(collect(u, |u| {
+                let key = if u.arbitrary()? {
+                    (*u.choose(&SPECIAL_KEYS)?).into()
+                } else {
+                    u.arbitrary()?
+                };
+                Ok((key, Self::arbitrary_with_depth(u, depth - 1)?))
+            })?),
+        })
+    }
+
+    /// Returns the nesting depth of the arrays and objects of the value.
+    pub fn depth(&self) -> usize {
+        match self {
+            ArbitraryValue::Array(x) => 1 + x.iter().map(Self::depth).max().unwrap_or(0),
+            ArbitraryValue::Object(x) => 1 + x.iter().map(|(_, x)| x.depth()).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn build<'js>(self, ctx: &Ctx<'js>, containers: &mut Vec<Object<'js>>) -> Result<Value<'js>> {
+        Ok(match self {
+            ArbitraryValue::Undefined => Value::new_undefined(ctx.clone()),
+            ArbitraryValue::Null => Value::new_null(ctx.clone()),
+            ArbitraryValue::Bool(x) => Value::new_bool(ctx.clone(), x),
+            ArbitraryValue::Int(x) => Value::new_int(ctx.clone(), x),
+            ArbitraryValue::Float(x) => Value::new_float(ctx.clone(), x),
+            ArbitraryValue::String(x) => x.into_js(ctx)?,
+            ArbitraryValue::BigInt(x) => BigInt::from_i64(ctx.clone(), x)?.into_value(),
+            ArbitraryValue::Array(items) => {
+                let array = Array::new(ctx.clone())?;
+                containers.push(array.as_object().clone());
+                for (index, item) in items.into_iter().enumerate() {
+                    array.set(index, item.build(ctx, containers)?)?;
+                }
+                array.into_value()
+            }
+            ArbitraryValue::Object(props) => {
+                let object = Object::new(ctx.clone())?;
+                containers.push(object.clone());
+                for (key, value) in props {
+                    // Define own properties like `JSON.parse`, `__proto__` doesn't change the
+                    // prototype.
+                    let desc = PropertyDescriptor {
+                        value: Some(value.build(ctx, containers)?),
+                        writable: Some(true),
+                        enumerable: Some(true),
+                        configurable: Some(true),
+                        ..Default::default()
+                    };
+                    object.define_property(key, desc)?;
+                }
+                object.into_value()
+            }
+            ArbitraryValue::ArrayBuffer(x) => ArrayBuffer::new(ctx.clone(), x)?.into_value(),
+            ArbitraryValue::Uint8Array(x) => TypedArray::<u8>::new(ctx.clone(), x)?.into_value(),
+            ArbitraryValue::Int32Array(x) => TypedArray::<i32>::new(ctx.clone(), x)?.into_value(),
+            ArbitraryValue::Float64Array(x) => TypedArray::<f64>::new(ctx.clone(), x)?.into_value(),
+            ArbitraryValue::Reference(index) => match containers.len() {
+                0 => Value::new_undefined(ctx.clone()),
+                len => containers[index % len].clone().into_value(),
+            },
+        })
+    }
+}
+
+/// Generate a list of at most [`MAX_LEN`] items.
+fn collect<'a, T>(
+    u: &mut Unstructured<'a>,
+    mut item: impl FnMut(&mut Unstructured<'a>) -> arbitrary::Result<T>,
+) -> arbitrary::Result<Vec<T>> {
+    let len = u.int_in_range(0..=MAX_LEN)?;
+    (0..len).map(|_| item(u)).collect()
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Self::arbitrary_with_depth(u, MAX_DEPTH)
+    }
+}
+
+impl<'js> IntoJs<'js> for ArbitraryValue {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.build(ctx, &mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use super::{ArbitraryValue, MAX_DEPTH};
+    use crate::{test_with, IntoJs, Object, Value};
+
+    /// Bytes from a linear congruential generator, the input of a fuzzer would be used instead.
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn arbitrary_values() {
+        test_with(|ctx| {
+            let mut kinds = std::collections::HashSet::new();
+            for seed in 0..200 {
+                let data = bytes(seed, 512);
+                let value = ArbitraryValue::arbitrary(&mut Unstructured::new(&data)).unwrap();
+                assert!(value.depth() <= MAX_DEPTH);
+                let value: Value = value.into_js(&ctx).unwrap();
+                kinds.insert(value.type_name());
+                ctx.globals().set("value", value).unwrap();
+                // Walking the graph terminates, also with cycles.
+                let _: usize = ctx
+                    .eval(
+                        r#"
+                            (() => {
+                                const seen = new Set();
+                                const walk = (x) => {
+                                    if (typeof x !== "object" || x === null || seen.has(x)) return;
+                                    seen.add(x);
+                                    Object.values(x).forEach(walk);
+                                };
+                                walk(value);
+                                return seen.size;
+                            })()
+                        "#,
+                    )
+                    .unwrap();
+            }
+            assert!(kinds.len() > 5, "{:?}", kinds);
+        })
+    }
+
+    #[test]
+    fn references() {
+        test_with(|ctx| {
+            let value = ArbitraryValue::Object(vec![
+                ("self".into(), ArbitraryValue::Reference(0)),
+                (
+                    "list".into(),
+                    ArbitraryValue::Array(vec![ArbitraryValue::Reference(3)]),
+                ),
+            ]);
+            let object: Object = value.into_js(&ctx).unwrap().into_object().unwrap();
+            assert_eq!(object.get::<_, Object>("self").unwrap(), object);
+            let list: Object = object.get("list").unwrap();
+            assert_eq!(list.get::<_, Object>(0).unwrap(), list);
+
+            let value =
+                ArbitraryValue::Object(vec![("__proto__".into(), ArbitraryValue::Reference(0))]);
+            let object: Object = value.into_js(&ctx).unwrap().into_object().unwrap();
+            assert_eq!(object.get::<_, Object>("__proto__").unwrap(), object);
+            assert_ne!(object.get_prototype(), Some(object.clone()));
+        })
+    }
+}
@@ -5,10 +5,17 @@ pub mod array;
 pub mod atom;
 mod bigint;
 pub mod convert;
+mod date;
 mod exception;
 pub mod function;
+mod inspect;
+pub mod iterator;
+mod map;
 pub mod module;
 pub mod object;
+mod regexp;
+mod serialize;
+mod set;
 mod string;
 mod symbol;
 
@@ -16,23 +23,38 @@ pub use array::Array;
 pub use atom::Atom;
 pub use bigint::BigInt;
 pub use convert::{Coerced, FromAtom, FromIteratorJs, FromJs, IntoAtom, IntoJs, IteratorJs};
-pub use exception::Exception;
+pub use date::Date;
+pub use exception::{Exception, JsError};
 pub use function::{Constructor, Function};
+pub use inspect::InspectOptions;
+pub use map::{Map, MapEntries, MapIter};
 pub use module::Module;
 pub use object::{Filter, Object};
-pub use string::String;
+pub use regexp::RegExp;
+pub use serialize::{ReadOptions, WriteOptions};
+pub use set::Set;
+pub use string::{JsStr, String};
 pub use symbol::Symbol;
 
 #[cfg(feature = "array-buffer")]
 pub mod array_buffer;
 #[cfg(feature = "array-buffer")]
+pub mod data_view;
+#[cfg(feature = "array-buffer")]
 pub mod typed_array;
 
 #[cfg(feature = "array-buffer")]
 pub use array_buffer::ArrayBuffer;
 #[cfg(feature = "array-buffer")]
+pub use data_view::DataView;
+#[cfg(feature = "array-buffer")]
 pub use typed_array::TypedArray;
 
+#[cfg(feature = "shared-array-buffer")]
+pub mod shared_array_buffer;
+#[cfg(feature = "shared-array-buffer")]
+pub use shared_array_buffer::SharedArrayBuffer;
+
 /// Any JavaScript value
 pub struct Value<'js> {
     pub(crate) ctx: Ctx<'js>,
@@ -13,7 +13,7 @@ use super::convert::FromIteratorJs;
 /// JavaScript array's are objects and can be used as such.
 /// However arrays in QuickJS are optimized when they do not have any holes.
 /// This value represents such an optimized array.
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[derive(PartialEq, Clone, Eq, Hash)]
 #[repr(transparent)]
 pub struct Array<'js>(pub(crate) Object<'js>);
 
@@ -1,11 +1,11 @@
-use crate::{qjs, Ctx, Error, FromJs, IntoJs, Object, Outlive, Result, Value};
+use crate::{qjs, Ctx, Error, Exception, FromJs, IntoJs, Object, Outlive, Result, Value};
 use core::fmt;
 use std::{
     convert::TryInto,
     mem::{self, size_of, ManuallyDrop, MaybeUninit},
     ops::Deref,
     os::raw::c_void,
-    ptr::NonNull,
+    ptr::{null_mut, NonNull},
     result::Result as StdResult,
     slice,
 };
@@ -34,6 +34,24 @@ impl fmt::Display for AsSliceError {
     }
 }
 
+extern "C" fn drop_raw<T>(_rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
+    // detached buffers call the function again with a null pointer when finalized
+    if ptr.is_null() {
+        return;
+    }
+    let ptr = ptr as *mut T;
+    let capacity = opaque as usize;
+    // reconstruct vector in order to free data
+    // the length of actual data does not matter for copyable types
+    unsafe { Vec::from_raw_parts(ptr, capacity, capacity) };
+}
+
+/// Frees the data of buffers created from vectors of byte sized items, which
+/// [`ArrayBuffer::transfer`] can give back as a `Vec<u8>` without copying.
+extern "C" fn drop_bytes(rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
+    drop_raw::<u8>(rt, opaque, ptr)
+}
+
 /// Rust representation of a JavaScript object of class ArrayBuffer.
 ///
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "array-buffer")))]
@@ -52,21 +70,19 @@ impl<'js> ArrayBuffer<'js> {
         let ptr = src.as_mut_ptr();
         let capacity = src.capacity();
         let size = src.len() * size_of::<T>();
-
-        extern "C" fn drop_raw<T>(_rt: *mut qjs::JSRuntime, opaque: *mut c_void, ptr: *mut c_void) {
-            let ptr = ptr as *mut T;
-            let capacity = opaque as usize;
-            // reconstruct vector in order to free data
-            // the length of actual data does not matter for copyable types
-            unsafe { Vec::from_raw_parts(ptr, capacity, capacity) };
-        }
+        let free_func: unsafe extern "C" fn(_, _, _) =
+            if size_of::<T>() == 1 && mem::align_of::<T>() == 1 {
+                drop_bytes
+            } else {
+                drop_raw::<T>
+            };
 
         Ok(Self(Object(unsafe {
             let val = qjs::JS_NewArrayBuffer(
                 ctx.as_ptr(),
                 ptr as _,
                 size as _,
-                Some(drop_raw::<T>),
+                Some(free_func),
                 capacity as _,
                 0,
             );
@@ -92,9 +108,9 @@ impl<'js> ArrayBuffer<'js> {
         })))
     }
 
-    /// Get the length of the array buffer in bytes.
+    /// Get the length of the array buffer in bytes, which is `0` if it was detached.
     pub fn len(&self) -> usize {
-        Self::get_raw(&self.0).expect("Not an ArrayBuffer").len
+        Self::get_raw(&self.0).map(|raw| raw.len).unwrap_or(0)
     }
 
     /// Returns whether an array buffer is empty.
@@ -110,6 +126,14 @@ impl<'js> ArrayBuffer<'js> {
         Some(unsafe { slice::from_raw_parts_mut(raw.ptr.as_ptr(), raw.len) })
     }
 
+    /// Returns the underlying bytes of the buffer,
+    ///
+    /// Throws a `TypeError` if the array is detached.
+    pub fn try_as_bytes(&self) -> Result<&[u8]> {
+        self.as_bytes()
+            .ok_or_else(|| Exception::throw_type(self.ctx(), "ArrayBuffer is detached"))
+    }
+
     /// Returns a slice if the buffer underlying buffer is properly aligned for the type and the
     /// buffer is not detached.
     pub fn as_slice<T: TypedArrayItem>(&self) -> StdResult<&[T], AsSliceError> {
@@ -126,6 +150,53 @@ impl<'js> ArrayBuffer<'js> {
         unsafe { qjs::JS_DetachArrayBuffer(self.0.ctx.as_ptr(), self.0.as_js_value()) }
     }
 
+    /// Returns whether the array buffer was detached.
+    pub fn is_detached(&self) -> bool {
+        unsafe { qjs::JS_IsArrayBufferDetached(self.0.ctx.as_ptr(), self.0.as_js_value()) == 1 }
+    }
+
+    /// Detach the array buffer and return its data.
+    ///
+    /// The data is handed over without copying when the buffer was created from a vector of
+    /// bytes with [`ArrayBuffer::new`], otherwise it is copied and the original memory freed.
+    /// Typed arrays and views on the buffer become empty, like with [`detach`](Self::detach).
+    ///
+    /// Throws a `TypeError` if the buffer was already detached or is a `SharedArrayBuffer`.
+    pub fn transfer(self) -> Result<Vec<u8>> {
+        let ctx = self.ctx();
+        let mut data = null_mut();
+        let mut size = 0;
+        let mut free_func = None;
+        let mut opaque = null_mut();
+        unsafe {
+            if qjs::JS_TransferArrayBuffer(
+                ctx.as_ptr(),
+                self.0.as_js_value(),
+                &mut data,
+                &mut size,
+                &mut free_func,
+                &mut opaque,
+            ) < 0
+            {
+                return Err(ctx.raise_exception());
+            }
+            let len: usize = size.try_into().expect(qjs::SIZE_T_ERROR);
+            let drop_bytes: unsafe extern "C" fn(_, _, _) = drop_bytes;
+            if free_func.map(|x| x as usize) == Some(drop_bytes as usize) {
+                return Ok(Vec::from_raw_parts(data, len, opaque as usize));
+            }
+            let bytes = if len == 0 {
+                Vec::new()
+            } else {
+                slice::from_raw_parts(data, len).to_vec()
+            };
+            if let Some(free_func) = free_func {
+                free_func(qjs::JS_GetRuntime(ctx.as_ptr()), opaque, data.cast());
+            }
+            Ok(bytes)
+        }
+    }
+
     /// Reference to value
     #[inline]
     pub fn as_value(&self) -> &Value<'js> {
@@ -157,7 +228,7 @@ impl<'js> ArrayBuffer<'js> {
 
     /// Convert from an object
     pub fn from_object(object: Object<'js>) -> Option<Self> {
-        if Self::get_raw(&object.0).is_some() {
+        if Self::is_array_buffer(&object.0) {
             Some(Self(object))
         } else {
             None
@@ -171,9 +242,18 @@ impl<'js> ArrayBuffer<'js> {
         Self::get_raw(self.as_value())
     }
 
+    /// Returns whether the value is an ArrayBuffer or SharedArrayBuffer, including detached ones.
+    fn is_array_buffer(val: &Value<'js>) -> bool {
+        unsafe { qjs::JS_IsArrayBufferDetached(val.ctx().as_ptr(), val.as_js_value()) >= 0 }
+    }
+
     pub(crate) fn get_raw(val: &Value<'js>) -> Option<RawArrayBuffer> {
         let ctx = val.ctx();
         let val = val.as_js_value();
+        // Checked first as getting the data of a detached buffer throws.
+        if unsafe { qjs::JS_IsArrayBufferDetached(ctx.as_ptr(), val) } != 0 {
+            return None;
+        }
         let mut size = MaybeUninit::<qjs::size_t>::uninit();
         let ptr = unsafe { qjs::JS_GetArrayBuffer(ctx.as_ptr(), size.as_mut_ptr(), val) };
 
@@ -234,7 +314,7 @@ impl<'js> IntoJs<'js> for ArrayBuffer<'js> {
 impl<'js> Object<'js> {
     /// Returns whether the object is an instance of [`ArrayBuffer`].
     pub fn is_array_buffer(&self) -> bool {
-        ArrayBuffer::get_raw(&self.0).is_some()
+        ArrayBuffer::is_array_buffer(&self.0)
     }
 
     /// Interpret as [`ArrayBuffer`]
@@ -349,4 +429,55 @@ mod test {
             assert_eq!(val.as_bytes().unwrap(), &res)
         });
     }
+
+    #[test]
+    fn detach() {
+        test_with(|ctx| {
+            let mut val: ArrayBuffer = ctx
+                .eval("(globalThis.a = new Uint8Array([1, 2, 3])).buffer")
+                .unwrap();
+            assert!(!val.is_detached());
+            val.detach();
+            assert!(val.is_detached());
+            assert_eq!(val.len(), 0);
+            assert!(val.as_bytes().is_none());
+            assert!(!ctx.catch().is_error());
+
+            let val: ArrayBuffer = ctx.eval("a.buffer").unwrap();
+            assert!(val.is_detached());
+            let view: TypedArray<u8> = ctx.globals().get("a").unwrap();
+            assert!(view.as_bytes().is_none());
+            assert!(!ctx.catch().is_error());
+            assert_eq!(ctx.eval::<usize, _>("a.length").unwrap(), 0);
+
+            let err = view.try_as_bytes().unwrap_err();
+            assert!(err.is_exception());
+            let err = ctx.catch().into_exception().unwrap();
+            assert_eq!(err.message().as_deref(), Some("ArrayBuffer is detached"));
+        });
+    }
+
+    #[test]
+    fn transfer() {
+        test_with(|ctx| {
+            let data = vec![1u8, 2, 3];
+            let ptr = data.as_ptr();
+            let val = ArrayBuffer::new(ctx.clone(), data).unwrap();
+            ctx.globals().set("a", val.clone()).unwrap();
+            let data = val.clone().transfer().unwrap();
+            assert_eq!(data, [1, 2, 3]);
+            assert_eq!(data.as_ptr(), ptr);
+            assert!(ctx.eval::<bool, _>("a.byteLength === 0").unwrap());
+            assert!(val.transfer().is_err());
+            assert!(ctx.catch().is_error());
+
+            let val: ArrayBuffer = ctx
+                .eval("(globalThis.b = new Int16Array([1, 2])).buffer")
+                .unwrap();
+            let data = val.transfer().unwrap();
+            assert_eq!(data.len(), 4);
+            assert_eq!(&data[..2], &1i16.to_ne_bytes());
+            assert!(ctx.eval::<bool, _>("b.length === 0").unwrap());
+        });
+    }
 }
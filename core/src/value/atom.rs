@@ -1,7 +1,7 @@
 //!  QuickJS atom functionality.
 
 use crate::{qjs, Ctx, Error, Result, String, Value};
-use std::{ffi::CStr, hash::Hash, string::String as StdString};
+use std::{ffi::CStr, hash::Hash, mem, ptr, string::String as StdString};
 
 mod predefined;
 pub use predefined::PredefinedAtom;
@@ -145,6 +145,14 @@ impl<'js> Atom<'js> {
         Atom { atom: val, ctx }
     }
 
+    /// Returns the raw atom transferring ownership of the atom to the caller.
+    pub(crate) fn into_atom_val(self) -> qjs::JSAtom {
+        let this = mem::ManuallyDrop::new(self);
+        // Still release the reference to the context.
+        mem::drop(unsafe { ptr::read(&this.ctx) });
+        this.atom
+    }
+
     pub(crate) unsafe fn from_atom_val_dup(ctx: Ctx<'js>, val: qjs::JSAtom) -> Self {
         qjs::JS_DupAtom(ctx.as_ptr(), val);
         Atom { atom: val, ctx }
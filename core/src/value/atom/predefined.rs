@@ -413,6 +413,8 @@ pub enum PredefinedAtom {
     URIError = qjs::JS_ATOM_URIError as u32,
     /// "InternalError"
     InternalError = qjs::JS_ATOM_InternalError as u32,
+    /// "Symbol.toPrimitive"
+    SymbolToPrimitive = qjs::JS_ATOM_Symbol_toPrimitive as u32,
     /// "Symbol.iterator"
     SymbolIterator = qjs::JS_ATOM_Symbol_iterator as u32,
     /// "Symbol.match"
@@ -435,13 +437,18 @@ pub enum PredefinedAtom {
     SymbolSpecies = qjs::JS_ATOM_Symbol_species as u32,
     /// "Symbol.unscopables"
     SymbolUnscopables = qjs::JS_ATOM_Symbol_unscopables as u32,
+    /// "Symbol.asyncIterator"
+    SymbolAsyncIterator = qjs::JS_ATOM_Symbol_asyncIterator as u32,
+    /// "Symbol.operatorSet"
+    SymbolOperatorSet = qjs::JS_ATOM_Symbol_operatorSet as u32,
 }
 
 impl PredefinedAtom {
     pub const fn is_symbol(self) -> bool {
         matches!(
             self,
-            PredefinedAtom::SymbolIterator
+            PredefinedAtom::SymbolToPrimitive
+                | PredefinedAtom::SymbolIterator
                 | PredefinedAtom::SymbolMatch
                 | PredefinedAtom::SymbolMatchAll
                 | PredefinedAtom::SymbolReplace
@@ -452,6 +459,8 @@ impl PredefinedAtom {
                 | PredefinedAtom::SymbolHasInstance
                 | PredefinedAtom::SymbolSpecies
                 | PredefinedAtom::SymbolUnscopables
+                | PredefinedAtom::SymbolAsyncIterator
+                | PredefinedAtom::SymbolOperatorSet
         )
     }
 
@@ -659,6 +668,7 @@ impl PredefinedAtom {
             PredefinedAtom::TypeError => "TypeError",
             PredefinedAtom::URIError => "URIError",
             PredefinedAtom::InternalError => "InternalError",
+            PredefinedAtom::SymbolToPrimitive => "Symbol.toPrimitive",
             PredefinedAtom::SymbolIterator => "Symbol.iterator",
             PredefinedAtom::SymbolMatch => "Symbol.match",
             PredefinedAtom::SymbolMatchAll => "Symbol.matchAll",
@@ -670,6 +680,8 @@ impl PredefinedAtom {
             PredefinedAtom::SymbolHasInstance => "Symbol.hasInstance",
             PredefinedAtom::SymbolSpecies => "Symbol.species",
             PredefinedAtom::SymbolUnscopables => "Symbol.unscopables",
+            PredefinedAtom::SymbolAsyncIterator => "Symbol.asyncIterator",
+            PredefinedAtom::SymbolOperatorSet => "Symbol.operatorSet",
         }
     }
 }
@@ -883,6 +895,7 @@ mod test {
             PredefinedAtom::TypeError,
             PredefinedAtom::URIError,
             PredefinedAtom::InternalError,
+            PredefinedAtom::SymbolToPrimitive,
             PredefinedAtom::SymbolIterator,
             PredefinedAtom::SymbolMatch,
             PredefinedAtom::SymbolMatchAll,
@@ -894,6 +907,8 @@ mod test {
             PredefinedAtom::SymbolHasInstance,
             PredefinedAtom::SymbolSpecies,
             PredefinedAtom::SymbolUnscopables,
+            PredefinedAtom::SymbolAsyncIterator,
+            PredefinedAtom::SymbolOperatorSet,
         ];
 
         let rt = Runtime::new().unwrap();
@@ -1,6 +1,6 @@
 //! Utilities for converting to and from JavaScript values.
 
-use crate::{Atom, Ctx, Result, Value};
+use crate::{Atom, Ctx, Result, StdResult, Value};
 
 mod atom;
 mod coerce;
@@ -42,6 +42,102 @@ mod into;
 #[repr(transparent)]
 pub struct Coerced<T>(pub T);
 
+/// The wrapper for results to convert them to and from `{ ok }` or `{ err }` objects
+///
+/// Results are normally converted by throwing the error, this wrapper instead passes both
+/// outcomes as a plain object.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Result, convert::ResultObject};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| -> Result<()> {
+/// #
+/// let res: ResultObject<i32, String> = ResultObject(Err("failed".to_string()));
+/// ctx.globals().set("res", res)?;
+/// assert_eq!(ctx.eval::<String, _>("res.err")?, "failed");
+///
+/// let res: ResultObject<i32, String> = ctx.eval("({ ok: 42 })")?;
+/// assert_eq!(res.0, Ok(42));
+/// #
+/// # Ok(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ResultObject<T, E>(pub StdResult<T, E>);
+
+/// A tri-state value which keeps `null` and `undefined` apart
+///
+/// [`Option`] converts both `null` and `undefined` into `None`, this type can be used when the
+/// difference matters, for example to distinguish clearing a field from leaving it unchanged.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Result, convert::Nullable};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| -> Result<()> {
+/// #
+/// let patch: Vec<Nullable<i32>> = ctx.eval("[1, null, undefined]")?;
+/// assert_eq!(patch, [Nullable::Value(1), Nullable::Null, Nullable::Undefined]);
+///
+/// ctx.globals().set("cleared", Nullable::<i32>::Null)?;
+/// assert!(ctx.eval::<bool, _>("cleared === null")?);
+/// #
+/// # Ok(())
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Nullable<T> {
+    /// A value which is neither `null` nor `undefined`.
+    Value(T),
+    /// The `null` value.
+    Null,
+    /// The `undefined` value, also used for missing properties and arguments.
+    #[default]
+    Undefined,
+}
+
+impl<T> Nullable<T> {
+    /// Returns whether this is a value.
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+
+    /// Returns whether this is `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns whether this is `undefined`.
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Self::Undefined)
+    }
+
+    /// Converts from `&Nullable<T>` to `Nullable<&T>`.
+    pub fn as_ref(&self) -> Nullable<&T> {
+        match self {
+            Self::Value(value) => Nullable::Value(value),
+            Self::Null => Nullable::Null,
+            Self::Undefined => Nullable::Undefined,
+        }
+    }
+
+    /// Returns the value if there is one, collapsing `null` and `undefined` into `None`.
+    pub fn into_option(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            Self::Null | Self::Undefined => None,
+        }
+    }
+}
+
+impl<T> From<T> for Nullable<T> {
+    fn from(value: T) -> Self {
+        Self::Value(value)
+    }
+}
+
 /// For converting JavaScript values to Rust values
 ///
 /// This trait automatically converts any value which can be
@@ -97,3 +193,45 @@ where
 /// A helper type for turning a tuple into a JavaScript array.
 /// Implements [`IntoJs`] and [`FromJs`] for tuples of various lengths
 pub struct List<T>(pub T);
+
+/// A helper type for returning multiple values as a JavaScript array.
+///
+/// Tuples are already converted into arrays and structs deriving [`IntoJs`] into objects,
+/// wrapping a struct converts it into an array of its fields in declaration order instead, so
+/// the result of a function can be destructured with `const [a, b] = f()`.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Ctx, Function, IntoJs, Result, Value};
+/// # use rquickjs::convert::{IntoMultiValue, MultiValue};
+/// struct MinMax {
+///     min: i32,
+///     max: i32,
+/// }
+///
+/// // Derived by `#[derive(IntoJs)]`.
+/// impl<'js> IntoMultiValue<'js> for MinMax {
+///     fn into_multi_value(self, ctx: &Ctx<'js>) -> Result<Vec<Value<'js>>> {
+///         Ok(vec![self.min.into_js(ctx)?, self.max.into_js(ctx)?])
+///     }
+/// }
+///
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let pair = Function::new(ctx.clone(), || (1, "one")).unwrap();
+/// let min_max = Function::new(ctx.clone(), || MultiValue(MinMax { min: 1, max: 3 })).unwrap();
+/// ctx.globals().set("pair", pair).unwrap();
+/// ctx.globals().set("minMax", min_max).unwrap();
+/// assert!(ctx.eval::<bool, _>("const [n, name] = pair(); n === 1 && name === 'one'").unwrap());
+/// assert!(ctx.eval::<bool, _>("const [min, max] = minMax(); min === 1 && max === 3").unwrap());
+/// # });
+/// ```
+pub struct MultiValue<T>(pub T);
+
+/// For converting Rust values into a list of JavaScript values, used by [`MultiValue`].
+///
+/// Implemented for tuples and derived by `#[derive(IntoJs)]` for structs.
+pub trait IntoMultiValue<'js> {
+    /// Convert into the values in declaration order.
+    fn into_multi_value(self, ctx: &Ctx<'js>) -> Result<Vec<Value<'js>>>;
+}
@@ -38,6 +38,12 @@ impl<'js> IntoAtom<'js> for Atom<'js> {
     }
 }
 
+impl<'js> IntoAtom<'js> for &Atom<'js> {
+    fn into_atom(self, _: &Ctx<'js>) -> Result<Atom<'js>> {
+        Ok(self.clone())
+    }
+}
+
 impl<'js> IntoAtom<'js> for Value<'js> {
     fn into_atom(self, ctx: &Ctx<'js>) -> Result<Atom<'js>> {
         Atom::from_value(ctx.clone(), &self)
@@ -1,11 +1,17 @@
 use crate::{
-    convert::List, Array, Ctx, Error, FromAtom, FromJs, Object, Result, StdString, String, Type,
+    convert::{List, Nullable, ResultObject},
+    function::Null,
+    value::Date,
+    Array, Atom, Ctx, Error, FromAtom, FromJs, JsStr, Object, Result, StdString, String, Type,
     Value,
 };
 use std::{
+    borrow::Cow,
     cell::{Cell, RefCell},
     collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
     hash::{BuildHasher, Hash},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    path::PathBuf,
     rc::Rc,
     sync::{Arc, Mutex, RwLock},
     time::{Duration, SystemTime},
@@ -29,6 +35,25 @@ impl<'js> FromJs<'js> for StdString {
     }
 }
 
+impl<'js> FromJs<'js> for JsStr<'js> {
+    fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        String::from_value(value).and_then(|string| string.to_str())
+    }
+}
+
+impl<'js, 'a> FromJs<'js> for Cow<'a, str> {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        StdString::from_js(ctx, value).map(Cow::Owned)
+    }
+}
+
+/// Convert from a JS string
+impl<'js> FromJs<'js> for PathBuf {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        StdString::from_js(ctx, value).map(PathBuf::from)
+    }
+}
+
 /// Convert from JS as any
 impl<'js> FromJs<'js> for () {
     fn from_js(_: &Ctx<'js>, _: Value<'js>) -> Result<Self> {
@@ -50,6 +75,30 @@ where
     }
 }
 
+/// Convert from JS as optional, with `None` for both `null` and `undefined`
+impl<'js, T> FromJs<'js> for Null<T>
+where
+    T: FromJs<'js>,
+{
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        Option::from_js(ctx, value).map(Null)
+    }
+}
+
+/// Convert from JS keeping `null` and `undefined` apart
+impl<'js, T> FromJs<'js> for Nullable<T>
+where
+    T: FromJs<'js>,
+{
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        match value.type_of() {
+            Type::Null => Ok(Nullable::Null),
+            Type::Undefined | Type::Uninitialized => Ok(Nullable::Undefined),
+            _ => T::from_js(ctx, value).map(Nullable::Value),
+        }
+    }
+}
+
 /// Convert from JS as result
 impl<'js, T> FromJs<'js> for Result<T>
 where
@@ -67,6 +116,28 @@ where
     }
 }
 
+/// Convert from a JS object with either an `ok` or an `err` property
+impl<'js, T, E> FromJs<'js> for ResultObject<T, E>
+where
+    T: FromJs<'js>,
+    E: FromJs<'js>,
+{
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let object = Object::from_js(ctx, value)?;
+        if object.contains_key("ok")? {
+            object.get("ok").map(|value| ResultObject(Ok(value)))
+        } else if object.contains_key("err")? {
+            object.get("err").map(|value| ResultObject(Err(value)))
+        } else {
+            Err(Error::new_from_js_message(
+                "object",
+                "ResultObject",
+                "Missing `ok` or `err` property",
+            ))
+        }
+    }
+}
+
 /// Convert from JS to either
 #[cfg(feature = "either")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "either")))]
@@ -166,6 +237,9 @@ macro_rules! from_js_impls {
                 $($param: $($pguard)*,)*
             {
                 fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                    if let Some(set) = value.as_object().and_then(Object::as_set) {
+                        return set.values()?.collect::<Result<_>>();
+                    }
                     let array = Array::from_value(value)?;
                     array.iter().collect::<Result<_>>()
                 }
@@ -183,7 +257,17 @@ macro_rules! from_js_impls {
                 V: FromJs<'js>,
                 $($param: $($pguard)*,)*
             {
-                fn from_js(_ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                    if let Some(map) = value.as_object().and_then(Object::as_map) {
+                        return map
+                            .entries::<Value, V>()?
+                            .map(|entry| {
+                                let (key, value) = entry?;
+                                let key = K::from_atom(Atom::from_value(ctx.clone(), &key)?)?;
+                                Ok((key, value))
+                            })
+                            .collect::<Result<_>>();
+                    }
                     let object = Object::from_value(value)?;
                     object.props().collect::<Result<_>>()
                 }
@@ -222,6 +306,20 @@ macro_rules! from_js_impls {
         )*
     };
 
+    // for types parsed from strings
+    (str: $($(#[$meta:meta])* $type:ty,)*) => {
+        $(
+            $(#[$meta])*
+            impl<'js> FromJs<'js> for $type {
+                fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+                    StdString::from_js(ctx, value)?.parse().map_err(|error| {
+                        Error::new_from_js_message("string", stringify!($type), format!("{error}"))
+                    })
+                }
+            }
+        )*
+    };
+
     (@one $($t:tt)*) => { 1 };
 
     (@idx A) => { 0 };
@@ -288,17 +386,17 @@ from_js_impls! {
 
 from_js_impls! {
     list:
-    /// Convert from JS array to Rust vector
+    /// Convert from JS array or set to Rust vector
     Vec,
-    /// Convert from JS array to Rust vector deque
+    /// Convert from JS array or set to Rust vector deque
     VecDeque,
-    /// Convert from JS array to Rust linked list
+    /// Convert from JS array or set to Rust linked list
     LinkedList,
-    /// Convert from JS array to Rust hash set
+    /// Convert from JS array or set to Rust hash set
     HashSet {S: Default + BuildHasher} (Eq + Hash),
-    /// Convert from JS array to Rust btree set
+    /// Convert from JS array or set to Rust btree set
     BTreeSet (Eq + Ord),
-    /// Convert from JS array to Rust index set
+    /// Convert from JS array or set to Rust index set
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexSet {S: Default + BuildHasher} (Eq + Hash),
@@ -306,40 +404,67 @@ from_js_impls! {
 
 from_js_impls! {
     map:
-    /// Convert from JS object to Rust hash map
+    /// Convert from JS object or map to Rust hash map
     HashMap {S: Default + BuildHasher} (Eq + Hash),
-    /// Convert from JS object to Rust btree map
+    /// Convert from JS object or map to Rust btree map
     BTreeMap (Eq + Ord),
-    /// Convert from JS object to Rust index map
+    /// Convert from JS object or map to Rust index map
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexMap {S: Default + BuildHasher} (Eq + Hash),
 }
 
+from_js_impls! {
+    str:
+    /// Convert from a JS string with an IP address
+    IpAddr,
+    /// Convert from a JS string with an IPv4 address
+    Ipv4Addr,
+    /// Convert from a JS string with an IPv6 address
+    Ipv6Addr,
+    /// Convert from a JS string with a socket address
+    SocketAddr,
+    /// Convert from a JS string with an IPv4 socket address
+    SocketAddrV4,
+    /// Convert from a JS string with an IPv6 socket address
+    SocketAddrV6,
+    /// Convert from a JS string with an UUID
+    #[cfg(feature = "uuid")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "uuid")))]
+    uuid::Uuid,
+}
+
 impl<'js> FromJs<'js> for f32 {
     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
         f64::from_js(ctx, value).map(|value| value as _)
     }
 }
 
-fn date_to_millis<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> Result<i64> {
-    let global = ctx.globals();
-    let date_ctor: Object = global.get("Date")?;
-
-    let value = Object::from_value(value)?;
-
-    if !value.is_instance_of(&date_ctor) {
-        return Err(Error::new_from_js("Object", "Date"));
+/// Convert from a JS number of milliseconds
+impl<'js> FromJs<'js> for Duration {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let secs = f64::from_js(ctx, value)? / 1000.0;
+        if secs.is_nan() || secs < 0.0 {
+            return Err(Error::new_from_js_message(
+                "number",
+                "Duration",
+                "Duration must not be negative",
+            ));
+        }
+        if secs >= u64::MAX as f64 {
+            return Err(Error::new_from_js_message("number", "Duration", "Overflow"));
+        }
+        Ok(Duration::from_secs_f64(secs))
     }
+}
 
-    let get_time_fn: crate::Function = value.get("getTime")?;
-
-    get_time_fn.call((crate::function::This(value),))
+fn date_to_millis<'js>(ctx: &Ctx<'js>, value: Value<'js>, to: &'static str) -> Result<i64> {
+    Date::from_js(ctx, value)?.millis(to)
 }
 
 impl<'js> FromJs<'js> for SystemTime {
     fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<SystemTime> {
-        let millis = date_to_millis(ctx, value)?;
+        let millis = date_to_millis(ctx, value, "SystemTime")?;
 
         if millis >= 0 {
             // since unix epoch
@@ -367,7 +492,7 @@ macro_rules! chrono_from_js_impls {
                 fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<chrono::DateTime<chrono::$type>> {
                     use chrono::TimeZone;
 
-                    let millis = date_to_millis(ctx, value)?;
+                    let millis = date_to_millis(ctx, value, "chrono::DateTime")?;
 
                     chrono::$type.timestamp_millis_opt(millis).single()
                         .ok_or_else(|| {
@@ -384,6 +509,17 @@ chrono_from_js_impls! {
     Local;
 }
 
+#[cfg(feature = "time")]
+impl<'js> FromJs<'js> for time::OffsetDateTime {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<time::OffsetDateTime> {
+        let millis = date_to_millis(ctx, value, "time::OffsetDateTime")?;
+
+        time::OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000).map_err(|_| {
+            Error::new_from_js_message("Date", "time::OffsetDateTime", "Invalid timestamp")
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
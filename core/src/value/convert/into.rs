@@ -1,13 +1,18 @@
 use crate::{
-    convert::{IteratorJs, List},
-    value::Constructor,
-    Array, Ctx, Error, IntoAtom, IntoJs, Object, Result, StdResult, StdString, String, Value,
+    convert::{IntoMultiValue, IteratorJs, List, MultiValue, Nullable, ResultObject},
+    function::{Null, UndefinedAsNull},
+    value::Date,
+    Array, Ctx, Error, IntoJs, Map, Object, Result, Set, StdResult, StdString, String, Value,
 };
 use std::{
+    borrow::Cow,
     cell::{Cell, RefCell},
     collections::{BTreeMap, BTreeSet, HashMap, HashSet, LinkedList, VecDeque},
-    sync::{Mutex, RwLock},
-    time::SystemTime,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime},
 };
 
 #[cfg(feature = "either")]
@@ -46,6 +51,41 @@ impl<'js> IntoJs<'js> for &str {
     }
 }
 
+impl<'js> IntoJs<'js> for Cow<'_, str> {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
+impl<'js> IntoJs<'js> for &Cow<'_, str> {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
+/// Convert into a JS string, fails if the path is not valid UTF-8
+impl<'js> IntoJs<'js> for &Path {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.to_str()
+            .ok_or_else(|| Error::new_into_js_message("Path", "string", "Path is not valid UTF-8"))?
+            .into_js(ctx)
+    }
+}
+
+/// Convert into a JS string, fails if the path is not valid UTF-8
+impl<'js> IntoJs<'js> for PathBuf {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_path().into_js(ctx)
+    }
+}
+
+/// Convert into a JS string, fails if the path is not valid UTF-8
+impl<'js> IntoJs<'js> for &PathBuf {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_path().into_js(ctx)
+    }
+}
+
 impl<'js, T> IntoJs<'js> for &[T]
 where
     for<'a> &'a T: IntoJs<'js>,
@@ -93,6 +133,69 @@ where
     }
 }
 
+/// Convert `None` into `null` instead of `undefined`
+impl<'js, T> IntoJs<'js> for Null<T>
+where
+    T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(match self.0 {
+            Some(value) => value.into_js(ctx)?,
+            _ => Value::new_null(ctx.clone()),
+        })
+    }
+}
+
+/// Convert `None` into `null` instead of `undefined`
+impl<'js, T> IntoJs<'js> for &Null<T>
+where
+    for<'a> &'a T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(match &self.0 {
+            Some(value) => value.into_js(ctx)?,
+            _ => Value::new_null(ctx.clone()),
+        })
+    }
+}
+
+/// Convert `undefined` into `null`
+impl<'js, T> IntoJs<'js> for UndefinedAsNull<T>
+where
+    T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let value = self.0.into_js(ctx)?;
+        Ok(if value.is_undefined() {
+            Value::new_null(ctx.clone())
+        } else {
+            value
+        })
+    }
+}
+
+impl<'js, T> IntoJs<'js> for Nullable<T>
+where
+    T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(match self {
+            Nullable::Value(value) => value.into_js(ctx)?,
+            Nullable::Null => Value::new_null(ctx.clone()),
+            Nullable::Undefined => Value::new_undefined(ctx.clone()),
+        })
+    }
+}
+
+impl<'js, T> IntoJs<'js> for &Nullable<T>
+where
+    for<'a> &'a T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
 impl<'js, T, E> IntoJs<'js> for StdResult<T, E>
 where
     T: IntoJs<'js>,
@@ -116,6 +219,38 @@ where
     }
 }
 
+/// Convert into a JS object with either an `ok` or an `err` property
+impl<'js, T, E> IntoJs<'js> for ResultObject<T, E>
+where
+    T: IntoJs<'js>,
+    E: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let object = Object::new(ctx.clone())?;
+        match self.0 {
+            Ok(value) => object.set("ok", value)?,
+            Err(error) => object.set("err", error)?,
+        }
+        Ok(object.into_value())
+    }
+}
+
+/// Convert into a JS object with either an `ok` or an `err` property
+impl<'js, T, E> IntoJs<'js> for &ResultObject<T, E>
+where
+    for<'a> &'a T: IntoJs<'js>,
+    for<'a> &'a E: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let object = Object::new(ctx.clone())?;
+        match &self.0 {
+            Ok(value) => object.set("ok", value)?,
+            Err(error) => object.set("err", error)?,
+        }
+        Ok(object.into_value())
+    }
+}
+
 /// Convert the either into JS
 #[cfg(feature = "either")]
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "either")))]
@@ -166,6 +301,42 @@ where
     }
 }
 
+impl<'js, T> IntoJs<'js> for Rc<T>
+where
+    for<'r> &'r T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
+impl<'js, T> IntoJs<'js> for &Rc<T>
+where
+    for<'r> &'r T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
+impl<'js, T> IntoJs<'js> for Arc<T>
+where
+    for<'r> &'r T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
+impl<'js, T> IntoJs<'js> for &Arc<T>
+where
+    for<'r> &'r T: IntoJs<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.as_ref().into_js(ctx)
+    }
+}
+
 impl<'js, T> IntoJs<'js> for &Cell<T>
 where
     T: IntoJs<'js> + Copy,
@@ -224,6 +395,19 @@ where
     }
 }
 
+impl<'js, T> IntoJs<'js> for MultiValue<T>
+where
+    T: IntoMultiValue<'js>,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.0
+            .into_multi_value(ctx)?
+            .into_iter()
+            .collect_js(ctx)
+            .map(|Array(value)| value.into_value())
+    }
+}
+
 macro_rules! into_js_impls {
     // for cells
     (cell: $($type:ident,)*) => {
@@ -254,6 +438,26 @@ macro_rules! into_js_impls {
                     Ok(array.into_value())
                 }
             }
+
+            impl<'js, $($type,)*> IntoJs<'js> for ($($type,)*)
+            where
+                $($type: IntoJs<'js>,)*
+            {
+                fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+                    List(self).into_js(ctx)
+                }
+            }
+
+            impl<'js, $($type,)*> IntoMultiValue<'js> for ($($type,)*)
+            where
+                $($type: IntoJs<'js>,)*
+            {
+                #[allow(non_snake_case)]
+                fn into_multi_value(self, ctx: &Ctx<'js>) -> Result<Vec<Value<'js>>> {
+                    let ($($type,)*) = self;
+                    Ok(vec![$($type.into_js(ctx)?,)*])
+                }
+            }
         )*
     };
 
@@ -286,32 +490,53 @@ macro_rules! into_js_impls {
         )*
     };
 
+    // for set-like Rust types
+    (set: $($(#[$meta:meta])* $type:ident $({$param:ident})*,)*) => {
+        $(
+            $(#[$meta])*
+            impl<'js, T $(,$param)*> IntoJs<'js> for $type<T $(,$param)*>
+            where
+                T: IntoJs<'js>,
+            {
+                fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+                    Set::from_values(ctx.clone(), self).map(Set::into_value)
+                }
+            }
+
+            $(#[$meta])*
+            impl<'js, T $(,$param)*> IntoJs<'js> for &$type<T $(,$param)*>
+            where
+                for<'a> &'a T: IntoJs<'js>,
+            {
+                fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+                    Set::from_values(ctx.clone(), self).map(Set::into_value)
+                }
+            }
+        )*
+    };
+
     // for map-like Rust types
     (map: $($(#[$meta:meta])* $type:ident $({$param:ident})*,)*) => {
         $(
             $(#[$meta])*
             impl<'js, K, V $(,$param)*> IntoJs<'js> for $type<K, V $(,$param)*>
             where
-                K: IntoAtom<'js>,
+                K: IntoJs<'js>,
                 V: IntoJs<'js>,
             {
                 fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
-                    self.into_iter()
-                        .collect_js(ctx)
-                        .map(|Object(value)| value)
+                    Map::from_entries(ctx.clone(), self).map(Map::into_value)
                 }
             }
 
             $(#[$meta])*
             impl<'js, K, V $(,$param)*> IntoJs<'js> for &$type<K, V $(,$param)*>
             where
-                for<'a> &'a K: IntoAtom<'js>,
+                for<'a> &'a K: IntoJs<'js>,
                 for<'a> &'a V: IntoJs<'js>,
             {
                 fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
-                    self.into_iter()
-                        .collect_js(ctx)
-                        .map(|Object(value)| value)
+                    Map::from_entries(ctx.clone(), self).map(Map::into_value)
                 }
             }
         )*
@@ -361,6 +586,25 @@ macro_rules! into_js_impls {
         )*
     };
 
+    // for types converted to strings
+    (str: $($(#[$meta:meta])* $type:ty,)*) => {
+        $(
+            $(#[$meta])*
+            impl<'js> IntoJs<'js> for $type {
+                fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+                    self.to_string().into_js(ctx)
+                }
+            }
+
+            $(#[$meta])*
+            impl<'js> IntoJs<'js> for &$type {
+                fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+                    self.to_string().into_js(ctx)
+                }
+            }
+        )*
+    };
+
     (@idx A) => { 0 };
     (@idx B) => { 1 };
     (@idx C) => { 2 };
@@ -413,11 +657,15 @@ into_js_impls! {
     VecDeque,
     /// Convert from Rust linked list to JS array
     LinkedList,
-    /// Convert from Rust hash set to JS array
+}
+
+into_js_impls! {
+    set:
+    /// Convert from Rust hash set to JS set
     HashSet {S},
-    /// Convert from Rust btree set to JS array
+    /// Convert from Rust btree set to JS set
     BTreeSet,
-    /// Convert from Rust index set to JS array
+    /// Convert from Rust index set to JS set
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexSet {S},
@@ -425,11 +673,11 @@ into_js_impls! {
 
 into_js_impls! {
     map:
-    /// Convert from Rust hash map to JS object
+    /// Convert from Rust hash map to JS map
     HashMap {S},
-    /// Convert from Rust btree map to JS object
+    /// Convert from Rust btree map to JS map
     BTreeMap,
-    /// Convert from Rust index map to JS object
+    /// Convert from Rust index map to JS map
     #[cfg(feature = "indexmap")]
     #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "indexmap")))]
     IndexMap {S},
@@ -447,10 +695,42 @@ into_js_impls! {
     i32 f64 => i64 u32 u64 usize isize,
 }
 
-fn millis_to_date<'js>(ctx: &Ctx<'js>, millis: i64) -> Result<Value<'js>> {
-    let date_ctor: Constructor = ctx.globals().get("Date")?;
+into_js_impls! {
+    str:
+    /// Convert into a JS string
+    IpAddr,
+    /// Convert into a JS string
+    Ipv4Addr,
+    /// Convert into a JS string
+    Ipv6Addr,
+    /// Convert into a JS string
+    SocketAddr,
+    /// Convert into a JS string
+    SocketAddrV4,
+    /// Convert into a JS string
+    SocketAddrV6,
+    /// Convert into a JS string in the hyphenated form
+    #[cfg(feature = "uuid")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "uuid")))]
+    uuid::Uuid,
+}
+
+/// Convert into a JS number of milliseconds
+impl<'js> IntoJs<'js> for Duration {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(Value::new_float(ctx.clone(), self.as_secs_f64() * 1000.0))
+    }
+}
 
-    date_ctor.construct((millis,))
+/// Convert into a JS number of milliseconds
+impl<'js> IntoJs<'js> for &Duration {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        (*self).into_js(ctx)
+    }
+}
+
+fn millis_to_date<'js>(ctx: &Ctx<'js>, millis: i64) -> Result<Value<'js>> {
+    Date::new(ctx.clone(), millis as f64).map(Date::into_value)
 }
 
 impl<'js> IntoJs<'js> for SystemTime {
@@ -472,7 +752,10 @@ impl<'js> IntoJs<'js> for SystemTime {
             }
             // before unix epoch
             Err(error) => {
-                let millis = error.duration().as_millis();
+                let duration = error.duration();
+                // Round towards negative infinity like for times after the epoch.
+                let millis =
+                    duration.as_millis() + (duration.subsec_nanos() % 1_000_000 != 0) as u128;
 
                 if millis > -(i64::MIN as i128) as _ {
                     return Err(Error::new_into_js_message(
@@ -497,6 +780,17 @@ impl<'js, Tz: chrono::TimeZone> IntoJs<'js> for chrono::DateTime<Tz> {
     }
 }
 
+#[cfg(feature = "time")]
+impl<'js> IntoJs<'js> for time::OffsetDateTime {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        // Round towards negative infinity like for dates after the epoch.
+        millis_to_date(
+            ctx,
+            self.unix_timestamp_nanos().div_euclid(1_000_000) as i64,
+        )
+    }
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -530,7 +824,113 @@ mod test {
             let globs = ctx.globals();
             globs.set("ts", ts.into_js(&ctx).unwrap()).unwrap();
             let res: i64 = ctx.eval("ts.getTime()").unwrap();
-            assert_eq!(-(millis as i64), res as _);
+            assert_eq!(-(millis as i64), res);
+        });
+
+        let ts = SystemTime::UNIX_EPOCH - Duration::from_micros(1500);
+        ctx.with(|ctx| {
+            let globs = ctx.globals();
+            globs.set("ts", ts.into_js(&ctx).unwrap()).unwrap();
+            let res: i64 = ctx.eval("ts.getTime()").unwrap();
+            assert_eq!(res, -2);
+        });
+    }
+
+    #[test]
+    fn std_types() {
+        use crate::{convert::ResultObject, Context, Runtime};
+        use std::{
+            borrow::Cow,
+            net::{IpAddr, SocketAddr},
+            path::PathBuf,
+            rc::Rc,
+            sync::Arc,
+            time::Duration,
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let globs = ctx.globals();
+
+            globs.set("path", PathBuf::from("dir/file.js")).unwrap();
+            let res: String = ctx.eval("path").unwrap();
+            assert_eq!(res, "dir/file.js");
+            let res: PathBuf = ctx.eval("path + '.map'").unwrap();
+            assert_eq!(res, PathBuf::from("dir/file.js.map"));
+            #[cfg(unix)]
+            {
+                use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+                let path = PathBuf::from(OsStr::from_bytes(b"\xff"));
+                assert!(globs.set("path", path).unwrap_err().is_into_js());
+            }
+
+            globs.set("duration", Duration::from_micros(1500)).unwrap();
+            let res: f64 = ctx.eval("duration").unwrap();
+            assert_eq!(res, 1.5);
+            let res: Duration = ctx.eval("duration * 2").unwrap();
+            assert_eq!(res, Duration::from_millis(3));
+            assert!(ctx.eval::<Duration, _>("-1").unwrap_err().is_from_js());
+            assert!(ctx.eval::<Duration, _>("NaN").unwrap_err().is_from_js());
+            assert!(ctx
+                .eval::<Duration, _>("Infinity")
+                .unwrap_err()
+                .is_from_js());
+
+            let ip: IpAddr = "::1".parse().unwrap();
+            globs.set("ip", ip).unwrap();
+            let res: String = ctx.eval("ip").unwrap();
+            assert_eq!(res, "::1");
+            let res: SocketAddr = ctx.eval("`[${ip}]:8080`").unwrap();
+            assert_eq!(res, SocketAddr::new(ip, 8080));
+            assert!(ctx
+                .eval::<IpAddr, _>("'localhost'")
+                .unwrap_err()
+                .is_from_js());
+
+            globs.set("cow", Cow::Borrowed("borrowed")).unwrap();
+            let res: Cow<str> = ctx.eval("cow").unwrap();
+            assert_eq!(res, "borrowed");
+
+            globs.set("rc", Rc::new(vec![1, 2, 3])).unwrap();
+            globs.set("arc", Arc::new("shared".to_string())).unwrap();
+            let res: String = ctx.eval("arc + rc.length").unwrap();
+            assert_eq!(res, "shared3");
+
+            let ok: ResultObject<i32, String> = ResultObject(Ok(1));
+            let err: ResultObject<i32, String> = ResultObject(Err("failed".into()));
+            globs.set("ok", ok).unwrap();
+            globs.set("err", &err).unwrap();
+            let res: bool = ctx
+                .eval("ok.ok === 1 && !('err' in ok) && err.err === 'failed' && !('ok' in err)")
+                .unwrap();
+            assert!(res);
+            let res: ResultObject<i32, String> = ctx.eval("err").unwrap();
+            assert_eq!(res, err);
+            let res: ResultObject<(), String> = ctx.eval("({ ok: undefined })").unwrap();
+            assert_eq!(res.0, Ok(()));
+            let res = ctx.eval::<ResultObject<i32, String>, _>("({})");
+            assert!(res.unwrap_err().is_from_js());
+        });
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_conversion() {
+        use crate::{Context, Runtime};
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let id = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+            ctx.globals().set("id", id).unwrap();
+            let res: String = ctx.eval("id").unwrap();
+            assert_eq!(res, "67e55044-10b1-426f-9247-bb680e5fe0c8");
+            let res: uuid::Uuid = ctx.eval("id.toUpperCase()").unwrap();
+            assert_eq!(res, id);
+            assert!(ctx.eval::<uuid::Uuid, _>("'abc'").unwrap_err().is_from_js());
         });
     }
 
@@ -553,4 +953,53 @@ mod test {
             assert_eq!(millis, res);
         });
     }
+
+    #[test]
+    fn nullable() {
+        use crate::{
+            convert::Nullable,
+            function::{Null, UndefinedAsNull},
+            Context, IntoJs, Object, Result, Runtime,
+        };
+
+        let runtime = Runtime::new().unwrap();
+        let ctx = Context::full(&runtime).unwrap();
+
+        ctx.with(|ctx| {
+            let patch: Object = ctx.eval("({ a: 1, b: null })").unwrap();
+            let a: Nullable<i32> = patch.get("a").unwrap();
+            let b: Nullable<i32> = patch.get("b").unwrap();
+            let c: Nullable<i32> = patch.get("c").unwrap();
+            assert_eq!(a, Nullable::Value(1));
+            assert_eq!(b, Nullable::Null);
+            assert_eq!(c, Nullable::Undefined);
+            assert!(patch.get::<_, Nullable<i32>>("a").unwrap().is_value());
+            assert!(ctx.eval::<Nullable<i32>, _>("'a'").is_err());
+
+            let globs = ctx.globals();
+            globs.set("a", Nullable::Value(2)).unwrap();
+            let b = Nullable::<i32>::Null;
+            globs.set("b", (&b).into_js(&ctx).unwrap()).unwrap();
+            globs.set("c", Nullable::<i32>::Undefined).unwrap();
+            let res: bool = ctx
+                .eval("a === 2 && b === null && c === undefined")
+                .unwrap();
+            assert!(res);
+
+            globs.set("d", Null::<i32>(None)).unwrap();
+            let e = Null(Some(3));
+            globs.set("e", (&e).into_js(&ctx).unwrap()).unwrap();
+            let res: bool = ctx.eval("d === null && e === 3").unwrap();
+            assert!(res);
+            let res: Null<i32> = ctx.eval("undefined").unwrap();
+            assert_eq!(res.into_inner(), None);
+
+            let found: Result<Option<i32>> = Ok(None);
+            globs.set("f", UndefinedAsNull(found)).unwrap();
+            globs.set("g", UndefinedAsNull(Some(()))).unwrap();
+            globs.set("h", UndefinedAsNull(Some(4))).unwrap();
+            let res: bool = ctx.eval("f === null && g === null && h === 4").unwrap();
+            assert!(res);
+        });
+    }
 }
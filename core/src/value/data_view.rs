@@ -0,0 +1,353 @@
+//! JavaScript `DataView` functionality.
+
+use std::ptr;
+
+use crate::{
+    atom::PredefinedAtom, qjs, ArrayBuffer, Ctx, Error, Exception, FromJs, IntoJs, Object, Outlive,
+    Result, Value,
+};
+
+use super::Constructor;
+
+/// Rust representation of a JavaScript object of class DataView.
+///
+/// The accessors read and write the bytes of the underlying [`ArrayBuffer`] in place, they take
+/// a byte offset into the view and, for values larger than a byte, whether the value is stored
+/// little endian, just like the JavaScript methods of the same name.
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "array-buffer")))]
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+pub struct DataView<'js> {
+    object: Object<'js>,
+    buffer: ArrayBuffer<'js>,
+    offset: usize,
+    len: usize,
+}
+
+unsafe impl<'js> Outlive<'js> for DataView<'js> {
+    type Target<'to> = DataView<'to>;
+}
+
+macro_rules! accessors {
+    ($($get:ident $set:ident: $ty:ty,)*) => {$(
+        #[doc = concat!("Read a `", stringify!($ty), "` at the given byte offset.")]
+        pub fn $get(&self, offset: usize, little_endian: bool) -> Result<$ty> {
+            let bytes = self.read(offset)?;
+            Ok(if little_endian {
+                <$ty>::from_le_bytes(bytes)
+            } else {
+                <$ty>::from_be_bytes(bytes)
+            })
+        }
+
+        #[doc = concat!("Write a `", stringify!($ty), "` at the given byte offset.")]
+        pub fn $set(&self, offset: usize, value: $ty, little_endian: bool) -> Result<()> {
+            self.write(
+                offset,
+                if little_endian {
+                    value.to_le_bytes()
+                } else {
+                    value.to_be_bytes()
+                },
+            )
+        }
+    )*};
+}
+
+impl<'js> DataView<'js> {
+    /// Create a view on a whole array buffer.
+    pub fn new(buffer: ArrayBuffer<'js>) -> Result<Self> {
+        let ctor: Constructor = buffer.ctx().globals().get(PredefinedAtom::DataView)?;
+        ctor.construct((buffer,))
+    }
+
+    /// Create a view on `len` bytes of an array buffer starting at the byte `offset`.
+    pub fn new_range(buffer: ArrayBuffer<'js>, offset: usize, len: usize) -> Result<Self> {
+        let ctor: Constructor = buffer.ctx().globals().get(PredefinedAtom::DataView)?;
+        ctor.construct((buffer, offset, len))
+    }
+
+    /// Get the length of the view in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the offset of the view into its buffer in bytes.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Get the underlying ArrayBuffer.
+    pub fn buffer(&self) -> &ArrayBuffer<'js> {
+        &self.buffer
+    }
+
+    /// Returns the bytes of the buffer the view is on,
+    ///
+    /// Returns `None` if the buffer is detached.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.buffer
+            .as_bytes()?
+            .get(self.offset..self.offset.checked_add(self.len)?)
+    }
+
+    /// Read a `u8` at the given byte offset.
+    pub fn get_u8(&self, offset: usize) -> Result<u8> {
+        self.read(offset).map(u8::from_ne_bytes)
+    }
+
+    /// Write a `u8` at the given byte offset.
+    pub fn set_u8(&self, offset: usize, value: u8) -> Result<()> {
+        self.write(offset, value.to_ne_bytes())
+    }
+
+    /// Read an `i8` at the given byte offset.
+    pub fn get_i8(&self, offset: usize) -> Result<i8> {
+        self.read(offset).map(i8::from_ne_bytes)
+    }
+
+    /// Write an `i8` at the given byte offset.
+    pub fn set_i8(&self, offset: usize, value: i8) -> Result<()> {
+        self.write(offset, value.to_ne_bytes())
+    }
+
+    accessors! {
+        get_u16 set_u16: u16,
+        get_i16 set_i16: i16,
+        get_u32 set_u32: u32,
+        get_i32 set_i32: i32,
+        get_u64 set_u64: u64,
+        get_i64 set_i64: i64,
+        get_f32 set_f32: f32,
+        get_f64 set_f64: f64,
+    }
+
+    /// Returns a pointer to `N` bytes at the offset into the view.
+    ///
+    /// Throws a `RangeError` if they are out of the bounds of the view, or a `TypeError` if the
+    /// buffer is detached.
+    fn ptr<const N: usize>(&self, offset: usize) -> Result<*mut u8> {
+        let ctx = self.object.ctx();
+        let raw = self
+            .buffer
+            .as_raw()
+            .ok_or_else(|| Exception::throw_type(ctx, "ArrayBuffer is detached"))?;
+        let in_bounds = offset
+            .checked_add(N)
+            .filter(|end| *end <= self.len)
+            .and_then(|_| self.offset.checked_add(self.len))
+            .map(|end| end <= raw.len)
+            .unwrap_or(false);
+        if !in_bounds {
+            return Err(Exception::throw_range(ctx, "out of bound"));
+        }
+        Ok(unsafe { raw.ptr.as_ptr().add(self.offset + offset) })
+    }
+
+    fn read<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        let ptr = self.ptr::<N>(offset)?;
+        let mut bytes = [0; N];
+        unsafe { ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), N) };
+        Ok(bytes)
+    }
+
+    fn write<const N: usize>(&self, offset: usize, bytes: [u8; N]) -> Result<()> {
+        let ptr = self.ptr::<N>(offset)?;
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, N) };
+        Ok(())
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.object.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.object.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Result<Self> {
+        Self::from_object(Object::from_value(value)?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.object
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.object
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Result<Self> {
+        if !object.is_data_view() {
+            return Err(Error::new_from_js("object", "DataView"));
+        }
+        // The internal slots of a view can't change, so they are only read once.
+        let ctx = object.ctx();
+        let mut offset = 0;
+        let mut len = 0;
+        let buffer = unsafe {
+            let val = qjs::JS_GetDataViewBuffer(
+                ctx.as_ptr(),
+                object.as_js_value(),
+                &mut offset,
+                &mut len,
+            );
+            let val = ctx.handle_exception(val)?;
+            ArrayBuffer(Object(Value::from_js_value(ctx.clone(), val)))
+        };
+        Ok(DataView {
+            buffer,
+            offset: offset as usize,
+            len: len as usize,
+            object,
+        })
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for DataView<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for DataView<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        Self::from_value(value)
+    }
+}
+
+impl<'js> IntoJs<'js> for DataView<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is a [`DataView`].
+    ///
+    /// Objects which only have the prototype of `DataView` are not.
+    pub fn is_data_view(&self) -> bool {
+        unsafe { qjs::JS_IsDataView(self.as_js_value()) != 0 }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let view: DataView = ctx
+                .eval(
+                    r#"
+                        let view = new DataView(new ArrayBuffer(16), 2, 14);
+                        view.setUint32(0, 0xCAFEBEEF);
+                        view.setInt16(4, -2, true);
+                        view.setFloat64(6, 1.5);
+                        view
+                    "#,
+                )
+                .unwrap();
+            assert_eq!((view.offset(), view.len()), (2, 14));
+            assert_eq!(view.buffer().len(), 16);
+            assert_eq!(view.get_u32(0, false).unwrap(), 0xCAFEBEEF);
+            assert_eq!(view.get_u32(0, true).unwrap(), 0xEFBEFECA);
+            assert_eq!(view.get_u8(0).unwrap(), 0xCA);
+            assert_eq!(view.get_i16(4, true).unwrap(), -2);
+            assert_eq!(view.get_f64(6, false).unwrap(), 1.5);
+            assert_eq!(&view.as_bytes().unwrap()[..2], &[0xCA, 0xFE]);
+
+            let err = view.get_u32(12, false).unwrap_err();
+            assert!(err.is_exception());
+            let err = ctx.catch().into_exception().unwrap();
+            assert_eq!(
+                err.get::<_, std::string::String>("name").unwrap(),
+                "RangeError"
+            );
+            assert!(view.get_u8(usize::MAX).is_err());
+            ctx.catch();
+
+            assert!(ctx.eval::<DataView, _>("new Uint8Array(2)").is_err());
+        });
+    }
+
+    #[test]
+    fn forged() {
+        test_with(|ctx| {
+            let source = r#"
+                Object.create(DataView.prototype, {
+                    buffer: { value: new ArrayBuffer(16) },
+                    byteOffset: { value: 2 ** 63 },
+                    byteLength: { value: 2 ** 63 },
+                })
+            "#;
+            let object: Object = ctx.eval(source).unwrap();
+            assert!(!object.is_data_view());
+            assert!(ctx.eval::<DataView, _>(source).is_err());
+
+            // The properties of a real view don't matter either.
+            let view: DataView = ctx
+                .eval(
+                    r#"
+                        const view = new DataView(new ArrayBuffer(8), 4);
+                        Object.defineProperty(view, "byteLength", { value: 2 ** 63 });
+                        view
+                    "#,
+                )
+                .unwrap();
+            assert_eq!((view.offset(), view.len()), (4, 4));
+            assert!(view.get_u32(4, true).is_err());
+            ctx.catch();
+        });
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let buffer = ArrayBuffer::new(ctx.clone(), vec![0u8; 8]).unwrap();
+            let view = DataView::new_range(buffer.clone(), 4, 4).unwrap();
+            view.set_u16(0, 0x1234, true).unwrap();
+            view.set_i8(2, -1).unwrap();
+            ctx.globals().set("view", view.clone()).unwrap();
+            let res: bool = ctx
+                .eval("view.getUint16(0, true) === 0x1234 && view.getInt8(2) === -1")
+                .unwrap();
+            assert!(res);
+            assert_eq!(
+                buffer.as_bytes().unwrap(),
+                &[0, 0, 0, 0, 0x34, 0x12, 0xFF, 0]
+            );
+
+            let mut buffer = buffer;
+            buffer.detach();
+            assert!(view.as_bytes().is_none());
+            assert!(view.get_u8(0).is_err());
+            let err = ctx.catch().into_exception().unwrap();
+            assert_eq!(
+                err.get::<_, std::string::String>("name").unwrap(),
+                "TypeError"
+            );
+        });
+    }
+}
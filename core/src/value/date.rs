@@ -0,0 +1,285 @@
+//! JavaScript `Date` functionality.
+
+use std::ops::Deref;
+
+use crate::{qjs, Ctx, Error, FromJs, IntoJs, Object, Outlive, Result, StdString, Value};
+
+/// Rust representation of a JavaScript object of class Date.
+///
+/// Besides this type dates can also be converted directly from and into
+/// [`SystemTime`](std::time::SystemTime) and, with the respective features enabled,
+/// `chrono::DateTime` and `time::OffsetDateTime`.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Date<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> Outlive<'js> for Date<'js> {
+    type Target<'to> = Date<'to>;
+}
+
+impl<'js> Date<'js> {
+    /// Create a new date from the number of milliseconds since the unix epoch.
+    ///
+    /// The time is clipped like the `Date` constructor does, times outside of the representable
+    /// range result in an invalid date.
+    pub fn new(ctx: Ctx<'js>, millis: f64) -> Result<Self> {
+        unsafe {
+            let val = qjs::JS_NewDate(ctx.as_ptr(), millis);
+            let val = ctx.handle_exception(val)?;
+            Ok(Date(Object::from_js_value(ctx, val)))
+        }
+    }
+
+    /// Create a new date with the current time, from the clock set with
+    /// [`Runtime::set_clock`](crate::Runtime::set_clock) if any.
+    pub fn now(ctx: Ctx<'js>) -> Result<Self> {
+        let millis = unsafe { qjs::JS_DateNow(ctx.as_ptr()) };
+        Self::new(ctx, millis as f64)
+    }
+
+    /// Returns the number of milliseconds since the unix epoch, `NaN` if the date is invalid.
+    pub fn get_time(&self) -> Result<f64> {
+        Ok(unsafe { qjs::JS_GetDateTime(self.as_js_value()) })
+    }
+
+    /// Returns whether the date represents a valid time.
+    pub fn is_valid(&self) -> Result<bool> {
+        self.get_time().map(|time| !time.is_nan())
+    }
+
+    /// Returns the date formatted as an ISO 8601 string.
+    ///
+    /// Returns an error if the date is invalid.
+    pub fn to_iso_string(&self) -> Result<StdString> {
+        let millis = self.millis("String")?;
+        let days = millis.div_euclid(MILLIS_PER_DAY);
+        let time = millis.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        let year = if (0..=9999).contains(&year) {
+            format!("{year:04}")
+        } else {
+            format!("{year:+07}")
+        };
+        Ok(format!(
+            "{year}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            time / 3_600_000,
+            time / 60_000 % 60,
+            time / 1000 % 60,
+            time % 1000,
+        ))
+    }
+
+    /// Returns the number of whole milliseconds since the unix epoch.
+    pub(crate) fn millis(&self, to: &'static str) -> Result<i64> {
+        let time = self.get_time()?;
+        if time.is_nan() {
+            return Err(Error::new_from_js_message("Date", to, "Invalid date"));
+        }
+        Ok(time as i64)
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_date().then_some(Date(object))
+    }
+}
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// Returns the proleptic gregorian year, month and day of a number of days since the unix epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Shift the epoch to 0000-03-01 so that leap days are at the end of each 400 year era.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl<'js> Deref for Date<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Date<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Date<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Date<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "Date"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for Date<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`Date`].
+    pub fn is_date(&self) -> bool {
+        unsafe { qjs::JS_IsDate(self.as_js_value()) != 0 }
+    }
+
+    /// Interpret as [`Date`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_date(&self) -> &Date<'js> {
+        &*(self as *const Object as *const Date)
+    }
+
+    /// Turn the object into a date if the object is an instance of [`Date`].
+    pub fn as_date(&self) -> Option<&Date<'js>> {
+        self.is_date().then_some(unsafe { self.ref_date() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let date: Date = ctx.eval("new Date(Date.UTC(2020, 0, 2))").unwrap();
+            assert_eq!(date.get_time().unwrap(), 1577923200000.0);
+            assert_eq!(date.to_iso_string().unwrap(), "2020-01-02T00:00:00.000Z");
+
+            let date: Date = ctx.eval("new Date(NaN)").unwrap();
+            assert!(!date.is_valid().unwrap());
+            assert!(date.to_iso_string().is_err());
+
+            let obj: Object = ctx.eval("({ getTime() { return 0 } })").unwrap();
+            assert!(!obj.is_date());
+
+            for iso in [
+                "1969-12-31T23:59:59.999Z",
+                "0000-03-01T00:00:00.000Z",
+                "-000001-12-31T12:00:00.000Z",
+                "+275760-09-13T00:00:00.000Z",
+            ] {
+                let date: Date = ctx.eval(format!("new Date('{iso}')")).unwrap();
+                assert_eq!(date.to_iso_string().unwrap(), iso);
+            }
+        })
+    }
+
+    #[test]
+    fn ignores_prototype() {
+        test_with(|ctx| {
+            let date: Date = ctx
+                .eval(
+                    r#"
+                    const date = new Date(0);
+                    Date.prototype.getTime = () => 42;
+                    Date.prototype.toISOString = () => "";
+                    globalThis.Date = function() {};
+                    date
+                    "#,
+                )
+                .unwrap();
+            assert!(date.is_date());
+            assert_eq!(date.get_time().unwrap(), 0.0);
+            assert_eq!(date.to_iso_string().unwrap(), "1970-01-01T00:00:00.000Z");
+            assert_eq!(
+                Date::new(ctx.clone(), 1.0).unwrap().get_time().unwrap(),
+                1.0
+            );
+
+            let fake: Object = ctx
+                .eval("Object.setPrototypeOf({}, Object.getPrototypeOf(date))")
+                .unwrap();
+            assert!(!fake.is_date());
+        })
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let date = Date::new(ctx.clone(), 1577923200000.0).unwrap();
+            ctx.globals().set("date", date).unwrap();
+            let res: bool = ctx
+                .eval("date instanceof Date && date.getUTCFullYear() == 2020")
+                .unwrap();
+            assert!(res);
+
+            let now = Date::now(ctx.clone()).unwrap();
+            assert!(now.get_time().unwrap() > 1577923200000.0);
+        })
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_conversion() {
+        test_with(|ctx| {
+            let time =
+                time::OffsetDateTime::from_unix_timestamp_nanos(1_577_934_245_006_000_000).unwrap();
+            ctx.globals().set("date", time).unwrap();
+            let res: StdString = ctx.eval("date.toISOString()").unwrap();
+            assert_eq!(res, "2020-01-02T03:04:05.006Z");
+            let back: time::OffsetDateTime = ctx.eval("date").unwrap();
+            assert_eq!(back, time);
+
+            let time = time::OffsetDateTime::from_unix_timestamp_nanos(-1_500_000).unwrap();
+            ctx.globals().set("date", time).unwrap();
+            let res: StdString = ctx.eval("date.toISOString()").unwrap();
+            assert_eq!(res, "1969-12-31T23:59:59.998Z");
+            let back: time::OffsetDateTime = ctx.eval("date").unwrap();
+            assert_eq!(back.unix_timestamp_nanos(), -2_000_000);
+        })
+    }
+}
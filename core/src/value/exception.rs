@@ -2,6 +2,9 @@ use std::{error::Error as ErrorTrait, fmt};
 
 use crate::{atom::PredefinedAtom, convert::Coerced, qjs, Ctx, Error, Object, Result, Value};
 
+mod js_error;
+pub use js_error::JsError;
+
 /// A JavaScript instance of Error
 ///
 /// Will turn into a error when converted to JavaScript but won't automatically be thrown.
@@ -124,6 +127,15 @@ impl<'js> Exception<'js> {
             .map(|x| x.0)
     }
 
+    /// Returns the error stack with the locations rewritten by the registered source maps.
+    ///
+    /// See [`source_map`](crate::source_map) for how source maps are registered.
+    #[cfg(feature = "source-map")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "source-map")))]
+    pub fn mapped_stack(&self) -> Option<String> {
+        self.stack().map(|stack| self.ctx().map_stack(&stack))
+    }
+
     /// Throws a new generic error.
     ///
     /// Equivalent to:
@@ -281,7 +293,11 @@ impl fmt::Display for Exception<'_> {
             ' '.fmt(f)?;
             message.fmt(f)?;
         }
-        if let Some(stack) = self.stack() {
+        #[cfg(feature = "source-map")]
+        let stack = self.mapped_stack();
+        #[cfg(not(feature = "source-map"))]
+        let stack = self.stack();
+        if let Some(stack) = stack {
             '\n'.fmt(f)?;
             stack.fmt(f)?;
         }
@@ -0,0 +1,273 @@
+use std::fmt;
+
+use crate::{
+    atom::PredefinedAtom,
+    function::{Constructor, Rest, This},
+    object::PropertyDescriptor,
+    Ctx, Error, Exception, Function, IntoJs, Object, Result, Value,
+};
+
+/// A Rust error which is thrown as an instance of its own JavaScript error class.
+///
+/// Converting an implementor into an [`Error`] with [`Error::new_js`] and returning it from a
+/// native function throws an instance of the error class named by [`JsError::name`], a subclass
+/// of `Error` registered as a global on first use, see [`Exception::class`]. The message is
+/// the [`Display`](fmt::Display) output, and the [`code`](JsError::code) and
+/// [`cause`](JsError::cause) are set as the `code` and `cause` properties.
+///
+/// With the `macro` feature this trait can be derived together with the `From` conversion into
+/// [`Error`], see the `JsError` derive macro.
+pub trait JsError: fmt::Debug + fmt::Display {
+    /// The name of the error class, like `NotFoundError`.
+    fn name(&self) -> &str;
+
+    /// A machine readable code for the error, like `NOT_FOUND`.
+    fn code(&self) -> Option<&str> {
+        None
+    }
+
+    /// The error which caused this one, thrown as its own error class.
+    fn cause(&self) -> Option<&dyn JsError> {
+        None
+    }
+}
+
+impl<E: JsError + ?Sized> JsError for Box<E> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn code(&self) -> Option<&str> {
+        (**self).code()
+    }
+
+    fn cause(&self) -> Option<&dyn JsError> {
+        (**self).cause()
+    }
+}
+
+impl<'js> Exception<'js> {
+    /// Returns the error class with the given name.
+    ///
+    /// If the global with this name is an error class it is returned. Otherwise a subclass of
+    /// `Error` is created, which is set as a global if the name isn't taken yet.
+    pub fn class(ctx: &Ctx<'js>, name: &str) -> Result<Constructor<'js>> {
+        let globals = ctx.globals();
+        let base: Constructor = globals.get(PredefinedAtom::Error)?;
+        let existing: Value = globals.get(name)?;
+        if let Some(existing) = existing.as_constructor() {
+            if existing.as_value() == base.as_value() {
+                return Ok(base);
+            }
+            if let Some(proto) = existing.get::<_, Option<Object>>(PredefinedAtom::Prototype)? {
+                if proto.try_instance_of(&base)? {
+                    return Ok(existing.clone());
+                }
+            }
+        }
+
+        let proto = Object::new(ctx.clone())?;
+        proto.set_prototype(
+            base.get::<_, Option<Object>>(PredefinedAtom::Prototype)?
+                .as_ref(),
+        )?;
+        proto.define_property(
+            PredefinedAtom::Name,
+            PropertyDescriptor {
+                value: Some(name.into_js(ctx)?),
+                writable: Some(true),
+                configurable: Some(true),
+                enumerable: Some(false),
+                ..Default::default()
+            },
+        )?;
+        // Nothing is captured as the function can't tell the garbage collector about it.
+        let class = Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, this: This<Value<'js>>, args: Rest<Value<'js>>| {
+                let base: Constructor = ctx.globals().get(PredefinedAtom::Error)?;
+                let error: Object = base.construct((args,))?;
+                // `this` is the constructor called with `new`, which can be a subclass.
+                if let Some(new_target) = this.0.as_object() {
+                    let proto: Option<Object> = new_target.get(PredefinedAtom::Prototype)?;
+                    error.set_prototype(proto.as_ref())?;
+                }
+                Ok::<_, Error>(error)
+            },
+        )?
+        .with_name(name)?
+        .with_length(1)?
+        .with_constructor(true);
+        class.define_property(
+            PredefinedAtom::Prototype,
+            PropertyDescriptor {
+                value: Some(proto.clone().into_value()),
+                writable: Some(false),
+                configurable: Some(false),
+                enumerable: Some(false),
+                ..Default::default()
+            },
+        )?;
+        proto.define_property(
+            PredefinedAtom::Constructor,
+            PropertyDescriptor {
+                value: Some(class.clone().into_value()),
+                writable: Some(true),
+                configurable: Some(true),
+                enumerable: Some(false),
+                ..Default::default()
+            },
+        )?;
+        let class = Constructor(class);
+        class.set_prototype(Some(&base))?;
+        if existing.is_undefined() {
+            globals.define_property(
+                name,
+                PropertyDescriptor {
+                    value: Some(class.clone().into_value()),
+                    writable: Some(true),
+                    configurable: Some(true),
+                    enumerable: Some(false),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(class)
+    }
+
+    /// Creates an instance of the error class of a Rust error, see [`JsError`].
+    pub fn from_js_error(ctx: &Ctx<'js>, error: &dyn JsError) -> Result<Self> {
+        let class = Self::class(ctx, error.name())?;
+        let obj: Object = class.construct((error.to_string(),))?;
+        if let Some(code) = error.code() {
+            obj.set("code", code)?;
+        }
+        if let Some(cause) = error.cause() {
+            let cause = Self::from_js_error(ctx, cause)?;
+            obj.define_property(
+                "cause",
+                PropertyDescriptor {
+                    value: Some(cause.into_value()),
+                    writable: Some(true),
+                    configurable: Some(true),
+                    enumerable: Some(false),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(Exception(obj))
+    }
+
+    /// Throws a Rust error as an instance of its error class, see [`JsError`].
+    pub fn throw_js_error(ctx: &Ctx<'js>, error: &dyn JsError) -> Error {
+        let (Ok(e) | Err(e)) = Self::from_js_error(ctx, error).map(|x| x.throw());
+        e
+    }
+}
+
+impl Error {
+    /// Create an error which is thrown as an instance of its own error class, see [`JsError`].
+    pub fn new_js<E: JsError + Send + Sync + 'static>(error: E) -> Self {
+        Error::Js(Box::new(error))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt;
+
+    use crate::{test_with, Error, Exception, Function, JsError};
+
+    #[derive(Debug)]
+    enum StoreError {
+        Missing(&'static str),
+        Broken(Box<StoreError>),
+    }
+
+    impl fmt::Display for StoreError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                StoreError::Missing(key) => write!(f, "missing {}", key),
+                StoreError::Broken(_) => "broken".fmt(f),
+            }
+        }
+    }
+
+    impl JsError for StoreError {
+        fn name(&self) -> &str {
+            match self {
+                StoreError::Missing(_) => "MissingError",
+                StoreError::Broken(_) => "TypeError",
+            }
+        }
+
+        fn code(&self) -> Option<&str> {
+            match self {
+                StoreError::Missing(_) => Some("MISSING"),
+                StoreError::Broken(_) => None,
+            }
+        }
+
+        fn cause(&self) -> Option<&dyn JsError> {
+            match self {
+                StoreError::Missing(_) => None,
+                StoreError::Broken(cause) => Some(cause),
+            }
+        }
+    }
+
+    fn fail(key: Option<&'static str>) -> crate::Result<()> {
+        Err(Error::new_js(match key {
+            Some(key) => StoreError::Missing(key),
+            None => StoreError::Broken(Box::new(StoreError::Missing("inner"))),
+        }))
+    }
+
+    #[test]
+    fn custom_error_classes() {
+        test_with(|ctx| {
+            let missing = Function::new(ctx.clone(), || fail(Some("a"))).unwrap();
+            ctx.globals().set("missing", missing).unwrap();
+            let broken = Function::new(ctx.clone(), || fail(None)).unwrap();
+            ctx.globals().set("broken", broken).unwrap();
+
+            let res: Vec<bool> = ctx
+                .eval(
+                    r#"
+                    const get = (f) => { try { f() } catch (e) { return e } };
+                    const a = get(missing);
+                    const b = get(broken);
+                    class SubError extends MissingError {}
+                    const sub = new SubError("sub");
+                    [
+                        a instanceof MissingError && a instanceof Error,
+                        a.name === "MissingError" && a.message === "missing a" && a.code === "MISSING",
+                        String(a) === "MissingError: missing a",
+                        typeof a.stack === "string",
+                        !Object.keys(a).includes("cause") && a.cause === undefined,
+                        b instanceof TypeError && b.message === "broken" && b.code === undefined,
+                        b.cause instanceof MissingError && b.cause.message === "missing inner",
+                        !Object.keys(b).includes("cause"),
+                        sub instanceof SubError && sub instanceof MissingError && sub.message === "sub",
+                        MissingError.name === "MissingError" && MissingError.length === 1,
+                        !Object.keys(globalThis).includes("MissingError"),
+                    ]
+                    "#,
+                )
+                .unwrap();
+            assert!(res.iter().all(|x| *x), "{:?}", res);
+
+            // Classes are reused, and names already taken by other globals get a fresh class.
+            let first = Exception::class(&ctx, "MissingError").unwrap();
+            let second = Exception::class(&ctx, "MissingError").unwrap();
+            assert_eq!(first.as_value(), second.as_value());
+            ctx.globals().set("Taken", 1).unwrap();
+            let taken = Exception::class(&ctx, "Taken").unwrap();
+            assert_eq!(taken.get::<_, String>("name").unwrap(), "Taken");
+            assert_eq!(ctx.globals().get::<_, i32>("Taken").unwrap(), 1);
+
+            let error = Error::new_js(StoreError::Missing("b"));
+            assert_eq!(error.to_string(), "MissingError: missing b");
+        })
+    }
+}
@@ -3,22 +3,30 @@
 use crate::{
     atom::PredefinedAtom,
     class::{Class, JsClass},
-    function::ffi::RustFunc,
-    qjs, Ctx, Error, FromJs, IntoJs, Object, Result, Value,
+    function::{
+        ffi::{RequiredParams, RustFunc},
+        native_frame::NativeFrame,
+    },
+    qjs, Ctx, Error, FromJs, IntoJs, Object, Result, StdString, Value,
 };
 
 mod args;
 mod ffi;
 mod into_func;
+mod native_frame;
 mod params;
+mod typed;
 mod types;
 
 pub use args::{Args, IntoArg, IntoArgs};
 pub use ffi::{RustFunction, StaticJsFn};
 pub use params::{FromParam, FromParams, ParamRequirement, Params, ParamsAccessor};
+pub use typed::TypedFunction;
 #[cfg(feature = "futures")]
 pub use types::Async;
-pub use types::{Exhaustive, Flat, Func, FuncArg, MutFn, Null, OnceFn, Opt, Rest, This};
+pub use types::{
+    Exhaustive, Flat, Func, FuncArg, MutFn, Null, OnceFn, Opt, Rest, This, UndefinedAsNull,
+};
 
 /// A trait for converting a Rust function to a JavaScript function.
 pub trait IntoJsFunc<'js, P> {
@@ -37,19 +45,26 @@ pub trait StaticJsFunction {
 }
 
 /// A JavaScript function.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct Function<'js>(pub(crate) Object<'js>);
 
 impl<'js> Function<'js> {
     /// Create a new function from a Rust function which implements [`IntoJsFunc`].
+    #[track_caller]
     pub fn new<P, F>(ctx: Ctx<'js>, f: F) -> Result<Self>
     where
         F: IntoJsFunc<'js, P> + 'js,
     {
-        let func = Box::new(move |params: Params<'_, 'js>| {
-            params.check_params(F::param_requirements())?;
-            f.call(params)
+        let frame = NativeFrame::caller();
+        let func = Box::new(RequiredParams {
+            requirement: F::param_requirements(),
+            func: move |params: Params<'_, 'js>| {
+                frame.call(params, |params| {
+                    params.check_params(F::param_requirements())?;
+                    f.call(params)
+                })
+            },
         }) as Box<dyn RustFunc<'js> + 'js>;
 
         let cls = Class::instance(ctx, RustFunction(func))?;
@@ -78,6 +93,39 @@ impl<'js> Function<'js> {
         args.apply(self)
     }
 
+    /// Call the function with the given `this` value and arguments.
+    ///
+    /// Equivalent to `func.call(this, ...args)` in JavaScript.
+    pub fn call_on<T, A, R>(&self, this: T, args: A) -> Result<R>
+    where
+        T: IntoJs<'js>,
+        A: IntoArgs<'js>,
+        R: FromJs<'js>,
+    {
+        let ctx = self.ctx();
+        let num = args.num_args();
+        let mut accum_args = Args::new(ctx.clone(), num);
+        accum_args.this(this)?;
+        args.into_args(&mut accum_args)?;
+        self.call_arg(accum_args)
+    }
+
+    /// Call the function as a constructor with the given arguments.
+    ///
+    /// Equivalent to calling the function with the new keyword. Returns an error if the function
+    /// is not a constructor.
+    pub fn construct<A, R>(&self, args: A) -> Result<R>
+    where
+        A: IntoArgs<'js>,
+        R: FromJs<'js>,
+    {
+        let ctx = self.ctx();
+        let num = args.num_args();
+        let mut accum_args = Args::new(ctx.clone(), num);
+        args.into_args(&mut accum_args)?;
+        accum_args.construct(&Constructor(self.clone()))
+    }
+
     /// Defer call the function with given arguments.
     ///
     /// Calling a function with defer is equivalent to calling a JavaScript function with
@@ -99,6 +147,52 @@ impl<'js> Function<'js> {
         args.defer(self.clone())
     }
 
+    /// Defer a function call with the given `this` value and arguments.
+    pub fn defer_on<T, A>(&self, this: T, args: A) -> Result<()>
+    where
+        T: IntoJs<'js>,
+        A: IntoArgs<'js>,
+    {
+        let ctx = self.ctx();
+        let num = args.num_args();
+        let mut accum_args = Args::new(ctx.clone(), num);
+        accum_args.this(this)?;
+        args.into_args(&mut accum_args)?;
+        self.defer_arg(accum_args)
+    }
+
+    /// Returns a new function which calls this function with the given `this` value.
+    ///
+    /// Equivalent to `func.bind(this)` in JavaScript, uses `Function.prototype.bind` so the
+    /// returned function is a regular bound function.
+    pub fn bind<T>(&self, this: T) -> Result<Function<'js>>
+    where
+        T: IntoJs<'js>,
+    {
+        let bind: Function = Function::prototype(self.ctx().clone()).get("bind")?;
+        bind.call_on(self.clone(), (this,))
+    }
+
+    /// Wrap the function into a [`TypedFunction`] with fixed argument and return types.
+    ///
+    /// Returns an error if the function requires more arguments than `A` can provide.
+    pub fn into_typed<A, R>(self) -> Result<TypedFunction<'js, A, R>>
+    where
+        A: IntoArgs<'js>,
+        R: FromJs<'js>,
+    {
+        TypedFunction::new(self)
+    }
+
+    /// Returns the `name` property of this function, empty if it isn't a string.
+    pub fn name(&self) -> Result<StdString> {
+        let name: Value = self.0.get(PredefinedAtom::Name)?;
+        match name.as_string() {
+            Some(name) => name.to_string(),
+            None => Ok(StdString::new()),
+        }
+    }
+
     /// Set the `name` property of this function
     pub fn set_name<S: AsRef<str>>(&self, name: S) -> Result<()> {
         let name = name.as_ref().into_js(self.ctx())?;
@@ -123,6 +217,36 @@ impl<'js> Function<'js> {
         Ok(self)
     }
 
+    /// Returns the `length` property of the function, the number of parameters it expects.
+    ///
+    /// Returns 0 if the property isn't a non negative number.
+    pub fn length(&self) -> Result<usize> {
+        let length: Value = self.0.get(PredefinedAtom::Length)?;
+        Ok(length.as_number().map(|x| x.max(0.0) as usize).unwrap_or(0))
+    }
+
+    /// Returns the requirements of the parameters of a function created from Rust.
+    ///
+    /// Returns `None` for JavaScript functions, the `length` property is the closest they have.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Function, function::Opt};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let func = Function::new(ctx.clone(), |a: i32, b: Opt<i32>| a + b.0.unwrap_or(0)).unwrap();
+    /// let requirement = func.param_requirement().unwrap();
+    /// assert_eq!((requirement.min(), requirement.max()), (1, 2));
+    /// let func: Function = ctx.eval("(a, b) => a + b").unwrap();
+    /// assert!(func.param_requirement().is_none());
+    /// # })
+    /// ```
+    pub fn param_requirement(&self) -> Option<ParamRequirement> {
+        let func = Class::<RustFunction>::from_object(self.0.clone())?;
+        let requirement = func.borrow().0.param_requirement();
+        requirement
+    }
+
     /// Sets the `length` property of the function.
     pub fn set_length(&self, len: usize) -> Result<()> {
         let len = len.into_js(self.ctx())?;
@@ -165,6 +289,24 @@ impl<'js> Function<'js> {
         res != 0
     }
 
+    /// Returns whether this is a generator function, declared with `function*` or
+    /// `async function*`.
+    ///
+    /// Bound functions report the kind of their target function.
+    pub fn is_generator(&self) -> bool {
+        let kind = unsafe { qjs::JS_GetFunctionKind(self.0.as_js_value()) };
+        kind == 1 || kind == 3
+    }
+
+    /// Returns whether this is an async function, declared with `async function` or
+    /// `async function*`.
+    ///
+    /// Bound functions report the kind of their target function.
+    pub fn is_async(&self) -> bool {
+        let kind = unsafe { qjs::JS_GetFunctionKind(self.0.as_js_value()) };
+        kind == 2 || kind == 3
+    }
+
     /// Set whether this function is a constructor or not.
     pub fn set_constructor(&self, is_constructor: bool) {
         unsafe {
@@ -195,29 +337,38 @@ impl<'js> Constructor<'js> {
     ///
     /// Note that this function creates a constructor from a given function, the returned constructor
     /// is thus not the same as the one returned from [`JsClass::constructor`].
+    #[track_caller]
     pub fn new_class<C, F, P>(ctx: Ctx<'js>, f: F) -> Result<Self>
     where
         F: IntoJsFunc<'js, P> + 'js,
         C: JsClass<'js>,
     {
-        let func = Box::new(move |params: Params<'_, 'js>| -> Result<Value<'js>> {
-            params.check_params(F::param_requirements())?;
-            let this = params.this();
-            let ctx = params.ctx().clone();
-            let proto = this
-                .into_function()
-                .map(|func| func.get(PredefinedAtom::Prototype))
-                .unwrap_or_else(|| Ok(Class::<C>::prototype(ctx.clone())))?;
-
-            let res = f.call(params)?;
-            res.as_object()
-                .ok_or_else(|| Error::IntoJs {
-                    from: res.type_of().as_str(),
-                    to: "object",
-                    message: Some("rust constructor function did not return a object".to_owned()),
-                })?
-                .set_prototype(proto.as_ref())?;
-            Ok(res)
+        let frame = NativeFrame::caller();
+        let func = Box::new(RequiredParams {
+            requirement: F::param_requirements(),
+            func: move |params: Params<'_, 'js>| -> Result<Value<'js>> {
+                frame.call(params, |params| {
+                    params.check_params(F::param_requirements())?;
+                    let this = params.this();
+                    let ctx = params.ctx().clone();
+                    let proto = this
+                        .into_function()
+                        .map(|func| func.get(PredefinedAtom::Prototype))
+                        .unwrap_or_else(|| Ok(Class::<C>::prototype(ctx.clone())))?;
+
+                    let res = f.call(params)?;
+                    res.as_object()
+                        .ok_or_else(|| Error::IntoJs {
+                            from: res.type_of().as_str(),
+                            to: "object",
+                            message: Some(
+                                "rust constructor function did not return a object".to_owned(),
+                            ),
+                        })?
+                        .set_prototype(proto.as_ref())?;
+                    Ok(res)
+                })
+            },
         });
         let func = Function(Class::instance(ctx.clone(), RustFunction(func))?.into_inner())
             .with_constructor(true);
@@ -237,28 +388,37 @@ impl<'js> Constructor<'js> {
     /// Create a new Rust constructor function with a given prototype.
     ///
     /// Useful if the function does not return a Rust class.
+    #[track_caller]
     pub fn new_prototype<F, P>(ctx: &Ctx<'js>, prototype: Object<'js>, f: F) -> Result<Self>
     where
         F: IntoJsFunc<'js, P> + 'js,
     {
         let proto_clone = prototype.clone();
-        let func = Box::new(move |params: Params<'_, 'js>| -> Result<Value<'js>> {
-            params.check_params(F::param_requirements())?;
-            let this = params.this();
-            let proto = this
-                .as_function()
-                .map(|func| func.get(PredefinedAtom::Prototype))
-                .unwrap_or_else(|| Ok(Some(proto_clone.clone())))?;
-
-            let res = f.call(params)?;
-            res.as_object()
-                .ok_or_else(|| Error::IntoJs {
-                    from: res.type_of().as_str(),
-                    to: "object",
-                    message: Some("rust constructor function did not return a object".to_owned()),
-                })?
-                .set_prototype(proto.as_ref())?;
-            Ok(res)
+        let frame = NativeFrame::caller();
+        let func = Box::new(RequiredParams {
+            requirement: F::param_requirements(),
+            func: move |params: Params<'_, 'js>| -> Result<Value<'js>> {
+                frame.call(params, |params| {
+                    params.check_params(F::param_requirements())?;
+                    let this = params.this();
+                    let proto = this
+                        .as_function()
+                        .map(|func| func.get(PredefinedAtom::Prototype))
+                        .unwrap_or_else(|| Ok(Some(proto_clone.clone())))?;
+
+                    let res = f.call(params)?;
+                    res.as_object()
+                        .ok_or_else(|| Error::IntoJs {
+                            from: res.type_of().as_str(),
+                            to: "object",
+                            message: Some(
+                                "rust constructor function did not return a object".to_owned(),
+                            ),
+                        })?
+                        .set_prototype(proto.as_ref())?;
+                    Ok(res)
+                })
+            },
         });
         let func = Function(Class::instance(ctx.clone(), RustFunction(func))?.into_inner())
             .with_constructor(true);
@@ -818,4 +978,149 @@ mod test {
             assert_eq!(n, 3);
         });
     }
+
+    #[test]
+    fn call_with_this_and_construct() {
+        test_with(|ctx| {
+            let f: Function = ctx
+                .eval(
+                    "(function(a) { if (new.target) { this.a = a; } else { return this.x + a; } })",
+                )
+                .unwrap();
+
+            let this = Object::new(ctx.clone()).unwrap();
+            this.set("x", 1).unwrap();
+            let res: i32 = f.call_on(this.clone(), (2,)).unwrap();
+            assert_eq!(res, 3);
+
+            let bound = f.bind(this).unwrap();
+            let res: i32 = bound.call((3,)).unwrap();
+            assert_eq!(res, 4);
+
+            let obj: Object = f.construct((5,)).unwrap();
+            assert_eq!(obj.get::<_, i32>("a").unwrap(), 5);
+
+            let arrow: Function = ctx.eval("() => {}").unwrap();
+            assert!(arrow.construct::<_, Value>(()).is_err());
+        })
+    }
+
+    #[test]
+    fn defer_with_this() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let f: Function = ctx.eval("(function(a) { this.called = a; })").unwrap();
+            let this = Object::new(ctx.clone()).unwrap();
+            ctx.globals().set("target", this.clone()).unwrap();
+            f.defer_on(this, (true,)).unwrap();
+        });
+        while rt.execute_pending_job().unwrap() {}
+        ctx.with(|ctx| {
+            let res: bool = ctx.eval("target.called").unwrap();
+            assert!(res);
+        });
+    }
+
+    #[test]
+    fn function_kind() {
+        test_with(|ctx| {
+            let kinds: Vec<Function> = ctx
+                .eval(
+                    r#"[
+                        function() {},
+                        function*() {},
+                        async function() {},
+                        async function*() {},
+                        (function*() {}).bind(null),
+                        Math.max,
+                    ]"#,
+                )
+                .unwrap();
+            let kinds: Vec<_> = kinds
+                .iter()
+                .map(|f| (f.is_generator(), f.is_async()))
+                .collect();
+            assert_eq!(
+                kinds,
+                [
+                    (false, false),
+                    (true, false),
+                    (false, true),
+                    (true, true),
+                    (true, false),
+                    (false, false)
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn reflection() {
+        test_with(|ctx| {
+            let func = Function::new(ctx.clone(), |_: i32, _: Opt<StdString>, _: Rest<i32>| {})
+                .unwrap()
+                .with_name("native")
+                .unwrap();
+            assert_eq!(func.name().unwrap(), "native");
+            assert_eq!(func.length().unwrap(), 1);
+            let requirement = func.param_requirement().unwrap();
+            assert_eq!(requirement.min(), 1);
+            assert_eq!(requirement.max(), usize::MAX);
+            assert!(!requirement.is_exhaustive());
+
+            let func = Function::new(ctx.clone(), |_: Exhaustive| {}).unwrap();
+            let requirement = func.param_requirement().unwrap();
+            assert_eq!((requirement.max(), requirement.is_exhaustive()), (0, true));
+
+            let func: Function = ctx
+                .eval("(function add(a, b = 1) { return a + b })")
+                .unwrap();
+            assert_eq!(func.name().unwrap(), "add");
+            assert_eq!(func.length().unwrap(), 1);
+            assert!(func.param_requirement().is_none());
+            func.set_name("plus").unwrap();
+            assert_eq!(func.name().unwrap(), "plus");
+
+            // Bound native functions are JavaScript functions.
+            let bound: Function = ctx.eval("Math.max.bind(null, 1)").unwrap();
+            assert_eq!(bound.name().unwrap(), "bound max");
+            assert!(bound.param_requirement().is_none());
+        })
+    }
+
+    #[test]
+    fn native_recursion() {
+        let rt = Runtime::new().unwrap();
+        rt.set_max_native_recursion(Some(10));
+        let ctx = Context::full(&rt).unwrap();
+        ctx.with(|ctx| {
+            let native = Function::new(ctx.clone(), |ctx: Ctx, n: u32| -> Result<usize> {
+                if n == 0 {
+                    return Ok(ctx.native_call_depth());
+                }
+                let down: Function = ctx.globals().get("down")?;
+                down.call((n - 1,))
+            })
+            .unwrap();
+            ctx.globals().set("native", native).unwrap();
+            ctx.eval::<(), _>("globalThis.down = (n) => native(n)")
+                .unwrap();
+
+            assert_eq!(ctx.native_call_depth(), 0);
+            let depth: usize = ctx.eval("native(4)").unwrap();
+            assert_eq!(depth, 5);
+            let res: StdString = ctx
+                .eval("try { native(1000) } catch (e) { `${e.name}: ${e.message}` }")
+                .unwrap();
+            assert_eq!(res, "RangeError: Maximum native recursion depth exceeded");
+            assert_eq!(ctx.native_call_depth(), 0);
+        });
+
+        rt.set_max_native_recursion(None);
+        ctx.with(|ctx| {
+            let depth: usize = ctx.eval("native(12)").unwrap();
+            assert_eq!(depth, 13);
+        });
+    }
 }
@@ -209,6 +209,10 @@ impl Drop for Args<'_> {
 
 /// A trait for converting values into arguments.
 pub trait IntoArg<'js> {
+    /// The maximum number of arguments this value can produce, `None` if it is unbounded or
+    /// unknown.
+    const MAX_ARGS: Option<usize> = None;
+
     /// The number of arguments this value produces.
     fn num_args(&self) -> usize;
 
@@ -218,6 +222,10 @@ pub trait IntoArg<'js> {
 
 /// A trait for converting a tuple of values into a list arguments.
 pub trait IntoArgs<'js> {
+    /// The maximum number of arguments this value can produce, `None` if it is unbounded or
+    /// unknown.
+    const MAX_ARGS: Option<usize> = None;
+
     /// The number of arguments this value produces.
     fn num_args(&self) -> usize;
 
@@ -254,6 +262,8 @@ pub trait IntoArgs<'js> {
 }
 
 impl<'js, T: IntoJs<'js>> IntoArg<'js> for T {
+    const MAX_ARGS: Option<usize> = Some(1);
+
     fn num_args(&self) -> usize {
         1
     }
@@ -264,6 +274,8 @@ impl<'js, T: IntoJs<'js>> IntoArg<'js> for T {
 }
 
 impl<'js, T: IntoJs<'js>> IntoArg<'js> for This<T> {
+    const MAX_ARGS: Option<usize> = Some(0);
+
     fn num_args(&self) -> usize {
         0
     }
@@ -274,6 +286,8 @@ impl<'js, T: IntoJs<'js>> IntoArg<'js> for This<T> {
 }
 
 impl<'js, T: IntoJs<'js>> IntoArg<'js> for Opt<T> {
+    const MAX_ARGS: Option<usize> = Some(1);
+
     fn num_args(&self) -> usize {
         self.0.is_some() as usize
     }
@@ -297,6 +311,8 @@ impl<'js, T: IntoJs<'js>> IntoArg<'js> for Rest<T> {
 }
 
 impl<'js, T: IntoArgs<'js>> IntoArg<'js> for Flat<T> {
+    const MAX_ARGS: Option<usize> = T::MAX_ARGS;
+
     fn num_args(&self) -> usize {
         self.0.num_args()
     }
@@ -306,6 +322,13 @@ impl<'js, T: IntoArgs<'js>> IntoArg<'js> for Flat<T> {
     }
 }
 
+const fn add_max_args(a: Option<usize>, b: Option<usize>) -> Option<usize> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        _ => None,
+    }
+}
+
 macro_rules! impl_into_args {
     ($($t:ident),*) => {
         #[allow(non_snake_case)]
@@ -313,6 +336,12 @@ macro_rules! impl_into_args {
         where
             $($t : IntoArg<'js>,)*
         {
+            const MAX_ARGS: Option<usize> = {
+                let max = Some(0);
+                $(let max = add_max_args(max, $t::MAX_ARGS);)*
+                max
+            };
+
             fn num_args(&self) -> usize{
                 let ($(ref $t,)*) = *self;
                 0 $(+ $t.num_args())*
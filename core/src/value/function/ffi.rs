@@ -1,10 +1,12 @@
 use std::panic::AssertUnwindSafe;
 
 use crate::{
+    atom::PredefinedAtom,
     class::{Class, ClassId, JsClass, Readable, Trace, Tracer},
     qjs,
-    value::function::{Params, StaticJsFunction},
-    Ctx, FromJs, Function, Object, Outlive, Result, Value,
+    runtime::{profile, raw::Opaque, ProfileKind},
+    value::function::{ParamRequirement, Params, StaticJsFunction},
+    Ctx, Exception, FromJs, Function, Object, Outlive, Result, StdString, Value,
 };
 pub use mac::static_fn;
 
@@ -22,14 +24,73 @@ pub unsafe extern "C" fn js_callback_class<F: StaticJsFunction>(
     let args = Params::from_ffi_class(ctx, function, this, argc, argv, _flags);
     let ctx = args.ctx().clone();
 
+    let Some(_depth) = DepthGuard::enter(&ctx) else {
+        return Exception::throw_range(&ctx, "Maximum native recursion depth exceeded").throw(&ctx);
+    };
+    // The profiler is called within the panic guard too, a panic must not unwind into C.
     ctx.handle_panic(AssertUnwindSafe(|| {
-        let value = F::call(args)
-            .map(Value::into_js_value)
-            .unwrap_or_else(|error| error.throw(&ctx));
-        value
+        profile(
+            ctx.get_opaque(),
+            ProfileKind::NativeCall,
+            || function_name(&ctx, function),
+            || {
+                F::call(args)
+                    .map(Value::into_js_value)
+                    .unwrap_or_else(|error| error.throw(&ctx))
+            },
+            |value| qjs::JS_VALUE_GET_NORM_TAG(*value) == qjs::JS_TAG_EXCEPTION,
+        )
     }))
 }
 
+/// Counts a running native function in the depth of the runtime for the duration of the guard.
+struct DepthGuard(*mut Opaque<'static>);
+
+impl DepthGuard {
+    /// Returns `None` when the maximum depth is reached.
+    unsafe fn enter(ctx: &Ctx) -> Option<Self> {
+        let opaque = ctx.get_opaque().cast::<Opaque<'static>>();
+        if (*opaque)
+            .max_native_recursion
+            .map(|max| (*opaque).native_depth >= max)
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        (*opaque).native_depth += 1;
+        Some(DepthGuard(opaque))
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        unsafe { (*self.0).native_depth -= 1 }
+    }
+}
+
+/// Returns the name of a function for the profiler.
+///
+/// Only an own data property is read, so a redefined `name` getter is never invoked from within
+/// a native call.
+unsafe fn function_name(ctx: &Ctx, function: qjs::JSValue) -> StdString {
+    let function = Value::from_js_value_const(ctx.clone(), function);
+    let desc = function
+        .as_object()
+        .map(|x| x.get_own_property_descriptor(PredefinedAtom::Name));
+    match desc {
+        Some(Ok(desc)) => desc
+            .and_then(|x| x.value)
+            .and_then(|x| x.as_string()?.to_string().ok())
+            .unwrap_or_default(),
+        Some(Err(_)) => {
+            // Don't leave the exception pending.
+            ctx.catch();
+            StdString::new()
+        }
+        None => StdString::new(),
+    }
+}
+
 pub unsafe extern "C" fn defer_call_job(
     ctx: *mut qjs::JSContext,
     argc: qjs::c_int,
@@ -67,6 +128,11 @@ impl StaticJsFn {
 pub trait RustFunc<'js> {
     /// Call the actual function with a given set of parameters and return a function.
     fn call<'a>(&self, params: Params<'a, 'js>) -> Result<Value<'js>>;
+
+    /// Returns the requirements of the parameters of the function, if they are known.
+    fn param_requirement(&self) -> Option<ParamRequirement> {
+        None
+    }
 }
 
 impl<'js, F> RustFunc<'js> for F
@@ -78,6 +144,26 @@ where
     }
 }
 
+/// A callback created from an [`IntoJsFunc`](crate::function::IntoJsFunc), which knows the
+/// requirements of its parameters.
+pub(crate) struct RequiredParams<F> {
+    pub(crate) requirement: ParamRequirement,
+    pub(crate) func: F,
+}
+
+impl<'js, F> RustFunc<'js> for RequiredParams<F>
+where
+    for<'a> F: Fn(Params<'a, 'js>) -> Result<Value<'js>>,
+{
+    fn call<'a>(&self, params: Params<'a, 'js>) -> Result<Value<'js>> {
+        (self.func)(params)
+    }
+
+    fn param_requirement(&self) -> Option<ParamRequirement> {
+        Some(self.requirement)
+    }
+}
+
 /// The class used for wrapping closures, rquickjs implements callbacks by creating an instances of
 /// this class.
 pub struct RustFunction<'js>(pub Box<dyn RustFunc<'js> + 'js>);
@@ -0,0 +1,152 @@
+//! Synthetic stack frames for native functions.
+//!
+//! Native Rust functions don't show up in the stack of JavaScript errors. When enabled with
+//! [`Runtime::set_native_stack_frames`](crate::Runtime::set_native_stack_frames), exceptions
+//! passing through a native function get a frame like `at native name (src/lib.rs:10:5)` added
+//! to their stack at the position of the call, where the location is where the function was
+//! created.
+
+use std::panic::Location;
+
+use crate::{
+    atom::PredefinedAtom, function::Params, Ctx, Function, Object, Result, StdString, Value,
+};
+
+/// The location a native function was created at.
+#[derive(Clone, Copy)]
+pub(crate) struct NativeFrame(&'static Location<'static>);
+
+impl NativeFrame {
+    #[track_caller]
+    pub fn caller() -> Self {
+        NativeFrame(Location::caller())
+    }
+
+    /// Call the function, adding the frame to any exception it results in if enabled.
+    pub fn call<'a, 'js, F>(self, params: Params<'a, 'js>, f: F) -> Result<Value<'js>>
+    where
+        F: FnOnce(Params<'a, 'js>) -> Result<Value<'js>>,
+    {
+        let ctx = params.ctx().clone();
+        if !unsafe { (*ctx.get_opaque()).native_stack_frames } {
+            return f(params);
+        }
+        let function = params.function();
+        match f(params) {
+            Ok(x) => Ok(x),
+            Err(error) => {
+                // Other errors are turned into exceptions now, so they get the frame as well.
+                error.throw(&ctx);
+                let exception = ctx.catch();
+                if let Some(object) = exception.as_object() {
+                    if self.add_to(&ctx, &function, object).is_err() {
+                        // Keep the original exception over one thrown while adding the frame.
+                        ctx.catch();
+                    }
+                }
+                Err(ctx.throw(exception))
+            }
+        }
+    }
+
+    fn add_to<'js>(self, ctx: &Ctx<'js>, function: &Value<'js>, error: &Object<'js>) -> Result<()> {
+        // Errors thrown from native code only get their stack once they reach a JavaScript frame.
+        let stack = error.get::<_, Option<StdString>>(PredefinedAtom::Stack)?;
+        if stack.is_none() && !error.is_error() {
+            return Ok(());
+        }
+        let name = function
+            .as_object()
+            .map(|x| x.get::<_, Option<StdString>>(PredefinedAtom::Name))
+            .transpose()?
+            .flatten()
+            .filter(|x| !x.is_empty())
+            .unwrap_or_else(|| "<anonymous>".to_string());
+        let frame = format!(
+            "    at native {} ({}:{}:{})\n",
+            name,
+            self.0.file(),
+            self.0.line(),
+            self.0.column()
+        );
+
+        // The frames of the callers of the native function end the stack if the error was
+        // created while the function was running, the frame goes right before them.
+        let constructor: Function = ctx.globals().get(PredefinedAtom::Error)?;
+        let callers = constructor
+            .construct::<_, Object>(())?
+            .get::<_, Option<StdString>>(PredefinedAtom::Stack)?
+            .unwrap_or_default();
+        let stack = stack.unwrap_or_else(|| callers.clone());
+        let stack = match stack.strip_suffix(callers.as_str()) {
+            Some(inner) if inner.is_empty() || inner.ends_with('\n') => {
+                format!("{}{}{}", inner, frame, callers)
+            }
+            _ if stack.is_empty() || stack.ends_with('\n') => format!("{}{}", stack, frame),
+            _ => format!("{}\n{}", stack, frame),
+        };
+        error.set(PredefinedAtom::Stack, stack)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Context, Ctx, Exception, Function, Runtime};
+
+    fn stack_lines(ctx: &Ctx, source: &str) -> Vec<String> {
+        let error = ctx.eval::<(), _>(source).unwrap_err();
+        assert!(error.is_exception());
+        let exception = Exception::from_object(ctx.catch().into_object().unwrap()).unwrap();
+        exception
+            .stack()
+            .unwrap()
+            .lines()
+            .map(|x| x.trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn native_frames() {
+        let rt = Runtime::new().unwrap();
+        let ctx = Context::full(&rt).unwrap();
+        let source = "function outer() { fail() }\nouter()";
+        let line = ctx.with(|ctx| {
+            let line = line!() + 1;
+            let fail = Function::new(ctx.clone(), || -> crate::Result<()> {
+                Err(crate::Error::Unknown)
+            })
+            .unwrap()
+            .with_name("fail")
+            .unwrap();
+            ctx.globals().set("fail", fail).unwrap();
+            let call = Function::new(ctx.clone(), |f: Function| f.call::<_, ()>(())).unwrap();
+            ctx.globals().set("call", call).unwrap();
+
+            let lines = stack_lines(&ctx, source);
+            assert!(lines.iter().all(|x| !x.contains("native")), "{:?}", lines);
+            line
+        });
+
+        rt.set_native_stack_frames(true);
+        ctx.with(|ctx| {
+            let lines = stack_lines(&ctx, source);
+            let frame = format!("at native fail ({}:{}:", file!(), line);
+            assert!(lines[0].starts_with(&frame), "{:?}", lines);
+            assert!(lines[1].starts_with("at outer"), "{:?}", lines);
+
+            // Frames of native functions called further up go between the JavaScript frames.
+            let lines = stack_lines(
+                &ctx,
+                "function inner() { throw new Error('x') }\ncall(() => inner())",
+            );
+            assert!(lines[0].starts_with("at inner"), "{:?}", lines);
+            assert!(lines[1].starts_with("at <anonymous>"), "{:?}", lines);
+            assert!(
+                lines[2].starts_with("at native <anonymous> ("),
+                "{:?}",
+                lines
+            );
+            assert!(lines[3].starts_with("at <eval>"), "{:?}", lines);
+        })
+    }
+}
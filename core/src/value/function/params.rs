@@ -1,6 +1,6 @@
 use crate::{
     function::{Exhaustive, Flat, FuncArg, Opt, Rest, This},
-    qjs, Ctx, FromJs, Result, Value,
+    qjs, Ctx, FromJs, Result, Type, Value,
 };
 use std::slice;
 
@@ -26,7 +26,11 @@ impl<'a, 'js> Params<'a, 'js> {
         _flags: qjs::c_int,
     ) -> Self {
         let argc = usize::try_from(argc).expect("invalid argument number");
-        let args = slice::from_raw_parts(argv, argc);
+        let args = if argc == 0 {
+            &[]
+        } else {
+            slice::from_raw_parts(argv, argc)
+        };
         Self {
             ctx: Ctx::from_ptr(ctx),
             function,
@@ -140,6 +144,25 @@ impl<'a, 'js> ParamsAccessor<'a, 'js> {
         unsafe { Value::from_js_value_const(self.params.ctx.clone(), res) }
     }
 
+    /// Takes the last remaining argument if it is a plain object and more than `required`
+    /// arguments remain.
+    ///
+    /// Used for functions which take a trailing options object like `f(a, b, { flag: true })`,
+    /// the `required` arguments before it are never taken.
+    pub fn trailing_options(&mut self, required: usize) -> Option<Value<'js>> {
+        let args = self.params.args;
+        if self.len() <= required {
+            return None;
+        }
+        let last =
+            unsafe { Value::from_js_value_const(self.params.ctx.clone(), args[args.len() - 1]) };
+        if last.type_of() != Type::Object {
+            return None;
+        }
+        self.params.args = &args[..args.len() - 1];
+        Some(last)
+    }
+
     /// returns the number of arguments remaining
     pub fn len(&self) -> usize {
         self.params.args.len() - self.offset
@@ -151,6 +174,7 @@ impl<'a, 'js> ParamsAccessor<'a, 'js> {
 }
 
 /// A struct encoding the requirements of a parameter set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ParamRequirement {
     min: usize,
     max: usize,
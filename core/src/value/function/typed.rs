@@ -0,0 +1,159 @@
+use std::{fmt, marker::PhantomData};
+
+use crate::{
+    atom::PredefinedAtom,
+    class::{Trace, Tracer},
+    Ctx, Error, FromJs, Function, IntoJs, Outlive, Result, Value,
+};
+
+use super::IntoArgs;
+
+/// A JavaScript function with fixed argument and return types.
+///
+/// Useful for storing JavaScript callbacks in Rust structs without having to repeat the
+/// conversion types at every call site.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, function::TypedFunction};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let func: Function = ctx.eval("(a, b) => b.length == a").unwrap();
+/// let func: TypedFunction<(usize, String), bool> = func.into_typed().unwrap();
+/// assert!(func.call((3, "foo".to_string())).unwrap());
+/// # })
+/// ```
+pub struct TypedFunction<'js, A, R> {
+    func: Function<'js>,
+    _marker: PhantomData<fn(A) -> R>,
+}
+
+impl<'js, A, R> Clone for TypedFunction<'js, A, R> {
+    fn clone(&self) -> Self {
+        TypedFunction {
+            func: self.func.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'js, A, R> fmt::Debug for TypedFunction<'js, A, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedFunction").field(&self.func).finish()
+    }
+}
+
+unsafe impl<'js, A: 'static, R: 'static> Outlive<'js> for TypedFunction<'js, A, R> {
+    type Target<'to> = TypedFunction<'to, A, R>;
+}
+
+impl<'js, A, R> Trace<'js> for TypedFunction<'js, A, R> {
+    fn trace<'a>(&self, tracer: Tracer<'a, 'js>) {
+        self.func.trace(tracer)
+    }
+}
+
+impl<'js, A, R> TypedFunction<'js, A, R>
+where
+    A: IntoArgs<'js>,
+    R: FromJs<'js>,
+{
+    /// Wrap a function.
+    ///
+    /// Returns an error if the `length` of the function is larger than the maximum number of
+    /// arguments `A` can provide.
+    pub fn new(func: Function<'js>) -> Result<Self> {
+        if let Some(max) = A::MAX_ARGS {
+            let length: Option<usize> = func.get(PredefinedAtom::Length)?;
+            if let Some(length) = length.filter(|&length| length > max) {
+                return Err(Error::MissingArgs {
+                    expected: length,
+                    given: max,
+                });
+            }
+        }
+        Ok(TypedFunction {
+            func,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Call the function with the given arguments.
+    pub fn call(&self, args: A) -> Result<R> {
+        self.func.call(args)
+    }
+
+    /// Defer call the function with the given arguments.
+    pub fn defer(&self, args: A) -> Result<()> {
+        self.func.defer(args)
+    }
+}
+
+impl<'js, A, R> TypedFunction<'js, A, R> {
+    /// Returns the context of the function.
+    pub fn ctx(&self) -> &Ctx<'js> {
+        self.func.ctx()
+    }
+
+    /// Returns a reference to the untyped function.
+    pub fn as_function(&self) -> &Function<'js> {
+        &self.func
+    }
+
+    /// Returns the untyped function.
+    pub fn into_function(self) -> Function<'js> {
+        self.func
+    }
+}
+
+impl<'js, A, R> FromJs<'js> for TypedFunction<'js, A, R>
+where
+    A: IntoArgs<'js>,
+    R: FromJs<'js>,
+{
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        Function::from_js(ctx, value).and_then(TypedFunction::new)
+    }
+}
+
+impl<'js, A, R> IntoJs<'js> for TypedFunction<'js, A, R> {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        self.func.into_js(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        function::{Opt, Rest, TypedFunction},
+        test_with, Error, Function,
+    };
+
+    #[test]
+    fn typed_call() {
+        test_with(|ctx| {
+            let func: TypedFunction<(i32, i32), i32> = ctx.eval("(a, b) => a + b").unwrap();
+            assert_eq!(func.call((1, 2)).unwrap(), 3);
+            assert_eq!(func.clone().call((3, 4)).unwrap(), 7);
+        })
+    }
+
+    #[test]
+    fn arity_validation() {
+        test_with(|ctx| {
+            let func: Function = ctx.eval("(a, b, c) => a").unwrap();
+            let err = func.clone().into_typed::<(i32, i32), i32>().unwrap_err();
+            assert!(matches!(
+                err,
+                Error::MissingArgs {
+                    expected: 3,
+                    given: 2
+                }
+            ));
+            func.clone()
+                .into_typed::<(i32, i32, Opt<i32>), i32>()
+                .unwrap();
+            func.into_typed::<(Rest<i32>,), i32>().unwrap();
+        })
+    }
+}
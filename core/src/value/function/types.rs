@@ -54,6 +54,12 @@ pub struct Rest<T>(pub Vec<T>);
 /// Helper type for converting an option into null instead of undefined.
 pub struct Null<T>(pub Option<T>);
 
+/// Helper type for converting a value which converts into `undefined`, like `None` or `()`, into
+/// `null` instead.
+///
+/// Unlike [`Null`] it also works with values wrapping an option, like `Result<Option<T>>`.
+pub struct UndefinedAsNull<T>(pub T);
+
 /// A type to flatten tuples into another tuple.
 ///
 /// ToArgs is only implemented for tuples with a length of up to 8.
@@ -182,6 +188,8 @@ type_impls! {
     This<T>(T): into_inner From AsRef AsMut Deref DerefMut;
     FuncArg<T>(T): into_inner From AsRef AsMut Deref DerefMut;
     Opt<T>(Option<T>): into_inner From AsRef AsMut Deref DerefMut;
+    Null<T>(Option<T>): into_inner From AsRef AsMut Deref DerefMut;
+    UndefinedAsNull<T>(T): into_inner From AsRef AsMut Deref DerefMut;
     Rest<T>(Vec<T>): into_inner From AsRef AsMut Deref DerefMut;
     Flat<T>(T): into_inner From AsRef AsMut Deref DerefMut;
 }
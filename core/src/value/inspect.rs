@@ -0,0 +1,569 @@
+use std::fmt;
+
+use crate::{
+    atom::PredefinedAtom, convert::Coerced, qjs, Array, Atom, Ctx, Error, Exception, FromJs,
+    Function, Object, Result, StdString, Symbol, Type, Value,
+};
+
+use super::object::Filter;
+
+/// Options for [`Value::inspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InspectOptions {
+    /// How many levels of nested objects to show, deeper objects are abbreviated like
+    /// `[Object]`.
+    pub depth: usize,
+    /// The maximum number of elements of arrays, typed arrays, maps and sets to show.
+    pub max_array_length: usize,
+    /// The maximum number of characters of strings to show.
+    pub max_string_length: usize,
+    /// The line length above which objects are split over multiple lines.
+    pub break_length: usize,
+    /// Also show non-enumerable properties.
+    pub show_hidden: bool,
+}
+
+impl Default for InspectOptions {
+    fn default() -> Self {
+        InspectOptions {
+            depth: 2,
+            max_array_length: 100,
+            max_string_length: 10000,
+            break_length: 80,
+            show_hidden: false,
+        }
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Format the value as a human readable string, like `util.inspect` in Node.js does.
+    ///
+    /// Unlike `JSON.stringify` this handles cycles, functions and symbols, and shows the class
+    /// names of objects. Accessor properties are shown as `[Getter]` and `[Setter]` without
+    /// calling them.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value, InspectOptions};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value: Value = ctx.eval("const a = { b: [1, 'two'], f() {} }; a.self = a; a").unwrap();
+    /// let res = value.inspect(InspectOptions::default()).unwrap();
+    /// assert_eq!(res, "{ b: [ 1, 'two' ], f: [Function: f], self: [Circular] }");
+    /// # });
+    /// ```
+    pub fn inspect(&self, options: InspectOptions) -> Result<StdString> {
+        Inspector {
+            ctx: self.ctx.clone(),
+            options,
+            seen: Vec::new(),
+        }
+        .value(self, 0)
+    }
+}
+
+enum Property<'js> {
+    Value(Value<'js>),
+    Accessor { get: bool, set: bool },
+}
+
+/// Reads an own property without invoking getters.
+fn own_property<'js>(obj: &Object<'js>, atom: &Atom<'js>) -> Result<Option<Property<'js>>> {
+    Ok(obj.get_own_property_descriptor(atom.clone())?.map(|desc| {
+        if desc.is_accessor() {
+            Property::Accessor {
+                get: desc.get.is_some(),
+                set: desc.set.is_some(),
+            }
+        } else {
+            Property::Value(
+                desc.value
+                    .unwrap_or_else(|| Value::new_undefined(obj.ctx().clone())),
+            )
+        }
+    }))
+}
+
+fn own_value<'js>(obj: &Object<'js>, atom: PredefinedAtom) -> Result<Option<Value<'js>>> {
+    let atom = Atom::from_predefined(obj.ctx().clone(), atom);
+    Ok(match own_property(obj, &atom)? {
+        Some(Property::Value(x)) => Some(x),
+        _ => None,
+    })
+}
+
+fn quote(s: &str, max_len: usize) -> StdString {
+    let mut res = StdString::with_capacity(s.len() + 2);
+    res.push('\'');
+    let mut count = 0;
+    for c in s.chars() {
+        if count == max_len {
+            break;
+        }
+        count += 1;
+        match c {
+            '\'' => res.push_str("\\'"),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\x{:02X}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('\'');
+    let remaining = s.chars().count() - count;
+    if remaining > 0 {
+        res.push_str(&format!(
+            "... {} more character{}",
+            remaining,
+            plural(remaining)
+        ));
+    }
+    res
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn is_index(s: &str) -> bool {
+    s.parse::<u32>()
+        .map(|x| x.to_string() == s)
+        .unwrap_or(false)
+}
+
+struct Inspector<'js> {
+    ctx: Ctx<'js>,
+    options: InspectOptions,
+    seen: Vec<Object<'js>>,
+}
+
+impl<'js> Inspector<'js> {
+    fn value(&mut self, value: &Value<'js>, level: usize) -> Result<StdString> {
+        Ok(match value.type_of() {
+            Type::Uninitialized => "<uninitialized>".into(),
+            Type::Undefined => "undefined".into(),
+            Type::Null => "null".into(),
+            Type::Bool => value.as_bool().unwrap().to_string(),
+            Type::Int => value.as_int().unwrap().to_string(),
+            Type::Float => {
+                let x = value.as_float().unwrap();
+                if x == 0.0 && x.is_sign_negative() {
+                    "-0".into()
+                } else {
+                    Coerced::<StdString>::from_js(&self.ctx, value.clone())?.0
+                }
+            }
+            Type::BigInt => {
+                format!(
+                    "{}n",
+                    Coerced::<StdString>::from_js(&self.ctx, value.clone())?.0
+                )
+            }
+            Type::String => quote(
+                &value.as_string().unwrap().to_string()?,
+                self.options.max_string_length,
+            ),
+            Type::Symbol => self.symbol(value.as_symbol().unwrap())?,
+            Type::Module => "[Module]".into(),
+            Type::Unknown => "<unknown>".into(),
+            Type::Object | Type::Array | Type::Function | Type::Constructor | Type::Exception => {
+                self.object(value.as_object().unwrap(), level)?
+            }
+        })
+    }
+
+    fn symbol(&self, symbol: &Symbol<'js>) -> Result<StdString> {
+        let desc = symbol.description()?.into_value();
+        Ok(if desc.is_undefined() {
+            "Symbol()".into()
+        } else {
+            format!(
+                "Symbol({})",
+                Coerced::<StdString>::from_js(&self.ctx, desc)?.0
+            )
+        })
+    }
+
+    fn key(&self, atom: &Atom<'js>) -> Result<StdString> {
+        let key = unsafe {
+            Value::from_js_value(
+                self.ctx.clone(),
+                qjs::JS_AtomToValue(self.ctx.as_ptr(), atom.atom),
+            )
+        };
+        if let Some(symbol) = key.as_symbol() {
+            return Ok(format!("[{}]", self.symbol(symbol)?));
+        }
+        let key = atom.to_string()?;
+        Ok(if is_identifier(&key) {
+            key
+        } else {
+            quote(&key, usize::MAX)
+        })
+    }
+
+    fn builtin(&self, name: PredefinedAtom) -> Option<Object<'js>> {
+        self.ctx
+            .globals()
+            .get::<_, Option<Object>>(name)
+            .ok()
+            .flatten()
+    }
+
+    fn class_name(&self, obj: &Object<'js>) -> Result<Option<StdString>> {
+        let Some(proto) = obj.get_prototype() else {
+            return Ok(None);
+        };
+        let name = own_value(&proto, PredefinedAtom::Constructor)?
+            .and_then(Value::into_object)
+            .map(|ctor| own_value(&ctor, PredefinedAtom::Name))
+            .transpose()?
+            .flatten()
+            .and_then(|name| name.as_string().map(|x| x.to_string()))
+            .transpose()?;
+        Ok(Some(name.unwrap_or_default()))
+    }
+
+    fn function(&self, func: &Function<'js>) -> Result<StdString> {
+        let kind = match (func.is_async(), func.is_generator()) {
+            (false, false) => "Function",
+            (false, true) => "GeneratorFunction",
+            (true, false) => "AsyncFunction",
+            (true, true) => "AsyncGeneratorFunction",
+        };
+        let name = own_value(func, PredefinedAtom::Name)?
+            .and_then(|name| name.as_string().map(|x| x.to_string()))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(if name.is_empty() {
+            format!("[{} (anonymous)]", kind)
+        } else {
+            format!("[{}: {}]", kind, name)
+        })
+    }
+
+    fn error(&self, error: Exception<'js>) -> Result<StdString> {
+        let name = error
+            .get::<_, Option<Coerced<StdString>>>(PredefinedAtom::Name)?
+            .map(|x| x.0)
+            .unwrap_or_else(|| "Error".into());
+        let mut res = match error.message() {
+            Some(message) if !message.is_empty() => format!("{}: {}", name, message),
+            _ => name,
+        };
+        if let Some(stack) = error.stack() {
+            let stack = stack.trim_end();
+            if !stack.is_empty() {
+                res.push('\n');
+                res.push_str(stack);
+            }
+        }
+        Ok(res)
+    }
+
+    fn object(&mut self, obj: &Object<'js>, level: usize) -> Result<StdString> {
+        if self.seen.contains(obj) {
+            return Ok("[Circular]".into());
+        }
+
+        if let Some(func) = obj.as_function() {
+            return self.function(func);
+        }
+        if let Some(error) = Exception::from_object(obj.clone()) {
+            return self.error(error);
+        }
+        if let Some(date) = obj.as_date() {
+            return if date.is_valid()? {
+                date.to_iso_string()
+            } else {
+                Ok("Invalid Date".into())
+            };
+        }
+        if let Some(re) = obj.as_regexp() {
+            let source = re.source()?;
+            let source = if source.is_empty() { "(?:)" } else { &source };
+            return Ok(format!("/{}/{}", source, re.flags()?));
+        }
+
+        let class_name = self.class_name(obj)?;
+        let name = match class_name.as_deref() {
+            None => "[Object: null prototype]",
+            Some("Object") | Some("") => "",
+            Some(name) => name,
+        };
+        let typed_array = self
+            .builtin(PredefinedAtom::Uint8Array)
+            .and_then(|x| x.get_prototype())
+            .map(|x| obj.is_instance_of(x))
+            .unwrap_or(false);
+        let is_list = obj.as_array().is_some() || typed_array;
+
+        if level > self.options.depth {
+            let name = match name {
+                "" => "Object",
+                name => name.trim_start_matches('[').trim_end_matches(']'),
+            };
+            return Ok(format!("[{}]", name));
+        }
+
+        self.seen.push(obj.clone());
+        let entries = self.entries(obj, level, is_list);
+        self.seen.pop();
+        let (size, entries) = entries?;
+
+        let mut prefix = match size {
+            _ if is_list && name == "Array" => StdString::new(),
+            Some(size) => format!("{}({})", name, size),
+            None => name.to_owned(),
+        };
+        if !prefix.is_empty() {
+            prefix.push(' ');
+        }
+        let (open, close) = if is_list { ("[", "]") } else { ("{", "}") };
+        Ok(self.join(&prefix, open, close, entries, level))
+    }
+
+    fn more(entries: &mut Vec<StdString>, len: usize, max: usize) {
+        if len > max {
+            let more = len - max;
+            entries.push(format!("... {} more item{}", more, plural(more)));
+        }
+    }
+
+    fn entries(
+        &mut self,
+        obj: &Object<'js>,
+        level: usize,
+        is_list: bool,
+    ) -> Result<(Option<usize>, Vec<StdString>)> {
+        let max = self.options.max_array_length;
+        let mut size = None;
+        let mut entries = Vec::new();
+
+        if is_list {
+            let len: usize = obj.get(PredefinedAtom::Length)?;
+            size = Some(len);
+            let mut holes = 0;
+            for i in 0..len.min(max) {
+                let atom = Atom::from_u32(self.ctx.clone(), i as u32)?;
+                let Some(prop) = own_property(obj, &atom)? else {
+                    holes += 1;
+                    continue;
+                };
+                if holes > 0 {
+                    entries.push(format!("<{} empty item{}>", holes, plural(holes)));
+                    holes = 0;
+                }
+                entries.push(self.property(prop, level)?);
+            }
+            if holes > 0 {
+                entries.push(format!("<{} empty item{}>", holes, plural(holes)));
+            }
+            Self::more(&mut entries, len, max);
+        } else if let Some(map) = obj.as_map() {
+            let len = map.len()?;
+            size = Some(len);
+            for entry in map.entries::<Value, Value>()?.take(max) {
+                let (key, value) = entry?;
+                entries.push(format!(
+                    "{} => {}",
+                    self.value(&key, level + 1)?,
+                    self.value(&value, level + 1)?
+                ));
+            }
+            Self::more(&mut entries, len, max);
+        } else if let Some(set) = obj.as_set() {
+            let len = set.len()?;
+            size = Some(len);
+            for value in set.values::<Value>()?.take(max) {
+                entries.push(self.value(&value?, level + 1)?);
+            }
+            Self::more(&mut entries, len, max);
+        } else if obj.is_builtin(PredefinedAtom::ArrayBuffer) {
+            let len: usize = obj.get("byteLength")?;
+            entries.push(format!("byteLength: {}", len));
+        }
+
+        let mut filter = Filter::new().string().symbol();
+        if !self.options.show_hidden {
+            filter = filter.enum_only();
+        }
+        for atom in obj.own_keys::<Atom>(filter) {
+            let atom = atom?;
+            if is_list && is_index(&atom.to_string()?) {
+                continue;
+            }
+            if let Some(prop) = own_property(obj, &atom)? {
+                let key = self.key(&atom)?;
+                entries.push(format!("{}: {}", key, self.property(prop, level)?));
+            }
+        }
+        Ok((size, entries))
+    }
+
+    fn property(&mut self, prop: Property<'js>, level: usize) -> Result<StdString> {
+        Ok(match prop {
+            Property::Value(value) => self.value(&value, level + 1)?,
+            Property::Accessor {
+                get: true,
+                set: true,
+            } => "[Getter/Setter]".into(),
+            Property::Accessor { get: true, .. } => "[Getter]".into(),
+            Property::Accessor { .. } => "[Setter]".into(),
+        })
+    }
+
+    fn join(
+        &self,
+        prefix: &str,
+        open: &str,
+        close: &str,
+        entries: Vec<StdString>,
+        level: usize,
+    ) -> StdString {
+        if entries.is_empty() {
+            return format!("{}{}{}", prefix, open, close);
+        }
+        let single = format!("{}{} {} {}", prefix, open, entries.join(", "), close);
+        if single.len() + level * 2 <= self.options.break_length
+            && !entries.iter().any(|x| x.contains('\n'))
+        {
+            return single;
+        }
+        let mut res = format!("{}{}\n", prefix, open);
+        let count = entries.len();
+        for (idx, entry) in entries.into_iter().enumerate() {
+            res.push_str("  ");
+            res.push_str(&entry.replace('\n', "\n  "));
+            if idx + 1 < count {
+                res.push(',');
+            }
+            res.push('\n');
+        }
+        res.push_str(close);
+        res
+    }
+}
+
+fn debug_inspect(value: &Value, f: &mut fmt::Formatter) -> fmt::Result {
+    match value.inspect(InspectOptions::default()) {
+        Ok(res) => f.write_str(&res),
+        Err(Error::Exception) => {
+            value.ctx().catch();
+            fmt::Debug::fmt(value, f)
+        }
+        Err(_) => fmt::Debug::fmt(value, f),
+    }
+}
+
+macro_rules! debug_impls {
+    ($($type:ident,)*) => {
+        $(
+            impl<'js> fmt::Debug for $type<'js> {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    debug_inspect(self.as_value(), f)
+                }
+            }
+        )*
+    };
+}
+
+debug_impls! {
+    Object,
+    Array,
+    Function,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, InspectOptions, Object, Value};
+
+    #[test]
+    fn inspect() {
+        test_with(|ctx| {
+            let inspect = |source: &str| {
+                ctx.eval::<Value, _>(source)
+                    .unwrap()
+                    .inspect(InspectOptions::default())
+                    .unwrap()
+            };
+            assert_eq!(inspect("undefined"), "undefined");
+            assert_eq!(inspect("-0"), "-0");
+            assert_eq!(inspect("1.5e300"), "1.5e+300");
+            assert_eq!(inspect("12n"), "12n");
+            assert_eq!(inspect("\"it's\\n\""), "'it\\'s\\n'");
+            assert_eq!(inspect("Symbol('a')"), "Symbol(a)");
+            assert_eq!(inspect("[1, , , 'a']"), "[ 1, <2 empty items>, 'a' ]");
+            assert_eq!(
+                inspect("({ a: { b: { c: { d: 1 } } }, 'x-y': [], [Symbol.iterator]: null })"),
+                "{ a: { b: { c: [Object] } }, 'x-y': [], [Symbol(Symbol.iterator)]: null }"
+            );
+            assert_eq!(
+                inspect("class Point { constructor() { this.x = 1; } get y() { return 2; } }; new Point()"),
+                "Point { x: 1 }"
+            );
+            assert_eq!(
+                inspect("({ get a() { throw 1 }, set b(v) {} })"),
+                "{ a: [Getter], b: [Setter] }"
+            );
+            assert_eq!(
+                inspect("Object.create(null)"),
+                "[Object: null prototype] {}"
+            );
+            assert_eq!(inspect("new Uint8Array([1, 2])"), "Uint8Array(2) [ 1, 2 ]");
+            assert_eq!(
+                inspect("new Map([['a', 1], [{}, new Set([2])]])"),
+                "Map(2) { 'a' => 1, {} => Set(1) { 2 } }"
+            );
+            assert_eq!(
+                inspect("new ArrayBuffer(4)"),
+                "ArrayBuffer { byteLength: 4 }"
+            );
+            assert_eq!(
+                inspect("[async function* gen() {}, () => {}]"),
+                "[ [AsyncGeneratorFunction: gen], [Function (anonymous)] ]"
+            );
+            assert_eq!(inspect("new Date(0)"), "1970-01-01T00:00:00.000Z");
+            assert_eq!(inspect("/a+/g"), "/a+/g");
+            assert!(inspect("new TypeError('bad')").starts_with("TypeError: bad\n"));
+            assert_eq!(
+                inspect("Array.from({ length: 120 }, (_, i) => i).slice(0, 101)")
+                    .lines()
+                    .last(),
+                Some("]")
+            );
+            assert!(inspect("Array.from({ length: 120 }, () => 0)").contains("... 20 more items"));
+            assert_eq!(
+                inspect("({ a: 'aaaaaaaaaaaaaaaaaaaa', b: 'bbbbbbbbbbbbbbbbbbbb', c: 'cccccccccccccccccccc', d: [1] })"),
+                "{\n  a: 'aaaaaaaaaaaaaaaaaaaa',\n  b: 'bbbbbbbbbbbbbbbbbbbb',\n  c: 'cccccccccccccccccccc',\n  d: [ 1 ]\n}"
+            );
+
+            let value: Value = ctx.eval("'a'.repeat(20)").unwrap();
+            let options = InspectOptions {
+                max_string_length: 3,
+                ..InspectOptions::default()
+            };
+            assert_eq!(
+                value.inspect(options).unwrap(),
+                "'aaa'... 17 more characters"
+            );
+
+            let obj: Object = ctx
+                .eval("const obj = { list: [1] }; obj.list.push(obj); obj")
+                .unwrap();
+            assert_eq!(format!("{:?}", obj), "{ list: [ 1, [Circular] ] }");
+        })
+    }
+}
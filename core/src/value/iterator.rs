@@ -0,0 +1,444 @@
+//! Bridging between Rust iterators and JavaScript iterators.
+
+use std::{cell::RefCell, fmt, marker::PhantomData, ops::Deref, rc::Rc};
+
+use crate::{
+    atom::PredefinedAtom,
+    convert::Coerced,
+    function::{Opt, This},
+    qjs,
+    result::BorrowError,
+    Ctx, Error, FromJs, Function, IntoJs, Object, Result, Value,
+};
+
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "stream")))]
+pub use stream::{AsyncIterable, ReadableStream};
+
+/// The result of a step of the iterator protocol, a `{ value, done }` object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct IteratorResult<T> {
+    /// The produced value, or the returned value when the iterator is done.
+    pub value: T,
+    /// Whether the iterator is finished.
+    pub done: bool,
+}
+
+impl<T> IteratorResult<Option<T>> {
+    /// The next item, with the iterator done if there is none.
+    pub(crate) fn next(value: Option<T>) -> Self {
+        IteratorResult {
+            done: value.is_none(),
+            value,
+        }
+    }
+}
+
+impl<T> IteratorResult<T> {
+    /// The final result of a finished iterator.
+    pub(crate) fn done(value: T) -> Self {
+        IteratorResult { value, done: true }
+    }
+}
+
+impl<'js, T: IntoJs<'js>> IntoJs<'js> for IteratorResult<T> {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let res = Object::new(ctx.clone())?;
+        res.set(PredefinedAtom::Value, self.value)?;
+        res.set(PredefinedAtom::Done, self.done)?;
+        res.into_js(ctx)
+    }
+}
+
+impl<'js, T: FromJs<'js>> FromJs<'js> for IteratorResult<T> {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let obj = Object::from_js(ctx, value)?;
+        Ok(IteratorResult {
+            value: obj.get(PredefinedAtom::Value)?,
+            done: obj.get::<_, Coerced<bool>>(PredefinedAtom::Done)?.0,
+        })
+    }
+}
+
+/// A JavaScript generator object, as returned by calling a generator function.
+///
+/// This allows driving the generator as a coroutine from Rust, resuming it with a value, an
+/// early return or an exception.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, iterator::{Generator, IteratorResult}};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let gen: Generator = ctx.eval("(function*() { const x = yield 1; yield x * 2; })()").unwrap();
+/// assert_eq!(gen.next::<_, i32>(()).unwrap(), IteratorResult { value: 1, done: false });
+/// assert_eq!(gen.next::<_, i32>(21).unwrap(), IteratorResult { value: 42, done: false });
+/// assert!(gen.next::<_, ()>(()).unwrap().done);
+/// # });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Generator<'js>(pub(crate) Object<'js>);
+
+impl<'js> Generator<'js> {
+    /// Resume the generator, with the value becoming the result of the paused `yield`.
+    pub fn next<A, T>(&self, value: A) -> Result<IteratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.0.call_method("next", (value,))
+    }
+
+    /// Resume the generator as if the paused `yield` was a `return` statement.
+    ///
+    /// The generator can still yield values from `finally` blocks.
+    pub fn return_<A, T>(&self, value: A) -> Result<IteratorResult<T>>
+    where
+        A: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.0.call_method("return", (value,))
+    }
+
+    /// Resume the generator by throwing the error from the paused `yield`.
+    ///
+    /// Returns an error if the generator doesn't catch the thrown value.
+    pub fn throw<E, T>(&self, error: E) -> Result<IteratorResult<T>>
+    where
+        E: IntoJs<'js>,
+        T: FromJs<'js>,
+    {
+        self.0.call_method("throw", (error,))
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object, if it is a generator
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        let is_generator = unsafe { qjs::JS_IsGenerator(object.as_js_value()) } != 0;
+        is_generator.then_some(Generator(object))
+    }
+}
+
+impl<'js> Deref for Generator<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> FromJs<'js> for Generator<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        value
+            .into_object()
+            .and_then(Self::from_object)
+            .ok_or_else(|| Error::new_from_js(ty_name, "Generator"))
+    }
+}
+
+impl<'js> IntoJs<'js> for Generator<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.0.into_value())
+    }
+}
+
+/// A helper type for returning a Rust iterator to JavaScript.
+///
+/// Converting it creates a JavaScript iterator object which can be consumed with `for..of` or
+/// spread syntax. The items are only produced and converted when JavaScript asks for the next
+/// value, and the Rust iterator is dropped once it is exhausted or the loop exits early.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, iterator::Iterable};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let squares = Function::new(ctx.clone(), |n: u32| Iterable((0..n).map(|x| x * x))).unwrap();
+/// ctx.globals().set("squares", squares).unwrap();
+/// let sum: u32 = ctx.eval("let sum = 0; for (const x of squares(4)) { sum += x; } sum").unwrap();
+/// assert_eq!(sum, 14);
+/// # });
+/// ```
+pub struct Iterable<I>(pub I);
+
+impl<'js, I> IntoJs<'js> for Iterable<I>
+where
+    I: IntoIterator,
+    I::IntoIter: 'js,
+    I::Item: IntoJs<'js> + 'js,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let state = Rc::new(RefCell::new(Some(self.0.into_iter())));
+        let res = Object::new(ctx.clone())?;
+
+        let next_state = state.clone();
+        let next = Function::new(
+            ctx.clone(),
+            move || -> Result<IteratorResult<Option<I::Item>>> {
+                let mut iter = next_state
+                    .try_borrow_mut()
+                    .map_err(|_| Error::FunctionBorrow(BorrowError::AlreadyBorrowed))?;
+                let item = iter.as_mut().and_then(Iterator::next);
+                if item.is_none() {
+                    *iter = None;
+                }
+                Ok(IteratorResult::next(item))
+            },
+        )?;
+        res.set(PredefinedAtom::Next, next)?;
+
+        let ret = Function::new(ctx.clone(), move |value: Opt<Value<'js>>| {
+            state
+                .try_borrow_mut()
+                .map_err(|_| Error::FunctionBorrow(BorrowError::AlreadyBorrowed))?
+                .take();
+            Ok::<_, Error>(IteratorResult::done(value.0))
+        })?;
+        res.set(PredefinedAtom::Return, ret)?;
+
+        let iter = Function::new(ctx.clone(), |this: This<Object<'js>>| this.0)?;
+        res.set(PredefinedAtom::SymbolIterator, iter)?;
+        res.into_js(ctx)
+    }
+}
+
+/// A Rust iterator over the values of a JavaScript iterable.
+///
+/// It can be converted from any value with a `Symbol.iterator` method, like arrays, maps, sets
+/// and generators. Each item is converted with [`FromJs`] when it is requested.
+pub struct JsIterator<'js, T = Value<'js>> {
+    iter: Object<'js>,
+    next: Function<'js>,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<'js, T> fmt::Debug for JsIterator<'js, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsIterator")
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<'js, T> JsIterator<'js, T> {
+    /// Get the iterator of an iterable value by calling its `Symbol.iterator` method.
+    pub fn from_iterable(value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        let method = value
+            .as_object()
+            .map(|obj| obj.get::<_, Value>(PredefinedAtom::SymbolIterator))
+            .transpose()?
+            .and_then(|method| method.into_function())
+            .ok_or_else(|| Error::new_from_js(ty_name, "iterable"))?;
+        let iter: Object = method.call((This(value),))?;
+        Self::from_iterator(iter)
+    }
+
+    /// Wrap an object which already implements the iterator protocol.
+    pub fn from_iterator(iter: Object<'js>) -> Result<Self> {
+        let next = iter.get(PredefinedAtom::Next)?;
+        Ok(JsIterator {
+            iter,
+            next,
+            done: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the underlying JavaScript iterator object.
+    pub fn into_object(self) -> Object<'js> {
+        self.iter
+    }
+
+    /// Stop iterating early, calling the `return` method of the iterator if it has one.
+    ///
+    /// This allows the iterator to clean up, like `break` inside a `for..of` loop does.
+    pub fn close(mut self) -> Result<()> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        let ret: Option<Function> = self.iter.get(PredefinedAtom::Return)?;
+        if let Some(ret) = ret {
+            ret.call::<_, Value>((This(self.iter.clone()),))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'js, T: FromJs<'js>> Iterator for JsIterator<'js, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self
+            .next
+            .call((This(self.iter.clone()),))
+            .and_then(|res: Object<'js>| {
+                if res.get(PredefinedAtom::Done)? {
+                    Ok(None)
+                } else {
+                    res.get(PredefinedAtom::Value).map(Some)
+                }
+            });
+        match res {
+            Ok(Some(x)) => Some(Ok(x)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'js, T> FromJs<'js> for JsIterator<'js, T> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        Self::from_iterable(value)
+    }
+}
+
+impl<'js, T> IntoJs<'js> for JsIterator<'js, T> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.iter.into_value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::{
+        iterator::{Generator, Iterable, IteratorResult, JsIterator},
+        test_with, Function, Result, StdString,
+    };
+
+    #[test]
+    fn rust_to_js() {
+        test_with(|ctx| {
+            let produced = Rc::new(Cell::new(0));
+            let count = produced.clone();
+            let f = Function::new(ctx.clone(), move || {
+                let count = count.clone();
+                Iterable((0..100).inspect(move |_| count.set(count.get() + 1)))
+            })
+            .unwrap();
+            ctx.globals().set("numbers", f).unwrap();
+
+            let res: Vec<i32> = ctx
+                .eval(
+                    r#"
+                    const res = [];
+                    for (const x of numbers()) {
+                        if (x == 3) break;
+                        res.push(x);
+                    }
+                    res
+                "#,
+                )
+                .unwrap();
+            assert_eq!(res, [0, 1, 2]);
+            assert_eq!(produced.get(), 4);
+
+            let res: Vec<i32> = ctx.eval("[...numbers()].slice(-2)").unwrap();
+            assert_eq!(res, [98, 99]);
+            let res: bool = ctx
+                .eval("const it = numbers(); it.return(); it.next().done")
+                .unwrap();
+            assert!(res);
+        })
+    }
+
+    #[test]
+    fn js_to_rust() {
+        test_with(|ctx| {
+            let iter: JsIterator<i32> = ctx.eval("new Set([1, 2, 3])").unwrap();
+            let res = iter.collect::<Result<Vec<_>>>().unwrap();
+            assert_eq!(res, [1, 2, 3]);
+
+            let mut iter: JsIterator<i32> = ctx
+                .eval("globalThis.closed = false; (function*() { try { yield 1; yield 2; } finally { closed = true; } })()")
+                .unwrap();
+            assert_eq!(iter.next().unwrap().unwrap(), 1);
+            iter.close().unwrap();
+            assert!(ctx.globals().get::<_, bool>("closed").unwrap());
+
+            assert!(ctx.eval::<JsIterator, _>("42").is_err());
+        })
+    }
+
+    #[test]
+    fn generator() {
+        test_with(|ctx| {
+            let gen: Generator = ctx
+                .eval(
+                    r#"
+                    (function*() {
+                        let x = 0;
+                        while (x < 100) {
+                            try {
+                                x += yield x;
+                            } catch (e) {
+                                x = e;
+                            }
+                        }
+                        return "big";
+                    })()
+                "#,
+                )
+                .unwrap();
+            let res = gen.next::<_, i32>(()).unwrap();
+            assert_eq!(
+                res,
+                IteratorResult {
+                    value: 0,
+                    done: false
+                }
+            );
+            assert_eq!(gen.next::<_, i32>(5).unwrap().value, 5);
+            assert_eq!(gen.throw::<_, i32>(10).unwrap().value, 10);
+            let res = gen.next::<_, StdString>(200).unwrap();
+            assert_eq!(
+                res,
+                IteratorResult {
+                    value: "big".into(),
+                    done: true
+                }
+            );
+
+            let gen: Generator = ctx.eval("(function*() { yield 1; yield 2; })()").unwrap();
+            let res = gen.return_::<_, i32>(3).unwrap();
+            assert_eq!(
+                res,
+                IteratorResult {
+                    value: 3,
+                    done: true
+                }
+            );
+            assert!(gen.throw::<_, ()>("error").is_err());
+
+            assert!(ctx.eval::<Generator, _>("[][Symbol.iterator]()").is_err());
+        })
+    }
+}
@@ -0,0 +1,248 @@
+use std::{future::poll_fn, pin::Pin, rc::Rc};
+
+use async_lock::Mutex;
+use futures_core::Stream;
+
+use crate::{
+    atom::PredefinedAtom,
+    function::{Opt, This},
+    iterator::IteratorResult,
+    promise::Promised,
+    Ctx, Function, IntoJs, Object, Result, Value,
+};
+
+type State<S> = Rc<Mutex<Option<Pin<Box<S>>>>>;
+
+async fn next_step<S: Stream>(state: State<S>) -> IteratorResult<Option<S::Item>> {
+    let mut lock = state.lock().await;
+    let Some(stream) = lock.as_mut() else {
+        return IteratorResult::done(None);
+    };
+    let item = poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+    if item.is_none() {
+        *lock = None;
+    }
+    IteratorResult::next(item)
+}
+
+async fn close<'js, S>(
+    state: State<S>,
+    value: Option<Value<'js>>,
+) -> IteratorResult<Option<Value<'js>>> {
+    state.lock().await.take();
+    IteratorResult::done(value)
+}
+
+fn next_fn<'js, S>(ctx: &Ctx<'js>, state: &State<S>) -> Result<Function<'js>>
+where
+    S: Stream + 'js,
+    S::Item: IntoJs<'js> + 'js,
+{
+    let state = state.clone();
+    Function::new(ctx.clone(), move || Promised(next_step(state.clone())))
+}
+
+fn close_fn<'js, S>(ctx: &Ctx<'js>, state: &State<S>) -> Result<Function<'js>>
+where
+    S: 'js,
+{
+    let state = state.clone();
+    Function::new(ctx.clone(), move |value: Opt<Value<'js>>| {
+        Promised(close(state.clone(), value.0))
+    })
+}
+
+fn async_iterator<'js, S>(ctx: &Ctx<'js>, state: &State<S>) -> Result<Object<'js>>
+where
+    S: Stream + 'js,
+    S::Item: IntoJs<'js> + 'js,
+{
+    let res = Object::new(ctx.clone())?;
+    res.set(PredefinedAtom::Next, next_fn(ctx, state)?)?;
+    res.set(PredefinedAtom::Return, close_fn(ctx, state)?)?;
+    let iter = Function::new(ctx.clone(), |this: This<Object<'js>>| this.0)?;
+    res.set(PredefinedAtom::SymbolAsyncIterator, iter)?;
+    Ok(res)
+}
+
+/// A helper type for returning a Rust [`Stream`] to JavaScript as an async iterable.
+///
+/// The stream is only polled when JavaScript calls `next()`, with the returned promise resolved
+/// once the stream yields the item. This way a consumer which is slower than the stream holds
+/// back the stream instead of having the items buffered. Items of type [`Result`] reject the
+/// promise on errors.
+///
+/// ```
+/// # use rquickjs::{AsyncRuntime, AsyncContext, async_with, Function, promise::Promise, iterator::AsyncIterable};
+/// # use futures_rs::stream;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let rt = AsyncRuntime::new().unwrap();
+/// let ctx = AsyncContext::full(&rt).await.unwrap();
+/// async_with!(ctx => |ctx| {
+///     let chunks = Function::new(ctx.clone(), || AsyncIterable(stream::iter(["a", "b", "c"]))).unwrap();
+///     ctx.globals().set("chunks", chunks).unwrap();
+///     let res: Promise<String> = ctx
+///         .eval("(async () => { let res = ''; for await (const x of chunks()) res += x; return res })()")
+///         .unwrap();
+///     assert_eq!(res.await.unwrap(), "abc");
+/// })
+/// .await;
+/// # }
+/// ```
+pub struct AsyncIterable<S>(pub S);
+
+impl<'js, S> IntoJs<'js> for AsyncIterable<S>
+where
+    S: Stream + 'js,
+    S::Item: IntoJs<'js> + 'js,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let state = Rc::new(Mutex::new(Some(Box::pin(self.0))));
+        async_iterator(ctx, &state)?.into_js(ctx)
+    }
+}
+
+/// A helper type for returning a Rust [`Stream`] to JavaScript as a minimal `ReadableStream`.
+///
+/// The object has the `getReader()` and `cancel()` methods and is async iterable. The readers
+/// implement `read()`, `cancel()` and `releaseLock()`, where `read()` polls the stream like
+/// `next()` of [`AsyncIterable`] does. Locking and queuing strategies are not implemented.
+pub struct ReadableStream<S>(pub S);
+
+impl<'js, S> IntoJs<'js> for ReadableStream<S>
+where
+    S: Stream + 'js,
+    S::Item: IntoJs<'js> + 'js,
+{
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        let state = Rc::new(Mutex::new(Some(Box::pin(self.0))));
+        let res = Object::new(ctx.clone())?;
+
+        let reader_state = state.clone();
+        let get_reader = Function::new(ctx.clone(), move |ctx: Ctx<'js>| -> Result<Object<'js>> {
+            let reader = Object::new(ctx.clone())?;
+            reader.set("read", next_fn(&ctx, &reader_state)?)?;
+            reader.set("cancel", close_fn(&ctx, &reader_state)?)?;
+            reader.set("releaseLock", Function::new(ctx.clone(), || {})?)?;
+            Ok(reader)
+        })?;
+        res.set("getReader", get_reader)?;
+        res.set("cancel", close_fn(ctx, &state)?)?;
+
+        let iter = Function::new(ctx.clone(), move |ctx: Ctx<'js>| {
+            async_iterator(&ctx, &state)
+        })?;
+        res.set(PredefinedAtom::SymbolAsyncIterator, iter)?;
+        res.into_js(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use futures_rs::stream::{self, StreamExt};
+
+    use crate::{
+        async_with,
+        iterator::{AsyncIterable, ReadableStream},
+        promise::Promise,
+        AsyncContext, AsyncRuntime, CatchResultExt, Error, Function,
+    };
+
+    #[tokio::test]
+    async fn async_iterable() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let produced = Rc::new(Cell::new(0));
+            let count = produced.clone();
+            let f = Function::new(ctx.clone(), move || {
+                let count = count.clone();
+                AsyncIterable(stream::iter(0..100).inspect(move |_| count.set(count.get() + 1)))
+            })
+            .unwrap();
+            ctx.globals().set("numbers", f).unwrap();
+
+            let res: Promise<i32> = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        let sum = 0;
+                        for await (const x of numbers()) {
+                            if (x == 4) break;
+                            sum += x;
+                        }
+                        return sum;
+                    })()
+                "#,
+                )
+                .unwrap();
+            assert_eq!(res.await.catch(&ctx).unwrap(), 6);
+            assert_eq!(produced.get(), 5);
+
+            let f = Function::new(ctx.clone(), || {
+                AsyncIterable(stream::iter([Ok(1), Err(Error::new_loading("chunk"))]))
+            })
+            .unwrap();
+            ctx.globals().set("failing", f).unwrap();
+            let res: Promise<bool> = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        const it = failing();
+                        const first = await it.next();
+                        try {
+                            await it.next();
+                            return false;
+                        } catch (e) {
+                            return first.value === 1 && !first.done;
+                        }
+                    })()
+                "#,
+                )
+                .unwrap();
+            assert!(res.await.catch(&ctx).unwrap());
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn readable_stream() {
+        let rt = AsyncRuntime::new().unwrap();
+        let ctx = AsyncContext::full(&rt).await.unwrap();
+
+        async_with!(ctx => |ctx| {
+            let f = Function::new(ctx.clone(), || {
+                ReadableStream(stream::iter(["a", "b", "c"]))
+            })
+            .unwrap();
+            ctx.globals().set("body", f).unwrap();
+
+            let res: Promise<String> = ctx
+                .eval(
+                    r#"
+                    (async () => {
+                        const reader = body().getReader();
+                        let res = "";
+                        while (true) {
+                            const { value, done } = await reader.read();
+                            if (done) break;
+                            res += value;
+                        }
+                        for await (const x of body()) res += x;
+                        const stream = body();
+                        await stream.cancel();
+                        const { done } = await stream.getReader().read();
+                        return done ? res : "";
+                    })()
+                "#,
+                )
+                .unwrap();
+            assert_eq!(res.await.catch(&ctx).unwrap(), "abcabc");
+        })
+        .await
+    }
+}
@@ -0,0 +1,458 @@
+//! JavaScript `Map` functionality.
+
+use std::{fmt, marker::PhantomData, ops::Deref};
+
+use crate::{
+    convert::{FromIteratorJs, List},
+    qjs, Ctx, Error, FromJs, IntoJs, Object, Outlive, Result, Value,
+};
+
+/// Rust representation of a JavaScript object of class Map.
+///
+/// Rust maps like [`HashMap`](std::collections::HashMap) are converted from and into JavaScript
+/// maps. They can also be converted from plain objects, with the property names as keys:
+/// ```
+/// # use std::collections::HashMap;
+/// # use rquickjs::{IntoJs, Runtime, Context, Map};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let entries: HashMap<String, i32> = ctx.eval("new Map([['a', 1]])").unwrap();
+/// let map = Map::from_value(entries.into_js(&ctx).unwrap()).unwrap();
+/// assert_eq!(map.get::<_, i32>("a").unwrap(), 1);
+/// # })
+/// ```
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Map<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> Outlive<'js> for Map<'js> {
+    type Target<'to> = Map<'to>;
+}
+
+/// Convert the result of one of the raw map functions.
+///
+/// The functions operate on the internal map state directly, so they are unaffected by changes
+/// to `Map.prototype`.
+pub(crate) unsafe fn from_raw<'js, T: FromJs<'js>>(ctx: &Ctx<'js>, val: qjs::JSValue) -> Result<T> {
+    let val = ctx.handle_exception(val)?;
+    T::from_js(ctx, Value::from_js_value(ctx.clone(), val))
+}
+
+impl<'js> Map<'js> {
+    /// Create a new empty map.
+    pub fn new(ctx: Ctx<'js>) -> Result<Self> {
+        unsafe {
+            let val = qjs::JS_NewMap(ctx.as_ptr(), 0);
+            let val = ctx.handle_exception(val)?;
+            Ok(Map(Object::from_js_value(ctx, val)))
+        }
+    }
+
+    /// Create a new map from an iterator of key-value pairs.
+    pub fn from_entries<K, V, I>(ctx: Ctx<'js>, entries: I) -> Result<Self>
+    where
+        K: IntoJs<'js>,
+        V: IntoJs<'js>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let map = Map::new(ctx)?;
+        for (key, value) in entries {
+            map.set(key, value)?;
+        }
+        Ok(map)
+    }
+
+    /// Returns the value associated with a key, `undefined` if the map doesn't contain the key.
+    pub fn get<K: IntoJs<'js>, V: FromJs<'js>>(&self, key: K) -> Result<V> {
+        let ctx = self.ctx();
+        let key = key.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapGet(ctx.as_ptr(), self.as_js_value(), key.as_js_value());
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Set the value associated with a key.
+    pub fn set<K: IntoJs<'js>, V: IntoJs<'js>>(&self, key: K, value: V) -> Result<()> {
+        let ctx = self.ctx();
+        let key = key.into_js(ctx)?;
+        let value = value.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapSet(
+                ctx.as_ptr(),
+                self.as_js_value(),
+                key.as_js_value(),
+                value.as_js_value(),
+                0,
+            );
+            from_raw::<Value>(ctx, val).map(|_| ())
+        }
+    }
+
+    /// Returns whether the map contains a key.
+    pub fn has<K: IntoJs<'js>>(&self, key: K) -> Result<bool> {
+        let ctx = self.ctx();
+        let key = key.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapHas(ctx.as_ptr(), self.as_js_value(), key.as_js_value(), 0);
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Remove a key from the map, returns whether the key was present.
+    pub fn delete<K: IntoJs<'js>>(&self, key: K) -> Result<bool> {
+        let ctx = self.ctx();
+        let key = key.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapDelete(ctx.as_ptr(), self.as_js_value(), key.as_js_value(), 0);
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Remove all entries from the map.
+    pub fn clear(&self) -> Result<()> {
+        let ctx = self.ctx();
+        unsafe { from_raw(ctx, qjs::JS_MapClear(ctx.as_ptr(), self.as_js_value(), 0)) }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> Result<usize> {
+        let ctx = self.ctx();
+        unsafe { from_raw(ctx, qjs::JS_MapSize(ctx.as_ptr(), self.as_js_value(), 0)) }
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns an iterator over the entries of the map in insertion order.
+    ///
+    /// The entries can be collected into a Rust map:
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use rquickjs::{Runtime, Context, Map, Result};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let map: Map = ctx.eval("new Map([['a', 1], ['b', 2]])").unwrap();
+    /// let map: HashMap<String, i32> = map.entries().unwrap().collect::<Result<_>>().unwrap();
+    /// assert_eq!(map["b"], 2);
+    /// # })
+    /// ```
+    pub fn entries<K: FromJs<'js>, V: FromJs<'js>>(&self) -> Result<MapEntries<'js, K, V>> {
+        MapIter::new(&self.0, IterKind::Entries, false).map(MapEntries)
+    }
+
+    /// Returns an iterator over the keys of the map in insertion order.
+    pub fn keys<K: FromJs<'js>>(&self) -> Result<MapIter<'js, K>> {
+        MapIter::new(&self.0, IterKind::Keys, false)
+    }
+
+    /// Returns an iterator over the values of the map in insertion order.
+    pub fn values<V: FromJs<'js>>(&self) -> Result<MapIter<'js, V>> {
+        MapIter::new(&self.0, IterKind::Values, false)
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_map().then_some(Map(object))
+    }
+}
+
+#[derive(Clone, Copy)]
+#[repr(i32)]
+pub(crate) enum IterKind {
+    Keys = 0,
+    Values = 1,
+    Entries = 2,
+}
+
+/// An iterator over the keys or values of a [`Map`] or the values of a [`Set`](crate::Set).
+pub struct MapIter<'js, T> {
+    iter: Object<'js>,
+    is_set: bool,
+    done: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<'js, T> fmt::Debug for MapIter<'js, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapIter").field("done", &self.done).finish()
+    }
+}
+
+impl<'js, T> MapIter<'js, T> {
+    pub(crate) fn new(obj: &Object<'js>, kind: IterKind, is_set: bool) -> Result<Self> {
+        let ctx = obj.ctx();
+        let iter = unsafe {
+            let val =
+                qjs::JS_NewMapIterator(ctx.as_ptr(), obj.as_js_value(), kind as _, is_set as _);
+            from_raw(ctx, val)?
+        };
+        Ok(MapIter {
+            iter,
+            is_set,
+            done: false,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<'js, T: FromJs<'js>> Iterator for MapIter<'js, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let ctx = self.iter.ctx();
+        let mut done = 0;
+        let res = unsafe {
+            let val = qjs::JS_MapIteratorNext(
+                ctx.as_ptr(),
+                self.iter.as_js_value(),
+                &mut done,
+                self.is_set as _,
+            );
+            ctx.handle_exception(val)
+                .map(|val| Value::from_js_value(ctx.clone(), val))
+        };
+        let res = match res {
+            Ok(_) if done != 0 => None,
+            Ok(val) => Some(T::from_js(ctx, val)),
+            Err(e) => Some(Err(e)),
+        };
+        self.done = !matches!(res, Some(Ok(_)));
+        res
+    }
+}
+
+/// An iterator over the entries of a [`Map`].
+#[derive(Debug)]
+pub struct MapEntries<'js, K, V>(MapIter<'js, List<(K, V)>>);
+
+impl<'js, K: FromJs<'js>, V: FromJs<'js>> Iterator for MapEntries<'js, K, V> {
+    type Item = Result<(K, V)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|res| res.map(|List(entry)| entry))
+    }
+}
+
+impl<'js> Deref for Map<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Map<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Map<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Map<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "Map"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for Map<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js, K, V> FromIteratorJs<'js, (K, V)> for Map<'js>
+where
+    K: IntoJs<'js>,
+    V: IntoJs<'js>,
+{
+    type Item = (Value<'js>, Value<'js>);
+
+    fn from_iter_js<T>(ctx: &Ctx<'js>, iter: T) -> Result<Self>
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        Map::from_entries(ctx.clone(), iter)
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`Map`].
+    pub fn is_map(&self) -> bool {
+        unsafe { qjs::JS_IsMap(self.as_js_value()) != 0 }
+    }
+
+    /// Interpret as [`Map`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_map(&self) -> &Map<'js> {
+        &*(self as *const Object as *const Map)
+    }
+
+    /// Turn the object into a map if the object is an instance of [`Map`].
+    pub fn as_map(&self) -> Option<&Map<'js>> {
+        self.is_map().then_some(unsafe { self.ref_map() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::{convert::IteratorJs, *};
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let map: Map = ctx.eval("new Map([[1, 'a'], [2, 'b']])").unwrap();
+            assert_eq!(map.len().unwrap(), 2);
+            assert_eq!(map.get::<_, StdString>(1).unwrap(), "a");
+            assert_eq!(map.get::<_, Option<StdString>>(3).unwrap(), None);
+            assert!(map.has(2).unwrap());
+            assert!(map.delete(2).unwrap());
+            assert!(!map.has(2).unwrap());
+
+            let entries: BTreeMap<i32, StdString> =
+                map.entries().unwrap().collect::<Result<_>>().unwrap();
+            assert_eq!(entries, [(1, "a".to_string())].into_iter().collect());
+
+            map.clear().unwrap();
+            assert!(map.is_empty().unwrap());
+
+            let obj: Object = ctx.eval("({})").unwrap();
+            assert!(!obj.is_map());
+            assert!(Map::from_js(&ctx, obj.into_value()).is_err());
+        })
+    }
+
+    #[test]
+    fn ignores_prototype() {
+        test_with(|ctx| {
+            let map: Map = ctx
+                .eval(
+                    r#"
+                    const map = new Map([[1, 'a']]);
+                    for (const name of ['get', 'set', 'has', 'delete', 'clear', 'entries']) {
+                        Map.prototype[name] = () => { throw new Error(name) };
+                    }
+                    Object.defineProperty(Map.prototype, 'size', { get: () => 42 });
+                    Map.prototype[Symbol.iterator] = () => [][Symbol.iterator]();
+                    globalThis.Map = function() {};
+                    map
+                    "#,
+                )
+                .unwrap();
+            assert!(map.is_map());
+            map.set(2, "b").unwrap();
+            assert_eq!(map.get::<_, StdString>(1).unwrap(), "a");
+            assert!(map.has(2).unwrap());
+            assert_eq!(map.len().unwrap(), 2);
+            let entries: HashMap<StdString, StdString> = ctx.eval("map").unwrap();
+            assert_eq!(entries.len(), 2);
+            assert!(map.delete(1).unwrap());
+            map.clear().unwrap();
+            assert!(Map::new(ctx.clone()).unwrap().is_empty().unwrap());
+
+            let fake: Object = ctx
+                .eval("Object.setPrototypeOf({}, Object.getPrototypeOf(map))")
+                .unwrap();
+            assert!(!fake.is_map());
+        })
+    }
+
+    #[test]
+    fn rust_map_conversion() {
+        test_with(|ctx| {
+            let map: HashMap<StdString, i32> = ctx.eval("new Map([['a', 1], [2, 3]])").unwrap();
+            assert_eq!(map.len(), 2);
+            assert_eq!(map["a"], 1);
+            assert_eq!(map["2"], 3);
+            let map: BTreeMap<StdString, i32> = ctx.eval("new Map([['b', 2], ['a', 1]])").unwrap();
+            assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b"]);
+            let map: HashMap<StdString, i32> = ctx.eval("({ a: 1 })").unwrap();
+            assert_eq!(map["a"], 1);
+
+            let entries: BTreeMap<StdString, i32> = [("a".to_string(), 1), ("b".to_string(), 2)]
+                .into_iter()
+                .collect();
+            let map: Map = entries.clone().into_iter().collect_js(&ctx).unwrap();
+            ctx.globals().set("map", map).unwrap();
+            let res: bool = ctx
+                .eval("map instanceof Map && map.size == 2 && map.get('b') == 2")
+                .unwrap();
+            assert!(res);
+            let back: BTreeMap<StdString, i32> = ctx.eval("map").unwrap();
+            assert_eq!(back, entries);
+        })
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let entries: HashMap<StdString, i32> = [("a".to_string(), 1), ("b".to_string(), 2)]
+                .into_iter()
+                .collect();
+            let map = Map::from_entries(ctx.clone(), &entries).unwrap();
+            ctx.globals().set("map", map).unwrap();
+            let res: bool = ctx
+                .eval("map instanceof Map && map.get('a') == 1 && map.get('b') == 2")
+                .unwrap();
+            assert!(res);
+
+            ctx.globals().set("entries", &entries).unwrap();
+            let numbers: BTreeMap<i32, bool> = [(1, true), (2, false)].into_iter().collect();
+            ctx.globals().set("numbers", numbers).unwrap();
+            let res: bool = ctx
+                .eval("entries instanceof Map && entries.get('b') == 2 && numbers.get(1) === true && !numbers.has('1')")
+                .unwrap();
+            assert!(res);
+        })
+    }
+}
@@ -5,6 +5,7 @@ use std::{
     collections::HashSet,
     ffi::{CStr, CString},
     fmt,
+    io::Read,
     mem::MaybeUninit,
     ptr::{self, NonNull},
     slice,
@@ -13,7 +14,7 @@ use std::{
 #[cfg(feature = "exports")]
 use std::marker::PhantomData;
 
-use crate::{qjs, Atom, Context, Ctx, Error, FromAtom, FromJs, IntoJs, Result, Value};
+use crate::{qjs, Atom, Context, Ctx, Error, FromAtom, FromJs, IntoJs, Result, StdString, Value};
 
 /// Helper macro to provide module init function.
 /// Use for exporting module definitions to be loaded as part of a dynamic library.
@@ -94,7 +95,11 @@ impl ModuleDataKind {
                 let ptr = NonNull::new(ptr).ok_or(Error::Unknown)?;
                 Ok(Module::from_module_def(ctx, ptr))
             }
-            ModuleDataKind::ByteCode(x) => Module::unsafe_declare_read_object(ctx, x.as_ref()),
+            // Static bytecode outlives the runtime so QuickJS can reference it without copying.
+            ModuleDataKind::ByteCode(Cow::Borrowed(x)) => {
+                Module::read_object(ctx, x, qjs::JS_READ_OBJ_ROM_DATA)
+            }
+            ModuleDataKind::ByteCode(Cow::Owned(x)) => Module::read_object(ctx, &x, 0),
         }
     }
 }
@@ -172,6 +177,21 @@ impl ModuleData {
         &self.data
     }
 
+    /// Replace the source of a module loaded from source, other kinds are returned unchanged.
+    pub(crate) fn map_source<F>(self, f: F) -> Result<Self>
+    where
+        F: FnOnce(Vec<u8>) -> Result<Vec<u8>>,
+    {
+        let data = match self.data {
+            ModuleDataKind::Source(source) => ModuleDataKind::Source(f(source)?),
+            data => data,
+        };
+        Ok(ModuleData {
+            name: self.name,
+            data,
+        })
+    }
+
     /// Declare the module defined in the `ModuleData`.
     pub fn declare<'js>(self, ctx: Ctx<'js>) -> Result<()> {
         unsafe {
@@ -189,6 +209,21 @@ impl ModuleData {
     pub unsafe fn unsafe_declare<'js>(self, ctx: Ctx<'js>) -> Result<Module<'js>> {
         self.data.declare(ctx, self.name)
     }
+
+    /// Declare the module without resolving its imports, they are resolved when the module is
+    /// linked.
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    pub(crate) unsafe fn unsafe_declare_unresolved<'js>(
+        self,
+        ctx: Ctx<'js>,
+    ) -> Result<Module<'js>> {
+        match self.data {
+            ModuleDataKind::Source(x) => {
+                Module::declare_source(ctx, self.name, x, qjs::JS_EVAL_FLAG_NO_RESOLVE)
+            }
+            data => data.declare(ctx, self.name),
+        }
+    }
 }
 
 /// A struct for loading multiple modules at once safely.
@@ -279,12 +314,94 @@ impl Default for ModulesBuilder {
 /// Struct used in the [`ModuleDef`] trait for declaring module exports.
 pub struct Declarations {
     declarations: HashSet<Cow<'static, CStr>>,
+    duplicates: Vec<StdString>,
+}
+
+/// The words which can't be imported without renaming them, `default` is the default export.
+const RESERVED_WORDS: [&str; 45] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// A problem with an export declared by a [`ModuleDef`], reported by [`Error::InvalidExports`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ExportIssue {
+    /// The export was declared more than once.
+    Duplicate(StdString),
+    /// The name of the export is a reserved word.
+    Reserved(StdString),
+    /// The name of the export isn't an identifier.
+    NotIdentifier(StdString),
+    /// The name of the export is the name of a global, which is shadowed when it is imported.
+    ShadowsGlobal(StdString),
+}
+
+impl fmt::Display for ExportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportIssue::Duplicate(name) => write!(f, "'{}' is declared more than once", name),
+            ExportIssue::Reserved(name) => write!(f, "'{}' is a reserved word", name),
+            ExportIssue::NotIdentifier(name) => write!(f, "'{}' is not an identifier", name),
+            ExportIssue::ShadowsGlobal(name) => write!(f, "'{}' shadows a global", name),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let start = |c: char| c.is_alphabetic() || c == '_' || c == '$';
+    chars.next().map(start).unwrap_or(false)
+        && chars.all(|c| start(c) || c.is_alphanumeric() || c == '\u{200c}' || c == '\u{200d}')
 }
 
 impl Declarations {
     pub(crate) fn new() -> Self {
         Declarations {
             declarations: HashSet::new(),
+            duplicates: Vec::new(),
         }
     }
 
@@ -293,7 +410,7 @@ impl Declarations {
     where
         N: Into<Vec<u8>>,
     {
-        self.declarations.insert(Cow::Owned(CString::new(name)?));
+        self.insert(Cow::Owned(CString::new(name)?));
         Ok(self)
     }
 
@@ -301,10 +418,49 @@ impl Declarations {
     ///
     /// This method is can be used to avoid some allocation in the case that the name is static.
     pub fn declare_static(&mut self, name: &'static CStr) -> Result<&mut Self> {
-        self.declarations.insert(Cow::Borrowed(name));
+        self.insert(Cow::Borrowed(name));
         Ok(self)
     }
 
+    fn insert(&mut self, name: Cow<'static, CStr>) {
+        if self.declarations.contains(&name) {
+            self.duplicates.push(name.to_string_lossy().into_owned());
+        } else {
+            self.declarations.insert(name);
+        }
+    }
+
+    /// Returns the problems with the declared exports, the duplicates in the order they were
+    /// declared followed by the other problems ordered by name.
+    pub(crate) fn issues(&self, ctx: &Ctx<'_>) -> Result<Vec<ExportIssue>> {
+        let mut issues: Vec<_> = self
+            .duplicates
+            .iter()
+            .cloned()
+            .map(ExportIssue::Duplicate)
+            .collect();
+        let mut names: Vec<_> = self
+            .declarations
+            .iter()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        let globals = ctx.globals();
+        for name in names {
+            if name == "default" {
+                continue;
+            }
+            if RESERVED_WORDS.contains(&name.as_str()) {
+                issues.push(ExportIssue::Reserved(name));
+            } else if !is_identifier(&name) {
+                issues.push(ExportIssue::NotIdentifier(name));
+            } else if globals.contains_key(name.as_str())? {
+                issues.push(ExportIssue::ShadowsGlobal(name));
+            }
+        }
+        Ok(issues)
+    }
+
     pub(crate) unsafe fn apply(self, ctx: Ctx<'_>, module: &Module) -> Result<()> {
         for k in self.declarations {
             let ptr = match k {
@@ -466,6 +622,23 @@ impl<'js> Module<'js> {
         Ok(())
     }
 
+    /// Declare a module in the runtime with the source read from a reader.
+    ///
+    /// Like [`Ctx::eval_reader`] this is a convenience wrapper which reads the whole source into
+    /// a buffer first, as the QuickJS parser can't be fed the source in chunks. See
+    /// [`Module::declare`] for the restrictions regarding unevaluated modules.
+    pub fn declare_reader<N, R>(ctx: Ctx<'js>, name: N, mut reader: R) -> Result<()>
+    where
+        N: Into<Vec<u8>>,
+        R: Read,
+    {
+        let mut source = Vec::new();
+        reader.read_to_end(&mut source)?;
+        // Reserve space for the nul terminator so it can be added without reallocating.
+        source.reserve_exact(1);
+        Self::declare(ctx, name, source)
+    }
+
     /// Creates a new module from JS source, and evaluates it.
     ///
     /// It is unsafe to hold onto unevaluated modules across this call.
@@ -632,12 +805,17 @@ impl<'js> Module<'js> {
     /// It is unsound to hold onto an unevaluated module across any call to this function which
     /// returns an error.
     pub unsafe fn unsafe_declare_read_object(ctx: Ctx<'js>, bytes: &[u8]) -> Result<Module<'js>> {
+        Self::read_object(ctx, bytes, 0)
+    }
+
+    /// Read module bytecode, with `JS_READ_OBJ_ROM_DATA` the bytes must outlive the runtime.
+    unsafe fn read_object(ctx: Ctx<'js>, bytes: &[u8], flags: u32) -> Result<Module<'js>> {
         let module = unsafe {
             qjs::JS_ReadObject(
                 ctx.as_ptr(),
                 bytes.as_ptr(),
                 bytes.len() as _,
-                (qjs::JS_READ_OBJ_BYTECODE | qjs::JS_READ_OBJ_ROM_DATA) as i32,
+                (qjs::JS_READ_OBJ_BYTECODE | flags) as i32,
             )
         };
         let module = ctx.handle_exception(module)?;
@@ -664,11 +842,32 @@ impl<'js> Module<'js> {
         N: Into<Vec<u8>>,
         S: Into<Vec<u8>>,
     {
-        let name = CString::new(name)?;
-        let flag =
-            qjs::JS_EVAL_TYPE_MODULE | qjs::JS_EVAL_FLAG_STRICT | qjs::JS_EVAL_FLAG_COMPILE_ONLY;
+        Self::declare_source(ctx, name, source, 0)
+    }
 
-        let module = unsafe { ctx.eval_raw(source, name.as_c_str(), flag as i32)? };
+    /// Declares a module from source, `flag` is added to the evaluation flags.
+    unsafe fn declare_source<N, S>(
+        ctx: Ctx<'js>,
+        name: N,
+        source: S,
+        flag: u32,
+    ) -> Result<Module<'js>>
+    where
+        N: Into<Vec<u8>>,
+        S: Into<Vec<u8>>,
+    {
+        let name = CString::new(name)?;
+        let flag = qjs::JS_EVAL_TYPE_MODULE
+            | qjs::JS_EVAL_FLAG_STRICT
+            | qjs::JS_EVAL_FLAG_COMPILE_ONLY
+            | flag;
+
+        let source = source.into();
+        #[cfg(feature = "source-map")]
+        ctx.register_linked_source_map(&name.to_string_lossy(), &source, None);
+        #[cfg(feature = "coverage")]
+        let source = ctx.instrument_source(&name, source, 0);
+        let module = unsafe { ctx.eval_raw(source, name.as_c_str(), 0, flag as i32)? };
         let module = ctx.handle_exception(module)?;
         debug_assert_eq!(qjs::JS_TAG_MODULE, unsafe { qjs::JS_VALUE_GET_TAG(module) });
         let module = qjs::JS_VALUE_GET_PTR(module).cast::<qjs::JSModuleDef>();
@@ -700,6 +899,13 @@ impl<'js> Module<'js> {
 
         let mut defs = Declarations::new();
         D::declare(&mut defs)?;
+        let issues = defs.issues(&ctx)?;
+        if !issues.is_empty() {
+            return Err(Error::InvalidExports {
+                module: name.to_string_lossy().into_owned(),
+                issues,
+            });
+        }
 
         let ptr =
             unsafe { qjs::JS_NewCModule(ctx.as_ptr(), name.as_ptr(), Some(Self::eval_fn::<D>)) };
@@ -776,6 +982,26 @@ impl<'js> Module<'js> {
 
         V::from_js(ctx, val)
     }
+
+    /// Import and evaluate a module, fetching it with the async loader of the runtime
+    ///
+    /// The module and the modules it depends on which aren't loaded yet are fetched with the
+    /// loader set by [`AsyncRuntime::set_async_loader`](crate::AsyncRuntime::set_async_loader)
+    /// before the module is linked and evaluated like with [`Module::import`]. The specifier is
+    /// resolved with an empty base.
+    ///
+    /// Dynamic `import()` calls in scripts can only import modules which were fetched before.
+    #[cfg(all(feature = "loader", feature = "futures"))]
+    #[cfg_attr(
+        feature = "doc-cfg",
+        doc(cfg(all(feature = "loader", feature = "futures")))
+    )]
+    pub async fn import_async<V: FromJs<'js>>(ctx: &Ctx<'js>, specifier: &str) -> Result<V> {
+        let modules = unsafe { (*ctx.get_opaque()).async_loader.clone() }
+            .ok_or_else(|| Error::new_loading_message(specifier, "No async loader is set"))?;
+        let name = modules.prefetch(ctx, specifier).await?;
+        Self::import(ctx, name)
+    }
 }
 
 #[cfg(feature = "exports")]
@@ -947,6 +1173,47 @@ mod test {
         }
     }
 
+    pub struct InvalidModule;
+
+    impl ModuleDef for InvalidModule {
+        fn declare(define: &mut Declarations) -> Result<()> {
+            for name in [
+                "value", "class", "Array", "value", "my-name", "default", "ok",
+            ] {
+                define.declare(name)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn invalid_exports() {
+        test_with(|ctx| {
+            let err = Module::declare_def::<InvalidModule, _>(ctx.clone(), "invalid").unwrap_err();
+            let Error::InvalidExports { module, issues } = &err else {
+                panic!("unexpected error: {}", err);
+            };
+            assert_eq!(module, "invalid");
+            assert_eq!(
+                *issues,
+                [
+                    ExportIssue::Duplicate("value".into()),
+                    ExportIssue::ShadowsGlobal("Array".into()),
+                    ExportIssue::Reserved("class".into()),
+                    ExportIssue::NotIdentifier("my-name".into()),
+                ]
+            );
+            assert_eq!(
+                err.to_string(),
+                "Invalid exports declared by module 'invalid': 'value' is declared more than \
+                 once, 'Array' shadows a global, 'class' is a reserved word, 'my-name' is not \
+                 an identifier"
+            );
+            // The module wasn't declared.
+            assert!(Module::evaluate(ctx.clone(), "main", "import 'invalid'").is_err());
+        })
+    }
+
     #[test]
     fn from_rust_def() {
         test_with(|ctx| {
@@ -997,6 +1264,18 @@ mod test {
         })
     }
 
+    #[test]
+    fn declare_reader() {
+        test_with(|ctx| {
+            let source = b"export const hello = 'world';";
+            Module::declare_reader(ctx.clone(), "js_mod", &source[..]).unwrap();
+            let val: Object = Module::import(&ctx, "js_mod").unwrap();
+            let hello: StdString = val.get("hello").unwrap();
+
+            assert_eq!(&hello, "world");
+        })
+    }
+
     #[test]
     #[should_panic(expected = "kaboom")]
     fn import_crashing() {
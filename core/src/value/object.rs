@@ -1,19 +1,24 @@
 //! Module for types dealing with JS objects.
 
 use crate::{
-    convert::FromIteratorJs, qjs, Array, Atom, Ctx, FromAtom, FromJs, IntoAtom, IntoJs, Result,
-    Value,
+    atom::PredefinedAtom,
+    convert::FromIteratorJs,
+    function::{Constructor, IntoArgs},
+    qjs, Array, Atom, Ctx, FromAtom, FromJs, Function, IntoAtom, IntoJs, Result, Value,
 };
 use std::{
     iter::{DoubleEndedIterator, ExactSizeIterator, FusedIterator, IntoIterator, Iterator},
     marker::PhantomData,
     mem,
 };
+mod path;
 mod property;
-pub use property::{Accessor, AsProperty, Property, PropertyFlags};
+mod watch;
+pub use property::{Accessor, AsProperty, Property, PropertyDescriptor, PropertyFlags};
+pub use watch::{AccessKind, PropertyAccess};
 
 /// Rust representation of a JavaScript object.
-#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+#[derive(PartialEq, Clone, Hash, Eq)]
 #[repr(transparent)]
 pub struct Object<'js>(pub(crate) Value<'js>);
 
@@ -27,6 +32,39 @@ impl<'js> Object<'js> {
         })
     }
 
+    /// Create a new JavaScript object with the given prototype, like `Object.create(proto)`.
+    pub fn new_with_prototype(ctx: Ctx<'js>, proto: &Object<'js>) -> Result<Self> {
+        Ok(unsafe {
+            let val = qjs::JS_NewObjectProto(ctx.as_ptr(), proto.as_js_value());
+            let val = ctx.handle_exception(val)?;
+            Object::from_js_value(ctx, val)
+        })
+    }
+
+    /// Create a new JavaScript object without a prototype, like `Object.create(null)`.
+    ///
+    /// The object doesn't inherit properties like `toString`, and `__proto__` is an ordinary
+    /// property of it, which makes it suited as a dictionary of keys coming from scripts.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let dict = Object::new_null_proto(ctx.clone()).unwrap();
+    /// dict.set("__proto__", Object::new(ctx.clone()).unwrap()).unwrap();
+    /// assert!(dict.get_prototype().is_none());
+    /// assert!(!dict.contains_key("toString").unwrap());
+    /// # })
+    /// ```
+    pub fn new_null_proto(ctx: Ctx<'js>) -> Result<Self> {
+        Ok(unsafe {
+            let val = qjs::JS_NewObjectProto(ctx.as_ptr(), qjs::JS_NULL);
+            let val = ctx.handle_exception(val)?;
+            Object::from_js_value(ctx, val)
+        })
+    }
+
     /// Get a new value
     pub fn get<K: IntoAtom<'js>, V: FromJs<'js>>(&self, k: K) -> Result<V> {
         let atom = k.into_atom(self.ctx())?;
@@ -171,16 +209,100 @@ impl<'js> Object<'js> {
         }
     }
 
+    /// Returns whether new properties can be added to the object.
+    ///
+    /// Same as `Object.isExtensible(obj)` in JavaScript.
+    pub fn is_extensible(&self) -> Result<bool> {
+        let res = unsafe { qjs::JS_IsExtensible(self.0.ctx.as_ptr(), self.0.as_js_value()) };
+        if res < 0 {
+            return Err(self.0.ctx.raise_exception());
+        }
+        Ok(res != 0)
+    }
+
+    /// Prevent new properties from being added to the object.
+    ///
+    /// Same as `Object.preventExtensions(obj)` in JavaScript.
+    pub fn prevent_extensions(&self) -> Result<()> {
+        let res = unsafe { qjs::JS_PreventExtensions(self.0.ctx.as_ptr(), self.0.as_js_value()) };
+        if res < 0 {
+            return Err(self.0.ctx.raise_exception());
+        }
+        Ok(())
+    }
+
+    /// Prevent extensions and make all own properties non-configurable.
+    ///
+    /// Same as `Object.seal(obj)` in JavaScript.
+    pub fn seal(&self) -> Result<()> {
+        self.restrict(false)
+    }
+
+    /// Prevent extensions and make all own properties non-configurable and read-only.
+    ///
+    /// Same as `Object.freeze(obj)` in JavaScript. Like in JavaScript the freeze is shallow,
+    /// objects stored in the properties can still be changed.
+    pub fn freeze(&self) -> Result<()> {
+        self.restrict(true)
+    }
+
+    fn restrict(&self, freeze: bool) -> Result<()> {
+        self.prevent_extensions()?;
+        for key in self.own_keys::<Atom>(Filter::new().string().symbol()) {
+            let key = key?;
+            let writable = if freeze {
+                match self.get_own_property_descriptor(key.clone())? {
+                    Some(desc) if !desc.is_accessor() => Some(false),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            self.define_property(
+                key,
+                PropertyDescriptor {
+                    writable,
+                    configurable: Some(false),
+                    ..Default::default()
+                },
+            )?;
+        }
+        Ok(())
+    }
+
     /// Check instance of object
+    ///
+    /// Returns false if the check throws an exception, see [`Object::try_instance_of`] for a
+    /// version which returns the exception.
     pub fn is_instance_of(&self, class: impl AsRef<Value<'js>>) -> bool {
-        let class = class.as_ref();
-        0 != unsafe {
+        match self.try_instance_of(class) {
+            Ok(x) => x,
+            Err(error) => {
+                if error.is_exception() {
+                    self.0.ctx.catch();
+                }
+                false
+            }
+        }
+    }
+
+    /// Check whether the object is an instance of a constructor, like the `instanceof` operator.
+    ///
+    /// Returns an exception if the constructor isn't callable or `Symbol.hasInstance` throws.
+    /// Use [`Object::instance_of`] to check for instances of a Rust class.
+    pub fn try_instance_of(&self, constructor: impl AsRef<Value<'js>>) -> Result<bool> {
+        let constructor = constructor.as_ref();
+        let res = unsafe {
             qjs::JS_IsInstanceOf(
                 self.0.ctx.as_ptr(),
                 self.0.as_js_value(),
-                class.as_js_value(),
+                constructor.as_js_value(),
             )
+        };
+        if res < 0 {
+            return Err(self.0.ctx.raise_exception());
         }
+        Ok(res != 0)
     }
 
     /// Convert into an array
@@ -191,6 +313,34 @@ impl<'js> Object<'js> {
             None
         }
     }
+
+    /// Create a new instance of a builtin class from the global object.
+    pub(crate) fn new_builtin<A>(ctx: &Ctx<'js>, class: PredefinedAtom, args: A) -> Result<Self>
+    where
+        A: IntoArgs<'js>,
+    {
+        let constructor: Constructor = ctx.globals().get(class)?;
+        constructor.construct(args)
+    }
+
+    /// Returns whether the object is an instance of a builtin class from the global object.
+    pub(crate) fn is_builtin(&self, class: PredefinedAtom) -> bool {
+        self.ctx()
+            .globals()
+            .get::<_, Object>(class)
+            .map(|constructor| self.is_instance_of(constructor))
+            .unwrap_or(false)
+    }
+
+    /// Call a method of the object with the object as `this`.
+    pub(crate) fn call_method<A, R>(&self, name: &str, args: A) -> Result<R>
+    where
+        A: IntoArgs<'js>,
+        R: FromJs<'js>,
+    {
+        let method: Function = self.get(name)?;
+        method.call_on(self.clone(), args)
+    }
 }
 
 /// The property filter
@@ -804,4 +954,112 @@ mod test {
             );
         })
     }
+
+    #[test]
+    fn atom_keys() {
+        test_with(|ctx| {
+            let val: Object = ctx
+                .eval(r#"({ a: 1, 2: "b", [Symbol.for("c")]: true })"#)
+                .unwrap();
+
+            let a = Atom::from_str(ctx.clone(), "a").unwrap();
+            for _ in 0..2 {
+                assert_eq!(val.get::<_, i32>(&a).unwrap(), 1);
+            }
+            assert!(val.contains_key(&a).unwrap());
+            val.set(&a, 3).unwrap();
+            assert_eq!(val.get::<_, i32>(a).unwrap(), 3);
+
+            assert_eq!(val.get::<_, StdString>(2).unwrap(), "b");
+
+            let sym: Symbol = ctx.eval(r#"Symbol.for("c")"#).unwrap();
+            assert!(val.contains_key(sym.clone()).unwrap());
+            assert!(val.get::<_, bool>(sym).unwrap());
+
+            let key = String::from_str(ctx.clone(), "a").unwrap();
+            assert_eq!(val.get::<_, i32>(key).unwrap(), 3);
+        });
+    }
+
+    #[test]
+    fn meta_state() {
+        test_with(|ctx| {
+            let obj: Object = ctx
+                .eval(r#"({ a: 1, get b() { return 2 }, [Symbol.for("c")]: {} })"#)
+                .unwrap();
+            ctx.globals().set("obj", obj.clone()).unwrap();
+            let check = |source: &str| ctx.eval::<bool, _>(source).unwrap();
+            assert!(obj.is_extensible().unwrap());
+            obj.prevent_extensions().unwrap();
+            assert!(!obj.is_extensible().unwrap());
+            assert!(obj.set("d", 1).is_err());
+            ctx.catch();
+            assert!(!check("Object.isSealed(obj)"));
+
+            obj.seal().unwrap();
+            assert!(check("Object.isSealed(obj)"));
+            assert!(!check("Object.isFrozen(obj)"));
+            obj.set("a", 2).unwrap();
+
+            obj.freeze().unwrap();
+            assert!(check("Object.isFrozen(obj)"));
+            assert!(obj.set("a", 3).is_err());
+            ctx.catch();
+            assert_eq!(obj.get::<_, i32>("a").unwrap(), 2);
+            // The freeze is shallow.
+            let sym: Symbol = ctx.eval(r#"Symbol.for("c")"#).unwrap();
+            let inner: Object = obj.get(sym).unwrap();
+            assert!(inner.is_extensible().unwrap());
+
+            let proto: Object = ctx.eval("({ inherited: true })").unwrap();
+            let obj = Object::new(ctx.clone()).unwrap();
+            obj.set_prototype(Some(&proto)).unwrap();
+            assert_eq!(obj.get_prototype(), Some(proto.clone()));
+            assert!(obj.get::<_, bool>("inherited").unwrap());
+            assert!(proto.set_prototype(Some(&obj)).is_err());
+            ctx.catch();
+
+            let array: Function = ctx.globals().get("Array").unwrap();
+            let list: Object = ctx.eval("[]").unwrap();
+            assert!(list.try_instance_of(&array).unwrap());
+            assert!(!obj.try_instance_of(&array).unwrap());
+            assert!(obj.try_instance_of(&proto).is_err());
+            ctx.catch();
+            assert!(!obj.is_instance_of(&proto));
+        });
+    }
+
+    #[test]
+    fn prototypes() {
+        test_with(|ctx| {
+            let proto: Object = ctx
+                .eval("({ greet() { return 'hi ' + this.name } })")
+                .unwrap();
+            let obj = Object::new_with_prototype(ctx.clone(), &proto).unwrap();
+            obj.set("name", "js").unwrap();
+            assert_eq!(obj.get_prototype(), Some(proto));
+            let greet: Function = obj.get("greet").unwrap();
+            let res: StdString = greet.call((This(obj),)).unwrap();
+            assert_eq!(res, "hi js");
+
+            let dict = Object::new_null_proto(ctx.clone()).unwrap();
+            ctx.globals().set("dict", dict.clone()).unwrap();
+            let res: StdString = ctx
+                .eval(
+                    r#"
+                        dict["__proto__"] = { polluted: true };
+                        dict.constructor = 1;
+                        [
+                            Object.getPrototypeOf(dict),
+                            dict.polluted,
+                            typeof dict.toString,
+                            Object.keys(dict).join(),
+                        ].map(String).join(" ")
+                    "#,
+                )
+                .unwrap();
+            assert_eq!(res, "null undefined undefined __proto__,constructor");
+            assert!(dict.get_prototype().is_none());
+        })
+    }
 }
@@ -0,0 +1,127 @@
+use crate::{FromJs, IntoAtom, Object, Result, Value};
+
+impl<'js> Object<'js> {
+    /// Get a nested property by following a path of keys, like optional chaining does.
+    ///
+    /// Returns `None` if any step of the path is `undefined` or `null`, or isn't an object which
+    /// could have the next key. Array indices can be given as strings as JavaScript doesn't
+    /// differentiate between `obj[0]` and `obj["0"]`.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let obj: Object = ctx.eval("({ a: { b: [{ c: 1 }] } })").unwrap();
+    /// assert_eq!(obj.get_path::<_, _, i32>(["a", "b", "0", "c"]).unwrap(), Some(1));
+    /// assert_eq!(obj.get_path::<_, _, i32>(["a", "x", "0", "c"]).unwrap(), None);
+    /// # })
+    /// ```
+    pub fn get_path<K, P, V>(&self, path: P) -> Result<Option<V>>
+    where
+        K: IntoAtom<'js>,
+        P: IntoIterator<Item = K>,
+        V: FromJs<'js>,
+    {
+        let mut value = self.clone().into_value();
+        for key in path {
+            let Some(obj) = value.into_object() else {
+                return Ok(None);
+            };
+            value = obj.get(key)?;
+        }
+        if value.is_undefined() || value.is_null() {
+            return Ok(None);
+        }
+        V::from_js(self.ctx(), value).map(Some)
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Get a nested value with a [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) like
+    /// `/a/b/0/c`.
+    ///
+    /// The empty pointer refers to the value itself. Like [`Object::get_path`] this returns `None`
+    /// if any step is missing, and also if the pointer doesn't start with a `/`.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value: Value = ctx.json_parse(r#"{ "a/b": [1, { "~c": "d" }] }"#).unwrap();
+    /// assert_eq!(value.pointer::<String>("/a~1b/1/~0c").unwrap().as_deref(), Some("d"));
+    /// assert_eq!(value.pointer::<i32>("/a~1b/2").unwrap(), None);
+    /// # })
+    /// ```
+    pub fn pointer<V: FromJs<'js>>(&self, pointer: &str) -> Result<Option<V>> {
+        if pointer.is_empty() {
+            if self.is_undefined() || self.is_null() {
+                return Ok(None);
+            }
+            return V::from_js(self.ctx(), self.clone()).map(Some);
+        }
+        let Some(pointer) = pointer.strip_prefix('/') else {
+            return Ok(None);
+        };
+        let Some(obj) = self.as_object() else {
+            return Ok(None);
+        };
+        obj.get_path(
+            pointer
+                .split('/')
+                .map(|x| x.replace("~1", "/").replace("~0", "~")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, Object, StdString, Value};
+
+    #[test]
+    fn get_path() {
+        test_with(|ctx| {
+            let obj: Object = ctx
+                .eval("({ a: { b: [10, { c: 'x' }], n: null }, s: 'str' })")
+                .unwrap();
+            assert_eq!(
+                obj.get_path::<_, _, i32>(["a", "b", "0"]).unwrap(),
+                Some(10)
+            );
+            assert_eq!(
+                obj.get_path::<_, _, StdString>(vec!["a", "b", "1", "c"])
+                    .unwrap()
+                    .as_deref(),
+                Some("x")
+            );
+            let b: Option<Value> = obj.get_path(["a", "b"]).unwrap();
+            assert!(b.unwrap().is_array());
+            assert_eq!(obj.get_path::<_, _, i32>(["a", "n", "x"]).unwrap(), None);
+            assert_eq!(obj.get_path::<_, _, i32>(["a", "n"]).unwrap(), None);
+            assert_eq!(obj.get_path::<_, _, i32>(["s", "length"]).unwrap(), None);
+            assert_eq!(obj.get_path::<_, _, i32>(["missing", "x"]).unwrap(), None);
+            // Values of the wrong type are still errors.
+            assert!(obj.get_path::<_, _, i32>(["a", "b", "1", "c"]).is_err());
+        })
+    }
+
+    #[test]
+    fn pointer() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .json_parse(r#"{ "a": { "b": [1, { "c": true }] }, "x/y": 2, "m~n": 3 }"#)
+                .unwrap();
+            assert_eq!(value.pointer::<bool>("/a/b/1/c").unwrap(), Some(true));
+            assert_eq!(value.pointer::<i32>("/x~1y").unwrap(), Some(2));
+            assert_eq!(value.pointer::<i32>("/m~0n").unwrap(), Some(3));
+            assert_eq!(value.pointer::<i32>("/a/b/5").unwrap(), None);
+            assert_eq!(value.pointer::<i32>("a/b").unwrap(), None);
+            assert!(value.pointer::<Object>("").unwrap().is_some());
+
+            let value = Value::new_int(ctx.clone(), 4);
+            assert_eq!(value.pointer::<i32>("").unwrap(), Some(4));
+            assert_eq!(value.pointer::<i32>("/a").unwrap(), None);
+        })
+    }
+}
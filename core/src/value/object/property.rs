@@ -1,5 +1,8 @@
+use std::mem;
+
 use crate::{
-    function::IntoJsFunc, qjs, Ctx, Function, IntoAtom, IntoJs, Object, Result, Undefined, Value,
+    function::IntoJsFunc, qjs, Ctx, Exception, Function, IntoAtom, IntoJs, Object, Result,
+    Undefined, Value,
 };
 
 impl<'js> Object<'js> {
@@ -52,6 +55,165 @@ impl<'js> Object<'js> {
         }
         Ok(())
     }
+
+    /// Define a property from a full descriptor, like `Object.defineProperty` does.
+    ///
+    /// Fields which are `None` are left unchanged when redefining an existing property and
+    /// default to `false` or `undefined` for a new property.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Object, Function, object::PropertyDescriptor};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let obj = Object::new(ctx.clone()).unwrap();
+    /// obj.define_property("hidden", PropertyDescriptor {
+    ///     value: Some(ctx.eval("42").unwrap()),
+    ///     writable: Some(true),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// obj.define_property("double", PropertyDescriptor {
+    ///     get: Some(ctx.eval("(function() { return this.hidden * 2 })").unwrap()),
+    ///     enumerable: Some(true),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// assert_eq!(obj.get::<_, i32>("double").unwrap(), 84);
+    /// assert_eq!(obj.keys::<String>().count(), 1);
+    /// # })
+    /// ```
+    pub fn define_property<K>(&self, key: K, desc: PropertyDescriptor<'js>) -> Result<()>
+    where
+        K: IntoAtom<'js>,
+    {
+        let ctx = self.ctx();
+        if desc.is_accessor() && (desc.value.is_some() || desc.writable.is_some()) {
+            return Err(Exception::throw_type(
+                ctx,
+                "invalid property descriptor: can't have both a value and accessors",
+            ));
+        }
+        let key = key.into_atom(ctx)?;
+        let mut flags = qjs::JS_PROP_THROW as PropertyFlags;
+        let mut flag = |value: Option<bool>, has: u32, set: u32| {
+            if let Some(x) = value {
+                flags |= has as PropertyFlags;
+                if x {
+                    flags |= set as PropertyFlags;
+                }
+            }
+        };
+        flag(
+            desc.writable,
+            qjs::JS_PROP_HAS_WRITABLE,
+            qjs::JS_PROP_WRITABLE,
+        );
+        flag(
+            desc.enumerable,
+            qjs::JS_PROP_HAS_ENUMERABLE,
+            qjs::JS_PROP_ENUMERABLE,
+        );
+        flag(
+            desc.configurable,
+            qjs::JS_PROP_HAS_CONFIGURABLE,
+            qjs::JS_PROP_CONFIGURABLE,
+        );
+        flag(desc.value.as_ref().map(|_| true), qjs::JS_PROP_HAS_VALUE, 0);
+        flag(desc.get.as_ref().map(|_| true), qjs::JS_PROP_HAS_GET, 0);
+        flag(desc.set.as_ref().map(|_| true), qjs::JS_PROP_HAS_SET, 0);
+
+        let undefined = Value::new_undefined(ctx.clone());
+        let value = desc.value.unwrap_or_else(|| undefined.clone());
+        let getter = desc.get.map(Function::into_value);
+        let getter = getter.unwrap_or_else(|| undefined.clone());
+        let setter = desc.set.map(Function::into_value).unwrap_or(undefined);
+        unsafe {
+            let res = qjs::JS_DefineProperty(
+                ctx.as_ptr(),
+                self.0.as_js_value(),
+                key.atom,
+                value.as_js_value(),
+                getter.as_js_value(),
+                setter.as_js_value(),
+                flags,
+            );
+            if res < 0 {
+                return Err(self.0.ctx.raise_exception());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the descriptor of an own property, like `Object.getOwnPropertyDescriptor` does.
+    ///
+    /// All flags of the returned descriptor are set. Getters aren't invoked, so the value of
+    /// accessor properties is `None`.
+    pub fn get_own_property_descriptor<K>(&self, key: K) -> Result<Option<PropertyDescriptor<'js>>>
+    where
+        K: IntoAtom<'js>,
+    {
+        let ctx = self.ctx();
+        let key = key.into_atom(ctx)?;
+        let mut desc = mem::MaybeUninit::<qjs::JSPropertyDescriptor>::uninit();
+        let res = unsafe {
+            qjs::JS_GetOwnProperty(
+                ctx.as_ptr(),
+                desc.as_mut_ptr(),
+                self.0.as_js_value(),
+                key.atom,
+            )
+        };
+        if res < 0 {
+            return Err(ctx.raise_exception());
+        }
+        if res == 0 {
+            return Ok(None);
+        }
+        let desc = unsafe { desc.assume_init() };
+        let (value, getter, setter) = unsafe {
+            (
+                Value::from_js_value(ctx.clone(), desc.value),
+                Value::from_js_value(ctx.clone(), desc.getter),
+                Value::from_js_value(ctx.clone(), desc.setter),
+            )
+        };
+        let has = |flag: u32| desc.flags & flag as PropertyFlags != 0;
+        let accessor = has(qjs::JS_PROP_GETSET);
+        Ok(Some(PropertyDescriptor {
+            value: (!accessor).then_some(value),
+            get: getter.into_function(),
+            set: setter.into_function(),
+            writable: (!accessor).then(|| has(qjs::JS_PROP_WRITABLE)),
+            enumerable: Some(has(qjs::JS_PROP_ENUMERABLE)),
+            configurable: Some(has(qjs::JS_PROP_CONFIGURABLE)),
+        }))
+    }
+}
+
+/// A full property descriptor as used by `Object.defineProperty`.
+///
+/// A descriptor either describes a data property with a `value` and `writable` flag, or an
+/// accessor property with `get` and `set` functions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PropertyDescriptor<'js> {
+    /// The value of a data property.
+    pub value: Option<Value<'js>>,
+    /// The getter of an accessor property.
+    pub get: Option<Function<'js>>,
+    /// The setter of an accessor property.
+    pub set: Option<Function<'js>>,
+    /// Whether the value of a data property can be changed with an assignment.
+    pub writable: Option<bool>,
+    /// Whether the property shows up when enumerating the properties of the object.
+    pub enumerable: Option<bool>,
+    /// Whether the property can be deleted or have its descriptor changed.
+    pub configurable: Option<bool>,
+}
+
+impl<'js> PropertyDescriptor<'js> {
+    /// Returns true if the descriptor has a getter or setter.
+    pub fn is_accessor(&self) -> bool {
+        self.get.is_some() || self.set.is_some()
+    }
 }
 
 pub type PropertyFlags = qjs::c_int;
@@ -434,4 +596,86 @@ mod test {
             assert_eq!(val.lock().clone(), "");
         });
     }
+
+    #[test]
+    fn define_property() {
+        test_with(|ctx| {
+            let obj = Object::new(ctx.clone()).unwrap();
+            obj.define_property(
+                "data",
+                PropertyDescriptor {
+                    value: Some(ctx.eval("1").unwrap()),
+                    enumerable: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let desc = obj.get_own_property_descriptor("data").unwrap().unwrap();
+            assert_eq!(desc.value.as_ref().and_then(Value::as_int), Some(1));
+            assert_eq!(desc.writable, Some(false));
+            assert_eq!(desc.enumerable, Some(true));
+            assert_eq!(desc.configurable, Some(false));
+            assert!(!desc.is_accessor());
+
+            let get: Function = ctx.eval("(function() { return this.data + 1 })").unwrap();
+            obj.define_property(
+                "accessor",
+                PropertyDescriptor {
+                    get: Some(get.clone()),
+                    configurable: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(obj.get::<_, i32>("accessor").unwrap(), 2);
+            let desc = obj
+                .get_own_property_descriptor("accessor")
+                .unwrap()
+                .unwrap();
+            assert_eq!(desc.get.as_ref(), Some(&get));
+            assert_eq!(desc.set, None);
+            assert_eq!(desc.value, None);
+            assert_eq!(desc.writable, None);
+            assert_eq!(desc.configurable, Some(true));
+
+            // Redefining only changes the given fields.
+            obj.define_property(
+                "accessor",
+                PropertyDescriptor {
+                    enumerable: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let desc = obj
+                .get_own_property_descriptor("accessor")
+                .unwrap()
+                .unwrap();
+            assert!(desc.get.is_some());
+            assert_eq!(desc.enumerable, Some(true));
+
+            assert!(obj
+                .get_own_property_descriptor("missing")
+                .unwrap()
+                .is_none());
+            let err = obj
+                .define_property(
+                    "data",
+                    PropertyDescriptor {
+                        value: Some(ctx.eval("2").unwrap()),
+                        ..Default::default()
+                    },
+                )
+                .catch(&ctx)
+                .unwrap_err();
+            assert!(err.to_string().contains("not configurable"), "{}", err);
+            let invalid = PropertyDescriptor {
+                value: Some(get.clone().into_value()),
+                get: Some(get),
+                ..Default::default()
+            };
+            assert!(obj.define_property("invalid", invalid).is_err());
+            ctx.catch();
+        });
+    }
 }
@@ -0,0 +1,284 @@
+use crate::{Ctx, Error, FromJs, Function, IntoAtom, Object, Result, StdString, Value};
+
+/// Creates a membrane, a function wrapping objects in proxies which report the accesses to
+/// their properties and wrap the objects read from them as well.
+const MEMBRANE: &str = r#"
+(report) => {
+    const proxies = new WeakMap();
+    const targets = new WeakMap();
+    const unwrap = (value) => (targets.has(value) ? targets.get(value) : value);
+    // Objects with internal slots, their methods and accessors don't work on proxies.
+    const slotted = [Map, Set, WeakMap, WeakSet, Date, Promise, RegExp, ArrayBuffer, DataView];
+    const hasSlots = (value) =>
+        ArrayBuffer.isView(value) || slotted.some((constructor) => value instanceof constructor);
+    const wrap = (value, path) => {
+        if ((typeof value !== "object" && typeof value !== "function") || value === null) {
+            return value;
+        }
+        let proxy = proxies.get(value);
+        if (proxy === undefined) {
+            proxy = new Proxy(value, handler(path));
+            proxies.set(value, proxy);
+            targets.set(proxy, value);
+        }
+        return proxy;
+    };
+    const handler = (path) => ({
+        get(target, key, receiver) {
+            const value = Reflect.get(target, key, hasSlots(target) ? target : receiver);
+            const keyPath = [...path, String(key)];
+            report("get", keyPath, value, value);
+            // Proxies must return the value of non-configurable read only properties as is.
+            const desc = Reflect.getOwnPropertyDescriptor(target, key);
+            if (desc !== undefined && !desc.configurable && desc.writable === false) {
+                return value;
+            }
+            return wrap(value, keyPath);
+        },
+        set(target, key, value) {
+            value = unwrap(value);
+            const old = Reflect.get(target, key);
+            const ok = Reflect.set(target, key, value);
+            if (ok) {
+                report("set", [...path, String(key)], old, value);
+            }
+            return ok;
+        },
+        defineProperty(target, key, desc) {
+            const old = Reflect.get(target, key);
+            const ok = Reflect.defineProperty(target, key, desc);
+            if (ok) {
+                report("set", [...path, String(key)], old, Reflect.get(target, key));
+            }
+            return ok;
+        },
+        deleteProperty(target, key) {
+            const old = Reflect.get(target, key);
+            const ok = Reflect.deleteProperty(target, key);
+            if (ok) {
+                report("delete", [...path, String(key)], old, undefined);
+            }
+            return ok;
+        },
+        apply(target, thisArg, args) {
+            const self = unwrap(thisArg);
+            // Generic methods, like the ones of arrays, run on the proxy so their accesses are
+            // reported as well.
+            return Reflect.apply(target, hasSlots(self) ? self : thisArg, args.map(unwrap));
+        },
+    });
+    return wrap;
+}
+"#;
+
+/// The kind of a property access reported by [`Object::watch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    /// The property was read.
+    Get,
+    /// The property was assigned or defined.
+    Set,
+    /// The property was deleted.
+    Delete,
+}
+
+impl<'js> FromJs<'js> for AccessKind {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let type_name = value.type_name();
+        Ok(match StdString::from_js(ctx, value)?.as_str() {
+            "get" => AccessKind::Get,
+            "set" => AccessKind::Set,
+            "delete" => AccessKind::Delete,
+            _ => return Err(Error::new_from_js(type_name, "AccessKind")),
+        })
+    }
+}
+
+/// A property access on a watched object, see [`Object::watch`].
+#[derive(Debug)]
+pub struct PropertyAccess<'js> {
+    /// The kind of the access.
+    pub kind: AccessKind,
+    /// The keys from the watched object to the accessed property, symbols are converted with
+    /// `String(symbol)`.
+    pub path: Vec<StdString>,
+    /// The value of the property before the access, `undefined` if it didn't exist.
+    pub old: Value<'js>,
+    /// The value of the property after the access, the same as `old` for reads.
+    pub new: Value<'js>,
+}
+
+impl<'js> Object<'js> {
+    /// Create a proxy of the object which calls `callback` on every access to its properties.
+    ///
+    /// The objects read from the proxy are wrapped as well, so accesses to nested objects are
+    /// reported with the path to them, and wrapped objects assigned to a property are unwrapped
+    /// again. An object reachable by multiple paths is reported with the path it was first read
+    /// with. Accesses made directly on the object, bypassing the proxy, aren't reported.
+    ///
+    /// This is meant for debugging, like tracking down which code mutates a configuration
+    /// object. An error returned from the callback is thrown from the access.
+    ///
+    /// ```
+    /// # use std::{cell::RefCell, rc::Rc};
+    /// # use rquickjs::{Runtime, Context, Object, object::AccessKind};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let config: Object = ctx.eval("({ server: { port: 80 } })").unwrap();
+    /// let mutations = Rc::new(RefCell::new(Vec::new()));
+    /// let log = mutations.clone();
+    /// let watched = config.watch(move |access| {
+    ///     if access.kind == AccessKind::Set {
+    ///         log.borrow_mut().push(access.path.join("."));
+    ///     }
+    ///     Ok(())
+    /// }).unwrap();
+    /// ctx.globals().set("config", watched).unwrap();
+    /// ctx.eval::<(), _>("config.server.port = 8080").unwrap();
+    /// assert_eq!(*mutations.borrow(), ["server.port"]);
+    /// # })
+    /// ```
+    pub fn watch<F>(&self, callback: F) -> Result<Object<'js>>
+    where
+        F: Fn(PropertyAccess<'js>) -> Result<()> + 'js,
+    {
+        let ctx = self.ctx();
+        let report = Function::new(
+            ctx.clone(),
+            move |kind: AccessKind, path: Vec<StdString>, old: Value<'js>, new: Value<'js>| {
+                callback(PropertyAccess {
+                    kind,
+                    path,
+                    old,
+                    new,
+                })
+            },
+        )?;
+        let membrane: Function = ctx.eval(MEMBRANE)?;
+        let wrap: Function = membrane.call((report,))?;
+        wrap.call((self.clone(), Vec::<StdString>::new()))
+    }
+
+    /// Replace the object stored at `key` with a proxy watching it, see [`Object::watch`].
+    ///
+    /// The reported paths start at the watched object, not at `key`.
+    pub fn watch_property<K, F>(&self, key: K, callback: F) -> Result<()>
+    where
+        K: IntoAtom<'js> + Clone,
+        F: Fn(PropertyAccess<'js>) -> Result<()> + 'js,
+    {
+        let object: Object = self.get(key.clone())?;
+        self.set(key, object.watch(callback)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::AccessKind;
+    use crate::{test_with, CatchResultExt, Error, Object, StdString};
+
+    #[test]
+    fn watch_config() {
+        test_with(|ctx| {
+            ctx.eval::<(), _>(
+                r#"
+                    globalThis.config = {
+                        server: { port: 80, hosts: ["a"] },
+                        cache: new Map(),
+                        fixed: Object.freeze({ value: 1 }),
+                    };
+                "#,
+            )
+            .unwrap();
+            let log = Rc::new(RefCell::new(Vec::new()));
+            let events = log.clone();
+            ctx.globals()
+                .watch_property("config", move |access| {
+                    let old = access.old.as_int();
+                    let new = access.new.as_int();
+                    events
+                        .borrow_mut()
+                        .push((access.kind, access.path.join("."), old, new));
+                    Ok(())
+                })
+                .unwrap();
+
+            ctx.eval::<(), _>(
+                r#"
+                    config.server.port = 8080;
+                    config.server.hosts.push("b");
+                    config.cache.set("key", 1);
+                    if (config.cache.get("key") !== 1 || config.fixed.value !== 1) {
+                        throw new Error("membrane");
+                    }
+                    delete config.server.port;
+                    if (config.server !== config.server) {
+                        throw new Error("identity");
+                    }
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+
+            let log = log.borrow().clone();
+            let mutations = log
+                .iter()
+                .filter(|(kind, ..)| *kind != AccessKind::Get)
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(
+                mutations,
+                [
+                    (
+                        AccessKind::Set,
+                        "server.port".to_owned(),
+                        Some(80),
+                        Some(8080)
+                    ),
+                    (AccessKind::Set, "server.hosts.1".to_owned(), None, None),
+                    (
+                        AccessKind::Set,
+                        "server.hosts.length".to_owned(),
+                        Some(2),
+                        Some(2)
+                    ),
+                    (
+                        AccessKind::Delete,
+                        "server.port".to_owned(),
+                        Some(8080),
+                        None
+                    ),
+                ]
+            );
+            assert!(log.contains(&(AccessKind::Get, "fixed.value".to_owned(), Some(1), Some(1))));
+
+            // The accesses go through to the watched object.
+            let config: Object = ctx.globals().get("config").unwrap();
+            let server: Object = config.get("server").unwrap();
+            assert!(!server.contains_key("port").unwrap());
+        })
+    }
+
+    #[test]
+    fn watch_error() {
+        test_with(|ctx| {
+            let object = Object::new(ctx.clone()).unwrap();
+            let watched = object
+                .watch(|access| {
+                    if access.kind == AccessKind::Set {
+                        return Err(Error::new_from_js_message("value", "property", "rejected"));
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            ctx.globals().set("watched", watched).unwrap();
+            let res: StdString = ctx
+                .eval("try { watched.a = 1; 'ok' } catch (e) { e.message }")
+                .unwrap();
+            assert!(res.contains("rejected"), "{res}");
+        })
+    }
+}
@@ -0,0 +1,384 @@
+//! JavaScript `RegExp` functionality.
+
+use std::ops::Deref;
+
+use crate::{
+    atom::PredefinedAtom, qjs, value::map::from_raw, Array, Ctx, Error, FromJs, IntoJs, Object,
+    Outlive, Result, StdString, Value,
+};
+
+/// Rust representation of a JavaScript object of class RegExp.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct RegExp<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> Outlive<'js> for RegExp<'js> {
+    type Target<'to> = RegExp<'to>;
+}
+
+impl<'js> RegExp<'js> {
+    /// Create a new regular expression from a pattern and flags.
+    ///
+    /// Returns an exception if the pattern or flags are invalid, which makes this method usable
+    /// for validating user supplied patterns.
+    pub fn new(ctx: Ctx<'js>, pattern: &str, flags: &str) -> Result<Self> {
+        let pattern = pattern.into_js(&ctx)?;
+        let flags = flags.into_js(&ctx)?;
+        unsafe {
+            let val = qjs::JS_NewRegExp(ctx.as_ptr(), pattern.as_js_value(), flags.as_js_value());
+            let val = ctx.handle_exception(val)?;
+            Ok(RegExp(Object::from_js_value(ctx, val)))
+        }
+    }
+
+    /// Returns the pattern of the regular expression.
+    ///
+    /// Unlike the `source` getter of JavaScript the pattern is returned as written, an empty
+    /// pattern is not replaced by `(?:)` and slashes are not escaped.
+    pub fn source(&self) -> Result<StdString> {
+        let ctx = self.ctx();
+        unsafe {
+            from_raw(
+                ctx,
+                qjs::JS_GetRegExpSource(ctx.as_ptr(), self.as_js_value()),
+            )
+        }
+    }
+
+    /// Returns the flags of the regular expression.
+    pub fn flags(&self) -> Result<StdString> {
+        let bits = unsafe { qjs::JS_GetRegExpFlags(self.as_js_value()) };
+        Ok(FLAGS
+            .iter()
+            .filter(|(bit, _)| bits & bit != 0)
+            .map(|(_, flag)| flag)
+            .collect())
+    }
+
+    /// Returns the index at which the next match starts for global or sticky expressions.
+    pub fn last_index(&self) -> Result<usize> {
+        self.0.get(PredefinedAtom::LastIndex)
+    }
+
+    /// Set the index at which the next match starts for global or sticky expressions.
+    pub fn set_last_index(&self, index: usize) -> Result<()> {
+        self.0.set(PredefinedAtom::LastIndex, index)
+    }
+
+    /// Returns whether the expression matches the input.
+    pub fn test(&self, input: &str) -> Result<bool> {
+        self.exec(input).map(|res| res.is_some())
+    }
+
+    /// Match the expression against the input.
+    ///
+    /// Returns the match array, which contains the matched string followed by the captured
+    /// groups, or `None` if the expression didn't match.
+    pub fn exec(&self, input: &str) -> Result<Option<Array<'js>>> {
+        let ctx = self.ctx();
+        let input = input.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_ExecRegExp(ctx.as_ptr(), self.as_js_value(), input.as_js_value());
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Create a new regular expression from a [`regex::Regex`].
+    ///
+    /// The expression is created with the `u` flag, as the regex crate matches unicode scalar
+    /// values. Flags at the start of the pattern, like `(?i)`, are converted into the respective
+    /// JavaScript flags. Syntax JavaScript doesn't support, like `\A` or flags in other
+    /// positions, results in an exception.
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+    pub fn from_regex(ctx: Ctx<'js>, regex: &regex::Regex) -> Result<Self> {
+        let mut pattern = regex.as_str();
+        let mut flags = StdString::new();
+        if let Some(rest) = pattern.strip_prefix("(?") {
+            if let Some((group, rest)) = rest.split_once(')') {
+                if group.chars().all(|c| "ims".contains(c)) {
+                    flags.push_str(group);
+                    pattern = rest;
+                }
+            }
+        }
+        flags.push('u');
+        Self::new(ctx, pattern, &flags)
+    }
+
+    /// Convert the expression into a [`regex::Regex`].
+    ///
+    /// The `i`, `m` and `s` flags are mapped to the respective options of the regex crate, the
+    /// `g` and `y` flags only change where matching starts and are ignored. Note that the syntax
+    /// of both engines differs, for example the regex crate doesn't support lookaround, so the
+    /// conversion can fail for valid JavaScript patterns.
+    ///
+    /// Without the `u` flag JavaScript treats `\u{..}` and `\p{..}` as literal characters
+    /// instead of code point and property escapes, expressions using them are rejected.
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+    pub fn to_regex(&self) -> Result<regex::Regex> {
+        let flags = self.flags()?;
+        let source = self.source()?;
+        if !flags.contains('u') && has_unicode_escape(&source) {
+            return Err(Error::new_from_js_message(
+                "RegExp",
+                "regex::Regex",
+                "Unicode escapes require the `u` flag",
+            ));
+        }
+        regex::RegexBuilder::new(&source)
+            .case_insensitive(flags.contains('i'))
+            .multi_line(flags.contains('m'))
+            .dot_matches_new_line(flags.contains('s'))
+            .build()
+            .map_err(|e| Error::new_from_js_message("RegExp", "regex::Regex", e.to_string()))
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_regexp().then_some(RegExp(object))
+    }
+}
+
+/// Returns whether the pattern contains a `\u{..}`, `\p` or `\P` escape.
+#[cfg(feature = "regex")]
+fn has_unicode_escape(source: &str) -> bool {
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('p' | 'P') => return true,
+                Some('u') if chars.as_str().starts_with('{') => return true,
+                _ => {}
+            }
+        }
+    }
+    false
+}
+
+/// The flags in the order the `flags` getter of JavaScript returns them.
+const FLAGS: [(i32, char); 6] = [
+    (1 << 0, 'g'),
+    (1 << 1, 'i'),
+    (1 << 2, 'm'),
+    (1 << 3, 's'),
+    (1 << 4, 'u'),
+    (1 << 5, 'y'),
+];
+
+impl<'js> Deref for RegExp<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for RegExp<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for RegExp<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "RegExp"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for RegExp<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+#[cfg(feature = "regex")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+impl<'js> FromJs<'js> for regex::Regex {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        RegExp::from_js(ctx, value)?.to_regex()
+    }
+}
+
+#[cfg(feature = "regex")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+impl<'js> IntoJs<'js> for regex::Regex {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        RegExp::from_regex(ctx.clone(), &self).map(RegExp::into_value)
+    }
+}
+
+#[cfg(feature = "regex")]
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "regex")))]
+impl<'js> IntoJs<'js> for &regex::Regex {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        RegExp::from_regex(ctx.clone(), self).map(RegExp::into_value)
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`RegExp`].
+    pub fn is_regexp(&self) -> bool {
+        unsafe { qjs::JS_IsRegExp(self.as_js_value()) != 0 }
+    }
+
+    /// Interpret as [`RegExp`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_regexp(&self) -> &RegExp<'js> {
+        &*(self as *const Object as *const RegExp)
+    }
+
+    /// Turn the object into a regular expression if the object is an instance of [`RegExp`].
+    pub fn as_regexp(&self) -> Option<&RegExp<'js>> {
+        self.is_regexp().then_some(unsafe { self.ref_regexp() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let re: RegExp = ctx.eval("/a(b+)c/gi").unwrap();
+            assert_eq!(re.source().unwrap(), "a(b+)c");
+            assert_eq!(re.flags().unwrap(), "gi");
+            assert!(re.test("xABBC").unwrap());
+            assert_eq!(re.last_index().unwrap(), 5);
+            re.set_last_index(0).unwrap();
+
+            let res = re.exec("xabbbc").unwrap().unwrap();
+            assert_eq!(res.get::<StdString>(0).unwrap(), "abbbc");
+            assert_eq!(res.get::<StdString>(1).unwrap(), "bbb");
+            assert_eq!(res.as_object().get::<_, usize>("index").unwrap(), 1);
+            assert!(re.exec("xabbbc").unwrap().is_none());
+        })
+    }
+
+    #[test]
+    fn ignores_prototype() {
+        test_with(|ctx| {
+            let re: RegExp = ctx
+                .eval(
+                    r#"
+                    const re = /a+/u;
+                    RegExp.prototype.exec = () => null;
+                    Object.defineProperty(RegExp.prototype, "flags", { get: () => "" });
+                    Object.defineProperty(RegExp.prototype, "source", { get: () => "" });
+                    globalThis.RegExp = function() {};
+                    re
+                    "#,
+                )
+                .unwrap();
+            assert!(re.is_regexp());
+            assert_eq!(re.source().unwrap(), "a+");
+            assert_eq!(re.flags().unwrap(), "u");
+            assert!(re.test("baa").unwrap());
+            assert!(RegExp::new(ctx.clone(), "b", "y")
+                .unwrap()
+                .test("b")
+                .unwrap());
+
+            let fake: Object = ctx
+                .eval("Object.setPrototypeOf({}, Object.getPrototypeOf(re))")
+                .unwrap();
+            assert!(!fake.is_regexp());
+        })
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let re = RegExp::new(ctx.clone(), "^\\d+$", "").unwrap();
+            ctx.globals().set("re", re).unwrap();
+            let res: bool = ctx
+                .eval("re instanceof RegExp && re.test('123') && !re.test('12a')")
+                .unwrap();
+            assert!(res);
+
+            assert!(RegExp::new(ctx.clone(), "(", "").is_err());
+            assert!(RegExp::new(ctx.clone(), "a", "q").is_err());
+        })
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn to_regex() {
+        test_with(|ctx| {
+            let re: RegExp = ctx.eval("/^a.c$/is").unwrap();
+            let re = re.to_regex().unwrap();
+            assert!(re.is_match("A\nC"));
+
+            let re: regex::Regex = ctx.eval(r"/^\u{1F600}$/u").unwrap();
+            assert!(re.is_match("\u{1F600}"));
+            let re: RegExp = ctx.eval(r"/^\u{2}$/").unwrap();
+            assert!(re.test("uu").unwrap());
+            assert!(re.to_regex().is_err());
+            let re: RegExp = ctx.eval(r"/\\p/").unwrap();
+            assert!(re.to_regex().unwrap().is_match("\\p"));
+            assert!(ctx.eval::<regex::Regex, _>("/\\p{L}/").is_err());
+        })
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn from_regex() {
+        test_with(|ctx| {
+            let re = regex::Regex::new(r"(?is)^a.\p{L}$").unwrap();
+            ctx.globals().set("re", &re).unwrap();
+            let res: bool = ctx
+                .eval("re instanceof RegExp && re.flags == 'isu' && re.test('A\\nα')")
+                .unwrap();
+            assert!(res);
+            let back: regex::Regex = ctx.eval("re").unwrap();
+            assert!(back.is_match("a\nΩ"));
+
+            let re = regex::Regex::new(r"\Aa").unwrap();
+            assert!(RegExp::from_regex(ctx.clone(), &re).is_err());
+        })
+    }
+}
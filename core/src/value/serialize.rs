@@ -0,0 +1,202 @@
+use crate::{
+    context::snapshot::{read_object, write_object},
+    qjs, Ctx, Result, Value,
+};
+
+/// Options for [`Value::serialize`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Allow function bytecode and modules, which only the same version of QuickJS can read.
+    pub bytecode: bool,
+    /// Allow shared array buffers, which are written as a pointer to their memory.
+    pub shared_array_buffers: bool,
+    /// Write objects referenced multiple times once, keeping their identity and allowing
+    /// cycles. Otherwise shared objects are written for every reference and cycles are an
+    /// error.
+    pub references: bool,
+    /// Write the output with the byte order of the other endianness.
+    pub byte_swap: bool,
+}
+
+impl WriteOptions {
+    fn to_flag(self) -> i32 {
+        let mut flag = 0;
+        if self.bytecode {
+            flag |= qjs::JS_WRITE_OBJ_BYTECODE;
+        }
+        if self.shared_array_buffers {
+            flag |= qjs::JS_WRITE_OBJ_SAB;
+        }
+        if self.references {
+            flag |= qjs::JS_WRITE_OBJ_REFERENCE;
+        }
+        if self.byte_swap {
+            flag |= qjs::JS_WRITE_OBJ_BSWAP;
+        }
+        flag as i32
+    }
+}
+
+/// Options for [`Value::deserialize_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Allow function bytecode and modules.
+    pub bytecode: bool,
+    /// Allow shared array buffers.
+    pub shared_array_buffers: bool,
+    /// Allow references to objects written before.
+    pub references: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            bytecode: false,
+            shared_array_buffers: false,
+            references: true,
+        }
+    }
+}
+
+impl ReadOptions {
+    fn to_flag(self) -> i32 {
+        let mut flag = 0;
+        if self.bytecode {
+            flag |= qjs::JS_READ_OBJ_BYTECODE;
+        }
+        if self.shared_array_buffers {
+            flag |= qjs::JS_READ_OBJ_SAB;
+        }
+        if self.references {
+            flag |= qjs::JS_READ_OBJ_REFERENCE;
+        }
+        flag as i32
+    }
+}
+
+impl<'js> Value<'js> {
+    /// Serialize the value into the binary format of QuickJS.
+    ///
+    /// Primitives, arrays, plain objects, dates, boxed primitives, array buffers and typed
+    /// arrays can be serialized, other objects like functions or maps result in an exception.
+    /// The format is compact and fast to read, which makes it suited for caching computed data
+    /// between runs. It depends on the version of QuickJS, so the cached data should be
+    /// invalidated when the version changes.
+    ///
+    /// ```
+    /// # use rquickjs::{Runtime, Context, Value, WriteOptions};
+    /// # let rt = Runtime::new().unwrap();
+    /// # let ctx = Context::full(&rt).unwrap();
+    /// # ctx.with(|ctx| {
+    /// let value: Value = ctx.eval("const a = { n: 1 }; [a, a]").unwrap();
+    /// let options = WriteOptions {
+    ///     references: true,
+    ///     ..Default::default()
+    /// };
+    /// let bytes = value.serialize(options).unwrap();
+    /// let copy = Value::deserialize(ctx.clone(), &bytes).unwrap();
+    /// ctx.globals().set("copy", copy).unwrap();
+    /// assert!(ctx.eval::<bool, _>("copy[0] === copy[1] && copy[0].n === 1").unwrap());
+    /// # });
+    /// ```
+    pub fn serialize(&self, options: WriteOptions) -> Result<Vec<u8>> {
+        unsafe { write_object(&self.ctx, self.value, options.to_flag()) }
+    }
+
+    /// Deserialize a value written by [`Value::serialize`].
+    ///
+    /// Object references are allowed, the data may not contain bytecode or shared array
+    /// buffers. Invalid data results in an exception.
+    pub fn deserialize(ctx: Ctx<'js>, bytes: &[u8]) -> Result<Value<'js>> {
+        // Safety: without bytecode and shared array buffers the data is validated.
+        unsafe { Self::deserialize_with_options(ctx, bytes, ReadOptions::default()) }
+    }
+
+    /// Deserialize a value written by [`Value::serialize`] with the given options.
+    ///
+    /// # Safety
+    /// QuickJS doesn't validate bytecode, so when allowing bytecode the data must be bytecode
+    /// written by the same version of QuickJS. A shared array buffer is read from the pointer in
+    /// the data, so when allowing shared array buffers the data must have been written in this
+    /// process while the buffers it contains are still alive.
+    pub unsafe fn deserialize_with_options(
+        ctx: Ctx<'js>,
+        bytes: &[u8],
+        options: ReadOptions,
+    ) -> Result<Value<'js>> {
+        let value = read_object(&ctx, bytes, options.to_flag())?;
+        Ok(Value::from_js_value(ctx, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{test_with, Object, ReadOptions, Value, WriteOptions};
+
+    #[test]
+    fn object_graph() {
+        test_with(|ctx| {
+            let value: Value = ctx
+                .eval(
+                    r#"
+                        const shared = { name: "shared", bytes: new Uint8Array([1, 2, 3]) };
+                        const root = { a: shared, b: shared, date: new Date(0), big: 10n, list: [1, "two"] };
+                        root.self = root;
+                        root
+                    "#,
+                )
+                .unwrap();
+            assert!(value.serialize(WriteOptions::default()).is_err());
+            ctx.catch();
+
+            let bytes = value
+                .serialize(WriteOptions {
+                    references: true,
+                    ..Default::default()
+                })
+                .unwrap();
+            let copy = Value::deserialize(ctx.clone(), &bytes).unwrap();
+            ctx.globals().set("copy", copy).unwrap();
+            let res: bool = ctx
+                .eval(
+                    r#"
+                        copy !== root && copy.self === copy && copy.a === copy.b &&
+                        copy.a.bytes[2] === 3 && copy.date.getTime() === 0 &&
+                        copy.big === 10n && copy.list[1] === "two"
+                    "#,
+                )
+                .unwrap();
+            assert!(res);
+
+            // Without references the data can't be read back.
+            let res = unsafe {
+                Value::deserialize_with_options(
+                    ctx.clone(),
+                    &bytes,
+                    ReadOptions {
+                        references: false,
+                        ..Default::default()
+                    },
+                )
+            };
+            assert!(res.is_err());
+            ctx.catch();
+            assert!(Value::deserialize(ctx.clone(), &bytes[..bytes.len() / 2]).is_err());
+            ctx.catch();
+
+            // Shared objects are duplicated without references.
+            let value: Value = ctx.eval("const a = {}; [a, a]").unwrap();
+            let bytes = value.serialize(WriteOptions::default()).unwrap();
+            let copy = Value::deserialize(ctx.clone(), &bytes).unwrap();
+            let copy = copy.into_array().unwrap();
+            assert_ne!(
+                copy.get::<Object>(0).unwrap(),
+                copy.get::<Object>(1).unwrap()
+            );
+
+            let func: Value = ctx.eval("() => 1").unwrap();
+            assert!(func.serialize(WriteOptions::default()).is_err());
+            ctx.catch();
+        })
+    }
+}
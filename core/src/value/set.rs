@@ -0,0 +1,306 @@
+//! JavaScript `Set` functionality.
+
+use std::ops::Deref;
+
+use crate::{
+    convert::FromIteratorJs,
+    qjs,
+    value::map::{from_raw, IterKind, MapIter},
+    Ctx, Error, FromJs, IntoJs, Object, Outlive, Result, Value,
+};
+
+/// Rust representation of a JavaScript object of class Set.
+///
+/// Rust sets like [`HashSet`](std::collections::HashSet) are converted from and into JavaScript
+/// sets, other Rust collections can be converted from a JavaScript set too.
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct Set<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> Outlive<'js> for Set<'js> {
+    type Target<'to> = Set<'to>;
+}
+
+impl<'js> Set<'js> {
+    /// Create a new empty set.
+    pub fn new(ctx: Ctx<'js>) -> Result<Self> {
+        unsafe {
+            let val = qjs::JS_NewMap(ctx.as_ptr(), 1);
+            let val = ctx.handle_exception(val)?;
+            Ok(Set(Object::from_js_value(ctx, val)))
+        }
+    }
+
+    /// Create a new set from an iterator of values.
+    pub fn from_values<T, I>(ctx: Ctx<'js>, values: I) -> Result<Self>
+    where
+        T: IntoJs<'js>,
+        I: IntoIterator<Item = T>,
+    {
+        let set = Set::new(ctx)?;
+        for value in values {
+            set.add(value)?;
+        }
+        Ok(set)
+    }
+
+    /// Add a value to the set.
+    pub fn add<T: IntoJs<'js>>(&self, value: T) -> Result<()> {
+        let ctx = self.ctx();
+        let value = value.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapSet(
+                ctx.as_ptr(),
+                self.as_js_value(),
+                value.as_js_value(),
+                qjs::JS_UNDEFINED,
+                1,
+            );
+            from_raw::<Value>(ctx, val).map(|_| ())
+        }
+    }
+
+    /// Returns whether the set contains a value.
+    pub fn has<T: IntoJs<'js>>(&self, value: T) -> Result<bool> {
+        let ctx = self.ctx();
+        let value = value.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapHas(ctx.as_ptr(), self.as_js_value(), value.as_js_value(), 1);
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Remove a value from the set, returns whether the value was present.
+    pub fn delete<T: IntoJs<'js>>(&self, value: T) -> Result<bool> {
+        let ctx = self.ctx();
+        let value = value.into_js(ctx)?;
+        unsafe {
+            let val = qjs::JS_MapDelete(ctx.as_ptr(), self.as_js_value(), value.as_js_value(), 1);
+            from_raw(ctx, val)
+        }
+    }
+
+    /// Remove all values from the set.
+    pub fn clear(&self) -> Result<()> {
+        let ctx = self.ctx();
+        unsafe { from_raw(ctx, qjs::JS_MapClear(ctx.as_ptr(), self.as_js_value(), 1)) }
+    }
+
+    /// Returns the number of values in the set.
+    pub fn len(&self) -> Result<usize> {
+        let ctx = self.ctx();
+        unsafe { from_raw(ctx, qjs::JS_MapSize(ctx.as_ptr(), self.as_js_value(), 1)) }
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns an iterator over the values of the set in insertion order.
+    pub fn values<T: FromJs<'js>>(&self) -> Result<MapIter<'js, T>> {
+        MapIter::new(&self.0, IterKind::Values, true)
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object.is_set().then_some(Set(object))
+    }
+}
+
+impl<'js> Deref for Set<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for Set<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for Set<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for Set<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "Set"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for Set<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js, A> FromIteratorJs<'js, A> for Set<'js>
+where
+    A: IntoJs<'js>,
+{
+    type Item = Value<'js>;
+
+    fn from_iter_js<T>(ctx: &Ctx<'js>, iter: T) -> Result<Self>
+    where
+        T: IntoIterator<Item = A>,
+    {
+        Set::from_values(ctx.clone(), iter)
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is an instance of [`Set`].
+    pub fn is_set(&self) -> bool {
+        unsafe { qjs::JS_IsSet(self.as_js_value()) != 0 }
+    }
+
+    /// Interpret as [`Set`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_set(&self) -> &Set<'js> {
+        &*(self as *const Object as *const Set)
+    }
+
+    /// Turn the object into a set if the object is an instance of [`Set`].
+    pub fn as_set(&self) -> Option<&Set<'js>> {
+        self.is_set().then_some(unsafe { self.ref_set() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeSet, HashSet};
+
+    use crate::{convert::IteratorJs, *};
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let set: Set = ctx.eval("new Set([1, 2, 3, 2])").unwrap();
+            assert_eq!(set.len().unwrap(), 3);
+            assert!(set.has(2).unwrap());
+            assert!(set.delete(2).unwrap());
+            assert!(!set.has(2).unwrap());
+
+            let values: HashSet<i32> = set.values().unwrap().collect::<Result<_>>().unwrap();
+            assert_eq!(values, [1, 3].into_iter().collect());
+
+            set.clear().unwrap();
+            assert!(set.is_empty().unwrap());
+        })
+    }
+
+    #[test]
+    fn ignores_prototype() {
+        test_with(|ctx| {
+            let set: Set = ctx
+                .eval(
+                    r#"
+                    const set = new Set([1]);
+                    for (const name of ['add', 'has', 'delete', 'clear', 'values']) {
+                        Set.prototype[name] = () => { throw new Error(name) };
+                    }
+                    Object.defineProperty(Set.prototype, 'size', { get: () => 42 });
+                    globalThis.Set = function() {};
+                    set
+                    "#,
+                )
+                .unwrap();
+            assert!(set.is_set());
+            set.add(2).unwrap();
+            assert!(set.has(2).unwrap());
+            assert_eq!(set.len().unwrap(), 2);
+            let values: Vec<i32> = ctx.eval("set").unwrap();
+            assert_eq!(values, [1, 2]);
+            assert!(set.delete(1).unwrap());
+            set.clear().unwrap();
+            assert!(Set::new(ctx.clone()).unwrap().is_empty().unwrap());
+
+            let obj: Object = ctx.eval("new (class extends Object {})()").unwrap();
+            assert!(!obj.is_set());
+        })
+    }
+
+    #[test]
+    fn rust_set_conversion() {
+        test_with(|ctx| {
+            let set: HashSet<StdString> = ctx.eval("new Set(['a', 'b', 'a'])").unwrap();
+            assert_eq!(set.len(), 2);
+            assert!(set.contains("b"));
+            let set: BTreeSet<i32> = ctx.eval("[3, 1, 3]").unwrap();
+            assert_eq!(set.into_iter().collect::<Vec<_>>(), [1, 3]);
+
+            let values: BTreeSet<i32> = [1, 2, 3].into_iter().collect();
+            let set: Set = values.iter().copied().collect_js(&ctx).unwrap();
+            ctx.globals().set("set", set).unwrap();
+            let res: bool = ctx
+                .eval("set instanceof Set && set.size == 3 && set.has(2)")
+                .unwrap();
+            assert!(res);
+            let back: BTreeSet<i32> = ctx.eval("set").unwrap();
+            assert_eq!(back, values);
+        })
+    }
+
+    #[test]
+    fn into_javascript() {
+        test_with(|ctx| {
+            let values: HashSet<&str> = ["a", "b"].into_iter().collect();
+            let set = Set::from_values(ctx.clone(), values.iter().copied()).unwrap();
+            ctx.globals().set("set", set).unwrap();
+            let res: bool = ctx
+                .eval("set instanceof Set && set.has('a') && set.has('b') && set.size == 2")
+                .unwrap();
+            assert!(res);
+
+            ctx.globals().set("values", values).unwrap();
+            let numbers: BTreeSet<i32> = [3, 1].into_iter().collect();
+            ctx.globals().set("numbers", &numbers).unwrap();
+            let res: bool = ctx
+                .eval("values instanceof Set && values.has('b') && numbers instanceof Set && [...numbers].join() == '1,3'")
+                .unwrap();
+            assert!(res);
+        })
+    }
+}
@@ -0,0 +1,432 @@
+//! JavaScript `SharedArrayBuffer` functionality.
+
+use std::{
+    alloc::{self, Layout},
+    fmt,
+    mem::{self, MaybeUninit},
+    ops::Deref,
+    os::raw::c_void,
+    ptr::{self, NonNull},
+    slice,
+    sync::atomic::{self, AtomicU8, AtomicUsize, Ordering},
+};
+
+use crate::{qjs, Ctx, Error, FromJs, IntoJs, Object, Outlive, Result, Value};
+
+/// The header stored in front of the data of shared memory.
+#[repr(C)]
+struct Header {
+    count: AtomicUsize,
+    len: usize,
+}
+
+/// The offset of the data from the start of the allocation, also the alignment of the data.
+const DATA_OFFSET: usize = 16;
+
+/// Memory which can be shared between Rust, scripts and runtimes on other threads.
+///
+/// Every `SharedArrayBuffer` of a runtime is backed by shared memory, it is reference counted
+/// and freed once the last buffer and handle referencing it is dropped. The memory can only be
+/// accessed atomically, just like a script accesses it with `Atomics`.
+pub struct SharedMemory {
+    ptr: NonNull<AtomicU8>,
+}
+
+unsafe impl Send for SharedMemory {}
+unsafe impl Sync for SharedMemory {}
+
+impl SharedMemory {
+    /// The functions used by runtimes to allocate the memory of a `SharedArrayBuffer`.
+    pub(crate) const FUNCTIONS: qjs::JSSharedArrayBufferFunctions =
+        qjs::JSSharedArrayBufferFunctions {
+            sab_alloc: Some(Self::sab_alloc),
+            sab_free: Some(Self::sab_free),
+            sab_dup: Some(Self::sab_dup),
+            sab_opaque: ptr::null_mut(),
+        };
+
+    /// Allocate new zeroed shared memory of the given length in bytes.
+    ///
+    /// # Panic
+    /// Panics if the memory could not be allocated.
+    pub fn new(len: usize) -> Self {
+        Self::try_new(len).unwrap_or_else(|| alloc::handle_alloc_error(Self::layout(len)))
+    }
+
+    fn try_new(len: usize) -> Option<Self> {
+        let layout = Self::layout(len);
+        unsafe {
+            let base = NonNull::new(alloc::alloc_zeroed(layout))?;
+            base.cast::<Header>().as_ptr().write(Header {
+                count: AtomicUsize::new(1),
+                len,
+            });
+            Some(SharedMemory {
+                ptr: NonNull::new_unchecked(base.as_ptr().add(DATA_OFFSET).cast()),
+            })
+        }
+    }
+
+    fn layout(len: usize) -> Layout {
+        Layout::from_size_align(DATA_OFFSET + len.max(1), DATA_OFFSET).expect("invalid length")
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &*self.ptr.as_ptr().cast::<u8>().sub(DATA_OFFSET).cast() }
+    }
+
+    /// Take ownership of a reference to the memory starting at the given pointer.
+    ///
+    /// # Safety
+    /// The pointer must point to the data of shared memory with a reference which is not owned
+    /// by anything else.
+    pub(crate) unsafe fn from_raw(ptr: *mut c_void) -> Self {
+        SharedMemory {
+            ptr: NonNull::new_unchecked(ptr.cast()),
+        }
+    }
+
+    /// Take a new reference to the memory starting at the given pointer.
+    ///
+    /// # Safety
+    /// The pointer must point to the data of shared memory which is still referenced.
+    pub(crate) unsafe fn clone_raw(ptr: *mut c_void) -> Self {
+        SharedMemory::clone(&mem::ManuallyDrop::new(Self::from_raw(ptr)))
+    }
+
+    /// Returns the length of the memory in bytes.
+    pub fn len(&self) -> usize {
+        self.header().len
+    }
+
+    /// Returns whether the memory is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the memory as a slice of atomic bytes.
+    pub fn as_slice(&self) -> &[AtomicU8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len()) }
+    }
+
+    /// Returns a pointer to the start of the memory.
+    ///
+    /// The memory is concurrently accessed by other threads, all access has to be atomic.
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr().cast()
+    }
+
+    /// Returns whether two handles refer to the same memory.
+    pub fn ptr_eq(&self, other: &SharedMemory) -> bool {
+        self.ptr == other.ptr
+    }
+
+    unsafe extern "C" fn sab_alloc(_opaque: *mut c_void, size: qjs::size_t) -> *mut c_void {
+        match Self::try_new(size as _) {
+            Some(memory) => mem::ManuallyDrop::new(memory).ptr.as_ptr().cast(),
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe extern "C" fn sab_free(_opaque: *mut c_void, ptr: *mut c_void) {
+        drop(Self::from_raw(ptr));
+    }
+
+    unsafe extern "C" fn sab_dup(_opaque: *mut c_void, ptr: *mut c_void) {
+        mem::forget(Self::clone_raw(ptr));
+    }
+}
+
+impl Clone for SharedMemory {
+    fn clone(&self) -> Self {
+        self.header().count.fetch_add(1, Ordering::Relaxed);
+        SharedMemory { ptr: self.ptr }
+    }
+}
+
+impl Drop for SharedMemory {
+    fn drop(&mut self) {
+        if self.header().count.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        atomic::fence(Ordering::Acquire);
+        let layout = Self::layout(self.len());
+        unsafe { alloc::dealloc(self.ptr.as_ptr().cast::<u8>().sub(DATA_OFFSET), layout) }
+    }
+}
+
+impl Deref for SharedMemory {
+    type Target = [AtomicU8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl fmt::Debug for SharedMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedMemory")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Rust representation of a JavaScript object of class SharedArrayBuffer.
+///
+/// The `Atomics` object which scripts use to access the buffer is part of the
+/// [`TypedArrays`](crate::context::intrinsic::TypedArrays) intrinsic. `Atomics.wait` is only
+/// allowed once it is enabled with [`Runtime::set_can_block`](crate::Runtime::set_can_block).
+///
+/// ```
+/// # use std::sync::atomic::Ordering;
+/// # use rquickjs::{Runtime, Context, SharedArrayBuffer, shared_array_buffer::SharedMemory};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// let memory = SharedMemory::new(4);
+/// ctx.with(|ctx| {
+///     let buffer = SharedArrayBuffer::from_memory(ctx.clone(), memory.clone()).unwrap();
+///     ctx.globals().set("buffer", buffer).unwrap();
+///     let _: () = ctx.eval("Atomics.store(new Uint8Array(buffer), 1, 42)").unwrap();
+/// });
+/// assert_eq!(memory[1].load(Ordering::SeqCst), 42);
+/// ```
+#[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "shared-array-buffer")))]
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[repr(transparent)]
+pub struct SharedArrayBuffer<'js>(pub(crate) Object<'js>);
+
+unsafe impl<'js> Outlive<'js> for SharedArrayBuffer<'js> {
+    type Target<'to> = SharedArrayBuffer<'to>;
+}
+
+impl<'js> SharedArrayBuffer<'js> {
+    /// Create a new zeroed shared array buffer of the given length in bytes.
+    pub fn new(ctx: Ctx<'js>, len: usize) -> Result<Self> {
+        Self::from_memory(ctx, SharedMemory::new(len))
+    }
+
+    /// Create a shared array buffer backed by the given memory.
+    pub fn from_memory(ctx: Ctx<'js>, memory: SharedMemory) -> Result<Self> {
+        Ok(Self(Object(unsafe {
+            // The runtime takes its own reference to the memory.
+            let val = qjs::JS_NewArrayBuffer(
+                ctx.as_ptr(),
+                memory.as_ptr(),
+                memory.len() as _,
+                None,
+                ptr::null_mut(),
+                1,
+            );
+            ctx.handle_exception(val)?;
+            Value::from_js_value(ctx, val)
+        })))
+    }
+
+    /// Returns the memory backing the buffer.
+    pub fn memory(&self) -> Result<SharedMemory> {
+        let (ptr, _) = self
+            .raw()
+            .ok_or_else(|| Error::new_from_js("object", "SharedArrayBuffer"))?;
+        Ok(unsafe { SharedMemory::clone_raw(ptr.as_ptr().cast()) })
+    }
+
+    /// Get the length of the buffer in bytes.
+    pub fn len(&self) -> usize {
+        self.raw().map(|(_, len)| len).unwrap_or(0)
+    }
+
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the data and length of the buffer, `None` if the object isn't a shared array
+    /// buffer.
+    fn raw(&self) -> Option<(NonNull<u8>, usize)> {
+        // An `ArrayBuffer` would be accepted by `JS_GetArrayBuffer` too, but isn't backed by
+        // shared memory.
+        if !self.0.is_shared_array_buffer() {
+            return None;
+        }
+        let mut size = MaybeUninit::<qjs::size_t>::uninit();
+        let ptr = unsafe {
+            qjs::JS_GetArrayBuffer(self.0.ctx.as_ptr(), size.as_mut_ptr(), self.0.as_js_value())
+        };
+        NonNull::new(ptr).map(|ptr| (ptr, unsafe { size.assume_init() } as usize))
+    }
+
+    /// Reference to value
+    #[inline]
+    pub fn as_value(&self) -> &Value<'js> {
+        self.0.as_value()
+    }
+
+    /// Convert into value
+    #[inline]
+    pub fn into_value(self) -> Value<'js> {
+        self.0.into_value()
+    }
+
+    /// Convert from value
+    pub fn from_value(value: Value<'js>) -> Option<Self> {
+        Self::from_object(Object::from_value(value).ok()?)
+    }
+
+    /// Reference as an object
+    #[inline]
+    pub fn as_object(&self) -> &Object<'js> {
+        &self.0
+    }
+
+    /// Convert into an object
+    #[inline]
+    pub fn into_object(self) -> Object<'js> {
+        self.0
+    }
+
+    /// Convert from an object
+    pub fn from_object(object: Object<'js>) -> Option<Self> {
+        object
+            .is_shared_array_buffer()
+            .then_some(SharedArrayBuffer(object))
+    }
+}
+
+impl<'js> Deref for SharedArrayBuffer<'js> {
+    type Target = Object<'js>;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Object<'js>> for SharedArrayBuffer<'js> {
+    fn as_ref(&self) -> &Object<'js> {
+        self.as_object()
+    }
+}
+
+impl<'js> AsRef<Value<'js>> for SharedArrayBuffer<'js> {
+    fn as_ref(&self) -> &Value<'js> {
+        self.as_value()
+    }
+}
+
+impl<'js> FromJs<'js> for SharedArrayBuffer<'js> {
+    fn from_js(_: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        let ty_name = value.type_name();
+        if let Some(v) = Self::from_value(value) {
+            Ok(v)
+        } else {
+            Err(Error::new_from_js(ty_name, "SharedArrayBuffer"))
+        }
+    }
+}
+
+impl<'js> IntoJs<'js> for SharedArrayBuffer<'js> {
+    fn into_js(self, _: &Ctx<'js>) -> Result<Value<'js>> {
+        Ok(self.into_value())
+    }
+}
+
+impl<'js> FromJs<'js> for SharedMemory {
+    fn from_js(ctx: &Ctx<'js>, value: Value<'js>) -> Result<Self> {
+        SharedArrayBuffer::from_js(ctx, value)?.memory()
+    }
+}
+
+impl<'js> IntoJs<'js> for SharedMemory {
+    fn into_js(self, ctx: &Ctx<'js>) -> Result<Value<'js>> {
+        SharedArrayBuffer::from_memory(ctx.clone(), self).map(SharedArrayBuffer::into_value)
+    }
+}
+
+impl<'js> Object<'js> {
+    /// Returns whether the object is a [`SharedArrayBuffer`].
+    ///
+    /// Objects which only have the prototype of `SharedArrayBuffer` are not.
+    pub fn is_shared_array_buffer(&self) -> bool {
+        unsafe { qjs::JS_IsSharedArrayBuffer(self.as_js_value()) != 0 }
+    }
+
+    /// Interpret as [`SharedArrayBuffer`]
+    ///
+    /// # Safety
+    /// You should be sure that the object actually is the required type.
+    pub unsafe fn ref_shared_array_buffer(&self) -> &SharedArrayBuffer<'js> {
+        &*(self as *const Object as *const SharedArrayBuffer)
+    }
+
+    /// Turn the object into a shared array buffer if the object is an instance of
+    /// [`SharedArrayBuffer`].
+    pub fn as_shared_array_buffer(&self) -> Option<&SharedArrayBuffer<'js>> {
+        self.is_shared_array_buffer()
+            .then_some(unsafe { self.ref_shared_array_buffer() })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::Ordering;
+
+    use crate::{shared_array_buffer::SharedMemory, test_with, SharedArrayBuffer};
+
+    #[test]
+    fn shared_memory() {
+        let memory = SharedMemory::new(8);
+        test_with(|ctx| {
+            let buffer = SharedArrayBuffer::from_memory(ctx.clone(), memory.clone()).unwrap();
+            assert_eq!(buffer.len(), 8);
+            assert!(buffer.memory().unwrap().ptr_eq(&memory));
+            ctx.globals().set("buffer", buffer).unwrap();
+            memory[0].store(7, Ordering::SeqCst);
+            let res: i32 = ctx
+                .eval(
+                    r#"
+                    const view = new Int32Array(buffer);
+                    Atomics.add(view, 1, 5);
+                    new Uint8Array(buffer)[0]
+                "#,
+                )
+                .unwrap();
+            assert_eq!(res, 7);
+        });
+        let value = i32::from_ne_bytes([4, 5, 6, 7].map(|i| memory[i].load(Ordering::SeqCst)));
+        assert_eq!(value, 5);
+    }
+
+    #[test]
+    fn from_javascript() {
+        test_with(|ctx| {
+            let memory: SharedMemory = ctx
+                .eval("const b = new SharedArrayBuffer(3); new Uint8Array(b).set([1, 2, 3]); b")
+                .unwrap();
+            let bytes: Vec<u8> = memory.iter().map(|x| x.load(Ordering::SeqCst)).collect();
+            assert_eq!(bytes, [1, 2, 3]);
+
+            let buffer: crate::Object = ctx.eval("new ArrayBuffer(3)").unwrap();
+            assert!(!buffer.is_shared_array_buffer());
+            assert!(SharedArrayBuffer::new(ctx.clone(), 2)
+                .unwrap()
+                .is_shared_array_buffer());
+        });
+    }
+
+    #[test]
+    fn forged() {
+        test_with(|ctx| {
+            for source in [
+                "Object.setPrototypeOf(new ArrayBuffer(64), SharedArrayBuffer.prototype)",
+                "Object.create(SharedArrayBuffer.prototype)",
+            ] {
+                let object: crate::Object = ctx.eval(source).unwrap();
+                assert!(!object.is_shared_array_buffer());
+                assert!(SharedArrayBuffer::from_object(object.clone()).is_none());
+                assert!(ctx.eval::<SharedMemory, _>(source).is_err());
+                let buffer = unsafe { object.ref_shared_array_buffer() };
+                assert!(buffer.memory().is_err());
+                assert_eq!(buffer.len(), 0);
+            }
+        });
+    }
+}
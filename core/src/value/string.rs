@@ -1,5 +1,5 @@
 use crate::{qjs, Ctx, Error, Result, StdString, Value};
-use std::{mem, slice, str};
+use std::{fmt, mem, ops::Deref, os::raw::c_char, slice, str};
 
 /// Rust representation of a JavaScript string.
 #[derive(Debug, Clone, PartialEq, Hash)]
@@ -9,6 +9,14 @@ pub struct String<'js>(pub(crate) Value<'js>);
 impl<'js> String<'js> {
     /// Convert the JavaScript string to a Rust string.
     pub fn to_string(&self) -> Result<StdString> {
+        self.to_str().map(|s| s.as_ref().to_owned())
+    }
+
+    /// Borrow the content of the JavaScript string as UTF-8.
+    ///
+    /// Strings with only ASCII characters are borrowed as is, others are converted to UTF-8
+    /// once. Unlike [`String::to_string`] no Rust string is allocated.
+    pub fn to_str(&self) -> Result<JsStr<'js>> {
         let mut len = mem::MaybeUninit::uninit();
         let ptr = unsafe {
             qjs::JS_ToCStringLen(self.0.ctx.as_ptr(), len.as_mut_ptr(), self.0.as_js_value())
@@ -18,11 +26,14 @@ impl<'js> String<'js> {
             // so just incase check it.
             return Err(Error::Unknown);
         }
-        let len = unsafe { len.assume_init() };
-        let bytes: &[u8] = unsafe { slice::from_raw_parts(ptr as _, len as _) };
-        let result = str::from_utf8(bytes).map(|s| s.into());
-        unsafe { qjs::JS_FreeCString(self.0.ctx.as_ptr(), ptr) };
-        Ok(result?)
+        let string = JsStr {
+            ctx: self.0.ctx.clone(),
+            ptr,
+            len: unsafe { len.assume_init() } as _,
+        };
+        // Strings with lone surrogates are not valid UTF-8.
+        str::from_utf8(string.as_bytes())?;
+        Ok(string)
     }
 
     /// Create a new JavaScript string from an Rust string.
@@ -37,6 +48,78 @@ impl<'js> String<'js> {
     }
 }
 
+/// The content of a JavaScript string borrowed as UTF-8, see [`String::to_str`].
+///
+/// Taking it as an argument of a native function avoids allocating a Rust string for every
+/// call.
+///
+/// ```
+/// # use rquickjs::{Runtime, Context, Function, JsStr};
+/// # let rt = Runtime::new().unwrap();
+/// # let ctx = Context::full(&rt).unwrap();
+/// # ctx.with(|ctx| {
+/// let log = Function::new(ctx.clone(), |message: JsStr| message.len()).unwrap();
+/// ctx.globals().set("log", log).unwrap();
+/// assert_eq!(ctx.eval::<usize, _>("log('hello')").unwrap(), 5);
+/// # })
+/// ```
+pub struct JsStr<'js> {
+    ctx: Ctx<'js>,
+    ptr: *const c_char,
+    len: usize,
+}
+
+impl<'js> JsStr<'js> {
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr as _, self.len) }
+    }
+}
+
+impl<'js> Deref for JsStr<'js> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        // Safety: the content was validated when it was borrowed.
+        unsafe { str::from_utf8_unchecked(self.as_bytes()) }
+    }
+}
+
+impl<'js> AsRef<str> for JsStr<'js> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<'js> PartialEq<str> for JsStr<'js> {
+    fn eq(&self, other: &str) -> bool {
+        **self == *other
+    }
+}
+
+impl<'js> PartialEq<&str> for JsStr<'js> {
+    fn eq(&self, other: &&str) -> bool {
+        **self == **other
+    }
+}
+
+impl<'js> fmt::Debug for JsStr<'js> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<'js> fmt::Display for JsStr<'js> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<'js> Drop for JsStr<'js> {
+    fn drop(&mut self) {
+        unsafe { qjs::JS_FreeCString(self.ctx.as_ptr(), self.ptr) };
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{prelude::*, *};
@@ -57,4 +140,23 @@ mod test {
             assert_eq!(text, "foobar".to_string());
         });
     }
+
+    #[test]
+    fn borrowed_arguments() {
+        test_with(|ctx| {
+            let count = Function::new(ctx.clone(), |text: JsStr, needle: JsStr| {
+                text.matches(&*needle).count()
+            })
+            .unwrap();
+            ctx.globals().set("count", count).unwrap();
+            let res: usize = ctx.eval("count('a-é-a-😀-a', 'a')").unwrap();
+            assert_eq!(res, 3);
+            assert!(ctx.eval::<usize, _>("count(1, 'a')").is_err());
+
+            let s: String = ctx.eval("'café'").unwrap();
+            assert_eq!(s.to_str().unwrap(), "café");
+            let lone: String = ctx.eval(r"'\ud800'").unwrap();
+            assert!(lone.to_str().is_err());
+        });
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
-    atom::PredefinedAtom, qjs, ArrayBuffer, Ctx, Error, FromJs, Function, IntoJs, Object, Outlive,
-    Result, Value,
+    atom::PredefinedAtom, qjs, ArrayBuffer, Ctx, Error, Exception, FromJs, Function, IntoJs,
+    Object, Outlive, Result, Value,
 };
 use std::{
     convert::{TryFrom, TryInto},
@@ -172,6 +172,14 @@ impl<'js, T> TypedArray<'js, T> {
         Some(unsafe { slice::from_raw_parts(ptr.as_ptr(), len) })
     }
 
+    /// Returns the underlying bytes of the buffer,
+    ///
+    /// Throws a `TypeError` if the array is detached.
+    pub fn try_as_bytes(&self) -> Result<&[u8]> {
+        self.as_bytes()
+            .ok_or_else(|| Exception::throw_type(self.ctx(), "ArrayBuffer is detached"))
+    }
+
     pub fn as_raw(&self) -> Option<RawArrayBuffer> {
         let (_, len, ptr) = Self::get_raw_bytes(self.as_value())?;
         Some(RawArrayBuffer { len, ptr })
@@ -214,7 +222,11 @@ impl<'js, T> TypedArray<'js, T> {
                 len.as_mut_ptr(),
                 stp.as_mut_ptr(),
             );
-            ctx.handle_exception(val).ok()?;
+            if qjs::JS_VALUE_GET_NORM_TAG(val) == qjs::JS_TAG_EXCEPTION {
+                // The buffer is detached, the exception thrown for it isn't kept pending.
+                qjs::JS_FreeValue(ctx.as_ptr(), qjs::JS_GetException(ctx.as_ptr()));
+                return None;
+            }
             Value::from_js_value(ctx.clone(), val)
         };
         let off: usize = unsafe { off.assume_init() }
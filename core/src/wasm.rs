@@ -0,0 +1,94 @@
+//! Support for running on `wasm32-unknown-unknown`.
+//!
+//! There the clock and random source of the operating system are not available, so they have to
+//! be provided by the embedder with [`init`] before creating a [`Runtime`](crate::Runtime):
+//!
+//! ```ignore
+//! use wasm_bindgen::prelude::*;
+//!
+//! #[wasm_bindgen(start)]
+//! fn start() {
+//!     rquickjs::wasm::init(rquickjs::wasm::Host {
+//!         now: js_sys::Date::now,
+//!         fill_random: |buf| getrandom::getrandom(buf).is_ok(),
+//!     });
+//! }
+//! ```
+//!
+//! On other targets the functions are stored but not used.
+
+use std::{sync::RwLock, time::Duration};
+
+/// The functions provided by the host of the wasm module.
+#[derive(Debug, Clone, Copy)]
+pub struct Host {
+    /// Returns the current time in milliseconds since the unix epoch, like `Date.now()`.
+    ///
+    /// Used for `Date`, the `performance` global and the profiler.
+    pub now: fn() -> f64,
+    /// Fills the buffer with cryptographically secure random bytes, returning `false` on failure.
+    ///
+    /// Used for `crypto.getRandomValues`.
+    pub fill_random: fn(&mut [u8]) -> bool,
+}
+
+static HOST: RwLock<Option<Host>> = RwLock::new(None);
+
+/// Set the functions provided by the host.
+///
+/// Can be called again to replace the functions.
+pub fn init(host: Host) {
+    *HOST.write().unwrap_or_else(|e| e.into_inner()) = Some(host);
+    #[cfg(all(target_family = "wasm", target_os = "unknown"))]
+    crate::qjs::wasm::set_clock(host.now);
+}
+
+/// Returns the functions set with [`init`].
+pub fn host() -> Option<Host> {
+    *HOST.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn now() -> f64 {
+    host().map(|host| (host.now)()).unwrap_or_default()
+}
+
+pub(crate) fn fill_random(buf: &mut [u8]) -> bool {
+    host().map(|host| (host.fill_random)(buf)).unwrap_or(false)
+}
+
+/// A replacement of [`std::time::Instant`] based on the clock of the host.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Instant(f64);
+
+impl Instant {
+    pub fn now() -> Self {
+        Instant(now())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((now() - self.0).max(0.0) / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{fill_random, host, init, Host, Instant};
+
+    #[test]
+    fn host_functions() {
+        init(Host {
+            now: || 1500.0,
+            fill_random: |buf| {
+                buf.fill(7);
+                true
+            },
+        });
+        assert_eq!(host().map(|x| (x.now)()), Some(1500.0));
+        assert_eq!(Instant::now().elapsed(), Duration::ZERO);
+        let mut buf = [0u8; 4];
+        assert!(fill_random(&mut buf));
+        assert_eq!(buf, [7; 4]);
+    }
+}
@@ -0,0 +1,405 @@
+//! Scripts running in isolated runtimes on their own threads.
+//!
+//! A [`Worker`] evaluates a script in a new runtime on a new thread. The script and the thread
+//! which spawned it exchange messages with `postMessage` and `onmessage`, the messages are
+//! serialized with the QuickJS object serialization so only plain data can be sent, no values
+//! are ever shared between the runtimes. With the `shared-array-buffer` feature a
+//! `SharedArrayBuffer` in a message is shared with the receiver instead of copied.
+//!
+//! ```
+//! # use rquickjs::{Runtime, Context, worker::Worker};
+//! let worker = Worker::spawn(
+//!     r#"
+//!     onmessage = (event) => postMessage(event.data.map((x) => x * 2));
+//! "#,
+//! );
+//! let rt = Runtime::new().unwrap();
+//! let ctx = Context::full(&rt).unwrap();
+//! ctx.with(|ctx| {
+//!     let data: rquickjs::Value = ctx.eval("[1, 2, 3]").unwrap();
+//!     worker.post_message(data).unwrap();
+//!     let res: Vec<i32> = worker.receive(&ctx).unwrap().unwrap().get().unwrap();
+//!     assert_eq!(res, [2, 4, 6]);
+//! });
+//! worker.terminate();
+//! ```
+
+use std::{
+    result::Result as StdResult,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+#[cfg(feature = "shared-array-buffer")]
+use std::{mem::MaybeUninit, slice};
+
+#[cfg(not(feature = "shared-array-buffer"))]
+use crate::context::snapshot::write_object;
+use crate::{
+    context::snapshot::read_object, CaughtError, Context, Ctx, Error, Function, Null, Object,
+    Result, Runtime, StdString, Value,
+};
+#[cfg(feature = "shared-array-buffer")]
+use crate::{qjs, shared_array_buffer::SharedMemory};
+
+/// A serialized message, which keeps the shared memory it references alive until it is read.
+struct Message {
+    bytes: Vec<u8>,
+    #[cfg(feature = "shared-array-buffer")]
+    _memory: Vec<SharedMemory>,
+}
+
+enum Command {
+    Message(Message),
+    Terminate,
+}
+
+/// A script running in its own runtime on another thread.
+///
+/// Dropping the worker terminates it and waits for its thread to finish.
+pub struct Worker {
+    commands: Sender<Command>,
+    messages: Receiver<Message>,
+    terminated: Arc<AtomicBool>,
+    thread: Option<JoinHandle<StdResult<(), StdString>>>,
+}
+
+impl Worker {
+    /// Spawn a worker which evaluates the given script.
+    pub fn spawn<S>(source: S) -> Self
+    where
+        S: Into<Vec<u8>>,
+    {
+        Self::spawn_with(source, |_| Ok(()))
+    }
+
+    /// Spawn a worker which evaluates the given script after initializing its context.
+    ///
+    /// The initializer runs on the worker thread before the script and can be used to install
+    /// additional globals.
+    pub fn spawn_with<S, F>(source: S, init: F) -> Self
+    where
+        S: Into<Vec<u8>>,
+        F: FnOnce(&Ctx) -> Result<()> + Send + 'static,
+    {
+        let source = source.into();
+        let (commands, command_receiver) = mpsc::channel();
+        let (message_sender, messages) = mpsc::channel();
+        let terminated = Arc::new(AtomicBool::new(false));
+        let flag = terminated.clone();
+        let thread = thread::spawn(move || {
+            run(source, init, command_receiver, message_sender, flag).map_err(|e| e.to_string())
+        });
+        Worker {
+            commands,
+            messages,
+            terminated,
+            thread: Some(thread),
+        }
+    }
+
+    /// Send a message to the worker, it is delivered to the `onmessage` handler of the worker.
+    ///
+    /// Returns an exception if the value can not be serialized. Messages sent after the worker
+    /// has finished are discarded.
+    pub fn post_message(&self, value: Value) -> Result<()> {
+        let message = write_message(&value)?;
+        self.commands.send(Command::Message(message)).ok();
+        Ok(())
+    }
+
+    /// Wait for the next message posted by the worker.
+    ///
+    /// Returns `None` once the worker has finished and all of its messages have been received.
+    pub fn receive<'js>(&self, ctx: &Ctx<'js>) -> Result<Option<Value<'js>>> {
+        match self.messages.recv() {
+            Ok(message) => read_message(ctx, &message).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Wait for the next message posted by the worker for at most the given duration.
+    pub fn receive_timeout<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        timeout: Duration,
+    ) -> Result<Option<Value<'js>>> {
+        match self.messages.recv_timeout(timeout) {
+            Ok(message) => read_message(ctx, &message).map(Some),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+
+    /// Returns the next message posted by the worker if there is one.
+    pub fn try_receive<'js>(&self, ctx: &Ctx<'js>) -> Result<Option<Value<'js>>> {
+        match self.messages.try_recv() {
+            Ok(message) => read_message(ctx, &message).map(Some),
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => Ok(None),
+        }
+    }
+
+    /// Stop the worker.
+    ///
+    /// A running script is interrupted and no further messages are delivered to the worker.
+    pub fn terminate(&self) {
+        self.terminated.store(true, Ordering::SeqCst);
+        self.commands.send(Command::Terminate).ok();
+    }
+
+    /// Returns whether the worker thread has finished.
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .map(|thread| thread.is_finished())
+            .unwrap_or(true)
+    }
+
+    /// Wait for the worker to finish.
+    ///
+    /// Returns the message of the uncaught exception which stopped the worker, if any. A worker
+    /// stops once it calls `close()`, is terminated or the worker handle is dropped, so this
+    /// method has to be preceded by one of those to not block forever.
+    pub fn join(mut self) -> StdResult<(), StdString> {
+        self.thread
+            .take()
+            .map(|thread| thread.join().unwrap_or_else(|_| Err("panicked".into())))
+            .unwrap_or(Ok(()))
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.terminate();
+            thread.join().ok();
+        }
+    }
+}
+
+#[cfg(not(feature = "shared-array-buffer"))]
+fn write_message(value: &Value) -> Result<Message> {
+    let ctx = value.ctx();
+    let bytes = unsafe { write_object(ctx, value.as_js_value(), 0)? };
+    Ok(Message { bytes })
+}
+
+/// Serialize a message, shared array buffers are shared with the receiver instead of copied.
+#[cfg(feature = "shared-array-buffer")]
+fn write_message(value: &Value) -> Result<Message> {
+    let ctx = value.ctx();
+    let mut len = MaybeUninit::uninit();
+    let mut tab = MaybeUninit::uninit();
+    let mut tab_len = MaybeUninit::uninit();
+    unsafe {
+        let buf = qjs::JS_WriteObject2(
+            ctx.as_ptr(),
+            len.as_mut_ptr(),
+            value.as_js_value(),
+            qjs::JS_WRITE_OBJ_SAB as _,
+            tab.as_mut_ptr(),
+            tab_len.as_mut_ptr(),
+        );
+        if buf.is_null() {
+            return Err(ctx.raise_exception());
+        }
+        let bytes = slice::from_raw_parts(buf, len.assume_init() as _).to_vec();
+        qjs::js_free(ctx.as_ptr(), buf as _);
+        let tab = tab.assume_init();
+        let mut memory = Vec::new();
+        if !tab.is_null() {
+            for ptr in slice::from_raw_parts(tab, tab_len.assume_init() as _) {
+                memory.push(SharedMemory::clone_raw(*ptr as _));
+            }
+            qjs::js_free(ctx.as_ptr(), tab as _);
+        }
+        Ok(Message {
+            bytes,
+            _memory: memory,
+        })
+    }
+}
+
+fn read_message<'js>(ctx: &Ctx<'js>, message: &Message) -> Result<Value<'js>> {
+    #[cfg(not(feature = "shared-array-buffer"))]
+    let flags = 0;
+    #[cfg(feature = "shared-array-buffer")]
+    let flags = qjs::JS_READ_OBJ_SAB as _;
+    unsafe {
+        let value = read_object(ctx, &message.bytes, flags)?;
+        Ok(Value::from_js_value(ctx.clone(), value))
+    }
+}
+
+/// Returns the uncaught exception of a context as an error message.
+fn caught(ctx: &Ctx, error: Error) -> StdString {
+    CaughtError::from_error(ctx, error).to_string()
+}
+
+fn run<F>(
+    source: Vec<u8>,
+    init: F,
+    commands: Receiver<Command>,
+    messages: Sender<Message>,
+    terminated: Arc<AtomicBool>,
+) -> StdResult<(), StdString>
+where
+    F: FnOnce(&Ctx) -> Result<()>,
+{
+    let rt = Runtime::new().map_err(|e| e.to_string())?;
+    let flag = terminated.clone();
+    rt.set_interrupt_handler(Some(Box::new(move || flag.load(Ordering::SeqCst))));
+    let context = Context::full(&rt).map_err(|e| e.to_string())?;
+    let closed = Arc::new(AtomicBool::new(false));
+
+    context.with(|ctx| {
+        let globals = ctx.globals();
+        let setup = || -> Result<()> {
+            let post_message = Function::new(ctx.clone(), move |value: Value| {
+                messages.send(write_message(&value)?).ok();
+                Ok::<_, Error>(())
+            })?;
+            globals.set("postMessage", post_message.with_name("postMessage")?)?;
+            let flag = closed.clone();
+            let close = Function::new(ctx.clone(), move || flag.store(true, Ordering::SeqCst))?;
+            globals.set("close", close.with_name("close")?)?;
+            globals.set("self", globals.clone())?;
+            globals.set("onmessage", Null)?;
+            init(&ctx)?;
+            ctx.eval::<(), _>(source)
+        };
+        setup().map_err(|e| caught(&ctx, e))
+    })?;
+
+    loop {
+        loop {
+            match rt.execute_pending_job() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err(e.0.with(|ctx| caught(&ctx, Error::Exception))),
+            }
+        }
+        if closed.load(Ordering::SeqCst) || terminated.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let message = match commands.recv() {
+            Ok(Command::Message(message)) => message,
+            Ok(Command::Terminate) | Err(_) => return Ok(()),
+        };
+        context.with(|ctx| {
+            let dispatch = || -> Result<()> {
+                let handler: Option<Function> = ctx.globals().get("onmessage")?;
+                let Some(handler) = handler else {
+                    return Ok(());
+                };
+                let event = Object::new(ctx.clone())?;
+                event.set("data", read_message(&ctx, &message)?)?;
+                handler.call((event,))
+            };
+            dispatch().map_err(|e| caught(&ctx, e))
+        })?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{test_with, worker::Worker, StdString};
+
+    #[test]
+    fn messages() {
+        let worker = Worker::spawn(
+            r#"
+            let total = 0;
+            postMessage("ready");
+            onmessage = ({ data }) => {
+                total += data.value;
+                if (data.last) {
+                    postMessage({ total, names: data.names });
+                    close();
+                }
+            };
+        "#,
+        );
+        test_with(|ctx| {
+            let ready: StdString = worker.receive(&ctx).unwrap().unwrap().get().unwrap();
+            assert_eq!(ready, "ready");
+            for i in 1..=3 {
+                let message = ctx
+                    .eval(format!(
+                        "({{ value: {}, last: {}, names: ['a', 'b'] }})",
+                        i,
+                        i == 3
+                    ))
+                    .unwrap();
+                worker.post_message(message).unwrap();
+            }
+            let res: crate::Object = worker.receive(&ctx).unwrap().unwrap().get().unwrap();
+            assert_eq!(res.get::<_, i32>("total").unwrap(), 6);
+            assert_eq!(res.get::<_, Vec<StdString>>("names").unwrap(), ["a", "b"]);
+            assert!(worker.receive(&ctx).unwrap().is_none());
+            let func = ctx.eval("() => 1").unwrap();
+            assert!(worker.post_message(func).is_err());
+        });
+        assert_eq!(worker.join(), Ok(()));
+    }
+
+    #[test]
+    fn terminate() {
+        let worker = Worker::spawn("postMessage(1); while (true) {}");
+        test_with(|ctx| {
+            let res: i32 = worker.receive(&ctx).unwrap().unwrap().get().unwrap();
+            assert_eq!(res, 1);
+            assert!(worker
+                .receive_timeout(&ctx, Duration::from_millis(10))
+                .unwrap()
+                .is_none());
+        });
+        worker.terminate();
+        assert!(worker.join().is_err());
+    }
+
+    #[cfg(feature = "shared-array-buffer")]
+    #[test]
+    fn shared_memory() {
+        let worker = Worker::spawn(
+            r#"
+            onmessage = ({ data }) => {
+                Atomics.store(new Int32Array(data), 0, 42);
+                postMessage(data);
+            };
+        "#,
+        );
+        test_with(|ctx| {
+            let buffer = crate::SharedArrayBuffer::new(ctx.clone(), 4).unwrap();
+            worker.post_message(buffer.clone().into_value()).unwrap();
+            let res: crate::SharedArrayBuffer =
+                worker.receive(&ctx).unwrap().unwrap().get().unwrap();
+            assert!(res.memory().unwrap().ptr_eq(&buffer.memory().unwrap()));
+            let res: i32 = ctx
+                .globals()
+                .set("buffer", buffer)
+                .and_then(|_| ctx.eval("Atomics.load(new Int32Array(buffer), 0)"))
+                .unwrap();
+            assert_eq!(res, 42);
+        });
+    }
+
+    #[test]
+    fn errors() {
+        let worker = Worker::spawn_with("onmessage = () => { throw new Error(greeting) }", |ctx| {
+            ctx.globals().set("greeting", "hello")
+        });
+        test_with(|ctx| {
+            worker.post_message(ctx.eval("0").unwrap()).unwrap();
+            assert!(worker.receive(&ctx).unwrap().is_none());
+        });
+        let error = worker.join().unwrap_err();
+        assert!(error.contains("hello"), "{}", error);
+    }
+}
@@ -0,0 +1,5 @@
+import { double } from './util/double';
+
+export function quadruple(x){
+  return double(double(x))
+}
@@ -17,6 +17,9 @@ use crate::{
 #[derive(Debug, Default, Clone)]
 pub(crate) struct ClassConfig {
     pub frozen: bool,
+    pub extensible: Option<bool>,
+    pub exotic: bool,
+    pub no_constructor: bool,
     pub crate_: Option<String>,
     pub rename: Option<String>,
     pub rename_all: Option<Case>,
@@ -24,6 +27,9 @@ pub(crate) struct ClassConfig {
 
 pub(crate) enum ClassOption {
     Frozen(FlagOption<kw::frozen>),
+    Extensible(FlagOption<kw::extensible>),
+    Exotic(FlagOption<kw::exotic>),
+    NoConstructor(FlagOption<kw::no_constructor>),
     Crate(ValueOption<Token![crate], LitStr>),
     Rename(ValueOption<kw::rename, LitStr>),
     RenameAll(ValueOption<kw::rename_all, Case>),
@@ -33,6 +39,12 @@ impl Parse for ClassOption {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.peek(kw::frozen) {
             input.parse().map(Self::Frozen)
+        } else if input.peek(kw::extensible) {
+            input.parse().map(Self::Extensible)
+        } else if input.peek(kw::exotic) {
+            input.parse().map(Self::Exotic)
+        } else if input.peek(kw::no_constructor) {
+            input.parse().map(Self::NoConstructor)
         } else if input.peek(Token![crate]) {
             input.parse().map(Self::Crate)
         } else if input.peek(kw::rename) {
@@ -51,6 +63,15 @@ impl ClassConfig {
             ClassOption::Frozen(ref x) => {
                 self.frozen = x.is_true();
             }
+            ClassOption::Extensible(ref x) => {
+                self.extensible = Some(x.is_true());
+            }
+            ClassOption::Exotic(ref x) => {
+                self.exotic = x.is_true();
+            }
+            ClassOption::NoConstructor(ref x) => {
+                self.no_constructor = x.is_true();
+            }
             ClassOption::Crate(ref x) => {
                 self.crate_ = Some(x.value.value());
             }
@@ -66,6 +87,47 @@ impl ClassConfig {
     pub fn crate_name(&self) -> String {
         self.crate_.clone().unwrap_or_else(crate_ident)
     }
+
+    /// Instances of frozen classes are not extensible unless explicitly made extensible.
+    pub fn is_extensible(&self) -> bool {
+        self.extensible.unwrap_or(!self.frozen)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct VariantConfig {
+    pub skip: bool,
+    pub rename: Option<String>,
+}
+
+pub(crate) enum VariantOption {
+    Skip(FlagOption<kw::skip>),
+    Rename(ValueOption<kw::rename, LitStr>),
+}
+
+impl Parse for VariantOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::skip) {
+            input.parse().map(Self::Skip)
+        } else if input.peek(kw::rename) {
+            input.parse().map(Self::Rename)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid variant attribute"))
+        }
+    }
+}
+
+impl VariantConfig {
+    pub fn apply(&mut self, option: &VariantOption) {
+        match option {
+            VariantOption::Skip(ref x) => {
+                self.skip = x.is_true();
+            }
+            VariantOption::Rename(ref x) => {
+                self.rename = Some(x.value.value());
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -78,6 +140,7 @@ pub(crate) enum Class {
         ident: Ident,
         generics: syn::Generics,
         variants: syn::punctuated::Punctuated<syn::Variant, Token![,]>,
+        variant_configs: Vec<VariantConfig>,
     },
     Struct {
         config: ClassConfig,
@@ -138,11 +201,27 @@ impl Class {
             ..
         } = enum_;
 
+        let mut variant_configs = Vec::new();
+        let mut fold_variant = |mut v: syn::Variant| {
+            let mut config = VariantConfig::default();
+            take_attributes(&mut v.attrs, |attr| {
+                if !attr.path().is_ident("qjs") {
+                    return Ok(false);
+                }
+
+                let options: OptionList<VariantOption> = attr.parse_args()?;
+                options.0.iter().for_each(|x| config.apply(x));
+                Ok(true)
+            })
+            .unwrap_or_abort();
+            variant_configs.push(config);
+            ErrorAttribute.fold_variant(v)
+        };
         let variants = variants
             .into_pairs()
             .map(|x| match x {
-                Pair::Punctuated(v, c) => Pair::Punctuated(ErrorAttribute.fold_variant(v), c),
-                Pair::End(v) => Pair::End(ErrorAttribute.fold_variant(v)),
+                Pair::Punctuated(v, c) => Pair::Punctuated(fold_variant(v), c),
+                Pair::End(v) => Pair::End(fold_variant(v)),
             })
             .collect();
 
@@ -167,6 +246,7 @@ impl Class {
             ident,
             generics,
             variants,
+            variant_configs,
         }
     }
 
@@ -256,6 +336,108 @@ impl Class {
         }
     }
 
+    /// The `tag` accessor of enums which returns the name of the variant.
+    pub fn expand_tag(&self, crate_name: &Ident) -> TokenStream {
+        let Class::Enum {
+            ref variants,
+            ref variant_configs,
+            ..
+        } = self
+        else {
+            return TokenStream::new();
+        };
+
+        let arms = variants
+            .iter()
+            .zip(variant_configs)
+            .map(|(variant, config)| {
+                let ident = &variant.ident;
+                let name = config.rename.clone().unwrap_or_else(|| ident.to_string());
+                quote!(Self::#ident { .. } => #name,)
+            });
+
+        quote! {
+            proto.prop("tag",
+                #crate_name::object::Accessor::new_get(
+                    |this: #crate_name::function::This<#crate_name::class::Class<'js, Self>>| -> #crate_name::Result<&'static str> {
+                        Ok(match &*this.0.try_borrow()? {
+                            #(#arms)*
+                        })
+                    }
+                )
+                .configurable()
+            )?;
+        }
+    }
+
+    /// The static factory functions of enums which create an instance of a variant.
+    pub fn expand_factories(&self, crate_name: &Ident) -> TokenStream {
+        let Class::Enum {
+            ref variants,
+            ref variant_configs,
+            ..
+        } = self
+        else {
+            return TokenStream::new();
+        };
+
+        let factories = variants
+            .iter()
+            .zip(variant_configs)
+            .filter(|(_, config)| !config.skip)
+            .map(|(variant, config)| {
+                let ident = &variant.ident;
+                let name = config.rename.clone().unwrap_or_else(|| ident.to_string());
+                let (args, body) = match variant.fields {
+                    syn::Fields::Named(ref fields) => {
+                        let names = fields.named.iter().map(|x| x.ident.clone().unwrap());
+                        let types = fields.named.iter().map(|x| &x.ty);
+                        let args = quote!(#(#names: #types),*);
+                        let names = fields.named.iter().map(|x| x.ident.clone().unwrap());
+                        (args, quote!(Self::#ident { #(#names),* }))
+                    }
+                    syn::Fields::Unnamed(ref fields) => {
+                        let names = (0..fields.unnamed.len())
+                            .map(|idx| format_ident!("_{}", idx))
+                            .collect::<Vec<_>>();
+                        let types = fields.unnamed.iter().map(|x| &x.ty);
+                        let args = quote!(#(#names: #types),*);
+                        (args, quote!(Self::#ident(#(#names),*)))
+                    }
+                    syn::Fields::Unit => (TokenStream::new(), quote!(Self::#ident)),
+                };
+                quote! {
+                    constr.prop(#name,
+                        #crate_name::object::Property::from(
+                            #crate_name::Function::new(ctx.clone(), |#args| -> Self { #body })?
+                                .with_name(#name)?
+                        )
+                        .writable()
+                        .configurable()
+                    )?;
+                }
+            });
+
+        let javascript_name = self.javascript_name();
+        let message = format!(
+            "{} can't be constructed directly, use one of the variant factories like `{}.Variant()`",
+            javascript_name, javascript_name
+        );
+        quote! {
+            let constr = match (&implementor).create_constructor(ctx)? {
+                Some(x) => x,
+                None => #crate_name::function::Constructor::new_class::<Self, _, _>(
+                    ctx.clone(),
+                    |ctx: #crate_name::Ctx<'js>| -> #crate_name::Result<Self> {
+                        Err(#crate_name::Exception::throw_type(&ctx, #message))
+                    },
+                )?,
+            };
+            #(#factories)*
+            Ok(Some(constr))
+        }
+    }
+
     // Aeexpand the original definition with the attributes removed..
     pub fn reexpand(&self) -> TokenStream {
         match self {
@@ -318,7 +500,44 @@ impl Class {
         let generics_with_lifetimes = add_js_lifetime(&generics);
 
         let mutability = self.mutability();
+        let extensible = if self.config().is_extensible() {
+            TokenStream::new()
+        } else {
+            quote!(
+                const EXTENSIBLE: bool = false;
+            )
+        };
+        let exotic = if self.config().exotic {
+            quote! {
+                fn exotic() -> Option<#crate_name::class::ExoticMethods>{
+                    Some(#crate_name::class::ExoticMethods::new::<Self>())
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
         let props = self.expand_props(&crate_name);
+        let tag = self.expand_tag(&crate_name);
+        let create_constructor = if matches!(self, Class::Enum { .. }) {
+            self.expand_factories(&crate_name)
+        } else if self.config().no_constructor {
+            // Still define a constructor, so the class can be referred to from JavaScript.
+            let message = format!("{} can't be constructed directly", self.javascript_name());
+            quote! {
+                match (&implementor).create_constructor(ctx)? {
+                    Some(x) => Ok(Some(x)),
+                    None => #crate_name::function::Constructor::new_class::<Self, _, _>(
+                        ctx.clone(),
+                        |ctx: #crate_name::Ctx<'js>| -> #crate_name::Result<Self> {
+                            Err(#crate_name::Exception::throw_type(&ctx, #message))
+                        },
+                    )
+                    .map(Some),
+                }
+            }
+        } else {
+            quote!((&implementor).create_constructor(ctx))
+        };
         let reexpand = self.reexpand();
 
         quote! {
@@ -333,6 +552,8 @@ impl Class {
 
                     type Mutable = #crate_name::class::#mutability;
 
+                    #extensible
+
                     fn class_id() -> &'static #crate_name::class::ClassId{
                         static ID: #crate_name::class::ClassId =  #crate_name::class::ClassId::new();
                         &ID
@@ -343,6 +564,7 @@ impl Class {
 
                         let proto = #crate_name::Object::new(ctx.clone())?;
                         #props
+                        #tag
                         let implementor = #crate_name::class::impl_::MethodImpl::<Self>::new();
                         (&implementor).implement(&proto)?;
                         Ok(Some(proto))
@@ -352,7 +574,16 @@ impl Class {
                         use #crate_name::class::impl_::ConstructorCreator;
 
                         let implementor = #crate_name::class::impl_::ConstructorCreate::<Self>::new();
-                        (&implementor).create_constructor(ctx)
+                        #create_constructor
+                    }
+
+                    #exotic
+
+                    fn finalize(&mut self){
+                        use #crate_name::class::impl_::FinalizeImplementor;
+
+                        let implementor = #crate_name::class::impl_::FinalizeImpl::<Self>::new();
+                        (&implementor).finalize(self)
                     }
                 }
 
@@ -143,6 +143,7 @@ impl<'a> Fold for SelfReplacer<'a> {
 
 pub(crate) mod kw {
     syn::custom_keyword!(frozen);
+    syn::custom_keyword!(extensible);
     syn::custom_keyword!(skip_trace);
     syn::custom_keyword!(rename);
     syn::custom_keyword!(rename_all);
@@ -154,7 +155,21 @@ pub(crate) mod kw {
     syn::custom_keyword!(skip);
     syn::custom_keyword!(configurable);
     syn::custom_keyword!(enumerable);
+    syn::custom_keyword!(writable);
     syn::custom_keyword!(prefix);
     syn::custom_keyword!(declare);
     syn::custom_keyword!(evaluate);
+    syn::custom_keyword!(finalize);
+    syn::custom_keyword!(exotic);
+    syn::custom_keyword!(code);
+    syn::custom_keyword!(cause);
+    syn::custom_keyword!(tag);
+    syn::custom_keyword!(untagged);
+    syn::custom_keyword!(default);
+    syn::custom_keyword!(range);
+    syn::custom_keyword!(length);
+    syn::custom_keyword!(options);
+    syn::custom_keyword!(factory);
+    syn::custom_keyword!(no_constructor);
+    syn::custom_keyword!(null);
 }
@@ -1,10 +1,16 @@
-use std::{env, path::Path};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use crate::common::crate_ident;
 use proc_macro2::TokenStream;
 use proc_macro_error::abort;
 use quote::{format_ident, quote};
-use rquickjs_core::{Context, Module, Result, Runtime};
+use rquickjs_core::{
+    loader::{BuiltinLoader, BuiltinResolver},
+    Context, Module, Result, Runtime,
+};
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
@@ -74,6 +80,11 @@ pub fn embed(modules: EmbedModules) -> TokenStream {
             path.to_owned()
         };
 
+        if path.is_dir() {
+            embed_dir(&f.name, &f.name.value(), &path, &mut files);
+            continue;
+        }
+
         let source = match std::fs::read_to_string(&path) {
             Ok(x) => x,
             Err(e) => {
@@ -90,6 +101,14 @@ pub fn embed(modules: EmbedModules) -> TokenStream {
 
     let res = (|| -> Result<Vec<(String, Vec<u8>)>> {
         let rt = Runtime::new()?;
+        // Imports are resolved during compilation so the embedded modules must be loadable.
+        let mut resolver = BuiltinResolver::default();
+        let mut loader = BuiltinLoader::default();
+        for (name, source) in files.iter() {
+            resolver.add_module(name.clone());
+            loader.add_module(name.clone(), source.clone());
+        }
+        rt.set_loader(resolver, loader);
         let ctx = Context::full(&rt)?;
 
         let mut modules = Vec::new();
@@ -118,6 +137,67 @@ pub fn embed(modules: EmbedModules) -> TokenStream {
     expand(&res)
 }
 
+/// Collect all the `.js` and `.mjs` files in a directory, named by their path relative to the
+/// directory without the extension and prefixed with the given name.
+fn embed_dir(name: &LitStr, prefix: &str, dir: &Path, files: &mut Vec<(String, String)>) {
+    let entries = dir.read_dir().and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    });
+    let mut entries: Vec<PathBuf> = match entries {
+        Ok(x) => x,
+        Err(e) => {
+            abort!(
+                name,
+                "Error reading embedded js directory `{}`: {}",
+                dir.display(),
+                e
+            );
+        }
+    };
+    entries.sort();
+
+    for path in entries {
+        let (Some(stem), Some(file_name)) = (
+            path.file_stem().and_then(|x| x.to_str()),
+            path.file_name().and_then(|x| x.to_str()),
+        ) else {
+            continue;
+        };
+        let join = |x: &str| {
+            if prefix.is_empty() {
+                x.to_owned()
+            } else {
+                format!("{prefix}/{x}")
+            }
+        };
+
+        if path.is_dir() {
+            embed_dir(name, &join(file_name), &path, files);
+            continue;
+        }
+        if !matches!(
+            path.extension().and_then(|x| x.to_str()),
+            Some("js" | "mjs")
+        ) {
+            continue;
+        }
+
+        match std::fs::read_to_string(&path) {
+            Ok(source) => files.push((join(stem), source)),
+            Err(e) => {
+                abort!(
+                    name,
+                    "Error loading embedded js module from path `{}`: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
 fn to_entries(modules: impl Iterator<Item = (String, Vec<u8>)>) -> Vec<(String, TokenStream)> {
     modules
         .map(|(name, data)| (name, quote! { &[#(#data),*] }))
@@ -0,0 +1,249 @@
+use convert_case::Casing;
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, LitStr,
+};
+
+use crate::{
+    attrs::{FlagOption, OptionList, ValueOption},
+    common::{add_js_lifetime, crate_ident, kw, AbortResultExt, Case},
+};
+
+#[derive(Default)]
+pub(crate) struct ConvertConfig {
+    pub tag: Option<String>,
+    pub untagged: bool,
+    pub rename: Option<String>,
+    pub rename_all: Option<Case>,
+}
+
+pub(crate) enum ConvertOption {
+    Tag(ValueOption<kw::tag, LitStr>),
+    Untagged(FlagOption<kw::untagged>),
+    Rename(ValueOption<kw::rename, LitStr>),
+    RenameAll(ValueOption<kw::rename_all, Case>),
+}
+
+impl Parse for ConvertOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::tag) {
+            input.parse().map(Self::Tag)
+        } else if input.peek(kw::untagged) {
+            input.parse().map(Self::Untagged)
+        } else if input.peek(kw::rename_all) {
+            input.parse().map(Self::RenameAll)
+        } else if input.peek(kw::rename) {
+            input.parse().map(Self::Rename)
+        } else {
+            Err(syn::Error::new(
+                input.span(),
+                "invalid conversion attribute",
+            ))
+        }
+    }
+}
+
+impl ConvertConfig {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Self {
+        let mut config = ConvertConfig::default();
+        for attr in attrs.iter().filter(|x| x.path().is_ident("qjs")) {
+            let options: OptionList<ConvertOption> = attr.parse_args().unwrap_or_abort();
+            for option in options.0 {
+                match option {
+                    ConvertOption::Tag(x) => config.tag = Some(x.value.value()),
+                    ConvertOption::Untagged(x) => config.untagged = x.is_true(),
+                    ConvertOption::Rename(x) => config.rename = Some(x.value.value()),
+                    ConvertOption::RenameAll(x) => config.rename_all = Some(x.value),
+                }
+            }
+        }
+        config
+    }
+
+    /// The name of a field or variant, renamed by its own attribute or the parent's `rename_all`.
+    pub(crate) fn name(&self, ident: &Ident, rename_all: Option<Case>) -> String {
+        if let Some(name) = &self.rename {
+            return name.clone();
+        }
+        let ident = ident.to_string();
+        let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+        match rename_all {
+            Some(case) => ident.to_case(case.to_convert_case()),
+            None => ident.to_owned(),
+        }
+    }
+}
+
+/// Expands the conversion of `value` into the given fields, `path` is used to construct the value.
+fn expand_fields(
+    crate_name: &Ident,
+    path: TokenStream,
+    fields: &Fields,
+    rename_all: Option<Case>,
+) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let fields = fields.named.iter().map(|field| {
+                let ident = field.ident.as_ref().unwrap();
+                let name = ConvertConfig::from_attrs(&field.attrs).name(ident, rename_all);
+                quote!(#ident: object.get(#name)?,)
+            });
+            quote! {{
+                let object = <#crate_name::Object as #crate_name::FromJs>::from_js(ctx, value)?;
+                ::std::result::Result::Ok(#path { #(#fields)* })
+            }}
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            quote!(#crate_name::FromJs::from_js(ctx, value).map(#path))
+        }
+        Fields::Unnamed(fields) => {
+            let len = fields.unnamed.len();
+            let fields = (0..len).map(|idx| quote!(array.get(#idx)?,));
+            quote! {{
+                let array = <#crate_name::Array as #crate_name::FromJs>::from_js(ctx, value)?;
+                if array.len() != #len {
+                    return ::std::result::Result::Err(#crate_name::Error::new_from_js_message(
+                        "array",
+                        "tuple",
+                        ::std::format!("Expected {} values", #len),
+                    ));
+                }
+                ::std::result::Result::Ok(#path(#(#fields)*))
+            }}
+        }
+        Fields::Unit => quote!({
+            let _ = value;
+            ::std::result::Result::Ok(#path)
+        }),
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+    let crate_name = format_ident!("{}", crate_ident());
+    let config = ConvertConfig::from_attrs(&attrs);
+    let type_name = ident.to_string();
+
+    let mut impl_generics = add_js_lifetime(&generics);
+    let js_lifetime = syn::Lifetime::new("'js", Span::call_site());
+    let type_params = generics
+        .type_params()
+        .map(|x| x.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = impl_generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: #crate_name::FromJs<#js_lifetime>));
+    }
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    let body = match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            if config.tag.is_some() || config.untagged {
+                abort!(ident, "`tag` and `untagged` can only be used on enums");
+            }
+            expand_fields(&crate_name, quote!(Self), &fields, config.rename_all)
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            if config.tag.is_some() && config.untagged {
+                abort!(ident, "an enum can't be both tagged and untagged");
+            }
+            if config.untagged {
+                let attempts = variants.iter().map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let variant_config = ConvertConfig::from_attrs(&variant.attrs);
+                    let convert = match &variant.fields {
+                        // Unit variants match nullish values.
+                        Fields::Unit => quote! {
+                            if value.type_of().is_void() {
+                                ::std::result::Result::Ok(Self::#variant_ident)
+                            } else {
+                                ::std::result::Result::Err(#crate_name::Error::new_from_js(
+                                    value.type_name(),
+                                    #type_name,
+                                ))
+                            }
+                        },
+                        fields => expand_fields(
+                            &crate_name,
+                            quote!(Self::#variant_ident),
+                            fields,
+                            variant_config.rename_all,
+                        ),
+                    };
+                    quote! {
+                        let attempt = (|value: #crate_name::Value<'js>| -> #crate_name::Result<Self> {
+                            #convert
+                        })(value.clone());
+                        match attempt {
+                            ::std::result::Result::Err(error) if error.is_from_js() => {}
+                            res => return res,
+                        }
+                    }
+                });
+                quote! {{
+                    #(#attempts)*
+                    ::std::result::Result::Err(#crate_name::Error::new_from_js_message(
+                        value.type_name(),
+                        #type_name,
+                        "Value didn't match any variant",
+                    ))
+                }}
+            } else {
+                let tag = config.tag.clone().unwrap_or_else(|| "type".to_owned());
+                let arms = variants.iter().map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let variant_config = ConvertConfig::from_attrs(&variant.attrs);
+                    let name = variant_config.name(variant_ident, config.rename_all);
+                    let convert = expand_fields(
+                        &crate_name,
+                        quote!(Self::#variant_ident),
+                        &variant.fields,
+                        variant_config.rename_all,
+                    );
+                    quote!(#name => #convert,)
+                });
+                quote! {{
+                    let object = <#crate_name::Object as #crate_name::FromJs>::from_js(ctx, value.clone())?;
+                    let tag: ::std::option::Option<::std::string::String> = object.get(#tag)?;
+                    let tag = tag.ok_or_else(|| {
+                        #crate_name::Error::new_from_js_message(
+                            "object",
+                            #type_name,
+                            ::std::concat!("Missing the `", #tag, "` property"),
+                        )
+                    })?;
+                    match tag.as_str() {
+                        #(#arms)*
+                        tag => ::std::result::Result::Err(#crate_name::Error::new_from_js_message(
+                            "object",
+                            #type_name,
+                            ::std::format!(::std::concat!("Unknown `", #tag, "` `{}`"), tag),
+                        )),
+                    }
+                }}
+            }
+        }
+        Data::Union(_) => abort!(ident, "FromJs can't be derived for unions"),
+    };
+
+    quote! {
+        impl #impl_generics #crate_name::FromJs<'js> for #ident #ty_generics #where_clause {
+            fn from_js(ctx: &#crate_name::Ctx<'js>, value: #crate_name::Value<'js>) -> #crate_name::Result<Self> {
+                let _ = ctx;
+                #body
+            }
+        }
+    }
+}
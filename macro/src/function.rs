@@ -7,11 +7,11 @@ use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token::Comma,
-    FnArg, LitStr, Signature, Token, Type, Visibility,
+    Attribute, Expr, FnArg, LitStr, Pat, Signature, Token, Type, Visibility,
 };
 
 use crate::{
-    attrs::{take_attributes, OptionList, ValueOption},
+    attrs::{take_attributes, FlagOption, OptionList, ValueOption},
     common::{crate_ident, kw, AbortResultExt, Case, SelfReplacer, BASE_PREFIX},
 };
 
@@ -20,12 +20,14 @@ pub(crate) struct FunctionConfig {
     pub crate_: Option<String>,
     pub prefix: Option<String>,
     pub rename: Option<String>,
+    pub null: bool,
 }
 
 pub(crate) enum FunctionOption {
     Prefix(ValueOption<kw::prefix, LitStr>),
     Crate(ValueOption<Token![crate], LitStr>),
     Rename(ValueOption<kw::rename, LitStr>),
+    Null(FlagOption<kw::null>),
 }
 
 impl Parse for FunctionOption {
@@ -36,6 +38,8 @@ impl Parse for FunctionOption {
             input.parse().map(Self::Prefix)
         } else if input.peek(kw::rename) {
             input.parse().map(Self::Rename)
+        } else if input.peek(kw::null) {
+            input.parse().map(Self::Null)
         } else {
             Err(syn::Error::new(input.span(), "invalid class attribute"))
         }
@@ -54,6 +58,9 @@ impl FunctionConfig {
             FunctionOption::Prefix(ref x) => {
                 self.prefix = Some(x.value.value());
             }
+            FunctionOption::Null(ref x) => {
+                self.null = x.is_true();
+            }
         }
     }
 
@@ -106,7 +113,8 @@ pub(crate) fn expand(options: OptionList<FunctionOption>, mut item: syn::ItemFn)
     let crate_name = format_ident!("{}", config.crate_name());
     let prefix = config.prefix.as_deref().unwrap_or(BASE_PREFIX);
 
-    let func = JsFunction::new(item.vis.clone(), &item.sig, None);
+    let mut func = JsFunction::new(item.vis.clone(), &mut item.sig, None);
+    func.null = config.null;
 
     let carry_type = func.expand_carry_type(prefix);
     let impl_ = func.expand_to_js_function_impl(prefix, &crate_name);
@@ -131,17 +139,19 @@ pub(crate) struct JsFunction {
     pub rust_function: TokenStream,
     pub is_async: bool,
     pub params: JsParams,
+    /// Whether results converting into `undefined` are returned as `null`.
+    pub null: bool,
 }
 
 impl JsFunction {
-    pub fn new(vis: Visibility, sig: &Signature, self_type: Option<&Type>) -> Self {
+    pub fn new(vis: Visibility, sig: &mut Signature, self_type: Option<&Type>) -> Self {
         let Signature {
             ref asyncness,
             ref unsafety,
             ref abi,
             ref variadic,
             ref ident,
-            ref inputs,
+            ref mut inputs,
             ..
         } = sig;
 
@@ -176,6 +186,7 @@ impl JsFunction {
             is_async,
             rust_function,
             params,
+            null: false,
         }
     }
 
@@ -208,21 +219,30 @@ impl JsFunction {
         let arg_extract = self.params.expand_extract(lib_crate);
         let arg_apply = self.params.expand_apply();
         let rust_function = &self.rust_function;
+        let wrap_result = |res: TokenStream| {
+            if self.null {
+                quote!(#lib_crate::function::UndefinedAsNull(#res))
+            } else {
+                res
+            }
+        };
 
         if self.is_async {
+            let res = wrap_result(quote!(#rust_function(#arg_apply).await));
             quote! {
                 #arg_extract
 
                 let fut = async move {
-                    #rust_function(#arg_apply).await
+                    #res
                 };
 
                 #lib_crate::IntoJs::into_js(#lib_crate::promise::Promised(fut), &ctx)
             }
         } else {
+            let res = wrap_result(quote!(#rust_function(#arg_apply)));
             quote! {
                 #arg_extract
-                let res = #rust_function(#arg_apply);
+                let res = #res;
                 #lib_crate::IntoJs::into_js(res,&ctx)
             }
         }
@@ -278,11 +298,107 @@ impl JsParams {
     }
 
     pub fn expand_extract(&self, lib_crate: &Ident) -> TokenStream {
-        let res = self.params.iter().map(|x| x.expand_extract(lib_crate));
-        quote!(#(#res)*)
+        // A trailing options object is taken first, before other parameters like rest parameters
+        // consume it.
+        let options = self
+            .params
+            .iter()
+            .position(|x| x.config.options)
+            .map(|idx| {
+                let preceding = self.params[..idx].iter().map(|x| {
+                    let ty = x.expand_type(lib_crate);
+                    quote!(.combine(<#ty as #lib_crate::function::FromParam>::param_requirement()))
+                });
+                let required = quote! {
+                    #lib_crate::function::ParamRequirement::none() #(#preceding)* .min()
+                };
+                self.params[idx].expand_extract_options(lib_crate, required)
+            });
+        let res = self
+            .params
+            .iter()
+            .filter(|x| !x.config.options)
+            .map(|x| x.expand_extract(lib_crate));
+        quote!(#options #(#res)*)
+    }
+}
+
+pub(crate) enum ParamOption {
+    Default(Option<Expr>),
+    Range(ValueOption<kw::range, Expr>),
+    Length(ValueOption<kw::length, Expr>),
+    Options(FlagOption<kw::options>),
+}
+
+impl Parse for ParamOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::default) {
+            input.parse::<kw::default>()?;
+            let value = input
+                .peek(Token![=])
+                .then(|| {
+                    input.parse::<Token![=]>()?;
+                    input.parse()
+                })
+                .transpose()?;
+            Ok(Self::Default(value))
+        } else if input.peek(kw::range) {
+            input.parse().map(Self::Range)
+        } else if input.peek(kw::length) {
+            input.parse().map(Self::Length)
+        } else if input.peek(kw::options) {
+            input.parse().map(Self::Options)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid parameter attribute"))
+        }
     }
 }
 
+/// The options of a single function parameter.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParamConfig {
+    /// The value used when the argument is omitted, `Some(None)` for `Default::default()`.
+    pub default: Option<Option<Expr>>,
+    /// The range the value must be in.
+    pub range: Option<Expr>,
+    /// The range the length of the value must be in.
+    pub length: Option<Expr>,
+    /// Whether the parameter is taken from a trailing options object.
+    pub options: bool,
+}
+
+impl ParamConfig {
+    fn from_attrs(attrs: &mut Vec<Attribute>) -> Self {
+        let mut config = ParamConfig::default();
+        take_attributes(attrs, |attr| {
+            if !attr.path().is_ident("qjs") {
+                return Ok(false);
+            }
+            let options: OptionList<ParamOption> = attr.parse_args()?;
+            for option in options.0 {
+                match option {
+                    ParamOption::Default(value) => config.default = Some(value),
+                    ParamOption::Range(x) => config.range = Some(x.value),
+                    ParamOption::Length(x) => config.length = Some(x.value),
+                    ParamOption::Options(x) => config.options = x.is_true(),
+                }
+            }
+            Ok(true)
+        })
+        .unwrap_or_abort();
+        config
+    }
+
+    fn is_empty(&self) -> bool {
+        self.default.is_none() && self.range.is_none() && self.length.is_none() && !self.options
+    }
+}
+
+/// Prints an expression without the spaces between tokens, `0 ..= 10` becomes `0..=10`.
+fn compact_tokens(expr: &Expr) -> String {
+    quote!(#expr).to_string().replace(' ', "")
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum ParamKind {
     Value,
@@ -296,6 +412,8 @@ pub(crate) struct JsParam {
     number: usize,
     tokens: TokenStream,
     is_this: bool,
+    name: String,
+    config: ParamConfig,
 }
 
 impl JsParam {
@@ -329,6 +447,11 @@ impl JsParam {
             ParamKind::Borrow => quote!(#lib_crate::class::OwnedBorrow<'js,#t>),
             ParamKind::BorrowMut => quote!(#lib_crate::class::OwnedBorrowMut<'js,#t>),
         };
+        let ty = if self.config.default.is_some() || self.config.options {
+            quote!(#lib_crate::function::Opt<#ty>)
+        } else {
+            ty
+        };
         if self.is_this {
             quote!(
                 #lib_crate::function::This<#ty>
@@ -341,21 +464,113 @@ impl JsParam {
     pub fn expand_extract(&self, lib_crate: &Ident) -> TokenStream {
         let ty = self.expand_type(lib_crate);
         let binding = self.expand_binding();
+        let checks = self.expand_checks(lib_crate);
         quote! {
             let #binding = <#ty as #lib_crate::function::FromParam>::from_param(&mut _params)?;
+            #checks
+        }
+    }
+
+    /// Expands the extraction of a trailing options object, `required` is the amount of
+    /// arguments the preceding parameters need.
+    pub fn expand_extract_options(&self, lib_crate: &Ident, required: TokenStream) -> TokenStream {
+        let binding = self.expand_binding();
+        let t = &self.tokens;
+        let checks = self.expand_checks(lib_crate);
+        quote! {
+            let #binding = #lib_crate::function::Opt(
+                match _params.trailing_options(#required) {
+                    ::std::option::Option::Some(value) => ::std::option::Option::Some(
+                        <#t as #lib_crate::FromJs>::from_js(&ctx, value)?,
+                    ),
+                    ::std::option::Option::None => ::std::option::Option::None,
+                },
+            );
+            #checks
+        }
+    }
+
+    /// Expands the default value and validators of the parameter.
+    fn expand_checks(&self, lib_crate: &Ident) -> TokenStream {
+        let binding = self.expand_binding();
+        let tmp = format_ident!("tmp_{}", self.number);
+        let t = &self.tokens;
+        let name = &self.name;
+
+        let default = match self.config.default {
+            Some(ref default) => {
+                let default = default
+                    .as_ref()
+                    .map(|x| quote!(#x))
+                    .unwrap_or_else(|| quote!(::std::default::Default::default()));
+                Some(quote! {
+                    let #binding = #tmp.0.unwrap_or_else(|| #default);
+                })
+            }
+            // A missing options object is converted from undefined.
+            None if self.config.options => Some(quote! {
+                let #binding = match #tmp.0 {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => <#t as #lib_crate::FromJs>::from_js(
+                        &ctx,
+                        #lib_crate::Value::new_undefined(ctx.clone()),
+                    )?,
+                };
+            }),
+            None => None,
+        };
+        let range = self.config.range.as_ref().map(|range| {
+            let message = format!(
+                "Argument `{}` must be in the range {}",
+                name,
+                compact_tokens(range)
+            );
+            quote! {
+                if !::std::ops::RangeBounds::<#t>::contains(&(#range), &#tmp) {
+                    return ::std::result::Result::Err(#lib_crate::Exception::throw_range(&ctx, #message));
+                }
+            }
+        });
+        let length = self.config.length.as_ref().map(|length| {
+            let message = format!(
+                "The length of argument `{}` must be in the range {}",
+                name,
+                compact_tokens(length)
+            );
+            quote! {
+                if !::std::ops::RangeBounds::<usize>::contains(&(#length), &#tmp.len()) {
+                    return ::std::result::Result::Err(#lib_crate::Exception::throw_range(&ctx, #message));
+                }
+            }
+        });
+
+        quote! {
+            #default
+            #range
+            #length
         }
     }
 }
 
 impl JsParams {
-    pub fn from_input(inputs: &Punctuated<FnArg, Comma>, self_type: Option<&Type>) -> Self {
+    pub fn from_input(inputs: &mut Punctuated<FnArg, Comma>, self_type: Option<&Type>) -> Self {
         let mut types = Vec::<JsParam>::new();
 
         let mut self_replacer = self_type.map(SelfReplacer::with);
 
-        for (idx, arg) in inputs.iter().enumerate() {
+        let len = inputs.len();
+
+        for (idx, arg) in inputs.iter_mut().enumerate() {
             match arg {
                 FnArg::Typed(pat) => {
+                    let config = ParamConfig::from_attrs(&mut pat.attrs);
+                    if config.options && idx + 1 != len {
+                        abort!(pat, "the options parameter must be the last parameter");
+                    }
+                    let name = match *pat.pat {
+                        Pat::Ident(ref x) => x.ident.to_string(),
+                        _ => idx.to_string(),
+                    };
                     let (stream, kind) = match *pat.ty {
                         Type::Reference(ref borrow) => {
                             let ty = (*borrow.elem).clone();
@@ -388,11 +603,20 @@ impl JsParams {
                         }
                     };
 
+                    if !matches!(kind, ParamKind::Value) && !config.is_empty() {
+                        abort!(
+                            pat.ty,
+                            "default values and validators are not supported on borrowed parameters"
+                        );
+                    }
+
                     types.push(JsParam {
                         kind,
                         tokens: stream,
                         number: idx,
                         is_this: false,
+                        name,
+                        config,
                     });
                 }
                 FnArg::Receiver(recv) => {
@@ -414,6 +638,8 @@ impl JsParams {
                             number: idx,
                             tokens: stream,
                             is_this: true,
+                            name: "self".to_owned(),
+                            config: ParamConfig::default(),
                         })
                     } else {
                         abort!(
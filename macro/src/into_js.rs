@@ -0,0 +1,187 @@
+use proc_macro2::{Span, TokenStream};
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident};
+
+use crate::{
+    common::{add_js_lifetime, crate_ident, Case},
+    from_js::ConvertConfig,
+};
+
+/// Returns a pattern binding the fields of `path` and the bound identifiers.
+fn bind_fields(path: TokenStream, fields: &Fields) -> (TokenStream, Vec<Ident>) {
+    match fields {
+        // The fields are bound to generated names so they don't shadow the generated code.
+        Fields::Named(fields) => {
+            let names = fields
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap());
+            let idents = (0..fields.named.len())
+                .map(|idx| format_ident!("field_{}", idx))
+                .collect::<Vec<_>>();
+            (quote!(#path { #(#names: #idents,)* }), idents)
+        }
+        Fields::Unnamed(fields) => {
+            let idents = (0..fields.unnamed.len())
+                .map(|idx| format_ident!("field_{}", idx))
+                .collect::<Vec<_>>();
+            (quote!(#path(#(#idents,)*)), idents)
+        }
+        Fields::Unit => (quote!(#path), Vec::new()),
+    }
+}
+
+/// Expands the conversion of the bound fields into a value.
+fn expand_fields(
+    crate_name: &Ident,
+    fields: &Fields,
+    idents: &[Ident],
+    rename_all: Option<Case>,
+) -> TokenStream {
+    match fields {
+        Fields::Named(fields) => {
+            let sets = fields.named.iter().zip(idents).map(|(field, ident)| {
+                let name = ConvertConfig::from_attrs(&field.attrs)
+                    .name(field.ident.as_ref().unwrap(), rename_all);
+                quote!(object.set(#name, #ident)?;)
+            });
+            quote! {{
+                let object = #crate_name::Object::new(ctx.clone())?;
+                #(#sets)*
+                ::std::result::Result::Ok(object.into_value())
+            }}
+        }
+        Fields::Unnamed(_) if idents.len() == 1 => {
+            let ident = &idents[0];
+            quote!(#crate_name::IntoJs::into_js(#ident, ctx))
+        }
+        Fields::Unnamed(_) => {
+            let sets = idents
+                .iter()
+                .enumerate()
+                .map(|(idx, ident)| quote!(array.set(#idx, #ident)?;));
+            quote! {{
+                let array = #crate_name::Array::new(ctx.clone())?;
+                #(#sets)*
+                ::std::result::Result::Ok(array.into_value())
+            }}
+        }
+        Fields::Unit => quote!(::std::result::Result::Ok(#crate_name::Value::new_undefined(
+            ctx.clone()
+        ))),
+    }
+}
+
+pub(crate) fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+    let crate_name = format_ident!("{}", crate_ident());
+    let config = ConvertConfig::from_attrs(&attrs);
+    let type_name = ident.to_string();
+
+    let mut impl_generics = add_js_lifetime(&generics);
+    let js_lifetime = syn::Lifetime::new("'js", Span::call_site());
+    let type_params = generics
+        .type_params()
+        .map(|x| x.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = impl_generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: #crate_name::IntoJs<#js_lifetime>));
+    }
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+    let (_, ty_generics, _) = generics.split_for_impl();
+
+    match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            if config.tag.is_some() || config.untagged {
+                abort!(ident, "`tag` and `untagged` can only be used on enums");
+            }
+            let (pattern, idents) = bind_fields(quote!(Self), &fields);
+            let body = expand_fields(&crate_name, &fields, &idents, config.rename_all);
+            quote! {
+                impl #impl_generics #crate_name::IntoJs<'js> for #ident #ty_generics #where_clause {
+                    fn into_js(self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<#crate_name::Value<'js>> {
+                        let _ = ctx;
+                        let #pattern = self;
+                        #body
+                    }
+                }
+
+                impl #impl_generics #crate_name::convert::IntoMultiValue<'js> for #ident #ty_generics #where_clause {
+                    fn into_multi_value(self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<::std::vec::Vec<#crate_name::Value<'js>>> {
+                        let _ = ctx;
+                        let #pattern = self;
+                        ::std::result::Result::Ok(::std::vec![#(#crate_name::IntoJs::into_js(#idents, ctx)?,)*])
+                    }
+                }
+            }
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            if config.tag.is_some() && config.untagged {
+                abort!(ident, "an enum can't be both tagged and untagged");
+            }
+            let tag = config.tag.clone().unwrap_or_else(|| "type".to_owned());
+            let arms = variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                let variant_config = ConvertConfig::from_attrs(&variant.attrs);
+                let (pattern, idents) = bind_fields(quote!(Self::#variant_ident), &variant.fields);
+                if config.untagged {
+                    let body = expand_fields(
+                        &crate_name,
+                        &variant.fields,
+                        &idents,
+                        variant_config.rename_all,
+                    );
+                    return quote!(#pattern => #body,);
+                }
+                let name = variant_config.name(variant_ident, config.rename_all);
+                // Unit variants are just the tag, the tag of the other variants is added to
+                // the object their fields are converted into.
+                let value = match &variant.fields {
+                    Fields::Unit => quote!(#crate_name::Object::new(ctx.clone())?.into_value()),
+                    fields => {
+                        let body =
+                            expand_fields(&crate_name, fields, &idents, variant_config.rename_all);
+                        quote!({
+                            let value: #crate_name::Result<#crate_name::Value<'js>> = #body;
+                            value?
+                        })
+                    }
+                };
+                quote! {
+                    #pattern => {
+                        let object = #value.into_object().ok_or_else(|| {
+                            #crate_name::Error::new_into_js_message(
+                                #type_name,
+                                "object",
+                                "A tagged variant must be converted into an object",
+                            )
+                        })?;
+                        object.set(#tag, #name)?;
+                        ::std::result::Result::Ok(object.into_value())
+                    }
+                }
+            });
+            quote! {
+                impl #impl_generics #crate_name::IntoJs<'js> for #ident #ty_generics #where_clause {
+                    fn into_js(self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<#crate_name::Value<'js>> {
+                        let _ = ctx;
+                        match self {
+                            #(#arms)*
+                        }
+                    }
+                }
+            }
+        }
+        Data::Union(_) => abort!(ident, "IntoJs can't be derived for unions"),
+    }
+}
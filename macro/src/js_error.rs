@@ -0,0 +1,183 @@
+use convert_case::{Case, Casing};
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote, Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, Ident, LitStr, Member,
+};
+
+use crate::{
+    attrs::{FlagOption, OptionList, ValueOption},
+    common::{crate_ident, kw, AbortResultExt},
+};
+
+#[derive(Default)]
+pub(crate) struct ErrorConfig {
+    pub rename: Option<String>,
+    pub code: Option<String>,
+}
+
+pub(crate) enum ErrorOption {
+    Rename(ValueOption<kw::rename, LitStr>),
+    Code(ValueOption<kw::code, LitStr>),
+}
+
+impl Parse for ErrorOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(kw::rename) {
+            input.parse().map(Self::Rename)
+        } else if input.peek(kw::code) {
+            input.parse().map(Self::Code)
+        } else {
+            Err(syn::Error::new(input.span(), "invalid error attribute"))
+        }
+    }
+}
+
+impl ErrorConfig {
+    fn from_attrs(attrs: &[Attribute]) -> Self {
+        let mut config = ErrorConfig::default();
+        for attr in attrs.iter().filter(|x| x.path().is_ident("qjs")) {
+            let options: OptionList<ErrorOption> = attr.parse_args().unwrap_or_abort();
+            for option in options.0 {
+                match option {
+                    ErrorOption::Rename(x) => config.rename = Some(x.value.value()),
+                    ErrorOption::Code(x) => config.code = Some(x.value.value()),
+                }
+            }
+        }
+        config
+    }
+
+    /// The name of the error class, `NotFound` becomes `NotFoundError`.
+    fn name(&self, ident: &Ident) -> String {
+        if let Some(name) = &self.rename {
+            return name.clone();
+        }
+        let ident = ident.to_string();
+        if ident.ends_with("Error") {
+            ident
+        } else {
+            format!("{}Error", ident)
+        }
+    }
+
+    /// The code of the error, `NotFound` becomes `NOT_FOUND`.
+    fn code(&self, ident: &Ident) -> String {
+        if let Some(code) = &self.code {
+            return code.clone();
+        }
+        let ident = ident.to_string();
+        let ident = match ident.strip_suffix("Error") {
+            Some(x) if !x.is_empty() => x,
+            _ => &ident,
+        };
+        ident.to_case(Case::ScreamingSnake)
+    }
+}
+
+/// Returns the field marked with `#[qjs(cause)]`, if any.
+fn cause_field(fields: &Fields) -> Option<Member> {
+    let mut cause = None;
+    for (idx, field) in fields.iter().enumerate() {
+        for attr in field.attrs.iter().filter(|x| x.path().is_ident("qjs")) {
+            let options: OptionList<FlagOption<kw::cause>> = attr.parse_args().unwrap_or_abort();
+            if !options.0.iter().any(|x| x.is_true()) {
+                continue;
+            }
+            if cause.is_some() {
+                abort!(field, "only one field can be the cause of an error");
+            }
+            cause = Some(match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(idx.into()),
+            });
+        }
+    }
+    cause
+}
+
+pub(crate) fn expand(input: DeriveInput) -> TokenStream {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        attrs,
+        ..
+    } = input;
+    let crate_name = format_ident!("{}", crate_ident());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut from_generics = generics.clone();
+    from_generics.make_where_clause().predicates.push(
+        parse_quote!(#ident #ty_generics: ::std::marker::Send + ::std::marker::Sync + 'static),
+    );
+    let from_where_clause = &from_generics.where_clause;
+
+    let arms = match data {
+        Data::Enum(DataEnum { variants, .. }) => variants
+            .iter()
+            .map(|variant| {
+                let config = ErrorConfig::from_attrs(&variant.attrs);
+                let variant_ident = &variant.ident;
+                (
+                    quote!(Self::#variant_ident),
+                    config.name(variant_ident),
+                    config.code(variant_ident),
+                    cause_field(&variant.fields),
+                )
+            })
+            .collect::<Vec<_>>(),
+        Data::Struct(DataStruct { fields, .. }) => {
+            let config = ErrorConfig::from_attrs(&attrs);
+            vec![(
+                quote!(Self),
+                config.name(&ident),
+                config.code(&ident),
+                cause_field(&fields),
+            )]
+        }
+        Data::Union(_) => abort!(ident, "JsError can't be derived for unions"),
+    };
+
+    let name_arms = arms
+        .iter()
+        .map(|(path, name, _, _)| quote!(#path { .. } => #name,));
+    let code_arms = arms
+        .iter()
+        .map(|(path, _, code, _)| quote!(#path { .. } => ::std::option::Option::Some(#code),));
+    let cause_arms = arms.iter().map(|(path, _, _, cause)| match cause {
+        Some(member) => {
+            quote!(#path { #member: ref cause, .. } => ::std::option::Option::Some(cause),)
+        }
+        None => quote!(#path { .. } => ::std::option::Option::None,),
+    });
+
+    quote! {
+        impl #impl_generics #crate_name::JsError for #ident #ty_generics #where_clause {
+            fn name(&self) -> &str {
+                match self {
+                    #(#name_arms)*
+                }
+            }
+
+            fn code(&self) -> ::std::option::Option<&str> {
+                match self {
+                    #(#code_arms)*
+                }
+            }
+
+            fn cause(&self) -> ::std::option::Option<&dyn #crate_name::JsError> {
+                match self {
+                    #(#cause_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<#ident #ty_generics> for #crate_name::Error #from_where_clause {
+            fn from(error: #ident #ty_generics) -> Self {
+                #crate_name::Error::new_js(error)
+            }
+        }
+    }
+}
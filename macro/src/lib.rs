@@ -21,7 +21,10 @@ mod class;
 mod common;
 mod embed;
 mod fields;
+mod from_js;
 mod function;
+mod into_js;
+mod js_error;
 mod methods;
 mod module;
 mod trace;
@@ -41,7 +44,10 @@ mod trace;
 /// | `crate`      | String    | Changes the name from which the attribute tries to use rquickjs types. Use when the name behind which the rquickjs crate is declared is not properly resolved by the macro.             |
 /// | `rename`     | String    | Changes the name of the implemented class on the JavaScript side.                                                                                                                       |
 /// | `rename_all` | Casing    | Converts the case of all the fields of this struct which have implement accessors. Can be one of `lowercase`, `UPPERCASE`, `camelCase`, `PascalCase`,`snake_case`, or `SCREAMING_SNAKE` |
-/// | `frozen`     | Flag      | Changes the class implementation to only allow borrowing immutably.  Trying to borrow mutably will result in an error.                                                                  |
+/// | `frozen`     | Flag      | Changes the class implementation to only allow borrowing immutably.  Trying to borrow mutably will result in an error. Instances are also not extensible unless `extensible` is set. |
+/// | `extensible` | Flag      | Sets whether scripts can add properties to instances, which is the default for classes which are not `frozen`. Instances of non extensible classes are created with `Object.preventExtensions`. |
+/// | `exotic`     | Flag      | Resolves properties of the class dynamically using its [`JsExotic`](rquickjs_core::class::JsExotic) implementation, which must be implemented separately.                            |
+/// | `no_constructor` | Flag  | Defines a constructor which throws a `TypeError`, so instances can only be created from Rust or factories while the class can still be used with `instanceof`. Don't combine it with a `constructor` method. |
 ///
 /// # Field options
 ///
@@ -110,6 +116,45 @@ mod trace;
 ///     })
 /// }
 /// ```
+///
+/// # Enums
+///
+/// For enums every variant gets a static factory function on the class constructor which takes
+/// the fields of the variant in order, like `Shape.Circle(radius)`, and instances have a `tag`
+/// getter which returns the name of the variant. Unless a constructor is defined with
+/// [`methods`], calling `new` on the class throws. Converting an instance back to the Rust enum
+/// works for enums which implement `Clone`, like for structs.
+///
+/// | **Option** | **Value** | **Description**                                                    |
+/// |------------|-----------|--------------------------------------------------------------------|
+/// | `rename`   | String    | Changes the name of the variant factory and tag in JavaScript.     |
+/// | `skip`     | Flag      | Doesn't create a factory for the variant.                          |
+///
+/// ```
+/// use rquickjs::{class::Trace, Class, Context, Runtime};
+///
+/// #[derive(Trace, Clone)]
+/// #[rquickjs::class]
+/// pub enum Shape {
+///     Circle(f64),
+///     Rect { width: f64, height: f64 },
+///     #[qjs(rename = "Nothing")]
+///     Empty,
+/// }
+///
+/// pub fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///
+///     ctx.with(|ctx| {
+///         Class::<Shape>::define(&ctx.globals()).unwrap();
+///         let shape: Shape = ctx.eval("Shape.Rect(2, 3)").unwrap();
+///         assert!(matches!(shape, Shape::Rect { width, .. } if width == 2.0));
+///         let tag: String = ctx.eval("Shape.Nothing().tag").unwrap();
+///         assert_eq!(tag, "Nothing");
+///     })
+/// }
+/// ```
 
 #[proc_macro_attribute]
 #[proc_macro_error]
@@ -125,6 +170,12 @@ pub fn class(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 /// then when you use closures or the functions for which the proper traits are already
 /// implemented..
 ///
+/// Parameters support the same `default`, `range`, `length` and `options` options as the
+/// parameters of [`macro@methods`].
+///
+/// A result which converts into `undefined`, like `None` or `Ok(None)`, is returned as is unless
+/// the function is tagged with `#[qjs(null)]`, then it is returned as `null`.
+///
 #[proc_macro_attribute]
 #[proc_macro_error]
 pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
@@ -171,12 +222,44 @@ pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 /// |----------------|-------------------------------------------------------------------|-------------------------------------------------------------------------------------------------|
 /// | `get`          | Flag                                                              | Makes this method a getter for a field of the same name.                                        |
 /// | `set`          | Flag                                                              | Makes this method a setter for a field of the same name.                                        |
-/// | `enumerable`   | Flag                                                              | Makes the method, if it is a getter or setter, enumerable in JavaScript.                        |
-/// | `configurable` | Flag                                                              | Makes the method, if it is a getter or setter, configurable in JavaScript.                      |
+/// | `enumerable`   | Flag                                                              | Makes the method or accessor enumerable in JavaScript, defaults to `false`.                     |
+/// | `configurable` | Flag                                                              | Makes the method or accessor configurable in JavaScript, defaults to `true`.                    |
+/// | `writable`     | Flag                                                              | Makes the method, if it isn't a getter or setter, writable in JavaScript, defaults to `true`.   |
 /// | `rename`       | String or [`PredefinedAtom`](rquickjs_core::atom::PredefinedAtom) | Changes the name of the field getter and/or setter to the specified name in JavaScript.         |
 /// | `static`       | Flag                                                              | Makes the method a static method i.e. defined on the type constructor instead of the prototype. |
 /// | `constructor`  | Flag                                                              | Marks this method a the constructor for this type.                                              |
+/// | `factory`      | Flag                                                              | Makes the method a static method creating instances. A class can have any number of factories.  |
 /// | `skip`         | Flag                                                              | Skips defining this method on the JavaScript class.                                             |
+/// | `finalize`     | Flag                                                              | Marks this `fn(&mut self)` method as the finalizer, called when the object is garbage collected. |
+/// | `null`         | Flag                                                              | Returns results which convert into `undefined`, like `None` or `Ok(None)`, as `null`.           |
+///
+/// # Parameter options
+///
+/// The parameters of methods can be tagged with a `#[qjs(option = value)]` attribute as well.
+///
+/// | **Option** | **Value**          | **Description**                                                                                      |
+/// |------------|--------------------|------------------------------------------------------------------------------------------------------|
+/// | `default`  | Flag or expression | Uses the expression, or `Default::default()` for the flag, when the argument is omitted.            |
+/// | `range`    | Range expression   | Throws a `RangeError` when the value is outside the range, e.g. `#[qjs(range = 0..=100)]`.          |
+/// | `length`   | Range expression   | Throws a `RangeError` when the `len()` of the value is outside the range.                            |
+/// | `options`  | Flag               | Converts the last parameter from a trailing plain object argument like in `f(a, b, { flag: true })`. |
+///
+/// The options parameter is taken before the other parameters, so it can follow a
+/// [`Rest`](rquickjs_core::function::Rest) parameter which takes every remaining argument. When the
+/// options object is missing the parameter is converted from `undefined`, or uses its default.
+///
+/// These options are only supported on parameters which are passed by value, they can also be used
+/// on the parameters of a [`macro@function`].
+///
+/// A class without a `constructor` method which has static methods or factories gets a
+/// constructor which throws a `TypeError` when called, the static methods are defined on it.
+///
+/// Like in JavaScript classes, methods and accessors are configurable but not enumerable by default,
+/// flags like `#[qjs(enumerable, configurable = false)]` change the property descriptor.
+///
+/// Well-known symbols can also be written like in JavaScript: `#[qjs(rename = Symbol.toPrimitive)]`
+/// is the same as `#[qjs(rename = PredefinedAtom::SymbolToPrimitive)]`. Operators like `+` and `==`
+/// can be overloaded with [`Operators`](rquickjs_core::class::Operators) on the class prototype.
 ///
 /// # Example
 /// ```
@@ -259,6 +342,12 @@ pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 ///         )?;
 ///         Ok(res)
 ///     }
+///
+///     /// Implementing `Symbol.toPrimitive` allows the object to be used in arithmetic.
+///     #[qjs(rename = Symbol.toPrimitive)]
+///     pub fn to_primitive(&self, _hint: String) -> u32 {
+///         self.value
+///     }
 /// }
 ///
 /// pub fn main() {
@@ -274,6 +363,9 @@ pub fn function(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
 ///             if(nv.value !== 5){
 ///                 throw new Error('invalid value')
 ///             }
+///             if(nv + 1 !== 6){
+///                 throw new Error('invalid primitive')
+///             }
 ///         "#,
 ///         ).catch(&ctx).unwrap();
 ///     });
@@ -480,6 +572,247 @@ pub fn trace(stream: TokenStream1) -> TokenStream1 {
     trace::expand(derive_input).into()
 }
 
+/// A macro for deriving [`JsError`](rquickjs_core::JsError) and the conversion into
+/// [`Error`](rquickjs_core::Error).
+///
+/// Returning the error from a native function throws it as an instance of its own error class,
+/// with the [`Display`](std::fmt::Display) output of the error as the message. For enums every
+/// variant is an error class, named after the variant with an `Error` suffix, so
+/// `MyError::NotFound` becomes a `NotFoundError` with the code `NOT_FOUND`. For structs the name
+/// of the struct is used. The error types must be `Send`, `Sync` and `'static`.
+///
+/// # Attribute options
+///
+/// | **Option**  | **Value** | **Description**                                                      |
+/// |-------------|-----------|----------------------------------------------------------------------|
+/// | `rename`    | String    | Changes the name of the error class.                                 |
+/// | `code`      | String    | Changes the code set as the `code` property of the error.           |
+/// | `cause`     | Flag      | On a field, thrown as the `cause` of the error. Must be a `JsError`. |
+///
+/// # Example
+///
+/// ```
+/// use rquickjs::{CatchResultExt, Context, Function, JsError, Runtime};
+/// use std::fmt;
+///
+/// #[derive(Debug, JsError)]
+/// pub enum StoreError {
+///     NotFound(String),
+///     #[qjs(rename = "StoreIoError", code = "EIO")]
+///     Io {
+///         path: String,
+///         #[qjs(cause)]
+///         cause: DiskError,
+///     },
+/// }
+///
+/// #[derive(Debug, JsError)]
+/// pub struct DiskError;
+///
+/// impl fmt::Display for StoreError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         match self {
+///             StoreError::NotFound(key) => write!(f, "no value for `{}`", key),
+///             StoreError::Io { path, .. } => write!(f, "failed to read `{}`", path),
+///         }
+///     }
+/// }
+///
+/// impl fmt::Display for DiskError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         "disk is gone".fmt(f)
+///     }
+/// }
+///
+/// pub fn get(key: String) -> rquickjs::Result<String> {
+///     if key == "disk" {
+///         let path = "/data".to_string();
+///         Err(StoreError::Io { path, cause: DiskError }.into())
+///     } else {
+///         Err(StoreError::NotFound(key).into())
+///     }
+/// }
+///
+/// pub fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         ctx.globals().set("get", Function::new(ctx.clone(), get)).unwrap();
+///         let res: String = ctx
+///             .eval(
+///                 r#"
+///                 const describe = (f) => { try { f() } catch (e) { return `${e.name}/${e.code}: ${e.message}` } };
+///                 [
+///                     describe(() => get("key")),
+///                     describe(() => get("disk")),
+///                     describe(() => { try { get("disk") } catch (e) { throw e.cause } }),
+///                     String(describe(() => get("key")) && (() => { try { get("x") } catch (e) { return e instanceof NotFoundError && e instanceof Error } })()),
+///                 ].join("\n")
+///                 "#,
+///             )
+///             .catch(&ctx)
+///             .unwrap();
+///         assert_eq!(
+///             res,
+///             "NotFoundError/NOT_FOUND: no value for `key`\n\
+///              StoreIoError/EIO: failed to read `/data`\n\
+///              DiskError/DISK: disk is gone\n\
+///              true"
+///         );
+///     })
+/// }
+/// ```
+#[proc_macro_derive(JsError, attributes(qjs))]
+#[proc_macro_error]
+pub fn js_error(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    js_error::expand(derive_input).into()
+}
+
+/// A macro for deriving [`FromJs`](rquickjs_core::FromJs).
+///
+/// Structs with named fields are converted from objects with a property for every field, tuple
+/// structs from arrays and newtype structs from whatever their field is converted from.
+///
+/// Enums are by default converted from objects with a `type` property naming the variant, the
+/// other properties of the object are converted into the fields of the variant. With `untagged`
+/// every variant is tried in order instead and the first one which converts is used, which
+/// handles values which may for example be either a string or an object. Unit variants of
+/// untagged enums match `undefined` and `null`.
+///
+/// # Attribute options
+///
+/// | **Option**   | **Value** | **Description**                                                                |
+/// |--------------|-----------|--------------------------------------------------------------------------------|
+/// | `tag`        | String    | On an enum, changes the name of the property which names the variant.          |
+/// | `untagged`   | Flag      | On an enum, tries every variant in order instead of reading a tag.             |
+/// | `rename`     | String    | On a field or variant, changes the name of the property or the variant's tag.  |
+/// | `rename_all` | Casing    | Converts the case of all fields of a struct or variant, or all variant tags.   |
+///
+/// # Example
+///
+/// ```
+/// use rquickjs::{Context, FromJs, Runtime};
+///
+/// #[derive(Debug, PartialEq, FromJs)]
+/// #[qjs(rename_all = "lowercase")]
+/// pub enum Shape {
+///     Circle { radius: f64 },
+///     #[qjs(rename_all = "camelCase")]
+///     Rect { top_left: Vec<f64>, size: Size },
+/// }
+///
+/// #[derive(Debug, PartialEq, FromJs)]
+/// pub struct Size {
+///     width: f64,
+///     #[qjs(rename = "h")]
+///     height: f64,
+/// }
+///
+/// #[derive(Debug, PartialEq, FromJs)]
+/// #[qjs(untagged)]
+/// pub enum Source {
+///     Url(String),
+///     Inline { code: String },
+/// }
+///
+/// pub fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         let shape: Shape = ctx
+///             .eval("({ type: 'rect', topLeft: [0, 1], size: { width: 2, h: 3 } })")
+///             .unwrap();
+///         assert_eq!(
+///             shape,
+///             Shape::Rect {
+///                 top_left: vec![0.0, 1.0],
+///                 size: Size { width: 2.0, height: 3.0 }
+///             }
+///         );
+///         assert!(ctx.eval::<Shape, _>("({ type: 'square' })").is_err());
+///
+///         let source: Source = ctx.eval("'https://example.com/lib.js'").unwrap();
+///         assert_eq!(source, Source::Url("https://example.com/lib.js".into()));
+///         let source: Source = ctx.eval("({ code: '1 + 1' })").unwrap();
+///         assert_eq!(source, Source::Inline { code: "1 + 1".into() });
+///     })
+/// }
+/// ```
+#[proc_macro_derive(FromJs, attributes(qjs))]
+#[proc_macro_error]
+pub fn from_js(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    from_js::expand(derive_input).into()
+}
+
+/// A macro for deriving [`IntoJs`](rquickjs_core::IntoJs).
+///
+/// The conversion is the reverse of the one derived by [`FromJs`](macro@FromJs) and takes the
+/// same attribute options. Structs with named fields are converted into objects with a property
+/// for every field, tuple structs into arrays and newtype structs into whatever their field is
+/// converted into. Variants of tagged enums are converted into objects with the tag property
+/// added, the unit variants of untagged enums into `undefined`.
+///
+/// For structs [`IntoMultiValue`](rquickjs_core::convert::IntoMultiValue) is derived too,
+/// wrapping a struct in [`MultiValue`](rquickjs_core::convert::MultiValue) converts it into an
+/// array of its fields instead.
+///
+/// # Example
+///
+/// ```
+/// use rquickjs::{convert::MultiValue, Context, Function, IntoJs, Runtime};
+///
+/// #[derive(IntoJs)]
+/// #[qjs(rename_all = "camelCase")]
+/// pub struct Stats {
+///     line_count: u32,
+///     #[qjs(rename = "words")]
+///     word_count: u32,
+/// }
+///
+/// #[derive(IntoJs)]
+/// #[qjs(tag = "kind")]
+/// pub enum Token {
+///     Word(Stats),
+///     Number { value: f64 },
+///     End,
+/// }
+///
+/// pub fn main() {
+///     let rt = Runtime::new().unwrap();
+///     let ctx = Context::full(&rt).unwrap();
+///     ctx.with(|ctx| {
+///         let stats = Function::new(ctx.clone(), || Stats { line_count: 2, word_count: 5 });
+///         ctx.globals().set("stats", stats.unwrap()).unwrap();
+///         let pair = Function::new(ctx.clone(), || {
+///             MultiValue(Stats { line_count: 1, word_count: 3 })
+///         });
+///         ctx.globals().set("pair", pair.unwrap()).unwrap();
+///         ctx.globals().set("token", Token::Number { value: 1.5 }).unwrap();
+///         ctx.globals().set("end", Token::End).unwrap();
+///
+///         let res: bool = ctx
+///             .eval(
+///                 r#"
+///                     const { lineCount, words } = stats();
+///                     const [lines, count] = pair();
+///                     lineCount === 2 && words === 5 && lines === 1 && count === 3
+///                         && token.kind === "Number" && token.value === 1.5 && end.kind === "End"
+///                 "#,
+///             )
+///             .unwrap();
+///         assert!(res);
+///     })
+/// }
+/// ```
+#[proc_macro_derive(IntoJs, attributes(qjs))]
+#[proc_macro_error]
+pub fn into_js(stream: TokenStream1) -> TokenStream1 {
+    let derive_input = parse_macro_input!(stream as DeriveInput);
+    into_js::expand(derive_input).into()
+}
+
 /// A macro for embedding JavaScript code into a binary.
 ///
 /// Compiles a JavaScript module to bytecode and then compiles the resulting bytecode into the
@@ -487,6 +820,11 @@ pub fn trace(stream: TokenStream1) -> TokenStream1 {
 /// files to be compiled into a module with an option name. Module paths are relative to the crate
 /// manifest file.
 ///
+/// If a path points to a directory, every `.js` and `.mjs` file in it, and its subdirectories, is
+/// compiled into a module. The modules are named by their path relative to the directory without
+/// the extension, prefixed with the given name, so `"lib": "js"` embeds `js/util/math.js` as
+/// `lib/util/math`.
+///
 /// # Usage
 ///
 /// ```
@@ -519,6 +857,31 @@ pub fn trace(stream: TokenStream1) -> TokenStream1 {
 ///     })
 /// }
 /// ```
+///
+/// Embedding a directory of modules which import each other:
+///
+/// ```
+/// use rquickjs::{embed, loader::Bundle, Context, Module, Runtime};
+///
+/// /// load `my_modules/math.js` as `lib/math` and `my_modules/util/double.js` as `lib/util/double`
+/// static BUNDLE: Bundle = embed! {
+///     "lib": "my_modules",
+/// };
+///
+/// let rt = Runtime::new().unwrap();
+/// let ctx = Context::full(&rt).unwrap();
+///
+/// rt.set_loader(BUNDLE, BUNDLE);
+/// ctx.with(|ctx| {
+///     let module = Module::evaluate(
+///         ctx,
+///         "main",
+///         "import { quadruple } from 'lib/math'; export const res = quadruple(3);",
+///     )
+///     .unwrap();
+///     assert_eq!(module.get::<_, i32>("res").unwrap(), 12);
+/// })
+/// ```
 #[proc_macro_error]
 #[proc_macro]
 pub fn embed(item: TokenStream1) -> TokenStream1 {
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use proc_macro2::{Span, TokenStream};
-use proc_macro_error::{abort, emit_warning};
+use proc_macro_error::abort;
 use quote::{format_ident, quote};
 use syn::{
     parse::{Parse, ParseStream},
@@ -132,6 +132,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
     let mut accessors = HashMap::new();
     let mut functions = Vec::new();
     let mut constructor: Option<Method> = None;
+    let mut finalizer: Option<Method> = None;
     let mut static_span: Option<Span> = None;
     //let mut consts = Vec::new();
 
@@ -150,6 +151,15 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
                     } else {
                         access.define_set(function, config.rename_all);
                     }
+                } else if function.config.finalize {
+                    if let Some(first) = finalizer.replace(function) {
+                        let first_span = first.attr_span;
+                        abort!(
+                            span,
+                            "A class can only have a single finalizer";
+                            hint = first_span => "First finalizer defined here"
+                        );
+                    }
                 } else if function.config.constructor {
                     if let Some(first) = constructor.replace(function) {
                         let first_span = first.attr_span;
@@ -170,20 +180,10 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
         }
     }
 
-    // Warn about unused static definitions if no constructor was created.
-    if constructor.is_none() {
-        if let Some(span) = static_span {
-            emit_warning!(
-                span,
-                "Static methods are unused if an class doesn't have a constructor.";
-                hint = "Static methods are defined on the class constructor."
-            );
-        }
-    }
-
     let function_impls = functions.iter().map(|func| func.expand_impl());
     let accessor_impls = accessors.values().map(|access| access.expand_impl());
     let constructor_impl = constructor.as_ref().map(|constr| constr.expand_impl());
+    let finalizer_impl = finalizer.as_ref().map(|finalizer| finalizer.expand_impl());
 
     let function_js_impls = functions
         .iter()
@@ -204,7 +204,13 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
         .iter()
         .filter(|&func| (!func.config.r#static))
         .map(|func| {
-            func.expand_apply_to_object(&prefix, &self_ty, &proto_ident, config.rename_all)
+            func.expand_apply_to_object(
+                &prefix,
+                &self_ty,
+                &proto_ident,
+                config.rename_all,
+                &crate_name,
+            )
         });
     let accessor_apply_proto = accessors
         .values()
@@ -212,10 +218,29 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
 
     let constructor_ident = format_ident!("constr");
 
-    let constructor_create = if let Some(c) = constructor.as_ref() {
-        let name = c.function.expand_carry_type_name(IMPL_PREFIX);
-
+    let has_static = functions.iter().any(|func| func.config.r#static);
+    let constructor_create = if constructor.is_some() || has_static {
         let js_added_generics = add_js_lifetime(&generics);
+        let new_constructor = if let Some(c) = constructor.as_ref() {
+            let name = c.function.expand_carry_type_name(IMPL_PREFIX);
+            quote!(#crate_name::function::Constructor::new_class::<#self_ty,_,_>(ctx.clone(),#name)?)
+        } else {
+            // Static methods and factories still need a constructor object to be defined on.
+            quote! {
+                #crate_name::function::Constructor::new_class::<#self_ty, _, _>(
+                    ctx.clone(),
+                    |ctx: #crate_name::Ctx<'js>| -> #crate_name::Result<#self_ty> {
+                        Err(#crate_name::Exception::throw_type(
+                            &ctx,
+                            &::std::format!(
+                                "{} can't be constructed directly",
+                                <#self_ty as #crate_name::class::JsClass<'js>>::NAME,
+                            ),
+                        ))
+                    },
+                )?
+            }
+        };
 
         let static_function_apply =
             functions
@@ -227,13 +252,14 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
                         &self_ty,
                         &constructor_ident,
                         config.rename_all,
+                        &crate_name,
                     )
                 });
 
         quote! {
             impl #js_added_generics #crate_name::class::impl_::ConstructorCreator<'js,#self_ty> for #crate_name::class::impl_::ConstructorCreate<#self_ty> {
                 fn create_constructor(&self, ctx: &#crate_name::Ctx<'js>) -> #crate_name::Result<Option<#crate_name::function::Constructor<'js>>>{
-                    let constr = #crate_name::function::Constructor::new_class::<#self_ty,_,_>(ctx.clone(),#name)?;
+                    let constr = #new_constructor;
                     #(#static_function_apply)*
                     Ok(Some(constr))
                 }
@@ -243,6 +269,19 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
         TokenStream::new()
     };
 
+    let finalizer_create = if let Some(f) = finalizer.as_ref() {
+        let name = &f.function.name;
+        quote! {
+            impl #generics #crate_name::class::impl_::FinalizeImplementor<#self_ty> for #crate_name::class::impl_::FinalizeImpl<#self_ty> {
+                fn finalize(&self, this: &mut #self_ty){
+                    <#self_ty>::#name(this)
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
     let class_name = get_class_name(&self_ty);
     let impl_mod_name = format_ident!("__impl_methods_{class_name}__");
 
@@ -252,6 +291,7 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
             #(#function_impls)*
             #(#accessor_impls)*
             #constructor_impl
+            #finalizer_impl
         }
 
 
@@ -275,6 +315,8 @@ pub(crate) fn expand(options: OptionList<ImplOption>, item: ItemImpl) -> TokenSt
             }
 
             #constructor_create
+
+            #finalizer_create
         }
     }
 }
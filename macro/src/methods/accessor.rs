@@ -64,69 +64,43 @@ impl JsAccessor {
     }
 
     pub fn expand_apply_to_proto(&self, lib_crate: &Ident, case: Option<Case>) -> TokenStream {
-        match (self.get.as_ref(), self.set.as_ref()) {
+        let methods = || self.get.iter().chain(self.set.iter());
+        let Some(first) = methods().next() else {
+            return TokenStream::new();
+        };
+        let name = first.name(case);
+
+        // Like accessors of JavaScript classes, accessors are configurable but not enumerable
+        // unless specified otherwise on the getter or setter.
+        let configurable = methods()
+            .find_map(|x| x.config.configurable)
+            .unwrap_or(true)
+            .then(|| quote!(.configurable()));
+        let enumerable = methods()
+            .find_map(|x| x.config.enumerable)
+            .unwrap_or(false)
+            .then(|| quote!(.enumerable()));
+
+        let accessor = match (self.get.as_ref(), self.set.as_ref()) {
             (Some(get), Some(set)) => {
-                let configurable = get.config.configurable || set.config.configurable;
-                let enumerable = get.config.enumerable || set.config.enumerable;
-
-                let name = get.name(case);
-
-                let configurable = configurable
-                    .then(|| quote!(.configurable()))
-                    .unwrap_or_default();
-                let enumerable = enumerable
-                    .then(|| quote!(.enumerable()))
-                    .unwrap_or_default();
-
                 let get_name = get.function.expand_carry_type_name(GET_PREFIX);
                 let set_name = set.function.expand_carry_type_name(SET_PREFIX);
-                quote! {_proto.prop(#name,
-                        #lib_crate::object::Accessor::new(#get_name,#set_name)
-                        #configurable
-                        #enumerable
-                )?;}
+                quote!(#lib_crate::object::Accessor::new(#get_name,#set_name))
             }
             (Some(get), None) => {
-                let configurable = get.config.configurable;
-                let enumerable = get.config.enumerable;
-
-                let name = get.name(case);
-
-                let configurable = configurable
-                    .then(|| quote!(.configurable()))
-                    .unwrap_or_default();
-                let enumerable = enumerable
-                    .then(|| quote!(.enumerable()))
-                    .unwrap_or_default();
-
                 let get_name = get.function.expand_carry_type_name(GET_PREFIX);
-                quote! {_proto.prop(#name,
-                        #lib_crate::object::Accessor::new_get(#get_name)
-                        #configurable
-                        #enumerable
-                )?;}
+                quote!(#lib_crate::object::Accessor::new_get(#get_name))
             }
             (None, Some(set)) => {
-                let configurable = set.config.configurable;
-                let enumerable = set.config.enumerable;
-
-                let name = set.name(case);
-
-                let configurable = configurable
-                    .then(|| quote!(.configurable()))
-                    .unwrap_or_default();
-                let enumerable = enumerable
-                    .then(|| quote!(.enumerable()))
-                    .unwrap_or_default();
-
-                let set_name = set.function.expand_carry_type_name(GET_PREFIX);
-                quote! {_proto.prop(#name,
-                        #lib_crate::object::Accessor::new_set(#set_name)
-                        #configurable
-                        #enumerable
-                )?;}
+                let set_name = set.function.expand_carry_type_name(SET_PREFIX);
+                quote!(#lib_crate::object::Accessor::new_set(#set_name))
             }
-            (None, None) => TokenStream::new(),
-        }
+            (None, None) => unreachable!(),
+        };
+        quote! {_proto.prop(#name,
+                #accessor
+                #configurable
+                #enumerable
+        )?;}
     }
 }
@@ -5,25 +5,29 @@ use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
     spanned::Spanned,
-    Attribute, Block, Expr, ImplItemFn, LitStr, Signature, Token, Type, Visibility,
+    Attribute, Block, Expr, ImplItemFn, LitStr, Member, Signature, Token, Type, Visibility,
 };
 
 use crate::{
     attrs::{take_attributes, FlagOption, OptionList, ValueOption},
-    common::{kw, AbortResultExt, Case},
+    common::{crate_ident, kw, AbortResultExt, Case},
     function::JsFunction,
 };
 
 #[derive(Default, Clone)]
 pub(crate) struct MethodConfig {
     pub constructor: bool,
+    pub factory: bool,
+    pub finalize: bool,
     pub skip: bool,
     pub r#static: bool,
-    pub configurable: bool,
-    pub enumerable: bool,
+    pub configurable: Option<bool>,
+    pub enumerable: Option<bool>,
+    pub writable: Option<bool>,
     pub get: bool,
     pub set: bool,
     pub rename: Option<Expr>,
+    pub null: bool,
 }
 
 impl MethodConfig {
@@ -32,6 +36,14 @@ impl MethodConfig {
             MethodOption::Constructor(x) => {
                 self.constructor = x.is_true();
             }
+            MethodOption::Factory(x) => {
+                // Factories are defined on the constructor like static methods.
+                self.factory = x.is_true();
+                self.r#static |= self.factory;
+            }
+            MethodOption::Finalize(x) => {
+                self.finalize = x.is_true();
+            }
             MethodOption::Static(x) => {
                 self.r#static = x.is_true();
             }
@@ -39,10 +51,13 @@ impl MethodConfig {
                 self.skip = x.is_true();
             }
             MethodOption::Configurable(x) => {
-                self.configurable = x.is_true();
+                self.configurable = Some(x.is_true());
             }
             MethodOption::Enumerable(x) => {
-                self.enumerable = x.is_true();
+                self.enumerable = Some(x.is_true());
+            }
+            MethodOption::Writable(x) => {
+                self.writable = Some(x.is_true());
             }
             MethodOption::Get(x) => {
                 self.get = x.is_true();
@@ -50,28 +65,75 @@ impl MethodConfig {
             MethodOption::Set(x) => {
                 self.set = x.is_true();
             }
+            MethodOption::Null(x) => {
+                self.null = x.is_true();
+            }
             MethodOption::Rename(x) => {
-                self.rename = Some(x.value.clone());
+                self.rename = Some(well_known_symbol(&x.value).unwrap_or_else(|| x.value.clone()));
             }
         }
     }
 }
 
+/// Translates a `Symbol.<name>` rename into the matching predefined symbol atom.
+fn well_known_symbol(expr: &Expr) -> Option<Expr> {
+    let Expr::Field(field) = expr else {
+        return None;
+    };
+    let Expr::Path(base) = &*field.base else {
+        return None;
+    };
+    if !base.path.is_ident("Symbol") {
+        return None;
+    }
+    let Member::Named(name) = &field.member else {
+        return None;
+    };
+    let variant = match name.to_string().as_str() {
+        "toPrimitive" => "SymbolToPrimitive",
+        "iterator" => "SymbolIterator",
+        "asyncIterator" => "SymbolAsyncIterator",
+        "match" => "SymbolMatch",
+        "matchAll" => "SymbolMatchAll",
+        "replace" => "SymbolReplace",
+        "search" => "SymbolSearch",
+        "split" => "SymbolSplit",
+        "toStringTag" => "SymbolToStringTag",
+        "isConcatSpreadable" => "SymbolIsConcatSpreadable",
+        "hasInstance" => "SymbolHasInstance",
+        "species" => "SymbolSpecies",
+        "unscopables" => "SymbolUnscopables",
+        "operatorSet" => "SymbolOperatorSet",
+        _ => abort!(name, "unknown well-known symbol `Symbol.{}`", name),
+    };
+    let lib_crate = Ident::new(&crate_ident(), Span::call_site());
+    let variant = Ident::new(variant, name.span());
+    Some(syn::parse_quote!(#lib_crate::atom::PredefinedAtom::#variant))
+}
+
 pub(crate) enum MethodOption {
     Constructor(FlagOption<kw::constructor>),
+    Factory(FlagOption<kw::factory>),
+    Finalize(FlagOption<kw::finalize>),
     Static(FlagOption<Token![static]>),
     Skip(FlagOption<kw::skip>),
     Configurable(FlagOption<kw::configurable>),
     Enumerable(FlagOption<kw::enumerable>),
+    Writable(FlagOption<kw::writable>),
     Get(FlagOption<kw::get>),
     Set(FlagOption<kw::set>),
     Rename(ValueOption<kw::rename, Expr>),
+    Null(FlagOption<kw::null>),
 }
 
 impl Parse for MethodOption {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         if input.peek(kw::constructor) {
             input.parse().map(Self::Constructor)
+        } else if input.peek(kw::factory) {
+            input.parse().map(Self::Factory)
+        } else if input.peek(kw::finalize) {
+            input.parse().map(Self::Finalize)
         } else if input.peek(Token![static]) {
             input.parse().map(Self::Static)
         } else if input.peek(kw::skip) {
@@ -80,12 +142,16 @@ impl Parse for MethodOption {
             input.parse().map(Self::Configurable)
         } else if input.peek(kw::enumerable) {
             input.parse().map(Self::Enumerable)
+        } else if input.peek(kw::writable) {
+            input.parse().map(Self::Writable)
         } else if input.peek(kw::get) {
             input.parse().map(Self::Get)
         } else if input.peek(kw::set) {
             input.parse().map(Self::Set)
         } else if input.peek(kw::rename) {
             input.parse().map(Self::Rename)
+        } else if input.peek(kw::null) {
+            input.parse().map(Self::Null)
         } else {
             Err(syn::Error::new(input.span(), "invalid method attribute"))
         }
@@ -121,15 +187,40 @@ impl MethodConfig {
             )
         }
 
-        if self.configurable && !(self.get || self.set) {
+        if self.factory && (self.constructor || self.get || self.set) {
             abort!(
                 span,
-                "configurable can only be set for getters and setters."
+                "a factory can't also be a constructor, getter or setter."
             )
         }
 
-        if self.enumerable && !(self.get || self.set) {
-            abort!(span, "enumerable can only be set for getters and setters.")
+        if self.finalize
+            && (self.constructor || self.get || self.set || self.r#static || self.rename.is_some())
+        {
+            abort!(
+                span,
+                "a finalizer can't also be a constructor, getter, setter, static or renamed."
+            )
+        }
+
+        let has_flags =
+            self.configurable.is_some() || self.enumerable.is_some() || self.writable.is_some();
+        if has_flags && (self.constructor || self.finalize) {
+            abort!(
+                span,
+                "configurable, enumerable and writable can't be set for a constructor or finalizer."
+            )
+        }
+
+        if self.null && (self.constructor || self.finalize || self.set) {
+            abort!(
+                span,
+                "null can't be set for a constructor, setter or finalizer."
+            )
+        }
+
+        if self.writable.is_some() && (self.get || self.set) {
+            abort!(span, "writable can't be set for getters and setters.")
         }
     }
 }
@@ -152,7 +243,7 @@ impl Method {
             mut attrs,
             vis,
             defaultness,
-            sig,
+            mut sig,
             block,
         } = func;
 
@@ -175,6 +266,10 @@ impl Method {
 
         config.validate(attr_span);
 
+        if let (true, Some(recv)) = (config.factory, sig.receiver()) {
+            abort!(recv, "a factory can't take a `self` argument.")
+        }
+
         let attr_span = attrs
             .is_empty()
             .then_some(span)
@@ -186,7 +281,8 @@ impl Method {
 
         attrs.retain(|x| !x.path().is_ident("qjs"));
 
-        let function = JsFunction::new(vis.clone(), &sig, Some(self_ty));
+        let mut function = JsFunction::new(vis.clone(), &mut sig, Some(self_ty));
+        function.null = config.null;
 
         Method {
             config,
@@ -268,14 +364,37 @@ impl Method {
         self_ty: &Type,
         object_name: &Ident,
         case: Option<Case>,
+        lib_crate: &Ident,
     ) -> TokenStream {
         if self.config.skip {
             return TokenStream::new();
         }
         let func_name_str = self.name(case);
         let js_func_name = self.function.expand_carry_type_name(prefix);
+        // Like methods of JavaScript classes, methods are writable and configurable but not
+        // enumerable unless specified otherwise.
+        let writable = self
+            .config
+            .writable
+            .unwrap_or(true)
+            .then(|| quote!(.writable()));
+        let configurable = self
+            .config
+            .configurable
+            .unwrap_or(true)
+            .then(|| quote!(.configurable()));
+        let enumerable = self
+            .config
+            .enumerable
+            .unwrap_or(false)
+            .then(|| quote!(.enumerable()));
         quote! {
-            #object_name.set(#func_name_str,<#self_ty>::#js_func_name)?;
+            #object_name.prop(#func_name_str,
+                #lib_crate::object::Property::from(<#self_ty>::#js_func_name)
+                #writable
+                #configurable
+                #enumerable
+            )?;
         }
     }
 }
@@ -187,6 +187,9 @@ impl ModuleTypeConfig {
         if self.class.frozen {
             attrs.push(quote!(frozen));
         }
+        if let Some(x) = self.class.extensible {
+            attrs.push(quote!(extensible = #x));
+        }
         if let Some(x) = self.class.crate_.as_ref() {
             attrs.push(quote!(crate = #x));
         }
@@ -55,9 +55,16 @@
 //! [`Runtime::set_loader`]. The resolvers and loaders can be easily combined via tuples. When the
 //! previous resolver or loader failed the next one will be applied.
 //!
-//! - `dyn-load` adds support for loadable native modules (so/dll/dylib).
+//! - `dyn-load` adds support for loadable native modules (so/dll/dylib). It does nothing on wasm
+//!   targets.
 //!
-//! - `array-buffer` adds support for [`ArrayBuffer`] and [`TypedArray`].
+//! - `wasm` adds support for `wasm32-unknown-unknown`, where the libc functions QuickJS needs are
+//!   linked from the [wasi-libc](https://github.com/WebAssembly/wasi-libc) found through the
+//!   `WASI_SDK_PATH` or `WASI_SYSROOT` environment variables. As there are no bundled bindings for
+//!   wasm targets the `bindgen` feature is needed too. The clock and random source must be given
+//!   with [`wasm::init`] before use, for example in the start function of `wasm-bindgen`.
+//!
+//! - `array-buffer` adds support for [`ArrayBuffer`], [`TypedArray`] and [`DataView`].
 //!
 //! - `futures` adds support for async Rust. When enabled the library exports [`AsyncRuntime`] and
 //! [`AsyncContext`]. These are the asynchronous variants of the normal runtime and context. In
@@ -74,6 +81,9 @@
 //!
 //! - `phf` enables using Perfect Hash Function for builtin modules lookup
 //!
+//! - `unsafe-api` documents the raw QuickJS C API in the [`qjs`] module and makes
+//!   [`Ctx::handle_exception`] public, for calling C functions the bindings don't cover yet.
+//!
 //! ## Extra types
 //!
 //! This crate has support for conversion of many Rust types like [`Option`],
@@ -135,7 +145,7 @@ pub use rquickjs_core::*;
 
 #[cfg_attr(feature = "doc-cfg", doc(cfg(feature = "macro")))]
 #[cfg(feature = "macro")]
-pub use rquickjs_macro::{class, embed, function, methods, module};
+pub use rquickjs_macro::{class, embed, function, methods, module, FromJs, IntoJs, JsError};
 
 pub mod class {
     //! JavaScript classes defined from Rust.
@@ -1,7 +1,7 @@
 use std::{
     env, fs,
     io::Write,
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
@@ -11,6 +11,7 @@ fn main() {
 
     let features = [
         "exports",
+        "debug",
         "bindgen",
         "update-bindings",
         "dump-bytecode",
@@ -31,6 +32,8 @@ fn main() {
     for feature in &features {
         println!("cargo:rerun-if-env-changed={}", feature_to_cargo(feature));
     }
+    println!("cargo:rerun-if-env-changed=WASI_SYSROOT");
+    println!("cargo:rerun-if-env-changed=WASI_SDK_PATH");
 
     let src_dir = Path::new("quickjs");
     let patches_dir = Path::new("patches");
@@ -66,6 +69,22 @@ fn main() {
         "infinity_handling.patch",
         "atomic_new_class_id.patch",
         "dynamic_import_sync.patch",
+        "invalidate_module.patch",
+        "function_kind.patch",
+        "builtin_objects.patch",
+        "eval_line.patch",
+        "heap_dump.patch",
+        "debugger.patch",
+        "uncatchable_error.patch",
+        "interrupt_interval.patch",
+        "context_stats.patch",
+        "array_buffer_transfer.patch",
+        "deterministic.patch",
+        "realm_template.patch",
+        "module_requests.patch",
+        "hide_frames.patch",
+        "gc_hook.patch",
+        "array_buffer_views.patch",
     ];
 
     let mut defines = vec![
@@ -80,11 +99,20 @@ fn main() {
         patch_files.push("basic_msvc_compat.patch");
     }
 
+    let wasm = env::var("CARGO_CFG_TARGET_ARCH").unwrap() == "wasm32";
+    if wasm {
+        patch_files.push("wasm_compat.patch");
+    }
+
     if env::var("CARGO_FEATURE_EXPORTS").is_ok() {
         patch_files.push("read_module_exports.patch");
         defines.push(("CONFIG_MODULE_EXPORTS".into(), None));
     }
 
+    if env::var("CARGO_FEATURE_DEBUG").is_ok() {
+        defines.push(("CONFIG_DEBUGGER".into(), None));
+    }
+
     for feature in &features {
         if feature.starts_with("dump-") && env::var(feature_to_cargo(feature)).is_ok() {
             defines.push((feature_to_define(feature), None));
@@ -115,6 +143,10 @@ fn main() {
         builder.define(name, *value);
     }
 
+    if wasm {
+        wasm_config(&mut builder);
+    }
+
     for src in &source_files {
         builder.file(out_dir.join(src));
     }
@@ -122,6 +154,38 @@ fn main() {
     builder.compile("libquickjs.a");
 }
 
+fn wasm_config(builder: &mut cc::Build) {
+    let sysroot = wasi_sysroot();
+    if let Some(sysroot) = &sysroot {
+        builder.flag(format!("--sysroot={}", sysroot.display()));
+    }
+    if env::var("CARGO_CFG_TARGET_OS").unwrap() == "unknown" {
+        // There is no libc for wasm32-unknown-unknown so the one of WASI is used. The functions
+        // which would need WASI imports are implemented on the Rust side, see `src/wasm.rs`.
+        builder.flag("--target=wasm32-wasi");
+        match &sysroot {
+            Some(sysroot) => {
+                let lib_dir = sysroot.join("lib").join("wasm32-wasi");
+                println!("cargo:rustc-link-search=native={}", lib_dir.display());
+                println!("cargo:rustc-link-lib=static=c");
+            }
+            None => println!(
+                "cargo:warning=building for wasm32-unknown-unknown needs the wasi-libc, set `WASI_SDK_PATH` or `WASI_SYSROOT`."
+            ),
+        }
+    }
+}
+
+/// The sysroot with the WASI libc, either given directly or as part of an installed wasi-sdk.
+fn wasi_sysroot() -> Option<PathBuf> {
+    if let Some(sysroot) = env::var_os("WASI_SYSROOT") {
+        return Some(sysroot.into());
+    }
+    env::var_os("WASI_SDK_PATH")
+        .map(|sdk| Path::new(&sdk).join("share").join("wasi-sysroot"))
+        .filter(|sysroot| sysroot.exists())
+}
+
 fn feature_to_cargo(name: impl AsRef<str>) -> String {
     format!("CARGO_FEATURE_{}", feature_to_define(name))
 }
@@ -201,6 +265,12 @@ where
 
     let mut cflags = vec![format!("--target={}", target)];
 
+    if env::var("CARGO_CFG_TARGET_ARCH").unwrap() == "wasm32" {
+        if let Some(sysroot) = wasi_sysroot() {
+            cflags.push(format!("--sysroot={}", sysroot.display()));
+        }
+    }
+
     //format!("-I{}", out_dir.parent().display()),
 
     for (name, value) in defines {
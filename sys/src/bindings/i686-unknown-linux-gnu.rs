@@ -31,6 +31,10 @@ pub const JS_EVAL_FLAG_STRICT: u32 = 8;
 pub const JS_EVAL_FLAG_STRIP: u32 = 16;
 pub const JS_EVAL_FLAG_COMPILE_ONLY: u32 = 32;
 pub const JS_EVAL_FLAG_BACKTRACE_BARRIER: u32 = 64;
+pub const JS_EVAL_FLAG_NO_RESOLVE: u32 = 128;
+pub const JS_EVAL_FLAG_HIDE_FRAMES: u32 = 256;
+pub const JS_GC_PHASE_START: u32 = 0;
+pub const JS_GC_PHASE_END: u32 = 1;
 pub const JS_ATOM_NULL: u32 = 0;
 pub const JS_CALL_FLAG_CONSTRUCTOR: u32 = 1;
 pub const JS_GPN_STRING_MASK: u32 = 1;
@@ -310,6 +314,20 @@ extern "C" {
 extern "C" {
     pub fn JS_SetGCThreshold(rt: *mut JSRuntime, gc_threshold: size_t);
 }
+extern "C" {
+    pub fn JS_GetGCThreshold(rt: *mut JSRuntime) -> size_t;
+}
+pub type JSGCHook = ::std::option::Option<
+    unsafe extern "C" fn(
+        rt: *mut JSRuntime,
+        phase: ::std::os::raw::c_int,
+        malloc_size: size_t,
+        opaque: *mut ::std::os::raw::c_void,
+    ),
+>;
+extern "C" {
+    pub fn JS_SetGCHook(rt: *mut JSRuntime, hook: JSGCHook, opaque: *mut ::std::os::raw::c_void);
+}
 extern "C" {
     pub fn JS_SetMaxStackSize(rt: *mut JSRuntime, stack_size: size_t);
 }
@@ -369,6 +387,12 @@ extern "C" {
 extern "C" {
     pub fn JS_NewContextRaw(rt: *mut JSRuntime) -> *mut JSContext;
 }
+extern "C" {
+    pub fn JS_NewContextFromTemplate(tpl: *mut JSContext) -> *mut JSContext;
+}
+extern "C" {
+    pub fn JS_GetIntrinsics(ctx: *mut JSContext) -> JSValue;
+}
 extern "C" {
     pub fn JS_AddIntrinsicBaseObjects(ctx: *mut JSContext);
 }
@@ -818,6 +842,49 @@ fn bindgen_test_layout_JSMemoryUsage() {
 extern "C" {
     pub fn JS_ComputeMemoryUsage(rt: *mut JSRuntime, s: *mut JSMemoryUsage);
 }
+extern "C" {
+    pub fn JS_DumpAtomsTo(
+        rt: *mut JSRuntime,
+        cb: ::std::option::Option<
+            unsafe extern "C" fn(
+                opaque: *mut ::std::os::raw::c_void,
+                name: *const ::std::os::raw::c_char,
+                atom_type: ::std::os::raw::c_int,
+                ref_count: ::std::os::raw::c_int,
+            ),
+        >,
+        opaque: *mut ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn JS_DumpShapesTo(
+        rt: *mut JSRuntime,
+        cb: ::std::option::Option<
+            unsafe extern "C" fn(
+                opaque: *mut ::std::os::raw::c_void,
+                ref_count: ::std::os::raw::c_int,
+                prop_count: ::std::os::raw::c_int,
+                prop_size: ::std::os::raw::c_int,
+                is_hashed: ::std::os::raw::c_int,
+            ),
+        >,
+        opaque: *mut ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn JS_DumpObjectsTo(
+        rt: *mut JSRuntime,
+        cb: ::std::option::Option<
+            unsafe extern "C" fn(
+                opaque: *mut ::std::os::raw::c_void,
+                class_name: *const ::std::os::raw::c_char,
+                ref_count: ::std::os::raw::c_int,
+                prop_count: ::std::os::raw::c_int,
+            ),
+        >,
+        opaque: *mut ::std::os::raw::c_void,
+    );
+}
 extern "C" {
     pub fn JS_NewAtomLen(
         ctx: *mut JSContext,
@@ -1224,6 +1291,9 @@ extern "C" {
 extern "C" {
     pub fn JS_ResetUncatchableError(ctx: *mut JSContext);
 }
+extern "C" {
+    pub fn JS_SetUncatchableError(ctx: *mut JSContext, val: JSValue, flag: ::std::os::raw::c_int);
+}
 extern "C" {
     pub fn JS_NewError(ctx: *mut JSContext) -> JSValue;
 }
@@ -1353,6 +1423,12 @@ extern "C" {
 extern "C" {
     pub fn JS_IsConstructor(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
 }
+extern "C" {
+    pub fn JS_GetFunctionKind(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsGenerator(val: JSValue) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn JS_SetConstructorBit(
         ctx: *mut JSContext,
@@ -1366,6 +1442,111 @@ extern "C" {
 extern "C" {
     pub fn JS_IsArray(ctx: *mut JSContext, val: JSValue) -> ::std::os::raw::c_int;
 }
+extern "C" {
+    pub fn JS_IsMap(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsSet(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsDate(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsRegExp(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsSharedArrayBuffer(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_IsDataView(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_GetDataViewBuffer(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        pbyte_offset: *mut size_t,
+        pbyte_length: *mut size_t,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_NewMap(ctx: *mut JSContext, is_set: ::std::os::raw::c_int) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapSet(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        key: JSValue,
+        value: JSValue,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapGet(ctx: *mut JSContext, obj: JSValue, key: JSValue) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapHas(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        key: JSValue,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapDelete(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        key: JSValue,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapClear(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapSize(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_NewMapIterator(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        kind: ::std::os::raw::c_int,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_MapIteratorNext(
+        ctx: *mut JSContext,
+        iter: JSValue,
+        pdone: *mut ::std::os::raw::c_int,
+        is_set: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
+extern "C" {
+    pub fn JS_NewDate(ctx: *mut JSContext, epoch_ms: f64) -> JSValue;
+}
+extern "C" {
+    pub fn JS_GetDateTime(val: JSValue) -> f64;
+}
+extern "C" {
+    pub fn JS_NewRegExp(ctx: *mut JSContext, pattern: JSValue, flags: JSValue) -> JSValue;
+}
+extern "C" {
+    pub fn JS_GetRegExpSource(ctx: *mut JSContext, val: JSValue) -> JSValue;
+}
+extern "C" {
+    pub fn JS_GetRegExpFlags(val: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_ExecRegExp(ctx: *mut JSContext, val: JSValue, str_: JSValue) -> JSValue;
+}
 extern "C" {
     pub fn JS_GetPropertyInternal(
         ctx: *mut JSContext,
@@ -1516,6 +1697,16 @@ extern "C" {
         eval_flags: ::std::os::raw::c_int,
     ) -> JSValue;
 }
+extern "C" {
+    pub fn JS_EvalLine(
+        ctx: *mut JSContext,
+        input: *const ::std::os::raw::c_char,
+        input_len: size_t,
+        filename: *const ::std::os::raw::c_char,
+        line_num: ::std::os::raw::c_int,
+        eval_flags: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
 extern "C" {
     pub fn JS_EvalThis(
         ctx: *mut JSContext,
@@ -1651,6 +1842,19 @@ extern "C" {
 extern "C" {
     pub fn JS_GetArrayBuffer(ctx: *mut JSContext, psize: *mut size_t, obj: JSValue) -> *mut u8;
 }
+extern "C" {
+    pub fn JS_IsArrayBufferDetached(ctx: *mut JSContext, obj: JSValue) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_TransferArrayBuffer(
+        ctx: *mut JSContext,
+        obj: JSValue,
+        pdata: *mut *mut u8,
+        psize: *mut size_t,
+        pfree_func: *mut JSFreeArrayBufferDataFunc,
+        popaque: *mut *mut ::std::os::raw::c_void,
+    ) -> ::std::os::raw::c_int;
+}
 extern "C" {
     pub fn JS_GetTypedArrayBuffer(
         ctx: *mut JSContext,
@@ -1771,6 +1975,79 @@ extern "C" {
         opaque: *mut ::std::os::raw::c_void,
     );
 }
+extern "C" {
+    pub fn JS_SetInterruptInterval(
+        rt: *mut JSRuntime,
+        interval: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_SetInterruptCounter(
+        ctx: *mut JSContext,
+        counter: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int;
+}
+pub type JSDateNowFunc =
+    ::std::option::Option<unsafe extern "C" fn(opaque: *mut ::std::os::raw::c_void) -> i64>;
+extern "C" {
+    pub fn JS_SetDateNowFunc(
+        rt: *mut JSRuntime,
+        func: JSDateNowFunc,
+        opaque: *mut ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn JS_DateNow(ctx: *mut JSContext) -> i64;
+}
+extern "C" {
+    pub fn JS_SetRandomSeed(rt: *mut JSRuntime, has_seed: ::std::os::raw::c_int, seed: u64);
+}
+extern "C" {
+    pub fn JS_SetContextRandomSeed(ctx: *mut JSContext, seed: u64);
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct JSContextStats {
+    pub evaluations: i64,
+    pub jobs: i64,
+    pub exceptions: i64,
+    pub interrupt_checks: i64,
+    pub peak_memory: size_t,
+}
+extern "C" {
+    pub fn JS_GetContextStats(ctx: *mut JSContext, s: *mut JSContextStats);
+}
+extern "C" {
+    pub fn JS_ResetContextStats(ctx: *mut JSContext);
+}
+pub type JSDebugHook = ::std::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut JSContext,
+        opaque: *mut ::std::os::raw::c_void,
+        filename: JSAtom,
+        line_num: ::std::os::raw::c_int,
+    ) -> ::std::os::raw::c_int,
+>;
+extern "C" {
+    pub fn JS_SetDebugHook(
+        rt: *mut JSRuntime,
+        cb: JSDebugHook,
+        opaque: *mut ::std::os::raw::c_void,
+    );
+}
+extern "C" {
+    pub fn JS_GetDebugStackDepth(ctx: *mut JSContext) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_GetDebugFrame(ctx: *mut JSContext, level: ::std::os::raw::c_int) -> JSValue;
+}
+extern "C" {
+    pub fn JS_GetDebugFrameVariables(
+        ctx: *mut JSContext,
+        level: ::std::os::raw::c_int,
+        closure: ::std::os::raw::c_int,
+    ) -> JSValue;
+}
 extern "C" {
     pub fn JS_SetCanBlock(rt: *mut JSRuntime, can_block: ::std::os::raw::c_int);
 }
@@ -1805,6 +2082,28 @@ extern "C" {
         opaque: *mut ::std::os::raw::c_void,
     );
 }
+extern "C" {
+    pub fn JS_InvalidateModule(
+        rt: *mut JSRuntime,
+        module_name: *const ::std::os::raw::c_char,
+    ) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_FindLoadedModule(
+        ctx: *mut JSContext,
+        module_name: *const ::std::os::raw::c_char,
+    ) -> *mut JSModuleDef;
+}
+extern "C" {
+    pub fn JS_GetModuleRequestCount(m: *mut JSModuleDef) -> ::std::os::raw::c_int;
+}
+extern "C" {
+    pub fn JS_GetModuleRequest(
+        ctx: *mut JSContext,
+        m: *mut JSModuleDef,
+        idx: ::std::os::raw::c_int,
+    ) -> JSAtom;
+}
 extern "C" {
     pub fn JS_GetImportMeta(ctx: *mut JSContext, m: *mut JSModuleDef) -> JSValue;
 }
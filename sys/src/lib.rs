@@ -22,3 +22,6 @@ include!("inlines/ptr_64.rs");
 include!("inlines/ptr_32_nan_boxing.rs");
 
 include!("inlines/common.rs");
+
+#[cfg(all(target_family = "wasm", target_os = "unknown"))]
+pub mod wasm;
@@ -0,0 +1,43 @@
+//! Replacements for the libc functions which would need WASI imports on `wasm32-unknown-unknown`.
+//!
+//! The rest of the libc is linked from the wasi-libc, which works without any imports.
+
+use std::{
+    os::raw::{c_int, c_void},
+    sync::RwLock,
+};
+
+static CLOCK: RwLock<Option<fn() -> f64>> = RwLock::new(None);
+
+/// Set the clock used for `Date`, returning the milliseconds since the unix epoch like
+/// `Date.now()` in the embedding JavaScript does.
+///
+/// Without a clock the time is always the unix epoch.
+pub fn set_clock(now: fn() -> f64) {
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = Some(now);
+}
+
+/// The current time in milliseconds since the unix epoch, if a clock was set.
+pub fn now() -> Option<f64> {
+    CLOCK
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .map(|now| now())
+}
+
+/// The layout of `struct timeval` in the wasi-libc headers.
+#[repr(C)]
+pub struct timeval {
+    pub tv_sec: i64,
+    pub tv_usec: i64,
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn gettimeofday(tv: *mut timeval, _tz: *mut c_void) -> c_int {
+    if !tv.is_null() {
+        let micros = (now().unwrap_or_default() * 1000.0) as i64;
+        (*tv).tv_sec = micros.div_euclid(1_000_000);
+        (*tv).tv_usec = micros.rem_euclid(1_000_000);
+    }
+    0
+}
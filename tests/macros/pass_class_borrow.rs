@@ -0,0 +1,101 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Runtime};
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Account {
+    balance: u32,
+}
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Ledger {
+    transfers: u32,
+}
+
+#[rquickjs::methods]
+impl Account {
+    #[qjs(constructor)]
+    pub fn new(balance: u32) -> Self {
+        Account { balance }
+    }
+
+    #[qjs(get)]
+    pub fn balance(&self) -> u32 {
+        self.balance
+    }
+
+    pub fn transfer(&mut self, to: &mut Account, amount: u32, ledger: &mut Ledger) -> bool {
+        if self.balance < amount {
+            return false;
+        }
+        self.balance -= amount;
+        to.balance += amount;
+        ledger.transfers += 1;
+        true
+    }
+}
+
+#[rquickjs::methods]
+impl Ledger {
+    #[qjs(constructor)]
+    pub fn new() -> Self {
+        Ledger { transfers: 0 }
+    }
+
+    #[qjs(get)]
+    pub fn transfers(&self) -> u32 {
+        self.transfers
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Account>::define(&ctx.globals()).unwrap();
+        Class::<Ledger>::define(&ctx.globals()).unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            var a = new Account(10);
+            var b = new Account(0);
+            var ledger = new Ledger();
+            if(!a.transfer(b, 4, ledger) || a.transfer(b, 7, ledger)){
+                throw new Error(1)
+            }
+            if(a.balance !== 6 || b.balance !== 4 || ledger.transfers !== 1){
+                throw new Error(2)
+            }
+            let failed = false;
+            try {
+                a.transfer(a, 1, ledger);
+            } catch(e) {
+                failed = true;
+            }
+            if(!failed || a.balance !== 6){
+                throw new Error(3)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        let a: Class<Account> = ctx.globals().get("a").unwrap();
+        assert_eq!(a.try_borrow().unwrap().balance, 6);
+
+        let mut owned = a.clone().try_borrow_mut_owned().unwrap();
+        owned.balance += 1;
+        assert!(a.try_borrow().is_err());
+        assert!(ctx.eval::<u32, _>("a.balance").is_err());
+        assert!(owned.as_class() == &a);
+        drop(owned);
+
+        let owned = a.clone().try_borrow_owned().unwrap();
+        assert_eq!(ctx.eval::<u32, _>("a.balance").unwrap(), 7);
+        assert!(a.try_borrow_mut().is_err());
+        drop(owned);
+        a.borrow_mut().balance = 0;
+        assert_eq!(ctx.eval::<u32, _>("a.balance").unwrap(), 0);
+    });
+}
@@ -0,0 +1,107 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Exception, Result, Runtime};
+
+#[derive(Trace)]
+#[rquickjs::class(no_constructor)]
+pub struct Token {
+    id: u32,
+}
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+#[rquickjs::methods]
+impl Color {
+    #[qjs(constructor)]
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+
+    #[qjs(factory)]
+    pub fn black() -> Self {
+        Color::new(0, 0, 0)
+    }
+
+    #[qjs(factory, rename = "fromHex")]
+    pub fn from_hex(ctx: rquickjs::Ctx<'_>, hex: String) -> Result<Self> {
+        let value = u32::from_str_radix(hex.trim_start_matches('#'), 16)
+            .map_err(|_| Exception::throw_type(&ctx, "invalid hex color"))?;
+        Ok(Color::new((value >> 16) as u8, (value >> 8) as u8, value as u8))
+    }
+
+    pub fn sum(&self) -> u32 {
+        self.r as u32 + self.g as u32 + self.b as u32
+    }
+}
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Handle {
+    fd: u32,
+}
+
+#[rquickjs::methods]
+impl Handle {
+    #[qjs(factory)]
+    pub fn open(fd: u32) -> Self {
+        Handle { fd }
+    }
+
+    #[qjs(get)]
+    pub fn fd(&self) -> u32 {
+        self.fd
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Token>::define(&ctx.globals()).unwrap();
+        Class::<Color>::define(&ctx.globals()).unwrap();
+        Class::<Handle>::define(&ctx.globals()).unwrap();
+        ctx.globals().set("token", Token { id: 7 }).unwrap();
+
+        ctx.eval::<(), _>(
+            r##"
+            function typeError(f, message){
+                try {
+                    f();
+                } catch(e) {
+                    if(!(e instanceof TypeError) || e.message !== message){
+                        throw new Error("unexpected error: " + e)
+                    }
+                    return;
+                }
+                throw new Error("no error thrown")
+            }
+            typeError(() => new Token(), "Token can't be constructed directly");
+            if(!(token instanceof Token)){
+                throw new Error(1)
+            }
+            if(new Color(1, 2, 3).sum() !== 6 || Color.black().sum() !== 0){
+                throw new Error(2)
+            }
+            const c = Color.fromHex("#010203");
+            if(!(c instanceof Color) || c.sum() !== 6){
+                throw new Error(3)
+            }
+            typeError(() => Color.fromHex("zz"), "invalid hex color");
+            typeError(() => new Handle(), "Handle can't be constructed directly");
+            if(Handle.open(3).fd !== 3 || !(Handle.open(1) instanceof Handle)){
+                throw new Error(4)
+            }
+        "##,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        let token: Class<Token> = ctx.globals().get("token").unwrap();
+        assert_eq!(token.borrow().id, 7);
+    });
+}
@@ -0,0 +1,79 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Runtime};
+
+#[derive(Trace, Clone, Debug, PartialEq)]
+#[rquickjs::class]
+pub enum Shape {
+    Circle(f64),
+    Rect {
+        width: f64,
+        height: f64,
+    },
+    #[qjs(rename = "Nothing")]
+    Empty,
+    #[qjs(skip)]
+    Hidden(u32),
+}
+
+#[rquickjs::methods]
+impl Shape {
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Circle(r) => std::f64::consts::PI * r * r,
+            Shape::Rect { width, height } => width * height,
+            Shape::Empty | Shape::Hidden(_) => 0.0,
+        }
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Shape>::define(&ctx.globals()).unwrap();
+        ctx.globals().set("hidden", Shape::Hidden(1)).unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            const circle = Shape.Circle(2);
+            if(circle.tag !== "Circle" || !(circle instanceof Shape)){
+                throw new Error(1)
+            }
+            const rect = Shape.Rect(2, 3);
+            if(rect.tag !== "Rect" || rect.area() !== 6){
+                throw new Error(2)
+            }
+            if(Shape.Nothing().tag !== "Nothing" || Shape.Nothing().area() !== 0){
+                throw new Error(3)
+            }
+            if(Shape.Hidden !== undefined || Shape.Empty !== undefined || hidden.tag !== "Hidden"){
+                throw new Error(4)
+            }
+            if(Object.keys(Shape).length !== 0 || Shape.Circle.name !== "Circle"){
+                throw new Error(5)
+            }
+            let threw = false;
+            try {
+                new Shape();
+            } catch(e) {
+                threw = e instanceof TypeError;
+            }
+            if(!threw){
+                throw new Error(6)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        let rect: Shape = ctx.eval("Shape.Rect(1, 2)").catch(&ctx).unwrap();
+        assert_eq!(
+            rect,
+            Shape::Rect {
+                width: 1.0,
+                height: 2.0
+            }
+        );
+        assert!(ctx.eval::<Shape, _>("Shape.Circle('wide')").is_err());
+    });
+}
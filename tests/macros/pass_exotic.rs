@@ -0,0 +1,58 @@
+use rquickjs::{
+    class::{JsExotic, Trace},
+    Atom, CatchResultExt, Class, Context, Ctx, IntoJs, Result, Runtime, Value,
+};
+
+#[derive(Trace)]
+#[rquickjs::class(exotic)]
+pub struct Row {
+    columns: Vec<(String, u32)>,
+}
+
+impl<'js> JsExotic<'js> for Row {
+    fn get_own_property(&self, ctx: &Ctx<'js>, atom: Atom<'js>) -> Result<Option<Value<'js>>> {
+        let name = atom.to_string()?;
+        self.columns
+            .iter()
+            .find(|(column, _)| *column == name)
+            .map(|(_, value)| value.into_js(ctx))
+            .transpose()
+    }
+
+    fn own_keys(&self, ctx: &Ctx<'js>) -> Result<Vec<Atom<'js>>> {
+        self.columns
+            .iter()
+            .map(|(column, _)| Atom::from_str(ctx.clone(), column))
+            .collect()
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        let row = Row {
+            columns: vec![("id".to_string(), 1), ("age".to_string(), 30)],
+        };
+        ctx.globals()
+            .set("row", Class::instance(ctx.clone(), row).unwrap())
+            .unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            if(row.id !== 1 || row.age !== 30){
+                throw new Error(1)
+            }
+            if(Object.keys(row).join() !== "id,age"){
+                throw new Error(2)
+            }
+            if(row.name !== undefined){
+                throw new Error(3)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+    });
+}
@@ -0,0 +1,96 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Runtime};
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Node {
+    id: u32,
+}
+
+#[derive(Trace)]
+#[rquickjs::class(frozen)]
+pub struct Constant {
+    value: u32,
+}
+
+#[derive(Trace)]
+#[rquickjs::class(frozen, extensible)]
+pub struct Tagged {
+    value: u32,
+}
+
+#[derive(Trace)]
+#[rquickjs::class(extensible = false)]
+pub struct Sealed {
+    value: u32,
+}
+
+#[rquickjs::methods]
+impl Node {
+    #[qjs(constructor)]
+    pub fn new(id: u32) -> Self {
+        Node { id }
+    }
+
+    #[qjs(get)]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Node>::define(&ctx.globals()).unwrap();
+        let globals = ctx.globals();
+        globals
+            .set("constant", Class::instance(ctx.clone(), Constant { value: 1 }))
+            .unwrap();
+        globals
+            .set("tagged", Class::instance(ctx.clone(), Tagged { value: 2 }))
+            .unwrap();
+        globals
+            .set("sealed", Class::instance(ctx.clone(), Sealed { value: 3 }))
+            .unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            const node = new Node(1);
+            node.meta = "tag";
+            if (node.meta !== "tag" || node.id !== 1 || !Object.isExtensible(node)) {
+                throw new Error(1);
+            }
+            tagged.meta = "tag";
+            if (tagged.meta !== "tag") {
+                throw new Error(2);
+            }
+            for (const value of [constant, sealed]) {
+                if (Object.isExtensible(value)) {
+                    throw new Error(3);
+                }
+                let failed = false;
+                try {
+                    value.meta = "tag";
+                } catch (e) {
+                    failed = e instanceof TypeError;
+                }
+                if (!failed || "meta" in value) {
+                    throw new Error(4);
+                }
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        let constant: Class<Constant> = globals.get("constant").unwrap();
+        assert_eq!(constant.borrow().value, 1);
+        assert!(constant.try_borrow_mut().is_err());
+        let sealed: Class<Sealed> = globals.get("sealed").unwrap();
+        sealed.borrow_mut().value += 1;
+        assert_eq!(sealed.borrow().value, 4);
+        let tagged: Class<Tagged> = globals.get("tagged").unwrap();
+        assert_eq!(tagged.borrow().value, 2);
+    });
+}
@@ -0,0 +1,36 @@
+use rquickjs::{class::Trace, Class, Context, Function, Persistent, Runtime};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FINALIZED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Callback {
+    callback: Option<Persistent<Function<'static>>>,
+}
+
+#[rquickjs::methods]
+impl Callback {
+    #[qjs(finalize)]
+    pub fn finalize(&mut self) {
+        assert!(self.callback.take().is_some());
+        FINALIZED.store(true, Ordering::SeqCst);
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        let cls = Class::instance(ctx.clone(), Callback { callback: None }).unwrap();
+        ctx.globals().set("cls", cls.clone()).unwrap();
+        // Create a cycle between the class and a JavaScript function via a persistent value.
+        let func: Function = ctx.eval("((c) => () => c)(cls)").unwrap();
+        cls.borrow_mut().callback = Some(Persistent::save(&ctx, func));
+        ctx.globals().remove("cls").unwrap();
+    });
+
+    rt.run_gc();
+    assert!(FINALIZED.load(Ordering::SeqCst));
+}
@@ -0,0 +1,105 @@
+use rquickjs::{Context, FromJs, Runtime, Value};
+
+#[derive(Debug, PartialEq, FromJs)]
+pub struct Point(f64, f64);
+
+#[derive(Debug, PartialEq, FromJs)]
+pub struct Name(String);
+
+#[derive(Debug, PartialEq, FromJs)]
+pub struct Label {
+    name: Name,
+}
+
+#[derive(Debug, PartialEq, FromJs)]
+#[qjs(rename_all = "camelCase")]
+pub struct Options<T> {
+    max_depth: Option<u32>,
+    r#type: String,
+    extra: T,
+}
+
+#[derive(Debug, PartialEq, FromJs)]
+#[qjs(tag = "kind")]
+pub enum Event {
+    Start,
+    #[qjs(rename = "move")]
+    Move {
+        to: Point,
+    },
+    Label(Label),
+}
+
+#[derive(FromJs)]
+pub struct Holder<'js> {
+    value: Value<'js>,
+}
+
+#[derive(Debug, PartialEq, FromJs)]
+#[qjs(untagged)]
+pub enum Input {
+    Nothing,
+    Number(f64),
+    Pair(String, String),
+    Text(String),
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+    ctx.with(|ctx| {
+        let point: Point = ctx.eval("[1, 2]").unwrap();
+        assert_eq!(point, Point(1.0, 2.0));
+        assert!(ctx.eval::<Point, _>("[1, 2, 3]").is_err());
+
+        let options: Options<bool> = ctx
+            .eval("({ maxDepth: 3, type: 'deep', extra: true })")
+            .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                max_depth: Some(3),
+                r#type: "deep".into(),
+                extra: true,
+            }
+        );
+        let options: Options<()> = ctx.eval("({ type: 'flat' })").unwrap();
+        assert_eq!(options.max_depth, None);
+
+        let event: Event = ctx.eval("({ kind: 'Start' })").unwrap();
+        assert_eq!(event, Event::Start);
+        let event: Event = ctx.eval("({ kind: 'move', to: [3, 4] })").unwrap();
+        assert_eq!(
+            event,
+            Event::Move {
+                to: Point(3.0, 4.0)
+            }
+        );
+        let event: Event = ctx.eval("({ kind: 'Label', name: 'x' })").unwrap();
+        assert_eq!(
+            event,
+            Event::Label(Label {
+                name: Name("x".into())
+            })
+        );
+        let err = ctx.eval::<Event, _>("({ kind: 'Stop' })").unwrap_err();
+        assert!(err.to_string().contains("Unknown `kind` `Stop`"), "{}", err);
+        let err = ctx.eval::<Event, _>("({ type: 'Start' })").unwrap_err();
+        assert!(err.to_string().contains("Missing the `kind` property"), "{}", err);
+
+        let holder: Holder = ctx.eval("({ value: 42 })").unwrap();
+        assert_eq!(holder.value.as_int(), Some(42));
+
+        let inputs: Vec<Input> = ctx.eval("[null, 1, ['a', 'b'], 'c']").unwrap();
+        assert_eq!(
+            inputs,
+            vec![
+                Input::Nothing,
+                Input::Number(1.0),
+                Input::Pair("a".into(), "b".into()),
+                Input::Text("c".into()),
+            ]
+        );
+        assert!(ctx.eval::<Input, _>("({})").is_err());
+    })
+}
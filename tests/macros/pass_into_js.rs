@@ -0,0 +1,154 @@
+use rquickjs::{
+    class::Trace, convert::MultiValue, CatchResultExt, Class, Context, IntoJs, Runtime,
+};
+
+#[derive(IntoJs)]
+#[qjs(rename_all = "camelCase")]
+pub struct Bounds<T> {
+    min_value: T,
+    max_value: T,
+    r#type: String,
+}
+
+#[derive(IntoJs)]
+pub struct Pair(u32, String);
+
+#[derive(IntoJs)]
+pub struct Name(String);
+
+#[derive(IntoJs)]
+pub struct Shadowing {
+    ctx: u32,
+    object: u32,
+}
+
+#[derive(IntoJs)]
+#[qjs(tag = "kind")]
+pub enum Event {
+    Start,
+    #[qjs(rename = "move")]
+    Move {
+        to: Pair,
+    },
+    Named(Bounds<u32>),
+}
+
+#[derive(IntoJs)]
+#[qjs(untagged)]
+pub enum Output {
+    Nothing,
+    Number(f64),
+    Text { text: String },
+}
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Series {
+    values: Vec<u32>,
+}
+
+#[rquickjs::methods]
+impl Series {
+    #[qjs(constructor)]
+    pub fn new(values: Vec<u32>) -> Self {
+        Series { values }
+    }
+
+    #[qjs(rename = "minMax")]
+    pub fn min_max(&self) -> (u32, u32) {
+        let min = self.values.iter().copied().min().unwrap_or(0);
+        let max = self.values.iter().copied().max().unwrap_or(0);
+        (min, max)
+    }
+
+    pub fn bounds(&self) -> Bounds<u32> {
+        let (min_value, max_value) = self.min_max();
+        Bounds {
+            min_value,
+            max_value,
+            r#type: "u32".into(),
+        }
+    }
+
+    #[qjs(rename = "boundsList")]
+    pub fn bounds_list(&self) -> MultiValue<Bounds<u32>> {
+        MultiValue(self.bounds())
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Series>::define(&ctx.globals()).unwrap();
+        let globals = ctx.globals();
+        globals.set("pair", Pair(1, "one".into())).unwrap();
+        globals.set("name", Name("x".into())).unwrap();
+        globals.set("shadowing", Shadowing { ctx: 1, object: 2 }).unwrap();
+        globals.set("start", Event::Start).unwrap();
+        globals
+            .set(
+                "move",
+                Event::Move {
+                    to: Pair(2, "two".into()),
+                },
+            )
+            .unwrap();
+        globals
+            .set(
+                "named",
+                Event::Named(Bounds {
+                    min_value: 0,
+                    max_value: 1,
+                    r#type: "range".into(),
+                }),
+            )
+            .unwrap();
+        globals
+            .set(
+                "outputs",
+                vec![
+                    Output::Nothing,
+                    Output::Number(1.5),
+                    Output::Text { text: "t".into() },
+                ],
+            )
+            .unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            const series = new Series([3, 1, 4]);
+            const [min, max] = series.minMax();
+            if (min !== 1 || max !== 4) {
+                throw new Error(1);
+            }
+            const { minValue, maxValue, type } = series.bounds();
+            if (minValue !== 1 || maxValue !== 4 || type !== "u32") {
+                throw new Error(2);
+            }
+            const [low, high, kind] = series.boundsList();
+            if (low !== 1 || high !== 4 || kind !== "u32") {
+                throw new Error(3);
+            }
+            if (pair[0] !== 1 || pair[1] !== "one" || name !== "x") {
+                throw new Error(4);
+            }
+            if (shadowing.ctx !== 1 || shadowing.object !== 2) {
+                throw new Error(5);
+            }
+            if (start.kind !== "Start" || move.kind !== "move" || move.to[1] !== "two") {
+                throw new Error(6);
+            }
+            if (named.kind !== "Named" || named.type !== "range" || named.maxValue !== 1) {
+                throw new Error(7);
+            }
+            if (outputs[0] !== undefined || outputs[1] !== 1.5 || outputs[2].text !== "t") {
+                throw new Error(8);
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+    });
+}
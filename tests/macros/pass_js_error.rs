@@ -0,0 +1,89 @@
+use std::fmt;
+
+use rquickjs::{CatchResultExt, Context, Error, Function, JsError, Runtime};
+
+#[derive(Debug, JsError)]
+pub struct ConfigError;
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "invalid config".fmt(f)
+    }
+}
+
+#[derive(Debug, JsError)]
+#[qjs(rename = "WrappedError", code = "WRAPPED")]
+pub struct Wrapper<E: JsError + Send + Sync + 'static>(&'static str, #[qjs(cause)] E);
+
+impl<E: JsError + Send + Sync + 'static> fmt::Display for Wrapper<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "while {}", self.0)
+    }
+}
+
+#[derive(Debug, JsError)]
+pub enum AppError {
+    Timeout,
+    #[qjs(code = "E_PARSE")]
+    Parse {
+        line: u32,
+        #[qjs(cause)]
+        cause: Box<AppError>,
+    },
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppError::Timeout => "timed out".fmt(f),
+            AppError::Parse { line, .. } => write!(f, "parse error at {}", line),
+        }
+    }
+}
+
+fn load() -> Result<(), Wrapper<ConfigError>> {
+    Err(Wrapper("loading", ConfigError))
+}
+
+fn parse() -> rquickjs::Result<()> {
+    Err(AppError::Parse {
+        line: 3,
+        cause: Box::new(AppError::Timeout),
+    }
+    .into())
+}
+
+fn main() {
+    let error = ConfigError;
+    assert_eq!(error.name(), "ConfigError");
+    assert_eq!(error.code(), Some("CONFIG"));
+    assert_eq!(AppError::Timeout.name(), "TimeoutError");
+    assert!(matches!(Error::from(AppError::Timeout), Error::Js(_)));
+
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+    ctx.with(|ctx| {
+        let globals = ctx.globals();
+        globals
+            .set("load", Function::new(ctx.clone(), load).unwrap())
+            .unwrap();
+        globals
+            .set("parse", Function::new(ctx.clone(), parse).unwrap())
+            .unwrap();
+        let res: String = ctx
+            .eval(
+                r#"
+                const describe = (e) => `${e.name}(${e.code}): ${e.message}` + (e.cause ? ` <- ${describe(e.cause)}` : "");
+                const get = (f) => { try { f() } catch (e) { return describe(e) } };
+                get(load) + "; " + get(parse)
+                "#,
+            )
+            .catch(&ctx)
+            .unwrap();
+        assert_eq!(
+            res,
+            "WrappedError(WRAPPED): while loading <- ConfigError(CONFIG): invalid config; \
+             ParseError(E_PARSE): parse error at 3 <- TimeoutError(TIMEOUT): timed out"
+        );
+    })
+}
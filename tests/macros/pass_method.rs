@@ -48,6 +48,15 @@ impl TestClass {
     #[qjs(skip)]
     pub fn inner_function(&self) {}
 
+    pub fn double(&self) -> u32 {
+        self.value * 2
+    }
+
+    #[qjs(enumerable, configurable = false, writable = false)]
+    pub fn describe(&self) -> String {
+        format!("TestClass({})", self.value)
+    }
+
     #[qjs(rename = PredefinedAtom::SymbolIterator)]
     pub fn iterate<'js>(&self, ctx: Ctx<'js>) -> Result<Object<'js>> {
         let res = Object::new(ctx)?;
@@ -62,6 +71,11 @@ impl TestClass {
         )?;
         Ok(res)
     }
+
+    #[qjs(rename = Symbol.toPrimitive)]
+    pub fn to_primitive(&self, hint: String) -> Result<u32> {
+        Ok(if hint == "string" { 0 } else { self.value })
+    }
 }
 
 pub fn main() {
@@ -113,6 +127,24 @@ pub fn main() {
             for(const v of t){
                 throw new Error("iterator should be done immediately")
             }
+            if(nv * 2 !== 10 || `${nv}` !== "0"){
+                throw new Error(9)
+            }
+            const double = Object.getOwnPropertyDescriptor(proto, "double");
+            if(!double.writable || !double.configurable || double.enumerable || nv.double() !== 10){
+                throw new Error(10)
+            }
+            const describe = Object.getOwnPropertyDescriptor(proto, "describe");
+            if(describe.writable || describe.configurable || !describe.enumerable){
+                throw new Error(11)
+            }
+            const value = Object.getOwnPropertyDescriptor(proto, "value");
+            if(!value.configurable || value.enumerable){
+                throw new Error(12)
+            }
+            if(Object.getOwnPropertyDescriptor(TestClass, "compare").enumerable){
+                throw new Error(13)
+            }
         "#,
         )
         .catch(&ctx)
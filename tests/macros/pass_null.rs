@@ -0,0 +1,72 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Function, Result, Runtime};
+
+#[rquickjs::function]
+pub fn find(key: String) -> Result<Option<u32>> {
+    Ok((key == "a").then_some(1))
+}
+
+#[rquickjs::function(null)]
+pub fn find_or_null(key: String) -> Result<Option<u32>> {
+    Ok((key == "a").then_some(1))
+}
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Store {
+    value: Option<u32>,
+}
+
+#[rquickjs::methods]
+impl Store {
+    #[qjs(constructor)]
+    pub fn new() -> Self {
+        Store { value: None }
+    }
+
+    #[qjs(get, null)]
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+
+    pub fn maybe(&self) -> Option<u32> {
+        self.value
+    }
+
+    #[qjs(null)]
+    pub fn clear(&mut self) {
+        self.value = None;
+    }
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        let globals = ctx.globals();
+        Class::<Store>::define(&globals).unwrap();
+        globals
+            .set("find", Function::new(ctx.clone(), js_find))
+            .unwrap();
+        globals
+            .set("findOrNull", Function::new(ctx.clone(), js_find_or_null))
+            .unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            if (find("a") !== 1 || find("b") !== undefined) {
+                throw new Error(1);
+            }
+            if (findOrNull("a") !== 1 || findOrNull("b") !== null) {
+                throw new Error(2);
+            }
+            const store = new Store();
+            if (store.value !== null || store.maybe() !== undefined || store.clear() !== null) {
+                throw new Error(3);
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+    });
+}
@@ -0,0 +1,66 @@
+use rquickjs::{prelude::Rest, CatchResultExt, Context, FromJs, Runtime};
+
+#[derive(Default, FromJs)]
+#[qjs(rename_all = "camelCase")]
+pub struct JoinOptions {
+    separator: Option<String>,
+    skip_empty: Option<bool>,
+}
+
+#[rquickjs::function]
+pub fn join(parts: Rest<String>, #[qjs(options, default)] opts: JoinOptions) -> String {
+    let sep = opts.separator.unwrap_or_else(|| ",".to_owned());
+    let parts = parts
+        .0
+        .into_iter()
+        .filter(|x| !(opts.skip_empty.unwrap_or(false) && x.is_empty()));
+    parts.collect::<Vec<_>>().join(&sep)
+}
+
+#[rquickjs::function]
+pub fn scale(value: f64, #[qjs(options)] opts: Option<ScaleOptions>) -> f64 {
+    value * opts.map(|x| x.factor).unwrap_or(1.0)
+}
+
+#[derive(FromJs)]
+pub struct ScaleOptions {
+    factor: f64,
+}
+
+#[rquickjs::function]
+pub fn sum(values: Rest<f64>) -> f64 {
+    values.iter().sum()
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        ctx.globals().set("join", js_join).unwrap();
+        ctx.globals().set("scale", js_scale).unwrap();
+        ctx.globals().set("sum", js_sum).unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            if(join("a", "b") !== "a,b" || join() !== ""){
+                throw new Error(1)
+            }
+            if(join("a", "", "b", { separator: "-", skipEmpty: true }) !== "a-b"){
+                throw new Error(2)
+            }
+            if(scale(2) !== 2 || scale(2, { factor: 3 }) !== 6){
+                throw new Error(3)
+            }
+            if(sum() !== 0 || sum(1, 2, 3) !== 6){
+                throw new Error(4)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+
+        // The required value is never taken as the options object.
+        assert!(ctx.eval::<f64, _>("scale({ factor: 3 })").is_err());
+    });
+}
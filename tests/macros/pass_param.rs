@@ -0,0 +1,77 @@
+use rquickjs::{class::Trace, CatchResultExt, Class, Context, Runtime};
+
+#[derive(Trace)]
+#[rquickjs::class]
+pub struct Counter {
+    step: u32,
+    count: u32,
+}
+
+#[rquickjs::methods]
+impl Counter {
+    #[qjs(constructor)]
+    pub fn new(#[qjs(default = 1, range = 1..=10)] step: u32) -> Self {
+        Counter { step, count: 0 }
+    }
+
+    #[qjs(get)]
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn advance(&mut self, #[qjs(default)] times: u32) -> u32 {
+        self.count += self.step * times.max(1);
+        self.count
+    }
+
+    pub fn label(&self, #[qjs(length = 1..=8)] name: String, #[qjs(default = "#".to_string())] sep: String) -> String {
+        format!("{}{}{}", name, sep, self.count)
+    }
+}
+
+#[rquickjs::function]
+pub fn clamp(#[qjs(range = -1.0..=1.0)] value: f64, #[qjs(default = 2.0)] scale: f64) -> f64 {
+    value * scale
+}
+
+pub fn main() {
+    let rt = Runtime::new().unwrap();
+    let ctx = Context::full(&rt).unwrap();
+
+    ctx.with(|ctx| {
+        Class::<Counter>::define(&ctx.globals()).unwrap();
+        ctx.globals().set("clamp", js_clamp).unwrap();
+
+        ctx.eval::<(), _>(
+            r#"
+            const a = new Counter();
+            if(a.advance() !== 1 || a.advance(3) !== 4){
+                throw new Error(1)
+            }
+            const b = new Counter(5);
+            if(b.advance() !== 5 || b.label("b") !== "b#5" || b.label("b", "-") !== "b-5"){
+                throw new Error(2)
+            }
+            function rangeError(f, message){
+                try {
+                    f();
+                } catch(e) {
+                    if(!(e instanceof RangeError) || e.message !== message){
+                        throw new Error("unexpected error: " + e)
+                    }
+                    return;
+                }
+                throw new Error("no error thrown")
+            }
+            rangeError(() => new Counter(11), "Argument `step` must be in the range 1..=10");
+            rangeError(() => b.label(""), "The length of argument `name` must be in the range 1..=8");
+            rangeError(() => clamp(2), "Argument `value` must be in the range -1.0..=1.0");
+            if(clamp(0.5) !== 1 || clamp(-1, 3) !== -3){
+                throw new Error(3)
+            }
+        "#,
+        )
+        .catch(&ctx)
+        .unwrap();
+    });
+}